@@ -0,0 +1,257 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::egui;
+
+use crate::{
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+};
+
+/// Aligns the selected entities' bounds along the chosen axes, or spaces them out evenly. All
+/// entities move in the same frame, so the history records one entry per operation.
+pub struct AlignPlugin;
+
+impl Plugin for AlignPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<AlignWindow>()
+            .add_systems(
+                Update,
+                (
+                    align_window_system.in_set(AlignWindow::ui_set()),
+                    apply_alignment_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Align & Distribute…", |world| {
+                world.resource_mut::<AlignWindow>().is_open = true;
+            }));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    Min,
+    Center,
+    Max,
+}
+
+impl Anchor {
+    const ALL: [(Anchor, &'static str); 3] = [
+        (Anchor::Min, "Min"),
+        (Anchor::Center, "Center"),
+        (Anchor::Max, "Max"),
+    ];
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum AlignTo {
+    /// The bounds of the whole selection.
+    #[default]
+    Selection,
+    /// The primary selection, which stays put.
+    Primary,
+}
+
+#[derive(Clone, Copy)]
+enum Operation {
+    Align(Anchor),
+    /// Equal gaps between neighbours; the outermost two stay put.
+    Distribute,
+}
+
+#[derive(Resource)]
+pub struct AlignWindow {
+    pub is_open: bool,
+    axes: [bool; 3],
+    align_to: AlignTo,
+    pending: Option<Operation>,
+}
+
+impl Default for AlignWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            axes: [true, false, false],
+            align_to: AlignTo::default(),
+            pending: None,
+        }
+    }
+}
+
+impl Panel for AlignWindow {
+    const TITLE: &'static str = "Align & Distribute";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn align_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<AlignWindow>,
+    selection: Res<Selection>,
+) {
+    let AlignWindow {
+        is_open,
+        axes,
+        align_to,
+        pending,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let count = selection.entities.len();
+    egui::Window::new(AlignWindow::TITLE)
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<AlignWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Axes");
+                for (axis, label) in axes.iter_mut().zip(["X", "Y", "Z"]) {
+                    ui.toggle_value(axis, label);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Relative to");
+                ui.selectable_value(align_to, AlignTo::Selection, "Selection")
+                    .on_hover_text("The bounds of everything selected");
+                ui.selectable_value(align_to, AlignTo::Primary, "Primary")
+                    .on_hover_text("The last entity selected, which does not move");
+            });
+            let any_axis = axes.iter().any(|axis| *axis);
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Align");
+                for (anchor, label) in Anchor::ALL {
+                    if ui
+                        .add_enabled(any_axis && count >= 2, egui::Button::new(label))
+                        .on_disabled_hover_text("Select two or more entities and an axis")
+                        .clicked()
+                    {
+                        *pending = Some(Operation::Align(anchor));
+                    }
+                }
+            });
+            if ui
+                .add_enabled(any_axis && count >= 3, egui::Button::new("Distribute"))
+                .on_hover_text("Space the selection with equal gaps between neighbours")
+                .on_disabled_hover_text("Select three or more entities and an axis")
+                .clicked()
+            {
+                *pending = Some(Operation::Distribute);
+            }
+            ui.weak(format!("{count} selected"));
+        });
+}
+
+/// World-space bounds of an entity: its mesh box, or its origin when it has no mesh.
+fn world_bounds(transform: &GlobalTransform, aabb: Option<&Aabb>) -> (Vec3, Vec3) {
+    let Some(aabb) = aabb else {
+        let origin = transform.translation();
+        return (origin, origin);
+    };
+    let affine = transform.affine();
+    let center = Vec3::from(affine.transform_point3a(aabb.center));
+    let half = Vec3::from(
+        affine.matrix3.x_axis.abs() * aabb.half_extents.x
+            + affine.matrix3.y_axis.abs() * aabb.half_extents.y
+            + affine.matrix3.z_axis.abs() * aabb.half_extents.z,
+    );
+    (center - half, center + half)
+}
+
+fn apply_alignment_system(
+    mut window: ResMut<AlignWindow>,
+    selection: Res<Selection>,
+    bounds: Query<(&GlobalTransform, Option<&Aabb>)>,
+    parents: Query<&Parent>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let Some(operation) = window.pending.take() else {
+        return;
+    };
+    // Entities inside another selected entity move with it, so moving them too would count
+    // the offset twice.
+    let movable: Vec<(Entity, (Vec3, Vec3))> = selection
+        .entities
+        .iter()
+        .filter(|entity| {
+            !parents
+                .iter_ancestors(**entity)
+                .any(|ancestor| selection.entities.contains(&ancestor))
+        })
+        .filter_map(|entity| {
+            let (transform, aabb) = bounds.get(*entity).ok()?;
+            Some((*entity, world_bounds(transform, aabb)))
+        })
+        .collect();
+    if movable.len() < 2 {
+        return;
+    }
+
+    let mut offsets = vec![Vec3::ZERO; movable.len()];
+    for axis in (0..3).filter(|axis| window.axes[*axis]) {
+        match operation {
+            Operation::Align(anchor) => {
+                let reference = match window.align_to {
+                    AlignTo::Primary => selection
+                        .primary()
+                        .and_then(|primary| movable.iter().find(|(entity, _)| *entity == primary))
+                        .map(|(_, (min, max))| (min[axis], max[axis])),
+                    AlignTo::Selection => None,
+                };
+                let (min, max) = reference.unwrap_or_else(|| {
+                    movable.iter().fold(
+                        (f32::INFINITY, f32::NEG_INFINITY),
+                        |(low, high), (_, (min, max))| (low.min(min[axis]), high.max(max[axis])),
+                    )
+                });
+                for (offset, (_, (low, high))) in offsets.iter_mut().zip(&movable) {
+                    offset[axis] = match anchor {
+                        Anchor::Min => min - low[axis],
+                        Anchor::Center => (min + max - low[axis] - high[axis]) / 2.0,
+                        Anchor::Max => max - high[axis],
+                    };
+                }
+            }
+            Operation::Distribute => {
+                let mut order: Vec<usize> = (0..movable.len()).collect();
+                let center = |index: usize| {
+                    let (min, max) = movable[index].1;
+                    min[axis] + max[axis]
+                };
+                order.sort_by(|a, b| center(*a).total_cmp(&center(*b)));
+                let (first, last) = (order[0], order[order.len() - 1]);
+                let span = movable[last].1 .1[axis] - movable[first].1 .0[axis];
+                let sizes: f32 = movable
+                    .iter()
+                    .map(|(_, (min, max))| max[axis] - min[axis])
+                    .sum();
+                let gap = (span - sizes) / (movable.len() - 1) as f32;
+                let mut cursor = movable[first].1 .0[axis];
+                for index in order {
+                    let (min, max) = movable[index].1;
+                    offsets[index][axis] = cursor - min[axis];
+                    cursor += max[axis] - min[axis] + gap;
+                }
+            }
+        }
+    }
+
+    for ((entity, _), offset) in movable.iter().zip(offsets) {
+        // Offsets are in world space; a parent's rotation and scale apply on top of `Transform`.
+        let local = match parents
+            .get(*entity)
+            .and_then(|parent| bounds.get(parent.get()))
+        {
+            Ok((parent, _)) => parent.affine().inverse().transform_vector3(offset),
+            Err(_) => offset,
+        };
+        if let Ok(mut transform) = transforms.get_mut(*entity) {
+            if local != Vec3::ZERO {
+                transform.translation += local;
+            }
+        }
+    }
+}
@@ -0,0 +1,575 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::{egui, EguiUserTextures};
+
+use crate::{
+    errors::AppError,
+    icons::{Icon, IconButtonsExt},
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+    settings::Settings,
+    status_bar::StatusBar,
+};
+
+const THUMBNAIL: f32 = 96.0;
+/// Decoded frames are kept whole, so long or large animations are refused past this.
+const MAX_DECODED_BYTES: usize = 256 * 1024 * 1024;
+/// Shorter frame delays are shown for this long instead, as browsers do; many GIFs store 0.
+const MIN_DELAY: f32 = 0.02;
+const DEFAULT_DELAY: f32 = 0.1;
+
+/// Plays animated GIF and APNG files as textures. Each animation owns one image whose pixels
+/// are replaced as its frames come due, so anything showing the image follows along: the side
+/// panel image slot, cube materials and egui thumbnails alike.
+pub struct AnimatedTexturesPlugin;
+
+impl Plugin for AnimatedTexturesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SidePanelAnimation>()
+            .register_panel::<AnimatedTexturesWindow>()
+            .add_systems(
+                Update,
+                (
+                    animated_textures_window_system.in_set(AnimatedTexturesWindow::ui_set()),
+                    advance_animations_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// An animation shown in the side panel image slot instead of the Bevy icon.
+#[derive(Default, Resource)]
+pub struct SidePanelAnimation(pub Option<(Handle<Image>, UVec2)>);
+
+/// Composited RGBA8 frames, each the full size of the animation.
+struct Frames {
+    size: UVec2,
+    frames: Vec<(Vec<u8>, f32)>,
+}
+
+impl Frames {
+    fn push(&mut self, canvas: &[u8], delay: f32) -> Result<(), String> {
+        if (self.frames.len() + 1) * canvas.len() > MAX_DECODED_BYTES {
+            return Err(format!(
+                "more than {} MiB once decoded",
+                MAX_DECODED_BYTES / 1024 / 1024
+            ));
+        }
+        let delay = if delay < MIN_DELAY {
+            DEFAULT_DELAY
+        } else {
+            delay
+        };
+        self.frames.push((canvas.to_vec(), delay));
+        Ok(())
+    }
+}
+
+struct Animation {
+    name: String,
+    image: Handle<Image>,
+    frames: Frames,
+    current: usize,
+    /// Seconds spent on the current frame.
+    elapsed: f32,
+    playing: bool,
+    speed: f32,
+}
+
+#[derive(Resource)]
+pub struct AnimatedTexturesWindow {
+    pub is_open: bool,
+    path: String,
+    animations: Vec<Animation>,
+}
+
+impl Default for AnimatedTexturesWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            path: "assets/".to_owned(),
+            animations: Vec::new(),
+        }
+    }
+}
+
+impl Panel for AnimatedTexturesWindow {
+    const TITLE: &'static str = "Animated Textures";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<Frames, String> {
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        decode_gif(bytes)
+    } else if bytes.starts_with(b"\x89PNG") {
+        decode_apng(bytes)
+    } else {
+        Err("not a GIF or PNG file".to_owned())
+    }
+}
+
+/// Reads bytes off the front of a GIF stream.
+struct GifReader<'a>(&'a [u8]);
+
+impl<'a> GifReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < count {
+            return Err("the file is truncated".to_owned());
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// The data sub-blocks up to their terminator, joined.
+    fn sub_blocks(&mut self) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        loop {
+            let length = self.byte()? as usize;
+            if length == 0 {
+                return Ok(data);
+            }
+            data.extend_from_slice(self.take(length)?);
+        }
+    }
+
+    /// A colour table of `2^(bits + 1)` RGB entries.
+    fn color_table(&mut self, bits: u8) -> Result<&'a [u8], String> {
+        self.take(3 << (bits + 1))
+    }
+}
+
+fn decode_gif(bytes: &[u8]) -> Result<Frames, String> {
+    let mut reader = GifReader(bytes);
+    reader.take(6)?;
+    let width = reader.u16()? as usize;
+    let height = reader.u16()? as usize;
+    let flags = reader.byte()?;
+    reader.take(2)?;
+    let global_table = if flags & 0x80 != 0 {
+        Some(reader.color_table(flags & 0x07)?)
+    } else {
+        None
+    };
+
+    let mut frames = Frames {
+        size: UVec2::new(width as u32, height as u32),
+        frames: Vec::new(),
+    };
+    let mut canvas = vec![0; width * height * 4];
+    // From the graphic control extension preceding each image.
+    let (mut delay, mut transparent, mut disposal) = (0.0, None, 0);
+    loop {
+        match reader.byte()? {
+            // Extension; only the graphic control one matters here.
+            0x21 => {
+                let label = reader.byte()?;
+                let data = reader.sub_blocks()?;
+                if label == 0xF9 && data.len() >= 4 {
+                    disposal = (data[0] >> 2) & 0x07;
+                    delay = u16::from_le_bytes([data[1], data[2]]) as f32 / 100.0;
+                    transparent = (data[0] & 0x01 != 0).then_some(data[3]);
+                }
+            }
+            // Image descriptor.
+            0x2C => {
+                let left = reader.u16()? as usize;
+                let top = reader.u16()? as usize;
+                let frame_width = reader.u16()? as usize;
+                let frame_height = reader.u16()? as usize;
+                let flags = reader.byte()?;
+                let table = if flags & 0x80 != 0 {
+                    reader.color_table(flags & 0x07)?
+                } else {
+                    global_table.ok_or("a frame has no colour table")?
+                };
+                let interlaced = flags & 0x40 != 0;
+                let code_size = reader.byte()?;
+                if !(2..=8).contains(&code_size) {
+                    return Err(format!("invalid LZW code size {code_size}"));
+                }
+                let indices = weezl::decode::Decoder::new(weezl::BitOrder::Lsb, code_size)
+                    .decode(&reader.sub_blocks()?)
+                    .map_err(|err| err.to_string())?;
+
+                let previous = (disposal == 3).then(|| canvas.clone());
+                let rows: Vec<usize> = if interlaced {
+                    [(0, 8), (4, 8), (2, 4), (1, 2)]
+                        .into_iter()
+                        .flat_map(|(start, step)| (start..frame_height).step_by(step))
+                        .collect()
+                } else {
+                    (0..frame_height).collect()
+                };
+                for (row, y) in rows.into_iter().enumerate() {
+                    for x in 0..frame_width {
+                        let Some(&index) = indices.get(row * frame_width + x) else {
+                            break;
+                        };
+                        let (cx, cy) = (left + x, top + y);
+                        if Some(index) == transparent || cx >= width || cy >= height {
+                            continue;
+                        }
+                        let Some(rgb) = table.get(index as usize * 3..index as usize * 3 + 3)
+                        else {
+                            continue;
+                        };
+                        let at = (cy * width + cx) * 4;
+                        canvas[at..at + 3].copy_from_slice(rgb);
+                        canvas[at + 3] = 255;
+                    }
+                }
+                frames.push(&canvas, delay)?;
+
+                match (disposal, previous) {
+                    // Restore to background, which players show as transparent.
+                    (2, _) => {
+                        for y in top..(top + frame_height).min(height) {
+                            let start = (y * width + left.min(width)) * 4;
+                            let end = (y * width + (left + frame_width).min(width)) * 4;
+                            canvas[start..end].fill(0);
+                        }
+                    }
+                    (3, Some(previous)) => canvas = previous,
+                    _ => {}
+                }
+                (delay, transparent, disposal) = (0.0, None, 0);
+            }
+            0x3B => break,
+            other => return Err(format!("unexpected block 0x{other:02x}")),
+        }
+    }
+    if frames.frames.is_empty() {
+        return Err("the file has no frames".to_owned());
+    }
+    Ok(frames)
+}
+
+/// Decodes a PNG, animated or not; a still image becomes a single frame.
+fn decode_apng(bytes: &[u8]) -> Result<Frames, String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+    let (width, height) = reader.info().size();
+    let (width, height) = (width as usize, height as usize);
+    let animated = reader
+        .info()
+        .animation_control()
+        .map(|control| control.num_frames);
+    // The default image is only part of the animation when it has frame control of its own.
+    let skip_default = animated.is_some() && reader.info().frame_control().is_none();
+    let count = animated.unwrap_or(1) as usize + usize::from(skip_default);
+
+    let mut frames = Frames {
+        size: UVec2::new(width as u32, height as u32),
+        frames: Vec::new(),
+    };
+    let mut canvas = vec![0; width * height * 4];
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    for index in 0..count {
+        let output = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        if index == 0 && skip_default {
+            continue;
+        }
+        let control = reader.info().frame_control().copied().unwrap_or_default();
+        let (left, top) = (control.x_offset as usize, control.y_offset as usize);
+        let (frame_width, frame_height) = (output.width as usize, output.height as usize);
+        let channels = output.color_type.samples();
+
+        // The first frame treats "restore previous" as "restore to background".
+        let previous = (control.dispose_op == png::DisposeOp::Previous
+            && !frames.frames.is_empty())
+        .then(|| canvas.clone());
+        for y in 0..frame_height {
+            let line = &buffer[y * output.line_size..][..frame_width * channels];
+            for (x, pixel) in line.chunks_exact(channels).enumerate() {
+                let (cx, cy) = (left + x, top + y);
+                if cx >= width || cy >= height {
+                    continue;
+                }
+                let source = match *pixel {
+                    [r, g, b, a] => [r, g, b, a],
+                    [r, g, b] => [r, g, b, 255],
+                    [gray, a] => [gray, gray, gray, a],
+                    [gray] => [gray, gray, gray, 255],
+                    _ => continue,
+                };
+                let at = (cy * width + cx) * 4;
+                let target = &mut canvas[at..at + 4];
+                if control.blend_op == png::BlendOp::Over && source[3] < 255 {
+                    let alpha = source[3] as f32 / 255.0;
+                    let below = target[3] as f32 / 255.0 * (1.0 - alpha);
+                    let out = alpha + below;
+                    for channel in 0..3 {
+                        let mixed = (source[channel] as f32 * alpha
+                            + target[channel] as f32 * below)
+                            / out.max(f32::EPSILON);
+                        target[channel] = mixed.round() as u8;
+                    }
+                    target[3] = (out * 255.0).round() as u8;
+                } else {
+                    target.copy_from_slice(&source);
+                }
+            }
+        }
+        let denominator = if control.delay_den == 0 {
+            100.0
+        } else {
+            control.delay_den as f32
+        };
+        frames.push(&canvas, control.delay_num as f32 / denominator)?;
+
+        match (control.dispose_op, previous) {
+            (png::DisposeOp::Previous, Some(previous)) => canvas = previous,
+            (png::DisposeOp::Background | png::DisposeOp::Previous, _) => {
+                for y in top..(top + frame_height).min(height) {
+                    let start = (y * width + left.min(width)) * 4;
+                    let end = (y * width + (left + frame_width).min(width)) * 4;
+                    canvas[start..end].fill(0);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(frames)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn animated_textures_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<AnimatedTexturesWindow>,
+    mut side_panel: ResMut<SidePanelAnimation>,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    selection: Res<Selection>,
+    cubes: Query<&Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    time: Res<Time>,
+) {
+    let AnimatedTexturesWindow {
+        is_open,
+        path,
+        animations,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut load = false;
+    let mut removed = None;
+    let mut textured = None;
+    egui::Window::new(AnimatedTexturesWindow::TITLE)
+        .open(is_open)
+        .default_width(340.0)
+        .show(contexts.ctx::<AnimatedTexturesWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                let response = ui
+                    .text_edit_singleline(path)
+                    .on_hover_text("An animated GIF or PNG, relative to the working directory");
+                load = ui.button("Load").clicked()
+                    || response.lost_focus()
+                        && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            });
+            if animations.is_empty() {
+                ui.weak("No animations loaded.");
+                return;
+            }
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(420.0)
+                .show(ui, |ui| {
+                    for (index, animation) in animations.iter_mut().enumerate() {
+                        let size = animation.frames.size;
+                        ui.horizontal(|ui| {
+                            if let Some(texture) = user_textures.image_id(&animation.image) {
+                                ui.add(
+                                    egui::Image::new(egui::load::SizedTexture::new(
+                                        texture,
+                                        [size.x as f32, size.y as f32],
+                                    ))
+                                    .max_size(egui::Vec2::splat(THUMBNAIL)),
+                                );
+                            }
+                            ui.vertical(|ui| {
+                                ui.label(&animation.name);
+                                ui.weak(format!(
+                                    "{}×{}, frame {} of {}",
+                                    size.x,
+                                    size.y,
+                                    animation.current + 1,
+                                    animation.frames.frames.len()
+                                ));
+                                ui.horizontal(|ui| {
+                                    let icon = if animation.playing {
+                                        Icon::Pause
+                                    } else {
+                                        Icon::Play
+                                    };
+                                    if ui.icon_button(icon, "").clicked() {
+                                        animation.playing = !animation.playing;
+                                    }
+                                    ui.add(
+                                        drag_value(&mut animation.speed)
+                                            .speed(0.05)
+                                            .range(0.05..=10.0)
+                                            .suffix("×"),
+                                    )
+                                    .on_hover_text("Playback speed");
+                                });
+                                let in_side_panel = side_panel
+                                    .0
+                                    .as_ref()
+                                    .is_some_and(|(image, _)| *image == animation.image);
+                                let mut toggled = in_side_panel;
+                                if ui
+                                    .toggle_value(&mut toggled, "Side panel image")
+                                    .on_hover_text(
+                                        "Show in the side panel instead of the Bevy icon",
+                                    )
+                                    .changed()
+                                {
+                                    side_panel.0 = toggled.then(|| (animation.image.clone(), size));
+                                }
+                                if ui
+                                    .add_enabled(
+                                        !selection.entities.is_empty(),
+                                        egui::Button::new("Texture the selection"),
+                                    )
+                                    .on_disabled_hover_text("Select cubes in the viewport first")
+                                    .clicked()
+                                {
+                                    textured = Some(animation.image.clone());
+                                }
+                                if ui.small_icon_button(Icon::Delete, "Remove").clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                        });
+                        ui.separator();
+                    }
+                });
+        });
+
+    if load {
+        let decoded = std::fs::read(&*path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| decode(&bytes));
+        match decoded {
+            Ok(frames) => {
+                let image = Image::new(
+                    Extent3d {
+                        width: frames.size.x,
+                        height: frames.size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    TextureDimension::D2,
+                    frames.frames[0].0.clone(),
+                    TextureFormat::Rgba8UnormSrgb,
+                    RenderAssetUsages::default(),
+                );
+                let image = images.add(image);
+                user_textures.add_image(image.clone());
+                let name = std::path::Path::new(&*path)
+                    .file_name()
+                    .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned());
+                status.flash(
+                    format!("Loaded {name}: {} frames", frames.frames.len()),
+                    time.elapsed_seconds(),
+                );
+                animations.push(Animation {
+                    name,
+                    image,
+                    frames,
+                    current: 0,
+                    elapsed: 0.0,
+                    playing: !settings.accessibility.reduced_motion,
+                    speed: 1.0,
+                });
+            }
+            Err(err) => {
+                errors.send(
+                    AppError::new("Animated textures", format!("Failed to load {path}: {err}"))
+                        .suggest("The path is relative to the working directory."),
+                );
+            }
+        }
+    }
+    if let Some(texture) = textured {
+        for material in cubes.iter_many(&selection.entities) {
+            if let Some(material) = materials.get_mut(material) {
+                material.base_color_texture = Some(texture.clone());
+            }
+        }
+    }
+    if let Some(index) = removed {
+        let animation = animations.remove(index);
+        user_textures.remove_image(&animation.image);
+        if side_panel
+            .0
+            .as_ref()
+            .is_some_and(|(image, _)| *image == animation.image)
+        {
+            side_panel.0 = None;
+        }
+    }
+}
+
+/// Moves each playing animation on by the frame time, uploading a frame only when it changes.
+fn advance_animations_system(
+    mut window: ResMut<AnimatedTexturesWindow>,
+    mut images: ResMut<Assets<Image>>,
+    time: Res<Time>,
+) {
+    for animation in &mut window.animations {
+        let count = animation.frames.frames.len();
+        if !animation.playing || count < 2 {
+            continue;
+        }
+        animation.elapsed += time.delta_seconds() * animation.speed;
+        let start = animation.current;
+        // Bounded, so a long stall skips ahead instead of stepping through every frame.
+        for _ in 0..count {
+            let delay = animation.frames.frames[animation.current].1;
+            if animation.elapsed < delay {
+                break;
+            }
+            animation.elapsed -= delay;
+            animation.current = (animation.current + 1) % count;
+        }
+        animation.elapsed = animation
+            .elapsed
+            .min(animation.frames.frames[animation.current].1);
+        if animation.current == start {
+            continue;
+        }
+        if let Some(image) = images.get_mut(&animation.image) {
+            image
+                .data
+                .copy_from_slice(&animation.frames.frames[animation.current].0);
+        }
+    }
+}
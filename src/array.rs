@@ -0,0 +1,359 @@
+use bevy::{ecs::system::SystemParam, prelude::*, render::primitives::Aabb};
+use bevy_egui::egui;
+
+use crate::{
+    numeric::{drag_value, unit_drag_value, Unit},
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{SceneId, SceneReader, SceneWriter},
+    selection::{vec3_edit, Selection},
+    settings::Settings,
+    RenderCube,
+};
+
+/// Copies made by one operation at most.
+const MAX_COPIES: usize = 10_000;
+/// Ghosts drawn at most; the rest of a large array is created but not previewed.
+const MAX_GHOSTS: usize = 500;
+
+/// Duplicates the primary selection along a line, over a grid or around a circle, with an
+/// optional rotation and scale added at every step. The copies are previewed as outlines
+/// while the window is open and spawned in one go, so the history records a single entry.
+pub struct ArrayPlugin;
+
+impl Plugin for ArrayPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ArrayWindow>()
+            .add_systems(
+                Update,
+                (
+                    array_window_system.in_set(ArrayWindow::ui_set()),
+                    draw_ghosts_system,
+                    create_array_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Array…", |world| {
+                world.resource_mut::<ArrayWindow>().is_open = true;
+            }));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Linear,
+    Grid,
+    Circle,
+}
+
+impl Layout {
+    const ALL: [(Layout, &'static str); 3] = [
+        (Layout::Linear, "Linear"),
+        (Layout::Grid, "Grid"),
+        (Layout::Circle, "Circle"),
+    ];
+}
+
+#[derive(Resource)]
+pub struct ArrayWindow {
+    pub is_open: bool,
+    layout: Layout,
+    /// Copies along the line or around the circle, not counting the original.
+    copies: usize,
+    /// Offset between neighbours on a line.
+    offset: Vec3,
+    /// Entities per grid axis, counting the original.
+    grid: [usize; 3],
+    /// Distance between grid cells.
+    spacing: Vec3,
+    radius: f32,
+    /// Degrees the circle covers; a full turn spaces the copies evenly all the way round.
+    sweep: f32,
+    /// Turn every copy to follow the circle.
+    orient: bool,
+    /// Degrees added about each axis at every step.
+    step_rotation: Vec3,
+    /// Factor the scale is multiplied by at every step.
+    step_scale: f32,
+    create: bool,
+}
+
+impl Default for ArrayWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            layout: Layout::Linear,
+            copies: 4,
+            offset: Vec3::new(1.5, 0.0, 0.0),
+            grid: [3, 1, 3],
+            spacing: Vec3::splat(1.5),
+            radius: 4.0,
+            sweep: 360.0,
+            orient: true,
+            step_rotation: Vec3::ZERO,
+            step_scale: 1.0,
+            create: false,
+        }
+    }
+}
+
+impl Panel for ArrayWindow {
+    const TITLE: &'static str = "Array";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl ArrayWindow {
+    fn copy_count(&self) -> usize {
+        let count = match self.layout {
+            Layout::Linear | Layout::Circle => self.copies,
+            Layout::Grid => self.grid.iter().product::<usize>().saturating_sub(1),
+        };
+        count.min(MAX_COPIES)
+    }
+
+    /// Transforms of the copies of `source`, in the space of its parent.
+    fn copies_of(&self, source: Transform) -> Vec<Transform> {
+        let count = self.copy_count();
+        let [columns, rows, _] = self.grid.map(|cells| cells.max(1));
+        // The original is the first point of the circle, which is centred on its left.
+        let pivot = source.translation - Vec3::X * self.radius;
+        // A full turn would put the last copy on top of the original.
+        let angle_step = if self.sweep.abs() >= 360.0 {
+            self.sweep.to_radians() / (count + 1) as f32
+        } else {
+            self.sweep.to_radians() / count.max(1) as f32
+        };
+        (1..=count)
+            .map(|step| {
+                let mut copy = source;
+                match self.layout {
+                    Layout::Linear => copy.translation += self.offset * step as f32,
+                    Layout::Grid => {
+                        let cell = Vec3::new(
+                            (step % columns) as f32,
+                            (step / columns % rows) as f32,
+                            (step / (columns * rows)) as f32,
+                        );
+                        copy.translation += self.spacing * cell;
+                    }
+                    Layout::Circle => {
+                        let turn = Quat::from_rotation_y(angle_step * step as f32);
+                        copy.translation = pivot + turn * (source.translation - pivot);
+                        if self.orient {
+                            copy.rotation = turn * copy.rotation;
+                        }
+                    }
+                }
+                let degrees = self.step_rotation * step as f32;
+                copy.rotation = Quat::from_euler(
+                    EulerRot::XYZ,
+                    degrees.x.to_radians(),
+                    degrees.y.to_radians(),
+                    degrees.z.to_radians(),
+                ) * copy.rotation;
+                copy.scale *= self.step_scale.powi(step as i32);
+                copy
+            })
+            .collect()
+    }
+}
+
+fn array_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ArrayWindow>,
+    selection: Res<Selection>,
+    cubes: Query<(), With<RenderCube>>,
+) {
+    let has_source = selection
+        .primary()
+        .is_some_and(|primary| cubes.contains(primary));
+    let count = window.copy_count();
+    let ArrayWindow {
+        is_open,
+        layout,
+        copies,
+        offset,
+        grid,
+        spacing,
+        radius,
+        sweep,
+        orient,
+        step_rotation,
+        step_scale,
+        create,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(ArrayWindow::TITLE)
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<ArrayWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                for (option, label) in Layout::ALL {
+                    ui.selectable_value(layout, option, label);
+                }
+            });
+            ui.separator();
+            egui::Grid::new("array_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    match layout {
+                        Layout::Linear => {
+                            ui.label("Copies");
+                            ui.add(drag_value(copies).range(1..=MAX_COPIES));
+                            ui.end_row();
+                            ui.label("Offset");
+                            vec3_edit(ui, offset, 0.05, Some(Unit::Meters));
+                            ui.end_row();
+                        }
+                        Layout::Grid => {
+                            ui.label("Cells");
+                            ui.horizontal(|ui| {
+                                for (cells, label) in grid.iter_mut().zip(["x ", "y ", "z "]) {
+                                    ui.add(drag_value(cells).range(1..=100).prefix(label));
+                                }
+                            });
+                            ui.end_row();
+                            ui.label("Spacing");
+                            vec3_edit(ui, spacing, 0.05, Some(Unit::Meters));
+                            ui.end_row();
+                        }
+                        Layout::Circle => {
+                            ui.label("Copies");
+                            ui.add(drag_value(copies).range(1..=MAX_COPIES));
+                            ui.end_row();
+                            ui.label("Radius");
+                            ui.add(
+                                unit_drag_value(radius, Unit::Meters)
+                                    .speed(0.05)
+                                    .range(0.0..=1000.0),
+                            );
+                            ui.end_row();
+                            ui.label("Sweep");
+                            ui.add(
+                                unit_drag_value(sweep, Unit::Degrees)
+                                    .speed(1.0)
+                                    .range(-360.0..=360.0),
+                            );
+                            ui.end_row();
+                            ui.label("Orient");
+                            ui.checkbox(orient, "Turn copies with the circle");
+                            ui.end_row();
+                        }
+                    }
+                    ui.label("Step rotation");
+                    vec3_edit(ui, step_rotation, 1.0, Some(Unit::Degrees));
+                    ui.end_row();
+                    ui.label("Step scale");
+                    ui.add(drag_value(step_scale).speed(0.01).range(0.01..=10.0))
+                        .on_hover_text("Each copy's scale is the previous one's times this");
+                    ui.end_row();
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        has_source,
+                        egui::Button::new(format!("Create {count} copies")),
+                    )
+                    .on_disabled_hover_text("Select a cube to duplicate")
+                    .clicked()
+                {
+                    *create = true;
+                }
+                if count == MAX_COPIES {
+                    ui.weak(format!("at most {MAX_COPIES}"));
+                }
+            });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_ghosts_system(
+    window: Res<ArrayWindow>,
+    selection: Res<Selection>,
+    sources: Query<(&Transform, Option<&Aabb>, Option<&Parent>), With<RenderCube>>,
+    globals: Query<&GlobalTransform>,
+    settings: Res<Settings>,
+    mut gizmos: Gizmos,
+) {
+    if !window.is_open {
+        return;
+    }
+    let Some((transform, aabb, parent)) = selection
+        .primary()
+        .and_then(|primary| sources.get(primary).ok())
+    else {
+        return;
+    };
+    let parent = parent
+        .and_then(|parent| globals.get(parent.get()).ok())
+        .copied()
+        .unwrap_or_default();
+    let aabb = aabb
+        .copied()
+        .unwrap_or_else(|| Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5)));
+    let bounds = Transform::from_translation(aabb.center.into())
+        .with_scale(Vec3::from(aabb.half_extents) * 2.0);
+    let color = settings.highlights().positive;
+    for copy in window.copies_of(*transform).into_iter().take(MAX_GHOSTS) {
+        gizmos.cuboid(parent.mul_transform(copy).mul_transform(bounds), color);
+    }
+}
+
+#[derive(SystemParam)]
+struct ArrayScene<'w, 's> {
+    scene: ParamSet<'w, 's, (SceneReader<'w, 's>, SceneWriter<'w, 's>)>,
+    sources: Query<
+        'w,
+        's,
+        (
+            &'static SceneId,
+            &'static Transform,
+            Option<&'static Parent>,
+        ),
+    >,
+}
+
+fn create_array_system(
+    mut window: ResMut<ArrayWindow>,
+    mut selection: ResMut<Selection>,
+    mut scene: ArrayScene,
+) {
+    if !std::mem::take(&mut window.create) {
+        return;
+    }
+    let Some(primary) = selection.primary() else {
+        return;
+    };
+    let Ok((id, transform, parent)) = scene.sources.get(primary) else {
+        return;
+    };
+    let (id, transform, parent) = (**id, *transform, parent.map(Parent::get));
+    // Copies carry everything the scene file keeps: text, scripts, properties and so on.
+    let Some(template) = scene
+        .scene
+        .p0()
+        .capture()
+        .entities
+        .into_iter()
+        .find(|entity| entity.id == id)
+    else {
+        return;
+    };
+    let mut writer = scene.scene.p1();
+    let mut copies = vec![primary];
+    for copy in window.copies_of(transform) {
+        let mut entity = template.clone();
+        entity.id = 0;
+        entity.translation = copy.translation.to_array();
+        entity.rotation = copy.rotation.to_array();
+        entity.scale = copy.scale.to_array();
+        copies.push(writer.spawn(&entity, parent));
+    }
+    selection.entities = copies;
+}
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use bevy::{asset::UntypedAssetId, prelude::*, render::camera::RenderTarget};
+use bevy_egui::{egui, EguiContexts, EguiUserTextures};
+
+use crate::{
+    icons::{self, Icon, IconButtonsExt},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    settings::{egui_color, Settings},
+};
+
+/// Rows listed per change kind; the counts still cover everything.
+const MAX_ROWS: usize = 200;
+
+/// A developer window for finding asset leaks: it records which meshes, materials, images and
+/// egui textures are alive, and compares a later snapshot against it. Anything that was in use
+/// by an entity or material in the baseline, but is still loaded after its users went away, is
+/// flagged as retained.
+pub struct AssetSnapshotsPlugin;
+
+impl Plugin for AssetSnapshotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<AssetSnapshotsWindow>().add_systems(
+            Update,
+            (
+                asset_snapshots_window_system.in_set(AssetSnapshotsWindow::ui_set()),
+                take_snapshot_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum AssetKey {
+    Asset(UntypedAssetId),
+    Egui(egui::TextureId),
+}
+
+struct AssetEntry {
+    kind: &'static str,
+    /// What the asset is, to tell it apart from others of its kind.
+    detail: String,
+    /// Entities, materials and cameras using it.
+    users: usize,
+}
+
+struct AssetSnapshot {
+    taken_at: f32,
+    assets: HashMap<AssetKey, AssetEntry>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Baseline,
+    Compare,
+}
+
+#[derive(Default, Resource)]
+pub struct AssetSnapshotsWindow {
+    pub is_open: bool,
+    baseline: Option<AssetSnapshot>,
+    compare: Option<AssetSnapshot>,
+    /// The difference between the two, worked out once when the comparison is taken.
+    changes: Vec<(Change, AssetKey)>,
+    take: Option<Slot>,
+}
+
+impl Panel for AssetSnapshotsWindow {
+    const TITLE: &'static str = "Asset Snapshots";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Change {
+    /// Used in the baseline, unused now, yet still loaded.
+    Retained,
+    Added,
+    Freed,
+}
+
+fn asset_snapshots_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<AssetSnapshotsWindow>,
+    settings: Res<Settings>,
+) {
+    let AssetSnapshotsWindow {
+        is_open,
+        baseline,
+        compare,
+        changes,
+        take,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let highlights = settings.highlights();
+    egui::Window::new(AssetSnapshotsWindow::TITLE)
+        .open(is_open)
+        .default_width(380.0)
+        .show(contexts.ctx::<AssetSnapshotsWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .icon_button(Icon::Snapshot, "Baseline")
+                    .on_hover_text("Record the live assets to compare against")
+                    .clicked()
+                {
+                    *take = Some(Slot::Baseline);
+                }
+                if ui
+                    .add_enabled(
+                        baseline.is_some(),
+                        icons::button(ui, Icon::Snapshot, "Compare"),
+                    )
+                    .on_hover_text("Record the live assets again and diff them with the baseline")
+                    .on_disabled_hover_text("Take a baseline first")
+                    .clicked()
+                {
+                    *take = Some(Slot::Compare);
+                }
+            });
+            let Some(before) = baseline else {
+                ui.weak(
+                    "Take a baseline, do what should free assets (remove cubes, close a window), \
+                     then compare.",
+                );
+                return;
+            };
+            ui.label(format!(
+                "Baseline at {:.1}s: {} assets",
+                before.taken_at,
+                before.assets.len()
+            ));
+            let Some(after) = compare else {
+                return;
+            };
+            ui.label(format!(
+                "Compared at {:.1}s: {} assets",
+                after.taken_at,
+                after.assets.len()
+            ));
+            ui.separator();
+
+            let count = |change| changes.iter().filter(|(c, _)| *c == change).count();
+            let retained = count(Change::Retained);
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    egui_color(highlights.warning),
+                    format!("{retained} retained"),
+                );
+                ui.colored_label(
+                    egui_color(highlights.positive),
+                    format!("{} added", count(Change::Added)),
+                );
+                ui.colored_label(
+                    egui_color(highlights.negative),
+                    format!("{} freed", count(Change::Freed)),
+                );
+            });
+            if changes.is_empty() {
+                ui.weak("No changes.");
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("asset_snapshot_diff")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            let mut shown = HashMap::new();
+                            for (change, key) in changes.iter() {
+                                let snapshot = match change {
+                                    Change::Freed => &*before,
+                                    _ => &*after,
+                                };
+                                let Some(entry) = snapshot.assets.get(key) else {
+                                    continue;
+                                };
+                                let rows = shown.entry(*change).or_insert(0);
+                                *rows += 1;
+                                if *rows > MAX_ROWS {
+                                    continue;
+                                }
+                                let (symbol, color, hover) = match change {
+                                    Change::Retained => (
+                                        "!",
+                                        highlights.warning,
+                                        "In use in the baseline; nothing uses it now, but it is \
+                                         still loaded",
+                                    ),
+                                    Change::Added => {
+                                        ("+", highlights.positive, "Loaded since the baseline")
+                                    }
+                                    Change::Freed => {
+                                        ("-", highlights.negative, "Freed since the baseline")
+                                    }
+                                };
+                                ui.colored_label(
+                                    egui_color(color),
+                                    egui::RichText::new(symbol).monospace(),
+                                )
+                                .on_hover_text(hover);
+                                ui.label(entry.kind);
+                                ui.monospace(&entry.detail);
+                                ui.end_row();
+                            }
+                        });
+                });
+            if retained > 0 {
+                ui.weak(
+                    "Retained assets are held by a handle outside the scene, such as a window's \
+                     list or an egui texture registration.",
+                );
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn take_snapshot_system(
+    mut window: ResMut<AssetSnapshotsWindow>,
+    mut egui: EguiContexts,
+    time: Res<Time>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    images: Res<Assets<Image>>,
+    user_textures: Res<EguiUserTextures>,
+    mesh_users: Query<&Handle<Mesh>>,
+    material_users: Query<&Handle<StandardMaterial>>,
+    image_users: Query<&Handle<Image>>,
+    cameras: Query<&Camera>,
+) {
+    let Some(slot) = window.take.take() else {
+        return;
+    };
+
+    let mut users: HashMap<UntypedAssetId, usize> = HashMap::new();
+    let mut used = |id: UntypedAssetId| *users.entry(id).or_default() += 1;
+    mesh_users
+        .iter()
+        .for_each(|handle| used(handle.id().untyped()));
+    material_users
+        .iter()
+        .for_each(|handle| used(handle.id().untyped()));
+    image_users
+        .iter()
+        .for_each(|handle| used(handle.id().untyped()));
+    for (_, material) in materials.iter() {
+        for texture in [
+            &material.base_color_texture,
+            &material.emissive_texture,
+            &material.metallic_roughness_texture,
+            &material.normal_map_texture,
+            &material.occlusion_texture,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            used(texture.id().untyped());
+        }
+    }
+    for camera in &cameras {
+        if let RenderTarget::Image(target) = &camera.target {
+            used(target.id().untyped());
+        }
+    }
+
+    let mut assets = HashMap::new();
+    let mut add = |id: UntypedAssetId, kind, detail| {
+        let users = users.get(&id).copied().unwrap_or_default();
+        assets.insert(
+            AssetKey::Asset(id),
+            AssetEntry {
+                kind,
+                detail,
+                users,
+            },
+        );
+    };
+    for (id, mesh) in meshes.iter() {
+        add(
+            id.untyped(),
+            "Mesh",
+            format!("{} vertices", mesh.count_vertices()),
+        );
+    }
+    for (id, material) in materials.iter() {
+        let [r, g, b, _] = material.base_color.to_srgba().to_u8_array();
+        let textured = if material.base_color_texture.is_some() {
+            ", textured"
+        } else {
+            ""
+        };
+        add(
+            id.untyped(),
+            "Material",
+            format!("#{r:02x}{g:02x}{b:02x}{textured}"),
+        );
+    }
+    for (id, image) in images.iter() {
+        let size = image.size();
+        let egui = if user_textures.image_id(&Handle::Weak(id)).is_some() {
+            ", egui"
+        } else {
+            ""
+        };
+        add(
+            id.untyped(),
+            "Image",
+            format!("{}×{}{egui}", size.x, size.y),
+        );
+    }
+    // Textures egui manages itself, such as the font atlas; user images are counted above.
+    for (id, meta) in egui.ctx_mut().tex_manager().read().allocated() {
+        if let egui::TextureId::Managed(_) = id {
+            assets.insert(
+                AssetKey::Egui(*id),
+                AssetEntry {
+                    kind: "egui",
+                    detail: format!("{} {}×{}", meta.name, meta.size[0], meta.size[1]),
+                    users: 0,
+                },
+            );
+        }
+    }
+
+    let snapshot = AssetSnapshot {
+        taken_at: time.elapsed_seconds(),
+        assets,
+    };
+    match slot {
+        Slot::Baseline => {
+            window.baseline = Some(snapshot);
+            window.compare = None;
+        }
+        Slot::Compare => window.compare = Some(snapshot),
+    }
+    window.changes = match (&window.baseline, &window.compare) {
+        (Some(before), Some(after)) => changes(before, after),
+        _ => Vec::new(),
+    };
+}
+
+/// What changed from `before` to `after`, by kind of change and then of asset.
+fn changes(before: &AssetSnapshot, after: &AssetSnapshot) -> Vec<(Change, AssetKey)> {
+    let mut changes: Vec<(Change, &AssetEntry, AssetKey)> = after
+        .assets
+        .iter()
+        .filter_map(|(key, entry)| match before.assets.get(key) {
+            None => Some((Change::Added, entry, *key)),
+            Some(old) if old.users > 0 && entry.users == 0 => Some((Change::Retained, entry, *key)),
+            Some(_) => None,
+        })
+        .chain(
+            before
+                .assets
+                .iter()
+                .filter(|(key, _)| !after.assets.contains_key(key))
+                .map(|(key, entry)| (Change::Freed, entry, *key)),
+        )
+        .collect();
+    changes.sort_by(|(a, ea, _), (b, eb, _)| a.cmp(b).then(ea.kind.cmp(eb.kind)));
+    changes
+        .into_iter()
+        .map(|(change, _, key)| (change, key))
+        .collect()
+}
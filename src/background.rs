@@ -0,0 +1,591 @@
+use bevy::{
+    color::Srgba,
+    core_pipeline::Skybox,
+    pbr::environment_map::EnvironmentMapLight,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+        },
+    },
+};
+use bevy_egui::egui;
+use xihydra_bevy::widgets::{Gradient, GradientEditor};
+
+use crate::{
+    numeric::{slider, unit_drag_value, unit_slider, Unit},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    settings::{Settings, ViewportClear},
+    ViewportCamera,
+};
+
+/// Face size the environment is resampled at when turned; larger captured cubemaps are
+/// downsampled so turning stays interactive.
+const ROTATED_SIZE: u32 = 256;
+/// Face size of the blurred copy that lights diffuse surfaces.
+const DIFFUSE_SIZE: u32 = 8;
+
+/// Background options for each viewport camera, edited in the Background window.
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<BackgroundWindow>().add_systems(
+            Update,
+            (
+                background_window_system.in_set(BackgroundWindow::ui_set()),
+                sync_clear_settings_system,
+                spin_environment_system,
+                apply_background_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundMode {
+    Solid,
+    Gradient,
+    /// For inspecting alpha: the scene is cleared to transparent over a checkerboard.
+    Checkerboard,
+    Environment,
+}
+
+/// What is drawn behind a viewport's geometry. Gradient and checkerboard are painted by egui
+/// behind a transparent render; the environment is a procedural sky cubemap, which can be
+/// turned about the vertical axis and light the scene, so a material can be judged under
+/// moving reflections without moving the object.
+#[derive(Component, Clone, PartialEq)]
+pub struct ViewportBackground {
+    pub mode: BackgroundMode,
+    /// How the solid mode clears; taken from the Graphics settings.
+    pub clear: ViewportClear,
+    pub color: [f32; 4],
+    /// The gradient mode's colours, from the bottom edge at 0 to the top at 1.
+    pub sky: Gradient,
+    pub checker_size: f32,
+    pub environment_brightness: f32,
+    /// Turn of the environment about the vertical axis, in degrees.
+    pub environment_rotation: f32,
+    /// Degrees a second the environment turns by itself; zero holds it still.
+    pub environment_spin: f32,
+    /// Whether the environment also lights the scene, through reflections and ambient light.
+    pub environment_lighting: bool,
+}
+
+impl Default for ViewportBackground {
+    fn default() -> Self {
+        Self {
+            mode: BackgroundMode::Solid,
+            clear: ViewportClear::Custom,
+            color: [0.07, 0.07, 0.07, 1.0],
+            sky: Gradient::two([0.05, 0.05, 0.06], [0.32, 0.36, 0.42]),
+            checker_size: 16.0,
+            environment_brightness: 1000.0,
+            environment_rotation: 0.0,
+            environment_spin: 0.0,
+            environment_lighting: false,
+        }
+    }
+}
+
+impl ViewportBackground {
+    /// Paints the egui-drawn modes behind the viewport image at `rect`.
+    pub fn paint(&self, painter: &egui::Painter, rect: egui::Rect) {
+        match self.mode {
+            BackgroundMode::Gradient => self.sky.paint(painter, rect, egui::Direction::BottomUp),
+            BackgroundMode::Checkerboard => {
+                let size = self.checker_size.max(2.0);
+                painter.rect_filled(rect, 0.0, egui::Color32::from_gray(204));
+                let painter = painter.with_clip_rect(rect);
+                let columns = (rect.width() / size).ceil() as usize;
+                let rows = (rect.height() / size).ceil() as usize;
+                for row in 0..rows {
+                    for column in (row % 2..columns).step_by(2) {
+                        let min = rect.min + egui::vec2(column as f32, row as f32) * size;
+                        painter.rect_filled(
+                            egui::Rect::from_min_size(min, egui::Vec2::splat(size)),
+                            0.0,
+                            egui::Color32::from_gray(153),
+                        );
+                    }
+                }
+            }
+            BackgroundMode::Solid | BackgroundMode::Environment => {}
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct BackgroundWindow {
+    pub is_open: bool,
+    sky: Option<Handle<Image>>,
+    /// The sky as last turned, reused until the sky or its rotation changes.
+    turned: Option<TurnedSky>,
+}
+
+struct TurnedSky {
+    source: AssetId<Image>,
+    degrees: i32,
+    /// Sharp at the top level, blurring down the mip chain for rougher surfaces.
+    specular: Handle<Image>,
+    diffuse: Handle<Image>,
+}
+
+impl BackgroundWindow {
+    /// Replaces the procedural sky used by the environment mode.
+    pub fn set_sky(&mut self, image: Handle<Image>) {
+        self.sky = Some(image);
+    }
+}
+
+impl Panel for BackgroundWindow {
+    const TITLE: &'static str = "Background";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn background_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<BackgroundWindow>,
+    mut backgrounds: Query<&mut ViewportBackground, With<ViewportCamera>>,
+    mut settings: ResMut<Settings>,
+) {
+    if !window.is_open {
+        return;
+    }
+    let Ok(mut background) = backgrounds.get_single_mut() else {
+        return;
+    };
+
+    let mut edited = background.clone();
+    let mut graphics = settings.graphics.clone();
+    egui::Window::new("Background")
+        .open(&mut window.is_open)
+        .show(contexts.ctx::<BackgroundWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                for (mode, label) in [
+                    (BackgroundMode::Solid, "Solid"),
+                    (BackgroundMode::Gradient, "Gradient"),
+                    (BackgroundMode::Checkerboard, "Checkerboard"),
+                    (BackgroundMode::Environment, "Environment"),
+                ] {
+                    ui.selectable_value(&mut edited.mode, mode, label);
+                }
+            });
+            egui::Grid::new("background_grid")
+                .num_columns(2)
+                .show(ui, |ui| match edited.mode {
+                    BackgroundMode::Solid => {
+                        ui.label("Clear");
+                        graphics
+                            .viewport_clear_ui(ui)
+                            .on_hover_text("Saved with the Graphics settings");
+                        ui.end_row();
+                    }
+                    BackgroundMode::Gradient => {
+                        ui.label("Bottom → top");
+                        GradientEditor::new("sky_gradient", &mut edited.sky).show(ui);
+                        ui.end_row();
+                    }
+                    BackgroundMode::Checkerboard => {
+                        ui.label("Square size");
+                        ui.add(slider(&mut edited.checker_size, 4.0..=64.0).suffix(" px"));
+                        ui.end_row();
+                    }
+                    BackgroundMode::Environment => {
+                        ui.label("Brightness");
+                        ui.add(
+                            slider(&mut edited.environment_brightness, 10.0..=20_000.0)
+                                .logarithmic(true)
+                                .suffix(" cd/m²"),
+                        );
+                        ui.end_row();
+                        ui.label("Rotation");
+                        ui.add(unit_slider(
+                            &mut edited.environment_rotation,
+                            0.0..=360.0,
+                            Unit::Degrees,
+                        ));
+                        ui.end_row();
+                        ui.label("Spin");
+                        ui.add(
+                            unit_drag_value(&mut edited.environment_spin, Unit::Degrees)
+                                .range(-90.0..=90.0)
+                                .speed(0.5)
+                                .suffix("/s"),
+                        )
+                        .on_hover_text("Turns the environment continuously; 0 holds it still");
+                        ui.end_row();
+                        ui.label("");
+                        ui.checkbox(&mut edited.environment_lighting, "Light the scene")
+                            .on_hover_text(
+                                "Reflections and ambient light from the environment, for \
+                                 judging materials as it turns",
+                            );
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if edited != *background {
+        *background = edited;
+    }
+    if graphics != settings.graphics {
+        settings.graphics = graphics;
+    }
+}
+
+/// Copies the saved viewport clear into the viewport backgrounds whenever it is edited, so
+/// animated backgrounds such as the day/night sky are only overridden by a change.
+fn sync_clear_settings_system(
+    settings: Res<Settings>,
+    mut synced: Local<Option<(ViewportClear, [f32; 4])>>,
+    mut backgrounds: Query<&mut ViewportBackground, With<ViewportCamera>>,
+) {
+    let saved = (
+        settings.graphics.viewport_clear,
+        settings.graphics.viewport_clear_color,
+    );
+    if *synced == Some(saved) {
+        return;
+    }
+    *synced = Some(saved);
+    for mut background in &mut backgrounds {
+        (background.clear, background.color) = saved;
+    }
+}
+
+fn spin_environment_system(
+    time: Res<Time>,
+    mut backgrounds: Query<&mut ViewportBackground, With<ViewportCamera>>,
+) {
+    for mut background in &mut backgrounds {
+        if background.mode == BackgroundMode::Environment && background.environment_spin != 0.0 {
+            let rotation = background.environment_rotation
+                + background.environment_spin * time.delta_seconds();
+            background.environment_rotation = rotation.rem_euclid(360.0);
+        }
+    }
+}
+
+fn apply_background_system(
+    mut commands: Commands,
+    mut window: ResMut<BackgroundWindow>,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<(Entity, &mut Camera, &ViewportBackground), Changed<ViewportBackground>>,
+) {
+    for (entity, mut camera, background) in &mut cameras {
+        let [r, g, b, a] = background.color;
+        camera.clear_color = match (background.mode, background.clear) {
+            (BackgroundMode::Solid, ViewportClear::Window) => ClearColorConfig::Default,
+            (BackgroundMode::Solid, ViewportClear::Custom) => {
+                ClearColorConfig::Custom(Color::srgba(r, g, b, a))
+            }
+            (BackgroundMode::Solid, ViewportClear::None) => ClearColorConfig::None,
+            _ => ClearColorConfig::Custom(Color::NONE),
+        };
+        if background.mode != BackgroundMode::Environment {
+            commands
+                .entity(entity)
+                .remove::<(Skybox, EnvironmentMapLight)>();
+            continue;
+        }
+        let source = window
+            .sky
+            .get_or_insert_with(|| images.add(sky_cubemap(64)))
+            .clone();
+        // Whole degrees, so a spinning sky is resampled a few times a second, not every frame.
+        let degrees = (background.environment_rotation.round() as i32).rem_euclid(360);
+        let unchanged =
+            |turned: &TurnedSky| turned.source == source.id() && turned.degrees == degrees;
+        if !window.turned.as_ref().is_some_and(unchanged)
+            && (degrees != 0 || background.environment_lighting)
+        {
+            window.turned = images
+                .get(&source)
+                .and_then(CubeTexels::from_image)
+                .map(|texels| {
+                    let rotation = Quat::from_rotation_y((degrees as f32).to_radians());
+                    let turned = texels.rotated(rotation, texels.size.min(ROTATED_SIZE));
+                    let mut levels = vec![turned];
+                    while let Some(level) = levels.last().filter(|level| level.size > 1) {
+                        levels.push(level.downsampled());
+                    }
+                    let small = levels
+                        .iter()
+                        .find(|level| level.size <= 16)
+                        .unwrap_or(&levels[0]);
+                    let diffuse =
+                        images.add(CubeTexels::into_image(&[small.irradiance(DIFFUSE_SIZE)]));
+                    TurnedSky {
+                        source: source.id(),
+                        degrees,
+                        specular: images.add(CubeTexels::into_image(&levels)),
+                        diffuse,
+                    }
+                });
+        }
+        let turned = window.turned.as_ref().filter(|turned| unchanged(turned));
+        let image = match turned {
+            Some(turned) if degrees != 0 => turned.specular.clone(),
+            _ => source.clone(),
+        };
+        commands.entity(entity).insert(Skybox {
+            image,
+            brightness: background.environment_brightness,
+        });
+        match turned.filter(|_| background.environment_lighting) {
+            Some(turned) => {
+                commands.entity(entity).insert(EnvironmentMapLight {
+                    diffuse_map: turned.diffuse.clone(),
+                    specular_map: turned.specular.clone(),
+                    intensity: background.environment_brightness,
+                });
+            }
+            None => {
+                commands.entity(entity).remove::<EnvironmentMapLight>();
+            }
+        }
+    }
+}
+
+/// The direction of texel `u`, `v` (each -1 to 1) on cubemap `face`, with the face order and
+/// orientation wgpu expects: +X, -X, +Y, -Y, +Z, -Z.
+fn face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0),
+    }
+    .normalize()
+}
+
+/// The face `direction` points at and where on it, the inverse of [`face_direction`].
+fn face_coordinates(direction: Vec3) -> (usize, f32, f32) {
+    let Vec3 { x, y, z } = direction;
+    let abs = direction.abs();
+    if abs.x >= abs.y && abs.x >= abs.z {
+        if x > 0.0 {
+            (0, -z / abs.x, -y / abs.x)
+        } else {
+            (1, z / abs.x, -y / abs.x)
+        }
+    } else if abs.y >= abs.z {
+        if y > 0.0 {
+            (2, x / abs.y, z / abs.y)
+        } else {
+            (3, x / abs.y, -z / abs.y)
+        }
+    } else if z > 0.0 {
+        (4, x / abs.z, -y / abs.z)
+    } else {
+        (5, -x / abs.z, -y / abs.z)
+    }
+}
+
+/// A procedural sky: blue zenith, pale horizon, a dark ground and a sun, as a cubemap. Kept
+/// in the main world too, so it can be turned.
+fn sky_cubemap(size: u32) -> Image {
+    let zenith = Vec3::new(0.25, 0.45, 0.85);
+    let horizon = Vec3::new(0.8, 0.85, 0.9);
+    let ground = Vec3::new(0.25, 0.22, 0.2);
+    let sun = Vec3::new(1.0, 0.95, 0.85);
+    // Off to one side, so turning the sky moves it across the view and the reflections.
+    let sun_direction = Vec3::new(0.6, 0.45, -0.65).normalize();
+
+    let mut texels = Vec::with_capacity((size * size * 6) as usize);
+    for face in 0..6 {
+        for y in 0..size {
+            for x in 0..size {
+                let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let direction = face_direction(face, u, v);
+                let color = if direction.y >= 0.0 {
+                    horizon.lerp(zenith, direction.y.sqrt())
+                } else {
+                    horizon.lerp(ground, (-direction.y * 8.0).min(1.0))
+                };
+                let toward_sun = direction.dot(sun_direction).max(0.0);
+                let glow = toward_sun.powf(32.0) * 0.35 + toward_sun.powf(1500.0);
+                texels.push(color.lerp(sun, glow.min(1.0)));
+            }
+        }
+    }
+    let mut image = CubeTexels::into_image(&[CubeTexels { size, texels }]);
+    image.asset_usage = RenderAssetUsages::default();
+    image
+}
+
+/// A cubemap's top level in linear RGB, face after face, for resampling on the CPU.
+struct CubeTexels {
+    size: u32,
+    texels: Vec<Vec3>,
+}
+
+impl CubeTexels {
+    /// Reads an sRGB cubemap without mips, as the procedural and captured skies are.
+    fn from_image(image: &Image) -> Option<Self> {
+        let descriptor = &image.texture_descriptor;
+        let size = descriptor.size.width;
+        if descriptor.format != TextureFormat::Rgba8UnormSrgb
+            || descriptor.size.depth_or_array_layers != 6
+            || descriptor.mip_level_count != 1
+            || image.data.len() != (size * size * 6 * 4) as usize
+        {
+            return None;
+        }
+        let decode: Vec<f32> = (0..=255u8)
+            .map(|value| Srgba::gamma_function(value as f32 / 255.0))
+            .collect();
+        let texels = image
+            .data
+            .chunks_exact(4)
+            .map(|texel| {
+                Vec3::new(
+                    decode[texel[0] as usize],
+                    decode[texel[1] as usize],
+                    decode[texel[2] as usize],
+                )
+            })
+            .collect();
+        Some(Self { size, texels })
+    }
+
+    fn texel(&self, face: usize, x: u32, y: u32) -> Vec3 {
+        let x = x.min(self.size - 1);
+        let y = y.min(self.size - 1);
+        self.texels[((face as u32 * self.size + y) * self.size + x) as usize]
+    }
+
+    /// Bilinear within the face `direction` points at, clamped at its edges.
+    fn sample(&self, direction: Vec3) -> Vec3 {
+        let (face, u, v) = face_coordinates(direction);
+        let x = ((u + 1.0) / 2.0 * self.size as f32 - 0.5).max(0.0);
+        let y = ((v + 1.0) / 2.0 * self.size as f32 - 0.5).max(0.0);
+        let (x0, y0) = (x as u32, y as u32);
+        let (fx, fy) = (x.fract(), y.fract());
+        let top = self
+            .texel(face, x0, y0)
+            .lerp(self.texel(face, x0 + 1, y0), fx);
+        let bottom = self
+            .texel(face, x0, y0 + 1)
+            .lerp(self.texel(face, x0 + 1, y0 + 1), fx);
+        top.lerp(bottom, fy)
+    }
+
+    /// A cubemap of `size` with each texel set by `f` from its direction.
+    fn build(size: u32, mut f: impl FnMut(Vec3) -> Vec3) -> Self {
+        let mut texels = Vec::with_capacity((size * size * 6) as usize);
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                    let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                    texels.push(f(face_direction(face, u, v)));
+                }
+            }
+        }
+        Self { size, texels }
+    }
+
+    /// The environment as seen after turning it by `rotation`.
+    fn rotated(&self, rotation: Quat, size: u32) -> Self {
+        let inverse = rotation.inverse();
+        Self::build(size, |direction| self.sample(inverse * direction))
+    }
+
+    /// Half the size, averaging each two by two block; blurrier for each mip level down.
+    fn downsampled(&self) -> Self {
+        let size = (self.size / 2).max(1);
+        let mut texels = Vec::with_capacity((size * size * 6) as usize);
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    let sum = self.texel(face, x * 2, y * 2)
+                        + self.texel(face, x * 2 + 1, y * 2)
+                        + self.texel(face, x * 2, y * 2 + 1)
+                        + self.texel(face, x * 2 + 1, y * 2 + 1);
+                    texels.push(sum / 4.0);
+                }
+            }
+        }
+        Self { size, texels }
+    }
+
+    /// The light a diffuse surface facing each direction receives, from a cosine-weighted sum
+    /// over every texel. Meant for a small source: the cost is the product of both sizes.
+    fn irradiance(&self, size: u32) -> Self {
+        let step = 2.0 / self.size as f32;
+        let samples: Vec<(Vec3, Vec3)> = (0..6)
+            .flat_map(|face| {
+                (0..self.size).flat_map(move |y| (0..self.size).map(move |x| (face, x, y)))
+            })
+            .map(|(face, x, y)| {
+                let u = (x as f32 + 0.5) * step - 1.0;
+                let v = (y as f32 + 0.5) * step - 1.0;
+                // The solid angle a texel covers shrinks towards the face's corners.
+                let solid_angle = step * step / (1.0 + u * u + v * v).powf(1.5);
+                (
+                    face_direction(face, u, v),
+                    self.texel(face, x, y) * solid_angle,
+                )
+            })
+            .collect();
+        Self::build(size, |normal| {
+            let sum: Vec3 = samples
+                .iter()
+                .map(|(direction, light)| *light * normal.dot(*direction).max(0.0))
+                .sum();
+            sum / std::f32::consts::PI
+        })
+    }
+
+    fn encode(&self, data: &mut Vec<u8>, face: usize) {
+        let face_texels = (self.size * self.size) as usize;
+        for color in &self.texels[face * face_texels..(face + 1) * face_texels] {
+            data.extend(
+                Color::linear_rgb(color.x, color.y, color.z)
+                    .to_srgba()
+                    .to_u8_array(),
+            );
+        }
+    }
+
+    /// A cube texture with `levels` as its mip chain, largest first.
+    fn into_image(levels: &[CubeTexels]) -> Image {
+        // Each face holds all of its levels in turn, as wgpu reads layer by layer.
+        let mut data = Vec::new();
+        for face in 0..6 {
+            for level in levels {
+                level.encode(&mut data, face);
+            }
+        }
+        let size = levels[0].size;
+        let mut image = Image::new(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            TextureDimension::D2,
+            vec![0; (size * size * 6 * 4) as usize],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.data = data;
+        image.texture_descriptor.mip_level_count = levels.len() as u32;
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+        image
+    }
+}
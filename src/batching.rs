@@ -0,0 +1,312 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::{
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    scene::{self, CustomMesh, SceneEntity, SceneId},
+    selection::Selection,
+    RenderCube, RestRotation, Static,
+};
+
+/// "Bake Static" merges selected static cubes into combined meshes to cut draw calls, and
+/// "Unbake" restores them.
+pub struct BatchingPlugin;
+
+impl Plugin for BatchingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BakeCommand>()
+            .add_systems(Update, bake_command_system)
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Bake Static", |world| {
+                    world.send_event(BakeCommand::Bake);
+                })
+                .separator_before(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Unbake", |world| {
+                world.send_event(BakeCommand::Unbake);
+            }));
+    }
+}
+
+/// Acts on the current selection.
+#[derive(Event, Clone, Copy, PartialEq, Eq)]
+pub enum BakeCommand {
+    /// Merge selected static cubes that share a material, one batch per material.
+    Bake,
+    /// Replace selected batches by the cubes they were baked from.
+    Unbake,
+}
+
+/// A static mesh standing in for several cubes. It keeps the cubes it replaced, in world
+/// space, so they can be restored and so saving the scene still writes them out.
+#[derive(Component)]
+pub struct BakedBatch {
+    pub cubes: Vec<SceneEntity>,
+    /// Colours are per vertex and the material is plain white, rather than the cubes' own.
+    pub vertex_colors: bool,
+}
+
+impl BakedBatch {
+    /// The baked cubes with the batch's own `transform`, if it was moved since, applied.
+    pub fn placed_cubes<'a>(
+        &'a self,
+        transform: &'a Transform,
+    ) -> impl Iterator<Item = SceneEntity> + 'a {
+        self.cubes.iter().map(move |cube| {
+            let placed = transform.mul_transform(cube.transform());
+            SceneEntity {
+                translation: placed.translation.to_array(),
+                rotation: placed.rotation.to_array(),
+                scale: placed.scale.to_array(),
+                ..cube.clone()
+            }
+        })
+    }
+}
+
+/// Captures a cube in world space at its authored (rest) orientation, dropping its group.
+pub fn snapshot_cube(
+    global: &GlobalTransform,
+    transform: &Transform,
+    rest_rotation: Option<&RestRotation>,
+    color: Color,
+    id: Option<&SceneId>,
+    is_static: bool,
+) -> SceneEntity {
+    let parent = global.affine() * transform.compute_affine().inverse();
+    let rest = Transform {
+        rotation: rest_rotation.map_or(transform.rotation, |rest| **rest),
+        ..*transform
+    };
+    let world = GlobalTransform::from(parent * rest.compute_affine()).compute_transform();
+    SceneEntity {
+        id: id.map_or(0, |id| **id),
+        translation: world.translation.to_array(),
+        rotation: world.rotation.to_array(),
+        scale: world.scale.to_array(),
+        color: color.to_srgba().to_f32_array(),
+        group: None,
+        is_static,
+        text: None,
+        csg: None,
+        plant: None,
+        properties: default(),
+        fade: None,
+        script: None,
+        sprite: None,
+        mesh_file: None,
+        shape: default(),
+        surface: None,
+        finish: None,
+        texture: None,
+    }
+}
+
+/// Merges unit cubes into one triangle mesh. With `vertex_colors` each cube's colour is baked
+/// into its vertices, so cubes of different colours can share a single white material.
+pub fn batch_mesh(cubes: &[SceneEntity], vertex_colors: bool) -> Mesh {
+    let cube = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+    let attribute = |id| match cube.attribute(id) {
+        Some(VertexAttributeValues::Float32x3(values)) => values.clone(),
+        _ => Vec::new(),
+    };
+    let cube_positions = attribute(Mesh::ATTRIBUTE_POSITION);
+    let cube_normals = attribute(Mesh::ATTRIBUTE_NORMAL);
+    let cube_uvs = match cube.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(values)) => values.clone(),
+        _ => Vec::new(),
+    };
+    let cube_indices: Vec<u32> = cube
+        .indices()
+        .map(|indices| indices.iter().map(|index| index as u32).collect())
+        .unwrap_or_default();
+
+    let vertex_count = cube_positions.len() * cubes.len();
+    let mut positions = Vec::with_capacity(vertex_count);
+    let mut normals = Vec::with_capacity(vertex_count);
+    let mut uvs = Vec::with_capacity(vertex_count);
+    let mut colors = Vec::with_capacity(if vertex_colors { vertex_count } else { 0 });
+    let mut indices = Vec::with_capacity(cube_indices.len() * cubes.len());
+    for entity in cubes {
+        let transform = entity.transform();
+        let base = positions.len() as u32;
+        let [r, g, b, a] = entity.color;
+        let color = Color::srgba(r, g, b, a).to_linear().to_f32_array();
+        for (position, normal) in cube_positions.iter().zip(&cube_normals) {
+            positions.push(transform.transform_point(Vec3::from(*position)).to_array());
+            // Normals use the inverse-transpose, which for TRS is rotation * (n / scale).
+            let normal = transform.rotation * (Vec3::from(*normal) / transform.scale);
+            normals.push(normal.normalize_or_zero().to_array());
+            if vertex_colors {
+                colors.push(color);
+            }
+        }
+        uvs.extend_from_slice(&cube_uvs);
+        indices.extend(cube_indices.iter().map(|index| base + index));
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices));
+    if vertex_colors {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    mesh
+}
+
+/// Spawns a [`BakedBatch`] drawing `cubes` with `material`.
+pub fn spawn_batch(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+    cubes: Vec<SceneEntity>,
+    vertex_colors: bool,
+) -> Entity {
+    let mesh = batch_mesh(&cubes, vertex_colors);
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: meshes.add(mesh),
+                material,
+                ..default()
+            },
+            Name::new(format!("Baked batch ({} cubes)", cubes.len())),
+            BakedBatch {
+                cubes,
+                vertex_colors,
+            },
+        ))
+        .id()
+}
+
+/// Whether two cube materials render alike, comparing the fields the Inspector edits.
+fn same_material(a: &StandardMaterial, b: &StandardMaterial) -> bool {
+    a.base_color == b.base_color
+        && a.metallic == b.metallic
+        && a.perceptual_roughness == b.perceptual_roughness
+        && a.reflectance == b.reflectance
+        && a.unlit == b.unlit
+        && a.emissive == b.emissive
+        && a.base_color_texture == b.base_color_texture
+}
+
+#[allow(clippy::type_complexity)]
+fn bake_command_system(
+    mut events: EventReader<BakeCommand>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut selection: ResMut<Selection>,
+    cubes: Query<
+        (
+            &GlobalTransform,
+            &Transform,
+            Option<&RestRotation>,
+            &Handle<StandardMaterial>,
+            Option<&SceneId>,
+        ),
+        // Batches are rebuilt from unit cubes, so custom meshes are left out.
+        (With<RenderCube>, With<Static>, Without<CustomMesh>),
+    >,
+    batches: Query<(&BakedBatch, &Transform, &Handle<StandardMaterial>)>,
+) {
+    for command in events.read().copied() {
+        match command {
+            BakeCommand::Bake => {
+                let mut by_material: Vec<(StandardMaterial, Vec<Entity>, Vec<SceneEntity>)> =
+                    Vec::new();
+                for entity in &selection.entities {
+                    let Ok((global, transform, rest, handle, id)) = cubes.get(*entity) else {
+                        continue;
+                    };
+                    let Some(material) = materials.get(handle) else {
+                        continue;
+                    };
+                    let snapshot =
+                        snapshot_cube(global, transform, rest, material.base_color, id, true);
+                    match by_material
+                        .iter_mut()
+                        .find(|(shared, ..)| same_material(shared, material))
+                    {
+                        Some((_, entities, snapshots)) => {
+                            entities.push(*entity);
+                            snapshots.push(snapshot);
+                        }
+                        None => by_material.push((material.clone(), vec![*entity], vec![snapshot])),
+                    }
+                }
+
+                let mut baked = Vec::new();
+                for (material, entities, snapshots) in by_material {
+                    // A batch of one saves nothing.
+                    if entities.len() < 2 {
+                        continue;
+                    }
+                    for entity in entities {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                    let material = materials.add(material);
+                    baked.push(spawn_batch(
+                        &mut commands,
+                        &mut meshes,
+                        material,
+                        snapshots,
+                        false,
+                    ));
+                }
+                if baked.is_empty() {
+                    warn!("Bake Static needs two or more selected static cubes sharing a material");
+                    continue;
+                }
+                info!("Baked {} batch(es)", baked.len());
+                selection.entities = baked;
+            }
+            BakeCommand::Unbake => {
+                let mut restored = Vec::new();
+                for entity in &selection.entities {
+                    let Ok((batch, transform, handle)) = batches.get(*entity) else {
+                        continue;
+                    };
+                    let shared = materials.get(handle).cloned();
+                    for cube in batch.placed_cubes(transform) {
+                        let [r, g, b, a] = cube.color;
+                        let color = Color::srgba(r, g, b, a);
+                        let entity = scene::spawn_cube(
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                            cube.transform(),
+                            color,
+                        );
+                        if let (false, Some(shared)) = (batch.vertex_colors, &shared) {
+                            commands
+                                .entity(entity)
+                                .insert(materials.add(shared.clone()));
+                        }
+                        if cube.id != 0 {
+                            commands.entity(entity).insert(SceneId(cube.id));
+                        }
+                        if cube.is_static {
+                            commands.entity(entity).insert(Static);
+                        }
+                        restored.push(entity);
+                    }
+                    commands.entity(*entity).despawn_recursive();
+                }
+                if !restored.is_empty() {
+                    selection.entities = restored;
+                }
+            }
+        }
+    }
+}
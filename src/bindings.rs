@@ -0,0 +1,387 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    expr::{Expr, ExprError},
+    history::{DriveExt, Driven, Driver},
+    icons::{Icon, IconButtonsExt},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    properties::{Properties, PropertyValue},
+    selection::Selection,
+    timeline::AnimationTime,
+    SceneLight,
+};
+
+/// The Bindings window: properties driven every frame by an expression over time and other
+/// values, such as `sin(t*2)*0.5+0.5`.
+pub struct BindingsPlugin;
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<BindingsWindow>().add_systems(
+            Update,
+            (
+                bindings_window_system.in_set(BindingsWindow::ui_set()),
+                evaluate_bindings_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// What a binding writes.
+#[derive(Clone, PartialEq)]
+enum BindingTarget {
+    LightIntensity,
+    LightRange,
+    AmbientBrightness,
+    Translation(usize),
+    Scale,
+    /// A number custom property on the entity, created if missing.
+    Property(String),
+}
+
+impl BindingTarget {
+    fn needs_entity(&self) -> bool {
+        matches!(
+            self,
+            BindingTarget::Translation(_) | BindingTarget::Scale | BindingTarget::Property(_)
+        )
+    }
+
+    fn label(&self) -> String {
+        match self {
+            BindingTarget::LightIntensity => "Light intensity".to_owned(),
+            BindingTarget::LightRange => "Light range".to_owned(),
+            BindingTarget::AmbientBrightness => "Ambient brightness".to_owned(),
+            BindingTarget::Translation(axis) => format!("Translation {}", ["x", "y", "z"][*axis]),
+            BindingTarget::Scale => "Uniform scale".to_owned(),
+            BindingTarget::Property(key) => format!("Property {key:?}"),
+        }
+    }
+}
+
+struct Binding {
+    target: BindingTarget,
+    entity: Option<Entity>,
+    source: String,
+    compiled: Result<Expr, ExprError>,
+    enabled: bool,
+    /// The last value written, or why evaluation failed.
+    last: Option<Result<f64, ExprError>>,
+}
+
+#[derive(Resource)]
+pub struct BindingsWindow {
+    pub is_open: bool,
+    bindings: Vec<Binding>,
+    new_target: BindingTarget,
+    new_property: String,
+}
+
+impl Default for BindingsWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            bindings: Vec::new(),
+            new_target: BindingTarget::LightIntensity,
+            new_property: String::new(),
+        }
+    }
+}
+
+impl Panel for BindingsWindow {
+    const TITLE: &'static str = "Bindings";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn bindings_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<BindingsWindow>,
+    selection: Res<Selection>,
+    names: Query<&Name>,
+) {
+    let BindingsWindow {
+        is_open,
+        bindings,
+        new_target,
+        new_property,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Bindings")
+        .open(is_open)
+        .default_width(420.0)
+        .show(contexts.ctx::<BindingsWindow>(), |ui| {
+            ui.horizontal_wrapped(|ui| {
+                egui::ComboBox::from_id_source("binding_target")
+                    .selected_text(match &*new_target {
+                        BindingTarget::Property(_) => "Property".to_owned(),
+                        target => target.label(),
+                    })
+                    .show_ui(ui, |ui| {
+                        for target in [
+                            BindingTarget::LightIntensity,
+                            BindingTarget::LightRange,
+                            BindingTarget::AmbientBrightness,
+                            BindingTarget::Translation(0),
+                            BindingTarget::Translation(1),
+                            BindingTarget::Translation(2),
+                            BindingTarget::Scale,
+                        ] {
+                            let label = target.label();
+                            ui.selectable_value(new_target, target, label);
+                        }
+                        let is_property = matches!(new_target, BindingTarget::Property(_));
+                        if ui.selectable_label(is_property, "Property").clicked() {
+                            *new_target = BindingTarget::Property(String::new());
+                        }
+                    });
+                if matches!(new_target, BindingTarget::Property(_)) {
+                    ui.add(
+                        egui::TextEdit::singleline(new_property)
+                            .hint_text("key")
+                            .desired_width(80.0),
+                    );
+                }
+                let entity = selection.primary();
+                let target = match &*new_target {
+                    BindingTarget::Property(_) => {
+                        BindingTarget::Property(new_property.trim().to_owned())
+                    }
+                    target => target.clone(),
+                };
+                let ready = (!target.needs_entity() || entity.is_some())
+                    && target != BindingTarget::Property(String::new());
+                let add = ui
+                    .add_enabled(ready, egui::Button::new("Add binding"))
+                    .on_disabled_hover_text("Select the entity to bind first");
+                if add.clicked() {
+                    let source = "sin(t*2)*0.5+0.5".to_owned();
+                    bindings.push(Binding {
+                        entity: entity.filter(|_| target.needs_entity()),
+                        target,
+                        compiled: Expr::parse(&source),
+                        source,
+                        enabled: true,
+                        last: None,
+                    });
+                }
+            });
+            ui.weak(
+                "Variables: t (animation seconds), time (real seconds), light_intensity, \
+                 ambient, and the bound entity's number and bool properties by key.",
+            );
+
+            ui.separator();
+            if bindings.is_empty() {
+                ui.weak("No bindings.");
+                return;
+            }
+            let mut removed = None;
+            egui::Grid::new("bindings_grid")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, binding) in bindings.iter_mut().enumerate() {
+                        ui.checkbox(&mut binding.enabled, "");
+                        let owner = binding.entity.map(|entity| {
+                            names
+                                .get(entity)
+                                .map_or_else(|_| format!("{entity}"), |name| name.to_string())
+                        });
+                        match owner {
+                            Some(owner) => {
+                                ui.label(format!("{owner} · {}", binding.target.label()))
+                            }
+                            None => ui.label(binding.target.label()),
+                        };
+                        ui.vertical(|ui| {
+                            if ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut binding.source)
+                                        .code_editor()
+                                        .desired_width(180.0),
+                                )
+                                .changed()
+                            {
+                                binding.compiled = Expr::parse(&binding.source);
+                            }
+                            let error = match (&binding.compiled, &binding.last) {
+                                (Err(err), _) | (Ok(_), Some(Err(err))) => Some(err),
+                                _ => None,
+                            };
+                            match error {
+                                Some(err) => {
+                                    ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+                                }
+                                None => {
+                                    if let Some(Ok(value)) = binding.last {
+                                        ui.weak(format!("= {value:.3}"));
+                                    }
+                                }
+                            }
+                        });
+                        if ui
+                            .small_icon_button(Icon::Delete, "")
+                            .on_hover_text("Remove binding")
+                            .clicked()
+                        {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = removed {
+                bindings.remove(index);
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn evaluate_bindings_system(
+    mut window: ResMut<BindingsWindow>,
+    time: Res<Time>,
+    animation_time: Res<AnimationTime>,
+    mut lights: Query<&mut PointLight, With<SceneLight>>,
+    mut ambient: ResMut<AmbientLight>,
+    mut transforms: Query<&mut Transform>,
+    mut properties: Query<&mut Properties>,
+    driven: Query<(Entity, &Driven)>,
+    mut commands: Commands,
+) {
+    // Dropped entities take their bindings with them.
+    window.bindings.retain(|binding| {
+        binding
+            .entity
+            .is_none_or(|entity| transforms.contains(entity))
+    });
+
+    // Entities whose transform is bound are left out of the history while it is.
+    let bound: HashSet<Entity> = window
+        .bindings
+        .iter()
+        .filter(|binding| {
+            binding.enabled
+                && binding.compiled.is_ok()
+                && matches!(
+                    binding.target,
+                    BindingTarget::Translation(_) | BindingTarget::Scale
+                )
+        })
+        .filter_map(|binding| binding.entity)
+        .collect();
+    for (entity, driven) in &driven {
+        if driven.by(Driver::Binding) && !bound.contains(&entity) {
+            commands.drive(entity, Driver::Binding, false);
+        }
+    }
+    for &entity in &bound {
+        if !driven
+            .get(entity)
+            .is_ok_and(|(_, driven)| driven.by(Driver::Binding))
+        {
+            commands.drive(entity, Driver::Binding, true);
+        }
+    }
+
+    let light = lights
+        .get_single()
+        .ok()
+        .map(|light| (light.intensity, light.range));
+    for binding in &mut window.bindings {
+        let Binding {
+            target,
+            entity,
+            compiled: Ok(expr),
+            enabled: true,
+            last,
+            ..
+        } = binding
+        else {
+            continue;
+        };
+        let entity_properties = entity.and_then(|entity| properties.get(entity).ok());
+        let variable = |name: &str| match name {
+            "t" => Some(animation_time.seconds as f64),
+            "time" => Some(time.elapsed_seconds_f64()),
+            "light_intensity" => light.map(|(intensity, _)| intensity as f64),
+            "ambient" => Some(ambient.brightness as f64),
+            key => entity_properties?
+                .0
+                .iter()
+                .find(|(existing, _)| existing == key)
+                .and_then(|(_, value)| match value {
+                    PropertyValue::Number(number) => Some(*number),
+                    PropertyValue::Bool(value) => Some(*value as u8 as f64),
+                    _ => None,
+                }),
+        };
+        let result = expr.eval(&variable).and_then(|value| {
+            if value.is_finite() {
+                Ok(value)
+            } else {
+                Err(ExprError("the result is not a finite number".to_owned()))
+            }
+        });
+        if let Ok(value) = result {
+            let value32 = value as f32;
+            match target {
+                BindingTarget::LightIntensity => {
+                    if let Ok(mut light) = lights.get_single_mut() {
+                        light.intensity = value32.max(0.0);
+                    }
+                }
+                BindingTarget::LightRange => {
+                    if let Ok(mut light) = lights.get_single_mut() {
+                        light.range = value32.max(0.0);
+                    }
+                }
+                BindingTarget::AmbientBrightness => ambient.brightness = value32.max(0.0),
+                BindingTarget::Translation(axis) => {
+                    if let Some(mut transform) = entity.and_then(|e| transforms.get_mut(e).ok()) {
+                        transform.translation[*axis] = value32;
+                    }
+                }
+                BindingTarget::Scale => {
+                    if let Some(mut transform) = entity.and_then(|e| transforms.get_mut(e).ok()) {
+                        transform.scale = Vec3::splat(value32);
+                    }
+                }
+                BindingTarget::Property(key) => {
+                    let Some(entity) = *entity else {
+                        continue;
+                    };
+                    match properties.get_mut(entity) {
+                        Ok(mut properties) => {
+                            match properties
+                                .0
+                                .iter_mut()
+                                .find(|(existing, _)| existing == key)
+                            {
+                                Some((_, existing)) => *existing = PropertyValue::Number(value),
+                                None => properties
+                                    .0
+                                    .push((key.clone(), PropertyValue::Number(value))),
+                            }
+                        }
+                        Err(_) => {
+                            commands.entity(entity).insert(Properties(vec![(
+                                key.clone(),
+                                PropertyValue::Number(value),
+                            )]));
+                        }
+                    }
+                }
+            }
+        }
+        *last = Some(result);
+    }
+}
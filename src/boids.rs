@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use bevy::{pbr::NotShadowCaster, prelude::*, render::primitives::Aabb};
+use bevy_egui::egui;
+use rand::Rng;
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    simulation::Interpolated,
+    timeline::AnimationTime,
+    weather::Wind,
+    RenderCube,
+};
+
+/// Largest step integrated at once, whether from a fast timeline or a step while paused;
+/// longer jumps (scrubbing, loop wrap) are skipped rather than simulated.
+const MAX_STEP: f32 = 0.1;
+
+/// The Boids window: a flock steered by separation, alignment and cohesion. It advances once
+/// per fixed tick, scaled by the timeline's speed, so it pauses and steps with the timeline.
+pub struct BoidsPlugin;
+
+impl Plugin for BoidsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<BoidsWindow>()
+            .init_resource::<FlockAssets>()
+            .add_systems(Update, boids_window_system.in_set(BoidsWindow::ui_set()))
+            .add_systems(FixedUpdate, flock_system);
+    }
+}
+
+#[derive(Component)]
+pub struct Boid {
+    velocity: Vec3,
+}
+
+/// Mesh and material shared by every boid.
+#[derive(Resource, Default)]
+struct FlockAssets {
+    handles: Option<(Handle<Mesh>, Handle<StandardMaterial>)>,
+}
+
+#[derive(Resource)]
+pub struct BoidsWindow {
+    pub is_open: bool,
+    count: usize,
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
+    /// How far a boid sees its neighbours.
+    perception: f32,
+    max_speed: f32,
+    /// Half the side of the cube the flock is kept inside, centred above the origin.
+    bounds: f32,
+    avoid_obstacles: bool,
+    /// The timeline position while it is paused, so stepping it steps the flock.
+    paused_at: Option<f32>,
+}
+
+impl Default for BoidsWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            count: 300,
+            separation: 1.5,
+            alignment: 1.0,
+            cohesion: 0.8,
+            perception: 1.5,
+            max_speed: 4.0,
+            bounds: 8.0,
+            avoid_obstacles: true,
+            paused_at: None,
+        }
+    }
+}
+
+impl Panel for BoidsWindow {
+    const TITLE: &'static str = "Boids";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl BoidsWindow {
+    fn center(&self) -> Vec3 {
+        Vec3::Y * self.bounds
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn boids_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<BoidsWindow>,
+    mut commands: Commands,
+    mut flock_assets: ResMut<FlockAssets>,
+    boids: Query<Entity, With<Boid>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    animation_time: Res<AnimationTime>,
+) {
+    if !window.is_open {
+        return;
+    }
+    let center = window.center();
+    let BoidsWindow {
+        is_open,
+        count,
+        separation,
+        alignment,
+        cohesion,
+        perception,
+        max_speed,
+        bounds,
+        avoid_obstacles,
+        ..
+    } = &mut *window;
+
+    egui::Window::new("Boids")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<BoidsWindow>(), |ui| {
+            let existing = boids.iter().count();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Slider::new(count, 10..=3000)
+                        .logarithmic(true)
+                        .text("boids"),
+                );
+                if ui.button("Spawn").clicked() {
+                    let (mesh, material) = flock_assets
+                        .handles
+                        .get_or_insert_with(|| {
+                            (
+                                meshes.add(Cone {
+                                    radius: 0.08,
+                                    height: 0.3,
+                                }),
+                                materials.add(StandardMaterial {
+                                    base_color: Color::srgb(0.95, 0.75, 0.2),
+                                    perceptual_roughness: 0.6,
+                                    ..default()
+                                }),
+                            )
+                        })
+                        .clone();
+                    let mut rng = rand::thread_rng();
+                    for _ in 0..*count {
+                        let offset = Vec3::new(
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                        );
+                        let direction = Vec3::new(
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-0.2..0.2),
+                            rng.gen_range(-1.0..1.0),
+                        )
+                        .normalize_or(Vec3::X);
+                        let transform =
+                            Transform::from_translation(center + offset * *bounds * 0.5);
+                        commands.spawn((
+                            PbrBundle {
+                                mesh: mesh.clone(),
+                                material: material.clone(),
+                                transform,
+                                ..default()
+                            },
+                            NotShadowCaster,
+                            Interpolated::new(transform),
+                            Boid {
+                                velocity: direction * *max_speed * 0.5,
+                            },
+                        ));
+                    }
+                }
+                if ui
+                    .add_enabled(existing > 0, egui::Button::new("Clear"))
+                    .clicked()
+                {
+                    for entity in &boids {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            });
+            ui.weak(format!("{existing} in the scene"));
+
+            ui.separator();
+            egui::Grid::new("boids_rules")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Separation");
+                    ui.add(egui::Slider::new(separation, 0.0..=5.0));
+                    ui.end_row();
+                    ui.label("Alignment");
+                    ui.add(egui::Slider::new(alignment, 0.0..=5.0));
+                    ui.end_row();
+                    ui.label("Cohesion");
+                    ui.add(egui::Slider::new(cohesion, 0.0..=5.0));
+                    ui.end_row();
+                    ui.label("Perception");
+                    ui.add(egui::Slider::new(perception, 0.2..=5.0).suffix(" m"));
+                    ui.end_row();
+                    ui.label("Max speed");
+                    ui.add(egui::Slider::new(max_speed, 0.5..=15.0).suffix(" m/s"));
+                    ui.end_row();
+                    ui.label("Bounds");
+                    ui.add(egui::Slider::new(bounds, 2.0..=30.0).suffix(" m"));
+                    ui.end_row();
+                });
+            ui.checkbox(avoid_obstacles, "Avoid scene cubes");
+
+            ui.separator();
+            ui.weak(if animation_time.playing {
+                "Follows the timeline: pause or step it there."
+            } else {
+                "Paused with the timeline. Step forward to advance the flock."
+            });
+        });
+}
+
+/// Closest point to `point` on the oriented box of a cube's local `aabb`.
+pub fn closest_on_box(point: Vec3, aabb: &Aabb, transform: &GlobalTransform) -> Vec3 {
+    let affine = transform.affine();
+    let local = affine.inverse().transform_point3(point);
+    let clamped = local.clamp(aabb.min().into(), aabb.max().into());
+    affine.transform_point3(clamped)
+}
+
+/// Advances the flock by one fixed tick at the timeline's speed while it plays. While it is
+/// paused, only steps forward along it move the flock, by as much as the step.
+#[allow(clippy::type_complexity)]
+fn flock_system(
+    time: Res<Time<Fixed>>,
+    mut window: ResMut<BoidsWindow>,
+    animation_time: Res<AnimationTime>,
+    wind: Res<Wind>,
+    mut boids: Query<(&mut Interpolated, &mut Boid)>,
+    obstacles: Query<(&GlobalTransform, &Aabb), (With<RenderCube>, Without<Boid>)>,
+) {
+    for (mut interpolated, _) in &mut boids {
+        interpolated.advance();
+    }
+    let seconds = animation_time.seconds;
+    let dt = if animation_time.playing {
+        window.paused_at = None;
+        (time.delta_seconds() * animation_time.speed).min(MAX_STEP)
+    } else {
+        let paused_at = window.paused_at.replace(seconds).unwrap_or(seconds);
+        // Scrubbing backwards or jumping far ahead moves on from there rather than replaying.
+        Some(seconds - paused_at)
+            .filter(|step| *step <= MAX_STEP)
+            .unwrap_or(0.0)
+    };
+    if dt <= 0.0 || boids.is_empty() {
+        return;
+    }
+
+    // Bucket boids into a grid of perception-sized cells, so each only checks its neighbours.
+    let cell_size = window.perception;
+    let cell = |position: Vec3| (position / cell_size).floor().as_ivec3();
+    let snapshot: Vec<(Vec3, Vec3)> = boids
+        .iter()
+        .map(|(interpolated, boid)| (interpolated.current.translation, boid.velocity))
+        .collect();
+    let mut grid: HashMap<IVec3, Vec<usize>> = HashMap::new();
+    for (index, (position, _)) in snapshot.iter().enumerate() {
+        grid.entry(cell(*position)).or_default().push(index);
+    }
+    let obstacles: Vec<_> = if window.avoid_obstacles {
+        obstacles.iter().collect()
+    } else {
+        Vec::new()
+    };
+
+    // Boids steer through the air rather than with it, so the wind only drifts them a little.
+    let drift = wind.at(seconds) * 0.2;
+    let center = window.center();
+    let perception_squared = window.perception * window.perception;
+    let separation_distance = window.perception * 0.4;
+    for (index, (mut interpolated, mut boid)) in boids.iter_mut().enumerate() {
+        let (position, velocity) = snapshot[index];
+        let mut separation = Vec3::ZERO;
+        let mut heading = Vec3::ZERO;
+        let mut centroid = Vec3::ZERO;
+        let mut neighbours = 0;
+        let home = cell(position);
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let Some(bucket) = grid.get(&(home + IVec3::new(x, y, z))) else {
+                        continue;
+                    };
+                    for &other in bucket {
+                        if other == index {
+                            continue;
+                        }
+                        let (other_position, other_velocity) = snapshot[other];
+                        let offset = position - other_position;
+                        let distance_squared = offset.length_squared();
+                        if distance_squared > perception_squared {
+                            continue;
+                        }
+                        let distance = distance_squared.sqrt();
+                        if distance < separation_distance && distance > f32::EPSILON {
+                            separation += offset / (distance * distance);
+                        }
+                        heading += other_velocity;
+                        centroid += other_position;
+                        neighbours += 1;
+                    }
+                }
+            }
+        }
+
+        let mut steer = separation * window.separation;
+        if neighbours > 0 {
+            let neighbours = neighbours as f32;
+            steer += (heading / neighbours - velocity) * window.alignment;
+            steer += (centroid / neighbours - position) * window.cohesion;
+        }
+        // Turn back softly near the walls instead of bouncing off them.
+        let outside = (position - center).abs() - Vec3::splat(window.bounds * 0.8);
+        steer -= (position - center).signum() * outside.max(Vec3::ZERO) * 4.0;
+        for (obstacle, aabb) in &obstacles {
+            let closest = closest_on_box(position, aabb, obstacle);
+            let away = position - closest;
+            let distance = away.length();
+            if distance < window.perception {
+                let direction = if distance > f32::EPSILON {
+                    away / distance
+                } else {
+                    (position - obstacle.translation()).normalize_or(Vec3::Y)
+                };
+                steer += direction * (window.perception - distance) * 8.0;
+            }
+        }
+
+        let velocity =
+            (velocity + steer * dt).clamp_length(window.max_speed * 0.25, window.max_speed);
+        boid.velocity = velocity;
+        let transform = &mut interpolated.current;
+        transform.translation = position + (velocity + drift) * dt;
+        // The cone's tip is +Y.
+        transform.rotation = Quat::from_rotation_arc(Vec3::Y, velocity.normalize_or(Vec3::Y));
+    }
+}
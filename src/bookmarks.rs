@@ -0,0 +1,430 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::CameraWindow,
+    icons::{Icon, IconButtonsExt},
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    scene::Project,
+    settings::Settings,
+    ViewportCamera,
+};
+
+/// Saved viewpoints of the viewport camera. Choosing one flies the camera there with an eased
+/// transition; bookmarks marked for the tour play back one after another.
+pub struct BookmarksPlugin;
+
+impl Plugin for BookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BookmarkCommand>()
+            .register_panel::<BookmarksWindow>()
+            .add_systems(
+                Update,
+                (
+                    bookmarks_window_system.in_set(BookmarksWindow::ui_set()),
+                    camera_transition_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A viewpoint saved with the project.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    /// Vertical field of view in radians; `None` for orthographic views, which keep the zoom.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fov: Option<f32>,
+    /// Played by the tour.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub in_tour: bool,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease in",
+            Easing::EaseOut => "Ease out",
+            Easing::EaseInOut => "Ease in-out",
+        }
+    }
+
+    /// Cubic curves over `t` in `0..=1`.
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+        }
+    }
+}
+
+/// Sent by the window, and by anything else that wants to drive the camera through bookmarks.
+#[derive(Event, Clone, Copy)]
+pub enum BookmarkCommand {
+    /// Fly to the bookmark at this index.
+    FlyTo(usize),
+    PlayTour,
+    StopTour,
+}
+
+/// A camera move in progress towards `to`.
+struct Transition {
+    from: Transform,
+    from_fov: Option<f32>,
+    to: CameraBookmark,
+    elapsed: f32,
+}
+
+/// The tour's position: the index of the bookmark being flown to or held at.
+struct TourState {
+    current: usize,
+    /// Seconds spent at `current` after arriving.
+    held: f32,
+}
+
+#[derive(Resource)]
+pub struct BookmarksWindow {
+    pub is_open: bool,
+    new_name: String,
+    /// Seconds per transition.
+    duration: f32,
+    easing: Easing,
+    /// Seconds the tour rests at each bookmark.
+    hold: f32,
+    looping: bool,
+    transition: Option<Transition>,
+    tour: Option<TourState>,
+}
+
+impl Default for BookmarksWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            new_name: String::new(),
+            duration: 1.5,
+            easing: Easing::default(),
+            hold: 2.0,
+            looping: false,
+            transition: None,
+            tour: None,
+        }
+    }
+}
+
+impl Panel for BookmarksWindow {
+    const TITLE: &'static str = "Camera Bookmarks";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// Indices of the bookmarks the tour visits, in order.
+fn tour_stops(bookmarks: &[CameraBookmark]) -> Vec<usize> {
+    (0..bookmarks.len())
+        .filter(|index| bookmarks[*index].in_tour)
+        .collect()
+}
+
+fn bookmarks_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<BookmarksWindow>,
+    mut project: ResMut<Project>,
+    mut commands: EventWriter<BookmarkCommand>,
+    cameras: Query<(&Transform, &Projection), With<ViewportCamera>>,
+) {
+    let BookmarksWindow {
+        is_open,
+        new_name,
+        duration,
+        easing,
+        hold,
+        looping,
+        transition,
+        tour,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(BookmarksWindow::TITLE)
+        .open(is_open)
+        .default_width(340.0)
+        .show(contexts.ctx::<BookmarksWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(new_name)
+                        .hint_text(format!("View {}", project.camera_bookmarks.len() + 1))
+                        .desired_width(150.0),
+                );
+                if ui.button("Add current view").clicked() {
+                    if let Ok((transform, projection)) = cameras.get_single() {
+                        let name = match new_name.trim() {
+                            "" => format!("View {}", project.camera_bookmarks.len() + 1),
+                            name => name.to_owned(),
+                        };
+                        project.camera_bookmarks.push(CameraBookmark {
+                            name,
+                            translation: transform.translation.to_array(),
+                            rotation: transform.rotation.to_array(),
+                            fov: match projection {
+                                Projection::Perspective(perspective) => Some(perspective.fov),
+                                Projection::Orthographic(_) => None,
+                            },
+                            in_tour: true,
+                        });
+                        new_name.clear();
+                    }
+                }
+            });
+
+            ui.separator();
+            if project.camera_bookmarks.is_empty() {
+                ui.weak("No bookmarks. Frame a view and add it.");
+            } else {
+                let flying_to = transition.as_ref().map(|transition| &transition.to);
+                bookmark_list(ui, &mut project.camera_bookmarks, flying_to, &mut commands);
+            }
+
+            ui.separator();
+            egui::Grid::new("bookmark_transition")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Duration");
+                    ui.add(
+                        drag_value(duration)
+                            .speed(0.05)
+                            .range(0.0..=30.0)
+                            .suffix(" s"),
+                    );
+                    ui.end_row();
+                    ui.label("Easing");
+                    egui::ComboBox::from_id_source("bookmark_easing")
+                        .selected_text(easing.label())
+                        .show_ui(ui, |ui| {
+                            for option in Easing::ALL {
+                                ui.selectable_value(easing, option, option.label());
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Hold");
+                    ui.add(drag_value(hold).speed(0.05).range(0.0..=60.0).suffix(" s"))
+                        .on_hover_text("How long the tour rests at each bookmark");
+                    ui.end_row();
+                });
+
+            let stops = tour_stops(&project.camera_bookmarks).len();
+            ui.horizontal(|ui| {
+                match tour {
+                    Some(_) => {
+                        if ui.icon_button(Icon::Stop, "Stop tour").clicked() {
+                            commands.send(BookmarkCommand::StopTour);
+                        }
+                    }
+                    None => {
+                        if ui
+                            .add_enabled(stops > 0, egui::Button::new("▶ Play tour"))
+                            .on_disabled_hover_text("Tick bookmarks to include them in the tour")
+                            .clicked()
+                        {
+                            commands.send(BookmarkCommand::PlayTour);
+                        }
+                    }
+                }
+                ui.checkbox(looping, "Loop");
+                ui.weak(format!("{stops} stops"));
+            });
+        });
+}
+
+fn bookmark_list(
+    ui: &mut egui::Ui,
+    bookmarks: &mut Vec<CameraBookmark>,
+    flying_to: Option<&CameraBookmark>,
+    commands: &mut EventWriter<BookmarkCommand>,
+) {
+    let mut moved = None;
+    let mut removed = None;
+    let count = bookmarks.len();
+    egui::Grid::new("bookmark_list")
+        .num_columns(4)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, bookmark) in bookmarks.iter_mut().enumerate() {
+                let active = flying_to == Some(&*bookmark);
+                if ui
+                    .selectable_label(active, "🎥")
+                    .on_hover_text("Fly to this view")
+                    .clicked()
+                {
+                    commands.send(BookmarkCommand::FlyTo(index));
+                }
+                ui.add(egui::TextEdit::singleline(&mut bookmark.name).desired_width(140.0));
+                ui.checkbox(&mut bookmark.in_tour, "")
+                    .on_hover_text("Include in the tour");
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("⬆").small())
+                        .clicked()
+                    {
+                        moved = Some((index, index - 1));
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("⬇").small())
+                        .clicked()
+                    {
+                        moved = Some((index, index + 1));
+                    }
+                    if ui
+                        .small_icon_button(Icon::Delete, "")
+                        .on_hover_text("Remove bookmark")
+                        .clicked()
+                    {
+                        removed = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    if let Some((a, b)) = moved {
+        bookmarks.swap(a, b);
+    }
+    if let Some(index) = removed {
+        bookmarks.remove(index);
+    }
+}
+
+fn camera_transition_system(
+    mut commands: EventReader<BookmarkCommand>,
+    mut window: ResMut<BookmarksWindow>,
+    mut camera_window: ResMut<CameraWindow>,
+    project: Res<Project>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<ViewportCamera>>,
+) {
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let bookmarks = &project.camera_bookmarks;
+    let stops = tour_stops(bookmarks);
+    let current_fov = match &*projection {
+        Projection::Perspective(perspective) => Some(perspective.fov),
+        Projection::Orthographic(_) => None,
+    };
+    let start = |from: Transform, index: usize| {
+        bookmarks.get(index).map(|bookmark| Transition {
+            from,
+            from_fov: current_fov,
+            to: bookmark.clone(),
+            elapsed: 0.0,
+        })
+    };
+
+    for command in commands.read() {
+        match *command {
+            BookmarkCommand::FlyTo(index) => {
+                window.tour = None;
+                window.transition = start(*transform, index);
+            }
+            BookmarkCommand::PlayTour => {
+                if let Some(&first) = stops.first() {
+                    window.tour = Some(TourState {
+                        current: first,
+                        held: 0.0,
+                    });
+                    window.transition = start(*transform, first);
+                }
+            }
+            BookmarkCommand::StopTour => {
+                window.tour = None;
+                window.transition = None;
+            }
+        }
+    }
+
+    let delta = time.delta_seconds();
+    let BookmarksWindow {
+        duration,
+        easing,
+        hold,
+        looping,
+        transition,
+        tour,
+        ..
+    } = &mut *window;
+
+    if let Some(active) = transition {
+        active.elapsed += delta;
+        let progress = if settings.accessibility.reduced_motion || *duration <= 0.0 {
+            1.0
+        } else {
+            (active.elapsed / *duration).min(1.0)
+        };
+        let s = easing.apply(progress);
+        let to_translation = Vec3::from_array(active.to.translation);
+        let to_rotation = Quat::from_array(active.to.rotation).normalize();
+        transform.translation = active.from.translation.lerp(to_translation, s);
+        transform.rotation = active.from.rotation.slerp(to_rotation, s);
+        if let Some(to_fov) = active.to.fov {
+            let fov = active
+                .from_fov
+                .map_or(to_fov, |from| from + (to_fov - from) * s);
+            camera_window.set_fov(fov);
+            if let Projection::Perspective(perspective) = &mut *projection {
+                perspective.fov = fov;
+            }
+        }
+        if progress >= 1.0 {
+            *transition = None;
+        }
+        return;
+    }
+
+    let Some(state) = tour else {
+        return;
+    };
+    state.held += delta;
+    if state.held < *hold {
+        return;
+    }
+    let position = stops.iter().position(|index| *index == state.current);
+    let next = match position.map(|position| position + 1) {
+        Some(next) if next < stops.len() => Some(stops[next]),
+        _ if *looping => stops.first().copied(),
+        _ => None,
+    };
+    match next.and_then(|next| start(*transform, next).map(|transition| (next, transition))) {
+        Some((next, next_transition)) => {
+            state.current = next;
+            state.held = 0.0;
+            *transition = Some(next_transition);
+        }
+        None => *tour = None,
+    }
+}
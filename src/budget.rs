@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    batching,
+    pool::ReleaseCubeExt,
+    scene::{CustomMesh, SceneId},
+    settings::Settings,
+    RenderCube, RestRotation, Static,
+};
+
+/// Warns when the scene holds more cubes than the configured budget and offers bulk
+/// cleanup actions to get back under it.
+pub struct BudgetPlugin;
+
+impl Plugin for BudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityBudget>()
+            .add_event::<BudgetCleanup>()
+            .add_systems(
+                Update,
+                (
+                    stamp_spawn_order_system,
+                    budget_banner_system
+                        .in_set(crate::UiSet::Panels)
+                        .after(crate::menu_bar_system),
+                    budget_cleanup_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Spawn sequence number of a cube, so the oldest ones can be removed first.
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SpawnOrder(u64);
+
+#[derive(Default, Resource)]
+struct EntityBudget {
+    next_order: u64,
+    /// Cube count at which the banner was dismissed; it returns once the count grows past it.
+    dismissed_at: Option<usize>,
+}
+
+#[derive(Event, Clone, Copy)]
+enum BudgetCleanup {
+    DeleteOldest,
+    DeleteOffscreen,
+    MergeAll,
+}
+
+fn stamp_spawn_order_system(
+    mut commands: Commands,
+    mut budget: ResMut<EntityBudget>,
+    cubes: Query<Entity, (With<RenderCube>, Without<SpawnOrder>)>,
+) {
+    for entity in &cubes {
+        commands
+            .entity(entity)
+            .insert(SpawnOrder(budget.next_order));
+        budget.next_order += 1;
+    }
+}
+
+fn budget_banner_system(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    mut budget: ResMut<EntityBudget>,
+    cubes: Query<&ViewVisibility, With<RenderCube>>,
+    mut cleanup: EventWriter<BudgetCleanup>,
+) {
+    let limit = settings.spawn.entity_budget as usize;
+    let count = cubes.iter().len();
+    if count <= limit {
+        if budget.dismissed_at.is_some() {
+            budget.dismissed_at = None;
+        }
+        return;
+    }
+    if budget
+        .dismissed_at
+        .is_some_and(|dismissed| count <= dismissed)
+    {
+        return;
+    }
+    let offscreen = cubes.iter().filter(|visibility| !visibility.get()).count();
+
+    egui::TopBottomPanel::top("budget_banner").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("⚠ {count} entities exceed the budget of {limit}."),
+            );
+            if ui
+                .button(format!("Delete oldest {}", count - limit))
+                .clicked()
+            {
+                cleanup.send(BudgetCleanup::DeleteOldest);
+            }
+            if ui
+                .add_enabled(
+                    offscreen > 0,
+                    egui::Button::new(format!("Delete off-screen ({offscreen})")),
+                )
+                .clicked()
+            {
+                cleanup.send(BudgetCleanup::DeleteOffscreen);
+            }
+            if ui
+                .button("Merge into one mesh")
+                .on_hover_text(
+                    "Replace every plain cube with a single static mesh; cubes stop animating. \
+                     Text, shapes and other custom meshes stay as they are.",
+                )
+                .clicked()
+            {
+                cleanup.send(BudgetCleanup::MergeAll);
+            }
+            if ui.button("Dismiss").clicked() {
+                budget.dismissed_at = Some(count);
+            }
+        });
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn budget_cleanup_system(
+    mut events: EventReader<BudgetCleanup>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<Settings>,
+    cubes: Query<(Entity, Option<&SpawnOrder>, &ViewVisibility), With<RenderCube>>,
+    // Merging rebuilds unit cubes from transforms, which would lose any other geometry.
+    mergeable: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Transform,
+            Option<&RestRotation>,
+            &Handle<StandardMaterial>,
+            Option<&SceneId>,
+            Has<Static>,
+        ),
+        (With<RenderCube>, Without<CustomMesh>),
+    >,
+) {
+    for event in events.read() {
+        match event {
+            BudgetCleanup::DeleteOldest => {
+                let excess = cubes
+                    .iter()
+                    .len()
+                    .saturating_sub(settings.spawn.entity_budget as usize);
+                let mut oldest: Vec<_> = cubes
+                    .iter()
+                    .map(|(entity, order, ..)| (order.map_or(u64::MAX, |order| order.0), entity))
+                    .collect();
+                oldest.sort();
+                for (_, entity) in oldest.into_iter().take(excess) {
+                    commands.release_cube(entity);
+                }
+            }
+            BudgetCleanup::DeleteOffscreen => {
+                for (entity, _, visibility, ..) in &cubes {
+                    if !visibility.get() {
+                        commands.release_cube(entity);
+                    }
+                }
+            }
+            BudgetCleanup::MergeAll => {
+                let snapshots: Vec<_> = mergeable
+                    .iter()
+                    .map(
+                        |(entity, global, transform, rest, material, id, is_static)| {
+                            commands.entity(entity).despawn_recursive();
+                            let color = materials
+                                .get(material)
+                                .map_or(Color::WHITE, |material| material.base_color);
+                            batching::snapshot_cube(global, transform, rest, color, id, is_static)
+                        },
+                    )
+                    .collect();
+                if snapshots.is_empty() {
+                    continue;
+                }
+                let material = materials.add(StandardMaterial {
+                    reflectance: 1.0,
+                    ..default()
+                });
+                batching::spawn_batch(&mut commands, &mut meshes, material, snapshots, true);
+            }
+        }
+    }
+}
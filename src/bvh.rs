@@ -0,0 +1,370 @@
+use bevy::{prelude::*, render::primitives::Aabb, utils::HashMap};
+
+/// Items per leaf; small leaves keep the exact per-entity tests few.
+const LEAF_SIZE: usize = 4;
+
+/// A world-space axis-aligned box.
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Bounds {
+    const EMPTY: Self = Self {
+        min: Vec3::INFINITY,
+        max: Vec3::NEG_INFINITY,
+    };
+
+    /// The world-space box around a mesh's local `aabb` placed by `transform`.
+    pub fn from_aabb(aabb: &Aabb, transform: &GlobalTransform) -> Self {
+        let affine = transform.affine();
+        let center = affine.transform_point3a(aabb.center);
+        let matrix = affine.matrix3;
+        let half = aabb.half_extents;
+        let extent = matrix.x_axis.abs() * half.x
+            + matrix.y_axis.abs() * half.y
+            + matrix.z_axis.abs() * half.z;
+        Self {
+            min: (center - extent).into(),
+            max: (center + extent).into(),
+        }
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Entry distance of a ray given by its origin and reciprocal direction, if it hits. A ray
+    /// parallel to an axis that starts exactly on one of the box's planes gives 0 · ∞ for that
+    /// slab, and counts as a miss.
+    fn ray_distance(&self, origin: Vec3, inverse_direction: Vec3) -> Option<f32> {
+        let t1 = (self.min - origin) * inverse_direction;
+        let t2 = (self.max - origin) * inverse_direction;
+        if t1.is_nan() || t2.is_nan() {
+            return None;
+        }
+        let t_near = t1.min(t2).max_element().max(0.0);
+        let t_far = t1.max(t2).min_element();
+        (t_near <= t_far).then_some(t_near)
+    }
+}
+
+enum Node {
+    /// Items `start..end` of [`Bvh::items`].
+    Leaf {
+        bounds: Bounds,
+        start: usize,
+        end: usize,
+    },
+    Branch {
+        bounds: Bounds,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> Bounds {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => *bounds,
+        }
+    }
+}
+
+/// A bounding volume hierarchy over entity boxes. Moving entities only refits the boxes;
+/// adding or removing them needs a rebuild.
+#[derive(Default)]
+pub struct Bvh {
+    items: Vec<(Entity, Bounds)>,
+    slots: HashMap<Entity, usize>,
+    /// Parents always come before their children.
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn build(items: Vec<(Entity, Bounds)>) -> Self {
+        let mut bvh = Self { items, ..default() };
+        if !bvh.items.is_empty() {
+            bvh.split(0, bvh.items.len());
+        }
+        bvh.slots = bvh
+            .items
+            .iter()
+            .enumerate()
+            .map(|(slot, (entity, _))| (*entity, slot))
+            .collect();
+        bvh
+    }
+
+    /// Builds the subtree over `items[start..end]`, returning its node index.
+    fn split(&mut self, start: usize, end: usize) -> usize {
+        let bounds = self.items[start..end]
+            .iter()
+            .fold(Bounds::EMPTY, |bounds, (_, item)| bounds.union(*item));
+        let index = self.nodes.len();
+        if end - start <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf { bounds, start, end });
+            return index;
+        }
+        // Median split along the longest axis of the item centres.
+        let centers = self.items[start..end]
+            .iter()
+            .fold(Bounds::EMPTY, |centers, (_, item)| {
+                let center = item.center();
+                centers.union(Bounds {
+                    min: center,
+                    max: center,
+                })
+            });
+        let size = centers.max - centers.min;
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+        let middle = (start + end) / 2;
+        self.items[start..end].select_nth_unstable_by(middle - start, |(_, a), (_, b)| {
+            a.center()[axis].total_cmp(&b.center()[axis])
+        });
+        self.nodes.push(Node::Leaf { bounds, start, end });
+        let left = self.split(start, middle);
+        let right = self.split(middle, end);
+        self.nodes[index] = Node::Branch {
+            bounds,
+            left,
+            right,
+        };
+        index
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.slots.contains_key(&entity)
+    }
+
+    /// Moves an item's box; call [`Self::refit`] once all moves are done.
+    pub fn set_bounds(&mut self, entity: Entity, bounds: Bounds) {
+        if let Some(&slot) = self.slots.get(&entity) {
+            self.items[slot].1 = bounds;
+        }
+    }
+
+    /// Recomputes every node's box from its items, bottom-up.
+    pub fn refit(&mut self) {
+        for index in (0..self.nodes.len()).rev() {
+            let bounds = match &self.nodes[index] {
+                Node::Leaf { start, end, .. } => self.items[*start..*end]
+                    .iter()
+                    .fold(Bounds::EMPTY, |bounds, (_, item)| bounds.union(*item)),
+                Node::Branch { left, right, .. } => self.nodes[*left]
+                    .bounds()
+                    .union(self.nodes[*right].bounds()),
+            };
+            match &mut self.nodes[index] {
+                Node::Leaf { bounds: b, .. } | Node::Branch { bounds: b, .. } => *b = bounds,
+            }
+        }
+    }
+
+    /// The nearest result of `test` among items whose box `ray` passes through. `test` returns
+    /// the exact distance to the item, if hit, and the value to return for it.
+    pub fn cast<T>(
+        &self,
+        ray: Ray3d,
+        mut test: impl FnMut(Entity) -> Option<(f32, T)>,
+    ) -> Option<T> {
+        let origin = ray.origin;
+        let inverse_direction = ray.direction.recip();
+        let mut best: Option<(f32, T)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let best_distance = best
+                .as_ref()
+                .map_or(f32::INFINITY, |(distance, _)| *distance);
+            match node.bounds().ray_distance(origin, inverse_direction) {
+                Some(distance) if distance <= best_distance => {}
+                _ => continue,
+            }
+            match node {
+                Node::Leaf { start, end, .. } => {
+                    for (entity, bounds) in &self.items[*start..*end] {
+                        let best_distance = best.as_ref().map_or(f32::INFINITY, |(d, _)| *d);
+                        if bounds
+                            .ray_distance(origin, inverse_direction)
+                            .is_none_or(|distance| distance > best_distance)
+                        {
+                            continue;
+                        }
+                        if let Some((distance, value)) = test(*entity) {
+                            if distance < best_distance {
+                                best = Some((distance, value));
+                            }
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    // Visit the nearer child first so the farther one is more often skipped.
+                    let near = |child: usize| {
+                        self.nodes[child]
+                            .bounds()
+                            .ray_distance(origin, inverse_direction)
+                            .unwrap_or(f32::INFINITY)
+                    };
+                    if near(*left) <= near(*right) {
+                        stack.extend([*right, *left]);
+                    } else {
+                        stack.extend([*left, *right]);
+                    }
+                }
+            }
+        }
+        best.map(|(_, value)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    fn unit_box(center: Vec3) -> Bounds {
+        Bounds {
+            min: center - 0.5,
+            max: center + 0.5,
+        }
+    }
+
+    /// Boxes a unit apart along +X, starting at x = 2, never sorted by distance.
+    fn row(count: u32) -> Vec<(Entity, Bounds)> {
+        (0..count)
+            .map(|i| {
+                let slot = (i * 7) % count;
+                let center = Vec3::new(2.0 + 2.0 * slot as f32, 0.0, 0.0);
+                (Entity::from_raw(slot), unit_box(center))
+            })
+            .collect()
+    }
+
+    /// Casts `ray` with the boxes themselves as the exact shapes.
+    fn cast(bvh: &Bvh, ray: Ray3d) -> Option<Entity> {
+        let inverse_direction = ray.direction.recip();
+        bvh.cast(ray, |entity| {
+            let (_, bounds) = bvh.items[bvh.slots[&entity]];
+            let distance = bounds.ray_distance(ray.origin, inverse_direction)?;
+            Some((distance, entity))
+        })
+    }
+
+    #[test]
+    fn empty_tree_hits_nothing() {
+        let bvh = Bvh::build(Vec::new());
+        let hit = bvh.cast(
+            Ray3d::new(Vec3::ZERO, Vec3::X),
+            |entity| -> Option<(f32, ())> { panic!("tested {entity:?} in an empty tree") },
+        );
+        assert!(hit.is_none());
+        assert!(!bvh.contains(Entity::from_raw(0)));
+    }
+
+    #[test]
+    fn nearest_hit_wins() {
+        let bvh = Bvh::build(row(20));
+        assert_eq!(
+            cast(&bvh, Ray3d::new(Vec3::ZERO, Vec3::X)),
+            Some(Entity::from_raw(0))
+        );
+        // From the far end, the last box is the nearest.
+        assert_eq!(
+            cast(&bvh, Ray3d::new(Vec3::new(100.0, 0.0, 0.0), Vec3::NEG_X)),
+            Some(Entity::from_raw(19))
+        );
+        assert_eq!(cast(&bvh, Ray3d::new(Vec3::ZERO, Vec3::NEG_X)), None);
+        assert_eq!(cast(&bvh, Ray3d::new(Vec3::ZERO, Vec3::Y)), None);
+    }
+
+    #[test]
+    fn exact_misses_fall_through_to_the_next_box() {
+        let bvh = Bvh::build(row(20));
+        // The ray passes through the nearest boxes, but misses the shapes inside them.
+        let hit = bvh.cast(Ray3d::new(Vec3::ZERO, Vec3::X), |entity| {
+            let slot = entity.index();
+            (slot >= 3).then_some((1.5 + 2.0 * slot as f32, entity))
+        });
+        assert_eq!(hit, Some(Entity::from_raw(3)));
+    }
+
+    #[test]
+    fn refit_follows_moves() {
+        let mut bvh = Bvh::build(row(20));
+        let ray = Ray3d::new(Vec3::ZERO, Vec3::X);
+
+        // Move the nearest box out of the ray's way: the next one is hit instead.
+        bvh.set_bounds(Entity::from_raw(0), unit_box(Vec3::new(2.0, 10.0, 0.0)));
+        bvh.refit();
+        assert_eq!(cast(&bvh, ray), Some(Entity::from_raw(1)));
+
+        // Move the farthest box to the front, outside every node's old bounds.
+        bvh.set_bounds(Entity::from_raw(19), unit_box(Vec3::new(-3.0, 0.0, 0.0)));
+        bvh.refit();
+        let behind = Ray3d::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::X);
+        assert_eq!(cast(&bvh, behind), Some(Entity::from_raw(19)));
+
+        // Boxes of entities not in the tree are ignored.
+        bvh.set_bounds(Entity::from_raw(99), unit_box(Vec3::ZERO));
+        bvh.refit();
+        assert_eq!(cast(&bvh, ray), Some(Entity::from_raw(1)));
+    }
+
+    #[test]
+    fn axis_parallel_rays() {
+        let bounds = unit_box(Vec3::ZERO);
+        let inverse = |direction: Vec3| direction.recip();
+
+        // Inside the other slabs, an axis-parallel ray hits at the near face.
+        let distance = bounds.ray_distance(Vec3::new(-2.0, 0.25, -0.25), inverse(Vec3::X));
+        assert_eq!(distance, Some(1.5));
+        // Outside them it misses, however far the box extends along the ray.
+        let distance = bounds.ray_distance(Vec3::new(-2.0, 0.75, 0.0), inverse(Vec3::X));
+        assert_eq!(distance, None);
+        // Exactly on a face plane, the slab test is 0 · ∞, which must not count as a hit.
+        for origin in [Vec3::new(-2.0, 0.5, 0.0), Vec3::new(-2.0, -0.5, 0.0)] {
+            assert_eq!(bounds.ray_distance(origin, inverse(Vec3::X)), None);
+        }
+        assert_eq!(
+            bounds.ray_distance(Vec3::new(0.0, -2.0, 0.5), inverse(Vec3::Y)),
+            None
+        );
+        // Starting inside, the hit is at the origin.
+        assert_eq!(bounds.ray_distance(Vec3::ZERO, inverse(Vec3::Z)), Some(0.0));
+    }
+
+    #[test]
+    fn from_aabb_covers_rotated_boxes() {
+        let aabb = Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5));
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(1.0, 2.0, 3.0).with_rotation(Quat::from_rotation_z(FRAC_PI_4)),
+        );
+        let bounds = Bounds::from_aabb(&aabb, &transform);
+        let half = std::f32::consts::SQRT_2 * 0.5;
+        assert!(bounds
+            .min
+            .abs_diff_eq(Vec3::new(1.0 - half, 2.0 - half, 2.5), 1e-5));
+        assert!(bounds
+            .max
+            .abs_diff_eq(Vec3::new(1.0 + half, 2.0 + half, 3.5), 1e-5));
+    }
+}
@@ -0,0 +1,652 @@
+use bevy::{
+    core_pipeline::dof::{DepthOfFieldMode, DepthOfFieldSettings},
+    prelude::*,
+    render::{
+        camera::ScalingMode,
+        primitives::Aabb,
+        view::{InheritedVisibility, RenderLayers},
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    input::{InputOwner, InputRouting},
+    numeric::{format_quantity, unit_slider, Unit},
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    picking::Picking,
+    scene::SceneId,
+    settings::Settings,
+    viewport::{Viewport, ViewportTool},
+    Helper, ViewportCamera,
+};
+
+/// The Camera window: projection, field of view, roll, clipping planes and depth of field of the
+/// viewport camera.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<CameraWindow>()
+            .add_event::<FrameAll>()
+            .add_systems(
+                Update,
+                (
+                    camera_window_system.in_set(CameraWindow::ui_set()),
+                    click_to_focus_system.after(crate::UiSet::Central),
+                    animate_camera_system,
+                    frame_all_system,
+                    clip_planes_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::View, "Frame All", |world| {
+                world.send_event(FrameAll);
+            }));
+    }
+}
+
+/// Vertical field of view of a lens of `focal_length` mm on a full-frame (36×24 mm) sensor.
+fn full_frame_fov(focal_length: f32) -> f32 {
+    2.0 * (12.0 / focal_length).atan()
+}
+
+const LENS_PRESETS: [f32; 3] = [24.0, 50.0, 85.0];
+/// Room left in front of and behind the scene by the automatic clipping planes, as a fraction
+/// of their distances.
+const CLIP_MARGIN: f32 = 0.05;
+/// The automatic near plane is kept at least this fraction of the far plane, so a camera
+/// inside the scene keeps some depth precision.
+const MIN_NEAR_RATIO: f32 = 1e-5;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ProjectionKind {
+    Perspective,
+    Orthographic,
+}
+
+/// Target values edited in the window; the camera eases towards them.
+#[derive(Resource)]
+pub struct CameraWindow {
+    pub is_open: bool,
+    projection: ProjectionKind,
+    /// Vertical field of view, radians.
+    fov: f32,
+    /// Visible height in world units for the orthographic projection.
+    ortho_height: f32,
+    /// Roll around the view axis, radians.
+    roll: f32,
+    /// Roll currently applied to the camera transform, so other systems may still aim it.
+    applied_roll: f32,
+    dof_enabled: bool,
+    dof_mode: DepthOfFieldMode,
+    /// Meters along the view axis.
+    focal_distance: f32,
+    aperture_f_stops: f32,
+    /// Fit the near and far planes to the scene every frame, rather than using `near` and
+    /// `far`.
+    auto_clip: bool,
+    /// Meters along the view axis; negative for an orthographic near plane behind the camera.
+    near: f32,
+    far: f32,
+    /// The planes last applied to the camera, shown while they are automatic.
+    applied_clip: (f32, f32),
+}
+
+impl Default for CameraWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            projection: ProjectionKind::Perspective,
+            fov: PerspectiveProjection::default().fov,
+            ortho_height: 20.0,
+            roll: 0.0,
+            applied_roll: 0.0,
+            dof_enabled: false,
+            // Bokeh is not available on WebGL2.
+            dof_mode: DepthOfFieldMode::Gaussian,
+            focal_distance: 30.0,
+            aperture_f_stops: 2.8,
+            auto_clip: true,
+            near: PerspectiveProjection::default().near,
+            far: PerspectiveProjection::default().far,
+            applied_clip: (
+                PerspectiveProjection::default().near,
+                PerspectiveProjection::default().far,
+            ),
+        }
+    }
+}
+
+impl CameraWindow {
+    /// Targets a perspective projection with `fov`. Callers animating the field of view set the
+    /// projection too, so the window's easing has nothing left to do.
+    pub fn set_fov(&mut self, fov: f32) {
+        self.projection = ProjectionKind::Perspective;
+        self.fov = fov;
+    }
+
+    /// Targets an orthographic projection showing `height` world units vertically.
+    pub fn set_orthographic(&mut self, height: f32) {
+        self.projection = ProjectionKind::Orthographic;
+        self.ortho_height = height.clamp(0.5, 200.0);
+    }
+
+    pub fn ortho_height(&self) -> f32 {
+        self.ortho_height
+    }
+
+    /// Roll in radians currently applied to the camera, for callers that re-aim it.
+    pub fn applied_roll(&self) -> f32 {
+        self.applied_roll
+    }
+}
+
+impl Panel for CameraWindow {
+    const TITLE: &'static str = "Camera";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn camera_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<CameraWindow>,
+    mut tool: ResMut<ViewportTool>,
+) {
+    if !window.is_open {
+        return;
+    }
+
+    let mut is_open = true;
+    // Work on a copy so that merely drawing the window does not count as a change.
+    let mut edited = CameraTargets::from(&*window);
+    egui::Window::new("Camera")
+        .open(&mut is_open)
+        .show(contexts.ctx::<CameraWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(
+                    &mut edited.projection,
+                    ProjectionKind::Perspective,
+                    "Perspective",
+                );
+                ui.selectable_value(
+                    &mut edited.projection,
+                    ProjectionKind::Orthographic,
+                    "Orthographic",
+                );
+            });
+            egui::Grid::new("camera_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    match edited.projection {
+                        ProjectionKind::Perspective => {
+                            ui.label("Field of view");
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut edited.fov,
+                                    5f32.to_radians()..=120f32.to_radians(),
+                                )
+                                .custom_formatter(|radians, _| {
+                                    format!("{:.1}°", radians.to_degrees())
+                                })
+                                .custom_parser(|text| {
+                                    text.trim_end_matches('°')
+                                        .parse::<f64>()
+                                        .ok()
+                                        .map(f64::to_radians)
+                                }),
+                            );
+                            ui.end_row();
+
+                            ui.label("Lens");
+                            ui.horizontal(|ui| {
+                                for focal_length in LENS_PRESETS {
+                                    let fov = full_frame_fov(focal_length);
+                                    let selected = (edited.fov - fov).abs() < 1e-3;
+                                    if ui
+                                        .selectable_label(selected, format!("{focal_length:.0}mm"))
+                                        .clicked()
+                                    {
+                                        edited.fov = fov;
+                                    }
+                                }
+                                let focal_length = 12.0 / (edited.fov / 2.0).tan();
+                                ui.weak(format!("≈ {focal_length:.0}mm"));
+                            });
+                            ui.end_row();
+                        }
+                        ProjectionKind::Orthographic => {
+                            ui.label("View height");
+                            ui.add(
+                                egui::Slider::new(&mut edited.ortho_height, 0.5..=200.0)
+                                    .logarithmic(true)
+                                    .suffix(" m"),
+                            );
+                            ui.end_row();
+                        }
+                    }
+
+                    ui.label("Roll");
+                    ui.add(
+                        egui::Slider::new(
+                            &mut edited.roll,
+                            -std::f32::consts::PI..=std::f32::consts::PI,
+                        )
+                        .custom_formatter(|radians, _| format!("{:.1}°", radians.to_degrees()))
+                        .custom_parser(|text| {
+                            text.trim_end_matches('°')
+                                .parse::<f64>()
+                                .ok()
+                                .map(f64::to_radians)
+                        }),
+                    );
+                    ui.end_row();
+
+                    ui.label("Clipping");
+                    let (near, far) = window.applied_clip;
+                    ui.horizontal(|ui| {
+                        if ui
+                            .checkbox(&mut edited.auto_clip, "Fit to scene")
+                            .on_hover_text("Place the near and far planes around the scene")
+                            .changed()
+                            && !edited.auto_clip
+                        {
+                            // Start from the planes in use, so the view does not jump.
+                            (edited.near, edited.far) = (near, far);
+                        }
+                        if edited.auto_clip {
+                            ui.weak(format!(
+                                "{} – {}",
+                                format_quantity(near as f64, 3, Some(Unit::Meters)),
+                                format_quantity(far as f64, 1, Some(Unit::Meters))
+                            ));
+                        }
+                    });
+                    ui.end_row();
+                    if !edited.auto_clip {
+                        ui.label("Near");
+                        ui.add(
+                            unit_slider(&mut edited.near, 0.001..=100.0, Unit::Meters)
+                                .logarithmic(true),
+                        );
+                        ui.end_row();
+
+                        ui.label("Far");
+                        ui.add(
+                            unit_slider(&mut edited.far, 1.0..=100_000.0, Unit::Meters)
+                                .logarithmic(true),
+                        );
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+            ui.checkbox(&mut edited.dof_enabled, "Depth of field");
+            ui.add_enabled_ui(edited.dof_enabled, |ui| {
+                egui::Grid::new("dof_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Focal distance");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::Slider::new(&mut edited.focal_distance, 0.1..=200.0)
+                                .logarithmic(true)
+                                .suffix(" m"),
+                        );
+                        let focusing = *tool == ViewportTool::Focus;
+                        if ui
+                            .selectable_label(focusing, "Click to focus")
+                            .on_hover_text("Click a surface in the viewport to focus on it")
+                            .clicked()
+                        {
+                            *tool = if focusing {
+                                ViewportTool::Select
+                            } else {
+                                ViewportTool::Focus
+                            };
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Aperture");
+                    ui.add(
+                        egui::Slider::new(&mut edited.aperture_f_stops, 0.7..=32.0)
+                            .logarithmic(true)
+                            .prefix("f/"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Mode");
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(
+                            &mut edited.dof_mode,
+                            DepthOfFieldMode::Gaussian,
+                            "Gaussian",
+                        );
+                        ui.selectable_value(&mut edited.dof_mode, DepthOfFieldMode::Bokeh, "Bokeh")
+                            .on_hover_text("Not supported on WebGL2");
+                    });
+                    ui.end_row();
+                });
+            });
+
+            if ui.button("Reset").clicked() {
+                let defaults = CameraWindow::default();
+                edited = CameraTargets::from(&defaults);
+            }
+        });
+
+    if edited != CameraTargets::from(&*window) {
+        window.projection = edited.projection;
+        window.fov = edited.fov;
+        window.ortho_height = edited.ortho_height;
+        window.roll = edited.roll;
+        window.dof_enabled = edited.dof_enabled;
+        window.dof_mode = edited.dof_mode;
+        window.focal_distance = edited.focal_distance;
+        window.aperture_f_stops = edited.aperture_f_stops;
+        window.auto_clip = edited.auto_clip;
+        window.near = edited.near;
+        window.far = edited.far;
+    }
+    if !is_open {
+        window.is_open = false;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct CameraTargets {
+    projection: ProjectionKind,
+    fov: f32,
+    ortho_height: f32,
+    roll: f32,
+    dof_enabled: bool,
+    dof_mode: DepthOfFieldMode,
+    focal_distance: f32,
+    aperture_f_stops: f32,
+    auto_clip: bool,
+    near: f32,
+    far: f32,
+}
+
+impl From<&CameraWindow> for CameraTargets {
+    fn from(window: &CameraWindow) -> Self {
+        Self {
+            projection: window.projection,
+            fov: window.fov,
+            ortho_height: window.ortho_height,
+            roll: window.roll,
+            dof_enabled: window.dof_enabled,
+            dof_mode: window.dof_mode,
+            focal_distance: window.focal_distance,
+            aperture_f_stops: window.aperture_f_stops,
+            auto_clip: window.auto_clip,
+            near: window.near,
+            far: window.far,
+        }
+    }
+}
+
+/// Moves `transform` along its view axis so the sphere around the box `min..max` fills the
+/// view, keeping the orientation.
+pub fn frame_bounds(transform: &mut Transform, projection: &Projection, min: Vec3, max: Vec3) {
+    let center = (min + max) * 0.5;
+    let radius = ((max - min).length() * 0.5).max(0.1);
+    let distance = match projection {
+        Projection::Perspective(perspective) => {
+            let horizontal =
+                2.0 * ((perspective.fov * 0.5).tan() * perspective.aspect_ratio).atan();
+            radius / (perspective.fov.min(horizontal) * 0.5).sin()
+        }
+        // Zoom stays with the Camera window; only the position changes.
+        Projection::Orthographic(_) => transform.translation.distance(center).max(radius * 2.0),
+    };
+    transform.translation = center - *transform.forward() * distance;
+}
+
+/// The corners of `aabb` moved into world space, as a min and max. Rotated boxes come out
+/// somewhat loose, which is fine for framing.
+pub fn world_bounds(transform: &GlobalTransform, aabb: &Aabb) -> (Vec3, Vec3) {
+    let corners = [aabb.min(), aabb.max()].map(|corner| transform.transform_point(corner.into()));
+    (corners[0].min(corners[1]), corners[0].max(corners[1]))
+}
+
+/// Moves the viewport camera back until every scene entity is in view.
+#[derive(Event)]
+struct FrameAll;
+
+fn frame_all_system(
+    mut events: EventReader<FrameAll>,
+    bounds: Query<(&GlobalTransform, &Aabb), With<SceneId>>,
+    mut cameras: Query<(&mut Transform, &Projection), With<ViewportCamera>>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some((min, max)) = bounds
+        .iter()
+        .map(|(transform, aabb)| world_bounds(transform, aabb))
+        .reduce(|(a0, a1), (b0, b1)| (a0.min(b0), a1.max(b1)))
+    else {
+        return;
+    };
+    if let Ok((mut transform, projection)) = cameras.get_single_mut() {
+        frame_bounds(&mut transform, projection, min, max);
+    }
+}
+
+/// Eases `current` towards `target`, returning `None` once close enough to leave it alone.
+fn ease(current: f32, target: f32, t: f32) -> Option<f32> {
+    if (target - current).abs() < 1e-4 {
+        (current != target).then_some(target)
+    } else {
+        Some(current + (target - current) * t)
+    }
+}
+
+/// With `ViewportTool::Focus`, a click sets the focal distance to the depth of the surface hit.
+fn click_to_focus_system(
+    viewport: Res<Viewport>,
+    mut tool: ResMut<ViewportTool>,
+    routing: Res<InputRouting>,
+    mut window: ResMut<CameraWindow>,
+    picking: Picking,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    if *tool != ViewportTool::Focus || !viewport.clicked || routing.pointer != InputOwner::Tool {
+        return;
+    }
+    *tool = ViewportTool::Select;
+    let (Some(hit), Ok(camera)) = (picking.pick_pointer(&[]), cameras.get_single()) else {
+        return;
+    };
+    let depth = (hit.point - camera.translation()).dot(*camera.forward());
+    window.focal_distance = depth.max(0.1);
+    window.dof_enabled = true;
+}
+
+fn animate_camera_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut window: ResMut<CameraWindow>,
+    settings: Res<Settings>,
+    mut cameras: Query<
+        (
+            Entity,
+            &mut Projection,
+            &mut Transform,
+            Option<&mut DepthOfFieldSettings>,
+        ),
+        With<ViewportCamera>,
+    >,
+) {
+    let Ok((camera, mut projection, mut transform, dof)) = cameras.get_single_mut() else {
+        return;
+    };
+    let t = if settings.accessibility.reduced_motion {
+        1.0
+    } else {
+        1.0 - (-12.0 * time.delta_seconds()).exp()
+    };
+
+    match (&*projection, window.projection) {
+        (Projection::Perspective(perspective), ProjectionKind::Perspective) => {
+            if let Some(fov) = ease(perspective.fov, window.fov, t) {
+                if let Projection::Perspective(perspective) = &mut *projection {
+                    perspective.fov = fov;
+                }
+            }
+        }
+        (Projection::Orthographic(orthographic), ProjectionKind::Orthographic) => {
+            if let Some(scale) = ease(orthographic.scale, window.ortho_height, t) {
+                if let Projection::Orthographic(orthographic) = &mut *projection {
+                    orthographic.scale = scale;
+                }
+            }
+        }
+        (_, ProjectionKind::Perspective) => {
+            *projection = Projection::Perspective(PerspectiveProjection {
+                fov: window.fov,
+                ..default()
+            });
+        }
+        (_, ProjectionKind::Orthographic) => {
+            *projection = Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical(1.0),
+                scale: window.ortho_height,
+                ..default()
+            });
+        }
+    }
+
+    if let Some(roll) = ease(window.applied_roll, window.roll, t) {
+        transform.rotate_local_z(roll - window.applied_roll);
+        window.bypass_change_detection().applied_roll = roll;
+    }
+
+    match (dof, window.dof_enabled) {
+        (Some(mut dof), true) => {
+            if let Some(focal_distance) = ease(dof.focal_distance, window.focal_distance, t) {
+                dof.focal_distance = focal_distance;
+            }
+            if dof.aperture_f_stops != window.aperture_f_stops {
+                dof.aperture_f_stops = window.aperture_f_stops;
+            }
+            if dof.mode != window.dof_mode {
+                dof.mode = window.dof_mode;
+            }
+        }
+        (Some(_), false) => {
+            commands.entity(camera).remove::<DepthOfFieldSettings>();
+        }
+        (None, true) => {
+            commands.entity(camera).insert(DepthOfFieldSettings {
+                mode: window.dof_mode,
+                focal_distance: window.focal_distance,
+                aperture_f_stops: window.aperture_f_stops,
+                // Full-frame, to match the lens presets.
+                sensor_height: 0.024,
+                ..default()
+            });
+        }
+        (None, false) => {}
+    }
+}
+
+/// Sets the viewport camera's near and far planes, fitted around the bounds of everything the
+/// camera shows along the view axis when automatic, so large scenes are not cut off and small
+/// ones keep their depth precision. Applied after the projection is eased, which may replace it.
+///
+/// Frustum culling follows the planes, so entities count by their hierarchy visibility rather
+/// than by whether they were last drawn.
+#[allow(clippy::type_complexity)]
+fn clip_planes_system(
+    mut window: ResMut<CameraWindow>,
+    bounds: Query<
+        (
+            &GlobalTransform,
+            &Aabb,
+            &InheritedVisibility,
+            Option<&RenderLayers>,
+        ),
+        (Without<Helper>, Without<ViewportCamera>),
+    >,
+    mut cameras: Query<
+        (&mut Projection, &GlobalTransform, Option<&RenderLayers>),
+        With<ViewportCamera>,
+    >,
+) {
+    let Ok((mut projection, camera_transform, camera_layers)) = cameras.get_single_mut() else {
+        return;
+    };
+    let orthographic = matches!(*projection, Projection::Orthographic(_));
+    let (current_near, current_far) = match &*projection {
+        Projection::Perspective(perspective) => (perspective.near, perspective.far),
+        Projection::Orthographic(orthographic) => (orthographic.near, orthographic.far),
+    };
+    let (near, far) = if window.auto_clip {
+        let eye = camera_transform.translation();
+        let forward = camera_transform.forward();
+        let camera_layers = camera_layers.cloned().unwrap_or_default();
+        let shown = bounds.iter().filter(|(_, _, visibility, layers)| {
+            visibility.get() && camera_layers.intersects(&layers.cloned().unwrap_or_default())
+        });
+        let depths = shown.flat_map(|(transform, aabb, ..)| {
+            let (min, max) = world_bounds(transform, aabb);
+            (0..8).map(move |corner| {
+                let point = Vec3::select(
+                    BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                    max,
+                    min,
+                );
+                (point - eye).dot(*forward)
+            })
+        });
+        let Some((nearest, farthest)) =
+            depths.fold(None, |range: Option<(f32, f32)>, depth| match range {
+                Some((low, high)) => Some((low.min(depth), high.max(depth))),
+                None => Some((depth, depth)),
+            })
+        else {
+            return;
+        };
+        let margin = (farthest - nearest).max(1.0) * CLIP_MARGIN;
+        let far = (farthest + margin).max(1.0);
+        let near = if orthographic {
+            nearest - margin
+        } else {
+            (nearest - margin).max(far * MIN_NEAR_RATIO)
+        };
+        // Planes within half the margin of the fit still leave room around the scene, so they
+        // stay until the scene or camera moves further, rather than following every frame. A
+        // near plane pushed out by the precision limit has no room to spare.
+        let slack = margin * 0.5;
+        let furthest_near = (near + slack).min(nearest - slack).max(near);
+        let settled_near = (near - slack..=furthest_near).contains(&current_near);
+        let settled_far = (current_far - far).abs() <= slack;
+        if settled_near && settled_far {
+            (current_near, current_far)
+        } else {
+            (near, far)
+        }
+    } else {
+        (window.near, window.far.max(window.near * 2.0))
+    };
+
+    // Within a fraction of a percent is close enough not to touch the projection every frame.
+    let close = |a: f32, b: f32| (a - b).abs() <= b.abs().max(1e-3) * 1e-3;
+    if !close(current_near, near) || !close(current_far, far) {
+        match &mut *projection {
+            Projection::Perspective(perspective) => {
+                perspective.near = near;
+                perspective.far = far;
+            }
+            Projection::Orthographic(orthographic) => {
+                orthographic.near = near;
+                orthographic.far = far;
+            }
+        }
+    }
+    if window.applied_clip != (near, far) {
+        window.bypass_change_detection().applied_clip = (near, far);
+    }
+}
@@ -0,0 +1,367 @@
+use std::{borrow::Cow, sync::Mutex};
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::{egui, EguiUserTextures};
+
+use crate::{
+    decal::{paint_strokes, ProjectPainting},
+    errors::AppError,
+    icons::{Icon, IconButtonsExt},
+    keybindings::Action,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests, ReadbackSource},
+    selection::Selection,
+    status_bar::StatusBar,
+    ViewImage,
+};
+
+const THUMBNAIL: f32 = 96.0;
+
+/// Image exchange with the OS clipboard. The painting canvas or the viewport can be copied,
+/// ready to paste into a chat or a document without saving a file first; images pasted in
+/// are listed in the Pasted Images window, for use as the canvas background or as the
+/// texture of the selected cubes.
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OsClipboard>()
+            .init_resource::<CanvasBackground>()
+            .register_panel::<PastedImagesWindow>()
+            .add_event::<CopyCanvas>()
+            .add_event::<CopyViewport>()
+            .add_event::<PasteImage>()
+            .add_systems(
+                Update,
+                (
+                    copy_canvas_system,
+                    copy_viewport_system,
+                    write_clipboard_system,
+                    paste_image_system,
+                    pasted_images_window_system.in_set(PastedImagesWindow::ui_set()),
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Copy Viewport", |world| {
+                    world.send_event(CopyViewport);
+                })
+                .icon(Icon::Copy)
+                .shortcut(Action::CopyViewport),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Paste Image", |world| {
+                    world.send_event(PasteImage);
+                })
+                .icon(Icon::Paste)
+                .shortcut(Action::PasteImage),
+            );
+    }
+}
+
+/// An image drawn under the painting canvas strokes, stretched to fill it, and into copies
+/// of the canvas.
+#[derive(Default, Resource)]
+pub struct CanvasBackground(pub Option<Handle<Image>>);
+
+/// Copies the strokes at the canvas's on-screen resolution.
+#[derive(Event)]
+pub struct CopyCanvas {
+    pub painting: ProjectPainting,
+    /// The canvas fill, so the copy looks as it does on screen.
+    pub background: [u8; 4],
+    pub pixels_per_point: f32,
+}
+
+#[derive(Event)]
+pub struct CopyViewport;
+
+#[derive(Event)]
+pub struct PasteImage;
+
+/// The OS clipboard, opened on first use. On X11 and Wayland the copied data is served from
+/// this process, so the handle is kept for as long as the app runs.
+#[derive(Default, Resource)]
+struct OsClipboard {
+    clipboard: Mutex<Option<arboard::Clipboard>>,
+    /// An image waiting to be written, with what it shows for the status bar.
+    pending: Option<(UVec2, Vec<u8>, &'static str)>,
+}
+
+impl OsClipboard {
+    fn with<T>(
+        &self,
+        action: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+    ) -> Result<T, AppError> {
+        let mut handle = self
+            .clipboard
+            .lock()
+            .map_err(|_| AppError::new("Clipboard", "The clipboard is unavailable"))?;
+        let clipboard = match &mut *handle {
+            Some(clipboard) => clipboard,
+            slot @ None => slot.insert(arboard::Clipboard::new().map_err(|err| {
+                AppError::new("Clipboard", format!("Cannot open the clipboard: {err}"))
+                    .suggest("On Linux, run the sandbox inside an X11 or Wayland session.")
+            })?),
+        };
+        action(clipboard).map_err(|err| AppError::new("Clipboard", err.to_string()))
+    }
+}
+
+fn copy_canvas_system(
+    mut events: EventReader<CopyCanvas>,
+    mut clipboard: ResMut<OsClipboard>,
+    background: Res<CanvasBackground>,
+    images: Res<Assets<Image>>,
+) {
+    let Some(copy) = events.read().last() else {
+        return;
+    };
+    let size = (copy.painting.canvas_size * copy.pixels_per_point)
+        .round()
+        .as_uvec2();
+    if size.min_element() == 0 {
+        return;
+    }
+    let mut pixels = copy.background.repeat((size.x * size.y) as usize);
+    if let Some(image) = background.0.as_ref().and_then(|handle| images.get(handle)) {
+        stretch_onto(image, size, &mut pixels);
+    }
+    paint_strokes(&copy.painting, size, &mut pixels, 0);
+    clipboard.pending = Some((size, pixels, "canvas"));
+}
+
+/// Copies an 8-bit sRGB `image` over RGBA8 `pixels` of `size`, stretched to cover them as the
+/// canvas shows it. Other formats are left out.
+fn stretch_onto(image: &Image, size: UVec2, pixels: &mut [u8]) {
+    let source = image.size();
+    if image.texture_descriptor.format != TextureFormat::Rgba8UnormSrgb
+        || image.data.len() != (source.x * source.y * 4) as usize
+        || source.min_element() == 0
+    {
+        return;
+    }
+    for y in 0..size.y {
+        let row = (y * source.y / size.y) * source.x;
+        for x in 0..size.x {
+            let from = ((row + x * source.x / size.x) * 4) as usize;
+            let to = ((y * size.x + x) * 4) as usize;
+            pixels[to..to + 4].copy_from_slice(&image.data[from..from + 4]);
+        }
+    }
+}
+
+fn copy_viewport_system(
+    mut events: EventReader<CopyViewport>,
+    mut requests: ResMut<ReadbackRequests>,
+    view_image: Res<ViewImage>,
+    // Set while waiting for the readback.
+    mut pending: Local<bool>,
+    mut readbacks: EventReader<ReadbackComplete>,
+    mut clipboard: ResMut<OsClipboard>,
+) {
+    if events.read().count() > 0 {
+        requests.request(&view_image);
+        *pending = true;
+    }
+    if !*pending {
+        readbacks.clear();
+        return;
+    }
+    let id = view_image.id();
+    if let Some(readback) = readbacks
+        .read()
+        .filter(|readback| {
+            readback.image == id
+                && readback.region.is_none()
+                && readback.source == ReadbackSource::Output
+        })
+        .last()
+    {
+        *pending = false;
+        clipboard.pending = Some((readback.size, readback.data.clone(), "viewport"));
+    }
+}
+
+fn write_clipboard_system(
+    mut clipboard: ResMut<OsClipboard>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    time: Res<Time>,
+) {
+    let Some((size, pixels, what)) = clipboard.pending.take() else {
+        return;
+    };
+    let image = arboard::ImageData {
+        width: size.x as usize,
+        height: size.y as usize,
+        bytes: Cow::Owned(pixels),
+    };
+    match clipboard.with(|clipboard| clipboard.set_image(image)) {
+        Ok(()) => status.flash(
+            format!("Copied the {what} to the clipboard"),
+            time.elapsed_seconds(),
+        ),
+        Err(err) => {
+            errors.send(err);
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct PastedImagesWindow {
+    pub is_open: bool,
+    images: Vec<Handle<Image>>,
+}
+
+impl Panel for PastedImagesWindow {
+    const TITLE: &'static str = "Pasted Images";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn paste_image_system(
+    mut events: EventReader<PasteImage>,
+    clipboard: Res<OsClipboard>,
+    mut window: ResMut<PastedImagesWindow>,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    time: Res<Time>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let pasted = match clipboard.with(|clipboard| clipboard.get_image()) {
+        Ok(pasted) => pasted,
+        Err(err) => {
+            errors.send(err.suggest("Copy an image in another application first."));
+            return;
+        }
+    };
+    let size = UVec2::new(pasted.width as u32, pasted.height as u32);
+    // Kept on the CPU as well, so the Image Ops window can process it.
+    let image = Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pasted.bytes.into_owned(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let handle = images.add(image);
+    user_textures.add_image(handle.clone());
+    window.images.push(handle);
+    window.is_open = true;
+    status.flash(
+        format!("Pasted a {}×{} image", size.x, size.y),
+        time.elapsed_seconds(),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pasted_images_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<PastedImagesWindow>,
+    mut background: ResMut<CanvasBackground>,
+    images: Res<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    selection: Res<Selection>,
+    cubes: Query<&Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let PastedImagesWindow {
+        is_open,
+        images: pasted,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut removed = None;
+    let mut textured = None;
+    egui::Window::new(PastedImagesWindow::TITLE)
+        .open(is_open)
+        .default_width(320.0)
+        .show(contexts.ctx::<PastedImagesWindow>(), |ui| {
+            if pasted.is_empty() {
+                ui.weak("No images yet. Copy an image elsewhere and press Ctrl+V here.");
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(420.0)
+                .show(ui, |ui| {
+                    for (index, handle) in pasted.iter().enumerate() {
+                        let size = images.get(handle).map_or(UVec2::ONE, Image::size);
+                        ui.horizontal(|ui| {
+                            if let Some(texture) = user_textures.image_id(handle) {
+                                ui.add(
+                                    egui::Image::new(egui::load::SizedTexture::new(
+                                        texture,
+                                        [size.x as f32, size.y as f32],
+                                    ))
+                                    .max_size(egui::Vec2::splat(THUMBNAIL)),
+                                );
+                            }
+                            ui.vertical(|ui| {
+                                ui.label(format!("{}×{}", size.x, size.y));
+                                let is_background = background.0.as_ref() == Some(handle);
+                                let mut toggled = is_background;
+                                if ui
+                                    .toggle_value(&mut toggled, "Canvas background")
+                                    .on_hover_text("Draw the image under the painting strokes")
+                                    .changed()
+                                {
+                                    background.0 = toggled.then(|| handle.clone());
+                                }
+                                if ui
+                                    .add_enabled(
+                                        !selection.entities.is_empty(),
+                                        egui::Button::new("Texture the selection"),
+                                    )
+                                    .on_hover_text(
+                                        "Use as the base colour texture of the selected cubes",
+                                    )
+                                    .on_disabled_hover_text("Select cubes in the viewport first")
+                                    .clicked()
+                                {
+                                    textured = Some(handle.clone());
+                                }
+                                if ui.small_icon_button(Icon::Delete, "Remove").clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                        });
+                        ui.separator();
+                    }
+                });
+        });
+
+    if let Some(texture) = textured {
+        for material in cubes.iter_many(&selection.entities) {
+            if let Some(material) = materials.get_mut(material) {
+                material.base_color_texture = Some(texture.clone());
+            }
+        }
+    }
+    if let Some(index) = removed {
+        let handle = pasted.remove(index);
+        user_textures.remove_image(&handle);
+        if background.0.as_ref() == Some(&handle) {
+            background.0 = None;
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use bevy::{
+    pbr::{
+        ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+        OpaqueRendererMethod, RenderMaterialInstances,
+    },
+    prelude::*,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        primitives::Aabb,
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+        Render, RenderApp, RenderSet,
+    },
+    utils::HashMap,
+};
+use bevy_egui::egui;
+
+use crate::{
+    camera::world_bounds,
+    numeric::{unit_drag_value, Unit},
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+    RenderCube,
+};
+
+const MAX_PLANES: usize = 3;
+const PLANE_COLORS: [Color; MAX_PLANES] = [
+    Color::srgb(1.0, 0.55, 0.1),
+    Color::srgb(0.2, 0.8, 1.0),
+    Color::srgb(0.95, 0.3, 0.85),
+];
+
+const CLIP_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x7c41_d3a9_0b6e_4e28_95f7_1a2c_e83d_5b60);
+
+const CLIP_WGSL: &str = r#"#import bevy_pbr::{
+    forward_io::{FragmentOutput, VertexOutput},
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+
+// Normal and distance from the origin; points beyond a plane are cut away.
+@group(2) @binding(100) var<uniform> clip_planes: array<vec4<f32>, 3>;
+// Alpha zero leaves the inside surfaces lit as they are.
+@group(2) @binding(101) var<uniform> cap_color: vec4<f32>;
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    for (var i = 0; i < 3; i += 1) {
+        let plane = clip_planes[i];
+        if dot(plane.xyz, in.world_position.xyz) > plane.w {
+            discard;
+        }
+    }
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color =
+        alpha_discard(pbr_input.material, pbr_input.material.base_color);
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    // Through a cut only the back faces of a closed mesh show, so they fill it in.
+    if !is_front {
+        out.color = vec4<f32>(mix(out.color.rgb, cap_color.rgb, cap_color.a), out.color.a);
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
+"#;
+
+/// A scene material with the clipping planes applied.
+type ClipMaterial = ExtendedMaterial<StandardMaterial, ClipExtension>;
+
+/// Up to three clipping planes that cut the scene away in the viewport, to look inside
+/// models. Each plane is an entity that the transform gizmo moves and turns; while any plane
+/// is on, scene entities render with a copy of their material that discards what lies beyond
+/// the planes and fills the cuts with the cap colour. Shadows are still cast by the whole
+/// model, and clipped entities skip the depth prepass.
+pub struct ClippingPlugin;
+
+impl Plugin for ClippingPlugin {
+    fn build(&self, app: &mut App) {
+        app.world_mut().resource_mut::<Assets<Shader>>().insert(
+            CLIP_SHADER.id(),
+            Shader::from_wgsl(CLIP_WGSL, "clipping.wgsl"),
+        );
+        app.add_plugins(MaterialPlugin::<ClipMaterial> {
+            prepass_enabled: false,
+            ..default()
+        })
+        .init_resource::<ClipMaterials>()
+        .register_panel::<ClippingWindow>()
+        .add_systems(
+            Update,
+            (
+                clipping_window_system.in_set(ClippingWindow::ui_set()),
+                clip_materials_system,
+                draw_clip_planes_system,
+            )
+                .chain(),
+        )
+        .add_menu_item(MenuItem::new(Menu::View, "Clipping Planes…", |world| {
+            world.resource_mut::<ClippingWindow>().is_open = true;
+        }));
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(
+                Render,
+                hide_unclipped_materials_system.in_set(RenderSet::PrepareAssets),
+            );
+        }
+    }
+}
+
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+struct ClipExtension {
+    #[uniform(100)]
+    planes: [Vec4; MAX_PLANES],
+    #[uniform(101)]
+    cap_color: Vec4,
+}
+
+impl MaterialExtension for ClipExtension {
+    fn fragment_shader() -> ShaderRef {
+        CLIP_SHADER.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // The back faces seen through a cut are the caps.
+        descriptor.primitive.cull_mode = None;
+        Ok(())
+    }
+}
+
+/// A clipping plane through its translation, facing along its local Z axis; what lies in
+/// front of it is cut away.
+#[derive(Component)]
+struct ClipPlane {
+    enabled: bool,
+}
+
+#[derive(Resource)]
+pub struct ClippingWindow {
+    pub is_open: bool,
+    planes: Vec<Entity>,
+    caps: bool,
+    /// sRGB.
+    cap_color: [f32; 3],
+}
+
+impl Default for ClippingWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            planes: Vec::new(),
+            caps: true,
+            cap_color: [0.85, 0.2, 0.15],
+        }
+    }
+}
+
+impl Panel for ClippingWindow {
+    const TITLE: &'static str = "Clipping Planes";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// The clipped copy of each scene material, kept while any plane is on.
+#[derive(Default, Resource)]
+struct ClipMaterials {
+    copies: HashMap<AssetId<StandardMaterial>, Handle<ClipMaterial>>,
+    /// What the copies' uniforms were last set to.
+    uniforms: Option<ClipExtension>,
+}
+
+/// The centre and size of the scene, to place and draw planes by.
+fn scene_extent(scene: &Query<(&GlobalTransform, &Aabb), With<RenderCube>>) -> (Vec3, f32) {
+    let bounds = scene
+        .iter()
+        .map(|(transform, aabb)| world_bounds(transform, aabb))
+        .reduce(|(min, max), (low, high)| (min.min(low), max.max(high)));
+    match bounds {
+        Some((min, max)) => ((min + max) * 0.5, (max - min).max_element().max(1.0)),
+        None => (Vec3::ZERO, 4.0),
+    }
+}
+
+fn axis_rotation(axis: Vec3) -> Quat {
+    Quat::from_rotation_arc(Vec3::Z, axis)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn clipping_window_system(
+    mut commands: Commands,
+    mut contexts: PanelContexts,
+    mut window: ResMut<ClippingWindow>,
+    mut planes: Query<(&mut ClipPlane, &mut Transform)>,
+    mut selection: ResMut<Selection>,
+    scene: Query<(&GlobalTransform, &Aabb), With<RenderCube>>,
+) {
+    // Planes deleted along with a selection are gone for good.
+    window.planes.retain(|plane| planes.contains(*plane));
+    let ClippingWindow {
+        is_open,
+        planes: plane_entities,
+        caps,
+        cap_color,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut remove = None;
+    let mut add = false;
+    egui::Window::new(ClippingWindow::TITLE)
+        .open(is_open)
+        .default_width(320.0)
+        .show(contexts.ctx::<ClippingWindow>(), |ui| {
+            for (index, entity) in plane_entities.iter().enumerate() {
+                let Ok((mut plane, mut transform)) = planes.get_mut(*entity) else {
+                    continue;
+                };
+                let [r, g, b, _] = PLANE_COLORS[index].to_srgba().to_u8_array();
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut plane.enabled, "");
+                    ui.colored_label(
+                        egui::Color32::from_rgb(r, g, b),
+                        format!("Plane {}", index + 1),
+                    );
+                    for (label, axis) in [("X", Vec3::X), ("Y", Vec3::Y), ("Z", Vec3::Z)] {
+                        if ui
+                            .small_button(label)
+                            .on_hover_text(format!("Face along {label}, cutting away above it"))
+                            .clicked()
+                        {
+                            transform.rotation = axis_rotation(axis);
+                        }
+                    }
+                    if ui
+                        .small_button("Flip")
+                        .on_hover_text("Cut away the other side")
+                        .clicked()
+                    {
+                        transform.rotation =
+                            Quat::from_rotation_x(std::f32::consts::PI) * transform.rotation;
+                        transform.rotation = transform.rotation.normalize();
+                    }
+                    if ui
+                        .small_button("Move")
+                        .on_hover_text("Select the plane, to drag it with the transform gizmo")
+                        .clicked()
+                    {
+                        selection.select(*entity);
+                    }
+                    if ui.small_button("✕").clicked() {
+                        remove = Some(*entity);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add_space(24.0);
+                    ui.label("Offset");
+                    let normal = transform.rotation * Vec3::Z;
+                    let mut offset = normal.dot(transform.translation);
+                    if ui
+                        .add(unit_drag_value(&mut offset, Unit::Meters).speed(0.02))
+                        .on_hover_text("Distance of the plane from the origin, along its normal")
+                        .changed()
+                    {
+                        let along = normal.dot(transform.translation);
+                        transform.translation += normal * (offset - along);
+                    }
+                });
+            }
+            if plane_entities.is_empty() {
+                ui.weak("No planes yet.");
+            }
+            add = ui
+                .add_enabled(
+                    plane_entities.len() < MAX_PLANES,
+                    egui::Button::new("Add Plane"),
+                )
+                .on_disabled_hover_text(format!("At most {MAX_PLANES} planes"))
+                .clicked();
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.checkbox(caps, "Fill cuts")
+                    .on_hover_text("Colour the inside of closed meshes where they are cut");
+                ui.add_enabled_ui(*caps, |ui| ui.color_edit_button_rgb(cap_color));
+            });
+            ui.weak("Cuts apply to scene entities; shadows still fall from the whole model.");
+        });
+
+    if let Some(entity) = remove {
+        commands.entity(entity).despawn();
+        plane_entities.retain(|plane| *plane != entity);
+    }
+    if add {
+        // Each new plane faces along the next axis, through the middle of the scene.
+        let (center, _) = scene_extent(&scene);
+        let axis = [Vec3::X, Vec3::Y, Vec3::Z][plane_entities.len() % MAX_PLANES];
+        let entity = commands
+            .spawn((
+                Name::new(format!("Clip Plane {}", plane_entities.len() + 1)),
+                ClipPlane { enabled: true },
+                SpatialBundle::from_transform(
+                    Transform::from_translation(center).with_rotation(axis_rotation(axis)),
+                ),
+            ))
+            .id();
+        plane_entities.push(entity);
+    }
+}
+
+/// Gives scene entities clipped copies of their materials while a plane is on, and keeps the
+/// copies in step with the originals and the planes.
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn clip_materials_system(
+    mut commands: Commands,
+    window: Res<ClippingWindow>,
+    mut clip: ResMut<ClipMaterials>,
+    planes: Query<(&ClipPlane, &GlobalTransform)>,
+    entities: Query<
+        (
+            Entity,
+            &Handle<StandardMaterial>,
+            Option<&Handle<ClipMaterial>>,
+        ),
+        With<RenderCube>,
+    >,
+    clipped: Query<Entity, With<Handle<ClipMaterial>>>,
+    standard: Res<Assets<StandardMaterial>>,
+    mut clip_materials: ResMut<Assets<ClipMaterial>>,
+    mut events: EventReader<AssetEvent<StandardMaterial>>,
+) {
+    let mut uniforms = ClipExtension {
+        // Never true: 0 is not beyond 1.
+        planes: [Vec4::new(0.0, 0.0, 0.0, 1.0); MAX_PLANES],
+        cap_color: Vec4::ZERO,
+    };
+    let mut active = false;
+    for (slot, (_, transform)) in planes
+        .iter_many(&window.planes)
+        .filter(|(plane, _)| plane.enabled)
+        .enumerate()
+    {
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        let normal = rotation * Vec3::Z;
+        uniforms.planes[slot] = normal.extend(normal.dot(transform.translation()));
+        active = true;
+    }
+    if window.caps {
+        let [r, g, b] = window.cap_color;
+        uniforms.cap_color = Color::srgb(r, g, b).to_linear().to_vec4();
+    }
+
+    if !active {
+        for entity in &clipped {
+            commands.entity(entity).remove::<Handle<ClipMaterial>>();
+        }
+        events.clear();
+        *clip = ClipMaterials::default();
+        return;
+    }
+
+    // Forward rendered, as the clipped copies skip the prepass the deferred renderer needs.
+    let copy = |material: &StandardMaterial, uniforms: &ClipExtension| ClipMaterial {
+        base: StandardMaterial {
+            opaque_render_method: OpaqueRendererMethod::Forward,
+            ..material.clone()
+        },
+        extension: uniforms.clone(),
+    };
+    let ClipMaterials {
+        copies,
+        uniforms: current,
+    } = &mut *clip;
+    for event in events.read() {
+        if let AssetEvent::Modified { id } = event {
+            if let (Some(handle), Some(material)) = (copies.get(id), standard.get(*id)) {
+                clip_materials.insert(handle, copy(material, &uniforms));
+            }
+        }
+    }
+    if current
+        .as_ref()
+        .map(|current| (current.planes, current.cap_color))
+        != Some((uniforms.planes, uniforms.cap_color))
+    {
+        for handle in copies.values() {
+            if let Some(material) = clip_materials.get_mut(handle) {
+                material.extension = uniforms.clone();
+            }
+        }
+        *current = Some(uniforms.clone());
+    }
+    for (entity, material, clipped) in &entities {
+        let Some(original) = standard.get(material) else {
+            continue;
+        };
+        let handle = copies
+            .entry(material.id())
+            .or_insert_with(|| clip_materials.add(copy(original, &uniforms)));
+        if clipped != Some(handle) {
+            commands.entity(entity).insert(handle.clone());
+        }
+    }
+}
+
+/// Leaves clipped entities to their clipped material alone, so they are not drawn twice.
+fn hide_unclipped_materials_system(
+    clipped: Res<RenderMaterialInstances<ClipMaterial>>,
+    mut standard: ResMut<RenderMaterialInstances<StandardMaterial>>,
+) {
+    for entity in clipped.keys() {
+        standard.remove(entity);
+    }
+}
+
+fn draw_clip_planes_system(
+    mut gizmos: Gizmos,
+    window: Res<ClippingWindow>,
+    planes: Query<(&ClipPlane, &GlobalTransform)>,
+    scene: Query<(&GlobalTransform, &Aabb), With<RenderCube>>,
+) {
+    if window.planes.is_empty() {
+        return;
+    }
+    let (_, size) = scene_extent(&scene);
+    for (index, (plane, transform)) in planes.iter_many(&window.planes).enumerate() {
+        let color = if plane.enabled {
+            PLANE_COLORS[index]
+        } else {
+            PLANE_COLORS[index].with_alpha(0.3)
+        };
+        let (_, rotation, center) = transform.to_scale_rotation_translation();
+        gizmos.rect(center, rotation, Vec2::splat(size * 1.2), color);
+        gizmos.rect(
+            center,
+            rotation,
+            Vec2::splat(size * 0.6),
+            color.with_alpha(0.4),
+        );
+        gizmos.arrow(center, center + rotation * Vec3::Z * size * 0.25, color);
+    }
+}
@@ -0,0 +1,480 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        view::NoFrustumCulling,
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    input::{InputOwner, InputRouting},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    picking::{ray_mesh, Picking},
+    settings::{egui_color, Settings},
+    viewport::{Viewport, ViewportTool},
+    weather::{wind_ui, Wind},
+};
+
+/// Constraint relaxation passes per substep; more is stiffer.
+const ITERATIONS: usize = 8;
+const SUBSTEPS: usize = 4;
+const GRAVITY: Vec3 = Vec3::new(0.0, -9.81, 0.0);
+/// Fraction of velocity kept per substep.
+const DAMPING: f32 = 0.995;
+/// Longest tick simulated, so a very low tick rate does not explode the cloth.
+const MAX_DELTA: f32 = 1.0 / 30.0;
+
+/// The Cloth window: mass-spring sheets simulated on the CPU on each fixed tick.
+pub struct ClothPlugin;
+
+impl Plugin for ClothPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ClothWindow>()
+            .add_systems(
+                Update,
+                (
+                    cloth_window_system.in_set(ClothWindow::ui_set()),
+                    toggle_pin_system,
+                    write_cloth_meshes_system,
+                    draw_pins_system,
+                )
+                    .chain()
+                    .after(crate::UiSet::Central),
+            )
+            .add_systems(FixedUpdate, simulate_cloth_system);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ClothPreset {
+    /// Hangs vertically from its left edge, for wind.
+    Flag,
+    /// Lies flat and falls from its corners.
+    Drape,
+}
+
+impl ClothPreset {
+    fn label(self) -> &'static str {
+        match self {
+            ClothPreset::Flag => "Flag",
+            ClothPreset::Drape => "Drape",
+        }
+    }
+}
+
+/// A grid of particles joined by distance constraints, integrated with Verlet.
+#[derive(Component)]
+pub struct Cloth {
+    columns: usize,
+    rows: usize,
+    positions: Vec<Vec3>,
+    previous: Vec<Vec3>,
+    rest: Vec<Vec3>,
+    pinned: Vec<bool>,
+    /// Particle pairs and the distance between them at rest.
+    springs: Vec<(usize, usize, f32)>,
+}
+
+impl Cloth {
+    fn new(preset: ClothPreset, resolution: usize, size: f32, origin: Vec3) -> Self {
+        let (columns, rows) = (resolution + 1, resolution + 1);
+        let spacing = size / resolution as f32;
+        let mut rest = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let (u, v) = (column as f32 * spacing, row as f32 * spacing);
+                rest.push(match preset {
+                    ClothPreset::Flag => origin + Vec3::new(u, -v, 0.0),
+                    ClothPreset::Drape => origin + Vec3::new(u - size * 0.5, 0.0, v - size * 0.5),
+                });
+            }
+        }
+        let index = |column: usize, row: usize| row * columns + column;
+        let pinned = (0..columns * rows)
+            .map(|i| {
+                let (column, row) = (i % columns, i / columns);
+                match preset {
+                    ClothPreset::Flag => column == 0,
+                    ClothPreset::Drape => {
+                        (column == 0 || column == columns - 1) && (row == 0 || row == rows - 1)
+                    }
+                }
+            })
+            .collect();
+
+        let mut springs = Vec::new();
+        let mut connect = |a: usize, b: usize| springs.push((a, b, rest[a].distance(rest[b])));
+        for row in 0..rows {
+            for column in 0..columns {
+                // Structural, shear and bend springs.
+                let here = index(column, row);
+                if column + 1 < columns {
+                    connect(here, index(column + 1, row));
+                }
+                if row + 1 < rows {
+                    connect(here, index(column, row + 1));
+                }
+                if column + 1 < columns && row + 1 < rows {
+                    connect(here, index(column + 1, row + 1));
+                    connect(index(column + 1, row), index(column, row + 1));
+                }
+                if column + 2 < columns {
+                    connect(here, index(column + 2, row));
+                }
+                if row + 2 < rows {
+                    connect(here, index(column, row + 2));
+                }
+            }
+        }
+
+        Self {
+            columns,
+            rows,
+            positions: rest.clone(),
+            previous: rest.clone(),
+            rest,
+            pinned,
+            springs,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.positions.clone_from(&self.rest);
+        self.previous.clone_from(&self.rest);
+    }
+
+    fn step(&mut self, dt: f32, wind: Vec3) {
+        let normals = self.normals();
+        let particles = self.positions.iter_mut().zip(&mut self.previous);
+        for ((position, previous), (normal, pinned)) in
+            particles.zip(normals.iter().zip(&self.pinned))
+        {
+            if *pinned {
+                continue;
+            }
+            // Wind pushes along the surface normal, so a sheet edge-on to it barely moves.
+            let force = GRAVITY + *normal * normal.dot(wind);
+            let velocity = (*position - *previous) * DAMPING;
+            *previous = *position;
+            *position += velocity + force * dt * dt;
+        }
+        for _ in 0..ITERATIONS {
+            for &(a, b, length) in &self.springs {
+                let delta = self.positions[b] - self.positions[a];
+                let distance = delta.length();
+                if distance <= f32::EPSILON {
+                    continue;
+                }
+                let correction = delta * ((distance - length) / distance);
+                match (self.pinned[a], self.pinned[b]) {
+                    (true, true) => {}
+                    (true, false) => self.positions[b] -= correction,
+                    (false, true) => self.positions[a] += correction,
+                    (false, false) => {
+                        self.positions[a] += correction * 0.5;
+                        self.positions[b] -= correction * 0.5;
+                    }
+                }
+            }
+        }
+    }
+
+    fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        let columns = self.columns;
+        (0..self.rows - 1).flat_map(move |row| {
+            (0..columns - 1).flat_map(move |column| {
+                let here = row * columns + column;
+                [
+                    [here, here + columns, here + 1],
+                    [here + 1, here + columns, here + columns + 1],
+                ]
+            })
+        })
+    }
+
+    fn normals(&self) -> Vec<Vec3> {
+        let mut normals = vec![Vec3::ZERO; self.positions.len()];
+        for [a, b, c] in self.triangles() {
+            let [pa, pb, pc] = [a, b, c].map(|i| self.positions[i]);
+            // Area-weighted, since the cross product isn't normalised.
+            let normal = (pb - pa).cross(pc - pa);
+            for i in [a, b, c] {
+                normals[i] += normal;
+            }
+        }
+        normals
+            .into_iter()
+            .map(|normal| normal.normalize_or(Vec3::Z))
+            .collect()
+    }
+
+    fn mesh(&self) -> Mesh {
+        let uvs: Vec<[f32; 2]> = (0..self.positions.len())
+            .map(|i| {
+                let (column, row) = (i % self.columns, i / self.columns);
+                [
+                    column as f32 / (self.columns - 1) as f32,
+                    row as f32 / (self.rows - 1) as f32,
+                ]
+            })
+            .collect();
+        let indices = self.triangles().flatten().map(|i| i as u32).collect();
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices));
+        self.write_mesh(&mut mesh);
+        mesh
+    }
+
+    fn write_mesh(&self, mesh: &mut Mesh) {
+        let positions: Vec<[f32; 3]> = self.positions.iter().map(|p| p.to_array()).collect();
+        let normals: Vec<[f32; 3]> = self.normals().iter().map(|n| n.to_array()).collect();
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            VertexAttributeValues::Float32x3(positions),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            VertexAttributeValues::Float32x3(normals),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct ClothWindow {
+    pub is_open: bool,
+    preset: ClothPreset,
+    resolution: usize,
+    size: f32,
+    paused: bool,
+}
+
+impl Default for ClothWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            preset: ClothPreset::Flag,
+            resolution: 20,
+            size: 2.0,
+            paused: false,
+        }
+    }
+}
+
+impl Panel for ClothWindow {
+    const TITLE: &'static str = "Cloth";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cloth_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ClothWindow>,
+    mut wind: ResMut<Wind>,
+    mut tool: ResMut<ViewportTool>,
+    mut commands: Commands,
+    mut cloths: Query<(Entity, &mut Cloth)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let ClothWindow {
+        is_open,
+        preset,
+        resolution,
+        size,
+        paused,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Cloth")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<ClothWindow>(), |ui| {
+            egui::Grid::new("cloth_spawn")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Preset");
+                    ui.horizontal(|ui| {
+                        for option in [ClothPreset::Flag, ClothPreset::Drape] {
+                            ui.selectable_value(preset, option, option.label());
+                        }
+                    });
+                    ui.end_row();
+                    ui.label("Resolution");
+                    ui.add(egui::Slider::new(resolution, 4..=48));
+                    ui.end_row();
+                    ui.label("Size");
+                    ui.add(egui::Slider::new(size, 0.5..=6.0).suffix(" m"));
+                    ui.end_row();
+                });
+            if ui.button(format!("Spawn {}", preset.label())).clicked() {
+                let origin = match preset {
+                    ClothPreset::Flag => Vec3::new(-*size * 0.5, *size + 0.5, 0.0),
+                    ClothPreset::Drape => Vec3::new(0.0, *size + 0.5, 0.0),
+                };
+                let cloth = Cloth::new(*preset, *resolution, *size, origin);
+                commands.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(cloth.mesh()),
+                        material: materials.add(StandardMaterial {
+                            base_color: Color::srgb(0.75, 0.2, 0.25),
+                            perceptual_roughness: 0.9,
+                            double_sided: true,
+                            cull_mode: None,
+                            ..default()
+                        }),
+                        ..default()
+                    },
+                    // The bounds computed at spawn go stale as soon as the cloth moves.
+                    NoFrustumCulling,
+                    Name::new(format!("Cloth ({})", preset.label())),
+                    cloth,
+                ));
+            }
+
+            ui.separator();
+            // The same wind as the Environment window's.
+            wind_ui(ui, &mut wind);
+
+            ui.separator();
+            let count = cloths.iter().count();
+            ui.add_enabled_ui(count > 0, |ui| {
+                ui.horizontal(|ui| {
+                    let pinning = *tool == ViewportTool::PinCloth;
+                    if ui
+                        .selectable_label(pinning, "📌 Edit pins")
+                        .on_hover_text("Click the cloth in the viewport to pin or release a point")
+                        .clicked()
+                    {
+                        *tool = if pinning {
+                            ViewportTool::Select
+                        } else {
+                            ViewportTool::PinCloth
+                        };
+                    }
+                    ui.toggle_value(paused, "⏸ Pause");
+                    if ui.button("Reset").clicked() {
+                        for (_, mut cloth) in &mut cloths {
+                            cloth.reset();
+                        }
+                    }
+                    if ui.button("Remove all").clicked() {
+                        for (entity, _) in &cloths {
+                            commands.entity(entity).despawn_recursive();
+                        }
+                    }
+                });
+            });
+            ui.weak(match count {
+                0 => "No cloth in the scene.".to_owned(),
+                1 => "1 cloth".to_owned(),
+                count => format!("{count} cloths"),
+            });
+        });
+}
+
+/// With `ViewportTool::PinCloth`, a click pins or releases the cloth particle nearest the hit.
+fn toggle_pin_system(
+    viewport: Res<Viewport>,
+    tool: Res<ViewportTool>,
+    routing: Res<InputRouting>,
+    picking: Picking,
+    mut cloths: Query<(&mut Cloth, &Handle<Mesh>, &GlobalTransform)>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    if *tool != ViewportTool::PinCloth || !viewport.clicked || routing.pointer != InputOwner::Tool {
+        return;
+    }
+    let Some(ray) = picking.pointer_ray() else {
+        return;
+    };
+    let Some((mut cloth, distance)) = cloths
+        .iter_mut()
+        .filter_map(|(cloth, mesh, transform)| {
+            let distance = ray_mesh(ray, meshes.get(mesh)?, transform)?;
+            Some((cloth, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+    else {
+        return;
+    };
+    let hit = ray.get_point(distance);
+    let nearest = (0..cloth.positions.len())
+        .min_by(|&a, &b| {
+            let a = cloth.positions[a].distance_squared(hit);
+            let b = cloth.positions[b].distance_squared(hit);
+            a.total_cmp(&b)
+        })
+        .unwrap_or_default();
+    let pinned = &mut cloth.pinned[nearest];
+    *pinned = !*pinned;
+    // Pinned particles hold still wherever they were grabbed.
+    cloth.previous[nearest] = cloth.positions[nearest];
+}
+
+fn simulate_cloth_system(
+    time: Res<Time>,
+    window: Res<ClothWindow>,
+    wind: Res<Wind>,
+    mut cloths: Query<&mut Cloth>,
+) {
+    if window.paused {
+        return;
+    }
+    let dt = time.delta_seconds().min(MAX_DELTA) / SUBSTEPS as f32;
+    if dt <= 0.0 {
+        return;
+    }
+    let wind = wind.at(time.elapsed_seconds());
+    for mut cloth in &mut cloths {
+        for _ in 0..SUBSTEPS {
+            cloth.step(dt, wind);
+        }
+    }
+}
+
+/// Uploads cloth that was stepped or re-pinned, once per frame however many ticks ran.
+fn write_cloth_meshes_system(
+    cloths: Query<(&Cloth, &Handle<Mesh>), Changed<Cloth>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (cloth, handle) in &cloths {
+        if let Some(mesh) = meshes.get_mut(handle) {
+            cloth.write_mesh(mesh);
+        }
+    }
+}
+
+fn draw_pins_system(
+    window: Res<ClothWindow>,
+    tool: Res<ViewportTool>,
+    cloths: Query<&Cloth>,
+    settings: Res<Settings>,
+    mut gizmos: Gizmos,
+) {
+    if !window.is_open && *tool != ViewportTool::PinCloth {
+        return;
+    }
+    let color = egui_color(settings.highlights().primary);
+    let color = Color::srgb_u8(color.r(), color.g(), color.b());
+    for cloth in &cloths {
+        for (position, _) in cloth
+            .positions
+            .iter()
+            .zip(&cloth.pinned)
+            .filter(|(_, pinned)| **pinned)
+        {
+            gizmos.sphere(*position, Quat::IDENTITY, 0.03, color);
+        }
+    }
+}
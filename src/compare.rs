@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    selection::{material_edit, Selection},
+    RenderCube, RestRotation, ViewportCamera,
+};
+
+/// A/B comparison of two material variants on duplicated geometry.
+pub struct ComparePlugin;
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<CompareWindow>()
+            .add_systems(Update, (compare_window_system, sync_compare_system).chain());
+    }
+}
+
+/// An active comparison: `a` is the original entity, `b` its clone with the variant material.
+struct CompareSession {
+    a: Entity,
+    b: Entity,
+    /// Where `a` stood before the comparison started; both sit symmetrically around it.
+    anchor: Vec3,
+    /// Camera-right direction captured at start, so the pair stays side by side on screen.
+    side: Vec3,
+    spacing: f32,
+    swapped: bool,
+}
+
+#[derive(Default, Resource)]
+pub struct CompareWindow {
+    pub is_open: bool,
+    session: Option<CompareSession>,
+}
+
+impl Panel for CompareWindow {
+    const TITLE: &'static str = "A/B Compare";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn compare_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<CompareWindow>,
+    mut commands: Commands,
+    selection: Res<Selection>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cubes: Query<
+        (
+            &mut Transform,
+            &Handle<Mesh>,
+            &Handle<StandardMaterial>,
+            Option<&RestRotation>,
+        ),
+        Without<ViewportCamera>,
+    >,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    let CompareWindow { is_open, session } = &mut *window;
+    let mut stop = !*is_open && session.is_some();
+    if !*is_open && !stop {
+        return;
+    }
+
+    egui::Window::new("A/B Compare")
+        .open(is_open)
+        .show(contexts.ctx::<CompareWindow>(), |ui| {
+            let Some(current) = session else {
+                let Some(entity) = selection.primary() else {
+                    ui.weak("Select an entity to compare material variants on it.");
+                    return;
+                };
+                if ui.button("Start comparison").clicked() {
+                    let Ok((transform, mesh, material, rest)) = cubes.get(entity) else {
+                        return;
+                    };
+                    let variant = materials.get(material).cloned().unwrap_or_default();
+                    let side = cameras
+                        .get_single()
+                        .map_or(Vec3::X, |camera| *camera.right());
+                    let mut b = commands.spawn((
+                        PbrBundle {
+                            mesh: mesh.clone(),
+                            material: materials.add(variant),
+                            transform: *transform,
+                            ..default()
+                        },
+                        RenderCube,
+                    ));
+                    if let Some(rest) = rest {
+                        b.insert(RestRotation(**rest));
+                    }
+                    *session = Some(CompareSession {
+                        a: entity,
+                        b: b.id(),
+                        anchor: transform.translation,
+                        side,
+                        spacing: transform.scale.max_element() * 1.5,
+                        swapped: false,
+                    });
+                }
+                return;
+            };
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut current.swapped, "Swap left/right");
+                ui.add(
+                    drag_value(&mut current.spacing)
+                        .speed(0.05)
+                        .range(0.0..=100.0)
+                        .prefix("spacing "),
+                );
+            });
+            ui.separator();
+
+            let handles = [current.a, current.b].map(|entity| {
+                cubes
+                    .get(entity)
+                    .ok()
+                    .map(|(_, _, material, _)| material.clone())
+            });
+            let [Some(a), Some(b)] = handles else {
+                stop = true;
+                return;
+            };
+            ui.columns(2, |columns| {
+                for ((ui, handle), label) in columns.iter_mut().zip([&a, &b]).zip(["A", "B"]) {
+                    ui.strong(label);
+                    if let Some(mut edited) = materials.get(handle).cloned() {
+                        if material_edit(ui, &mut edited) {
+                            materials.insert(handle, edited);
+                        }
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Copy A → B").clicked() {
+                    if let Some(material) = materials.get(&a).cloned() {
+                        materials.insert(&b, material);
+                    }
+                }
+                if ui.button("Copy B → A").clicked() {
+                    if let Some(material) = materials.get(&b).cloned() {
+                        materials.insert(&a, material);
+                    }
+                }
+                if ui.button("End comparison").clicked() {
+                    stop = true;
+                }
+            });
+        });
+
+    if stop {
+        if let Some(session) = session.take() {
+            if let Ok((mut transform, ..)) = cubes.get_mut(session.a) {
+                transform.translation = session.anchor;
+            }
+            if let Some(b) = commands.get_entity(session.b) {
+                b.despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Keeps B mirroring A's layout and animation so the two are judged under identical conditions.
+fn sync_compare_system(
+    window: Res<CompareWindow>,
+    mut transforms: Query<&mut Transform>,
+    mut rest_rotations: Query<&mut RestRotation>,
+) {
+    let Some(session) = &window.session else {
+        return;
+    };
+    let offset = session.side * session.spacing * 0.5 * if session.swapped { -1.0 } else { 1.0 };
+    let Ok(scale) = transforms.get(session.a).map(|transform| transform.scale) else {
+        return;
+    };
+    for (entity, position) in [
+        (session.a, session.anchor - offset),
+        (session.b, session.anchor + offset),
+    ] {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.translation = position;
+            transform.scale = scale;
+        }
+    }
+    if let Ok(rest) = rest_rotations.get(session.a).map(|rest| rest.0) {
+        if let Ok(mut b) = rest_rotations.get_mut(session.b) {
+            b.0 = rest;
+        }
+    }
+}
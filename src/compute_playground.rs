@@ -0,0 +1,455 @@
+use std::{
+    num::NonZeroU64,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        graph::CameraDriverLabel,
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_resource::{
+            binding_types::{texture_storage_2d, uniform_buffer_sized},
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, Buffer,
+            BufferInitDescriptor, BufferUsages, CachedComputePipelineId, CachedPipelineState,
+            ComputePassDescriptor, ComputePipelineDescriptor, Extent3d, PipelineCache,
+            ShaderStages, StorageTextureAccess, TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::GpuImage,
+        Render, RenderApp, RenderSet,
+    },
+};
+use bevy_egui::{egui, EguiUserTextures};
+use xihydra_bevy::widgets::{CodeEditor, Language};
+
+use crate::icons::{Icon, IconButtonsExt};
+use crate::numeric::drag_value;
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+
+const SHADER_PATH: &str = "compute_playground.wgsl";
+/// Side of the square storage texture the shader writes.
+const SIZE: u32 = 256;
+/// Seconds without typing before an edited shader is recompiled.
+const RELOAD_DELAY: f32 = 0.4;
+
+const EXAMPLE: &str = r#"struct Params {
+    size: vec2<u32>,
+    time: f32,
+    values: vec4<f32>,
+}
+
+@group(0) @binding(0) var output: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(1) var<uniform> params: Params;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.size.x || id.y >= params.size.y {
+        return;
+    }
+    let uv = vec2<f32>(id.xy) / vec2<f32>(params.size);
+    let rings = sin(length(uv - 0.5) * 40.0 * params.values.x - params.time * 4.0);
+    let color = vec3<f32>(uv, params.values.y) * (0.5 + 0.5 * rings);
+    textureStore(output, vec2<i32>(id.xy), vec4<f32>(color, 1.0));
+}
+"#;
+
+/// A WGSL compute shader editor. The shader writes a storage texture shown in the panel
+/// and is recompiled shortly after every edit.
+///
+/// The entry point is `main`; binding 0 is the `rgba8unorm` output texture and binding 1 a
+/// uniform with the texture size, the elapsed time and four slider values.
+pub struct ComputePlaygroundPlugin;
+
+impl Plugin for ComputePlaygroundPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        app.register_panel::<ComputePlaygroundWindow>()
+            .insert_resource(PipelineStatusReceiver(Mutex::new(receiver)))
+            .add_plugins(ExtractResourcePlugin::<PlaygroundTarget>::default())
+            .add_systems(Startup, setup_playground_system)
+            .add_systems(Update, compute_playground_window_system);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(PipelineStatusSender(sender))
+            .add_systems(
+                Render,
+                prepare_playground_system.in_set(RenderSet::PrepareBindGroups),
+            );
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(PlaygroundLabel, PlaygroundNode);
+        graph.add_node_edge(PlaygroundLabel, CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The pipeline's bind group layout needs the render device, which exists only now.
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<PlaygroundPipeline>();
+        }
+    }
+}
+
+/// Mirrors the shader's `Params` struct.
+#[derive(Clone, Copy, Default)]
+struct PlaygroundUniform {
+    size: UVec2,
+    time: f32,
+    values: Vec4,
+}
+
+impl PlaygroundUniform {
+    /// WGSL uniform layout: `values` is 16-byte aligned, so four bytes of padding follow `time`.
+    const SIZE: u64 = 32;
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::SIZE as usize);
+        bytes.extend(self.size.to_array().map(u32::to_le_bytes).concat());
+        bytes.extend(self.time.to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.extend(self.values.to_array().map(f32::to_le_bytes).concat());
+        bytes
+    }
+}
+
+/// What the render world needs to dispatch the playground shader.
+#[derive(Resource, Clone, ExtractResource)]
+struct PlaygroundTarget {
+    image: Handle<Image>,
+    shader: Handle<Shader>,
+    uniform: PlaygroundUniform,
+    workgroups: UVec2,
+    running: bool,
+}
+
+/// Compilation results from the render world: `Err` carries the shader error.
+#[derive(Resource)]
+struct PipelineStatusReceiver(Mutex<Receiver<Result<(), String>>>);
+
+#[derive(Resource)]
+struct PipelineStatusSender(Sender<Result<(), String>>);
+
+#[derive(Resource)]
+pub struct ComputePlaygroundWindow {
+    pub is_open: bool,
+    source: String,
+    /// When the source was last edited, while the edit is not compiled yet.
+    edited_at: Option<f32>,
+    paused: bool,
+    elapsed: f32,
+    workgroups: [u32; 2],
+    values: [f32; 4],
+    status: Option<Result<(), String>>,
+}
+
+impl Default for ComputePlaygroundWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            source: EXAMPLE.to_owned(),
+            edited_at: None,
+            paused: false,
+            elapsed: 0.0,
+            workgroups: [SIZE.div_ceil(8); 2],
+            values: [0.5, 0.5, 0.0, 0.0],
+            status: None,
+        }
+    }
+}
+
+impl Panel for ComputePlaygroundWindow {
+    const TITLE: &'static str = "Compute Playground";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn setup_playground_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut shaders: ResMut<Assets<Shader>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::COPY_DST | TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
+    commands.insert_resource(PlaygroundTarget {
+        image: images.add(image),
+        shader: shaders.add(Shader::from_wgsl(EXAMPLE, SHADER_PATH)),
+        uniform: PlaygroundUniform::default(),
+        workgroups: UVec2::ONE,
+        running: false,
+    });
+}
+
+fn compute_playground_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ComputePlaygroundWindow>,
+    mut target: ResMut<PlaygroundTarget>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    receiver: Res<PipelineStatusReceiver>,
+    time: Res<Time<Real>>,
+) {
+    let ComputePlaygroundWindow {
+        is_open,
+        source,
+        edited_at,
+        paused,
+        elapsed,
+        workgroups,
+        values,
+        status,
+    } = &mut *window;
+    if let Some(latest) = receiver.0.lock().unwrap().try_iter().last() {
+        *status = Some(latest);
+    }
+    if !*is_open {
+        if target.running {
+            target.running = false;
+        }
+        return;
+    }
+
+    let now = time.elapsed_seconds();
+    if edited_at.is_some_and(|at| now - at >= RELOAD_DELAY) {
+        // Replacing the asset makes the pipeline cache recompile everything that uses it.
+        shaders.insert(
+            target.shader.id(),
+            Shader::from_wgsl(source.clone(), SHADER_PATH),
+        );
+        *edited_at = None;
+        *status = None;
+    }
+    if !*paused {
+        *elapsed += time.delta_seconds();
+    }
+
+    let texture = user_textures.add_image(target.image.clone_weak());
+    egui::Window::new(ComputePlaygroundWindow::TITLE)
+        .open(is_open)
+        .default_width(560.0)
+        .show(contexts.ctx::<ComputePlaygroundWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.toggle_value(paused, "⏸ Pause");
+                if ui.icon_button(Icon::Restore, "Restart").clicked() {
+                    *elapsed = 0.0;
+                }
+                ui.label("Workgroups");
+                for count in workgroups.iter_mut() {
+                    ui.add(drag_value(count).range(1..=256));
+                }
+                ui.weak(format!(
+                    "{}×{} invocations at 8×8",
+                    workgroups[0] * 8,
+                    workgroups[1] * 8
+                ));
+            });
+            ui.horizontal(|ui| {
+                for (value, name) in values.iter_mut().zip(["x", "y", "z", "w"]) {
+                    ui.add(
+                        egui::Slider::new(value, 0.0..=1.0)
+                            .text(name)
+                            .fixed_decimals(2),
+                    );
+                }
+            });
+            ui.separator();
+
+            ui.horizontal_top(|ui| {
+                ui.image(egui::load::SizedTexture::new(
+                    texture,
+                    [SIZE as f32, SIZE as f32],
+                ));
+                ui.vertical(|ui| {
+                    match (&edited_at, &status) {
+                        (Some(_), _) | (None, None) => {
+                            ui.weak("Compiling…");
+                        }
+                        (None, Some(Ok(()))) => {
+                            ui.weak("Running");
+                        }
+                        (None, Some(Err(err))) => {
+                            ui.colored_label(ui.visuals().error_fg_color, err);
+                        }
+                    }
+                    egui::ScrollArea::vertical()
+                        .id_source("compute_playground_source")
+                        .max_height(360.0)
+                        .show(ui, |ui| {
+                            let response =
+                                CodeEditor::new("compute_playground", source, Language::Wgsl)
+                                    .desired_rows(20)
+                                    .show(ui);
+                            if response.changed() {
+                                *edited_at = Some(now);
+                            }
+                        });
+                    if ui.button("Reset to example").clicked() {
+                        *source = EXAMPLE.to_owned();
+                        *edited_at = Some(now);
+                    }
+                });
+            });
+        });
+
+    let uniform = PlaygroundUniform {
+        size: UVec2::splat(SIZE),
+        time: *elapsed,
+        values: Vec4::from_array(*values),
+    };
+    target.uniform = uniform;
+    target.workgroups = UVec2::from_array(*workgroups);
+    target.running = *is_open;
+}
+
+#[derive(Resource)]
+struct PlaygroundPipeline {
+    layout: BindGroupLayout,
+    id: Option<CachedComputePipelineId>,
+    uniform: Buffer,
+    /// This frame's dispatch, when the playground is running and its texture exists.
+    dispatch: Option<(BindGroup, UVec2)>,
+    /// The last status sent to the main world, so each change is reported once.
+    reported: Option<Result<(), String>>,
+}
+
+impl FromWorld for PlaygroundPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "compute_playground_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_storage_2d(TextureFormat::Rgba8Unorm, StorageTextureAccess::WriteOnly),
+                    uniform_buffer_sized(false, NonZeroU64::new(PlaygroundUniform::SIZE)),
+                ),
+            ),
+        );
+        let uniform = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("compute_playground_uniform"),
+            contents: &PlaygroundUniform::default().to_bytes(),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+        Self {
+            layout,
+            id: None,
+            uniform,
+            dispatch: None,
+            reported: None,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_playground_system(
+    target: Option<Res<PlaygroundTarget>>,
+    mut pipeline: ResMut<PlaygroundPipeline>,
+    pipeline_cache: Res<PipelineCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    sender: Res<PipelineStatusSender>,
+) {
+    let PlaygroundPipeline {
+        layout,
+        id,
+        uniform,
+        dispatch,
+        reported,
+    } = &mut *pipeline;
+    *dispatch = None;
+    let Some(target) = target else {
+        return;
+    };
+    let id = *id.get_or_insert_with(|| {
+        pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("compute_playground_pipeline".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: target.shader.clone(),
+            shader_defs: Vec::new(),
+            entry_point: "main".into(),
+        })
+    });
+
+    let status = match pipeline_cache.get_compute_pipeline_state(id) {
+        CachedPipelineState::Ok(_) => Some(Ok(())),
+        CachedPipelineState::Err(err) => Some(Err(err.to_string())),
+        CachedPipelineState::Queued | CachedPipelineState::Creating(_) => None,
+    };
+    if let Some(status) = status {
+        if reported.as_ref() != Some(&status) {
+            let _ = sender.0.send(status.clone());
+            *reported = Some(status);
+        }
+    }
+
+    if !target.running {
+        return;
+    }
+    let Some(gpu_image) = gpu_images.get(&target.image) else {
+        return;
+    };
+    render_queue.write_buffer(uniform, 0, &target.uniform.to_bytes());
+    let bind_group = render_device.create_bind_group(
+        "compute_playground_bind_group",
+        layout,
+        &BindGroupEntries::sequential((&gpu_image.texture_view, uniform.as_entire_binding())),
+    );
+    *dispatch = Some((bind_group, target.workgroups));
+}
+
+#[derive(RenderLabel, Debug, Clone, Hash, PartialEq, Eq)]
+struct PlaygroundLabel;
+
+/// Runs before the cameras, so egui shows this frame's output.
+struct PlaygroundNode;
+
+impl render_graph::Node for PlaygroundNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(pipeline) = world.get_resource::<PlaygroundPipeline>() else {
+            return Ok(());
+        };
+        let (Some(id), Some((bind_group, workgroups))) = (pipeline.id, &pipeline.dispatch) else {
+            return Ok(());
+        };
+        let Some(compute) = world.resource::<PipelineCache>().get_compute_pipeline(id) else {
+            return Ok(());
+        };
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("compute_playground"),
+                    timestamp_writes: None,
+                });
+        pass.set_pipeline(compute);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups(workgroups.x, workgroups.y, 1);
+        Ok(())
+    }
+}
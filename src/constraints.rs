@@ -0,0 +1,20 @@
+//! Shift-to-constrain helpers shared by the drawing and placement tools, so every tool snaps
+//! to the same increments.
+
+use std::f32::consts::PI;
+
+use bevy::math::Vec2;
+
+/// Angle increment constrained strokes and rotations snap to: 15°.
+pub const ANGLE_STEP: f32 = PI / 12.0;
+
+/// Rounds `angle` (radians) to the nearest [`ANGLE_STEP`].
+pub fn snap_angle(angle: f32) -> f32 {
+    (angle / ANGLE_STEP).round() * ANGLE_STEP
+}
+
+/// Turns `delta` to the nearest [`ANGLE_STEP`] direction, keeping its length.
+pub fn snap_direction(delta: Vec2) -> Vec2 {
+    let angle = snap_angle(delta.y.atan2(delta.x));
+    Vec2::from_angle(angle) * delta.length()
+}
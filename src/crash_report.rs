@@ -0,0 +1,268 @@
+use std::{
+    collections::VecDeque,
+    io::Write as _,
+    panic::PanicHookInfo,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    log::{tracing_subscriber, BoxedLayer},
+    prelude::*,
+    render::renderer::RenderAdapterInfo,
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    scene::SceneReader,
+    settings::{Settings, SETTINGS_PATH},
+};
+
+pub const CRASH_REPORTS_DIR: &str = "crash-reports";
+/// Holds the folder of the last crash report until the user dismisses the notice about it.
+const LATEST_PATH: &str = "crash-reports/LATEST";
+/// Log lines kept for the report; older ones are dropped.
+const LOG_LINES: usize = 500;
+/// Seconds between the scene copies kept for the report.
+const SCENE_INTERVAL: f32 = 10.0;
+
+/// Writes a bug report bundle when the sandbox panics: the panic and its backtrace, the recent
+/// log, the settings in use, the scene as of a few seconds earlier and a description of the
+/// system and GPU. Each lands in a timestamped folder under [`CRASH_REPORTS_DIR`], and the next
+/// launch shows where, so it can be attached to an issue.
+pub struct CrashReportPlugin;
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        let context = app
+            .world_mut()
+            .get_resource_or_insert_with(CrashContext::default)
+            .clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            match context.write_report(info) {
+                Ok(folder) => eprintln!("Crash report written to {}", folder.display()),
+                Err(err) => eprintln!("Failed to write a crash report: {err}"),
+            }
+            previous(info);
+        }));
+
+        if let Ok(folder) = std::fs::read_to_string(LATEST_PATH) {
+            let folder = PathBuf::from(folder.trim());
+            warn!(
+                "The last session crashed; its report is in {}",
+                folder.display()
+            );
+            app.insert_resource(LastCrash { folder });
+        }
+
+        app.add_systems(
+            Update,
+            (
+                cache_settings_system,
+                cache_scene_system,
+                cache_adapter_system,
+                last_crash_banner_system
+                    .in_set(crate::UiSet::Panels)
+                    .after(crate::menu_bar_system),
+            ),
+        );
+    }
+}
+
+/// Sends log output to the crash context as well as the console; set as the `LogPlugin`
+/// custom layer, which is built before any other plugin.
+pub fn log_layer(app: &mut App) -> Option<BoxedLayer> {
+    let context = app
+        .world_mut()
+        .get_resource_or_insert_with(CrashContext::default)
+        .clone();
+    Some(Box::new(
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(move || LogWriter(context.clone())),
+    ))
+}
+
+/// What the panic hook writes out, kept up to date while the app runs. Shared with the hook
+/// and the log layer, which cannot reach the world.
+#[derive(Default, Clone, Resource)]
+struct CrashContext(Arc<Mutex<CrashState>>);
+
+#[derive(Default)]
+struct CrashState {
+    log: VecDeque<String>,
+    settings: Option<String>,
+    scene: Option<String>,
+    adapter: Option<String>,
+}
+
+struct LogWriter(CrashContext);
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Ok(mut state) = self.0 .0.lock() {
+            let line = String::from_utf8_lossy(buf);
+            state.log.push_back(line.trim_end().to_owned());
+            while state.log.len() > LOG_LINES {
+                state.log.pop_front();
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CrashContext {
+    /// Writes the bundle for a panic and records its folder for the next launch.
+    fn write_report(&self, info: &PanicHookInfo) -> std::io::Result<PathBuf> {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let folder = Path::new(CRASH_REPORTS_DIR).join(seconds.to_string());
+        std::fs::create_dir_all(&folder)?;
+
+        let thread = std::thread::current();
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        std::fs::write(
+            folder.join("panic.txt"),
+            format!(
+                "Thread '{}' {info}\n\n{backtrace}\n",
+                thread.name().unwrap_or("<unnamed>")
+            ),
+        )?;
+
+        // The panic may have happened while the state was locked on this thread, so the
+        // report goes without it rather than waiting forever.
+        let state = self.0.try_lock().ok();
+        let adapter = state.as_ref().and_then(|state| state.adapter.clone());
+        std::fs::write(folder.join("system.txt"), system_info(adapter.as_deref()))?;
+        if let Some(state) = &state {
+            let mut log = std::fs::File::create(folder.join("log.txt"))?;
+            for line in &state.log {
+                writeln!(log, "{line}")?;
+            }
+            if let Some(scene) = &state.scene {
+                std::fs::write(folder.join("scene.ron"), scene)?;
+            }
+        }
+        // Settings as they were in memory, or else as last saved.
+        match state.as_ref().and_then(|state| state.settings.as_ref()) {
+            Some(settings) => std::fs::write(folder.join("settings.ron"), settings)?,
+            None => {
+                if Path::new(SETTINGS_PATH).exists() {
+                    std::fs::copy(SETTINGS_PATH, folder.join("settings.ron"))?;
+                }
+            }
+        }
+
+        std::fs::write(LATEST_PATH, folder.display().to_string())?;
+        Ok(folder)
+    }
+}
+
+fn system_info(adapter: Option<&str>) -> String {
+    let threads = std::thread::available_parallelism().map_or(0, usize::from);
+    format!(
+        "Sandbox: {} {}\nOS: {} ({})\nCPU threads: {threads}\nGPU: {}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        adapter.unwrap_or("unknown"),
+    )
+}
+
+fn cache_settings_system(settings: Res<Settings>, context: Res<CrashContext>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let Ok(contents) = ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default())
+    else {
+        return;
+    };
+    if let Ok(mut state) = context.0.lock() {
+        state.settings = Some(contents);
+    }
+}
+
+fn cache_scene_system(
+    scene: SceneReader,
+    context: Res<CrashContext>,
+    time: Res<Time>,
+    mut last: Local<Option<f32>>,
+) {
+    let now = time.elapsed_seconds();
+    if last.is_some_and(|last| now - last < SCENE_INTERVAL) {
+        return;
+    }
+    *last = Some(now);
+    let Ok(contents) =
+        ron::ser::to_string_pretty(&scene.capture(), ron::ser::PrettyConfig::default())
+    else {
+        return;
+    };
+    if let Ok(mut state) = context.0.lock() {
+        state.scene = Some(contents);
+    }
+}
+
+fn cache_adapter_system(adapter: Option<Res<RenderAdapterInfo>>, context: Res<CrashContext>) {
+    let Some(adapter) = adapter.filter(|adapter| adapter.is_added()) else {
+        return;
+    };
+    if let Ok(mut state) = context.0.lock() {
+        state.adapter = Some(format!(
+            "{} ({:?}, {:?} backend, driver {} {})",
+            adapter.name, adapter.device_type, adapter.backend, adapter.driver, adapter.driver_info
+        ));
+    }
+}
+
+/// Present after a crash until the user dismisses the notice.
+#[derive(Resource)]
+struct LastCrash {
+    folder: PathBuf,
+}
+
+fn last_crash_banner_system(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    last_crash: Option<Res<LastCrash>>,
+) {
+    let Some(last_crash) = last_crash else {
+        return;
+    };
+
+    let mut dismissed = false;
+    let folder = last_crash.folder.display().to_string();
+    egui::TopBottomPanel::top("last_crash_banner").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("⚠ The sandbox crashed last time. A report was saved to {folder}."),
+            );
+            if ui
+                .button("Copy path")
+                .on_hover_text("Attach the folder's files when reporting the bug")
+                .clicked()
+            {
+                ui.output_mut(|output| output.copied_text = folder.clone());
+            }
+            if ui.button("Dismiss").clicked() {
+                dismissed = true;
+            }
+        });
+    });
+
+    if dismissed {
+        if let Err(err) = std::fs::remove_file(LATEST_PATH) {
+            warn!("Failed to remove {LATEST_PATH}: {err}");
+        }
+        commands.remove_resource::<LastCrash>();
+    }
+}
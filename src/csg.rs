@@ -0,0 +1,472 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    batching::snapshot_cube,
+    errors::AppError,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    scene::{self, CustomMesh},
+    selection::Selection,
+    RenderCube, RestRotation, Static,
+};
+
+/// Points closer than this to a plane count as lying on it.
+const EPSILON: f32 = 1e-5;
+
+/// Edit › Union / Subtract / Intersect: constructive solid geometry on the first two selected
+/// entities, replacing them with one new entity whose mesh is stored with the scene.
+pub struct CsgPlugin;
+
+impl Plugin for CsgPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CsgCommand>()
+            .add_systems(
+                Update,
+                (csg_command_system, build_csg_meshes_system).chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Union", |world| {
+                    world.send_event(CsgCommand::Union);
+                })
+                .separator_before(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Subtract", |world| {
+                world.send_event(CsgCommand::Subtract);
+            }))
+            .add_menu_item(MenuItem::new(Menu::Edit, "Intersect", |world| {
+                world.send_event(CsgCommand::Intersect);
+            }));
+    }
+}
+
+/// Subtract removes the second selected entity from the first.
+#[derive(Event, Clone, Copy)]
+pub enum CsgCommand {
+    Union,
+    Subtract,
+    Intersect,
+}
+
+/// Triangles of a CSG result relative to its entity; the mesh is rebuilt from this on load.
+#[derive(Component, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsgMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+}
+
+impl CsgMesh {
+    fn mesh(&self) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions.clone())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals.clone())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; self.positions.len()])
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+}
+
+impl Vertex {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(other.position, t),
+            normal: self.normal.lerp(other.normal, t),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Plane {
+    normal: Vec3,
+    w: f32,
+}
+
+#[derive(Clone)]
+struct Polygon {
+    vertices: Vec<Vertex>,
+    plane: Plane,
+}
+
+impl Polygon {
+    /// `None` for degenerate polygons, which have no plane.
+    fn new(vertices: Vec<Vertex>) -> Option<Self> {
+        let [a, b, c] = [0, 1, 2].map(|i| vertices[i].position);
+        let normal = (b - a).cross(c - a).try_normalize()?;
+        Some(Self {
+            vertices,
+            plane: Plane {
+                normal,
+                w: normal.dot(a),
+            },
+        })
+    }
+
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        for vertex in &mut self.vertices {
+            vertex.normal = -vertex.normal;
+        }
+        self.plane.normal = -self.plane.normal;
+        self.plane.w = -self.plane.w;
+    }
+}
+
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+/// Where pieces of a split polygon go.
+#[derive(Default)]
+struct Split {
+    coplanar_front: Vec<Polygon>,
+    coplanar_back: Vec<Polygon>,
+    front: Vec<Polygon>,
+    back: Vec<Polygon>,
+}
+
+impl Plane {
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    fn split(&self, polygon: Polygon, split: &mut Split) {
+        let kinds: Vec<u8> = polygon
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let t = self.normal.dot(vertex.position) - self.w;
+                if t < -EPSILON {
+                    BACK
+                } else if t > EPSILON {
+                    FRONT
+                } else {
+                    COPLANAR
+                }
+            })
+            .collect();
+        match kinds.iter().fold(COPLANAR, |kind, k| kind | k) {
+            COPLANAR if self.normal.dot(polygon.plane.normal) > 0.0 => {
+                split.coplanar_front.push(polygon)
+            }
+            COPLANAR => split.coplanar_back.push(polygon),
+            FRONT => split.front.push(polygon),
+            BACK => split.back.push(polygon),
+            _ => {
+                let (mut front, mut back) = (Vec::new(), Vec::new());
+                let count = polygon.vertices.len();
+                for i in 0..count {
+                    let j = (i + 1) % count;
+                    let (ki, kj) = (kinds[i], kinds[j]);
+                    let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                    if ki != BACK {
+                        front.push(vi);
+                    }
+                    if ki != FRONT {
+                        back.push(vi);
+                    }
+                    if ki | kj == SPANNING {
+                        let t = (self.w - self.normal.dot(vi.position))
+                            / self.normal.dot(vj.position - vi.position);
+                        let vertex = vi.lerp(vj, t);
+                        front.push(vertex);
+                        back.push(vertex);
+                    }
+                }
+                if front.len() >= 3 {
+                    split.front.extend(Polygon::new(front));
+                }
+                if back.len() >= 3 {
+                    split.back.extend(Polygon::new(back));
+                }
+            }
+        }
+    }
+}
+
+/// A BSP tree of polygons, after csg.js by Evan Wallace.
+#[derive(Default)]
+struct Node {
+    plane: Option<Plane>,
+    front: Option<Box<Node>>,
+    back: Option<Box<Node>>,
+    polygons: Vec<Polygon>,
+}
+
+impl Node {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = Self::default();
+        node.build(polygons);
+        node
+    }
+
+    /// Swaps solid and empty space.
+    fn invert(&mut self) {
+        for polygon in &mut self.polygons {
+            polygon.flip();
+        }
+        if let Some(plane) = &mut self.plane {
+            plane.flip();
+        }
+        if let Some(front) = &mut self.front {
+            front.invert();
+        }
+        if let Some(back) = &mut self.back {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Removes the parts of `polygons` inside this tree.
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let Some(plane) = self.plane else {
+            return polygons;
+        };
+        let mut split = Split::default();
+        for polygon in polygons {
+            plane.split(polygon, &mut split);
+        }
+        let mut front = split.front;
+        front.append(&mut split.coplanar_front);
+        let mut back = split.back;
+        back.append(&mut split.coplanar_back);
+
+        let mut front = match &self.front {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        if let Some(node) = &self.back {
+            front.extend(node.clip_polygons(back));
+        }
+        front
+    }
+
+    /// Removes the parts of this tree inside `other`.
+    fn clip_to(&mut self, other: &Node) {
+        self.polygons = other.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = &mut self.front {
+            front.clip_to(other);
+        }
+        if let Some(back) = &mut self.back {
+            back.clip_to(other);
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = self.polygons.clone();
+        for node in [&self.front, &self.back].into_iter().flatten() {
+            polygons.extend(node.all_polygons());
+        }
+        polygons
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        let Some(first) = polygons.first() else {
+            return;
+        };
+        let plane = *self.plane.get_or_insert(first.plane);
+        let mut split = Split::default();
+        for polygon in polygons {
+            plane.split(polygon, &mut split);
+        }
+        self.polygons.append(&mut split.coplanar_front);
+        self.polygons.append(&mut split.coplanar_back);
+        if !split.front.is_empty() {
+            self.front
+                .get_or_insert_with(Default::default)
+                .build(split.front);
+        }
+        if !split.back.is_empty() {
+            self.back
+                .get_or_insert_with(Default::default)
+                .build(split.back);
+        }
+    }
+}
+
+fn apply(command: CsgCommand, a: Vec<Polygon>, b: Vec<Polygon>) -> Vec<Polygon> {
+    let (mut a, mut b) = (Node::new(a), Node::new(b));
+    match command {
+        CsgCommand::Union => {
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+        }
+        CsgCommand::Subtract => {
+            a.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+            a.invert();
+        }
+        CsgCommand::Intersect => {
+            a.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            a.build(b.all_polygons());
+            a.invert();
+        }
+    }
+    a.all_polygons()
+}
+
+/// The mesh's triangles in world space.
+fn world_polygons(mesh: &Mesh, transform: &Transform) -> Option<Vec<Polygon>> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+        _ => None,
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    let affine = transform.compute_affine();
+    let normal_matrix = Mat3::from(affine.matrix3).inverse().transpose();
+    let vertex = |index: usize| Vertex {
+        position: affine.transform_point3(Vec3::from(positions[index])),
+        normal: normals.map_or(Vec3::ZERO, |normals| {
+            (normal_matrix * Vec3::from(normals[index])).normalize_or_zero()
+        }),
+    };
+    Some(
+        indices
+            .chunks_exact(3)
+            .filter_map(|triangle| Polygon::new(triangle.iter().map(|&i| vertex(i)).collect()))
+            .collect(),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn csg_command_system(
+    mut events: EventReader<CsgCommand>,
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    operands: Query<
+        (
+            &GlobalTransform,
+            &Transform,
+            Option<&RestRotation>,
+            &Handle<Mesh>,
+            &Handle<StandardMaterial>,
+            Has<Static>,
+        ),
+        With<RenderCube>,
+    >,
+    mut errors: EventWriter<AppError>,
+) {
+    for command in events.read().copied() {
+        let [Ok(a), Ok(b)] = [0, 1].map(|i| {
+            selection
+                .entities
+                .get(i)
+                .ok_or(())
+                .and_then(|entity| operands.get(*entity).map_err(|_| ()))
+        }) else {
+            errors.send(
+                AppError::new("CSG", "Boolean operations need two selected scene entities")
+                    .suggest("Ctrl-click to select a second entity; Subtract removes the second from the first."),
+            );
+            continue;
+        };
+
+        // Operate on the authored pose, not the animated one.
+        let polygons = [&a, &b].map(|(global, transform, rest, mesh, ..)| {
+            let world = snapshot_cube(global, transform, *rest, Color::WHITE, None, false);
+            meshes
+                .get(*mesh)
+                .and_then(|mesh| world_polygons(mesh, &world.transform()))
+        });
+        let [Some(polygons_a), Some(polygons_b)] = polygons else {
+            continue;
+        };
+
+        let result = apply(command, polygons_a, polygons_b);
+        if result.is_empty() {
+            errors.send(
+                AppError::new("CSG", "The operation left nothing behind")
+                    .suggest("Intersect needs overlapping operands."),
+            );
+            continue;
+        }
+        let vertices = result.iter().map(|polygon| polygon.vertices.len() as f32);
+        let count: f32 = vertices.sum();
+        let center = result
+            .iter()
+            .flat_map(|polygon| &polygon.vertices)
+            .map(|vertex| vertex.position)
+            .sum::<Vec3>()
+            / count;
+
+        // Fan-triangulate the convex result polygons, flat shaded by their planes.
+        let mut csg = CsgMesh {
+            positions: Vec::new(),
+            normals: Vec::new(),
+        };
+        for polygon in &result {
+            for i in 1..polygon.vertices.len() - 1 {
+                for vertex in [0, i, i + 1].map(|i| polygon.vertices[i]) {
+                    csg.positions.push((vertex.position - center).to_array());
+                    csg.normals.push(polygon.plane.normal.to_array());
+                }
+            }
+        }
+
+        let color = materials
+            .get(a.4)
+            .map_or(Color::WHITE, |material| material.base_color);
+        let entity = scene::spawn_cube(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Transform::from_translation(center),
+            color,
+        );
+        commands.entity(entity).insert(csg);
+        if a.5 {
+            commands.entity(entity).insert(Static);
+        }
+        for operand in &selection.entities[..2] {
+            commands.entity(*operand).despawn_recursive();
+        }
+        selection.select(entity);
+    }
+}
+
+fn build_csg_meshes_system(
+    mut commands: Commands,
+    mut results: Query<(Entity, &CsgMesh, &mut Handle<Mesh>), Changed<CsgMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, csg, mut mesh) in &mut results {
+        *mesh = meshes.add(csg.mesh());
+        commands.entity(entity).insert(CustomMesh);
+    }
+}
@@ -0,0 +1,358 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{
+    core_pipeline::Skybox,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+            TextureViewDescriptor, TextureViewDimension,
+        },
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    background::{BackgroundMode, BackgroundWindow, ViewportBackground},
+    errors::AppError,
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests},
+    report::encode_png,
+    status_bar::StatusBar,
+    window_title::TaskProgress,
+    ViewportCamera,
+};
+
+pub const STRIP_PATH: &str = "cubemap.png";
+pub const EQUIRECT_PATH: &str = "cubemap_equirect.png";
+
+/// World-space forward and up of each face camera, in the order wgpu stores cubemap layers
+/// (+X, -X, +Y, -Y, +Z, -Z). Bevy's skybox samples with Z negated, hence the swapped Z faces.
+const FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Y),
+    (Vec3::Y, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::NEG_Z),
+    (Vec3::NEG_Z, Vec3::Y),
+    (Vec3::Z, Vec3::Y),
+];
+
+/// Renders the scene from a point into the six faces of a cubemap and saves it as a vertical
+/// strip (loadable as a Bevy cubemap) or an equirectangular panorama, optionally using it as
+/// the viewport's environment straight away.
+pub struct CubemapPlugin;
+
+impl Plugin for CubemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<CubemapWindow>().add_systems(
+            Update,
+            (
+                cubemap_window_system.in_set(CubemapWindow::ui_set()),
+                capture_cubemap_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CubemapLayout {
+    /// The six faces stacked top to bottom.
+    Strip,
+    Equirectangular,
+}
+
+/// A capture in progress: the face cameras and what each has delivered so far.
+struct Capture {
+    cameras: [Entity; 6],
+    images: [Handle<Image>; 6],
+    faces: [Option<Vec<u8>>; 6],
+}
+
+#[derive(Resource)]
+pub struct CubemapWindow {
+    pub is_open: bool,
+    position: Vec3,
+    resolution: u32,
+    layout: CubemapLayout,
+    use_as_environment: bool,
+    capture: Option<Capture>,
+}
+
+impl Default for CubemapWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            position: Vec3::new(0.0, 2.0, 0.0),
+            resolution: 256,
+            layout: CubemapLayout::Strip,
+            use_as_environment: true,
+            capture: None,
+        }
+    }
+}
+
+impl Panel for CubemapWindow {
+    const TITLE: &'static str = "Cubemap Capture";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn face_target(size: u32) -> Image {
+    let size = Extent3d {
+        width: size,
+        height: size,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+fn cubemap_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<CubemapWindow>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    cameras: Query<(&Camera, &GlobalTransform, Option<&Skybox>), With<ViewportCamera>>,
+) {
+    let CubemapWindow {
+        is_open,
+        position,
+        resolution,
+        layout,
+        use_as_environment,
+        capture,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+    let Ok((viewport_camera, camera_transform, skybox)) = cameras.get_single() else {
+        return;
+    };
+
+    egui::Window::new("Cubemap Capture")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<CubemapWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Position");
+                ui.add(drag_value(&mut position.x).speed(0.1).prefix("x "));
+                ui.add(drag_value(&mut position.y).speed(0.1).prefix("y "));
+                ui.add(drag_value(&mut position.z).speed(0.1).prefix("z "));
+                if ui.button("From camera").clicked() {
+                    *position = camera_transform.translation();
+                }
+            });
+            egui::ComboBox::from_label("Face size")
+                .selected_text(format!("{resolution}²"))
+                .show_ui(ui, |ui| {
+                    for size in [64, 128, 256, 512, 1024] {
+                        ui.selectable_value(resolution, size, format!("{size}²"));
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.radio_value(layout, CubemapLayout::Strip, "Cubemap strip")
+                    .on_hover_text(format!(
+                        "{STRIP_PATH}: +X, -X, +Y, -Y, +Z, -Z top to bottom"
+                    ));
+                ui.radio_value(layout, CubemapLayout::Equirectangular, "Equirectangular")
+                    .on_hover_text(format!("{EQUIRECT_PATH}: a 2:1 panorama"));
+            });
+            ui.checkbox(use_as_environment, "Use as the viewport environment");
+
+            ui.separator();
+            if let Some(capture) = capture {
+                let done = capture.faces.iter().flatten().count();
+                ui.add(egui::ProgressBar::new(done as f32 / 6.0).text("Capturing…"));
+                return;
+            }
+            if ui.button("Capture").clicked() {
+                let mut images_out = Vec::with_capacity(6);
+                let mut cameras_out = Vec::with_capacity(6);
+                for (forward, up) in FACES {
+                    let image = images.add(face_target(*resolution));
+                    let mut camera = commands.spawn(Camera3dBundle {
+                        camera: Camera {
+                            target: RenderTarget::Image(image.clone()),
+                            clear_color: viewport_camera.clear_color,
+                            ..default()
+                        },
+                        projection: PerspectiveProjection {
+                            fov: FRAC_PI_2,
+                            aspect_ratio: 1.0,
+                            ..default()
+                        }
+                        .into(),
+                        transform: Transform::from_translation(*position).looking_to(forward, up),
+                        ..default()
+                    });
+                    if let Some(skybox) = skybox {
+                        camera.insert(skybox.clone());
+                    }
+                    cameras_out.push(camera.id());
+                    images_out.push(image);
+                }
+                *capture = Some(Capture {
+                    cameras: cameras_out.try_into().unwrap(),
+                    images: images_out.try_into().unwrap(),
+                    faces: default(),
+                });
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn capture_cubemap_system(
+    mut commands: Commands,
+    mut window: ResMut<CubemapWindow>,
+    mut requests: ResMut<ReadbackRequests>,
+    mut readbacks: EventReader<ReadbackComplete>,
+    mut images: ResMut<Assets<Image>>,
+    mut backgrounds: Query<&mut ViewportBackground, With<ViewportCamera>>,
+    mut background_window: ResMut<BackgroundWindow>,
+    mut errors: EventWriter<AppError>,
+    mut status: ResMut<StatusBar>,
+    mut progress: ResMut<TaskProgress>,
+    time: Res<Time>,
+) {
+    let Some(capture) = &mut window.capture else {
+        readbacks.clear();
+        return;
+    };
+    for readback in readbacks
+        .read()
+        .filter(|readback| readback.region.is_none())
+    {
+        if let Some(index) = capture
+            .images
+            .iter()
+            .position(|image| image.id() == readback.image)
+        {
+            capture.faces[index] = Some(readback.data.clone());
+        }
+    }
+    // Keep asking until every face has arrived; requests for faces still in flight are skipped.
+    for (image, face) in capture.images.iter().zip(&capture.faces) {
+        if face.is_none() {
+            requests.request(image);
+        }
+    }
+    if capture.faces.iter().any(Option::is_none) {
+        let done = capture.faces.iter().flatten().count();
+        progress.report("Cubemap", done as f32 / 6.0);
+        return;
+    }
+
+    let Some(capture) = window.capture.take() else {
+        return;
+    };
+    for camera in capture.cameras {
+        commands.entity(camera).despawn();
+    }
+    let size = window.resolution;
+    let strip: Vec<u8> = capture.faces.into_iter().flatten().flatten().collect();
+    let (path, result) = match window.layout {
+        CubemapLayout::Strip => (STRIP_PATH, encode_png(UVec2::new(size, size * 6), &strip)),
+        CubemapLayout::Equirectangular => {
+            let (panorama_size, panorama) = equirectangular(&strip, size);
+            (EQUIRECT_PATH, encode_png(panorama_size, &panorama))
+        }
+    };
+    match result
+        .map_err(|err| err.to_string())
+        .and_then(|png| std::fs::write(path, png).map_err(|err| err.to_string()))
+    {
+        Ok(()) => status.flash(format!("Saved cubemap to {path}"), time.elapsed_seconds()),
+        Err(err) => {
+            errors.send(AppError::new(
+                "Cubemap",
+                format!("Failed to save {path}: {err}"),
+            ));
+        }
+    }
+
+    if window.use_as_environment {
+        let mut cubemap = Image::new(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            TextureDimension::D2,
+            strip,
+            TextureFormat::Rgba8UnormSrgb,
+            // Kept in the main world too, so the Background window can turn it.
+            RenderAssetUsages::default(),
+        );
+        cubemap.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+        background_window.set_sky(images.add(cubemap));
+        if let Ok(mut background) = backgrounds.get_single_mut() {
+            // Always marks the background changed, so the new sky is applied.
+            background.mode = BackgroundMode::Environment;
+        }
+    }
+}
+
+/// Resamples a strip of six `size`² RGBA8 faces into a 2:1 panorama, longitude zero facing -Z.
+fn equirectangular(strip: &[u8], size: u32) -> (UVec2, Vec<u8>) {
+    let (width, height) = (size * 4, size * 2);
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        let latitude = FRAC_PI_2 - (y as f32 + 0.5) / height as f32 * PI;
+        for x in 0..width {
+            let longitude = (x as f32 + 0.5) / width as f32 * 2.0 * PI - PI;
+            let world = Vec3::new(
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+                -latitude.cos() * longitude.cos(),
+            );
+            // Direction in cubemap space, see `FACES`.
+            let c = world * Vec3::new(1.0, 1.0, -1.0);
+            let a = c.abs();
+            let (face, u, v) = if a.x >= a.y && a.x >= a.z {
+                if c.x > 0.0 {
+                    (0, -c.z / a.x, -c.y / a.x)
+                } else {
+                    (1, c.z / a.x, -c.y / a.x)
+                }
+            } else if a.y >= a.z {
+                if c.y > 0.0 {
+                    (2, c.x / a.y, c.z / a.y)
+                } else {
+                    (3, c.x / a.y, -c.z / a.y)
+                }
+            } else if c.z > 0.0 {
+                (4, c.x / a.z, -c.y / a.z)
+            } else {
+                (5, -c.x / a.z, -c.y / a.z)
+            };
+            let texel = |t: f32| (((t + 1.0) * 0.5 * size as f32) as u32).min(size - 1);
+            let index = ((face * size + texel(v)) * size + texel(u)) as usize * 4;
+            data.extend_from_slice(&strip[index..index + 4]);
+        }
+    }
+    (UVec2::new(width, height), data)
+}
@@ -0,0 +1,188 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::CameraProjection,
+        primitives::{Aabb, Frustum},
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    settings::{egui_color, Settings},
+    ViewportCamera,
+};
+
+/// The Culling window: outlines meshes by whether the viewport camera's frustum contains
+/// them. Freezing keeps that frustum while the camera moves away, to inspect it from outside.
+pub struct CullingPlugin;
+
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<CullingWindow>().add_systems(
+            Update,
+            (
+                culling_window_system.in_set(CullingWindow::ui_set()),
+                draw_culling_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A camera's culling volume captured at one moment.
+struct FrozenCamera {
+    frustum: Frustum,
+    transform: GlobalTransform,
+    projection: Projection,
+}
+
+#[derive(Resource)]
+pub struct CullingWindow {
+    pub is_open: bool,
+    visualize: bool,
+    frozen: Option<FrozenCamera>,
+    /// How far out the frozen frustum is drawn; the real far plane is usually much further.
+    draw_distance: f32,
+    counts: [usize; 3],
+}
+
+impl Default for CullingWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            visualize: true,
+            frozen: None,
+            draw_distance: 40.0,
+            counts: [0; 3],
+        }
+    }
+}
+
+impl Panel for CullingWindow {
+    const TITLE: &'static str = "Culling";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn culling_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<CullingWindow>,
+    cameras: Query<(&Frustum, &GlobalTransform, &Projection), With<ViewportCamera>>,
+    settings: Res<Settings>,
+) {
+    let CullingWindow {
+        is_open,
+        visualize,
+        frozen,
+        draw_distance,
+        counts,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Culling")
+        .open(is_open)
+        .show(contexts.ctx::<CullingWindow>(), |ui| {
+            ui.checkbox(visualize, "Outline meshes by culling status");
+            ui.horizontal(|ui| match frozen {
+                Some(_) => {
+                    ui.label("Camera frozen.");
+                    if ui.button("Unfreeze").clicked() {
+                        *frozen = None;
+                    }
+                }
+                None => {
+                    ui.label("Following the viewport camera.");
+                    if ui.button("Freeze").clicked() {
+                        *frozen =
+                            cameras
+                                .get_single()
+                                .ok()
+                                .map(|(frustum, transform, projection)| FrozenCamera {
+                                    frustum: *frustum,
+                                    transform: *transform,
+                                    projection: projection.clone(),
+                                });
+                    }
+                }
+            });
+            ui.add(egui::Slider::new(draw_distance, 1.0..=200.0).text("Frustum draw distance"));
+            ui.separator();
+            let highlights = settings.highlights();
+            let [visible, culled, hidden] = *counts;
+            for (color, label, count) in [
+                (highlights.positive, "Visible", visible),
+                (highlights.negative, "Frustum-culled", culled),
+                (highlights.muted, "Hidden", hidden),
+            ] {
+                ui.colored_label(egui_color(color), format!("■ {label}: {count}"));
+            }
+            ui.weak("Bevy culls by frustum only; occluded meshes still count as visible.");
+        });
+}
+
+fn draw_culling_system(
+    mut window: ResMut<CullingWindow>,
+    cameras: Query<&Frustum, With<ViewportCamera>>,
+    meshes: Query<(&GlobalTransform, &Aabb, &InheritedVisibility), With<Handle<Mesh>>>,
+    settings: Res<Settings>,
+    mut gizmos: Gizmos,
+) {
+    if !window.is_open || !window.visualize {
+        return;
+    }
+    let frustum = match &window.frozen {
+        Some(frozen) => frozen.frustum,
+        None => match cameras.get_single() {
+            Ok(frustum) => *frustum,
+            Err(_) => return,
+        },
+    };
+
+    let highlights = settings.highlights();
+    let mut counts = [0; 3];
+    for (transform, aabb, visibility) in &meshes {
+        let (index, color) = if !visibility.get() {
+            (2, highlights.muted)
+        } else if frustum.intersects_obb(aabb, &transform.affine(), true, true) {
+            (0, highlights.positive)
+        } else {
+            (1, highlights.negative)
+        };
+        counts[index] += 1;
+        let local = Transform::from_translation(aabb.center.into())
+            .with_scale(Vec3::from(aabb.half_extents) * 2.02);
+        gizmos.cuboid(transform.mul_transform(local), color);
+    }
+    if window.counts != counts {
+        window.counts = counts;
+    }
+
+    if let Some(frozen) = &window.frozen {
+        let near = match &frozen.projection {
+            Projection::Perspective(projection) => projection.near,
+            Projection::Orthographic(projection) => projection.near,
+        };
+        let far = frozen.projection.far().min(window.draw_distance);
+        let corners = frozen
+            .projection
+            .get_frustum_corners(-near, -far)
+            .map(|corner| frozen.transform.transform_point(corner.into()));
+        for i in 0..4 {
+            let j = (i + 1) % 4;
+            gizmos.line(corners[i], corners[j], highlights.info);
+            gizmos.line(corners[i + 4], corners[j + 4], highlights.info);
+            gizmos.line(corners[i], corners[i + 4], highlights.info);
+        }
+        gizmos.sphere(
+            frozen.transform.translation(),
+            Quat::IDENTITY,
+            0.3,
+            highlights.info,
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiSet};
+
+use crate::{
+    input::{InputOwner, InputRouting},
+    picking::Picking,
+    viewport::{Viewport, ViewportTool},
+};
+
+/// Picks the mouse cursor over the viewport from the active tool and what is hovered. Other
+/// systems may ask for a cursor with [`ViewportCursor::request`] during `Update`; the most
+/// specific request wins.
+pub struct CursorPlugin;
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewportCursor>()
+            .add_systems(Update, tool_cursor_system.after(crate::UiSet::Central))
+            .add_systems(
+                PostUpdate,
+                apply_cursor_system.before(EguiSet::ProcessOutput),
+            );
+    }
+}
+
+/// Cursors in increasing order of precedence.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum CursorKind {
+    /// Something under the pointer can be clicked.
+    Hand,
+    /// A tool that targets a precise point.
+    Crosshair,
+    /// Sampling colours. The OS has no such cursor, so it is drawn by egui.
+    Eyedropper,
+}
+
+#[derive(Default, Resource)]
+pub struct ViewportCursor {
+    requested: Option<CursorKind>,
+}
+
+impl ViewportCursor {
+    pub fn request(&mut self, kind: CursorKind) {
+        self.requested = self.requested.max(Some(kind));
+    }
+}
+
+fn tool_cursor_system(
+    viewport: Res<Viewport>,
+    tool: Res<ViewportTool>,
+    routing: Res<InputRouting>,
+    picking: Picking,
+    mut cursor: ResMut<ViewportCursor>,
+) {
+    if viewport.pointer.is_none() || routing.pointer == InputOwner::Egui {
+        return;
+    }
+    match *tool {
+        ViewportTool::Select => {
+            if picking.pick_pointer(&[]).is_some() {
+                cursor.request(CursorKind::Hand);
+            }
+        }
+        ViewportTool::PlaceOnSurface
+        | ViewportTool::PickPivot
+        | ViewportTool::Focus
+        | ViewportTool::VertexPaint
+        | ViewportTool::PinCloth
+        | ViewportTool::PaintTiles => {
+            cursor.request(CursorKind::Crosshair);
+        }
+    }
+}
+
+fn apply_cursor_system(
+    mut contexts: EguiContexts,
+    viewport: Res<Viewport>,
+    mut cursor: ResMut<ViewportCursor>,
+) {
+    let Some(kind) = cursor.requested.take() else {
+        return;
+    };
+    let Some(pos) = viewport.pointer else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    match kind {
+        CursorKind::Hand => ctx.set_cursor_icon(egui::CursorIcon::PointingHand),
+        CursorKind::Crosshair => ctx.set_cursor_icon(egui::CursorIcon::Crosshair),
+        CursorKind::Eyedropper => {
+            ctx.set_cursor_icon(egui::CursorIcon::None);
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Tooltip,
+                egui::Id::new("eyedropper_cursor"),
+            ));
+            // A pipette whose tip is the hotspot.
+            let stroke = egui::Stroke::new(1.5, egui::Color32::WHITE);
+            let outline = egui::Stroke::new(3.5, egui::Color32::BLACK);
+            let tip = pos;
+            let bulb = pos + egui::vec2(11.0, -11.0);
+            for stroke in [outline, stroke] {
+                painter.line_segment([tip, bulb], stroke);
+                painter.circle_stroke(bulb + egui::vec2(2.0, -2.0), 3.0, stroke);
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use xihydra_bevy::widgets::Gradient;
+
+use crate::{
+    background::{BackgroundMode, ViewportBackground},
+    icons::{Icon, IconButtonsExt},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    timeline::{AnimationTime, TimelineMarkers},
+    ViewportCamera,
+};
+
+/// Direct sunlight at noon, in lux.
+const NOON_ILLUMINANCE: f32 = 10_000.0;
+/// The sun's height at noon, in degrees.
+const NOON_ELEVATION: f32 = 70.0;
+/// Keys closer than this to the playhead are replaced rather than added next to.
+const KEY_TOLERANCE: f32 = 0.05;
+
+/// The Day/Night window: a time of day driving a sun, the sky colour and ambient light, which
+/// can be keyframed on the animation timeline.
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<DayNightWindow>().add_systems(
+            Update,
+            (day_night_window_system, apply_time_of_day_system).chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+#[derive(Resource)]
+pub struct DayNightWindow {
+    pub is_open: bool,
+    pub enabled: bool,
+    /// Hours since midnight, used when there are no keys.
+    hours: f32,
+    /// `(animation seconds, hours)`, sorted by time.
+    keys: Vec<(f32, f32)>,
+    /// Ambient brightness before the cycle took it over, restored when it is turned off.
+    saved_ambient: Option<f32>,
+}
+
+impl Default for DayNightWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            enabled: false,
+            hours: 10.0,
+            keys: Vec::new(),
+            saved_ambient: None,
+        }
+    }
+}
+
+impl Panel for DayNightWindow {
+    const TITLE: &'static str = "Day/Night";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl DayNightWindow {
+    /// The time of day at `seconds`: interpolated between keys, held past either end. Like
+    /// every animated value this is a pure function of the animation time, so scrubbing works.
+    fn hours_at(&self, seconds: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return self.hours;
+        };
+        if seconds <= first.0 {
+            return first.1;
+        }
+        if seconds >= last.0 {
+            return last.1;
+        }
+        let next = self.keys.partition_point(|(time, _)| *time <= seconds);
+        let ((t0, h0), (t1, h1)) = (self.keys[next - 1], self.keys[next]);
+        h0 + (h1 - h0) * ((seconds - t0) / (t1 - t0))
+    }
+
+    fn set_key(&mut self, seconds: f32, hours: f32) {
+        match self
+            .keys
+            .iter_mut()
+            .find(|(time, _)| (*time - seconds).abs() < KEY_TOLERANCE)
+        {
+            Some(key) => key.1 = hours,
+            None => {
+                let index = self.keys.partition_point(|(time, _)| *time < seconds);
+                self.keys.insert(index, (seconds, hours));
+            }
+        }
+    }
+}
+
+fn format_hours(hours: f32) -> String {
+    let minutes = (hours.rem_euclid(24.0) * 60.0).round() as u32;
+    format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60)
+}
+
+fn day_night_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<DayNightWindow>,
+    mut animation_time: ResMut<AnimationTime>,
+    mut markers: ResMut<TimelineMarkers>,
+) {
+    markers.set(
+        "Day/Night",
+        if window.enabled {
+            window.keys.iter().map(|(time, _)| *time).collect()
+        } else {
+            Vec::new()
+        },
+    );
+    if !window.is_open {
+        return;
+    }
+
+    let seconds = animation_time.seconds;
+    let mut is_open = window.is_open;
+    egui::Window::new("Day/Night")
+        .open(&mut is_open)
+        .resizable(false)
+        .show(contexts.ctx::<DayNightWindow>(), |ui| {
+            ui.checkbox(&mut window.enabled, "Drive the sun, sky and ambient light");
+            ui.add_enabled_ui(window.enabled, |ui| {
+                let keyed = !window.keys.is_empty();
+                let mut hours = window.hours_at(seconds);
+                ui.horizontal(|ui| {
+                    ui.label("Time of day");
+                    let slider = ui.add(
+                        egui::Slider::new(&mut hours, 0.0..=24.0)
+                            .custom_formatter(|hours, _| format_hours(hours as f32)),
+                    );
+                    if slider.changed() {
+                        // With keys, editing the value keys it at the playhead.
+                        if keyed {
+                            window.set_key(seconds, hours);
+                        } else {
+                            window.hours = hours;
+                        }
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("◆ Key at playhead")
+                        .on_hover_text(format!("Key {} at {seconds:.2} s", format_hours(hours)))
+                        .clicked()
+                    {
+                        window.set_key(seconds, hours);
+                    }
+                    if ui
+                        .add_enabled(keyed, egui::Button::new("Clear keys"))
+                        .clicked()
+                    {
+                        window.hours = hours;
+                        window.keys.clear();
+                    }
+                });
+                let mut removed = None;
+                egui::Grid::new("day_night_keys")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (index, (time, key_hours)) in window.keys.iter().enumerate() {
+                            if ui.link(format!("{time:.2} s")).clicked() {
+                                animation_time.seconds = *time;
+                            }
+                            ui.monospace(format_hours(*key_hours));
+                            if ui
+                                .small_icon_button(Icon::Delete, "")
+                                .on_hover_text("Delete key")
+                                .clicked()
+                            {
+                                removed = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(index) = removed {
+                    window.keys.remove(index);
+                }
+                if !keyed {
+                    ui.weak("Add keys to animate the time of day along the timeline.");
+                }
+            });
+        });
+    window.is_open = is_open;
+}
+
+/// Sun direction (towards the sun) for `hours`: rising in the east at 6, setting at 18.
+fn sun_direction(hours: f32) -> Vec3 {
+    let elevation = ((hours - 6.0) / 12.0 * PI).sin() * NOON_ELEVATION.to_radians();
+    let azimuth = hours / 24.0 * TAU + PI;
+    Vec3::new(
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+        elevation.cos() * azimuth.cos(),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_time_of_day_system(
+    mut commands: Commands,
+    mut window: ResMut<DayNightWindow>,
+    animation_time: Res<AnimationTime>,
+    mut suns: Query<(Entity, &mut DirectionalLight, &mut Transform), With<Sun>>,
+    mut backgrounds: Query<&mut ViewportBackground, With<ViewportCamera>>,
+    mut ambient: ResMut<AmbientLight>,
+) {
+    if !window.enabled {
+        for (entity, ..) in &suns {
+            commands.entity(entity).despawn_recursive();
+        }
+        if let Some(brightness) = window.saved_ambient.take() {
+            ambient.brightness = brightness;
+        }
+        return;
+    }
+    let saved = *window.saved_ambient.get_or_insert(ambient.brightness);
+
+    let direction = sun_direction(window.hours_at(animation_time.seconds));
+    let height = direction.y;
+    let day = ((height + 0.1) / 0.4).clamp(0.0, 1.0);
+    // Warm near the horizon, white overhead.
+    let low = (1.0 - height.abs() / 0.3).clamp(0.0, 1.0);
+    let sunlight = Vec3::new(1.0, 0.95, 0.9).lerp(Vec3::new(1.0, 0.55, 0.3), low);
+    let zenith = Vec3::new(0.02, 0.03, 0.07).lerp(Vec3::new(0.3, 0.5, 0.85), day);
+    let horizon = zenith.lerp(Vec3::new(0.9, 0.5, 0.3), low * 0.8);
+
+    let transform = Transform::default().looking_to(-direction, Vec3::Y);
+    let illuminance = NOON_ILLUMINANCE * height.max(0.0);
+    match suns.get_single_mut() {
+        Ok((_, mut light, mut sun_transform)) => {
+            light.illuminance = illuminance;
+            light.color = Color::srgb(sunlight.x, sunlight.y, sunlight.z);
+            *sun_transform = transform;
+        }
+        Err(_) => {
+            commands.spawn((
+                DirectionalLightBundle {
+                    directional_light: DirectionalLight {
+                        illuminance,
+                        color: Color::srgb(sunlight.x, sunlight.y, sunlight.z),
+                        shadows_enabled: true,
+                        ..default()
+                    },
+                    transform,
+                    ..default()
+                },
+                Sun,
+                Name::new("Sun"),
+            ));
+        }
+    }
+    // Moonlight keeps a tenth of the ambient light at night.
+    ambient.brightness = saved * (0.1 + 0.9 * day);
+
+    let Ok(mut background) = backgrounds.get_single_mut() else {
+        return;
+    };
+    let mut edited = background.clone();
+    match edited.mode {
+        BackgroundMode::Solid => edited.color = [zenith.x, zenith.y, zenith.z, 1.0],
+        BackgroundMode::Gradient => {
+            let interpolation = edited.sky.interpolation;
+            edited.sky = Gradient::two(horizon.to_array(), zenith.to_array());
+            edited.sky.interpolation = interpolation;
+        }
+        BackgroundMode::Environment => edited.environment_brightness = 50.0 + 1950.0 * day,
+        BackgroundMode::Checkerboard => {}
+    }
+    if edited != *background {
+        *background = edited;
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use crate::{scene::CustomMesh, selection::Selection, RenderCube, ViewportCamera};
+
+/// Side of the baked decal texture in pixels.
+const DECAL_SIZE: u32 = 512;
+/// Quads per cube face edge. Projected UVs are interpolated per triangle, so faces are
+/// subdivided to keep the perspective error invisible.
+const SUBDIVISIONS: u32 = 16;
+
+/// Projects the 2D painting onto selected cubes from the viewport camera, as if the canvas
+/// were laid over the viewport. Strokes are tinted by the surface colour, like a texture.
+pub struct DecalPlugin;
+
+impl Plugin for DecalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ProjectPainting>()
+            .add_systems(Update, project_painting_system);
+    }
+}
+
+/// The painting to project, in canvas coordinates.
+#[derive(Event)]
+pub struct ProjectPainting {
+    pub lines: Vec<Vec<Vec2>>,
+    pub canvas_size: Vec2,
+    pub color: Color,
+    pub width: f32,
+}
+
+#[allow(clippy::type_complexity)]
+fn project_painting_system(
+    mut events: EventReader<ProjectPainting>,
+    selection: Res<Selection>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ViewportCamera>>,
+    mut cubes: Query<
+        (
+            &GlobalTransform,
+            &mut Handle<Mesh>,
+            &Handle<StandardMaterial>,
+        ),
+        // Decals are projected onto a subdivided cube, which would replace a custom mesh.
+        (With<RenderCube>, Without<CustomMesh>),
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(painting) = events.read().last() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    if painting.canvas_size.min_element() <= 0.0 {
+        return;
+    }
+
+    let texture = images.add(rasterize(painting));
+    for &entity in &selection.entities {
+        let Ok((transform, mut mesh, material)) = cubes.get_mut(entity) else {
+            continue;
+        };
+        *mesh = meshes.add(projected_cube(camera, camera_transform, transform));
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color_texture = Some(texture.clone());
+        }
+    }
+}
+
+/// Draws the strokes on white, scaled from the canvas to the texture. A one-pixel border is
+/// kept clear: back faces and anything outside the view sample it.
+fn rasterize(painting: &ProjectPainting) -> Image {
+    let mut pixels = vec![255; (DECAL_SIZE * DECAL_SIZE * 4) as usize];
+    paint_strokes(painting, UVec2::splat(DECAL_SIZE), &mut pixels, 1);
+    Image::new(
+        Extent3d {
+            width: DECAL_SIZE,
+            height: DECAL_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// Draws the strokes into RGBA8 `data` of `size`, scaled from the canvas. Nothing is drawn
+/// within `margin` pixels of the edges.
+pub fn paint_strokes(painting: &ProjectPainting, size: UVec2, data: &mut [u8], margin: u32) {
+    let width = size.x as usize;
+    let color = painting.color.to_srgba().to_u8_array();
+    let scale = size.as_vec2() / painting.canvas_size;
+    let radius = (painting.width * scale.max_element() * 0.5).max(0.75);
+    let (low, high) = (
+        Vec2::splat(margin as f32),
+        size.as_vec2() - 1.0 - margin as f32,
+    );
+
+    let mut stamp = |center: Vec2| {
+        let min = (center - radius).floor().max(low);
+        let max = (center + radius).ceil().min(high);
+        if min.x > max.x || min.y > max.y {
+            return;
+        }
+        for y in min.y as usize..=max.y as usize {
+            for x in min.x as usize..=max.x as usize {
+                if Vec2::new(x as f32, y as f32).distance(center) <= radius {
+                    let i = (y * width + x) * 4;
+                    data[i..i + 4].copy_from_slice(&color);
+                }
+            }
+        }
+    };
+    for line in &painting.lines {
+        for segment in line.windows(2) {
+            let (a, b) = (segment[0] * scale, segment[1] * scale);
+            let steps = (a.distance(b) / (radius * 0.5)).ceil().max(1.0) as usize;
+            for step in 0..=steps {
+                stamp(a.lerp(b, step as f32 / steps as f32));
+            }
+        }
+    }
+}
+
+/// A unit cube with UVs set to where each vertex appears in the camera's view. Faces turned
+/// away from the camera map to the texture's blank corner.
+fn projected_cube(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    transform: &GlobalTransform,
+) -> Mesh {
+    let view_size = camera.logical_viewport_size().unwrap_or(Vec2::ONE);
+    let eye = camera_transform.translation();
+    let faces = [
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+    ];
+
+    let n = SUBDIVISIONS;
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    for (normal, u, v) in faces {
+        let normal_matrix = Mat3::from(transform.affine().matrix3).inverse().transpose();
+        let world_normal = (normal_matrix * normal).normalize_or_zero();
+        let base = positions.len() as u32;
+        for j in 0..=n {
+            for i in 0..=n {
+                let (s, t) = (i as f32 / n as f32, j as f32 / n as f32);
+                let local = normal * 0.5 + u * (s - 0.5) + v * (t - 0.5);
+                let world = transform.transform_point(local);
+                let facing = world_normal.dot(world - eye) < 0.0;
+                let uv = camera
+                    .world_to_viewport(camera_transform, world)
+                    .filter(|_| facing)
+                    .map_or(Vec2::ZERO, |screen| screen / view_size);
+                positions.push(local.to_array());
+                normals.push(normal.to_array());
+                uvs.push(uv.to_array());
+            }
+        }
+        for j in 0..n {
+            for i in 0..n {
+                let a = base + j * (n + 1) + i;
+                let b = a + 1;
+                let c = a + n + 2;
+                let d = a + n + 1;
+                indices.extend([a, b, c, c, d, a]);
+            }
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
@@ -0,0 +1,179 @@
+use bevy::{asset::AssetLoadFailedEvent, prelude::*};
+use bevy_egui::egui;
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    status_bar::StatusBar,
+};
+
+/// Oldest entries are dropped beyond this.
+const MAX_ENTRIES: usize = 200;
+
+/// A single place for recoverable failures: systems send [`AppError`] instead of only
+/// logging, and the Errors window lists them with when they happened and what to try.
+/// Shader compile errors are raised inside the render world and still only reach the log.
+pub struct ErrorsPlugin;
+
+impl Plugin for ErrorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AppError>()
+            .register_panel::<ErrorsWindow>()
+            .add_systems(
+                Update,
+                (
+                    asset_errors_system,
+                    collect_errors_system,
+                    errors_window_system.in_set(ErrorsWindow::ui_set()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// A recoverable failure to show to the user.
+#[derive(Event, Clone, PartialEq)]
+pub struct AppError {
+    /// The feature that failed, e.g. "Scene".
+    pub source: &'static str,
+    pub message: String,
+    pub suggestion: Option<&'static str>,
+}
+
+impl AppError {
+    pub fn new(source: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            source,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    pub fn suggest(mut self, suggestion: &'static str) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+}
+
+struct ErrorEntry {
+    error: AppError,
+    /// Session time of the latest occurrence.
+    seconds: f32,
+    /// Identical consecutive errors are folded into one entry.
+    count: u32,
+}
+
+#[derive(Default, Resource)]
+pub struct ErrorsWindow {
+    pub is_open: bool,
+    entries: Vec<ErrorEntry>,
+}
+
+impl Panel for ErrorsWindow {
+    const TITLE: &'static str = "Errors";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl ErrorsWindow {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+fn asset_errors_system(
+    mut failed: EventReader<AssetLoadFailedEvent<Image>>,
+    mut errors: EventWriter<AppError>,
+) {
+    for event in failed.read() {
+        errors.send(
+            AppError::new(
+                "Assets",
+                format!("Failed to load texture {}: {}", event.path, event.error),
+            )
+            .suggest("Check that the file exists under assets/ and is a supported image format."),
+        );
+    }
+}
+
+fn collect_errors_system(
+    mut events: EventReader<AppError>,
+    time: Res<Time>,
+    mut window: ResMut<ErrorsWindow>,
+    mut status: ResMut<StatusBar>,
+) {
+    for error in events.read() {
+        error!("{}: {}", error.source, error.message);
+        let seconds = time.elapsed_seconds();
+        status.flash(format!("⚠ {}", error.message), seconds);
+        match window.entries.last_mut() {
+            Some(last) if last.error == *error => {
+                last.count += 1;
+                last.seconds = seconds;
+            }
+            _ => window.entries.push(ErrorEntry {
+                error: error.clone(),
+                seconds,
+                count: 1,
+            }),
+        }
+        if window.entries.len() > MAX_ENTRIES {
+            window.entries.remove(0);
+        }
+    }
+}
+
+fn errors_window_system(mut contexts: PanelContexts, mut window: ResMut<ErrorsWindow>) {
+    let ErrorsWindow { is_open, entries } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Errors")
+        .open(is_open)
+        .default_size([420.0, 300.0])
+        .show(contexts.ctx::<ErrorsWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} errors", entries.len()));
+                if ui.button("Clear").clicked() {
+                    entries.clear();
+                }
+            });
+            ui.separator();
+            if entries.is_empty() {
+                ui.weak("Nothing has gone wrong this session.");
+                return;
+            }
+            // One line per error, so only the visible rows are laid out; the full message and
+            // suggestion are on hover.
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show_rows(ui, row_height, entries.len(), |ui, range| {
+                    for entry in entries.iter().rev().skip(range.start).take(range.len()) {
+                        let seconds = entry.seconds as u32;
+                        let row = ui.horizontal(|ui| {
+                            ui.monospace(format!("{:02}:{:02}", seconds / 60, seconds % 60));
+                            ui.strong(entry.error.source);
+                            if entry.count > 1 {
+                                ui.weak(format!("×{}", entry.count));
+                            }
+                            ui.add(
+                                egui::Label::new(
+                                    egui::RichText::new(&entry.error.message)
+                                        .color(ui.visuals().error_fg_color),
+                                )
+                                .truncate(),
+                            );
+                        });
+                        row.response.on_hover_ui(|ui| {
+                            ui.label(&entry.error.message);
+                            if let Some(suggestion) = entry.error.suggestion {
+                                ui.weak(format!("→ {suggestion}"));
+                            }
+                        });
+                    }
+                });
+        });
+}
@@ -0,0 +1,328 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::world_bounds,
+    groups::{outermost_group, Group},
+    hierarchy::entity_label,
+    history::{DriveExt, Driver},
+    numeric::slider,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+    settings::Settings,
+    viewport::Viewport,
+    ViewportCamera,
+};
+
+/// How quickly parts ease toward the explode factor, per second.
+const EASE_RATE: f32 = 8.0;
+
+/// Pulls the parts of the selected group apart to show how an assembly fits together. Each
+/// child of the group moves away from the group's centre by the explode factor times its own
+/// distance from it, easing there, with a leader line back to where it sits when assembled.
+/// Parts are moved through their transforms, so they return when the factor is brought back
+/// to zero, another group is selected or the window is closed; meanwhile an [`ExplodedPart`]
+/// keeps where each sits assembled, and that is what saving and the history see.
+pub struct ExplodedViewPlugin;
+
+impl Plugin for ExplodedViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ExplodedViewWindow>()
+            .add_systems(
+                Update,
+                (
+                    exploded_view_window_system.in_set(ExplodedViewWindow::ui_set()),
+                    explode_system,
+                    draw_exploded_view_system.after(crate::UiSet::Central),
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::View, "Exploded View…", |world| {
+                world.resource_mut::<ExplodedViewWindow>().is_open = true;
+            }));
+    }
+}
+
+#[derive(Resource)]
+pub struct ExplodedViewWindow {
+    pub is_open: bool,
+    /// How far parts move out, in multiples of their distance from the group's centre.
+    factor: f32,
+    labels: bool,
+    exploded: Option<Exploded>,
+}
+
+impl Default for ExplodedViewWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            factor: 1.0,
+            labels: true,
+            exploded: None,
+        }
+    }
+}
+
+impl Panel for ExplodedViewWindow {
+    const TITLE: &'static str = "Exploded View";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// The group whose parts are moved, and how far apart they are.
+struct Exploded {
+    group: Entity,
+    /// The factor the parts are at now, easing toward the window's.
+    current: f32,
+    parts: Vec<Part>,
+}
+
+/// A child of the exploded group. Positions are in the group's local space.
+struct Part {
+    entity: Entity,
+    /// Its translation when assembled.
+    rest: Vec3,
+    /// The centre of its bounds when assembled.
+    center: Vec3,
+    /// From the group's centre to the part's, moved along once per unit of factor.
+    offset: Vec3,
+}
+
+/// Held by a part while it is moved out, so the scene and the history see where it sits
+/// assembled instead of its display position.
+#[derive(Component)]
+pub struct ExplodedPart {
+    /// Its translation when assembled, in its parent's space.
+    pub rest: Vec3,
+}
+
+impl Exploded {
+    fn new(
+        group: Entity,
+        globals: &Query<&GlobalTransform>,
+        children: &Query<&Children>,
+        transforms: &Query<&mut Transform>,
+        bounds: &Query<(&GlobalTransform, &Aabb)>,
+    ) -> Option<Self> {
+        let to_local = globals.get(group).ok()?.affine().inverse();
+        let mut parts: Vec<Part> = children
+            .get(group)
+            .into_iter()
+            .flatten()
+            .filter_map(|&entity| {
+                let rest = transforms.get(entity).ok()?.translation;
+                let center = std::iter::once(entity)
+                    .chain(children.iter_descendants(entity))
+                    .filter_map(|e| bounds.get(e).ok())
+                    .map(|(transform, aabb)| world_bounds(transform, aabb))
+                    .reduce(|(min, max), (low, high)| (min.min(low), max.max(high)))
+                    .map(|(min, max)| (min + max) * 0.5)
+                    .or_else(|| globals.get(entity).ok().map(GlobalTransform::translation))?;
+                Some(Part {
+                    entity,
+                    rest,
+                    center: to_local.transform_point3(center),
+                    offset: Vec3::ZERO,
+                })
+            })
+            .collect();
+        if parts.is_empty() {
+            return None;
+        }
+        let middle = parts.iter().map(|part| part.center).sum::<Vec3>() / parts.len() as f32;
+        for part in &mut parts {
+            part.offset = part.center - middle;
+        }
+        Some(Self {
+            group,
+            current: 0.0,
+            parts,
+        })
+    }
+}
+
+fn exploded_view_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ExplodedViewWindow>,
+    names: Query<Option<&Name>>,
+) {
+    let ExplodedViewWindow {
+        is_open,
+        factor,
+        labels,
+        exploded,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(ExplodedViewWindow::TITLE)
+        .open(is_open)
+        .default_width(280.0)
+        .show(contexts.ctx::<ExplodedViewWindow>(), |ui| {
+            match exploded {
+                Some(exploded) => {
+                    let name = names.get(exploded.group).ok().flatten();
+                    ui.label(format!(
+                        "{}: {} parts",
+                        entity_label(exploded.group, name, true),
+                        exploded.parts.len()
+                    ));
+                }
+                None => {
+                    ui.weak("Select a group to explode its parts.");
+                }
+            }
+            ui.add(slider(factor, 0.0..=3.0).text("Explode"));
+            ui.horizontal(|ui| {
+                if ui.button("Assemble").clicked() {
+                    *factor = 0.0;
+                }
+                ui.checkbox(labels, "Labels");
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn explode_system(
+    mut commands: Commands,
+    mut window: ResMut<ExplodedViewWindow>,
+    selection: Res<Selection>,
+    time: Res<Time>,
+    parents: Query<&Parent>,
+    is_group: Query<(), With<Group>>,
+    globals: Query<&GlobalTransform>,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+    mut transforms: Query<&mut Transform>,
+    marked: Query<&ExplodedPart>,
+) {
+    let selected = selection
+        .primary()
+        .map(|entity| outermost_group(entity, &parents, &is_group))
+        .filter(|entity| is_group.contains(*entity));
+    let ExplodedViewWindow {
+        is_open,
+        factor,
+        exploded,
+        ..
+    } = &mut *window;
+    if exploded
+        .as_ref()
+        .is_some_and(|e| !is_group.contains(e.group))
+    {
+        // Parts left behind by an ungrouped group go back together at once.
+        for part in exploded.take().into_iter().flat_map(|e| e.parts) {
+            if let Ok(mut transform) = transforms.get_mut(part.entity) {
+                transform.translation = part.rest;
+                commands.entity(part.entity).remove::<ExplodedPart>();
+                commands.drive(part.entity, Driver::ExplodedView, false);
+            }
+        }
+    }
+    if exploded.is_none() && *is_open {
+        *exploded = selected
+            .and_then(|group| Exploded::new(group, &globals, &children, &transforms, &bounds));
+    }
+    let Some(state) = exploded else {
+        return;
+    };
+
+    // A different group waits for this one to come back together first.
+    let switching = selected.is_some_and(|group| group != state.group);
+    let target = if *is_open && !switching { *factor } else { 0.0 };
+    let applied = state.current;
+    state.current += (target - state.current) * (1.0 - (-EASE_RATE * time.delta_seconds()).exp());
+    if (target - state.current).abs() < 1e-3 {
+        state.current = target;
+    }
+
+    state.parts.retain(|part| transforms.contains(part.entity));
+    for part in &mut state.parts {
+        let Ok(mut transform) = transforms.get_mut(part.entity) else {
+            continue;
+        };
+        // A part moved by hand while apart keeps that move when assembled.
+        let moved = transform.translation - (part.rest + part.offset * applied);
+        if moved != Vec3::ZERO {
+            part.rest += moved;
+            part.center += moved;
+        }
+        let translation = part.rest + part.offset * state.current;
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+        let apart = state.current != 0.0;
+        let marker = marked.get(part.entity).ok().map(|marker| marker.rest);
+        if apart && marker != Some(part.rest) {
+            commands
+                .entity(part.entity)
+                .insert(ExplodedPart { rest: part.rest });
+            commands.drive(part.entity, Driver::ExplodedView, true);
+        } else if !apart && marker.is_some() {
+            commands.entity(part.entity).remove::<ExplodedPart>();
+            commands.drive(part.entity, Driver::ExplodedView, false);
+        }
+    }
+    if state.current == 0.0 && (switching || !*is_open) {
+        *exploded = None;
+    }
+}
+
+/// Leader lines from each part back to where it sits when assembled, and its name beside it.
+#[allow(clippy::too_many_arguments)]
+fn draw_exploded_view_system(
+    mut contexts: EguiContexts,
+    window: Res<ExplodedViewWindow>,
+    viewport: Res<Viewport>,
+    settings: Res<Settings>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ViewportCamera>>,
+    globals: Query<&GlobalTransform>,
+    names: Query<(Option<&Name>, Has<Group>)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(exploded) = window.exploded.as_ref().filter(|e| e.current > 0.0) else {
+        return;
+    };
+    let Ok(group) = globals.get(exploded.group) else {
+        return;
+    };
+    let color = settings.highlights().info;
+    let camera = cameras.get_single().ok();
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background())
+        .with_clip_rect(viewport.rect);
+    let font = egui::FontId::proportional(12.0);
+    for part in &exploded.parts {
+        let assembled = group.transform_point(part.center);
+        let apart = group.transform_point(part.center + part.offset * exploded.current);
+        gizmos.line(assembled, apart, color);
+        gizmos.sphere(assembled, Quat::IDENTITY, 0.04, color);
+
+        let Some((camera, camera_transform)) = camera.filter(|_| window.labels) else {
+            continue;
+        };
+        let Some(pixel) = camera.world_to_viewport(camera_transform, apart) else {
+            continue;
+        };
+        let (name, is_group) = names.get(part.entity).unwrap_or_default();
+        let galley = painter.layout_no_wrap(
+            entity_label(part.entity, name, is_group),
+            font.clone(),
+            egui::Color32::WHITE,
+        );
+        let rect = egui::Rect::from_min_size(
+            viewport.screen_pos(pixel) + egui::vec2(8.0, -galley.size().y - 12.0),
+            galley.size() + egui::vec2(8.0, 4.0),
+        );
+        painter.rect_filled(rect, 3.0, egui::Color32::from_black_alpha(160));
+        painter.galley(
+            rect.min + egui::vec2(4.0, 2.0),
+            galley,
+            egui::Color32::WHITE,
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! A small arithmetic expression language for bindings: numbers, variables, `+ - * / % ^`,
+//! comparisons, parentheses and a handful of functions.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+    Less,
+    Greater,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Sqrt,
+    Floor,
+    Fract,
+    Min,
+    Max,
+    Clamp,
+    Lerp,
+    Step,
+}
+
+impl Function {
+    const ALL: [(&'static str, Function, usize); 12] = [
+        ("sin", Function::Sin, 1),
+        ("cos", Function::Cos, 1),
+        ("tan", Function::Tan, 1),
+        ("abs", Function::Abs, 1),
+        ("sqrt", Function::Sqrt, 1),
+        ("floor", Function::Floor, 1),
+        ("fract", Function::Fract, 1),
+        ("min", Function::Min, 2),
+        ("max", Function::Max, 2),
+        ("clamp", Function::Clamp, 3),
+        ("lerp", Function::Lerp, 3),
+        ("step", Function::Step, 2),
+    ];
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match (self, args) {
+            (Function::Sin, [x]) => x.sin(),
+            (Function::Cos, [x]) => x.cos(),
+            (Function::Tan, [x]) => x.tan(),
+            (Function::Abs, [x]) => x.abs(),
+            (Function::Sqrt, [x]) => x.sqrt(),
+            (Function::Floor, [x]) => x.floor(),
+            (Function::Fract, [x]) => x - x.floor(),
+            (Function::Min, [a, b]) => a.min(*b),
+            (Function::Max, [a, b]) => a.max(*b),
+            (Function::Clamp, [x, lo, hi]) => x.max(*lo).min(*hi),
+            (Function::Lerp, [a, b, t]) => a + (b - a) * t,
+            (Function::Step, [edge, x]) => (*x >= *edge) as u8 as f64,
+            _ => f64::NAN,
+        }
+    }
+}
+
+/// Why an expression failed to parse or evaluate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExprError(pub String);
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(char),
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let text = &source[start..end];
+            let number = text
+                .parse()
+                .map_err(|_| ExprError(format!("`{text}` is not a number")))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Identifier(source[start..end].to_owned()));
+        } else {
+            chars.next();
+            tokens.push(match c {
+                '(' => Token::Open,
+                ')' => Token::Close,
+                ',' => Token::Comma,
+                '+' | '-' | '*' | '/' | '%' | '^' | '<' | '>' => Token::Operator(c),
+                _ => return Err(ExprError(format!("unexpected `{c}`"))),
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), ExprError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(ExprError(format!("expected {what}"))),
+        }
+    }
+
+    /// Precedence climbing; `^` is right-associative and binds tighter than unary minus.
+    fn expression(&mut self, min_precedence: u8) -> Result<Expr, ExprError> {
+        let mut left = self.unary()?;
+        while let Some(Token::Operator(c)) = self.peek() {
+            let (op, precedence, right_associative) = match c {
+                '<' => (BinaryOp::Less, 1, false),
+                '>' => (BinaryOp::Greater, 1, false),
+                '+' => (BinaryOp::Add, 2, false),
+                '-' => (BinaryOp::Subtract, 2, false),
+                '*' => (BinaryOp::Multiply, 3, false),
+                '/' => (BinaryOp::Divide, 3, false),
+                '%' => (BinaryOp::Remainder, 3, false),
+                _ => (BinaryOp::Power, 5, true),
+            };
+            if precedence < min_precedence {
+                break;
+            }
+            self.next();
+            let next = if right_associative {
+                precedence
+            } else {
+                precedence + 1
+            };
+            let right = self.expression(next)?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ExprError> {
+        if self.peek() == Some(&Token::Operator('-')) {
+            self.next();
+            return Ok(Expr::Negate(Box::new(self.expression(4)?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, ExprError> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Open) => {
+                let inner = self.expression(0)?;
+                self.expect(Token::Close, "`)`")?;
+                Ok(inner)
+            }
+            Some(Token::Identifier(name)) if self.peek() == Some(&Token::Open) => {
+                let (_, function, arity) = Function::ALL
+                    .into_iter()
+                    .find(|(function, ..)| *function == name)
+                    .ok_or_else(|| ExprError(format!("unknown function `{name}`")))?;
+                self.next();
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::Close) {
+                    loop {
+                        args.push(self.expression(0)?);
+                        if self.peek() != Some(&Token::Comma) {
+                            break;
+                        }
+                        self.next();
+                    }
+                }
+                self.expect(Token::Close, "`)`")?;
+                if args.len() != arity {
+                    return Err(ExprError(format!(
+                        "`{name}` takes {arity} argument{}",
+                        if arity == 1 { "" } else { "s" }
+                    )));
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Identifier(name)) => match name.as_str() {
+                "pi" => Ok(Expr::Number(std::f64::consts::PI)),
+                "tau" => Ok(Expr::Number(std::f64::consts::TAU)),
+                _ => Ok(Expr::Variable(name)),
+            },
+            Some(token) => Err(ExprError(format!("unexpected {token:?}"))),
+            None => Err(ExprError("unexpected end of expression".to_owned())),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let expr = parser.expression(0)?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(ExprError(format!("unexpected {token:?}"))),
+        }
+    }
+
+    /// Evaluates with `variable` resolving names; unknown names are an error.
+    pub fn eval(&self, variable: &impl Fn(&str) -> Option<f64>) -> Result<f64, ExprError> {
+        Ok(match self {
+            Expr::Number(number) => *number,
+            Expr::Variable(name) => {
+                variable(name).ok_or_else(|| ExprError(format!("unknown variable `{name}`")))?
+            }
+            Expr::Negate(inner) => -inner.eval(variable)?,
+            Expr::Binary(left, op, right) => {
+                let (a, b) = (left.eval(variable)?, right.eval(variable)?);
+                match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Subtract => a - b,
+                    BinaryOp::Multiply => a * b,
+                    BinaryOp::Divide => a / b,
+                    BinaryOp::Remainder => a.rem_euclid(b),
+                    BinaryOp::Power => a.powf(b),
+                    BinaryOp::Less => (a < b) as u8 as f64,
+                    BinaryOp::Greater => (a > b) as u8 as f64,
+                }
+            }
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(variable))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.apply(&args)
+            }
+        })
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    icons::{Icon, IconButtonsExt},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+    timeline::{AnimationTime, TimelineMarkers},
+};
+
+/// Keys closer than this to the playhead are replaced rather than added next to.
+const KEY_TOLERANCE: f32 = 0.05;
+/// Length of the fades the quick buttons key, in seconds.
+const QUICK_FADE: f32 = 1.0;
+
+/// Keyframed opacity for cubes, played back from the animation timeline so objects can be
+/// revealed and hidden during camera paths and recordings.
+pub struct FadePlugin;
+
+impl Plugin for FadePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<FadeWindow>().add_systems(
+            Update,
+            (
+                fade_window_system.in_set(FadeWindow::ui_set()),
+                apply_fade_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Opacity keys of one entity, multiplied into its material's alpha.
+#[derive(Component, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpacityTrack {
+    /// `(animation seconds, opacity)`, sorted by time.
+    pub keys: Vec<(f32, f32)>,
+    /// The material's own alpha and blend mode, captured when the track first applied.
+    #[serde(skip)]
+    rest: Option<(f32, AlphaMode)>,
+}
+
+impl OpacityTrack {
+    /// Interpolated between keys and held past either end, so scrubbing reproduces it.
+    pub fn opacity_at(&self, seconds: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return 1.0;
+        };
+        if seconds <= first.0 {
+            return first.1;
+        }
+        if seconds >= last.0 {
+            return last.1;
+        }
+        let next = self.keys.partition_point(|(time, _)| *time <= seconds);
+        let ((t0, o0), (t1, o1)) = (self.keys[next - 1], self.keys[next]);
+        o0 + (o1 - o0) * ((seconds - t0) / (t1 - t0))
+    }
+
+    /// The material alpha the track scales, for saving the colour unfaded.
+    pub fn rest_alpha(&self) -> Option<f32> {
+        self.rest.map(|(alpha, _)| alpha)
+    }
+
+    /// The material blend mode before the track took over, for saving it unfaded.
+    pub fn rest_alpha_mode(&self) -> Option<AlphaMode> {
+        self.rest.map(|(_, alpha_mode)| alpha_mode)
+    }
+
+    fn set_key(&mut self, seconds: f32, opacity: f32) {
+        match self
+            .keys
+            .iter_mut()
+            .find(|(time, _)| (*time - seconds).abs() < KEY_TOLERANCE)
+        {
+            Some(key) => key.1 = opacity,
+            None => {
+                let index = self.keys.partition_point(|(time, _)| *time < seconds);
+                self.keys.insert(index, (seconds, opacity));
+            }
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct FadeWindow {
+    pub is_open: bool,
+}
+
+impl Panel for FadeWindow {
+    const TITLE: &'static str = "Fade";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn fade_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<FadeWindow>,
+    mut commands: Commands,
+    selection: Res<Selection>,
+    mut tracks: Query<(Option<&mut OpacityTrack>, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut animation_time: ResMut<AnimationTime>,
+) {
+    let FadeWindow { is_open } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let seconds = animation_time.seconds;
+    egui::Window::new(FadeWindow::TITLE)
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<FadeWindow>(), |ui| {
+            let Some((entity, (track, material))) = selection
+                .primary()
+                .and_then(|entity| Some((entity, tracks.get_mut(entity).ok()?)))
+            else {
+                ui.weak("Select a cube to key its opacity.");
+                return;
+            };
+            let Some(mut track) = track else {
+                ui.label("This entity has no fade keys.");
+                if ui.button("Add opacity track").clicked() {
+                    commands.entity(entity).insert(OpacityTrack {
+                        keys: vec![(seconds, 1.0)],
+                        rest: None,
+                    });
+                }
+                return;
+            };
+
+            let mut opacity = track.opacity_at(seconds);
+            ui.horizontal(|ui| {
+                ui.label("Opacity");
+                if ui
+                    .add(egui::Slider::new(&mut opacity, 0.0..=1.0))
+                    .on_hover_text("Editing keys the value at the playhead")
+                    .changed()
+                {
+                    track.set_key(seconds, opacity);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .button("◆ Key at playhead")
+                    .on_hover_text(format!("Key {opacity:.2} at {seconds:.2} s"))
+                    .clicked()
+                {
+                    track.set_key(seconds, opacity);
+                }
+                if ui
+                    .button("Fade in")
+                    .on_hover_text(format!("Go from hidden to opaque over {QUICK_FADE} s"))
+                    .clicked()
+                {
+                    track.set_key(seconds, 0.0);
+                    track.set_key(seconds + QUICK_FADE, 1.0);
+                }
+                if ui
+                    .button("Fade out")
+                    .on_hover_text(format!("Go from opaque to hidden over {QUICK_FADE} s"))
+                    .clicked()
+                {
+                    track.set_key(seconds, 1.0);
+                    track.set_key(seconds + QUICK_FADE, 0.0);
+                }
+            });
+
+            ui.separator();
+            let mut removed = None;
+            egui::Grid::new("fade_keys")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, (time, key)) in track.keys.iter().enumerate() {
+                        if ui.link(format!("{time:.2} s")).clicked() {
+                            animation_time.seconds = *time;
+                        }
+                        ui.monospace(format!("{key:.2}"));
+                        if ui
+                            .small_icon_button(Icon::Delete, "")
+                            .on_hover_text("Delete key")
+                            .clicked()
+                        {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = removed {
+                track.keys.remove(index);
+            }
+            if ui.button("Remove opacity track").clicked() {
+                if let (Some((alpha, alpha_mode)), Some(material)) =
+                    (track.rest, materials.get_mut(material))
+                {
+                    material.base_color.set_alpha(alpha);
+                    material.alpha_mode = alpha_mode;
+                }
+                commands.entity(entity).remove::<OpacityTrack>();
+            }
+        });
+}
+
+/// Writes each track's opacity into its material, blending only while it is see-through.
+fn apply_fade_system(
+    animation_time: Res<AnimationTime>,
+    mut tracks: Query<(&mut OpacityTrack, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut markers: ResMut<TimelineMarkers>,
+) {
+    let mut keys = Vec::new();
+    for (mut track, handle) in &mut tracks {
+        keys.extend(track.keys.iter().map(|(time, _)| *time));
+        let Some(material) = materials.get(handle) else {
+            continue;
+        };
+        if track.rest.is_none() {
+            track.rest = Some((material.base_color.alpha(), material.alpha_mode));
+        }
+        let Some((alpha, alpha_mode)) = track.rest else {
+            continue;
+        };
+        let faded = alpha * track.opacity_at(animation_time.seconds);
+        let mode = if faded < 1.0 {
+            AlphaMode::Blend
+        } else {
+            alpha_mode
+        };
+        // Only touch the asset when something changed, so idle tracks cost no re-upload.
+        if material.base_color.alpha() != faded || material.alpha_mode != mode {
+            let material = materials.get_mut(handle).unwrap();
+            material.base_color.set_alpha(faded);
+            material.alpha_mode = mode;
+        }
+    }
+    keys.sort_by(f32::total_cmp);
+    keys.dedup();
+    markers.set("Fade", keys);
+}
@@ -0,0 +1,90 @@
+use ab_glyph::Font as _;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::{errors::AppError, settings::Settings};
+
+/// Installs the fallback font chain from the settings after egui's built-in fonts, so labels,
+/// text fields, notes and entity names can show Chinese, Japanese, Korean and other scripts
+/// those fonts lack. egui uses the first font in the chain with a glyph for a character, and
+/// 3D text labels are laid out with the same fonts.
+pub struct FontsPlugin;
+
+impl Plugin for FontsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FallbackFonts>()
+            .add_systems(Update, apply_fonts_system);
+    }
+}
+
+#[derive(Default, Resource)]
+struct FallbackFonts {
+    /// The chain the definitions were built from, `None` before the first build.
+    built_from: Option<Vec<String>>,
+    definitions: egui::FontDefinitions,
+}
+
+/// Rebuilds the font definitions when the chain changes, and gives them to every egui context,
+/// including those of windows opened later.
+fn apply_fonts_system(
+    settings: Res<Settings>,
+    mut fonts: ResMut<FallbackFonts>,
+    mut contexts: Query<&mut EguiContext>,
+    mut errors: EventWriter<AppError>,
+) {
+    let chain = &settings.fonts.fallbacks;
+    let rebuilt = fonts.built_from.as_ref() != Some(chain);
+    if rebuilt {
+        fonts.definitions = font_definitions(chain, &mut errors);
+        fonts.built_from = Some(chain.clone());
+    }
+    for mut context in &mut contexts {
+        if rebuilt || context.is_added() {
+            context.get_mut().set_fonts(fonts.definitions.clone());
+        }
+    }
+}
+
+fn font_definitions(chain: &[String], errors: &mut EventWriter<AppError>) -> egui::FontDefinitions {
+    let mut definitions = egui::FontDefinitions::default();
+    for path in chain {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            // The defaults list fonts for every platform; only some exist on any one system.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                errors.send(AppError::new("Fonts", format!("Cannot read {path}: {err}")));
+                continue;
+            }
+        };
+        // egui panics on fonts it cannot parse, so they are checked first.
+        if let Err(err) = check_font(&bytes) {
+            errors.send(
+                AppError::new("Fonts", format!("Cannot use {path}: {err}"))
+                    .suggest("Pick a TrueType or OpenType font in Settings › Theme."),
+            );
+            continue;
+        }
+        info!("Using fallback font {path}");
+        definitions
+            .font_data
+            .insert(path.clone(), egui::FontData::from_owned(bytes));
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            definitions
+                .families
+                .entry(family)
+                .or_default()
+                .push(path.clone());
+        }
+    }
+    definitions
+}
+
+/// Whether egui can load the first face of a font file.
+fn check_font(bytes: &[u8]) -> Result<(), String> {
+    let font = ab_glyph::FontRef::try_from_slice(bytes).map_err(|err| err.to_string())?;
+    match font.units_per_em() {
+        Some(units) if (16.0..=16384.0).contains(&units) => Ok(()),
+        _ => Err("its units per em are out of range".to_owned()),
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::{
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        renderer::RenderDevice,
+        Render, RenderApp, RenderSet,
+    },
+};
+
+use crate::status_bar::StatusBar;
+
+/// Records one frame in RenderDoc, for debugging the render-to-texture and egui passes. wgpu
+/// only reaches RenderDoc when the sandbox was launched from it, so without it the request
+/// does nothing beyond a warning in the log.
+pub struct FrameCapturePlugin;
+
+impl Plugin for FrameCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameCapture>()
+            .add_plugins(ExtractResourcePlugin::<FrameCapture>::default())
+            .add_systems(First, finish_capture_request_system);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.add_systems(
+            Render,
+            (
+                start_capture_system.in_set(RenderSet::PrepareAssets),
+                stop_capture_system.in_set(RenderSet::Cleanup),
+            ),
+        );
+    }
+}
+
+#[derive(Clone, Resource, ExtractResource)]
+pub struct FrameCapture {
+    /// Set for the one frame that is captured, from the UI frame that asked for it.
+    pending: bool,
+    /// Whether RenderDoc is injected into the process, when that can be told. wgpu only
+    /// looks for a library that is already loaded, so this decides if a capture happens.
+    renderdoc: Option<bool>,
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        let renderdoc = if cfg!(target_os = "linux") {
+            std::fs::read_to_string("/proc/self/maps")
+                .ok()
+                .map(|maps| maps.contains("librenderdoc"))
+        } else {
+            None
+        };
+        Self {
+            pending: false,
+            renderdoc,
+        }
+    }
+}
+
+impl FrameCapture {
+    pub fn request(&mut self) {
+        self.pending = true;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    pub fn renderdoc_loaded(&self) -> Option<bool> {
+        self.renderdoc
+    }
+}
+
+/// The request is made during `Update` and extracted at the end of that frame, so it is
+/// cleared at the start of the next one to capture a single frame.
+fn finish_capture_request_system(
+    mut capture: ResMut<FrameCapture>,
+    mut status: ResMut<StatusBar>,
+    time: Res<Time>,
+) {
+    if !capture.pending {
+        return;
+    }
+    capture.pending = false;
+    status.flash("Sent a frame to RenderDoc", time.elapsed_seconds());
+}
+
+fn start_capture_system(capture: Res<FrameCapture>, device: Res<RenderDevice>) {
+    if capture.pending {
+        info!("Starting a RenderDoc frame capture");
+        device.wgpu_device().start_capture();
+    }
+}
+
+fn stop_capture_system(capture: Res<FrameCapture>, device: Res<RenderDevice>) {
+    if capture.pending {
+        device.wgpu_device().stop_capture();
+    }
+}
@@ -0,0 +1,700 @@
+use std::collections::VecDeque;
+
+use bevy::{math::URect, prelude::*, render::primitives::Aabb};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::{world_bounds, CameraWindow},
+    errors::AppError,
+    groups::Group,
+    hierarchy::entity_label,
+    numeric::{drag_value, unit_slider, Unit},
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests, ReadbackSource},
+    report::encode_png,
+    selection::Selection,
+    status_bar::StatusBar,
+    viewport::Viewport,
+    ViewImage, ViewportCamera,
+};
+
+/// Folder the catalog captures are written to, one PNG per object.
+const CATALOG_DIR: &str = "catalog";
+/// Frames a catalog view is left to render before it is read back, so transforms and the
+/// clipping planes have caught up with the camera.
+const SETTLE_FRAMES: u8 = 3;
+
+/// The Framing window: letterbox guides for a chosen aspect ratio and composition overlays
+/// over the viewport, so a capture can be composed before it is taken. The camera can be fitted
+/// to the selection from a preset angle with padding inside the frame, and a catalog captures
+/// each selected object that way in turn.
+pub struct FramingPlugin;
+
+impl Plugin for FramingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<FramingWindow>()
+            .add_systems(
+                Update,
+                (
+                    framing_window_system.in_set(FramingWindow::ui_set()),
+                    fit_selection_system,
+                    catalog_capture_system,
+                    letterbox_system,
+                    composition_system,
+                )
+                    .chain()
+                    .after(crate::UiSet::Central),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::View, "Rule of Thirds", |world| {
+                    world.resource_mut::<FramingWindow>().overlays.thirds ^= true;
+                })
+                .separator_before(),
+            )
+            .add_menu_item(MenuItem::new(Menu::View, "Center Cross", |world| {
+                world.resource_mut::<FramingWindow>().overlays.center ^= true;
+            }))
+            .add_menu_item(MenuItem::new(Menu::View, "Golden Ratio", |world| {
+                world.resource_mut::<FramingWindow>().overlays.golden ^= true;
+            }))
+            .add_menu_item(MenuItem::new(Menu::View, "Title-Safe Margins", |world| {
+                world.resource_mut::<FramingWindow>().overlays.title_safe ^= true;
+            }));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AspectPreset {
+    Off,
+    Wide,
+    Standard,
+    Square,
+    Custom,
+    /// The size of the image screenshots and reports are captured from.
+    MatchExport,
+}
+
+impl AspectPreset {
+    const ALL: [Self; 6] = [
+        Self::Off,
+        Self::Wide,
+        Self::Standard,
+        Self::Square,
+        Self::Custom,
+        Self::MatchExport,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Wide => "16:9",
+            Self::Standard => "4:3",
+            Self::Square => "1:1",
+            Self::Custom => "Custom",
+            Self::MatchExport => "Match export resolution",
+        }
+    }
+}
+
+/// The direction an object is fitted from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FitAngle {
+    Front,
+    ThreeQuarter,
+    Top,
+}
+
+impl FitAngle {
+    const ALL: [Self; 3] = [Self::Front, Self::ThreeQuarter, Self::Top];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Front => "Front",
+            Self::ThreeQuarter => "3/4",
+            Self::Top => "Top",
+        }
+    }
+
+    /// The camera's forward and up directions.
+    fn orientation(self) -> (Vec3, Vec3) {
+        match self {
+            Self::Front => (Vec3::NEG_Z, Vec3::Y),
+            // Half-way round from the front and 30° up.
+            Self::ThreeQuarter => {
+                let (azimuth, elevation) = (45f32.to_radians(), 30f32.to_radians());
+                let toward_camera = Vec3::new(
+                    azimuth.sin() * elevation.cos(),
+                    elevation.sin(),
+                    azimuth.cos() * elevation.cos(),
+                );
+                (-toward_camera, Vec3::Y)
+            }
+            // The front at the bottom of the frame.
+            Self::Top => (Vec3::NEG_Y, Vec3::NEG_Z),
+        }
+    }
+}
+
+/// Guides drawn inside the frame.
+#[derive(Default, Clone, Copy)]
+pub struct CompositionOverlays {
+    pub thirds: bool,
+    pub center: bool,
+    pub golden: bool,
+    /// Action-safe (93%) and title-safe (90%) rectangles.
+    pub title_safe: bool,
+}
+
+#[derive(Resource)]
+pub struct FramingWindow {
+    pub is_open: bool,
+    pub preset: AspectPreset,
+    custom: [u32; 2],
+    /// Opacity of the bars outside the frame.
+    opacity: f32,
+    pub overlays: CompositionOverlays,
+    fit_angle: FitAngle,
+    /// Space kept clear around a fitted object, as a fraction of the frame on each side.
+    padding: f32,
+    /// Fit the camera to the selection this frame.
+    fit_requested: bool,
+    catalog: Option<Catalog>,
+}
+
+/// Capturing each of a list of objects, fitted alone, to its own file.
+struct Catalog {
+    queue: VecDeque<Entity>,
+    /// The object in view, and how many frames it has left to settle, or `None` once its
+    /// readback is requested.
+    current: Option<(Entity, Option<u8>)>,
+    total: usize,
+    saved: usize,
+    /// Put back when the catalog is done, since the fitted camera and the cleared selection,
+    /// whose highlights would show in the captures, are only for its sake.
+    camera: Transform,
+    ortho_height: f32,
+    selection: Vec<Entity>,
+}
+
+impl Default for FramingWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            preset: AspectPreset::Off,
+            custom: [21, 9],
+            opacity: 0.6,
+            overlays: default(),
+            fit_angle: FitAngle::ThreeQuarter,
+            padding: 0.1,
+            fit_requested: false,
+            catalog: None,
+        }
+    }
+}
+
+impl Panel for FramingWindow {
+    const TITLE: &'static str = "Framing";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl FramingWindow {
+    /// Width over height of the frame, or `None` when framing is off.
+    pub fn aspect(&self, viewport: &Viewport) -> Option<f32> {
+        let [width, height] = match self.preset {
+            AspectPreset::Off => return None,
+            AspectPreset::Wide => [16, 9],
+            AspectPreset::Standard => [4, 3],
+            AspectPreset::Square => [1, 1],
+            AspectPreset::Custom => self.custom,
+            AspectPreset::MatchExport => viewport.image_size.to_array(),
+        };
+        (width > 0 && height > 0).then(|| width as f32 / height as f32)
+    }
+
+    /// The largest rect of the framing aspect centred in the viewport; the whole viewport when off.
+    pub fn frame_rect(&self, viewport: &Viewport) -> egui::Rect {
+        let rect = viewport.rect;
+        let Some(aspect) = self.aspect(viewport) else {
+            return rect;
+        };
+        let size = if rect.width() / rect.height() > aspect {
+            egui::vec2(rect.height() * aspect, rect.height())
+        } else {
+            egui::vec2(rect.width(), rect.width() / aspect)
+        };
+        egui::Rect::from_center_size(rect.center(), size)
+    }
+
+    /// The frame's share of the viewport's width and height.
+    fn frame_scale(&self, viewport: &Viewport) -> Vec2 {
+        let rect = viewport.rect;
+        if !rect.is_positive() {
+            return Vec2::ONE;
+        }
+        let frame = self.frame_rect(viewport);
+        Vec2::new(frame.width() / rect.width(), frame.height() / rect.height())
+    }
+
+    /// The frame in viewport image pixels, or `None` for the whole image.
+    fn frame_pixels(&self, viewport: &Viewport) -> Option<URect> {
+        self.aspect(viewport)?;
+        let frame = self.frame_rect(viewport);
+        let to_pixel = |pos: egui::Pos2| {
+            let uv = (pos - viewport.rect.min) / viewport.rect.size();
+            (Vec2::new(uv.x, uv.y) * viewport.image_size.as_vec2())
+                .round()
+                .as_uvec2()
+        };
+        let (min, max) = (to_pixel(frame.min), to_pixel(frame.max));
+        Some(URect::new(min.x, min.y, max.x, max.y))
+    }
+}
+
+fn framing_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<FramingWindow>,
+    viewport: Res<Viewport>,
+    selection: Res<Selection>,
+    camera_window: Res<CameraWindow>,
+    cameras: Query<&Transform, With<ViewportCamera>>,
+) {
+    let FramingWindow {
+        is_open,
+        preset,
+        custom,
+        opacity,
+        overlays,
+        fit_angle,
+        padding,
+        fit_requested,
+        catalog,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Framing")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<FramingWindow>(), |ui| {
+            egui::ComboBox::from_label("Aspect ratio")
+                .selected_text(preset.label())
+                .show_ui(ui, |ui| {
+                    for option in AspectPreset::ALL {
+                        ui.selectable_value(preset, option, option.label());
+                    }
+                });
+            match preset {
+                AspectPreset::Custom => {
+                    ui.horizontal(|ui| {
+                        ui.add(drag_value(&mut custom[0]).range(1..=100));
+                        ui.label(":");
+                        ui.add(drag_value(&mut custom[1]).range(1..=100));
+                    });
+                }
+                AspectPreset::MatchExport => {
+                    ui.weak(format!(
+                        "Export resolution {}×{}",
+                        viewport.image_size.x, viewport.image_size.y
+                    ));
+                }
+                _ => {}
+            }
+            ui.add(egui::Slider::new(opacity, 0.0..=1.0).text("Bar opacity"));
+            ui.separator();
+            ui.checkbox(&mut overlays.thirds, "Rule of thirds");
+            ui.checkbox(&mut overlays.center, "Center cross");
+            ui.checkbox(&mut overlays.golden, "Golden ratio");
+            ui.checkbox(&mut overlays.title_safe, "Title-safe margins");
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Fit from");
+                for angle in FitAngle::ALL {
+                    ui.selectable_value(fit_angle, angle, angle.label());
+                }
+            });
+            ui.add(unit_slider(padding, 0.0..=0.4, Unit::Percent).text("Padding"));
+            match catalog {
+                Some(running) => {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::ProgressBar::new(running.saved as f32 / running.total as f32)
+                                .text(format!("Capturing {} / {}…", running.saved, running.total)),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            running.queue.clear();
+                            running.current = None;
+                        }
+                    });
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        let any = !selection.entities.is_empty();
+                        if ui
+                            .add_enabled(any, egui::Button::new("Fit Selection"))
+                            .clicked()
+                        {
+                            *fit_requested = true;
+                        }
+                        let count = selection.entities.len();
+                        if ui
+                            .add_enabled(any, egui::Button::new(format!("Capture Each ({count})")))
+                            .on_hover_text(format!(
+                                "Fit each selected object in turn and save it to {CATALOG_DIR}/"
+                            ))
+                            .clicked()
+                        {
+                            let camera = cameras.get_single().copied().unwrap_or_default();
+                            *catalog = Some(Catalog {
+                                queue: selection.entities.iter().copied().collect(),
+                                current: None,
+                                total: count,
+                                saved: 0,
+                                camera,
+                                ortho_height: camera_window.ortho_height(),
+                                selection: selection.entities.clone(),
+                            });
+                        }
+                    });
+                }
+            }
+        });
+}
+
+/// The world bounds of `entity` and everything under it.
+fn subtree_bounds(
+    entity: Entity,
+    children: &Query<&Children>,
+    bounds: &Query<(&GlobalTransform, &Aabb)>,
+) -> Option<(Vec3, Vec3)> {
+    std::iter::once(entity)
+        .chain(children.iter_descendants(entity))
+        .filter_map(|e| bounds.get(e).ok())
+        .map(|(transform, aabb)| world_bounds(transform, aabb))
+        .reduce(|(min, max), (low, high)| (min.min(low), max.max(high)))
+}
+
+/// Aims the camera from the window's fit angle at the box `min..max` and brings it just near
+/// enough for the box to fill the frame inside the padding. An orthographic camera is zoomed
+/// instead, and set directly so the Camera window's easing has nothing left to do.
+fn fit_camera(
+    transform: &mut Transform,
+    projection: &mut Projection,
+    camera_window: &mut CameraWindow,
+    (min, max): (Vec3, Vec3),
+    window: &FramingWindow,
+    viewport: &Viewport,
+) {
+    let (forward, up) = window.fit_angle.orientation();
+    let center = (min + max) * 0.5;
+    *transform = Transform::from_translation(center).looking_to(forward, up);
+    transform.rotate_local_z(camera_window.applied_roll());
+    let (right, up, forward) = (*transform.right(), *transform.up(), *transform.forward());
+
+    let scale = window.frame_scale(viewport) * (1.0 - 2.0 * window.padding).max(0.05);
+    let aspect = viewport.image_size.x.max(1) as f32 / viewport.image_size.y.max(1) as f32;
+    // Corners relative to the centre, as right, up and forward distances.
+    let corners = (0..8).map(|corner| {
+        let point = Vec3::select(
+            BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+            max,
+            min,
+        ) - center;
+        Vec3::new(point.dot(right), point.dot(up), point.dot(forward))
+    });
+    match projection {
+        Projection::Perspective(perspective) => {
+            let tan_vertical = (perspective.fov * 0.5).tan() * scale.y;
+            let tan_horizontal = (perspective.fov * 0.5).tan() * aspect * scale.x;
+            // Each corner fits once its sideways offset is within the frame at its depth.
+            let distance = corners
+                .map(|corner| {
+                    (corner.x.abs() / tan_horizontal).max(corner.y.abs() / tan_vertical) - corner.z
+                })
+                .fold(0.1, f32::max);
+            transform.translation = center - forward * distance;
+        }
+        Projection::Orthographic(orthographic) => {
+            let extent = corners.fold(Vec3::ZERO, |extent, corner| extent.max(corner.abs()));
+            let height = (extent.y / scale.y).max(extent.x / (aspect * scale.x)) * 2.0;
+            camera_window.set_orthographic(height);
+            orthographic.scale = camera_window.ortho_height();
+            transform.translation = center - forward * (extent.z * 2.0 + 1.0);
+        }
+    }
+}
+
+fn fit_selection_system(
+    mut window: ResMut<FramingWindow>,
+    mut camera_window: ResMut<CameraWindow>,
+    selection: Res<Selection>,
+    viewport: Res<Viewport>,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<ViewportCamera>>,
+) {
+    if !window.fit_requested {
+        return;
+    }
+    window.fit_requested = false;
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let Some(selected) = selection
+        .entities
+        .iter()
+        .filter_map(|&entity| subtree_bounds(entity, &children, &bounds))
+        .reduce(|(min, max), (low, high)| (min.min(low), max.max(high)))
+    else {
+        return;
+    };
+    fit_camera(
+        &mut transform,
+        &mut projection,
+        &mut camera_window,
+        selected,
+        &window,
+        &viewport,
+    );
+}
+
+/// `label` with anything that does not belong in a file name replaced.
+fn file_stem(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Steps the catalog one object at a time: fit it, let the view settle, read the frame back
+/// and save it, then move on. The camera and selection are put back at the end.
+#[allow(clippy::too_many_arguments)]
+fn catalog_capture_system(
+    mut window: ResMut<FramingWindow>,
+    mut camera_window: ResMut<CameraWindow>,
+    mut selection: ResMut<Selection>,
+    viewport: Res<Viewport>,
+    view_image: Res<ViewImage>,
+    mut requests: ResMut<ReadbackRequests>,
+    mut readbacks: EventReader<ReadbackComplete>,
+    children: Query<&Children>,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+    names: Query<(Option<&Name>, Has<Group>)>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<ViewportCamera>>,
+    mut status: ResMut<StatusBar>,
+    time: Res<Time>,
+    mut errors: EventWriter<AppError>,
+) {
+    if window.catalog.is_none() {
+        readbacks.clear();
+        return;
+    }
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    let region = window.frame_pixels(&viewport);
+    let Some(mut catalog) = window.catalog.take() else {
+        return;
+    };
+    if !selection.entities.is_empty() {
+        selection.clear();
+    }
+    if !matches!(catalog.current, Some((_, None))) {
+        readbacks.clear();
+    }
+
+    match catalog.current {
+        Some((entity, Some(0))) => {
+            requests.request_from(&view_image, region, ReadbackSource::Output);
+            catalog.current = Some((entity, None));
+        }
+        Some((entity, Some(frames))) => catalog.current = Some((entity, Some(frames - 1))),
+        Some((entity, None)) => {
+            let id = view_image.id();
+            let Some(readback) = readbacks
+                .read()
+                .filter(|readback| {
+                    readback.image == id
+                        && readback.region == region
+                        && readback.source == ReadbackSource::Output
+                })
+                .last()
+            else {
+                window.catalog = Some(catalog);
+                return;
+            };
+            let (name, is_group) = names.get(entity).unwrap_or_default();
+            let index = catalog.total - catalog.queue.len();
+            let path = format!(
+                "{CATALOG_DIR}/{index:03}-{}.png",
+                file_stem(&entity_label(entity, name, is_group))
+            );
+            let written = std::fs::create_dir_all(CATALOG_DIR)
+                .map_err(|err| err.to_string())
+                .and_then(|()| {
+                    encode_png(readback.size, &readback.data).map_err(|err| err.to_string())
+                })
+                .and_then(|png| std::fs::write(&path, png).map_err(|err| err.to_string()));
+            match written {
+                Ok(()) => catalog.saved += 1,
+                Err(err) => {
+                    errors.send(AppError::new(
+                        "Framing",
+                        format!("Failed to save {path}: {err}"),
+                    ));
+                }
+            }
+            catalog.current = None;
+        }
+        None => match catalog.queue.pop_front() {
+            Some(entity) => {
+                if let Some(object) = subtree_bounds(entity, &children, &bounds) {
+                    fit_camera(
+                        &mut transform,
+                        &mut projection,
+                        &mut camera_window,
+                        object,
+                        &window,
+                        &viewport,
+                    );
+                    catalog.current = Some((entity, Some(SETTLE_FRAMES)));
+                }
+            }
+            None => {
+                *transform = catalog.camera;
+                if let Projection::Orthographic(orthographic) = &mut *projection {
+                    camera_window.set_orthographic(catalog.ortho_height);
+                    orthographic.scale = camera_window.ortho_height();
+                }
+                selection.entities = catalog.selection;
+                status.flash(
+                    format!("Saved {} captures to {CATALOG_DIR}/", catalog.saved),
+                    time.elapsed_seconds(),
+                );
+                return;
+            }
+        },
+    }
+    window.catalog = Some(catalog);
+}
+
+fn letterbox_system(
+    mut contexts: EguiContexts,
+    window: Res<FramingWindow>,
+    viewport: Res<Viewport>,
+) {
+    if window.preset == AspectPreset::Off || !viewport.rect.is_positive() {
+        return;
+    }
+    let rect = viewport.rect;
+    let frame = window.frame_rect(&viewport);
+
+    // The viewport image is painted on the background layer, so drawing there stays under windows.
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background())
+        .with_clip_rect(rect);
+    let bar = egui::Color32::from_black_alpha((window.opacity * 255.0) as u8);
+    for bar_rect in [
+        egui::Rect::from_min_max(rect.min, egui::pos2(rect.max.x, frame.min.y)),
+        egui::Rect::from_min_max(egui::pos2(rect.min.x, frame.max.y), rect.max),
+        egui::Rect::from_min_max(
+            egui::pos2(rect.min.x, frame.min.y),
+            egui::pos2(frame.min.x, frame.max.y),
+        ),
+        egui::Rect::from_min_max(
+            egui::pos2(frame.max.x, frame.min.y),
+            egui::pos2(rect.max.x, frame.max.y),
+        ),
+    ] {
+        if bar_rect.is_positive() {
+            painter.rect_filled(bar_rect, 0.0, bar);
+        }
+    }
+    painter.rect_stroke(
+        frame,
+        0.0,
+        egui::Stroke::new(1.0, egui::Color32::from_white_alpha(160)),
+    );
+    let label = match window.preset {
+        AspectPreset::Custom => format!("{}:{}", window.custom[0], window.custom[1]),
+        AspectPreset::MatchExport => format!("{}×{}", viewport.image_size.x, viewport.image_size.y),
+        preset => preset.label().to_owned(),
+    };
+    painter.text(
+        frame.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        label,
+        egui::FontId::proportional(11.0),
+        egui::Color32::from_white_alpha(200),
+    );
+}
+
+fn composition_system(
+    mut contexts: EguiContexts,
+    window: Res<FramingWindow>,
+    viewport: Res<Viewport>,
+) {
+    let overlays = window.overlays;
+    if !viewport.rect.is_positive() {
+        return;
+    }
+    let frame = window.frame_rect(&viewport);
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background())
+        .with_clip_rect(frame);
+    let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(110));
+    // Vertical and horizontal lines at fractions of the frame.
+    let lines = |fractions: &[f32], stroke: egui::Stroke| {
+        for &t in fractions {
+            let x = egui::lerp(frame.x_range(), t);
+            let y = egui::lerp(frame.y_range(), t);
+            painter.vline(x, frame.y_range(), stroke);
+            painter.hline(frame.x_range(), y, stroke);
+        }
+    };
+
+    if overlays.thirds {
+        lines(&[1.0 / 3.0, 2.0 / 3.0], stroke);
+    }
+    if overlays.golden {
+        let phi = 1.0 / ((1.0 + 5f32.sqrt()) / 2.0);
+        lines(
+            &[1.0 - phi, phi],
+            egui::Stroke::new(
+                1.0,
+                egui::Color32::from_rgba_unmultiplied(255, 200, 80, 140),
+            ),
+        );
+    }
+    if overlays.center {
+        let center = frame.center();
+        let arm = frame.size().min_elem() * 0.04;
+        painter.hline(center.x - arm..=center.x + arm, center.y, stroke);
+        painter.vline(center.x, center.y - arm..=center.y + arm, stroke);
+    }
+    if overlays.title_safe {
+        for (scale, label) in [(0.93, "action safe"), (0.9, "title safe")] {
+            let safe = egui::Rect::from_center_size(frame.center(), frame.size() * scale);
+            painter.rect_stroke(safe, 0.0, stroke);
+            painter.text(
+                safe.right_bottom() - egui::vec2(4.0, 2.0),
+                egui::Align2::RIGHT_BOTTOM,
+                label,
+                egui::FontId::proportional(10.0),
+                egui::Color32::from_white_alpha(140),
+            );
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+
+use crate::{
+    input::{InputOwner, InputRouting},
+    keybindings::Action,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    picking::Picking,
+    scene,
+    selection::Selection,
+    settings::Settings,
+    viewport::{Viewport, ViewportTool},
+    RestRotation,
+};
+
+/// Grouping of entities under an empty parent whose origin acts as the group's pivot.
+pub struct GroupsPlugin;
+
+impl Plugin for GroupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GroupCommand>()
+            .add_systems(
+                Update,
+                (
+                    group_command_system,
+                    pick_pivot_system.after(crate::UiSet::Central),
+                    draw_groups_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Group", |world| {
+                    world.send_event(GroupCommand::Group);
+                })
+                .shortcut(Action::GroupSelection),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Ungroup", |world| {
+                    world.send_event(GroupCommand::Ungroup);
+                })
+                .shortcut(Action::UngroupSelection),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Center Pivot", |world| {
+                world.send_event(GroupCommand::CenterPivot);
+            }));
+    }
+}
+
+/// An empty parent entity; its transform is the pivot for moving, rotating and scaling the group.
+#[derive(Component)]
+pub struct Group;
+
+#[derive(Event, Clone, Copy, PartialEq, Eq)]
+pub enum GroupCommand {
+    /// Parent the selection to a new group pivoted at its collective center.
+    Group,
+    /// Dissolve the selected groups, keeping their members where they are.
+    Ungroup,
+    /// Move the primary group's pivot to the center of its members.
+    CenterPivot,
+}
+
+/// Walks up from `entity` and returns the outermost group containing it, or `entity` itself.
+pub fn outermost_group(
+    entity: Entity,
+    parents: &Query<&Parent>,
+    groups: &Query<(), With<Group>>,
+) -> Entity {
+    let mut outermost = entity;
+    let mut current = entity;
+    while let Ok(parent) = parents.get(current) {
+        current = parent.get();
+        if groups.contains(current) {
+            outermost = current;
+        }
+    }
+    outermost
+}
+
+fn group_command_system(
+    mut events: EventReader<GroupCommand>,
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    groups: Query<(Entity, &GlobalTransform, Option<&Parent>), With<Group>>,
+    children: Query<&Children>,
+    mut members: Query<(&GlobalTransform, Option<&Parent>, Option<&mut RestRotation>)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for event in events.read() {
+        match event {
+            GroupCommand::Group => {
+                let entities: Vec<Entity> = selection
+                    .entities
+                    .iter()
+                    .copied()
+                    .filter(|e| members.contains(*e))
+                    .collect();
+                if entities.is_empty() {
+                    continue;
+                }
+                let center = entities
+                    .iter()
+                    .map(|e| members.get(*e).unwrap().0.translation())
+                    .sum::<Vec3>()
+                    / entities.len() as f32;
+
+                // The group lives at the root with no rotation, so members keep their world
+                // orientation as their new local one.
+                let group = scene::spawn_group(&mut commands, Transform::from_translation(center));
+                for &entity in &entities {
+                    let parent_rotation = members
+                        .get(entity)
+                        .ok()
+                        .and_then(|(_, parent, _)| parent.map(Parent::get))
+                        .and_then(|parent| members.get(parent).ok())
+                        .map_or(Quat::IDENTITY, |(global, _, _)| {
+                            global.to_scale_rotation_translation().1
+                        });
+                    if let Ok((_, _, Some(mut rest))) = members.get_mut(entity) {
+                        rest.0 = parent_rotation * rest.0;
+                    }
+                    commands.entity(entity).set_parent_in_place(group);
+                }
+                selection.select(group);
+            }
+            GroupCommand::Ungroup => {
+                let selected: Vec<Entity> = selection
+                    .entities
+                    .iter()
+                    .copied()
+                    .filter(|e| groups.contains(*e))
+                    .collect();
+                if selected.is_empty() {
+                    continue;
+                }
+                selection.clear();
+                for group in selected {
+                    let (_, group_global, group_parent) = groups.get(group).unwrap();
+                    let group_rotation = group_global.to_scale_rotation_translation().1;
+                    let new_parent = group_parent.map(Parent::get);
+                    let parent_rotation = new_parent
+                        .and_then(|parent| members.get(parent).ok())
+                        .map_or(Quat::IDENTITY, |(global, _, _)| {
+                            global.to_scale_rotation_translation().1
+                        });
+                    for &child in children.get(group).into_iter().flatten() {
+                        if let Ok((_, _, Some(mut rest))) = members.get_mut(child) {
+                            rest.0 = parent_rotation.inverse() * group_rotation * rest.0;
+                        }
+                        match new_parent {
+                            Some(parent) => commands.entity(child).set_parent_in_place(parent),
+                            None => commands.entity(child).remove_parent_in_place(),
+                        };
+                        selection.entities.push(child);
+                    }
+                    commands.entity(group).despawn();
+                }
+            }
+            GroupCommand::CenterPivot => {
+                let Some((group, _, _)) = selection.primary().and_then(|e| groups.get(e).ok())
+                else {
+                    continue;
+                };
+                let points: Vec<Vec3> = children
+                    .iter_descendants(group)
+                    .filter_map(|e| members.get(e).ok())
+                    .map(|(global, _, _)| global.translation())
+                    .collect();
+                if points.is_empty() {
+                    continue;
+                }
+                let center = points.iter().sum::<Vec3>() / points.len() as f32;
+                move_pivot(group, center, &groups, &children, &mut transforms);
+            }
+        }
+    }
+}
+
+/// Moves `group`'s origin to the world-space `pivot` without moving its members.
+fn move_pivot(
+    group: Entity,
+    pivot: Vec3,
+    groups: &Query<(Entity, &GlobalTransform, Option<&Parent>), With<Group>>,
+    children: &Query<&Children>,
+    transforms: &mut Query<&mut Transform>,
+) {
+    let Ok((_, global, parent)) = groups.get(group) else {
+        return;
+    };
+    let delta = pivot - global.translation();
+    // The group moves by `delta` in world space; members move back by the same amount in the
+    // group's local space.
+    let local_delta = global.affine().inverse().transform_vector3(delta);
+    for &child in children.get(group).into_iter().flatten() {
+        if let Ok(mut transform) = transforms.get_mut(child) {
+            transform.translation -= local_delta;
+        }
+    }
+    let parent_delta = parent
+        .and_then(|parent| groups.get(parent.get()).ok())
+        .map_or(delta, |(_, parent_global, _)| {
+            parent_global.affine().inverse().transform_vector3(delta)
+        });
+    if let Ok(mut transform) = transforms.get_mut(group) {
+        transform.translation += parent_delta;
+    }
+}
+
+/// With `ViewportTool::PickPivot`, a click on a surface moves the selected group's pivot there.
+#[allow(clippy::too_many_arguments)]
+fn pick_pivot_system(
+    viewport: Res<Viewport>,
+    mut tool: ResMut<ViewportTool>,
+    routing: Res<InputRouting>,
+    selection: Res<Selection>,
+    picking: Picking,
+    groups: Query<(Entity, &GlobalTransform, Option<&Parent>), With<Group>>,
+    children: Query<&Children>,
+    mut transforms: Query<&mut Transform>,
+) {
+    if *tool != ViewportTool::PickPivot || !viewport.clicked || routing.pointer != InputOwner::Tool
+    {
+        return;
+    }
+    let Some(group) = selection.primary().filter(|e| groups.contains(*e)) else {
+        *tool = ViewportTool::Select;
+        return;
+    };
+    if let Some(hit) = picking.pick_pointer(&[]) {
+        move_pivot(group, hit.point, &groups, &children, &mut transforms);
+    }
+    *tool = ViewportTool::Select;
+}
+
+fn draw_groups_system(
+    selection: Res<Selection>,
+    groups: Query<&GlobalTransform, With<Group>>,
+    children: Query<&Children>,
+    members: Query<(&GlobalTransform, &Aabb)>,
+    settings: Res<Settings>,
+    mut gizmos: Gizmos,
+) {
+    let highlights = settings.highlights();
+    for &group in &selection.entities {
+        let Ok(pivot) = groups.get(group) else {
+            continue;
+        };
+        for (transform, aabb) in children
+            .iter_descendants(group)
+            .filter_map(|e| members.get(e).ok())
+        {
+            let local = Transform::from_translation(aabb.center.into())
+                .with_scale(Vec3::from(aabb.half_extents) * 2.02);
+            gizmos.cuboid(transform.mul_transform(local), highlights.info);
+        }
+        let (_, rotation, translation) = pivot.to_scale_rotation_translation();
+        gizmos.sphere(translation, rotation, 0.15, highlights.primary);
+    }
+}
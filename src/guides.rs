@@ -0,0 +1,236 @@
+use bevy_egui::egui;
+
+/// Strokes closer than this to a guide, in points, are pulled onto it.
+const SNAP_DISTANCE: f32 = 12.0;
+/// Points between minor ruler ticks; every fifth is labelled.
+const RULER_TICK: f32 = 10.0;
+/// Degrees between minor protractor ticks; every sixth is labelled.
+const PROTRACTOR_TICK: f32 = 5.0;
+const HANDLE_RADIUS: f32 = 6.0;
+
+/// Ruler and protractor guides laid over the painting canvas. Each can be dragged by its
+/// handles, and while snapping is on, strokes drawn near a guide follow its edge.
+/// Positions are relative to the canvas corner, like the stroke points.
+#[derive(Default)]
+pub struct CanvasGuides {
+    ruler: Option<Ruler>,
+    protractor: Option<Protractor>,
+    snap: bool,
+}
+
+struct Ruler {
+    start: egui::Vec2,
+    end: egui::Vec2,
+}
+
+struct Protractor {
+    center: egui::Vec2,
+    radius: f32,
+    /// Direction of the 0° line, in radians clockwise from the canvas x axis.
+    baseline: f32,
+}
+
+impl Protractor {
+    /// Angle of `point` from the baseline in degrees, counterclockwise as on paper.
+    fn angle_of(&self, point: egui::Vec2) -> f32 {
+        let offset = point - self.center;
+        let angle = (-offset.y).atan2(offset.x) + self.baseline;
+        angle.to_degrees().rem_euclid(360.0)
+    }
+
+    /// The point on the arc at `degrees` from the baseline.
+    fn point_at(&self, degrees: f32, radius: f32) -> egui::Vec2 {
+        let angle = degrees.to_radians() - self.baseline;
+        self.center + egui::vec2(angle.cos(), -angle.sin()) * radius
+    }
+}
+
+impl CanvasGuides {
+    /// Toggles for the guides, shown with the other painting controls. `size` is the canvas
+    /// size, so new guides appear in its middle.
+    pub fn ui_control(&mut self, ui: &mut egui::Ui, size: egui::Vec2) {
+        let middle = size / 2.0;
+        let mut ruler = self.ruler.is_some();
+        if ui
+            .toggle_value(&mut ruler, "📏 Ruler")
+            .on_hover_text("A straight edge to draw along; drag its ends to measure")
+            .changed()
+        {
+            self.ruler = ruler.then(|| Ruler {
+                start: middle - egui::vec2(100.0, 0.0),
+                end: middle + egui::vec2(100.0, 0.0),
+            });
+        }
+        let mut protractor = self.protractor.is_some();
+        if ui
+            .toggle_value(&mut protractor, "◔ Protractor")
+            .on_hover_text("A circle marked in degrees; drag its edge handle to turn and size it")
+            .changed()
+        {
+            self.protractor = protractor.then(|| Protractor {
+                center: middle,
+                radius: (size.min_elem() / 3.0).max(40.0),
+                baseline: 0.0,
+            });
+        }
+        ui.add_enabled(
+            self.ruler.is_some() || self.protractor.is_some(),
+            egui::Checkbox::new(&mut self.snap, "Snap"),
+        )
+        .on_hover_text("Pull strokes onto nearby guides");
+    }
+
+    /// `point` moved onto the nearest guide edge when snapping is on and one is close enough.
+    pub fn snap(&self, point: egui::Vec2) -> egui::Vec2 {
+        if !self.snap {
+            return point;
+        }
+        let mut best = (SNAP_DISTANCE, point);
+        if let Some(ruler) = &self.ruler {
+            let along = ruler.end - ruler.start;
+            let t = (point - ruler.start).dot(along) / along.length_sq().max(f32::EPSILON);
+            let on_line = ruler.start + along * t.clamp(0.0, 1.0);
+            let distance = (point - on_line).length();
+            if distance < best.0 {
+                best = (distance, on_line);
+            }
+        }
+        if let Some(protractor) = &self.protractor {
+            let offset = point - protractor.center;
+            let distance = (offset.length() - protractor.radius).abs();
+            if distance < best.0 && offset.length() > f32::EPSILON {
+                best = (
+                    distance,
+                    protractor.center + offset.normalized() * protractor.radius,
+                );
+            }
+        }
+        best.1
+    }
+
+    /// Draws the guides on the canvas in `rect` and lets their handles be dragged; handles sit
+    /// above the canvas, so dragging one does not paint. `pointer` is the stroke point being
+    /// drawn, for the protractor's angle readout.
+    pub fn ui_content(
+        &mut self,
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        painter: &egui::Painter,
+        pointer: Option<egui::Vec2>,
+    ) {
+        let color = ui.visuals().warn_fg_color;
+        let stroke = egui::Stroke::new(1.0, color);
+        let font = egui::FontId::proportional(10.0);
+        let to_screen = |point: egui::Vec2| rect.min + point;
+        let handle = |name: &str, position: egui::Vec2| {
+            let response = ui.interact(
+                egui::Rect::from_center_size(
+                    to_screen(position),
+                    egui::Vec2::splat(HANDLE_RADIUS * 2.5),
+                ),
+                ui.id().with(("canvas_guide", name)),
+                egui::Sense::drag(),
+            );
+            let fill = if response.hovered() || response.dragged() {
+                color
+            } else {
+                color.gamma_multiply(0.4)
+            };
+            painter.circle(to_screen(position), HANDLE_RADIUS, fill, stroke);
+            response.drag_delta()
+        };
+
+        if let Some(ruler) = &mut self.ruler {
+            let along = ruler.end - ruler.start;
+            let length = along.length();
+            let direction = along / length.max(f32::EPSILON);
+            let normal = direction.rot90();
+            painter.line_segment([to_screen(ruler.start), to_screen(ruler.end)], stroke);
+            for tick in 0..=(length / RULER_TICK) as usize {
+                let at = ruler.start + direction * (tick as f32 * RULER_TICK);
+                let major = tick % 5 == 0;
+                let height = if major { 10.0 } else { 5.0 };
+                painter.line_segment([to_screen(at), to_screen(at + normal * height)], stroke);
+                if major && tick > 0 {
+                    painter.text(
+                        to_screen(at + normal * 18.0),
+                        egui::Align2::CENTER_CENTER,
+                        format!("{:.0}", tick as f32 * RULER_TICK),
+                        font.clone(),
+                        color,
+                    );
+                }
+            }
+            painter.text(
+                to_screen(ruler.end - normal * 14.0),
+                egui::Align2::CENTER_CENTER,
+                format!("{length:.0} pt"),
+                font.clone(),
+                color,
+            );
+            let start = handle("ruler_start", ruler.start);
+            let end = handle("ruler_end", ruler.end);
+            let body = handle("ruler_body", ruler.start + along / 2.0);
+            ruler.start += start + body;
+            ruler.end += end + body;
+        }
+
+        if let Some(protractor) = &mut self.protractor {
+            painter.circle_stroke(to_screen(protractor.center), protractor.radius, stroke);
+            for tick in 0..(360.0 / PROTRACTOR_TICK) as usize {
+                let degrees = tick as f32 * PROTRACTOR_TICK;
+                let major = tick % 6 == 0;
+                let inner = protractor.radius - if major { 12.0 } else { 6.0 };
+                painter.line_segment(
+                    [
+                        to_screen(protractor.point_at(degrees, inner)),
+                        to_screen(protractor.point_at(degrees, protractor.radius)),
+                    ],
+                    stroke,
+                );
+                if major {
+                    painter.text(
+                        to_screen(protractor.point_at(degrees, inner - 10.0)),
+                        egui::Align2::CENTER_CENTER,
+                        format!("{degrees:.0}°"),
+                        font.clone(),
+                        color,
+                    );
+                }
+            }
+            painter.line_segment(
+                [
+                    to_screen(protractor.center),
+                    to_screen(protractor.point_at(0.0, protractor.radius)),
+                ],
+                stroke,
+            );
+            if let Some(pointer) = pointer {
+                let degrees = protractor.angle_of(pointer);
+                painter.line_segment(
+                    [
+                        to_screen(protractor.center),
+                        to_screen(protractor.point_at(degrees, protractor.radius)),
+                    ],
+                    stroke,
+                );
+                painter.text(
+                    to_screen(pointer + egui::vec2(12.0, -12.0)),
+                    egui::Align2::LEFT_BOTTOM,
+                    format!("{degrees:.1}°"),
+                    font.clone(),
+                    color,
+                );
+            }
+            let center = handle("protractor_center", protractor.center);
+            let edge_at = protractor.point_at(0.0, protractor.radius);
+            let edge = edge_at + handle("protractor_edge", edge_at);
+            protractor.center += center;
+            if edge != edge_at {
+                let offset = edge - protractor.center;
+                protractor.radius = offset.length().max(20.0);
+                protractor.baseline = -(-offset.y).atan2(offset.x);
+            }
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use bevy::{
+    pbr::RenderMaterialInstances,
+    prelude::*,
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        primitives::Aabb,
+        Render, RenderApp, RenderSet,
+    },
+};
+use bevy_egui::{egui, EguiContexts};
+use xihydra_bevy::widgets::{ColorStop, Gradient, GradientEditor};
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    viewport::Viewport,
+    RenderCube, ViewportCamera,
+};
+
+/// Colour steps of the heat gradient, one shared material each.
+const BUCKETS: usize = 16;
+
+/// Recolours cubes by a cost metric so expensive objects stand out. Only the render world
+/// sees the heat materials: the cubes keep their own, so saving or editing while the heatmap
+/// is shown is unaffected.
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeatmapAssignments>()
+            .register_panel::<HeatmapWindow>()
+            .add_plugins(ExtractResourcePlugin::<HeatmapAssignments>::default())
+            .add_systems(Startup, setup_heat_materials_system)
+            .add_systems(
+                Update,
+                (
+                    track_modifications_system,
+                    heatmap_window_system.in_set(HeatmapWindow::ui_set()),
+                    assign_heat_system,
+                    heatmap_legend_system.after(crate::UiSet::Central),
+                )
+                    .chain(),
+            );
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.add_systems(
+                Render,
+                override_heat_materials_system.in_set(RenderSet::PrepareAssets),
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HeatMetric {
+    Triangles,
+    CameraDistance,
+    LastModified,
+    Overdraw,
+}
+
+impl HeatMetric {
+    const ALL: [HeatMetric; 4] = [
+        HeatMetric::Triangles,
+        HeatMetric::CameraDistance,
+        HeatMetric::LastModified,
+        HeatMetric::Overdraw,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            HeatMetric::Triangles => "Triangle count",
+            HeatMetric::CameraDistance => "Distance from camera",
+            HeatMetric::LastModified => "Last modified",
+            HeatMetric::Overdraw => "Overdraw contribution",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            HeatMetric::Triangles => "Triangles in the entity's mesh",
+            HeatMetric::CameraDistance => "World units from the viewport camera",
+            HeatMetric::LastModified => "Hot entities were moved, scaled or recoloured recently",
+            HeatMetric::Overdraw => {
+                "Estimated share of the viewport covered, doubled for blended materials"
+            }
+        }
+    }
+
+    fn format(self, value: f32, now: f32) -> String {
+        match self {
+            HeatMetric::Triangles => format!("{value:.0}"),
+            HeatMetric::CameraDistance => format!("{value:.1}"),
+            HeatMetric::LastModified => format!("{:.0}s ago", (now - value).max(0.0)),
+            HeatMetric::Overdraw => format!("{:.1}%", value * 100.0),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct HeatmapWindow {
+    pub is_open: bool,
+    /// The metric cubes are coloured by; `None` shows their own materials.
+    pub metric: Option<HeatMetric>,
+    /// Colours from the cheapest entity at 0 to the most expensive at 1.
+    gradient: Gradient,
+    /// Value range of the last assignment, for the legend.
+    range: Option<(f32, f32)>,
+}
+
+impl Default for HeatmapWindow {
+    fn default() -> Self {
+        // Blue for cheap through green and yellow to red for expensive.
+        let gradient = Gradient::new([
+            ColorStop::new(0.0, [0.0, 0.0, 0.9]),
+            ColorStop::new(0.25, [0.0, 0.65, 0.9]),
+            ColorStop::new(0.5, [0.0, 0.9, 0.0]),
+            ColorStop::new(0.75, [0.9, 0.65, 0.0]),
+            ColorStop::new(1.0, [0.9, 0.0, 0.0]),
+        ]);
+        Self {
+            is_open: false,
+            metric: None,
+            gradient,
+            range: None,
+        }
+    }
+}
+
+impl Panel for HeatmapWindow {
+    const TITLE: &'static str = "Heatmap";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// When a cube was last edited, and the transform it had then.
+#[derive(Component)]
+struct LastModified {
+    seconds: f32,
+    translation: Vec3,
+    scale: Vec3,
+}
+
+#[derive(Resource)]
+struct HeatMaterials(Vec<Handle<StandardMaterial>>);
+
+/// The heat material each coloured entity is drawn with this frame.
+#[derive(Clone, Default, Resource, ExtractResource)]
+struct HeatmapAssignments(Vec<(Entity, AssetId<StandardMaterial>)>);
+
+fn setup_heat_materials_system(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    window: Res<HeatmapWindow>,
+) {
+    let handles = (0..BUCKETS)
+        .map(|bucket| {
+            materials.add(StandardMaterial {
+                base_color: heat_color(&window.gradient, bucket),
+                unlit: true,
+                ..default()
+            })
+        })
+        .collect();
+    commands.insert_resource(HeatMaterials(handles));
+}
+
+fn heat_color(gradient: &Gradient, bucket: usize) -> Color {
+    let [r, g, b] = gradient.sample(bucket as f32 / (BUCKETS - 1) as f32);
+    Color::linear_rgb(r, g, b)
+}
+
+#[allow(clippy::type_complexity)]
+fn track_modifications_system(
+    mut commands: Commands,
+    mut cubes: Query<
+        (Entity, &Transform, Option<&mut LastModified>),
+        (
+            With<RenderCube>,
+            Or<(Changed<Transform>, Changed<Handle<StandardMaterial>>)>,
+        ),
+    >,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    mut materials: Query<(&Handle<StandardMaterial>, &mut LastModified), With<RenderCube>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+    for (entity, transform, modified) in &mut cubes {
+        match modified {
+            // The spin animation rewrites rotations every frame, so only moves and scales count.
+            Some(mut modified) => {
+                if modified.translation != transform.translation
+                    || modified.scale != transform.scale
+                {
+                    *modified = LastModified {
+                        seconds: now,
+                        translation: transform.translation,
+                        scale: transform.scale,
+                    };
+                }
+            }
+            None => {
+                commands.entity(entity).insert(LastModified {
+                    seconds: now,
+                    translation: transform.translation,
+                    scale: transform.scale,
+                });
+            }
+        }
+    }
+    let edited: Vec<_> = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if !edited.is_empty() {
+        for (material, mut modified) in &mut materials {
+            if edited.contains(&material.id()) {
+                modified.seconds = now;
+            }
+        }
+    }
+}
+
+fn heatmap_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<HeatmapWindow>,
+    heat_materials: Option<Res<HeatMaterials>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let HeatmapWindow {
+        is_open,
+        metric,
+        gradient,
+        ..
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(HeatmapWindow::TITLE)
+        .open(is_open)
+        .default_width(260.0)
+        .show(contexts.ctx::<HeatmapWindow>(), |ui| {
+            ui.radio_value(metric, None, "Off (own materials)");
+            for option in HeatMetric::ALL {
+                ui.radio_value(metric, Some(option), option.label())
+                    .on_hover_text(option.describe());
+            }
+            ui.separator();
+            ui.label("Colours, cheap to expensive");
+            if GradientEditor::new("heat_gradient", gradient)
+                .show(ui)
+                .changed()
+            {
+                for (bucket, handle) in heat_materials
+                    .iter()
+                    .flat_map(|heat| heat.0.iter().enumerate())
+                {
+                    if let Some(material) = materials.get_mut(handle) {
+                        material.base_color = heat_color(gradient, bucket);
+                    }
+                }
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn assign_heat_system(
+    mut window: ResMut<HeatmapWindow>,
+    mut assignments: ResMut<HeatmapAssignments>,
+    heat_materials: Option<Res<HeatMaterials>>,
+    cubes: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Handle<Mesh>,
+            &Handle<StandardMaterial>,
+            Option<&Aabb>,
+            Option<&LastModified>,
+        ),
+        With<RenderCube>,
+    >,
+    camera: Query<(&GlobalTransform, &Projection), With<ViewportCamera>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    let (Some(metric), Some(heat_materials)) = (window.metric, heat_materials) else {
+        if !assignments.0.is_empty() {
+            assignments.0.clear();
+            window.range = None;
+        }
+        return;
+    };
+    let Ok((camera, projection)) = camera.get_single() else {
+        return;
+    };
+    let eye = camera.translation();
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        Projection::Orthographic(_) => std::f32::consts::FRAC_PI_4,
+    };
+
+    let values: Vec<(Entity, f32)> = cubes
+        .iter()
+        .map(|(entity, transform, mesh, material, aabb, modified)| {
+            let value = match metric {
+                HeatMetric::Triangles => meshes.get(mesh).map_or(0.0, |mesh| {
+                    let corners = mesh
+                        .indices()
+                        .map_or(mesh.count_vertices(), |indices| indices.len());
+                    (corners / 3) as f32
+                }),
+                HeatMetric::CameraDistance => eye.distance(transform.translation()),
+                HeatMetric::LastModified => modified.map_or(0.0, |modified| modified.seconds),
+                HeatMetric::Overdraw => {
+                    let aabb = aabb
+                        .copied()
+                        .unwrap_or_else(|| Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5)));
+                    let center = transform.transform_point(aabb.center.into());
+                    let radius = (Vec3::from(aabb.half_extents)
+                        * transform.compute_transform().scale)
+                        .length();
+                    let distance = eye.distance(center).max(radius);
+                    // Projected disc of the bounding sphere over the square of the view height.
+                    let extent = radius / (distance * (fov / 2.0).tan());
+                    let coverage = (std::f32::consts::FRAC_PI_4 * extent * extent).min(1.0);
+                    let blended = materials
+                        .get(material)
+                        .is_some_and(|material| material.alpha_mode != AlphaMode::Opaque);
+                    if blended {
+                        coverage * 2.0
+                    } else {
+                        coverage
+                    }
+                }
+            };
+            (entity, value)
+        })
+        .collect();
+
+    let (min, max) = values
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), (_, value)| {
+            (min.min(*value), max.max(*value))
+        });
+    window.range = (!values.is_empty()).then_some((min, max));
+    let span = (max - min).max(f32::EPSILON);
+    assignments.0 = values
+        .into_iter()
+        .map(|(entity, value)| {
+            let bucket = (((value - min) / span) * (BUCKETS - 1) as f32).round() as usize;
+            (entity, heat_materials.0[bucket.min(BUCKETS - 1)].id())
+        })
+        .collect();
+}
+
+/// Runs after the materials of visible meshes were extracted and swaps in the heat ones.
+fn override_heat_materials_system(
+    assignments: Res<HeatmapAssignments>,
+    mut instances: ResMut<RenderMaterialInstances<StandardMaterial>>,
+) {
+    for (entity, material) in &assignments.0 {
+        if let Some(instance) = instances.get_mut(entity) {
+            *instance = *material;
+        }
+    }
+}
+
+/// Gradient and value range in the viewport's top-right corner while a metric is shown.
+fn heatmap_legend_system(
+    mut contexts: EguiContexts,
+    window: Res<HeatmapWindow>,
+    viewport: Res<Viewport>,
+    time: Res<Time<Real>>,
+) {
+    let (Some(metric), Some((min, max))) = (window.metric, window.range) else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+    egui::Area::new(egui::Id::new("heatmap_legend"))
+        .pivot(egui::Align2::RIGHT_TOP)
+        .fixed_pos(viewport.rect.right_top() + egui::vec2(-8.0, 8.0))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(160.0);
+                ui.strong(metric.label());
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(160.0, 12.0), egui::Sense::hover());
+                let steps = BUCKETS as f32;
+                for bucket in 0..BUCKETS {
+                    let [r, g, b, _] = heat_color(&window.gradient, bucket)
+                        .to_srgba()
+                        .to_u8_array();
+                    let left = rect.left() + rect.width() * bucket as f32 / steps;
+                    let step = egui::Rect::from_min_size(
+                        egui::pos2(left, rect.top()),
+                        egui::vec2(rect.width() / steps, rect.height()),
+                    );
+                    ui.painter()
+                        .rect_filled(step, 0.0, egui::Color32::from_rgb(r, g, b));
+                }
+                ui.horizontal(|ui| {
+                    ui.small(metric.format(min, now));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.small(metric.format(max, now));
+                    });
+                });
+            });
+        });
+}
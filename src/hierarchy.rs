@@ -0,0 +1,251 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_egui::egui;
+
+use crate::{
+    groups::Group,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+    thumbnails::EntityTooltips,
+    RenderCube,
+};
+
+/// The Hierarchy window: groups and cubes as a tree. It is drawn from an index kept up to date
+/// by change detection, so large scenes are not re-queried and re-sorted every frame.
+pub struct HierarchyPlugin;
+
+impl Plugin for HierarchyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HierarchyIndex>()
+            .register_panel::<HierarchyWindow>()
+            .add_systems(
+                Update,
+                (
+                    update_hierarchy_index_system,
+                    hierarchy_window_system.in_set(HierarchyWindow::ui_set()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+struct Node {
+    label: String,
+    parent: Option<Entity>,
+    is_group: bool,
+}
+
+/// Every cube and group, updated only from Added/Changed/Removed.
+#[derive(Default, Resource)]
+struct HierarchyIndex {
+    nodes: HashMap<Entity, Node>,
+    /// Set whenever `nodes` changes, so the window rebuilds its rows.
+    dirty: bool,
+}
+
+/// What lists of entities call one: its name, or its kind and index.
+pub fn entity_label(entity: Entity, name: Option<&Name>, is_group: bool) -> String {
+    match name {
+        Some(name) => name.to_string(),
+        None if is_group => format!("Group {}", entity.index()),
+        None => format!("Cube {}", entity.index()),
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct HierarchyWindow {
+    pub is_open: bool,
+    filter: String,
+    collapsed: HashSet<Entity>,
+    /// Depth-first `(entity, depth)` rows for the current index, filter and collapsed groups.
+    rows: Vec<(Entity, usize)>,
+    rows_dirty: bool,
+}
+
+impl Panel for HierarchyWindow {
+    const TITLE: &'static str = "Hierarchy";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl HierarchyWindow {
+    fn rebuild_rows(&mut self, index: &HierarchyIndex) {
+        self.rows.clear();
+        let order = |a: &Entity, b: &Entity| {
+            let (na, nb) = (&index.nodes[a], &index.nodes[b]);
+            // Groups first, then by label, then by entity so equal labels stay put.
+            nb.is_group
+                .cmp(&na.is_group)
+                .then_with(|| na.label.cmp(&nb.label))
+                .then_with(|| a.cmp(b))
+        };
+
+        let filter = self.filter.trim().to_lowercase();
+        if !filter.is_empty() {
+            let mut matches: Vec<Entity> = index
+                .nodes
+                .iter()
+                .filter(|(_, node)| node.label.to_lowercase().contains(&filter))
+                .map(|(entity, _)| *entity)
+                .collect();
+            matches.sort_by(order);
+            self.rows
+                .extend(matches.into_iter().map(|entity| (entity, 0)));
+            return;
+        }
+
+        let mut children: HashMap<Option<Entity>, Vec<Entity>> = HashMap::new();
+        for (entity, node) in &index.nodes {
+            // Parents outside the index (or not yet indexed) put the entity at the top level.
+            let parent = node
+                .parent
+                .filter(|parent| index.nodes.contains_key(parent));
+            children.entry(parent).or_default().push(*entity);
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by(order);
+        }
+        let mut stack: Vec<(Entity, usize)> = children
+            .get(&None)
+            .into_iter()
+            .flatten()
+            .rev()
+            .map(|entity| (*entity, 0))
+            .collect();
+        while let Some((entity, depth)) = stack.pop() {
+            self.rows.push((entity, depth));
+            if self.collapsed.contains(&entity) {
+                continue;
+            }
+            if let Some(siblings) = children.get(&Some(entity)) {
+                stack.extend(siblings.iter().rev().map(|child| (*child, depth + 1)));
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_hierarchy_index_system(
+    mut index: ResMut<HierarchyIndex>,
+    changed: Query<
+        (Entity, Option<&Name>, Option<&Parent>, Has<Group>),
+        (
+            Or<(With<RenderCube>, With<Group>)>,
+            Or<(
+                Added<RenderCube>,
+                Added<Group>,
+                Changed<Name>,
+                Changed<Parent>,
+            )>,
+        ),
+    >,
+    mut removed_cubes: RemovedComponents<RenderCube>,
+    mut removed_groups: RemovedComponents<Group>,
+    mut removed_parents: RemovedComponents<Parent>,
+    indexed: Query<(), Or<(With<RenderCube>, With<Group>)>>,
+) {
+    let index = index.bypass_change_detection();
+    for entity in removed_cubes.read().chain(removed_groups.read()) {
+        if !indexed.contains(entity) && index.nodes.remove(&entity).is_some() {
+            index.dirty = true;
+        }
+    }
+    for entity in removed_parents.read() {
+        if let Some(node) = index.nodes.get_mut(&entity) {
+            node.parent = None;
+            index.dirty = true;
+        }
+    }
+    for (entity, name, parent, is_group) in &changed {
+        index.nodes.insert(
+            entity,
+            Node {
+                label: entity_label(entity, name, is_group),
+                parent: parent.map(Parent::get),
+                is_group,
+            },
+        );
+        index.dirty = true;
+    }
+}
+
+fn hierarchy_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<HierarchyWindow>,
+    mut index: ResMut<HierarchyIndex>,
+    mut selection: ResMut<Selection>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut tooltips: EntityTooltips,
+) {
+    if !window.is_open {
+        return;
+    }
+    if std::mem::take(&mut index.dirty) || std::mem::take(&mut window.rows_dirty) {
+        window.rebuild_rows(&index);
+    }
+
+    let HierarchyWindow {
+        is_open,
+        filter,
+        collapsed,
+        rows,
+        rows_dirty,
+    } = &mut *window;
+    egui::Window::new("Hierarchy")
+        .open(is_open)
+        .default_size([260.0, 420.0])
+        .show(contexts.ctx::<HierarchyWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(filter)
+                        .hint_text("Filter by name")
+                        .desired_width(160.0),
+                );
+                *rows_dirty |= response.changed();
+                ui.weak(format!("{} entities", index.nodes.len()));
+            });
+            ui.separator();
+
+            let additive = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            let row_height = ui.spacing().interact_size.y;
+            // Only the visible rows are laid out, however many there are.
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .show_rows(ui, row_height, rows.len(), |ui, range| {
+                    for &(entity, depth) in &rows[range] {
+                        let Some(node) = index.nodes.get(&entity) else {
+                            continue;
+                        };
+                        ui.horizontal(|ui| {
+                            ui.add_space(depth as f32 * 12.0);
+                            if node.is_group {
+                                let icon = if collapsed.contains(&entity) {
+                                    "▶"
+                                } else {
+                                    "▼"
+                                };
+                                if ui.small_button(icon).clicked() {
+                                    if !collapsed.remove(&entity) {
+                                        collapsed.insert(entity);
+                                    }
+                                    *rows_dirty = true;
+                                }
+                            }
+                            let selected = selection.entities.contains(&entity);
+                            let row = ui.selectable_label(selected, &node.label);
+                            if tooltips.on_hover(row, entity).clicked() {
+                                if additive {
+                                    selection.toggle(entity);
+                                } else {
+                                    selection.select(entity);
+                                }
+                            }
+                        });
+                    }
+                });
+        });
+}
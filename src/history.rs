@@ -0,0 +1,786 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{ecs::world::Command, prelude::*};
+use bevy_egui::egui;
+
+use crate::{
+    exploded_view::ExplodedPart,
+    groups::Group,
+    icons::Icon,
+    keybindings::Action,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{Finish, SceneEntity, SceneId, SceneReader, SceneWriter},
+    scripts::EntityScript,
+    session_stats::SessionEvent,
+    RenderCube, RestRotation, UiState,
+};
+
+/// Entries kept; the oldest are dropped first.
+const MAX_ENTRIES: usize = 100;
+/// Seconds after startup before edits are recorded, so loading the initial scene is not one.
+const SETTLE_SECS: f32 = 0.5;
+/// Consecutive scene edits of the same kind within this many seconds become one entry, so a
+/// long drag or a running script does not flood the list.
+const COALESCE_SECS: f32 = 2.0;
+/// Edits this many seconds after the last key or mouse button count as the user's, which
+/// leaves time for a click to be carried out.
+const INPUT_GRACE_SECS: f32 = 0.5;
+
+/// One undo history for the whole sandbox. Scene edits are recorded per entity as they
+/// happen: moves and material changes by comparing each changed cube with its last recorded
+/// state, additions as cubes appear and removals just before they go. Edits made while a mouse
+/// button is held become one entry once it is released. Cubes a tool or script moves every
+/// frame are left out until it lets go, and edits nobody made by hand never drop what is
+/// redoable. Vertex paint strokes are pushed by the
+/// painter and canvas strokes by the canvas. The History window lists every entry and jumps to
+/// any of them; Edit › Undo and Redo step one entry at a time.
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<History>()
+            .register_panel::<HistoryWindow>()
+            .observe(record_removal_observer)
+            .add_systems(
+                Update,
+                (
+                    record_scene_edits_system,
+                    history_window_system.in_set(HistoryWindow::ui_set()),
+                    apply_history_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Undo", |world| {
+                    world.resource_mut::<History>().undo();
+                })
+                .icon(Icon::Undo)
+                .shortcut(Action::Undo),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Redo", |world| {
+                    world.resource_mut::<History>().redo();
+                })
+                .icon(Icon::Redo)
+                .shortcut(Action::Redo),
+            );
+    }
+}
+
+enum Change {
+    /// Edits to scene entities, in the order they happened.
+    Scene(Vec<SceneEdit>),
+    /// Vertex colours of one mesh before and after a stroke.
+    Stroke {
+        mesh: AssetId<Mesh>,
+        before: Vec<[f32; 4]>,
+        after: Vec<[f32; 4]>,
+    },
+    /// A line drawn on the painting canvas.
+    CanvasStroke(Vec<egui::Vec2>),
+    /// The lines on the painting canvas when it was cleared.
+    CanvasClear(Vec<Vec<egui::Vec2>>),
+}
+
+/// One entity's part in a scene edit, matched by [`SceneId`] so it still applies after the
+/// entity was removed and brought back.
+enum SceneEdit {
+    Transform {
+        id: u64,
+        before: Transform,
+        after: Transform,
+    },
+    Material {
+        id: u64,
+        before: Look,
+        after: Look,
+    },
+    /// `entity` is taken when the addition is undone, so redoing it brings the cube back as
+    /// it was then.
+    Add {
+        id: u64,
+        group: Option<u64>,
+        entity: Option<Box<SceneEntity>>,
+    },
+    Remove {
+        group: Option<u64>,
+        entity: Box<SceneEntity>,
+    },
+}
+
+impl SceneEdit {
+    fn id(&self) -> u64 {
+        match self {
+            Self::Transform { id, .. } | Self::Material { id, .. } | Self::Add { id, .. } => *id,
+            Self::Remove { entity, .. } => entity.id,
+        }
+    }
+}
+
+/// The parts of a cube's material a scene file stores.
+#[derive(Clone, PartialEq)]
+struct Look {
+    color: [f32; 4],
+    surface: Option<[f32; 2]>,
+    finish: Option<Finish>,
+    texture: Option<(Handle<Image>, Handle<Mesh>)>,
+}
+
+impl Look {
+    fn of(entity: &SceneEntity) -> Self {
+        Self {
+            color: entity.color,
+            surface: entity.surface,
+            finish: entity.finish,
+            texture: entity.texture.clone(),
+        }
+    }
+
+    fn apply(&self, entity: &mut SceneEntity) {
+        entity.color = self.color;
+        entity.surface = self.surface;
+        entity.finish = self.finish;
+        entity.texture.clone_from(&self.texture);
+    }
+}
+
+/// A cube's authored transform and look as last recorded or restored, to tell edits from
+/// changes the history made itself.
+#[derive(Component)]
+struct Recorded {
+    transform: Transform,
+    look: Look,
+}
+
+impl Recorded {
+    fn of(entity: &SceneEntity) -> Self {
+        Self {
+            transform: entity.transform(),
+            look: Look::of(entity),
+        }
+    }
+}
+
+/// Marks a cube the history despawns itself, so its removal is not recorded as an edit.
+#[derive(Component)]
+struct Unrecorded;
+
+/// A tool that writes entities every frame.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Driver {
+    Binding,
+    ExplodedView,
+    Scatter,
+}
+
+/// Marks an entity tools are writing every frame, so the history leaves its transform and
+/// material alone instead of recording each frame as an edit. What changed while driven is
+/// recorded once the last driver lets go.
+#[derive(Component)]
+pub struct Driven(Vec<Driver>);
+
+impl Driven {
+    pub fn by(&self, driver: Driver) -> bool {
+        self.0.contains(&driver)
+    }
+}
+
+pub trait DriveExt {
+    /// Adds or removes `driver` from the tools driving `entity`.
+    fn drive(&mut self, entity: Entity, driver: Driver, driven: bool);
+}
+
+impl DriveExt for Commands<'_, '_> {
+    fn drive(&mut self, entity: Entity, driver: Driver, driven: bool) {
+        self.add(Drive {
+            entity,
+            driver,
+            driven,
+        });
+    }
+}
+
+struct Drive {
+    entity: Entity,
+    driver: Driver,
+    driven: bool,
+}
+
+impl Command for Drive {
+    fn apply(self, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(self.entity) else {
+            return;
+        };
+        let Some(mut driven) = entity.get_mut::<Driven>() else {
+            if self.driven {
+                entity.insert(Driven(vec![self.driver]));
+            }
+            return;
+        };
+        if driven.by(self.driver) == self.driven {
+            return;
+        }
+        if self.driven {
+            driven.0.push(self.driver);
+        } else {
+            driven.0.retain(|driver| *driver != self.driver);
+            if driven.0.is_empty() {
+                entity.remove::<Driven>();
+            }
+        }
+    }
+}
+
+struct HistoryEntry {
+    label: String,
+    change: Change,
+    /// Real seconds when last recorded, for coalescing.
+    at: f32,
+}
+
+#[derive(Default, Resource)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+    /// How many entries are in effect; those after it are redoable.
+    applied: usize,
+    /// Where to move `applied` to this frame.
+    target: Option<usize>,
+    /// Scene edits not yet made into an entry.
+    pending: Vec<SceneEdit>,
+    /// Set once the initial scene has settled.
+    recording: bool,
+    /// Real seconds of the last key or mouse button input.
+    last_input: f32,
+}
+
+impl History {
+    pub fn can_undo(&self) -> bool {
+        self.applied > 0
+    }
+
+    pub fn undo(&mut self) {
+        self.target = Some(self.applied.saturating_sub(1));
+    }
+
+    pub fn redo(&mut self) {
+        self.target = Some((self.applied + 1).min(self.entries.len()));
+    }
+
+    /// Records a vertex paint stroke on `mesh`.
+    pub fn push_stroke(
+        &mut self,
+        mesh: AssetId<Mesh>,
+        before: Vec<[f32; 4]>,
+        after: Vec<[f32; 4]>,
+    ) {
+        if before != after {
+            self.push(
+                "Paint stroke".to_owned(),
+                Change::Stroke {
+                    mesh,
+                    before,
+                    after,
+                },
+                0.0,
+            );
+        }
+    }
+
+    /// Records a line drawn on the painting canvas.
+    pub fn push_canvas_stroke(&mut self, line: Vec<egui::Vec2>) {
+        self.push("Canvas stroke".to_owned(), Change::CanvasStroke(line), 0.0);
+    }
+
+    /// Records clearing `lines` from the painting canvas.
+    pub fn push_canvas_clear(&mut self, lines: Vec<Vec<egui::Vec2>>) {
+        self.push("Cleared canvas".to_owned(), Change::CanvasClear(lines), 0.0);
+    }
+
+    /// Drops anything redoable, then appends.
+    fn push(&mut self, label: String, change: Change, at: f32) {
+        self.entries.truncate(self.applied);
+        self.entries.push(HistoryEntry { label, change, at });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.applied = self.entries.len();
+    }
+}
+
+/// Adds `edit` to `edits`, folding a move or material change into an earlier one of the same
+/// entity.
+fn merge(edits: &mut Vec<SceneEdit>, edit: SceneEdit) {
+    for existing in edits.iter_mut() {
+        match (existing, &edit) {
+            (
+                SceneEdit::Transform { id, after, .. },
+                SceneEdit::Transform {
+                    id: edited,
+                    after: latest,
+                    ..
+                },
+            ) if id == edited => {
+                *after = *latest;
+                return;
+            }
+            (
+                SceneEdit::Material { id, after, .. },
+                SceneEdit::Material {
+                    id: edited,
+                    after: latest,
+                    ..
+                },
+            ) if id == edited => {
+                after.clone_from(latest);
+                return;
+            }
+            _ => {}
+        }
+    }
+    edits.push(edit);
+}
+
+/// Names a scene edit by what happened to its entities.
+fn describe(edits: &[SceneEdit]) -> String {
+    let mut counts: [HashSet<u64>; 5] = Default::default();
+    for edit in edits {
+        let verb = match edit {
+            SceneEdit::Add { .. } => 0,
+            SceneEdit::Remove { .. } => 1,
+            SceneEdit::Transform { .. } => 2,
+            SceneEdit::Material { before, after, .. }
+                if Look {
+                    color: after.color,
+                    ..before.clone()
+                } == *after =>
+            {
+                3
+            }
+            SceneEdit::Material { .. } => 4,
+        };
+        counts[verb].insert(edit.id());
+    }
+    let count = |n: usize| match n {
+        1 => "1 entity".to_owned(),
+        n => format!("{n} entities"),
+    };
+    let parts: Vec<String> = ["Added", "Removed", "Moved", "Recoloured", "Edited"]
+        .into_iter()
+        .zip(&counts)
+        .filter(|(_, ids)| !ids.is_empty())
+        .map(|(verb, ids)| format!("{verb} {}", count(ids.len())))
+        .collect();
+    if parts.is_empty() {
+        "Edited scene".to_owned()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// The [`SceneId`] of the group `parent` is, if it is one.
+fn group_id(parent: Option<&Parent>, groups: &Query<&SceneId, With<Group>>) -> Option<u64> {
+    parent
+        .and_then(|parent| groups.get(parent.get()).ok())
+        .map(|id| **id)
+}
+
+/// Records a cube's removal while its components are still there to restore it from.
+fn record_removal_observer(
+    trigger: Trigger<OnRemove, RenderCube>,
+    mut history: ResMut<History>,
+    scene: SceneReader,
+    cubes: Query<(Option<&Parent>, Has<Recorded>, Has<Unrecorded>)>,
+    groups: Query<&SceneId, With<Group>>,
+) {
+    let Ok((parent, true, false)) = cubes.get(trigger.entity()) else {
+        return;
+    };
+    if !history.recording {
+        return;
+    }
+    if let Some(entity) = scene.entity(trigger.entity()) {
+        let group = group_id(parent, &groups);
+        let edit = SceneEdit::Remove {
+            group,
+            entity: Box::new(entity),
+        };
+        history.pending.push(edit);
+    }
+}
+
+/// Compares changed cubes with their last recorded state, and turns what was edited since the
+/// last mouse release into an entry. Driven and scripted cubes are compared once let go.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn record_scene_edits_system(
+    mut commands: Commands,
+    mut history: ResMut<History>,
+    scene: SceneReader,
+    mut cubes: Query<(
+        Entity,
+        &SceneId,
+        Ref<Transform>,
+        Option<Ref<RestRotation>>,
+        Ref<Handle<StandardMaterial>>,
+        Option<Ref<ExplodedPart>>,
+        Option<Ref<EntityScript>>,
+        Has<Driven>,
+        &mut Recorded,
+    )>,
+    new: Query<(Entity, &SceneId, Option<&Parent>), (With<RenderCube>, Without<Recorded>)>,
+    groups: Query<&SceneId, With<Group>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    mut undriven: RemovedComponents<Driven>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_seconds();
+    let recording = history.recording || now >= SETTLE_SECS;
+    history.recording = recording;
+    if mouse.get_pressed().next().is_some()
+        || mouse.get_just_released().next().is_some()
+        || keys.get_pressed().next().is_some()
+        || keys.get_just_released().next().is_some()
+    {
+        history.last_input = now;
+    }
+    for (entity, id, parent) in &new {
+        let Some(cube) = scene.entity(entity) else {
+            continue;
+        };
+        commands.entity(entity).insert(Recorded::of(&cube));
+        if recording {
+            let group = group_id(parent, &groups);
+            history.pending.push(SceneEdit::Add {
+                id: **id,
+                group,
+                entity: None,
+            });
+        }
+    }
+
+    let modified: HashSet<AssetId<StandardMaterial>> = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let undriven: HashSet<Entity> = undriven.read().collect();
+    for (entity, id, transform, rest, material, exploded, script, driven, mut recorded) in
+        &mut cubes
+    {
+        let scripted = script.as_ref().is_some_and(|script| script.enabled);
+        if driven || scripted {
+            continue;
+        }
+        // Compare everything once let go, as changes made meanwhile were skipped.
+        let released = undriven.contains(&entity) || script.as_ref().is_some_and(Ref::is_changed);
+        let moved = released
+            || transform.is_changed()
+            || rest.as_ref().is_some_and(Ref::is_changed)
+            || exploded.as_ref().is_some_and(Ref::is_changed);
+        if moved {
+            let authored = Transform {
+                translation: exploded.map_or(transform.translation, |part| part.rest),
+                rotation: rest.map_or(transform.rotation, |rest| **rest),
+                ..*transform
+            };
+            if authored != recorded.transform {
+                let before = std::mem::replace(&mut recorded.transform, authored);
+                if recording {
+                    let edit = SceneEdit::Transform {
+                        id: **id,
+                        before,
+                        after: authored,
+                    };
+                    merge(&mut history.pending, edit);
+                }
+            }
+        }
+        if released || material.is_changed() || modified.contains(&material.id()) {
+            let Some(look) = scene.entity(entity).as_ref().map(Look::of) else {
+                continue;
+            };
+            if look != recorded.look {
+                let before = std::mem::replace(&mut recorded.look, look.clone());
+                if recording {
+                    let edit = SceneEdit::Material {
+                        id: **id,
+                        before,
+                        after: look,
+                    };
+                    merge(&mut history.pending, edit);
+                }
+            }
+        }
+    }
+
+    // Wait for drags to finish, unless a jump needs the edits in the list first.
+    if history.pending.is_empty()
+        || (mouse.get_pressed().next().is_some() && history.target.is_none())
+    {
+        return;
+    }
+    let edits = std::mem::take(&mut history.pending);
+    let label = describe(&edits);
+    let History {
+        entries,
+        applied,
+        last_input,
+        ..
+    } = &mut *history;
+    let at_end = *applied == entries.len();
+    // Edits nobody made by hand, such as a scatter settling, would drop what is redoable.
+    if !at_end && now - *last_input > INPUT_GRACE_SECS {
+        return;
+    }
+    match entries.last_mut() {
+        Some(HistoryEntry {
+            label: last,
+            change: Change::Scene(recorded),
+            at,
+        }) if at_end && *last == label && now - *at < COALESCE_SECS => {
+            for edit in edits {
+                merge(recorded, edit);
+            }
+            *last = describe(recorded);
+            *at = now;
+        }
+        _ => history.push(label, Change::Scene(edits), now),
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct HistoryWindow {
+    pub is_open: bool,
+}
+
+impl Panel for HistoryWindow {
+    const TITLE: &'static str = "History";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn history_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<HistoryWindow>,
+    mut history: ResMut<History>,
+) {
+    let HistoryWindow { is_open } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(HistoryWindow::TITLE)
+        .open(is_open)
+        .default_width(260.0)
+        .show(contexts.ctx::<HistoryWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(history.can_undo(), egui::Button::new("⟲ Undo"))
+                    .clicked()
+                {
+                    history.undo();
+                }
+                let redoable = history.applied < history.entries.len();
+                if ui
+                    .add_enabled(redoable, egui::Button::new("⟳ Redo"))
+                    .clicked()
+                {
+                    history.redo();
+                }
+            });
+            ui.separator();
+            let mut target = None;
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    if ui
+                        .selectable_label(history.applied == 0, "Start")
+                        .on_hover_text("Undo everything listed")
+                        .clicked()
+                    {
+                        target = Some(0);
+                    }
+                    for (index, entry) in history.entries.iter().enumerate() {
+                        let position = index + 1;
+                        let text = egui::RichText::new(&entry.label);
+                        // Undone entries are dimmed until redone or replaced by a new edit.
+                        let text = if position > history.applied {
+                            text.weak()
+                        } else {
+                            text
+                        };
+                        if ui
+                            .selectable_label(history.applied == position, text)
+                            .clicked()
+                        {
+                            target = Some(position);
+                        }
+                    }
+                });
+            if target.is_some() {
+                history.target = target;
+            }
+        });
+}
+
+/// A scene entity as the edits being applied leave it.
+struct Touched {
+    /// The cube it is now, if any.
+    live: Option<Entity>,
+    group: Option<u64>,
+    /// `None` once removed.
+    entity: Option<SceneEntity>,
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn apply_history_system(
+    mut commands: Commands,
+    mut history: ResMut<History>,
+    mut scene: ParamSet<(SceneReader, SceneWriter, ResMut<Assets<Mesh>>)>,
+    cubes: Query<(Entity, &SceneId, Option<&Parent>), With<RenderCube>>,
+    groups: Query<&SceneId, With<Group>>,
+    group_entities: Query<(Entity, &SceneId), With<Group>>,
+    mut ui_state: ResMut<UiState>,
+    mut session: EventWriter<SessionEvent>,
+) {
+    let Some(target) = history.target.take() else {
+        return;
+    };
+    let History {
+        entries, applied, ..
+    } = &mut *history;
+    let mut steps = Vec::new();
+    while *applied > target {
+        *applied -= 1;
+        steps.push((*applied, true));
+        session.send(SessionEvent::Undo);
+    }
+    while *applied < target.min(entries.len()) {
+        steps.push((*applied, false));
+        *applied += 1;
+    }
+
+    // Scene edits are played on copies of the entities they touch, so a long jump updates each
+    // cube once, and an entity brought back by one step can be edited by the next.
+    let live: HashMap<u64, (Entity, Option<&Parent>)> = cubes
+        .iter()
+        .map(|(entity, id, parent)| (**id, (entity, parent)))
+        .collect();
+    let mut touched: HashMap<u64, Touched> = HashMap::new();
+    let reader = scene.p0();
+    let load = |id: u64| match live.get(&id) {
+        Some((entity, parent)) => Touched {
+            live: Some(*entity),
+            group: group_id(*parent, &groups),
+            entity: reader.entity(*entity),
+        },
+        None => Touched {
+            live: None,
+            group: None,
+            entity: None,
+        },
+    };
+    let mut strokes = Vec::new();
+    for (index, undo) in steps {
+        match &mut entries[index].change {
+            Change::Scene(edits) => {
+                let mut order: Vec<usize> = (0..edits.len()).collect();
+                if undo {
+                    order.reverse();
+                }
+                for index in order {
+                    let edit = &mut edits[index];
+                    let id = edit.id();
+                    let touched = touched.entry(id).or_insert_with(|| load(id));
+                    match edit {
+                        SceneEdit::Transform { before, after, .. } => {
+                            let transform = if undo { before } else { after };
+                            if let Some(entity) = &mut touched.entity {
+                                entity.translation = transform.translation.to_array();
+                                entity.rotation = transform.rotation.to_array();
+                                entity.scale = transform.scale.to_array();
+                            }
+                        }
+                        SceneEdit::Material { before, after, .. } => {
+                            if let Some(entity) = &mut touched.entity {
+                                if undo { before } else { after }.apply(entity);
+                            }
+                        }
+                        SceneEdit::Add { group, entity, .. } if undo => {
+                            if let Some(taken) = touched.entity.take() {
+                                *entity = Some(Box::new(taken));
+                            }
+                            *group = touched.group;
+                        }
+                        SceneEdit::Remove { group, entity } if undo => {
+                            touched.entity = Some((**entity).clone());
+                            touched.group = *group;
+                        }
+                        SceneEdit::Add { group, entity, .. } => {
+                            if let Some(entity) = entity {
+                                touched.entity = Some((**entity).clone());
+                                touched.group = *group;
+                            }
+                        }
+                        SceneEdit::Remove { .. } => touched.entity = None,
+                    }
+                }
+            }
+            Change::Stroke {
+                mesh,
+                before,
+                after,
+            } => strokes.push((*mesh, if undo { before.clone() } else { after.clone() })),
+            Change::CanvasStroke(line) => {
+                if undo {
+                    ui_state.painting.pop_line();
+                } else {
+                    ui_state.painting.push_line(line.clone());
+                }
+            }
+            Change::CanvasClear(lines) => {
+                let lines = if undo { lines.clone() } else { Vec::new() };
+                ui_state.painting.set_lines(lines);
+            }
+        }
+    }
+
+    let mut meshes = scene.p2();
+    for (mesh, colors) in strokes {
+        // Strokes on meshes since replaced, by subdivision or removal, are skipped.
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            if mesh.count_vertices() == colors.len() {
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+            }
+        }
+    }
+    let mut writer = scene.p1();
+    for touched in touched.into_values() {
+        match (touched.live, touched.entity) {
+            (Some(cube), Some(entity)) => {
+                writer.update(cube, &entity);
+                commands.entity(cube).insert(Recorded::of(&entity));
+            }
+            (Some(cube), None) => {
+                commands.entity(cube).insert(Unrecorded);
+                commands.entity(cube).despawn_recursive();
+            }
+            (None, Some(entity)) => {
+                let parent = group_entities
+                    .iter()
+                    .find(|(_, id)| Some(***id) == touched.group)
+                    .map(|(group, _)| group);
+                let cube = writer.spawn(&entity, parent);
+                commands.entity(cube).insert(Recorded::of(&entity));
+            }
+            (None, None) => {}
+        }
+    }
+}
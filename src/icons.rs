@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext, EguiUserTextures};
+
+/// The sprite sheet under `assets/`: every icon at 16 pixels along the top row and at 32
+/// pixels below it, in [`Icon::ALL`] order.
+const ATLAS_PATH: &str = "icons.png";
+const SMALL_CELL: f32 = 16.0;
+const LARGE_CELL: f32 = 32.0;
+/// Size of an icon on its own in a button, in points.
+pub const ICON_SIZE: f32 = 16.0;
+
+/// Named icons for buttons, menus and the toolbar, drawn from one sprite sheet loaded at
+/// startup. The sheet's larger cells are used on high-DPI screens so icons stay sharp, and
+/// the icons are white, tinted to the theme's text colour. Until the sheet has loaded, or if
+/// it is missing, buttons show a text glyph instead.
+pub struct IconsPlugin;
+
+impl Plugin for IconsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IconAtlas>()
+            .add_systems(Update, share_icon_atlas_system);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Icon {
+    Open,
+    Save,
+    Undo,
+    Redo,
+    Copy,
+    Paste,
+    Delete,
+    Snapshot,
+    Restore,
+    Settings,
+    Layout,
+    Report,
+    Web,
+    Tour,
+    Play,
+    Pause,
+    PlayPause,
+    Stop,
+    Record,
+    Rewind,
+    StepBack,
+    StepForward,
+    Close,
+    Random,
+}
+
+impl Icon {
+    pub const ALL: [Icon; 24] = [
+        Icon::Open,
+        Icon::Save,
+        Icon::Undo,
+        Icon::Redo,
+        Icon::Copy,
+        Icon::Paste,
+        Icon::Delete,
+        Icon::Snapshot,
+        Icon::Restore,
+        Icon::Settings,
+        Icon::Layout,
+        Icon::Report,
+        Icon::Web,
+        Icon::Tour,
+        Icon::Play,
+        Icon::Pause,
+        Icon::PlayPause,
+        Icon::Stop,
+        Icon::Record,
+        Icon::Rewind,
+        Icon::StepBack,
+        Icon::StepForward,
+        Icon::Close,
+        Icon::Random,
+    ];
+
+    /// Shown in place of the icon while the sprite sheet is unavailable.
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Icon::Open => "🗁",
+            Icon::Save => "💾",
+            Icon::Undo => "⟲",
+            Icon::Redo => "⟳",
+            Icon::Copy => "📋",
+            Icon::Paste => "📋",
+            Icon::Delete => "🗑",
+            Icon::Snapshot => "📷",
+            Icon::Restore => "⟲",
+            Icon::Settings => "⚙",
+            Icon::Layout => "📐",
+            Icon::Report => "📄",
+            Icon::Web => "🌐",
+            Icon::Tour => "🎓",
+            Icon::Play => "▶",
+            Icon::Pause => "⏸",
+            Icon::PlayPause => "⏯",
+            Icon::Stop => "⏹",
+            Icon::Record => "⏺",
+            Icon::Rewind => "⏮",
+            Icon::StepBack => "⏪",
+            Icon::StepForward => "⏩",
+            Icon::Close => "✖",
+            Icon::Random => "🎲",
+        }
+    }
+
+    /// The icon's texture rectangle in the sheet, normalised to its `size`.
+    fn uv(self, size: egui::Vec2, large: bool) -> egui::Rect {
+        let index = Icon::ALL.iter().position(|icon| *icon == self).unwrap_or(0) as f32;
+        let (cell, top) = if large {
+            (LARGE_CELL, SMALL_CELL)
+        } else {
+            (SMALL_CELL, 0.0)
+        };
+        let min = egui::pos2(index * cell / size.x, top / size.y);
+        egui::Rect::from_min_size(min, egui::vec2(cell / size.x, cell / size.y))
+    }
+}
+
+#[derive(Resource)]
+struct IconAtlas {
+    image: Handle<Image>,
+}
+
+impl FromWorld for IconAtlas {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            image: world.resource::<AssetServer>().load(ATLAS_PATH),
+        }
+    }
+}
+
+/// The sheet's texture as the button helpers look it up from egui memory, so widgets can
+/// draw icons with only a `Ui` at hand.
+#[derive(Clone, Copy)]
+struct AtlasTexture {
+    texture: egui::TextureId,
+    size: egui::Vec2,
+}
+
+fn atlas_id() -> egui::Id {
+    egui::Id::new("icon_atlas")
+}
+
+/// Registers the sheet with egui once it has loaded and shares it with every window's
+/// context, including panels detached later.
+fn share_icon_atlas_system(
+    atlas: Res<IconAtlas>,
+    images: Res<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    mut contexts: Query<&mut EguiContext>,
+) {
+    let Some(image) = images.get(&atlas.image) else {
+        return;
+    };
+    let texture = match user_textures.image_id(&atlas.image) {
+        Some(texture) => texture,
+        None => user_textures.add_image(atlas.image.clone_weak()),
+    };
+    let size = image.size_f32();
+    let shared = AtlasTexture {
+        texture,
+        size: egui::vec2(size.x, size.y),
+    };
+    for mut context in &mut contexts {
+        context
+            .get_mut()
+            .data_mut(|data| data.insert_temp(atlas_id(), shared));
+    }
+}
+
+/// `icon` as an image [`ICON_SIZE`] points square, or `None` until the sheet has loaded.
+pub fn image(ui: &egui::Ui, icon: Icon) -> Option<egui::Image<'static>> {
+    let atlas = ui.data(|data| data.get_temp::<AtlasTexture>(atlas_id()))?;
+    let large = ui.ctx().pixels_per_point() * ICON_SIZE > SMALL_CELL;
+    Some(
+        egui::Image::new(egui::load::SizedTexture::new(
+            atlas.texture,
+            egui::Vec2::splat(ICON_SIZE),
+        ))
+        .uv(icon.uv(atlas.size, large))
+        .tint(ui.visuals().text_color()),
+    )
+}
+
+/// A button showing `icon` before `text`, or the icon alone when `text` is empty.
+pub fn button(ui: &egui::Ui, icon: Icon, text: &str) -> egui::Button<'static> {
+    match (image(ui, icon), text.is_empty()) {
+        (Some(image), true) => egui::Button::image(image),
+        (Some(image), false) => egui::Button::image_and_text(image, text),
+        (None, true) => egui::Button::new(icon.glyph()),
+        (None, false) => egui::Button::new(format!("{} {text}", icon.glyph())),
+    }
+}
+
+pub trait IconButtonsExt {
+    fn icon_button(&mut self, icon: Icon, text: &str) -> egui::Response;
+
+    fn small_icon_button(&mut self, icon: Icon, text: &str) -> egui::Response;
+}
+
+impl IconButtonsExt for egui::Ui {
+    fn icon_button(&mut self, icon: Icon, text: &str) -> egui::Response {
+        self.add(button(self, icon, text))
+    }
+
+    fn small_icon_button(&mut self, icon: Icon, text: &str) -> egui::Response {
+        self.add(button(self, icon, text).small())
+    }
+}
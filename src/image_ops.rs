@@ -0,0 +1,435 @@
+use bevy::{prelude::*, render::render_resource::TextureFormat};
+use bevy_egui::{egui, EguiUserTextures};
+
+use crate::icons::{Icon, IconButtonsExt};
+use crate::numeric::drag_value;
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+
+/// CPU image processing on `Image` assets: the Image Ops panel writes results to new assets,
+/// and [`derive_image`] keeps an asset in step with a processed source.
+pub struct ImageOpsPlugin;
+
+impl Plugin for ImageOpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DerivedImages>()
+            .register_panel::<ImageOpsWindow>()
+            .add_systems(
+                Update,
+                (
+                    update_derived_images_system,
+                    image_ops_window_system.in_set(ImageOpsWindow::ui_set()),
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageOp {
+    Invert,
+    /// Box blur over a square of `2 * radius + 1` pixels, alpha-weighted so edges don't halo.
+    Blur {
+        radius: u32,
+    },
+    /// Remaps `black..white` to the full range, then applies `gamma`.
+    Levels {
+        black: f32,
+        white: f32,
+        gamma: f32,
+    },
+    HueShift {
+        degrees: f32,
+    },
+}
+
+impl ImageOp {
+    const ALL: [ImageOp; 4] = [
+        ImageOp::Invert,
+        ImageOp::Blur { radius: 2 },
+        ImageOp::Levels {
+            black: 0.0,
+            white: 1.0,
+            gamma: 1.0,
+        },
+        ImageOp::HueShift { degrees: 90.0 },
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ImageOp::Invert => "Invert",
+            ImageOp::Blur { .. } => "Blur",
+            ImageOp::Levels { .. } => "Levels",
+            ImageOp::HueShift { .. } => "Hue shift",
+        }
+    }
+
+    fn apply(&self, pixels: &mut [[f32; 4]], width: usize, height: usize) {
+        match *self {
+            ImageOp::Invert => {
+                for pixel in pixels {
+                    for channel in &mut pixel[..3] {
+                        *channel = 1.0 - *channel;
+                    }
+                }
+            }
+            ImageOp::Blur { radius } => box_blur(pixels, width, height, radius as usize),
+            ImageOp::Levels {
+                black,
+                white,
+                gamma,
+            } => {
+                let range = (white - black).max(1e-3);
+                let exponent = 1.0 / gamma.max(1e-3);
+                for pixel in pixels {
+                    for channel in &mut pixel[..3] {
+                        *channel = ((*channel - black) / range).clamp(0.0, 1.0).powf(exponent);
+                    }
+                }
+            }
+            ImageOp::HueShift { degrees } => {
+                for pixel in pixels {
+                    let [r, g, b, a] = *pixel;
+                    let shifted =
+                        Srgba::from(Hsla::from(Srgba::new(r, g, b, a)).rotate_hue(degrees));
+                    *pixel = shifted.to_f32_array();
+                }
+            }
+        }
+    }
+}
+
+/// Only 8-bit RGBA images with CPU-side data can be processed.
+fn supported(image: &Image) -> Result<(), String> {
+    match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {}
+        format => {
+            return Err(format!(
+                "{format:?} images are not supported, only 8-bit RGBA"
+            ))
+        }
+    }
+    let size = image.size();
+    if image.data.len() != size.x as usize * size.y as usize * 4 {
+        return Err("The image has no CPU-side pixel data".to_owned());
+    }
+    Ok(())
+}
+
+/// Runs `ops` over a copy of `image`.
+pub fn apply_ops(image: &Image, ops: &[ImageOp]) -> Result<Image, String> {
+    supported(image)?;
+    let size = image.size();
+    let (width, height) = (size.x as usize, size.y as usize);
+
+    let mut pixels: Vec<[f32; 4]> = image
+        .data
+        .chunks_exact(4)
+        .map(|p| [p[0], p[1], p[2], p[3]].map(|c| c as f32 / 255.0))
+        .collect();
+    for op in ops {
+        op.apply(&mut pixels, width, height);
+    }
+
+    let mut result = image.clone();
+    result.data = pixels
+        .iter()
+        .flat_map(|pixel| pixel.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8))
+        .collect();
+    Ok(result)
+}
+
+fn box_blur(pixels: &mut [[f32; 4]], width: usize, height: usize, radius: usize) {
+    if radius == 0 {
+        return;
+    }
+    for pixel in pixels.iter_mut() {
+        let alpha = pixel[3];
+        for channel in &mut pixel[..3] {
+            *channel *= alpha;
+        }
+    }
+
+    let mut scratch = pixels.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let from = x.saturating_sub(radius);
+            let to = (x + radius).min(width - 1);
+            scratch[y * width + x] = average(&pixels[y * width + from..=y * width + to]);
+        }
+    }
+    for x in 0..width {
+        let column: Vec<[f32; 4]> = (0..height).map(|y| scratch[y * width + x]).collect();
+        for y in 0..height {
+            let from = y.saturating_sub(radius);
+            let to = (y + radius).min(height - 1);
+            pixels[y * width + x] = average(&column[from..=to]);
+        }
+    }
+
+    for pixel in pixels.iter_mut() {
+        let alpha = pixel[3];
+        if alpha > 0.0 {
+            for channel in &mut pixel[..3] {
+                *channel /= alpha;
+            }
+        }
+    }
+}
+
+fn average(pixels: &[[f32; 4]]) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    for pixel in pixels {
+        for (total, channel) in sum.iter_mut().zip(pixel) {
+            *total += channel;
+        }
+    }
+    sum.map(|total| total / pixels.len() as f32)
+}
+
+struct DerivedImage {
+    source: Handle<Image>,
+    target: Handle<Image>,
+    ops: Vec<ImageOp>,
+}
+
+/// Images recomputed from their source whenever it loads or changes.
+#[derive(Default, Resource)]
+pub struct DerivedImages(Vec<DerivedImage>);
+
+/// Returns an image that follows `source` through `ops`. It holds a blank placeholder until
+/// the source has loaded.
+pub fn derive_image(world: &mut World, source: Handle<Image>, ops: Vec<ImageOp>) -> Handle<Image> {
+    let target = world.resource_mut::<Assets<Image>>().add(Image::default());
+    world
+        .get_resource_or_insert_with(DerivedImages::default)
+        .0
+        .push(DerivedImage {
+            source,
+            target: target.clone(),
+            ops,
+        });
+    target
+}
+
+fn update_derived_images_system(
+    mut events: EventReader<AssetEvent<Image>>,
+    derived: Res<DerivedImages>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = *event
+        else {
+            continue;
+        };
+        for derived in derived.0.iter().filter(|derived| derived.source.id() == id) {
+            let Some(source) = images.get(id) else {
+                continue;
+            };
+            match apply_ops(source, &derived.ops) {
+                Ok(result) => {
+                    images.insert(derived.target.id(), result);
+                }
+                Err(err) => warn!("Could not derive image: {err}"),
+            }
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct ImageOpsWindow {
+    pub is_open: bool,
+    source: Option<Handle<Image>>,
+    ops: Vec<ImageOp>,
+    preview: Option<Handle<Image>>,
+    /// Set when the source or the op stack changed and the preview is stale.
+    dirty: bool,
+    outputs: Vec<Handle<Image>>,
+    error: Option<String>,
+}
+
+impl Panel for ImageOpsWindow {
+    const TITLE: &'static str = "Image Ops";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+const THUMBNAIL: f32 = 128.0;
+
+fn image_ops_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ImageOpsWindow>,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    asset_server: Res<AssetServer>,
+) {
+    let ImageOpsWindow {
+        is_open,
+        source,
+        ops,
+        preview,
+        dirty,
+        outputs,
+        error,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let preview = preview
+        .get_or_insert_with(|| images.add(Image::default()))
+        .clone();
+    let mut candidates: Vec<(AssetId<Image>, String)> = images
+        .iter()
+        .filter(|(id, image)| {
+            *id != preview.id()
+                && !outputs.iter().any(|output| output.id() == *id)
+                && supported(image).is_ok()
+        })
+        .map(|(id, image)| {
+            let size = image.size();
+            let label = match asset_server.get_path(id) {
+                Some(path) => format!("{path} ({}×{})", size.x, size.y),
+                None => format!("Image {id:?} ({}×{})", size.x, size.y),
+            };
+            (id, label)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let source_id = source.as_ref().map(Handle::id);
+    let mut chosen = source_id;
+    let mut apply = false;
+    let ctx = contexts.ctx::<ImageOpsWindow>();
+    egui::Window::new(ImageOpsWindow::TITLE)
+        .open(is_open)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            let selected = candidates
+                .iter()
+                .find(|(id, _)| Some(*id) == chosen)
+                .map_or("Choose an image…", |(_, label)| label.as_str());
+            egui::ComboBox::from_label("Source")
+                .selected_text(selected)
+                .width(220.0)
+                .show_ui(ui, |ui| {
+                    for (id, label) in &candidates {
+                        ui.selectable_value(&mut chosen, Some(*id), label);
+                    }
+                });
+            if candidates.is_empty() {
+                ui.weak("No 8-bit RGBA images with CPU data are loaded.");
+            }
+
+            ui.separator();
+            let mut remove = None;
+            for (index, op) in ops.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(op.label());
+                    let changed = match op {
+                        ImageOp::Invert => false,
+                        ImageOp::Blur { radius } => ui
+                            .add(egui::Slider::new(radius, 1..=16).text("radius"))
+                            .changed(),
+                        ImageOp::Levels {
+                            black,
+                            white,
+                            gamma,
+                        } => {
+                            ui.add(drag_value(black).speed(0.01).range(0.0..=1.0))
+                                .changed()
+                                | ui.add(drag_value(white).speed(0.01).range(0.0..=1.0))
+                                    .changed()
+                                | ui.add(
+                                    drag_value(gamma).speed(0.01).range(0.1..=4.0).prefix("γ "),
+                                )
+                                .changed()
+                        }
+                        ImageOp::HueShift { degrees } => ui
+                            .add(egui::Slider::new(degrees, -180.0..=180.0).suffix("°"))
+                            .changed(),
+                    };
+                    *dirty |= changed;
+                    if ui.small_icon_button(Icon::Close, "").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                ops.remove(index);
+                *dirty = true;
+            }
+            ui.menu_button("Add operation", |ui| {
+                for op in ImageOp::ALL {
+                    if ui.button(op.label()).clicked() {
+                        ops.push(op);
+                        *dirty = true;
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            if let Some(error) = error.as_deref() {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            if source.is_some() {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    for handle in [source.as_ref().unwrap(), &preview] {
+                        let texture = user_textures.add_image(handle.clone_weak());
+                        let size = images.get(handle).map_or(UVec2::ONE, Image::size);
+                        ui.add(
+                            egui::Image::new(egui::load::SizedTexture::new(
+                                texture,
+                                [size.x as f32, size.y as f32],
+                            ))
+                            .max_size(egui::Vec2::splat(THUMBNAIL)),
+                        );
+                    }
+                });
+                apply = ui
+                    .add_enabled(error.is_none(), egui::Button::new("Save as new image"))
+                    .on_hover_text("Write the result to a new image asset registered with egui")
+                    .clicked();
+            }
+
+            if !outputs.is_empty() {
+                ui.separator();
+                ui.strong("Results");
+                ui.horizontal_wrapped(|ui| {
+                    for output in outputs.iter() {
+                        let texture = user_textures.add_image(output.clone_weak());
+                        ui.image(egui::load::SizedTexture::new(texture, [64.0, 64.0]))
+                            .on_hover_text(format!("Image {:?}", output.id()));
+                    }
+                });
+            }
+        });
+
+    if chosen != source_id {
+        *source = chosen.and_then(|id| images.get_strong_handle(id));
+        *dirty = true;
+    }
+    let Some(source) = source.as_ref() else {
+        return;
+    };
+    let result = match images.get(source) {
+        Some(image) if *dirty || apply => apply_ops(image, ops),
+        Some(_) => return,
+        None => Err("The source image is no longer loaded".to_owned()),
+    };
+    *dirty = false;
+    match result {
+        Ok(result) => {
+            *error = None;
+            if apply {
+                let output = images.add(result.clone());
+                user_textures.add_image(output.clone());
+                outputs.push(output);
+            }
+            images.insert(preview.id(), result);
+        }
+        Err(err) => *error = Some(err),
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::prelude::*;
+
+use crate::{
+    errors::AppError,
+    icons::Icon,
+    panels::{Menu, MenuItem, PanelRegistry, RegisterPanelExt},
+    scene::{read_scene_file, SceneWriter, SpawnQueue, SCENE_PATH},
+    snapshots::TakeSnapshot,
+    timeline::AnimationTime,
+    ViewportCamera,
+};
+
+pub const INIT_SCRIPT_PATH: &str = "init.sandbox";
+
+/// Runs `init.sandbox`, if present, once startup is done. Each line is one command:
+///
+/// ```text
+/// # comments start with a hash
+/// load                      # replace the scene with scene.ron
+/// spawn 2 0 0 [1.5] [#ff8800]
+/// camera 0 5 30 [0 0 0]     # eye, then the point to look at
+/// open Inspector            # or close, by window title
+/// time 2.5 / play / pause
+/// snapshot [name]
+/// ```
+pub struct InitScriptPlugin;
+
+impl Plugin for InitScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RunInitScript>()
+            .add_systems(PostStartup, |mut events: EventWriter<RunInitScript>| {
+                events.send(RunInitScript { required: false });
+            })
+            .add_systems(Update, run_init_script_system)
+            .add_menu_item(
+                MenuItem::new(Menu::File, "Run Init Script", |world| {
+                    world.send_event(RunInitScript { required: true });
+                })
+                .icon(Icon::Play),
+            );
+    }
+}
+
+/// Runs the init script; a missing file is only an error if `required`.
+#[derive(Event)]
+pub struct RunInitScript {
+    pub required: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Load,
+    Spawn {
+        position: Vec3,
+        size: f32,
+        color: Color,
+    },
+    Camera {
+        eye: Vec3,
+        target: Vec3,
+    },
+    Window {
+        title: String,
+        open: bool,
+    },
+    Time(f32),
+    Play(bool),
+    Snapshot(Option<String>),
+}
+
+fn parse_numbers(words: &[&str]) -> Result<Vec<f32>, String> {
+    words
+        .iter()
+        .map(|word| {
+            word.parse::<f32>()
+                .map_err(|_| format!("\"{word}\" is not a number"))
+        })
+        .collect()
+}
+
+fn parse_vec3(numbers: &[f32]) -> Option<Vec3> {
+    match numbers {
+        [x, y, z] => Some(Vec3::new(*x, *y, *z)),
+        _ => None,
+    }
+}
+
+fn parse_line(line: &str) -> Result<Option<Command>, String> {
+    let line = line.split_once('#').map_or(line, |(code, comment)| {
+        // `#rrggbb` colours are not comments.
+        if comment.len() >= 6 && comment[..6].chars().all(|c| c.is_ascii_hexdigit()) {
+            line
+        } else {
+            code
+        }
+    });
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&name, args)) = words.split_first() else {
+        return Ok(None);
+    };
+    let rest = || args.join(" ");
+    let command = match name {
+        "load" => Command::Load,
+        "spawn" => {
+            let (color, numbers) = match args.last() {
+                Some(color) if color.starts_with('#') => (
+                    Srgba::hex(color)
+                        .map_err(|_| format!("\"{color}\" is not a #rrggbb colour"))?
+                        .into(),
+                    &args[..args.len() - 1],
+                ),
+                _ => (Color::srgb(0.8, 0.7, 0.6), args),
+            };
+            let numbers = parse_numbers(numbers)?;
+            let (position, size) = match numbers.as_slice() {
+                [x, y, z] => (Vec3::new(*x, *y, *z), 1.0),
+                [x, y, z, size] => (Vec3::new(*x, *y, *z), *size),
+                _ => return Err("spawn takes x y z [size] [#rrggbb]".to_owned()),
+            };
+            Command::Spawn {
+                position,
+                size,
+                color,
+            }
+        }
+        "camera" => {
+            let numbers = parse_numbers(args)?;
+            let usage = || "camera takes x y z [target x y z]".to_owned();
+            let eye = parse_vec3(numbers.get(..3).ok_or_else(usage)?).ok_or_else(usage)?;
+            let target = match &numbers[3..] {
+                [] => Vec3::ZERO,
+                target => parse_vec3(target).ok_or_else(usage)?,
+            };
+            Command::Camera { eye, target }
+        }
+        "open" | "close" if args.is_empty() => return Err(format!("{name} takes a window title")),
+        "open" | "close" => Command::Window {
+            title: rest(),
+            open: name == "open",
+        },
+        "time" => match parse_numbers(args)?.as_slice() {
+            [seconds] => Command::Time(*seconds),
+            _ => return Err("time takes a number of seconds".to_owned()),
+        },
+        "play" => Command::Play(true),
+        "pause" => Command::Play(false),
+        "snapshot" => Command::Snapshot((!args.is_empty()).then(rest)),
+        _ => return Err(format!("unknown command \"{name}\"")),
+    };
+    Ok(Some(command))
+}
+
+/// Parses a whole script, collecting every bad line instead of stopping at the first.
+fn parse_script(source: &str) -> (Vec<Command>, Vec<String>) {
+    let mut commands = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        match parse_line(line) {
+            Ok(Some(command)) => commands.push(command),
+            Ok(None) => {}
+            Err(err) => errors.push(format!("line {}: {err}", index + 1)),
+        }
+    }
+    (commands, errors)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_init_script_system(
+    mut events: EventReader<RunInitScript>,
+    mut scene: SceneWriter,
+    mut spawns: ResMut<SpawnQueue>,
+    mut registry: ResMut<PanelRegistry>,
+    mut time: ResMut<AnimationTime>,
+    mut cameras: Query<&mut Transform, With<ViewportCamera>>,
+    mut snapshots: EventWriter<TakeSnapshot>,
+    mut errors: EventWriter<AppError>,
+) {
+    let Some(required) = events
+        .read()
+        .map(|event| event.required)
+        .reduce(|a, b| a || b)
+    else {
+        return;
+    };
+
+    let source = match std::fs::read_to_string(INIT_SCRIPT_PATH) {
+        Ok(source) => source,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound && !required => return,
+        Err(err) => {
+            errors.send(
+                AppError::new(
+                    "Init script",
+                    format!("Failed to read {INIT_SCRIPT_PATH}: {err}"),
+                )
+                .suggest("Create the file next to scene.ron, one command per line."),
+            );
+            return;
+        }
+    };
+    let (script, mut problems) = parse_script(&source);
+
+    for command in script {
+        match command {
+            Command::Load => match read_scene_file(SCENE_PATH) {
+                Ok(file) => scene.replace(&file),
+                Err(err) => problems.push(format!("failed to load {SCENE_PATH}: {err}")),
+            },
+            Command::Spawn {
+                position,
+                size,
+                color,
+            } => {
+                spawns.push(
+                    Transform::from_translation(position).with_scale(Vec3::splat(size)),
+                    color,
+                );
+            }
+            Command::Camera { eye, target } => {
+                if let Ok(mut camera) = cameras.get_single_mut() {
+                    *camera = Transform::from_translation(eye).looking_at(target, Vec3::Y);
+                }
+            }
+            Command::Window { title, open } => {
+                if !registry.set_open(&title, open) {
+                    problems.push(format!("there is no window titled \"{title}\""));
+                }
+            }
+            Command::Time(seconds) => time.seconds = seconds,
+            Command::Play(playing) => time.playing = playing,
+            Command::Snapshot(name) => {
+                snapshots.send(TakeSnapshot(name));
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        errors.send(
+            AppError::new(
+                "Init script",
+                format!("{INIT_SCRIPT_PATH}: {}", problems.join("; ")),
+            )
+            .suggest(
+                "Commands are load, spawn, camera, open, close, time, play, pause and snapshot.",
+            ),
+        );
+    }
+    info!("Ran {INIT_SCRIPT_PATH}");
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiSet};
+
+use crate::viewport::{Viewport, ViewportTool};
+
+/// Decides once per frame who keyboard and pointer input belong to, so egui widgets, the
+/// viewport and the active tool do not all react to the same key or click.
+pub struct InputRoutingPlugin;
+
+impl Plugin for InputRoutingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRouting>()
+            .add_systems(PreUpdate, route_input_system.after(EguiSet::BeginFrame));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum InputOwner {
+    /// Nothing else claims it: global shortcuts and plain viewport interaction.
+    #[default]
+    Viewport,
+    /// A widget is focused or the pointer is over an egui area.
+    Egui,
+    /// A viewport tool other than selection is active.
+    Tool,
+}
+
+#[derive(Default, Resource)]
+pub struct InputRouting {
+    pub keyboard: InputOwner,
+    pub pointer: InputOwner,
+    /// Owner of the press that started a drag; the pointer stays with it until release.
+    captured: Option<InputOwner>,
+}
+
+impl InputRouting {
+    /// Whether keys may trigger global shortcuts rather than go to a focused text field.
+    pub fn keyboard_is_free(&self) -> bool {
+        self.keyboard != InputOwner::Egui
+    }
+}
+
+fn route_input_system(
+    mut routing: ResMut<InputRouting>,
+    mut contexts: EguiContexts,
+    viewport: Res<Viewport>,
+    tool: Res<ViewportTool>,
+    mouse: Res<ButtonInput<MouseButton>>,
+) {
+    let ctx = contexts.ctx_mut();
+    let viewport_owner = if *tool == ViewportTool::Select {
+        InputOwner::Viewport
+    } else {
+        InputOwner::Tool
+    };
+
+    let keyboard = if ctx.wants_keyboard_input() {
+        InputOwner::Egui
+    } else {
+        viewport_owner
+    };
+    // The viewport image is itself an egui widget, so check it before egui's own areas.
+    let hovered = if viewport.pointer.is_some() {
+        viewport_owner
+    } else if ctx.is_pointer_over_area() || ctx.wants_pointer_input() {
+        InputOwner::Egui
+    } else {
+        InputOwner::Viewport
+    };
+    if mouse.get_just_pressed().next().is_some() && routing.captured.is_none() {
+        routing.captured = Some(hovered);
+    } else if mouse.get_pressed().next().is_none() {
+        routing.captured = None;
+    }
+    let pointer = routing.captured.unwrap_or(hovered);
+
+    if routing.keyboard != keyboard || routing.pointer != pointer {
+        routing.keyboard = keyboard;
+        routing.pointer = pointer;
+    }
+}
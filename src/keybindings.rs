@@ -0,0 +1,439 @@
+use std::fmt;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::input::InputRouting;
+use crate::panels::{Menu, MenuItem, RegisterPanelExt};
+use crate::settings::Settings;
+
+/// Owns the keybinding registry and the shortcut cheat-sheet overlay.
+pub struct KeybindingsPlugin;
+
+impl Plugin for KeybindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Keybindings>()
+            .init_resource::<HelpOverlay>()
+            .add_systems(
+                Update,
+                (sync_shortcut_settings_system, help_overlay_system).chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Help, "Keyboard Shortcuts", |world| {
+                    let mut overlay = world.resource_mut::<HelpOverlay>();
+                    overlay.is_open = !overlay.is_open;
+                })
+                .shortcut(Action::ToggleHelp),
+            );
+    }
+}
+
+/// Everything that can be triggered from the keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    ToggleHelp,
+    OpenSettings,
+    OpenScene,
+    SaveScene,
+    TogglePlayback,
+    GroupSelection,
+    UngroupSelection,
+    Undo,
+    Redo,
+    CopyViewport,
+    PasteImage,
+    PieMenu,
+    ToggleHidpiScaling,
+    QuickSwitcher,
+    /// Held to show the pixel inspector loupe over the viewport.
+    InspectPixels,
+    /// Switches the pixel inspector between output and linear values.
+    ToggleInspectorSource,
+    /// The UI macro bound to this slot, 1 to 9.
+    RunMacro(u8),
+}
+
+/// Keys of the macro slots, Ctrl plus the slot's digit.
+pub const MACRO_SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// A key plus the exact set of modifiers that must be held with it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub const fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub const fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Whether a text field would take this chord as typing rather than a command. A modifier
+    /// held on its own counts as typing too, as it does nothing there.
+    pub fn types_text(&self) -> bool {
+        use KeyCode::*;
+        self.modifier_name().is_some()
+            || !self.ctrl
+                && !self.alt
+                && !matches!(
+                    self.key,
+                    F1 | F2 | F3 | F4 | F5 | F6 | F7 | F8 | F9 | F10 | F11 | F12 | Escape
+                )
+    }
+
+    pub fn just_pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
+        input.just_pressed(self.key) && self.modifiers_match(input)
+    }
+
+    /// Whether the chord is held down, for actions that last as long as it is.
+    pub fn pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
+        input.pressed(self.key) && self.modifiers_match(input)
+    }
+
+    fn modifiers_match(&self, input: &ButtonInput<KeyCode>) -> bool {
+        let ctrl = input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+        let shift = input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let alt = input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+        ctrl == self.ctrl && shift == self.shift && alt == self.alt
+    }
+
+    /// The name of the key when it is itself a modifier, as in a chord held on its own.
+    fn modifier_name(&self) -> Option<&'static str> {
+        match self.key {
+            KeyCode::ControlLeft | KeyCode::ControlRight => Some("Ctrl"),
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => Some("Shift"),
+            KeyCode::AltLeft | KeyCode::AltRight => Some("Alt"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifier = self.modifier_name();
+        for (held, name) in [
+            (self.ctrl, "Ctrl"),
+            (self.shift, "Shift"),
+            (self.alt, "Alt"),
+        ] {
+            if held && modifier != Some(name) {
+                write!(f, "{name}+")?;
+            }
+        }
+        if let Some(name) = modifier {
+            return write!(f, "{name}");
+        }
+        let key = format!("{:?}", self.key);
+        let key = key
+            .strip_prefix("Key")
+            .or_else(|| key.strip_prefix("Digit"))
+            .unwrap_or(&key);
+        write!(f, "{key}")
+    }
+}
+
+pub struct Keybinding {
+    pub action: Action,
+    pub chord: KeyChord,
+    pub category: &'static str,
+    pub description: &'static str,
+}
+
+/// The single source of truth for shortcuts; the help overlay is generated from it.
+#[derive(Resource)]
+pub struct Keybindings {
+    bindings: Vec<Keybinding>,
+    enabled: bool,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let mut keybindings = Self {
+            bindings: Vec::new(),
+            enabled: true,
+        };
+        keybindings
+            .register(
+                Action::ToggleHelp,
+                KeyChord::new(KeyCode::F1),
+                "General",
+                "Toggle this shortcut list",
+            )
+            .register(
+                Action::ToggleHelp,
+                KeyChord::new(KeyCode::Slash).shift(),
+                "General",
+                "Toggle this shortcut list",
+            )
+            .register(
+                Action::OpenSettings,
+                KeyChord::new(KeyCode::Comma).ctrl(),
+                "General",
+                "Open the Settings window",
+            )
+            .register(
+                Action::OpenScene,
+                KeyChord::new(KeyCode::KeyO).ctrl(),
+                "File",
+                "Open the scene file",
+            )
+            .register(
+                Action::SaveScene,
+                KeyChord::new(KeyCode::KeyS).ctrl(),
+                "File",
+                "Save the scene file",
+            )
+            .register(
+                Action::TogglePlayback,
+                KeyChord::new(KeyCode::Space),
+                "Timeline",
+                "Play/pause the animation",
+            )
+            .register(
+                Action::GroupSelection,
+                KeyChord::new(KeyCode::KeyG).ctrl(),
+                "Edit",
+                "Group the selected entities",
+            )
+            .register(
+                Action::UngroupSelection,
+                KeyChord::new(KeyCode::KeyG).ctrl().shift(),
+                "Edit",
+                "Ungroup the selected groups",
+            )
+            .register(
+                Action::Undo,
+                KeyChord::new(KeyCode::KeyZ).ctrl(),
+                "Edit",
+                "Undo the last change",
+            )
+            .register(
+                Action::Redo,
+                KeyChord::new(KeyCode::KeyZ).ctrl().shift(),
+                "Edit",
+                "Redo the last undone change",
+            )
+            .register(
+                Action::CopyViewport,
+                KeyChord::new(KeyCode::KeyC).ctrl().shift(),
+                "Edit",
+                "Copy the viewport image to the clipboard",
+            )
+            .register(
+                Action::PasteImage,
+                KeyChord::new(KeyCode::KeyV).ctrl(),
+                "Edit",
+                "Paste an image from the clipboard",
+            )
+            .register(
+                Action::PieMenu,
+                KeyChord::new(KeyCode::KeyQ),
+                "View",
+                "Quick menu at the cursor (over the viewport)",
+            )
+            .register(
+                Action::ToggleHidpiScaling,
+                KeyChord::new(KeyCode::Slash),
+                "View",
+                "Toggle HiDPI scaling",
+            );
+        keybindings.register(
+            Action::QuickSwitcher,
+            KeyChord::new(KeyCode::KeyP).ctrl(),
+            "View",
+            "Find an entity by name, then select and frame it",
+        );
+        // Either Alt; both show as the same chord.
+        for key in [KeyCode::AltLeft, KeyCode::AltRight] {
+            keybindings.register(
+                Action::InspectPixels,
+                KeyChord::new(key).alt(),
+                "View",
+                "Hold over the viewport to inspect pixel values",
+            );
+        }
+        keybindings.register(
+            Action::ToggleInspectorSource,
+            KeyChord::new(KeyCode::KeyL).alt(),
+            "View",
+            "Switch the pixel inspector between output and linear values",
+        );
+        for (slot, key) in (1..).zip(MACRO_SLOT_KEYS) {
+            keybindings.register(
+                Action::RunMacro(slot),
+                KeyChord::new(key).ctrl(),
+                "Macros",
+                "Run the macro bound to that number",
+            );
+        }
+        keybindings
+    }
+}
+
+impl Keybindings {
+    pub fn register(
+        &mut self,
+        action: Action,
+        chord: KeyChord,
+        category: &'static str,
+        description: &'static str,
+    ) -> &mut Self {
+        self.bindings.push(Keybinding {
+            action,
+            chord,
+            category,
+            description,
+        });
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Keybinding> {
+        self.bindings.iter()
+    }
+
+    /// Label of the first chord bound to `action`, for showing next to menu entries.
+    pub fn label(&self, action: Action) -> String {
+        self.iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.chord.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Resolves actions against the current keyboard state.
+#[derive(SystemParam)]
+pub struct Shortcuts<'w> {
+    input: Res<'w, ButtonInput<KeyCode>>,
+    keybindings: Res<'w, Keybindings>,
+    routing: Res<'w, InputRouting>,
+}
+
+impl Shortcuts<'_> {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.keybindings.enabled
+            && self.routing.keyboard_is_free()
+            && self
+                .keybindings
+                .iter()
+                .filter(|binding| binding.action == action)
+                .any(|binding| binding.chord.just_pressed(&self.input))
+    }
+
+    /// Whether a chord bound to `action` is held down.
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keybindings.enabled
+            && self.routing.keyboard_is_free()
+            && self
+                .keybindings
+                .iter()
+                .filter(|binding| binding.action == action)
+                .any(|binding| binding.chord.pressed(&self.input))
+    }
+
+    /// A binding whose chord was pressed this frame but withheld because a text field has
+    /// keyboard focus. Chords that simply type into the field are not reported.
+    pub fn suppressed(&self) -> Option<&Keybinding> {
+        if !self.keybindings.enabled || self.routing.keyboard_is_free() {
+            return None;
+        }
+        self.keybindings
+            .iter()
+            .find(|binding| !binding.chord.types_text() && binding.chord.just_pressed(&self.input))
+    }
+}
+
+fn sync_shortcut_settings_system(settings: Res<Settings>, mut keybindings: ResMut<Keybindings>) {
+    if settings.is_changed() {
+        keybindings.enabled = settings.input.shortcuts_enabled;
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct HelpOverlay {
+    pub is_open: bool,
+}
+
+#[allow(clippy::type_complexity)]
+fn help_overlay_system(
+    mut contexts: EguiContexts,
+    mut overlay: ResMut<HelpOverlay>,
+    keybindings: Res<Keybindings>,
+) {
+    if !overlay.is_open {
+        return;
+    }
+
+    // Group by category in registration order, merging chords that share an action.
+    let mut categories: Vec<(&str, Vec<(&str, Vec<String>)>)> = Vec::new();
+    for binding in keybindings.iter() {
+        let index = match categories.iter().position(|(c, _)| *c == binding.category) {
+            Some(index) => index,
+            None => {
+                categories.push((binding.category, Vec::new()));
+                categories.len() - 1
+            }
+        };
+        let rows = &mut categories[index].1;
+        let chord = binding.chord.to_string();
+        match rows.iter_mut().find(|(d, _)| *d == binding.description) {
+            Some((_, chords)) if chords.contains(&chord) => {}
+            Some((_, chords)) => chords.push(chord),
+            None => rows.push((binding.description, vec![chord])),
+        }
+    }
+
+    egui::Window::new("Keyboard Shortcuts")
+        .open(&mut overlay.is_open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(contexts.ctx_mut(), |ui| {
+            for (category, rows) in &categories {
+                ui.strong(*category);
+                egui::Grid::new(*category)
+                    .num_columns(2)
+                    .striped(true)
+                    .min_col_width(120.0)
+                    .show(ui, |ui| {
+                        for (description, chords) in rows {
+                            ui.monospace(chords.join(" / "));
+                            ui.label(*description);
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+            }
+        });
+}
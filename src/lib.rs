@@ -0,0 +1,3 @@
+//! Reusable pieces of the sandbox that other bevy_egui applications can depend on.
+
+pub mod widgets;
@@ -0,0 +1,228 @@
+use bevy::{
+    core_pipeline::prepass::{DepthPrepass, NormalPrepass},
+    pbr::{
+        ScreenSpaceAmbientOcclusionBundle, ScreenSpaceAmbientOcclusionQualityLevel,
+        ScreenSpaceAmbientOcclusionSettings, ScreenSpaceReflectionsSettings,
+    },
+    prelude::*,
+    render::view::RenderLayers,
+};
+use bevy_egui::egui;
+use xihydra_bevy::widgets::{Knob, XyPad};
+
+use crate::numeric::drag_value;
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+use crate::selection::render_layers_edit;
+use crate::settings::Settings;
+use crate::{SceneLight, ViewportCamera};
+
+/// The Lighting window: editing the scene's point light, ambient light and ambient occlusion.
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<LightingWindow>().add_systems(
+            Update,
+            lighting_window_system.in_set(LightingWindow::ui_set()),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct LightingWindow {
+    pub is_open: bool,
+}
+
+impl Panel for LightingWindow {
+    const TITLE: &'static str = "Lighting";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn lighting_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<LightingWindow>,
+    mut lights: Query<
+        (
+            Entity,
+            &mut PointLight,
+            &mut Transform,
+            Option<&RenderLayers>,
+        ),
+        With<SceneLight>,
+    >,
+    mut ambient: ResMut<AmbientLight>,
+    mut commands: Commands,
+    cameras: Query<
+        (
+            Entity,
+            Option<&ScreenSpaceAmbientOcclusionSettings>,
+            Has<ScreenSpaceReflectionsSettings>,
+        ),
+        With<ViewportCamera>,
+    >,
+    mut settings: ResMut<Settings>,
+) {
+    if !window.is_open {
+        return;
+    }
+
+    egui::Window::new("Lighting")
+        .open(&mut window.is_open)
+        .show(contexts.ctx::<LightingWindow>(), |ui| {
+            let Ok((light_entity, mut light, mut transform, layers)) = lights.get_single_mut()
+            else {
+                ui.weak("The scene has no point light.");
+                return;
+            };
+
+            ui.strong("Point light");
+            egui::Grid::new("point_light_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Intensity");
+                    let mut intensity = light.intensity;
+                    let knob = Knob::new(&mut intensity, 0.0..=10_000_000.0)
+                        .logarithmic(true)
+                        .suffix(" lm");
+                    if ui.add(knob).changed() {
+                        light.intensity = intensity;
+                    }
+                    ui.end_row();
+
+                    ui.label("Range");
+                    let mut range = light.range;
+                    if ui
+                        .add(Knob::new(&mut range, 0.1..=100.0).suffix(" m"))
+                        .changed()
+                    {
+                        light.range = range;
+                    }
+                    ui.end_row();
+
+                    ui.label("Color");
+                    let mut rgb = light.color.to_srgba().to_f32_array_no_alpha();
+                    if ui.color_edit_button_rgb(&mut rgb).changed() {
+                        light.color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+                    }
+                    ui.end_row();
+
+                    ui.label("Position (x, y)");
+                    let mut xy = egui::vec2(transform.translation.x, transform.translation.y);
+                    if ui
+                        .add(XyPad::new(&mut xy, -20.0..=20.0, -20.0..=20.0))
+                        .changed()
+                    {
+                        transform.translation.x = xy.x;
+                        transform.translation.y = xy.y;
+                    }
+                    ui.end_row();
+
+                    ui.label("Position (z)");
+                    let mut z = transform.translation.z;
+                    if ui.add(drag_value(&mut z).speed(0.1)).changed() {
+                        transform.translation.z = z;
+                    }
+                    ui.end_row();
+
+                    ui.label("Shadows");
+                    let mut shadows = light.shadows_enabled;
+                    if ui.checkbox(&mut shadows, "").changed() {
+                        light.shadows_enabled = shadows;
+                    }
+                    ui.end_row();
+
+                    ui.label("Layers")
+                        .on_hover_text("The light only reaches entities on one of its layers");
+                    let mut edited = layers.cloned().unwrap_or_default();
+                    if render_layers_edit(ui, &mut edited) {
+                        commands.entity(light_entity).insert(edited);
+                    }
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.strong("Ambient light");
+            egui::Grid::new("ambient_light_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Brightness");
+                    let mut brightness = ambient.brightness;
+                    let knob = Knob::new(&mut brightness, 0.0..=10_000.0)
+                        .logarithmic(true)
+                        .suffix(" cd/m²");
+                    if ui.add(knob).changed() {
+                        ambient.brightness = brightness;
+                    }
+                    ui.end_row();
+
+                    ui.label("Color");
+                    let mut rgb = ambient.color.to_srgba().to_f32_array_no_alpha();
+                    if ui.color_edit_button_rgb(&mut rgb).changed() {
+                        ambient.color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+                    }
+                    ui.end_row();
+                });
+
+            ui.separator();
+            let Ok((camera, ssao, has_ssr)) = cameras.get_single() else {
+                return;
+            };
+            let mut enabled = ssao.is_some();
+            if ui
+                .checkbox(&mut enabled, "Screen-space ambient occlusion")
+                .on_hover_text("Not supported on WebGL2")
+                .changed()
+            {
+                if enabled {
+                    commands
+                        .entity(camera)
+                        .insert(ScreenSpaceAmbientOcclusionBundle::default());
+                } else {
+                    commands
+                        .entity(camera)
+                        .remove::<(ScreenSpaceAmbientOcclusionSettings, NormalPrepass)>();
+                    // Screen-space reflections need the depth prepass too.
+                    if !has_ssr {
+                        commands.entity(camera).remove::<DepthPrepass>();
+                    }
+                }
+            }
+            let Some(ssao) = ssao else {
+                return;
+            };
+            ui.horizontal(|ui| {
+                ui.label("Quality");
+                let mut quality = ssao.quality_level;
+                for (level, label) in [
+                    (ScreenSpaceAmbientOcclusionQualityLevel::Low, "Low"),
+                    (ScreenSpaceAmbientOcclusionQualityLevel::Medium, "Medium"),
+                    (ScreenSpaceAmbientOcclusionQualityLevel::High, "High"),
+                    (ScreenSpaceAmbientOcclusionQualityLevel::Ultra, "Ultra"),
+                ] {
+                    ui.selectable_value(&mut quality, level, label);
+                }
+                if quality != ssao.quality_level {
+                    commands
+                        .entity(camera)
+                        .insert(ScreenSpaceAmbientOcclusionSettings {
+                            quality_level: quality,
+                        });
+                }
+            });
+            if settings.graphics.msaa_samples != 1 {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "SSAO only renders with MSAA off.",
+                    );
+                    if ui.button("Turn MSAA off").clicked() {
+                        settings.graphics.msaa_samples = 1;
+                    }
+                });
+            }
+        });
+}
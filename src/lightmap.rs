@@ -0,0 +1,527 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc, Arc, Mutex,
+};
+
+use bevy::{
+    pbr::Lightmap,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::egui;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    hierarchy::entity_label,
+    numeric::slider,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    path_tracer::{self, cosine_sample, SceneMesh, Tracer, RAY_OFFSET},
+    selection::Selection,
+    tasks::TasksWindow,
+    window_title::TaskProgress,
+    RenderCube, Static,
+};
+
+const TASK_NAME: &str = "Lightmap bake";
+/// Rows of one entity's lightmap a worker traces at a time.
+const ROWS_PER_JOB: u32 = 8;
+/// Texels around each triangle's cell, so filtering does not pick up its neighbours.
+const PADDING: f32 = 1.0;
+/// Smallest cell that leaves a texel inside the padding.
+const MIN_CELL: u32 = 3;
+const MAX_SIZE: u32 = 1024;
+
+/// Experimental lightmap baking for the selected [`Static`] entities. A CPU path tracer follows light from
+/// the scene's point lights as it bounces off the other scene entities, and writes what reaches
+/// each surface into a lightmap per entity. Bevy adds lightmaps as indirect light on top of
+/// direct and ambient light, which stay real-time, so only the bounces are baked. Tracing runs
+/// on background threads, reported in the Tasks window.
+pub struct LightmapPlugin;
+
+impl Plugin for LightmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<LightmapWindow>()
+            .add_systems(
+                Update,
+                (
+                    lightmap_window_system.in_set(LightmapWindow::ui_set()),
+                    bake_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Bake Lightmaps…", |world| {
+                world.resource_mut::<LightmapWindow>().is_open = true;
+            }));
+    }
+}
+
+#[derive(Resource)]
+pub struct LightmapWindow {
+    pub is_open: bool,
+    /// Texels along each side of a lightmap; meshes with many triangles get more.
+    size: u32,
+    samples: u32,
+    bounces: u32,
+    bake: Option<Bake>,
+}
+
+impl Default for LightmapWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            size: 128,
+            samples: 64,
+            bounces: 2,
+            bake: None,
+        }
+    }
+}
+
+impl Panel for LightmapWindow {
+    const TITLE: &'static str = "Lightmap Bake";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// The mesh a baked entity had before its lightmap UVs were added, restored on clearing.
+#[derive(Component)]
+struct BakedLightmap {
+    mesh: Handle<Mesh>,
+}
+
+/// One entity's lightmap layout: its triangles in mesh order, each given a square cell of a
+/// grid and mapped onto the cell's lower-left half.
+struct BakeTarget {
+    entity: Entity,
+    size: u32,
+    grid: u32,
+    cell: u32,
+    /// World-space positions and normals of each triangle's corners.
+    triangles: Vec<[(Vec3, Vec3); 3]>,
+}
+
+impl BakeTarget {
+    fn new(entity: Entity, triangles: Vec<[(Vec3, Vec3); 3]>, size: u32) -> Option<Self> {
+        let grid = (triangles.len() as f32).sqrt().ceil().max(1.0) as u32;
+        let size = size.max((grid * MIN_CELL).next_power_of_two());
+        (size <= MAX_SIZE).then(|| Self {
+            entity,
+            size,
+            grid,
+            cell: size / grid,
+            triangles,
+        })
+    }
+
+    /// Where on the lightmap, in texels, corner `corner` of triangle `index` lies.
+    fn corner_texel(&self, index: usize, corner: usize) -> Vec2 {
+        let cell = Vec2::new(
+            (index as u32 % self.grid) as f32,
+            (index as u32 / self.grid) as f32,
+        ) * self.cell as f32;
+        let local = [Vec2::ZERO, Vec2::X, Vec2::Y][corner];
+        cell + Vec2::splat(PADDING) + local * (self.cell as f32 - 2.0 * PADDING)
+    }
+
+    /// The surface point and normal texel `x`, `y` stands for. Texels of a cell outside its
+    /// triangle take the nearest edge, so filtering across the edge stays on the surface.
+    fn texel_surface(&self, x: u32, y: u32) -> Option<(Vec3, Vec3)> {
+        let (column, row) = (x / self.cell, y / self.cell);
+        if column >= self.grid {
+            return None;
+        }
+        let [(a, na), (b, nb), (c, nc)] =
+            *self.triangles.get((row * self.grid + column) as usize)?;
+        let inner = self.cell as f32 - 2.0 * PADDING;
+        let local = |texel: u32, origin: u32| ((texel - origin) as f32 + 0.5 - PADDING) / inner;
+        let mut s = local(x, column * self.cell).clamp(0.0, 1.0);
+        let mut t = local(y, row * self.cell).clamp(0.0, 1.0);
+        if s + t > 1.0 {
+            let sum = s + t;
+            s /= sum;
+            t /= sum;
+        }
+        let point = a + (b - a) * s + (c - a) * t;
+        let normal = (na * (1.0 - s - t) + nb * s + nc * t).normalize_or_zero();
+        Some((point, normal))
+    }
+
+    /// Traces rows `first..first + count`, texel by texel, left to right.
+    fn trace_rows(
+        &self,
+        tracer: &Tracer,
+        first: u32,
+        count: u32,
+        samples: u32,
+        bounces: u32,
+    ) -> Vec<Vec3> {
+        let mut rng = StdRng::seed_from_u64(u64::from(first) ^ self.entity.to_bits());
+        let mut texels = Vec::with_capacity((count * self.size) as usize);
+        for y in first..(first + count).min(self.size) {
+            for x in 0..self.size {
+                let Some((point, normal)) = self.texel_surface(x, y) else {
+                    texels.push(Vec3::ZERO);
+                    continue;
+                };
+                let origin = point + normal * RAY_OFFSET;
+                let sum: Vec3 = (0..samples)
+                    .map(|_| {
+                        tracer
+                            .radiance(
+                                origin,
+                                cosine_sample(normal, &mut rng),
+                                bounces,
+                                Vec3::ZERO,
+                                &mut rng,
+                            )
+                            .unwrap_or(Vec3::ZERO)
+                    })
+                    .sum();
+                texels.push(sum / samples as f32);
+            }
+        }
+        texels
+    }
+}
+
+/// Rows `first..` of target `index`'s lightmap, as a worker traced them.
+struct TracedRows {
+    index: usize,
+    first: u32,
+    texels: Vec<Vec3>,
+}
+
+/// A bake in progress: worker threads take chunks of rows in turn and send back what they
+/// traced. Dropping it stops them after their current chunk.
+struct Bake {
+    targets: Arc<Vec<BakeTarget>>,
+    traced: Mutex<mpsc::Receiver<TracedRows>>,
+    stop: Arc<AtomicBool>,
+    chunks: usize,
+    received: usize,
+    texels: Vec<Vec<Vec3>>,
+}
+
+impl Bake {
+    fn start(tracer: Tracer, targets: Vec<BakeTarget>, samples: u32, bounces: u32) -> Self {
+        let chunks: Vec<(usize, u32)> = targets
+            .iter()
+            .enumerate()
+            .flat_map(|(index, target)| {
+                (0..target.size)
+                    .step_by(ROWS_PER_JOB as usize)
+                    .map(move |first| (index, first))
+            })
+            .collect();
+        let tracer = Arc::new(tracer);
+        let targets = Arc::new(targets);
+        let chunks = Arc::new(chunks);
+        let next = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let workers = std::thread::available_parallelism().map_or(2, |count| count.get());
+        for _ in 0..workers.min(chunks.len()) {
+            let (tracer, targets, chunks) = (tracer.clone(), targets.clone(), chunks.clone());
+            let (next, stop, sender) = (next.clone(), stop.clone(), sender.clone());
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let Some(&(index, first)) = chunks.get(next.fetch_add(1, Ordering::Relaxed))
+                    else {
+                        break;
+                    };
+                    let texels =
+                        targets[index].trace_rows(&tracer, first, ROWS_PER_JOB, samples, bounces);
+                    if sender
+                        .send(TracedRows {
+                            index,
+                            first,
+                            texels,
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        Self {
+            texels: targets
+                .iter()
+                .map(|target| vec![Vec3::ZERO; (target.size * target.size) as usize])
+                .collect(),
+            targets,
+            traced: Mutex::new(receiver),
+            stop,
+            chunks: chunks.len(),
+            received: 0,
+        }
+    }
+
+    /// Copies in the rows traced since the last call.
+    fn receive(&mut self) {
+        let Ok(traced) = self.traced.get_mut() else {
+            return;
+        };
+        for rows in traced.try_iter() {
+            let start = (rows.first * self.targets[rows.index].size) as usize;
+            self.texels[rows.index][start..start + rows.texels.len()].copy_from_slice(&rows.texels);
+            self.received += 1;
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.received >= self.chunks
+    }
+
+    fn fraction(&self) -> f32 {
+        self.received as f32 / self.chunks.max(1) as f32
+    }
+}
+
+impl Drop for Bake {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn lightmap_window_system(
+    mut commands: Commands,
+    mut contexts: PanelContexts,
+    mut window: ResMut<LightmapWindow>,
+    mut tasks_window: ResMut<TasksWindow>,
+    selection: Res<Selection>,
+    scene: Query<SceneMesh, With<RenderCube>>,
+    animated: Query<(Entity, Option<&Name>), (With<RenderCube>, Without<Static>)>,
+    lights: Query<(&PointLight, &GlobalTransform)>,
+    baked: Query<(Entity, &BakedLightmap)>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    let LightmapWindow {
+        is_open,
+        size,
+        samples,
+        bounces,
+        bake,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    // A lightmap only holds while its entity stays put, so animated entities are left out.
+    let selected: Vec<SceneMesh> = scene
+        .iter_many(&selection.entities)
+        .filter(|(entity, ..)| !animated.contains(*entity))
+        .collect();
+    let skipped: Vec<(Entity, String)> = animated
+        .iter_many(&selection.entities)
+        .map(|(entity, name)| (entity, entity_label(entity, name, false)))
+        .collect();
+    let mut start = false;
+    let mut clear: Option<Vec<Entity>> = None;
+    egui::Window::new(LightmapWindow::TITLE)
+        .open(is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<LightmapWindow>(), |ui| {
+            ui.weak(
+                "Experimental. Bakes the light the scene's point lights bounce onto the \
+                 selected static entities; direct and ambient light stay real-time. Moving baked entities or \
+                 lights leaves their lightmaps stale.",
+            );
+            egui::Grid::new("lightmap_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Lightmap size");
+                    egui::ComboBox::from_id_source("lightmap_size")
+                        .selected_text(format!("{size}²"))
+                        .show_ui(ui, |ui| {
+                            for option in [64, 128, 256, 512] {
+                                ui.selectable_value(size, option, format!("{option}²"));
+                            }
+                        })
+                        .response
+                        .on_hover_text("Meshes with many triangles get larger lightmaps");
+                    ui.end_row();
+                    ui.label("Samples");
+                    ui.add(slider(samples, 8..=1024).logarithmic(true))
+                        .on_hover_text("Rays per texel; more is smoother and slower");
+                    ui.end_row();
+                    ui.label("Bounces");
+                    ui.add(slider(bounces, 1..=4));
+                    ui.end_row();
+                });
+            ui.separator();
+            match bake.as_ref().map(Bake::fraction) {
+                Some(fraction) => {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::ProgressBar::new(fraction)
+                                .show_percentage()
+                                .desired_width(200.0),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            *bake = None;
+                        }
+                    });
+                    ui.ctx().request_repaint();
+                }
+                None => {
+                    ui.horizontal(|ui| {
+                        start = ui
+                            .add_enabled(
+                                !selected.is_empty(),
+                                egui::Button::new(format!("Bake {} Selected", selected.len())),
+                            )
+                            .on_disabled_hover_text("Select static scene entities to bake")
+                            .clicked();
+                        if ui.button("Clear Selected").clicked() {
+                            clear = Some(selection.entities.clone());
+                        }
+                        if ui.button("Clear All").clicked() {
+                            clear = Some(baked.iter().map(|(entity, _)| entity).collect());
+                        }
+                    });
+                    if !skipped.is_empty() {
+                        let names: Vec<&str> = skipped.iter().map(|(_, name)| name.as_str()).collect();
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!("Skipping {} animated", skipped.len()),
+                            )
+                            .on_hover_text(names.join("\n"));
+                            if ui.button("Make Static").clicked() {
+                                for (entity, _) in &skipped {
+                                    commands.entity(*entity).insert(Static);
+                                }
+                            }
+                        });
+                    }
+                }
+            }
+        });
+
+    if let Some(entities) = clear {
+        for (entity, baked) in baked.iter_many(&entities) {
+            commands
+                .entity(entity)
+                .insert(baked.mesh.clone())
+                .remove::<(Lightmap, BakedLightmap)>();
+        }
+    }
+    if !start {
+        return;
+    }
+
+    let mut targets = Vec::new();
+    let tracer = path_tracer::snapshot(&scene, &lights, &meshes, &materials, |entity, corners| {
+        if selected.iter().any(|(selected, ..)| *selected == entity) {
+            match BakeTarget::new(entity, corners, *size) {
+                Some(target) => targets.push(target),
+                None => warn!("Skipping {entity}: too many triangles for a lightmap"),
+            }
+        }
+    });
+    *bake = Some(Bake::start(tracer, targets, *samples, *bounces));
+    tasks_window.is_open = true;
+}
+
+/// Collects finished rows and, once every row is in, applies the lightmaps.
+#[allow(clippy::too_many_arguments)]
+fn bake_system(
+    mut commands: Commands,
+    mut window: ResMut<LightmapWindow>,
+    mut progress: ResMut<TaskProgress>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    entities: Query<(
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+        Option<&BakedLightmap>,
+    )>,
+) {
+    let Some(bake) = &mut window.bake else {
+        return;
+    };
+    if progress.take_cancel(TASK_NAME) {
+        window.bake = None;
+        return;
+    }
+    bake.receive();
+    if !bake.is_done() {
+        progress.report_cancellable(TASK_NAME, bake.fraction());
+        return;
+    }
+
+    let Some(bake) = window.bake.take() else {
+        return;
+    };
+    for (target, texels) in bake.targets.iter().zip(&bake.texels) {
+        let Ok((mesh_handle, material_handle, baked)) = entities.get(target.entity) else {
+            continue;
+        };
+        let (Some(mesh), Some(material)) =
+            (meshes.get(mesh_handle), materials.get(material_handle))
+        else {
+            continue;
+        };
+        // One vertex per triangle corner, each with its own spot on the lightmap.
+        let mut mesh = mesh.clone();
+        mesh.duplicate_vertices();
+        let uvs: Vec<[f32; 2]> = (0..target.triangles.len())
+            .flat_map(|index| (0..3).map(move |corner| (index, corner)))
+            .map(|(index, corner)| (target.corner_texel(index, corner) / target.size as f32).into())
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, uvs);
+
+        // Stored relative to the brightest texel, which the material's exposure restores.
+        let brightest = texels
+            .iter()
+            .map(|texel| texel.max_element())
+            .fold(0.0, f32::max)
+            .max(1e-6);
+        let data: Vec<u8> = texels
+            .iter()
+            .flat_map(|texel| {
+                let texel = *texel / brightest;
+                Color::linear_rgb(texel.x, texel.y, texel.z)
+                    .to_srgba()
+                    .to_u8_array()
+            })
+            .collect();
+        let image = Image::new(
+            Extent3d {
+                width: target.size,
+                height: target.size,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        let material = StandardMaterial {
+            lightmap_exposure: brightest,
+            ..material.clone()
+        };
+
+        let original = baked.map_or_else(|| mesh_handle.clone(), |baked| baked.mesh.clone());
+        commands.entity(target.entity).insert((
+            meshes.add(mesh),
+            materials.add(material),
+            Lightmap {
+                image: images.add(image),
+                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            },
+            BakedLightmap { mesh: original },
+        ));
+    }
+    info!("Baked {} lightmaps", bake.targets.len());
+}
@@ -0,0 +1,452 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+use bevy_egui::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    icons::{Icon, IconButtonsExt},
+    numeric::drag_value,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{self, CustomMesh},
+    selection::Selection,
+    Static, ViewportCamera,
+};
+
+/// Expansion stops early once the string would grow past this many symbols.
+const MAX_SYMBOLS: usize = 200_000;
+/// Sides of each branch segment's prism.
+const SIDES: usize = 6;
+
+/// The L-System window: grows branching plants from rewrite rules and a turtle. Plants are
+/// ordinary scene entities whose mesh is rebuilt from their [`Plant`] parameters.
+pub struct LSystemPlugin;
+
+impl Plugin for LSystemPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<LSystemWindow>()
+            .add_systems(
+                Update,
+                (
+                    lsystem_window_system.in_set(LSystemWindow::ui_set()),
+                    build_plant_meshes_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Add Plant…", |world| {
+                world.resource_mut::<LSystemWindow>().is_open = true;
+            }));
+    }
+}
+
+/// Rewrites `symbol` into `replacement`. When several rules share a symbol one is picked at
+/// random each time, seeded by [`Plant::seed`].
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlantRule {
+    pub symbol: char,
+    pub replacement: String,
+}
+
+impl PlantRule {
+    fn new(symbol: char, replacement: &str) -> Self {
+        Self {
+            symbol,
+            replacement: replacement.to_owned(),
+        }
+    }
+}
+
+/// The source of a plant entity's mesh; it is rebuilt whenever this changes.
+///
+/// Turtle commands: `F`/`G` draw forward, `f` moves without drawing, `+`/`-` turn, `&`/`^`
+/// pitch, `\`/`/` roll, `|` turns around, `!` thins the branch and `[`/`]` push and pop.
+#[derive(Component, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plant {
+    pub axiom: String,
+    pub rules: Vec<PlantRule>,
+    pub iterations: u32,
+    /// Turn angle in degrees.
+    pub angle: f32,
+    /// World length of one `F`.
+    pub length: f32,
+    /// Radius of the trunk.
+    pub thickness: f32,
+    /// Radius multiplier applied by `!`.
+    pub taper: f32,
+    /// Random variation of each turn, in degrees.
+    pub jitter: f32,
+    pub seed: u64,
+}
+
+impl Default for Plant {
+    fn default() -> Self {
+        PRESETS[0].1()
+    }
+}
+
+/// Named starting points, from *The Algorithmic Beauty of Plants*.
+#[allow(clippy::type_complexity)]
+const PRESETS: &[(&str, fn() -> Plant)] = &[
+    ("Bush", || Plant {
+        axiom: "A".to_owned(),
+        rules: vec![
+            PlantRule::new('A', "[&F!A]/////[&F!A]///////[&F!A]"),
+            PlantRule::new('F', "S/////F"),
+            PlantRule::new('S', "F"),
+        ],
+        iterations: 5,
+        angle: 22.5,
+        length: 0.25,
+        thickness: 0.06,
+        taper: 0.7,
+        jitter: 4.0,
+        seed: 1,
+    }),
+    ("Weed", || Plant {
+        axiom: "F".to_owned(),
+        rules: vec![
+            PlantRule::new('F', "F[+F]F[-F]F"),
+            PlantRule::new('F', "F[+F]F"),
+            PlantRule::new('F', "F[-F]F"),
+        ],
+        iterations: 4,
+        angle: 25.7,
+        length: 0.05,
+        thickness: 0.015,
+        taper: 0.8,
+        jitter: 6.0,
+        seed: 1,
+    }),
+    ("Fern", || Plant {
+        axiom: "X".to_owned(),
+        rules: vec![
+            PlantRule::new('X', "F+[[X]-X]-F[-FX]+X"),
+            PlantRule::new('F', "FF"),
+        ],
+        iterations: 5,
+        angle: 25.0,
+        length: 0.04,
+        thickness: 0.01,
+        taper: 0.8,
+        jitter: 3.0,
+        seed: 1,
+    }),
+];
+
+/// Counts from the last rebuild, shown in the window.
+#[derive(Clone, Copy)]
+struct PlantStats {
+    symbols: usize,
+    segments: usize,
+    truncated: bool,
+}
+
+#[derive(Default, Resource)]
+pub struct LSystemWindow {
+    pub is_open: bool,
+    template: Plant,
+    stats: Option<PlantStats>,
+}
+
+impl Panel for LSystemWindow {
+    const TITLE: &'static str = "L-System";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl Plant {
+    /// Applies the rules `iterations` times. Returns the string and whether it was cut short.
+    fn expand(&self) -> (String, bool) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut current = self.axiom.clone();
+        for _ in 0..self.iterations {
+            let mut next = String::with_capacity(current.len() * 2);
+            for symbol in current.chars() {
+                let candidates: Vec<&PlantRule> = self
+                    .rules
+                    .iter()
+                    .filter(|rule| rule.symbol == symbol)
+                    .collect();
+                match candidates.len() {
+                    0 => next.push(symbol),
+                    1 => next.push_str(&candidates[0].replacement),
+                    n => next.push_str(&candidates[rng.gen_range(0..n)].replacement),
+                }
+                if next.len() > MAX_SYMBOLS {
+                    return (current, true);
+                }
+            }
+            current = next;
+        }
+        (current, false)
+    }
+
+    /// Walks the turtle over `symbols`, returning each branch segment as its start, end and
+    /// radius.
+    fn segments(&self, symbols: &str) -> Vec<(Vec3, Vec3, f32)> {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(1));
+        let mut turn = |sign: f32| {
+            let jitter = if self.jitter > 0.0 {
+                rng.gen_range(-self.jitter..=self.jitter)
+            } else {
+                0.0
+            };
+            (self.angle * sign + jitter).to_radians()
+        };
+        let mut position = Vec3::ZERO;
+        let mut rotation = Quat::IDENTITY;
+        let mut radius = self.thickness;
+        let mut stack = Vec::new();
+        let mut segments = Vec::new();
+        for symbol in symbols.chars() {
+            match symbol {
+                'F' | 'G' | 'f' => {
+                    let end = position + rotation * Vec3::Y * self.length;
+                    if symbol != 'f' {
+                        segments.push((position, end, radius));
+                    }
+                    position = end;
+                }
+                '+' => rotation *= Quat::from_rotation_z(turn(1.0)),
+                '-' => rotation *= Quat::from_rotation_z(turn(-1.0)),
+                '&' => rotation *= Quat::from_rotation_x(turn(1.0)),
+                '^' => rotation *= Quat::from_rotation_x(turn(-1.0)),
+                '\\' => rotation *= Quat::from_rotation_y(turn(1.0)),
+                '/' => rotation *= Quat::from_rotation_y(turn(-1.0)),
+                '|' => rotation *= Quat::from_rotation_z(std::f32::consts::PI),
+                '!' => radius *= self.taper,
+                '[' => stack.push((position, rotation, radius)),
+                ']' => {
+                    if let Some(state) = stack.pop() {
+                        (position, rotation, radius) = state;
+                    }
+                }
+                _ => {}
+            }
+        }
+        segments
+    }
+}
+
+/// One open prism per segment, with smooth radial normals.
+fn branch_mesh(segments: &[(Vec3, Vec3, f32)]) -> Mesh {
+    let mut positions = Vec::with_capacity(segments.len() * SIDES * 2);
+    let mut normals = Vec::with_capacity(segments.len() * SIDES * 2);
+    let mut indices = Vec::with_capacity(segments.len() * SIDES * 6);
+    for &(start, end, radius) in segments {
+        let axis = (end - start).normalize_or(Vec3::Y);
+        let rotation = Quat::from_rotation_arc(Vec3::Y, axis);
+        let base = positions.len() as u32;
+        for side in 0..SIDES {
+            let angle = side as f32 / SIDES as f32 * std::f32::consts::TAU;
+            let normal = rotation * Vec3::new(angle.cos(), 0.0, angle.sin());
+            for center in [start, end] {
+                positions.push((center + normal * radius).to_array());
+                normals.push(normal.to_array());
+            }
+        }
+        for side in 0..SIDES as u32 {
+            let next = (side + 1) % SIDES as u32;
+            let (a, b) = (base + side * 2, base + next * 2);
+            indices.extend([a, a + 1, b, b, a + 1, b + 1]);
+        }
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn plant_ui(ui: &mut egui::Ui, plant: &mut Plant) {
+    ui.horizontal(|ui| {
+        ui.label("Preset");
+        for (name, preset) in PRESETS {
+            if ui.button(*name).clicked() {
+                *plant = preset();
+            }
+        }
+    });
+    egui::Grid::new("plant_rules")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Axiom");
+            ui.add(egui::TextEdit::singleline(&mut plant.axiom).code_editor());
+            ui.end_row();
+            let mut removed = None;
+            for (index, rule) in plant.rules.iter_mut().enumerate() {
+                let mut symbol = rule.symbol.to_string();
+                if ui
+                    .add(
+                        egui::TextEdit::singleline(&mut symbol)
+                            .code_editor()
+                            .desired_width(16.0),
+                    )
+                    .changed()
+                {
+                    rule.symbol = symbol.chars().last().unwrap_or(rule.symbol);
+                }
+                ui.horizontal(|ui| {
+                    ui.label("→");
+                    ui.add(egui::TextEdit::singleline(&mut rule.replacement).code_editor());
+                    if ui
+                        .small_icon_button(Icon::Delete, "")
+                        .on_hover_text("Remove rule")
+                        .clicked()
+                    {
+                        removed = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+            if let Some(index) = removed {
+                plant.rules.remove(index);
+            }
+        });
+    if ui.button("Add rule").clicked() {
+        plant.rules.push(PlantRule::new('F', "F"));
+    }
+
+    ui.separator();
+    egui::Grid::new("plant_turtle")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Iterations");
+            ui.add(egui::Slider::new(&mut plant.iterations, 0..=8));
+            ui.end_row();
+            ui.label("Angle");
+            ui.add(egui::Slider::new(&mut plant.angle, 0.0..=180.0).suffix("°"));
+            ui.end_row();
+            ui.label("Step length");
+            ui.add(
+                egui::Slider::new(&mut plant.length, 0.005..=1.0)
+                    .logarithmic(true)
+                    .suffix(" m"),
+            );
+            ui.end_row();
+            ui.label("Thickness");
+            ui.add(
+                egui::Slider::new(&mut plant.thickness, 0.001..=0.5)
+                    .logarithmic(true)
+                    .suffix(" m"),
+            );
+            ui.end_row();
+            ui.label("Taper");
+            ui.add(egui::Slider::new(&mut plant.taper, 0.3..=1.0));
+            ui.end_row();
+            ui.label("Jitter");
+            ui.add(egui::Slider::new(&mut plant.jitter, 0.0..=45.0).suffix("°"));
+            ui.end_row();
+            ui.label("Seed");
+            ui.horizontal(|ui| {
+                ui.add(drag_value(&mut plant.seed));
+                if ui.icon_button(Icon::Random, "Randomize").clicked() {
+                    plant.seed = rand::thread_rng().gen();
+                }
+            });
+            ui.end_row();
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn lsystem_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<LSystemWindow>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut selection: ResMut<Selection>,
+    mut plants: Query<&mut Plant>,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    let LSystemWindow {
+        is_open,
+        template,
+        stats,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("L-System")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<LSystemWindow>(), |ui| {
+            let selected = selection
+                .primary()
+                .filter(|entity| plants.contains(*entity));
+            match selected.and_then(|entity| plants.get_mut(entity).ok()) {
+                Some(mut plant) => {
+                    ui.strong("Selected plant");
+                    let mut edited = plant.clone();
+                    plant_ui(ui, &mut edited);
+                    if edited != *plant {
+                        *plant = edited;
+                    }
+                }
+                None => {
+                    plant_ui(ui, template);
+                    if ui.button("Generate").clicked() {
+                        // In front of the camera, growing up from there.
+                        let translation = cameras.get_single().map_or(Vec3::ZERO, |camera| {
+                            camera.translation() + camera.forward() * 10.0
+                        });
+                        let entity = scene::spawn_cube(
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                            Transform::from_translation(translation),
+                            Color::srgb(0.45, 0.32, 0.2),
+                        );
+                        commands.entity(entity).insert((
+                            template.clone(),
+                            Static,
+                            Name::new("Plant"),
+                        ));
+                        selection.select(entity);
+                    }
+                }
+            }
+            if let Some(stats) = stats {
+                ui.separator();
+                ui.weak(format!(
+                    "{} symbols, {} segments",
+                    stats.symbols, stats.segments
+                ));
+                if stats.truncated {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Stopped early: the string grew too long. Lower the iterations.",
+                    );
+                }
+            }
+        });
+}
+
+fn build_plant_meshes_system(
+    mut commands: Commands,
+    mut window: ResMut<LSystemWindow>,
+    mut plants: Query<(Entity, &Plant, &mut Handle<Mesh>), Changed<Plant>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, plant, mut mesh) in &mut plants {
+        let (symbols, truncated) = plant.expand();
+        let segments = plant.segments(&symbols);
+        *mesh = meshes.add(branch_mesh(&segments));
+        commands.entity(entity).insert(CustomMesh);
+        window.stats = Some(PlantStats {
+            symbols: symbols.chars().count(),
+            segments: segments.len(),
+            truncated,
+        });
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    errors::AppError,
+    icons::{self, Icon, IconButtonsExt},
+    keybindings::{Action, Shortcuts, MACRO_SLOT_KEYS},
+    panels::{Menu, MenuItem, MenuItemRun, Panel, PanelContexts, PanelRegistry, RegisterPanelExt},
+    settings::{Settings, UiMacro},
+    status_bar::StatusBar,
+};
+
+const RECORD_LABEL: &str = "Record Macro";
+
+/// Records the menu commands the user runs, whether from the menu bar, the toolbar, a shortcut
+/// or a button standing in for a menu entry, and replays them as one: "Add Cube" five times,
+/// "Frame All", then "Take Snapshot", say. Macros are kept in the settings and can be bound to
+/// Ctrl and a digit. Playback runs one step per frame, so each step sees what the previous one
+/// did, such as cubes it spawned.
+pub struct MacrosPlugin;
+
+impl Plugin for MacrosPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<MacrosWindow>()
+            .init_resource::<MacroPlayback>()
+            .add_systems(
+                Update,
+                (
+                    record_macro_system,
+                    macros_window_system,
+                    macro_shortcuts_system,
+                    play_macro_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, RECORD_LABEL, |world| {
+                    let mut window = world.resource_mut::<MacrosWindow>();
+                    window.is_open = true;
+                    window.toggle_recording();
+                })
+                .icon(Icon::Record)
+                .separator_before(),
+            );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct MacrosWindow {
+    pub is_open: bool,
+    /// Labels recorded so far, while recording.
+    recording: Option<Vec<String>>,
+    /// A finished recording, saved to the settings on the next frame.
+    finished: Option<Vec<String>>,
+}
+
+impl Panel for MacrosWindow {
+    const TITLE: &'static str = "Macros";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl MacrosWindow {
+    fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(steps) => self.finished = Some(steps),
+            None => self.recording = Some(Vec::new()),
+        }
+    }
+}
+
+/// The steps of the macro being played, first to run at the front.
+#[derive(Default, Resource)]
+struct MacroPlayback {
+    name: String,
+    steps: VecDeque<String>,
+}
+
+impl MacroPlayback {
+    fn start(&mut self, recorded: &UiMacro) {
+        self.name.clone_from(&recorded.name);
+        self.steps = recorded.steps.iter().cloned().collect();
+    }
+}
+
+fn record_macro_system(mut runs: EventReader<MenuItemRun>, mut window: ResMut<MacrosWindow>) {
+    let Some(steps) = &mut window.bypass_change_detection().recording else {
+        runs.clear();
+        return;
+    };
+    for run in runs.read() {
+        if run.label != RECORD_LABEL {
+            steps.push(run.label.to_owned());
+        }
+    }
+}
+
+/// `steps` on one line, repeats folded: "Add Cube ×5 → Frame All".
+fn summary(steps: &[String]) -> String {
+    let mut parts: Vec<(&str, usize)> = Vec::new();
+    for step in steps {
+        match parts.last_mut() {
+            Some((last, count)) if *last == step.as_str() => *count += 1,
+            _ => parts.push((step, 1)),
+        }
+    }
+    parts
+        .into_iter()
+        .map(|(step, count)| match count {
+            1 => step.to_owned(),
+            count => format!("{step} ×{count}"),
+        })
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+fn slot_label(slot: Option<u8>) -> String {
+    match slot {
+        Some(slot) => format!("Ctrl+{slot}"),
+        None => "No shortcut".to_owned(),
+    }
+}
+
+fn macros_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<MacrosWindow>,
+    mut settings: ResMut<Settings>,
+    mut playback: ResMut<MacroPlayback>,
+) {
+    if let Some(steps) = window.finished.take() {
+        if !steps.is_empty() {
+            let name = format!("Macro {}", settings.macros.len() + 1);
+            settings.macros.push(UiMacro {
+                name,
+                steps,
+                slot: None,
+            });
+        }
+    }
+    let MacrosWindow {
+        is_open, recording, ..
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut toggle = false;
+    // Edit a copy, so only a real edit counts as a settings change.
+    let mut macros = settings.macros.clone();
+    egui::Window::new(MacrosWindow::TITLE)
+        .open(is_open)
+        .default_width(420.0)
+        .show(contexts.ctx::<MacrosWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                match recording {
+                    Some(steps) => {
+                        if ui.icon_button(Icon::Stop, "Stop recording").clicked() {
+                            toggle = true;
+                        }
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!("Recording, {} steps", steps.len()),
+                        );
+                    }
+                    None => {
+                        if ui
+                            .icon_button(Icon::Record, "Record")
+                            .on_hover_text(
+                                "Record menu commands, toolbar buttons and shortcuts until \
+                                 stopped",
+                            )
+                            .clicked()
+                        {
+                            toggle = true;
+                        }
+                    }
+                }
+                if !playback.steps.is_empty() {
+                    ui.label(format!(
+                        "Playing {}, {} steps left",
+                        playback.name,
+                        playback.steps.len()
+                    ));
+                }
+            });
+            if let Some(steps) = recording.as_ref().filter(|steps| !steps.is_empty()) {
+                ui.weak(summary(steps));
+            }
+            ui.separator();
+
+            if macros.is_empty() {
+                ui.weak("No macros yet. Record one, then run it here or with its shortcut.");
+                return;
+            }
+            let mut removed = None;
+            let mut bound = None;
+            egui::Grid::new("macros")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, recorded) in macros.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    playback.steps.is_empty(),
+                                    icons::button(ui, Icon::Play, ""),
+                                )
+                                .on_hover_text("Run")
+                                .clicked()
+                            {
+                                playback.start(recorded);
+                            }
+                            ui.add(
+                                egui::TextEdit::singleline(&mut recorded.name).desired_width(120.0),
+                            );
+                            egui::ComboBox::from_id_source(("macro_slot", index))
+                                .selected_text(slot_label(recorded.slot))
+                                .show_ui(ui, |ui| {
+                                    let slots = std::iter::once(None)
+                                        .chain((1..=MACRO_SLOT_KEYS.len() as u8).map(Some));
+                                    for slot in slots {
+                                        if ui
+                                            .selectable_value(
+                                                &mut recorded.slot,
+                                                slot,
+                                                slot_label(slot),
+                                            )
+                                            .clicked()
+                                        {
+                                            bound = Some(index);
+                                        }
+                                    }
+                                });
+                            if ui
+                                .small_icon_button(Icon::Delete, "")
+                                .on_hover_text("Delete")
+                                .clicked()
+                            {
+                                removed = Some(index);
+                            }
+                        });
+                        ui.label(summary(&recorded.steps));
+                        ui.end_row();
+                    }
+                });
+            // A shortcut runs one macro; the one just bound takes it from the others.
+            if let Some(index) = bound {
+                let slot = macros[index].slot;
+                for (other, recorded) in macros.iter_mut().enumerate() {
+                    if other != index && slot.is_some() && recorded.slot == slot {
+                        recorded.slot = None;
+                    }
+                }
+            }
+            if let Some(index) = removed {
+                macros.remove(index);
+            }
+        });
+
+    if toggle {
+        window.toggle_recording();
+    }
+    if macros != settings.macros {
+        settings.macros = macros;
+    }
+}
+
+fn macro_shortcuts_system(
+    shortcuts: Shortcuts,
+    settings: Res<Settings>,
+    mut playback: ResMut<MacroPlayback>,
+) {
+    if !playback.steps.is_empty() {
+        return;
+    }
+    if let Some(recorded) = settings.macros.iter().find(|recorded| {
+        recorded
+            .slot
+            .is_some_and(|slot| shortcuts.just_pressed(Action::RunMacro(slot)))
+    }) {
+        playback.start(recorded);
+    }
+}
+
+fn play_macro_system(
+    mut playback: ResMut<MacroPlayback>,
+    registry: Res<PanelRegistry>,
+    mut commands: Commands,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    time: Res<Time>,
+) {
+    let Some(step) = playback.steps.pop_front() else {
+        return;
+    };
+    // Run directly rather than through `RunMenuItem`, so a recording does not pick up the
+    // steps of a macro played during it.
+    match registry.find_item(&step) {
+        Some(item) => commands.add(item.run),
+        None => {
+            errors.send(
+                AppError::new(
+                    "Macros",
+                    format!("{} stopped: there is no \"{step}\" command", playback.name),
+                )
+                .suggest("Delete the macro and record it again."),
+            );
+            playback.steps.clear();
+            return;
+        }
+    }
+    if playback.steps.is_empty() {
+        status.flash(format!("Ran {}", playback.name), time.elapsed_seconds());
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::{
+    log::LogPlugin,
     prelude::*,
     render::{
         camera::RenderTarget,
@@ -9,8 +10,227 @@ use bevy::{
     },
     window::PrimaryWindow,
 };
-use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSettings, EguiUserTextures};
-use rand::thread_rng;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiUserTextures};
+
+mod align;
+mod animated_textures;
+mod array;
+mod asset_snapshots;
+mod background;
+mod batching;
+mod bindings;
+mod boids;
+mod bookmarks;
+mod budget;
+mod bvh;
+mod camera;
+mod clipboard;
+mod clipping;
+mod cloth;
+mod compare;
+mod compute_playground;
+mod constraints;
+mod crash_report;
+mod csg;
+mod cubemap;
+mod culling;
+mod cursor;
+mod day_night;
+mod decal;
+mod errors;
+mod exploded_view;
+mod expr;
+mod fade;
+mod fonts;
+mod frame_capture;
+mod framing;
+mod groups;
+mod guides;
+mod heatmap;
+mod hierarchy;
+mod history;
+mod icons;
+mod image_ops;
+mod init_script;
+mod input;
+mod keybindings;
+mod lighting;
+mod lightmap;
+mod lsystem;
+mod macros;
+mod material_ball;
+mod notes;
+mod numeric;
+mod outline;
+mod overlay;
+mod palette;
+mod panels;
+mod particles;
+mod path_tracer;
+mod picking;
+mod pie_menu;
+mod pixel_inspector;
+mod placement;
+mod pool;
+mod post_fx;
+mod presenter;
+mod progressive_render;
+mod properties;
+mod quick_switcher;
+mod randomize;
+mod readback;
+mod reference_objects;
+mod reflections;
+mod report;
+mod resources;
+mod safe_mode;
+mod scatter;
+mod scene;
+mod scene_diff;
+mod scene_templates;
+mod scopes;
+mod scripts;
+mod selection;
+mod selection_sets;
+mod session_stats;
+mod settings;
+mod simulation;
+mod slow_frames;
+mod snapshot_compare;
+mod snapshots;
+mod sprite_sheet;
+mod sprites;
+mod status_bar;
+mod stereo;
+mod style_compare;
+mod tasks;
+mod telemetry;
+mod terrain;
+mod text3d;
+mod thumbnails;
+mod tilemap;
+mod timeline;
+mod tour;
+mod transform_gizmo;
+#[cfg(feature = "tray")]
+mod tray;
+mod ui_layout;
+mod versioning;
+mod vertex_paint;
+mod viewport;
+mod virtual_keyboard;
+mod watch_folder;
+mod weather;
+mod web_export;
+mod window_scale;
+mod window_title;
+
+use align::AlignPlugin;
+use animated_textures::{AnimatedTexturesPlugin, SidePanelAnimation};
+use array::ArrayPlugin;
+use asset_snapshots::AssetSnapshotsPlugin;
+use background::{BackgroundPlugin, ViewportBackground};
+use batching::BatchingPlugin;
+use bindings::BindingsPlugin;
+use boids::BoidsPlugin;
+use bookmarks::BookmarksPlugin;
+use budget::BudgetPlugin;
+use camera::CameraPlugin;
+use clipboard::{CanvasBackground, ClipboardPlugin, CopyCanvas};
+use clipping::ClippingPlugin;
+use cloth::ClothPlugin;
+use compare::ComparePlugin;
+use compute_playground::ComputePlaygroundPlugin;
+use constraints::snap_direction;
+use crash_report::CrashReportPlugin;
+use csg::CsgPlugin;
+use cubemap::CubemapPlugin;
+use culling::CullingPlugin;
+use cursor::CursorPlugin;
+use day_night::DayNightPlugin;
+use decal::{DecalPlugin, ProjectPainting};
+use errors::{AppError, ErrorsPlugin};
+use exploded_view::ExplodedViewPlugin;
+use fade::FadePlugin;
+use fonts::FontsPlugin;
+use frame_capture::FrameCapturePlugin;
+use framing::FramingPlugin;
+use groups::GroupsPlugin;
+use guides::CanvasGuides;
+use heatmap::HeatmapPlugin;
+use hierarchy::HierarchyPlugin;
+use history::{History, HistoryPlugin};
+use icons::{Icon, IconButtonsExt, IconsPlugin};
+use image_ops::{derive_image, ImageOp, ImageOpsPlugin};
+use init_script::InitScriptPlugin;
+use input::InputRoutingPlugin;
+use keybindings::{Action, Keybindings, KeybindingsPlugin, Shortcuts};
+use lighting::LightingPlugin;
+use lightmap::LightmapPlugin;
+use lsystem::LSystemPlugin;
+use macros::MacrosPlugin;
+use material_ball::MaterialBallPlugin;
+use notes::NotesPlugin;
+use outline::OutlinePlugin;
+use overlay::{overlay_off, OverlayPlugin};
+use palette::{ColorPalette, PalettePlugin};
+use panels::{
+    Menu, MenuItem, PanelRegistry, PanelsPlugin, RegisterPanelExt, RunMenuItem, UiStateRegistry,
+};
+use particles::ParticlesPlugin;
+use picking::PickingPlugin;
+use pie_menu::PieMenuPlugin;
+use pixel_inspector::PixelInspectorPlugin;
+use placement::{Placement, PlacementPlugin};
+use pool::PoolPlugin;
+use post_fx::PostFxPlugin;
+use presenter::PresenterPlugin;
+use progressive_render::ProgressiveRenderPlugin;
+use quick_switcher::QuickSwitcherPlugin;
+use randomize::RandomizePlugin;
+use readback::ReadbackPlugin;
+use reference_objects::ReferenceObjectsPlugin;
+use reflections::ReflectionsPlugin;
+use report::ReportPlugin;
+use resources::ResourcesPlugin;
+use safe_mode::SafeModePlugin;
+use scatter::ScatterPlugin;
+use scene::{ScenePlugin, SpawnQueue};
+use scene_diff::SceneDiffPlugin;
+use scene_templates::SceneTemplatesPlugin;
+use scopes::ScopesPlugin;
+use scripts::ScriptsPlugin;
+use selection::{Selection, SelectionPlugin};
+use selection_sets::SelectionSetsPlugin;
+use session_stats::{SessionEvent, SessionStatsPlugin};
+use settings::{Settings, SettingsPlugin, SettingsWindow};
+use simulation::SimulationPlugin;
+use slow_frames::SlowFramesPlugin;
+use snapshot_compare::SnapshotComparePlugin;
+use snapshots::SnapshotsPlugin;
+use sprite_sheet::SpriteSheetPlugin;
+use sprites::{SandboxMode, SpritesPlugin};
+use status_bar::StatusBarPlugin;
+use stereo::StereoPlugin;
+use style_compare::StyleComparePlugin;
+use tasks::TasksPlugin;
+use telemetry::TelemetryPlugin;
+use terrain::TerrainPlugin;
+use text3d::{Billboard, Text3dPlugin};
+use thumbnails::ThumbnailsPlugin;
+use tilemap::TilemapPlugin;
+use timeline::{AnimationTime, TimelinePlugin};
+use tour::{RegisterTourExt, TourAnchors, TourPlugin, TourStep};
+use transform_gizmo::{GizmoMode, GizmoSpace, TransformGizmoPlugin};
+use ui_layout::{SidePanelLayout, SideWidget, UiLayoutPlugin};
+use vertex_paint::VertexPaintPlugin;
+use viewport::{Viewport, ViewportTool};
+use virtual_keyboard::VirtualKeyboardPlugin;
+use watch_folder::WatchFolderPlugin;
+use weather::WeatherPlugin;
+use web_export::WebExportPlugin;
+use window_scale::WindowScalePlugin;
+use window_title::WindowTitlePlugin;
 
 struct Images {
     bevy_icon: Handle<Image>,
@@ -19,10 +239,12 @@ struct Images {
 
 impl FromWorld for Images {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.get_resource_mut::<AssetServer>().unwrap();
+        let bevy_icon = world.resource::<AssetServer>().load("icon.png");
+        // Generated at runtime rather than shipped as a second asset.
+        let bevy_icon_inverted = derive_image(world, bevy_icon.clone(), vec![ImageOp::Invert]);
         Self {
-            bevy_icon: asset_server.load("icon.png"),
-            bevy_icon_inverted: asset_server.load("icon_inverted.png"),
+            bevy_icon,
+            bevy_icon_inverted,
         }
     }
 }
@@ -30,6 +252,35 @@ impl FromWorld for Images {
 #[derive(Component)]
 struct RenderCube;
 
+/// The scene's main light, edited from the Lighting window.
+#[derive(Component)]
+struct SceneLight;
+
+/// The camera rendering the scene into `ViewImage`.
+#[derive(Component)]
+struct ViewportCamera;
+
+/// The authored orientation of an animated cube; the displayed rotation is derived from it
+/// and the current `AnimationTime`.
+#[derive(Component, Deref)]
+struct RestRotation(Quat);
+
+/// A cube held at its rest orientation instead of spinning with the animation.
+#[derive(Component)]
+struct Static;
+
+/// A viewport aid, such as the placement ghost, that the automatic clipping planes leave out.
+#[derive(Component)]
+struct Helper;
+
+/// Ordering of egui systems: side/top/bottom panels must be laid out before the central panel
+/// claims the remaining space.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+enum UiSet {
+    Panels,
+    Central,
+}
+
 #[derive(Deref, Resource)]
 struct ViewImage(Handle<Image>);
 
@@ -42,22 +293,187 @@ fn main() {
         .insert_resource(ClearColor(Color::BLACK))
         .insert_resource(Msaa::Sample4)
         .init_resource::<UiState>()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                prevent_default_event_handling: false,
-                ..default()
-            }),
-            ..default()
-        }))
+        .init_resource::<Viewport>()
+        .init_resource::<ViewportTool>()
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(overlay::primary_window()),
+                    ..default()
+                })
+                .set(LogPlugin {
+                    custom_layer: crash_report::log_layer,
+                    ..default()
+                }),
+        )
         .add_plugins(EguiPlugin)
+        // Registered first so the tour opens on the core layout before plugin contributions.
+        .add_tour_step(TourStep::new(
+            "side_panel",
+            "Side panel",
+            "Quick controls for the scene: spawning, viewport tools and the egui demo widgets.",
+        ))
+        .add_tour_step(TourStep::new(
+            "spawn_button",
+            "Add entities",
+            "Spawns a cube at a random position, using the spawn settings and colour palette.",
+        ))
+        .add_tour_step(TourStep::new(
+            "viewport",
+            "Viewport",
+            "The 3D scene rendered to a texture. Click to select, then drag the gizmo to move it.",
+        ))
+        .add_tour_step(TourStep::new(
+            "painting_canvas",
+            "Painting canvas",
+            "Draw with the mouse, then use \"Project onto selection\" to paint it onto cubes.",
+        ))
+        .add_plugins(TourPlugin)
+        .add_plugins(PanelsPlugin)
+        .add_plugins(IconsPlugin)
+        .add_plugins(InputRoutingPlugin)
+        .add_plugins(StatusBarPlugin)
+        .add_plugins(ErrorsPlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(FontsPlugin)
+        .add_plugins(SafeModePlugin)
+        .add_plugins(CrashReportPlugin)
+        .add_plugins(KeybindingsPlugin)
+        .add_plugins(ScenePlugin)
+        .add_plugins(SceneTemplatesPlugin)
+        .add_plugins(SceneDiffPlugin)
+        .add_plugins(NotesPlugin)
+        .add_plugins(TimelinePlugin)
+        .add_plugins(PalettePlugin)
+        .add_plugins(ReadbackPlugin)
+        .add_plugins(ScopesPlugin)
+        .add_plugins(ReportPlugin)
+        .add_plugins(PixelInspectorPlugin)
+        .add_plugins(SelectionPlugin)
+        .add_plugins(GroupsPlugin)
+        .add_plugins(PlacementPlugin)
+        .add_plugins(ComparePlugin)
+        .add_plugins(LightingPlugin)
+        .add_plugins(LightmapPlugin)
+        .add_plugins(CameraPlugin)
+        .add_plugins(BackgroundPlugin)
+        .add_plugins(ReflectionsPlugin)
+        .add_plugins(BatchingPlugin)
+        .add_plugins(BudgetPlugin)
+        .add_plugins(CullingPlugin)
+        .add_plugins(SlowFramesPlugin)
+        .add_plugins(VirtualKeyboardPlugin)
+        .add_plugins(CursorPlugin)
+        .add_plugins(DecalPlugin)
+        .add_plugins(TasksPlugin)
+        .add_plugins(TelemetryPlugin)
+        .add_plugins(FrameCapturePlugin)
+        .add_plugins(FramingPlugin)
+        .add_plugins(StereoPlugin)
+        .add_plugins(CubemapPlugin)
+        .add_plugins(SpriteSheetPlugin)
+        .add_plugins(Text3dPlugin)
+        .add_plugins(ThumbnailsPlugin)
+        .add_plugins(CsgPlugin)
+        .add_plugins(VertexPaintPlugin)
+        .add_plugins(ClothPlugin)
+        .add_plugins(BoidsPlugin)
+        .add_plugins(LSystemPlugin)
+        .add_plugins(TerrainPlugin)
+        .add_plugins(WeatherPlugin)
+        .add_plugins(DayNightPlugin)
+        .add_plugins(PostFxPlugin)
+        .add_plugins(OutlinePlugin)
+        .add_plugins(BindingsPlugin)
+        .add_plugins(TransformGizmoPlugin)
+        .add_plugins(SnapshotsPlugin)
+        .add_plugins(InitScriptPlugin)
+        .add_plugins(HierarchyPlugin)
+        .add_plugins(PickingPlugin)
+        .add_plugins(SimulationPlugin)
+        .add_plugins(PieMenuPlugin)
+        .add_plugins(PresenterPlugin)
+        .add_plugins(ProgressiveRenderPlugin)
+        .add_plugins(QuickSwitcherPlugin)
+        .add_plugins(SessionStatsPlugin)
+        .add_plugins(StyleComparePlugin)
+        .add_plugins(ImageOpsPlugin)
+        .add_plugins(ComputePlaygroundPlugin)
+        .add_plugins(ParticlesPlugin)
+        .add_plugins(PoolPlugin)
+        .add_plugins(ResourcesPlugin)
+        .add_plugins(AssetSnapshotsPlugin)
+        .add_plugins(UiLayoutPlugin)
+        .add_plugins(WebExportPlugin)
+        .add_plugins(HeatmapPlugin)
+        .add_plugins(FadePlugin)
+        .add_plugins(ScriptsPlugin)
+        .add_plugins(BookmarksPlugin)
+        .add_plugins(SpritesPlugin)
+        .add_plugins(TilemapPlugin)
+        .add_plugins(OverlayPlugin)
+        .add_plugins(WindowTitlePlugin)
+        .add_plugins(WindowScalePlugin)
+        .add_plugins(WatchFolderPlugin)
+        .add_plugins(HistoryPlugin)
+        .add_plugins(SelectionSetsPlugin)
+        .add_plugins(AlignPlugin)
+        .add_plugins(ArrayPlugin)
+        .add_plugins(RandomizePlugin)
+        .add_plugins(ScatterPlugin)
+        .add_plugins(ClipboardPlugin)
+        .add_plugins(ClippingPlugin)
+        .add_plugins(ExplodedViewPlugin)
+        .add_plugins(ReferenceObjectsPlugin)
+        .add_plugins(SnapshotComparePlugin)
+        .add_plugins(MacrosPlugin)
+        .add_plugins(MaterialBallPlugin)
+        .add_plugins(AnimatedTexturesPlugin)
+        .add_plugins(feature_plugins)
+        .add_event::<SpawnRandomCube>()
+        .add_menu_item(MenuItem::new(Menu::Edit, "Add Cube", |world| {
+            world.send_event(SpawnRandomCube);
+        }))
+        // Registered last so they close the File menu, after plugin contributions.
+        .add_menu_item(
+            MenuItem::new(Menu::File, "Settings…", |world| {
+                world.resource_mut::<SettingsWindow>().is_open = true;
+            })
+            .icon(Icon::Settings)
+            .shortcut(Action::OpenSettings)
+            .separator_before(),
+        )
+        .add_menu_item(MenuItem::new(Menu::File, "Quit", |world| {
+            world.send_event(AppExit::Success);
+        }))
+        .configure_sets(Update, (UiSet::Panels, UiSet::Central).chain())
         .add_systems(Startup, bevy_setup)
-        .add_systems(Startup, configure_visuals_system)
         .add_systems(Startup, configure_ui_state_system)
         .add_systems(Update, update_ui_scale_factor_system)
-        .add_systems(Update, ui_example_system)
-        .add_systems(Update, rotator_system)
+        .add_systems(Update, spawn_random_cube_system)
+        .add_systems(
+            Update,
+            (ui_example_system, menu_bar_system)
+                .chain()
+                .run_if(overlay_off)
+                .in_set(UiSet::Panels),
+        )
+        .add_systems(
+            Update,
+            central_panel_system
+                .in_set(UiSet::Central)
+                .after(menu_bar_system),
+        )
+        .add_systems(Update, (init_rest_rotation_system, rotator_system).chain())
         .run();
 }
+/// Plugins built only with their cargo feature.
+#[cfg_attr(not(feature = "tray"), allow(unused_variables))]
+fn feature_plugins(app: &mut App) {
+    #[cfg(feature = "tray")]
+    app.add_plugins(tray::TrayPlugin);
+}
+
 #[derive(Default, Resource)]
 struct UiState {
     label: String,
@@ -71,8 +487,6 @@ struct UiState {
 fn bevy_setup(
     mut egui_user_textures: ResMut<EguiUserTextures>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
     mut images: ResMut<Assets<Image>>,
 ) {
     let size = Extent3d {
@@ -91,6 +505,7 @@ fn bevy_setup(
             format: TextureFormat::Bgra8UnormSrgb,
             usage: TextureUsages::TEXTURE_BINDING
                 | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
                 | TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         },
@@ -104,78 +519,70 @@ fn bevy_setup(
     egui_user_textures.add_image(image_handle.clone());
     commands.insert_resource(ViewImage(image_handle.clone()));
 
-    let cube_handle = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
-    let default_material = StandardMaterial {
-        base_color: Color::srgb(0.8, 0.7, 0.6),
-        reflectance: 1.0,
-        unlit: false,
-        ..default()
-    };
-    let default_material_handle = materials.add(default_material.clone());
-
-    commands
-        .spawn(PbrBundle {
-            mesh: cube_handle,
-            material: default_material_handle,
-            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
-            ..default()
-        })
-        .insert(RenderCube);
-
-    // Light definition
+    // Light definition; the scene's entities come from the startup template, see
+    // `scene_templates`.
     commands
         .spawn(PointLightBundle {
             transform: Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
             ..default()
         })
-        .insert(RenderLayers::default());
+        .insert(RenderLayers::default())
+        .insert(SceneLight);
 
     // Camera definition
     commands
         .spawn(Camera3dBundle {
             camera: Camera {
                 target: RenderTarget::Image(image_handle),
-                clear_color: ClearColorConfig::Custom(Color::srgba(0.07, 0.07, 0.07, 0.0)),
                 ..default()
             },
             transform: Transform::from_translation(Vec3::new(0.0, 0.0, 30.0))
                 .looking_at(Vec3::default(), Vec3::Y),
             ..default()
         })
-        .insert(RenderLayers::default());
-}
-
-fn configure_visuals_system(mut contexts: EguiContexts) {
-    contexts.ctx_mut().set_visuals(egui::Visuals {
-        window_rounding: 0.0.into(),
-        ..Default::default()
-    });
+        .insert(RenderLayers::default())
+        .insert(ViewportCamera)
+        .insert(ViewportBackground::default());
 }
 
 fn configure_ui_state_system(mut ui_state: ResMut<UiState>) {
     ui_state.is_window_open = true;
 }
 
-fn update_ui_scale_factor_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut toggle_scale_factor: Local<Option<bool>>,
-    mut egui_settings: ResMut<EguiSettings>,
-    windows: Query<&Window, With<PrimaryWindow>>,
+fn update_ui_scale_factor_system(shortcuts: Shortcuts, mut settings: ResMut<Settings>) {
+    if shortcuts.just_pressed(Action::ToggleHidpiScaling) {
+        settings.graphics.hidpi_scaling = !settings.graphics.hidpi_scaling;
+    }
+}
+
+/// Adds a cube at a random position within the spawn range.
+#[derive(Event)]
+struct SpawnRandomCube;
+
+fn spawn_random_cube_system(
+    mut events: EventReader<SpawnRandomCube>,
+    mut spawns: ResMut<SpawnQueue>,
+    settings: Res<Settings>,
+    mut palette: ResMut<ColorPalette>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Slash) || toggle_scale_factor.is_none() {
-        *toggle_scale_factor = Some(!toggle_scale_factor.unwrap_or(true));
-
-        if let Ok(window) = windows.get_single() {
-            let scale_factor = if toggle_scale_factor.unwrap() {
-                1.0
-            } else {
-                1.0 / window.scale_factor()
-            };
-            egui_settings.scale_factor = scale_factor;
-        }
+    use rand::Rng;
+
+    for _ in events.read() {
+        let spawn = &settings.spawn;
+        let mut rng = rand::thread_rng();
+        let x = rng.gen_range(-spawn.range..spawn.range);
+        let y = rng.gen_range(-spawn.range..spawn.range);
+        let z = rng.gen_range(-spawn.range..spawn.range);
+        let [r, g, b] = spawn.color;
+        let color = palette.next_spawn_color().unwrap_or(Color::srgb(r, g, b));
+        spawns.push(
+            Transform::from_xyz(x, y, z).with_scale(Vec3::splat(spawn.cube_size)),
+            color,
+        );
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn ui_example_system(
     mut ui_state: ResMut<UiState>,
     // You are not required to store Egui texture ids in systems. We store this one here just to
@@ -187,15 +594,13 @@ fn ui_example_system(
     // resource while building the app and use `Res<Images>` instead.
     images: Local<Images>,
     mut contexts: EguiContexts,
-    cube_image: Res<ViewImage>,
-    cube_query: Query<&Handle<StandardMaterial>, With<RenderCube>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
     mut commands: Commands,
+    mut tool: ResMut<ViewportTool>,
+    mut placement: ResMut<Placement>,
+    mut anchors: ResMut<TourAnchors>,
+    layout: Res<SidePanelLayout>,
+    side_animation: Res<SidePanelAnimation>,
 ) {
-    use rand::Rng;
-    let cube_texture_id = contexts.image_id(&cube_image).unwrap();
-
     let egui_texture_handle = ui_state
         .egui_texture_handle
         .get_or_insert_with(|| {
@@ -215,91 +620,259 @@ fn ui_example_system(
         *is_initialized = true;
         *rendered_texture_id = contexts.add_image(images.bevy_icon.clone_weak());
     }
+    let icon = side_animation
+        .0
+        .as_ref()
+        .and_then(|(image, size)| {
+            let texture = contexts.image_id(image)?;
+            let size = egui::vec2(size.x as f32, size.y as f32);
+            Some((texture, size * (256.0 / size.max_elem().max(1.0))))
+        })
+        .unwrap_or((*rendered_texture_id, egui::vec2(256.0, 256.0)));
 
     let ctx = contexts.ctx_mut();
 
-    egui::SidePanel::left("side_panel")
+    let side_panel = egui::SidePanel::left("side_panel")
         .default_width(200.0)
         .show(ctx, |ui| {
-            ui.heading("Side Panel");
-
-            if ui.button("Add Entity").clicked() {
-                let mut rng = rand::thread_rng();
-                let x = rng.gen_range(-10.0..10.0);
-                let y = rng.gen_range(-10.0..10.0);
-                let z = rng.gen_range(-10.0..10.0);
-                let new_cube_handle = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
-                let new_cube_material = StandardMaterial {
-                    base_color: Color::srgb(0.8, 0.7, 0.6),
-                    reflectance: 1.0,
-                    unlit: false,
-                    ..default()
-                };
-                let new_cube_material_handle = materials.add(new_cube_material);
-                commands
-                    .spawn(PbrBundle {
-                        mesh: new_cube_handle,
-                        material: new_cube_material_handle,
-                        transform: Transform::from_translation(Vec3::new(x, y, z)),
-                        ..default()
-                    })
-                    .insert(RenderCube);
+            for widget in &layout.widgets {
+                match widget {
+                    SideWidget::Heading(text) => {
+                        ui.heading(text);
+                    }
+                    SideWidget::Label(text) => {
+                        ui.label(text);
+                    }
+                    SideWidget::Separator => {
+                        ui.separator();
+                    }
+                    SideWidget::Space(height) => {
+                        ui.allocate_space(egui::Vec2::new(1.0, *height));
+                    }
+                    SideWidget::AddEntity(label) => {
+                        let add_entity = ui.button(label);
+                        anchors.set("spawn_button", add_entity.rect);
+                        if add_entity.clicked() {
+                            commands.add(RunMenuItem("Add Cube"));
+                        }
+                    }
+                    SideWidget::ClickTool(label) => {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            ui.selectable_value(&mut *tool, ViewportTool::Select, "Select");
+                            ui.selectable_value(&mut *tool, ViewportTool::PlaceOnSurface, "Place");
+                        });
+                        if *tool == ViewportTool::PlaceOnSurface {
+                            ui.checkbox(&mut placement.align_to_normal, "Align to surface normal");
+                        }
+                    }
+                    SideWidget::TextInput(label) => {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            ui.text_edit_singleline(&mut ui_state.label);
+                        });
+                    }
+                    SideWidget::ExampleImage => {
+                        ui.add(egui::widgets::Image::new(egui::load::SizedTexture::new(
+                            egui_texture_handle.id(),
+                            egui_texture_handle.size_vec2(),
+                        )));
+                    }
+                    SideWidget::ValueSlider(label) => {
+                        ui.add(egui::Slider::new(&mut ui_state.value, 0.0..=10.0).text(label));
+                    }
+                    SideWidget::IncrementButton(label) => {
+                        if ui.button(label).clicked() {
+                            ui_state.value += 1.0;
+                        }
+                    }
+                    SideWidget::IconButtons => {
+                        ui.horizontal(|ui| {
+                            load |= ui.button("Load").clicked();
+                            invert |= ui.button("Invert").clicked();
+                            remove |= ui.button("Remove").clicked();
+                        });
+                    }
+                    SideWidget::IconImage => {
+                        ui.add(egui::widgets::Image::new(egui::load::SizedTexture::new(
+                            icon.0, icon.1,
+                        )));
+                    }
+                    SideWidget::WindowToggle(label) => {
+                        ui.checkbox(&mut ui_state.is_window_open, label);
+                    }
+                    SideWidget::Footer => {
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            ui.add(egui::Hyperlink::from_label_and_url(
+                                "powered by egui",
+                                "https://github.com/emilk/egui/",
+                            ));
+                        });
+                    }
+                }
             }
+        });
+    anchors.set("side_panel", side_panel.response.rect);
 
-            ui.horizontal(|ui| {
-                ui.label("Write something: ");
-                ui.text_edit_singleline(&mut ui_state.label);
-            });
-
-            ui.add(egui::widgets::Image::new(egui::load::SizedTexture::new(
-                egui_texture_handle.id(),
-                egui_texture_handle.size_vec2(),
-            )));
-
-            ui.add(egui::Slider::new(&mut ui_state.value, 0.0..=10.0).text("value"));
-            if ui.button("Increment").clicked() {
-                ui_state.value += 1.0;
-            }
+    if invert {
+        ui_state.inverted = !ui_state.inverted;
+    }
+    if load || invert {
+        // If an image is already added to the context, it'll return an existing texture id.
+        if ui_state.inverted {
+            *rendered_texture_id = contexts.add_image(images.bevy_icon_inverted.clone_weak());
+        } else {
+            *rendered_texture_id = contexts.add_image(images.bevy_icon.clone_weak());
+        };
+    }
+    if remove {
+        contexts.remove_image(&images.bevy_icon);
+        contexts.remove_image(&images.bevy_icon_inverted);
+    }
+}
 
-            ui.allocate_space(egui::Vec2::new(1.0, 100.0));
-            ui.horizontal(|ui| {
-                load = ui.button("Load").clicked();
-                invert = ui.button("Invert").clicked();
-                remove = ui.button("Remove").clicked();
-            });
-
-            ui.add(egui::widgets::Image::new(egui::load::SizedTexture::new(
-                *rendered_texture_id,
-                [256.0, 256.0],
-            )));
-
-            ui.allocate_space(egui::Vec2::new(1.0, 10.0));
-            ui.checkbox(&mut ui_state.is_window_open, "Window Is Open");
-
-            ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
-                ui.add(egui::Hyperlink::from_label_and_url(
-                    "powered by egui",
-                    "https://github.com/emilk/egui/",
-                ));
-            });
-        });
+#[allow(clippy::too_many_arguments)]
+fn menu_bar_system(
+    mut contexts: EguiContexts,
+    keybindings: Res<Keybindings>,
+    mut panels: ResMut<PanelRegistry>,
+    mut ui_states: ResMut<UiStateRegistry>,
+    windows: Query<(Entity, &Window, Has<PrimaryWindow>)>,
+    mut commands: Commands,
+    mut gizmo_space: ResMut<GizmoSpace>,
+    mut gizmo_mode: ResMut<GizmoMode>,
+    mut sandbox_mode: ResMut<SandboxMode>,
+    mut settings: ResMut<Settings>,
+) {
+    let ctx = contexts.ctx_mut();
+    let windows: Vec<(Option<Entity>, String)> = windows
+        .iter()
+        .map(|(entity, window, primary)| ((!primary).then_some(entity), window.title.clone()))
+        .collect();
 
     egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
         // The top panel is often a good place for a menu bar:
         egui::menu::bar(ui, |ui| {
-            egui::menu::menu_button(ui, "File", |ui| {
-                if ui.button("Quit").clicked() {
-                    std::process::exit(0);
+            for menu in Menu::ALL {
+                let has_panels = menu == Menu::View;
+                if !has_panels && panels.items(menu).next().is_none() {
+                    continue;
+                }
+                egui::menu::menu_button(ui, menu.label(), |ui| {
+                    if has_panels {
+                        for panel in panels.iter_mut() {
+                            panel.toggle_ui(ui);
+                        }
+                        ui.separator();
+                        // Edit a copy, so only a real edit counts as a settings change.
+                        let mut workspaces = settings.workspaces.clone();
+                        if panels::workspaces_menu(ui, &mut panels, &mut workspaces) {
+                            settings.workspaces = workspaces;
+                        }
+                        if windows.len() > 1 {
+                            ui.menu_button("Move Panel to Window", |ui| {
+                                for panel in panels.iter_mut() {
+                                    panel.move_ui(ui, &mut ui_states, &windows);
+                                }
+                            });
+                        }
+                        if panels.items(menu).next().is_some() {
+                            ui.separator();
+                        }
+                    }
+                    for item in panels.items(menu) {
+                        if item.menu_button(ui, &keybindings).clicked() {
+                            commands.add(RunMenuItem(item.label));
+                            ui.close_menu();
+                        }
+                    }
+                });
+            }
+        });
+        ui.horizontal(|ui| {
+            for item in panels.toolbar_items() {
+                if item.toolbar_button(ui, &keybindings).clicked() {
+                    commands.add(RunMenuItem(item.label));
                 }
-            });
+            }
+            ui.separator();
+            for (mode, label, tooltip) in SandboxMode::ALL {
+                ui.selectable_value(&mut *sandbox_mode, mode, label)
+                    .on_hover_text(tooltip);
+            }
+            ui.separator();
+            for (mode, icon, tooltip) in GizmoMode::ALL {
+                ui.selectable_value(&mut *gizmo_mode, mode, icon)
+                    .on_hover_text(tooltip);
+            }
+            for (space, label, tooltip) in GizmoSpace::ALL {
+                ui.selectable_value(&mut *gizmo_space, space, label)
+                    .on_hover_text(tooltip);
+            }
         });
     });
+}
 
-    egui::CentralPanel::default().show(ctx, |ui| {
-        ui.image(egui::load::SizedTexture::new(
+#[allow(clippy::too_many_arguments)]
+fn central_panel_system(
+    mut ui_state: ResMut<UiState>,
+    mut contexts: EguiContexts,
+    cube_image: Res<ViewImage>,
+    images: Res<Assets<Image>>,
+    mut viewport: ResMut<Viewport>,
+    backgrounds: Query<&ViewportBackground, With<ViewportCamera>>,
+    selection: Res<Selection>,
+    mut project: EventWriter<ProjectPainting>,
+    mut copy_canvas: EventWriter<CopyCanvas>,
+    canvas_background: Res<CanvasBackground>,
+    mut errors: EventWriter<AppError>,
+    mut anchors: ResMut<TourAnchors>,
+    mut session: EventWriter<SessionEvent>,
+    mut history: ResMut<History>,
+    settings: Res<Settings>,
+) {
+    let Some(cube_texture_id) = contexts.image_id(&cube_image) else {
+        errors.send(
+            AppError::new(
+                "Viewport",
+                "The viewport texture is not registered with egui",
+            )
+            .suggest("Restart the sandbox; the render target was dropped."),
+        );
+        return;
+    };
+    let image_size = images
+        .get(&**cube_image)
+        .map_or(UVec2::ZERO, |image| image.size());
+    let canvas_texture = canvas_background
+        .0
+        .as_ref()
+        .and_then(|image| contexts.image_id(image));
+    let ctx = contexts.ctx_mut();
+    let overlay = settings.graphics.overlay_mode;
+    // In overlay mode only the viewport is drawn, over nothing.
+    let frame = if overlay {
+        egui::Frame::none()
+    } else {
+        egui::Frame::central_panel(&ctx.style())
+    };
+
+    egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(500., 500.), egui::Sense::click_and_drag());
+        if let Ok(background) = backgrounds.get_single() {
+            background.paint(ui.painter(), rect);
+        }
+        egui::Image::new(egui::load::SizedTexture::new(
             cube_texture_id,
             egui::vec2(500., 500.),
-        ));
+        ))
+        .paint_at(ui, rect);
+        viewport.update(&response, image_size);
+        anchors.set("viewport", rect);
+        if overlay {
+            return;
+        }
 
         ui.heading("Egui Template");
         ui.hyperlink("https://github.com/emilk/egui_template");
@@ -316,32 +889,51 @@ fn ui_example_system(
         ui.label("It is often a great place for big things, like drawings:");
 
         ui.heading("Draw with your mouse to paint:");
-        ui_state.painting.ui_control(ui);
-        egui::Frame::dark_canvas(ui.style()).show(ui, |ui| {
-            ui_state.painting.ui_content(ui);
+        ui.horizontal(|ui| {
+            if let Some(lines) = ui_state.painting.ui_control(ui) {
+                history.push_canvas_clear(lines);
+            }
+            ui.separator();
+            let button = ui
+                .add_enabled(
+                    !selection.entities.is_empty(),
+                    egui::Button::new("Project onto selection"),
+                )
+                .on_hover_text(
+                    "Lay the canvas over the viewport and paint it onto the selected cubes",
+                )
+                .on_disabled_hover_text("Select cubes in the viewport first");
+            if button.clicked() {
+                project.send(ui_state.painting.projection());
+            }
+            if ui
+                .icon_button(Icon::Copy, "Copy")
+                .on_hover_text("Copy the canvas to the clipboard as an image")
+                .clicked()
+            {
+                copy_canvas.send(CopyCanvas {
+                    painting: ui_state.painting.projection(),
+                    background: ui.visuals().extreme_bg_color.to_srgba_unmultiplied(),
+                    pixels_per_point: ui.ctx().pixels_per_point(),
+                });
+            }
+        });
+        let canvas = egui::Frame::dark_canvas(ui.style()).show(ui, |ui| {
+            if let Some(line) = ui_state.painting.ui_content(ui, canvas_texture) {
+                history.push_canvas_stroke(line);
+                session.send(SessionEvent::StrokeDrawn);
+            }
         });
+        anchors.set("painting_canvas", canvas.response.rect);
     });
-
-    if invert {
-        ui_state.inverted = !ui_state.inverted;
-    }
-    if load || invert {
-        // If an image is already added to the context, it'll return an existing texture id.
-        if ui_state.inverted {
-            *rendered_texture_id = contexts.add_image(images.bevy_icon_inverted.clone_weak());
-        } else {
-            *rendered_texture_id = contexts.add_image(images.bevy_icon.clone_weak());
-        };
-    }
-    if remove {
-        contexts.remove_image(&images.bevy_icon);
-        contexts.remove_image(&images.bevy_icon_inverted);
-    }
 }
 
 struct Painting {
     lines: Vec<Vec<egui::Vec2>>,
     stroke: egui::Stroke,
+    /// Size of the canvas when last drawn; line points are relative to its corner.
+    size: egui::Vec2,
+    guides: CanvasGuides,
 }
 
 impl Default for Painting {
@@ -349,26 +941,80 @@ impl Default for Painting {
         Self {
             lines: Default::default(),
             stroke: egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+            size: egui::Vec2::ZERO,
+            guides: CanvasGuides::default(),
         }
     }
 }
 
 impl Painting {
-    pub fn ui_control(&mut self, ui: &mut egui::Ui) -> egui::Response {
+    /// Returns the lines drawn so far when the canvas is cleared.
+    pub fn ui_control(&mut self, ui: &mut egui::Ui) -> Option<Vec<Vec<egui::Vec2>>> {
         ui.horizontal(|ui| {
             ui.add(&mut self.stroke);
             ui.separator();
-            if ui.button("Clear Painting").clicked() {
-                self.lines.clear();
+            self.guides.ui_control(ui, self.size);
+            ui.separator();
+            let drawn = self.lines.iter().any(|line| !line.is_empty());
+            if ui.button("Clear Painting").clicked() && drawn {
+                let mut lines = std::mem::take(&mut self.lines);
+                lines.retain(|line| !line.is_empty());
+                return Some(lines);
             }
+            None
         })
-        .response
+        .inner
+    }
+
+    /// Adds a finished line, ahead of the one in progress.
+    pub fn push_line(&mut self, line: Vec<egui::Vec2>) {
+        match self.lines.last() {
+            Some(last) if last.is_empty() => self.lines.insert(self.lines.len() - 1, line),
+            _ => self.lines.push(line),
+        }
+    }
+
+    /// Removes the last finished line.
+    pub fn pop_line(&mut self) -> Option<Vec<egui::Vec2>> {
+        let index = self.lines.iter().rposition(|line| !line.is_empty())?;
+        Some(self.lines.remove(index))
     }
 
-    pub fn ui_content(&mut self, ui: &mut egui::Ui) {
+    /// Replaces every line, as [`Self::ui_control`] returned them when clearing.
+    pub fn set_lines(&mut self, lines: Vec<Vec<egui::Vec2>>) {
+        self.lines = lines;
+    }
+
+    pub fn projection(&self) -> ProjectPainting {
+        let [r, g, b, a] = self.stroke.color.to_srgba_unmultiplied();
+        ProjectPainting {
+            lines: self
+                .lines
+                .iter()
+                .map(|line| line.iter().map(|p| Vec2::new(p.x, p.y)).collect())
+                .collect(),
+            canvas_size: Vec2::new(self.size.x, self.size.y),
+            color: Color::srgba_u8(r, g, b, a),
+            width: self.stroke.width,
+        }
+    }
+
+    /// Draws the canvas, over `background` stretched to fill it, and records pointer strokes.
+    /// Returns the stroke on the frame it ends; a single click draws nothing and is dropped.
+    pub fn ui_content(
+        &mut self,
+        ui: &mut egui::Ui,
+        background: Option<egui::TextureId>,
+    ) -> Option<Vec<egui::Vec2>> {
         let (response, painter) =
             ui.allocate_painter(ui.available_size_before_wrap(), egui::Sense::drag());
+        let response = response.on_hover_cursor(egui::CursorIcon::Crosshair);
         let rect = response.rect;
+        self.size = rect.size();
+        if let Some(texture) = background {
+            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            painter.image(texture, rect, uv, egui::Color32::WHITE);
+        }
 
         if self.lines.is_empty() {
             self.lines.push(vec![]);
@@ -376,12 +1022,29 @@ impl Painting {
 
         let current_line = self.lines.last_mut().unwrap();
 
+        let mut finished = None;
         if let Some(pointer_pos) = response.interact_pointer_pos() {
             let canvas_pos = pointer_pos - rect.min;
-            if current_line.last() != Some(&canvas_pos) {
-                current_line.push(canvas_pos);
+            let straight = ui.input(|input| input.modifiers.shift);
+            match current_line.first() {
+                // Holding Shift turns the stroke into one segment snapped to 15° steps.
+                Some(&start) if straight => {
+                    let delta =
+                        snap_direction(Vec2::new(canvas_pos.x - start.x, canvas_pos.y - start.y));
+                    current_line.truncate(1);
+                    current_line.push(start + egui::vec2(delta.x, delta.y));
+                }
+                _ => {
+                    let canvas_pos = self.guides.snap(canvas_pos);
+                    if current_line.last() != Some(&canvas_pos) {
+                        current_line.push(canvas_pos);
+                    }
+                }
             }
+        } else if current_line.len() == 1 {
+            current_line.clear();
         } else if !current_line.is_empty() {
+            finished = Some(current_line.clone());
             self.lines.push(vec![]);
         }
 
@@ -391,12 +1054,43 @@ impl Painting {
                 painter.add(egui::Shape::line(points, self.stroke));
             }
         }
+        let drawing = self.lines.last().and_then(|line| line.last()).copied();
+        self.guides.ui_content(ui, rect, &painter, drawing);
+        finished
     }
 }
 
-fn rotator_system(time: Res<Time>, mut query: Query<&mut Transform, With<RenderCube>>) {
-    for mut transform in &mut query {
-        transform.rotate_x(1.5 * time.delta_seconds());
-        transform.rotate_z(1.3 * time.delta_seconds());
+#[allow(clippy::type_complexity)]
+fn init_rest_rotation_system(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), (With<RenderCube>, Without<RestRotation>)>,
+) {
+    for (entity, transform) in &query {
+        commands
+            .entity(entity)
+            .insert(RestRotation(transform.rotation));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn rotator_system(
+    animation_time: Res<AnimationTime>,
+    settings: Res<Settings>,
+    mut query: Query<
+        (&mut Transform, &RestRotation, Has<Static>),
+        (With<RenderCube>, Without<Billboard>),
+    >,
+) {
+    let t = animation_time.seconds;
+    let spin = Quat::from_rotation_z(1.3 * t) * Quat::from_rotation_x(1.5 * t);
+    for (mut transform, rest_rotation, is_static) in &mut query {
+        let rotation = if is_static || settings.accessibility.reduced_motion {
+            **rest_rotation
+        } else {
+            spin * **rest_rotation
+        };
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
     }
 }
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use crate::{
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    scene::{SceneWriter, Shape},
+    scene_templates::entity,
+    selection::Selection,
+    ViewportCamera,
+};
+
+/// How far in front of the camera a new shader ball is placed.
+const PLACE_DISTANCE: f32 = 6.0;
+
+/// Edit › Add Shader Ball: the standard material preview object, placed in front of the camera
+/// and selected so its material is ready to edit in the Inspector. Turning the environment in
+/// the Background window, with it lighting the scene, then moves the reflections across the
+/// ball instead of the ball under the light.
+pub struct MaterialBallPlugin;
+
+impl Plugin for MaterialBallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AddShaderBall>()
+            .add_systems(Update, add_shader_ball_system)
+            .add_menu_item(MenuItem::new(Menu::Edit, "Add Shader Ball", |world| {
+                world.send_event(AddShaderBall);
+            }));
+    }
+}
+
+#[derive(Event)]
+struct AddShaderBall;
+
+fn add_shader_ball_system(
+    mut events: EventReader<AddShaderBall>,
+    mut scene: SceneWriter,
+    mut selection: ResMut<Selection>,
+    cameras: Query<&Transform, With<ViewportCamera>>,
+) {
+    for _ in events.read() {
+        let position = cameras.get_single().map_or(Vec3::ZERO, |camera| {
+            camera.translation + camera.forward() * PLACE_DISTANCE
+        });
+        let ball = entity(
+            Shape::ShaderBall,
+            Transform::from_translation(position).with_scale(Vec3::splat(2.0)),
+            [0.8, 0.8, 0.8],
+        );
+        selection.select(scene.spawn(&ball, None));
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+use crate::scene::Project;
+
+/// The Notes window: a markdown editor whose contents are saved with the scene.
+pub struct NotesPlugin;
+
+impl Plugin for NotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<NotesWindow>()
+            .add_systems(Update, notes_window_system.in_set(NotesWindow::ui_set()));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum NotesView {
+    Edit,
+    Preview,
+    #[default]
+    Split,
+}
+
+#[derive(Default, Resource)]
+pub struct NotesWindow {
+    pub is_open: bool,
+    view: NotesView,
+}
+
+impl Panel for NotesWindow {
+    const TITLE: &'static str = "Notes";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn notes_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<NotesWindow>,
+    mut project: ResMut<Project>,
+) {
+    let NotesWindow { is_open, view } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Notes")
+        .open(is_open)
+        .default_size([520.0, 360.0])
+        .show(contexts.ctx::<NotesWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(view, NotesView::Edit, "Edit");
+                ui.selectable_value(view, NotesView::Preview, "Preview");
+                ui.selectable_value(view, NotesView::Split, "Split");
+            });
+            ui.separator();
+
+            let notes = &mut project.notes;
+            match view {
+                NotesView::Edit => notes_editor(ui, notes),
+                NotesView::Preview => notes_preview(ui, notes),
+                NotesView::Split => {
+                    ui.columns(2, |columns| {
+                        notes_editor(&mut columns[0], notes);
+                        notes_preview(&mut columns[1], notes);
+                    });
+                }
+            }
+        });
+}
+
+fn notes_editor(ui: &mut egui::Ui, notes: &mut String) {
+    egui::ScrollArea::vertical()
+        .id_source("notes_editor")
+        .show(ui, |ui| {
+            ui.add(
+                egui::TextEdit::multiline(notes)
+                    .desired_width(f32::INFINITY)
+                    .desired_rows(16)
+                    .hint_text("# Design notes\n- [ ] TODO"),
+            );
+        });
+}
+
+fn notes_preview(ui: &mut egui::Ui, notes: &mut String) {
+    egui::ScrollArea::vertical()
+        .id_source("notes_preview")
+        .show(ui, |ui| {
+            if let Some(line) = markdown(ui, notes) {
+                toggle_task(notes, line);
+            }
+        });
+}
+
+/// Renders the small markdown subset used for notes: headings, bullets, task lists, quotes,
+/// rules, fenced code and inline `code`/**bold**. Returns the line of a task box that was clicked.
+fn markdown(ui: &mut egui::Ui, source: &str) -> Option<usize> {
+    let mut toggled = None;
+    let mut in_code_block = false;
+    for (index, line) in source.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            ui.label(egui::RichText::new(line).code());
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if let Some(text) = trimmed.strip_prefix("### ") {
+            ui.label(egui::RichText::new(text).strong());
+        } else if let Some(text) = trimmed.strip_prefix("## ") {
+            ui.label(egui::RichText::new(text).heading().size(16.0));
+        } else if let Some(text) = trimmed.strip_prefix("# ") {
+            ui.heading(text);
+        } else if let Some((mut done, text)) = task(trimmed) {
+            ui.horizontal_wrapped(|ui| {
+                if ui.checkbox(&mut done, "").changed() {
+                    toggled = Some(index);
+                }
+                inline(ui, text);
+            });
+        } else if let Some(text) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("•");
+                inline(ui, text);
+            });
+        } else if let Some(text) = trimmed.strip_prefix("> ") {
+            ui.label(egui::RichText::new(text).italics().weak());
+        } else if trimmed == "---" {
+            ui.separator();
+        } else if trimmed.is_empty() {
+            ui.add_space(4.0);
+        } else {
+            ui.horizontal_wrapped(|ui| inline(ui, trimmed));
+        }
+    }
+    toggled
+}
+
+fn task(line: &str) -> Option<(bool, &str)> {
+    let rest = line.strip_prefix("- [")?;
+    let (mark, text) = rest.split_at_checked(2)?;
+    match mark {
+        " ]" => Some((false, text.trim_start())),
+        "x]" | "X]" => Some((true, text.trim_start())),
+        _ => None,
+    }
+}
+
+fn toggle_task(source: &mut String, line_index: usize) {
+    let toggled: Vec<String> = source
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            if index != line_index {
+                line.to_owned()
+            } else if line.contains("- [ ]") {
+                line.replacen("- [ ]", "- [x]", 1)
+            } else {
+                line.replacen("- [x]", "- [ ]", 1)
+                    .replacen("- [X]", "- [ ]", 1)
+            }
+        })
+        .collect();
+    *source = toggled.join("\n");
+}
+
+fn inline(ui: &mut egui::Ui, text: &str) {
+    ui.spacing_mut().item_spacing.x = 0.0;
+    for (i, code_split) in text.split('`').enumerate() {
+        if i % 2 == 1 {
+            ui.label(egui::RichText::new(code_split).code());
+            continue;
+        }
+        for (j, bold_split) in code_split.split("**").enumerate() {
+            if bold_split.is_empty() {
+                continue;
+            }
+            if j % 2 == 1 {
+                ui.label(egui::RichText::new(bold_split).strong());
+            } else {
+                ui.label(bold_split);
+            }
+        }
+    }
+}
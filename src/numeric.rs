@@ -0,0 +1,257 @@
+//! Numeric widgets whose typed values may be constant expressions such as `2*pi/3` or
+//! `1.5+0.25`, evaluated when the edit is committed. Use these instead of
+//! `egui::DragValue::new` and `egui::Slider::new` so every panel accepts the same input.
+//!
+//! Numbers are shown with the decimal separator chosen in the Settings, and fields given a
+//! [`Unit`] show its suffix. Typing accepts either separator and any of the unit's suffixes.
+
+use std::{
+    ops::RangeInclusive,
+    sync::{PoisonError, RwLock},
+};
+
+use bevy_egui::egui::{self, emath::Numeric};
+
+use crate::expr::Expr;
+
+/// How numbers are written, set from the Settings when they change.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NumberFormat {
+    pub decimal_comma: bool,
+    pub show_units: bool,
+}
+
+static FORMAT: RwLock<NumberFormat> = RwLock::new(NumberFormat {
+    decimal_comma: false,
+    show_units: true,
+});
+
+pub fn set_format(format: NumberFormat) {
+    *FORMAT.write().unwrap_or_else(PoisonError::into_inner) = format;
+}
+
+fn format() -> NumberFormat {
+    *FORMAT.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Whether the system locale writes decimals with a comma, judged from the POSIX locale
+/// variables; where they are unset, as on Windows, a dot is assumed.
+pub fn system_decimal_comma() -> bool {
+    const COMMA_LANGUAGES: &[&str] = &[
+        "bg", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl", "hr", "hu", "id",
+        "is", "it", "lt", "lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr",
+        "sv", "tr", "uk", "vi",
+    ];
+    /// Countries speaking those languages that write a dot anyway.
+    const DOT_EXCEPTIONS: &[&str] = &["de_CH", "es_MX", "es_US", "it_CH"];
+    let Some(locale) = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+    else {
+        return false;
+    };
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let language = locale.split('_').next().unwrap_or_default();
+    COMMA_LANGUAGES.contains(&language) && !DOT_EXCEPTIONS.contains(&locale)
+}
+
+/// What a field measures. Values are stored in the first unit of each; a percentage is kept
+/// as a fraction and shown multiplied by 100.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Unit {
+    Meters,
+    Degrees,
+    Percent,
+}
+
+impl Unit {
+    fn suffix(self) -> &'static str {
+        match self {
+            Unit::Meters => " m",
+            Unit::Degrees => "°",
+            Unit::Percent => "%",
+        }
+    }
+
+    /// From the stored value to the shown one.
+    fn display_scale(self) -> f64 {
+        match self {
+            Unit::Percent => 100.0,
+            Unit::Meters | Unit::Degrees => 1.0,
+        }
+    }
+
+    /// Suffixes a typed number may end with, and what they multiply it by into the shown
+    /// unit. Longer suffixes come first, so "cm" is not read as "m".
+    fn accepted(self) -> &'static [(&'static str, f64)] {
+        match self {
+            Unit::Meters => &[("mm", 0.001), ("cm", 0.01), ("km", 1000.0), ("m", 1.0)],
+            Unit::Degrees => &[
+                ("rad", 180.0 / std::f64::consts::PI),
+                ("deg", 1.0),
+                ("°", 1.0),
+            ],
+            Unit::Percent => &[("%", 1.0)],
+        }
+    }
+}
+
+/// `text` with its decimal points written in the chosen separator.
+fn localize(text: String) -> String {
+    if format().decimal_comma {
+        text.replace('.', ",")
+    } else {
+        text
+    }
+}
+
+/// Writes `value` as a field of `unit` shows it, with `decimals` places.
+pub fn format_quantity(value: f64, decimals: usize, unit: Option<Unit>) -> String {
+    let scale = unit.map_or(1.0, Unit::display_scale);
+    let text = localize(format!("{:.*}", decimals, value * scale));
+    match unit.filter(|_| format().show_units) {
+        Some(unit) => text + unit.suffix(),
+        None => text,
+    }
+}
+
+/// Reads commas between digits as decimal points, unless the text calls a function, whose
+/// arguments commas separate.
+fn normalize_decimals(text: &str) -> String {
+    if text.contains('(') {
+        return text.to_owned();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(index, &c)| {
+            let between_digits = index > 0
+                && chars[index - 1].is_ascii_digit()
+                && chars.get(index + 1).is_some_and(char::is_ascii_digit);
+            if c == ',' && between_digits {
+                '.'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Evaluates a number typed into a field; `None` leaves the field's value unchanged.
+pub fn parse_number(text: &str) -> Option<f64> {
+    let constant = |name: &str| match name {
+        "pi" => Some(std::f64::consts::PI),
+        "tau" => Some(std::f64::consts::TAU),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    };
+    Expr::parse(&normalize_decimals(text))
+        .and_then(|expr| expr.eval(&constant))
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// Evaluates a number typed into a field of `unit`, which may end with any of its suffixes.
+pub fn parse_quantity(text: &str, unit: Unit) -> Option<f64> {
+    let text = text.trim();
+    let (number, factor) = unit
+        .accepted()
+        .iter()
+        .find_map(|(suffix, factor)| Some((text.strip_suffix(suffix)?, *factor)))
+        .unwrap_or((text, 1.0));
+    Some(parse_number(number)? * factor / unit.display_scale())
+}
+
+fn format_decimals(value: f64, decimals: RangeInclusive<usize>) -> String {
+    localize(egui::emath::format_with_decimals_in_range(value, decimals))
+}
+
+fn format_unit(value: f64, decimals: RangeInclusive<usize>, unit: Unit) -> String {
+    // Scaling up by a power of ten needs that many fewer decimals for the same precision.
+    let shift = unit.display_scale().log10() as usize;
+    let decimals = decimals.start().saturating_sub(shift)..=decimals.end().saturating_sub(shift);
+    let text = format_decimals(value * unit.display_scale(), decimals);
+    if format().show_units {
+        text + unit.suffix()
+    } else {
+        text
+    }
+}
+
+pub fn drag_value<Num: Numeric>(value: &mut Num) -> egui::DragValue<'_> {
+    egui::DragValue::new(value)
+        .custom_formatter(format_decimals)
+        .custom_parser(parse_number)
+}
+
+/// A [`drag_value`] for a quantity measured in `unit`.
+pub fn unit_drag_value<Num: Numeric>(value: &mut Num, unit: Unit) -> egui::DragValue<'_> {
+    egui::DragValue::new(value)
+        .custom_formatter(move |value, decimals| format_unit(value, decimals, unit))
+        .custom_parser(move |text| parse_quantity(text, unit))
+}
+
+pub fn slider<Num: Numeric>(value: &mut Num, range: RangeInclusive<Num>) -> egui::Slider<'_> {
+    egui::Slider::new(value, range)
+        .custom_formatter(format_decimals)
+        .custom_parser(parse_number)
+}
+
+/// A [`slider`] for a quantity measured in `unit`.
+pub fn unit_slider<Num: Numeric>(
+    value: &mut Num,
+    range: RangeInclusive<Num>,
+    unit: Unit,
+) -> egui::Slider<'_> {
+    egui::Slider::new(value, range)
+        .custom_formatter(move |value, decimals| format_unit(value, decimals, unit))
+        .custom_parser(move |text| parse_quantity(text, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(value: Option<f64>, expected: f64) -> bool {
+        value.is_some_and(|value| (value - expected).abs() < 1e-9)
+    }
+
+    #[test]
+    fn numbers_may_be_expressions() {
+        assert!(close(parse_number("1.5+0.25"), 1.75));
+        assert!(close(parse_number("2*pi/3"), std::f64::consts::TAU / 3.0));
+        assert!(close(parse_number(" -2 ^ 2 "), -4.0));
+        assert!(close(parse_number("(1 + 2) * 3"), 9.0));
+        assert!(close(parse_number("max(1, 5)"), 5.0));
+    }
+
+    #[test]
+    fn commas_between_digits_are_decimal_separators() {
+        assert!(close(parse_number("1,5"), 1.5));
+        assert!(close(parse_number("1,5*2"), 3.0));
+        // Inside a call they separate arguments instead.
+        assert!(close(parse_number("min(1,5)"), 1.0));
+    }
+
+    #[test]
+    fn invalid_numbers_leave_the_value_alone() {
+        for text in ["", "abc", "1 +", "(1", "1/0", "sqrt(-1)", "x * 2"] {
+            assert_eq!(parse_number(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn quantities_accept_unit_suffixes() {
+        assert!(close(parse_quantity("25 cm", Unit::Meters), 0.25));
+        assert!(close(parse_quantity("1.5km", Unit::Meters), 1500.0));
+        assert!(close(parse_quantity("2 mm", Unit::Meters), 0.002));
+        assert!(close(parse_quantity("3", Unit::Meters), 3.0));
+        assert!(close(parse_quantity("50%", Unit::Percent), 0.5));
+        assert!(close(parse_quantity("50", Unit::Percent), 0.5));
+        assert!(close(parse_quantity("90°", Unit::Degrees), 90.0));
+        assert!(close(parse_quantity("pi rad", Unit::Degrees), 180.0));
+        assert_eq!(parse_quantity("1 kg", Unit::Meters), None);
+    }
+}
@@ -0,0 +1,400 @@
+use std::num::NonZeroU64;
+
+use bevy::{
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+        prepass::{DepthPrepass, NormalPrepass, ViewPrepassTextures},
+    },
+    ecs::query::QueryItem,
+    pbr::{ScreenSpaceAmbientOcclusionSettings, ScreenSpaceReflectionsSettings},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{
+                sampler, texture_2d, texture_2d_multisampled, texture_depth_2d,
+                texture_depth_2d_multisampled, uniform_buffer_sized,
+            },
+            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BufferInitDescriptor,
+            BufferUsages, CachedRenderPipelineId, ColorTargetState, ColorWrites, FragmentState,
+            MultisampleState, Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
+            RenderPassDescriptor, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+            SamplerDescriptor, ShaderStages, SpecializedRenderPipeline, SpecializedRenderPipelines,
+            TextureFormat, TextureSampleType,
+        },
+        renderer::{RenderContext, RenderDevice},
+        view::ViewTarget,
+        Render, RenderApp, RenderSet,
+    },
+};
+
+const OUTLINE_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x2d7a_90c4_5e18_4f63_b0a9_83e1_6c5f_d247);
+
+/// Bytes of the `EdgeOutline` uniform: the colour, then the three scalars padded to 16.
+const OUTLINE_UNIFORM_SIZE: u64 = 32;
+
+const OUTLINE_WGSL: &str = r#"#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct EdgeOutline {
+    color: vec4<f32>,
+    thickness: f32,
+    depth_threshold: f32,
+    normal_threshold: f32,
+}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+#ifdef MULTISAMPLED
+@group(0) @binding(2) var depth_texture: texture_depth_multisampled_2d;
+@group(0) @binding(3) var normal_texture: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(2) var depth_texture: texture_depth_2d;
+@group(0) @binding(3) var normal_texture: texture_2d<f32>;
+#endif
+@group(0) @binding(4) var<uniform> outline: EdgeOutline;
+
+fn clamped(pixel: vec2<i32>) -> vec2<i32> {
+    return clamp(pixel, vec2<i32>(0), vec2<i32>(textureDimensions(depth_texture)) - 1);
+}
+
+// The last argument is the sample with multisampling and the mip level without.
+fn depth_at(pixel: vec2<i32>) -> f32 {
+    return textureLoad(depth_texture, clamped(pixel), 0);
+}
+
+fn normal_at(pixel: vec2<i32>) -> vec3<f32> {
+    return textureLoad(normal_texture, clamped(pixel), 0).xyz * 2.0 - 1.0;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, screen_sampler, in.uv);
+    let pixel = vec2<i32>(in.position.xy);
+    let depth = depth_at(pixel);
+    let normal = normal_at(pixel);
+    let offset = i32(round(outline.thickness));
+
+    var edge = 0.0;
+    var neighbours = array<vec2<i32>, 4>(
+        vec2<i32>(offset, 0),
+        vec2<i32>(-offset, 0),
+        vec2<i32>(0, offset),
+        vec2<i32>(0, -offset),
+    );
+    for (var i = 0; i < 4; i += 1) {
+        let neighbour = pixel + neighbours[i];
+        // Depth is reversed and inversely proportional to distance, so this is the relative
+        // difference in distance; the sky, at zero, always differs.
+        let other = depth_at(neighbour);
+        let depth_change = abs(max(depth, other) / max(min(depth, other), 1e-7) - 1.0);
+        if depth_change > outline.depth_threshold {
+            edge = 1.0;
+        }
+        // Only between surfaces: the sky has no normal.
+        if depth > 0.0 && other > 0.0 {
+            let crease = 1.0 - dot(normal, normal_at(neighbour));
+            if crease > outline.normal_threshold {
+                edge = 1.0;
+            }
+        }
+    }
+    return vec4<f32>(mix(color.rgb, outline.color.rgb, edge * outline.color.a), color.a);
+}
+"#;
+
+/// Cartoon-style outlines for the viewport camera, drawn where the depth or the normal of
+/// neighbouring pixels jumps. Runs after tonemapping, so the lines keep their colour, and
+/// needs the depth and normal prepasses, which it adds while a camera has [`EdgeOutline`].
+pub struct OutlinePlugin;
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.world_mut().resource_mut::<Assets<Shader>>().insert(
+            OUTLINE_SHADER.id(),
+            Shader::from_wgsl(OUTLINE_WGSL, "outline.wgsl"),
+        );
+        app.add_plugins(ExtractComponentPlugin::<EdgeOutline>::default())
+            .add_systems(PostUpdate, outline_prepasses_system);
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<SpecializedRenderPipelines<OutlinePipeline>>()
+            .add_systems(
+                Render,
+                prepare_outline_pipelines_system.in_set(RenderSet::Prepare),
+            )
+            .add_render_graph_node::<ViewNodeRunner<OutlineNode>>(Core3d, OutlineLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    OutlineLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.init_resource::<OutlinePipeline>();
+        }
+    }
+}
+
+/// Outlines what a camera sees.
+#[derive(Component, Clone, Copy, PartialEq, Debug, ExtractComponent)]
+pub struct EdgeOutline {
+    /// Linear colour; alpha blends the lines over the image.
+    pub color: Vec4,
+    /// Pixels between the compared neighbours, roughly the width of the lines.
+    pub thickness: f32,
+    /// Relative change in distance that makes an edge.
+    pub depth_threshold: f32,
+    /// One minus the cosine between normals that makes a crease, `0..2`.
+    pub normal_threshold: f32,
+}
+
+impl Default for EdgeOutline {
+    fn default() -> Self {
+        Self {
+            color: Vec4::new(0.0, 0.0, 0.0, 1.0),
+            thickness: 1.0,
+            depth_threshold: 0.1,
+            normal_threshold: 0.4,
+        }
+    }
+}
+
+impl EdgeOutline {
+    fn uniform(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .color
+            .to_array()
+            .into_iter()
+            .chain([self.thickness, self.depth_threshold, self.normal_threshold])
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        bytes.resize(OUTLINE_UNIFORM_SIZE as usize, 0);
+        bytes
+    }
+}
+
+/// Adds the prepasses outlined cameras need, and takes them away again once no other effect
+/// uses them.
+#[allow(clippy::type_complexity)]
+fn outline_prepasses_system(
+    mut commands: Commands,
+    cameras: Query<(
+        Entity,
+        Has<EdgeOutline>,
+        Has<DepthPrepass>,
+        Has<NormalPrepass>,
+        Has<ScreenSpaceAmbientOcclusionSettings>,
+        Has<ScreenSpaceReflectionsSettings>,
+    )>,
+    mut removed: RemovedComponents<EdgeOutline>,
+) {
+    for (camera, outlined, depth, normal, _, _) in &cameras {
+        if outlined && !depth {
+            commands.entity(camera).insert(DepthPrepass);
+        }
+        if outlined && !normal {
+            commands.entity(camera).insert(NormalPrepass);
+        }
+    }
+    for camera in removed.read() {
+        let Ok((_, outlined, _, _, ssao, ssr)) = cameras.get(camera) else {
+            continue;
+        };
+        if outlined {
+            continue;
+        }
+        if !ssao {
+            commands.entity(camera).remove::<NormalPrepass>();
+        }
+        if !ssao && !ssr {
+            commands.entity(camera).remove::<DepthPrepass>();
+        }
+    }
+}
+
+#[derive(Resource)]
+struct OutlinePipeline {
+    /// Without and with multisampled prepass textures.
+    layouts: [BindGroupLayout; 2],
+    sampler: Sampler,
+}
+
+impl FromWorld for OutlinePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let screen = texture_2d(TextureSampleType::Float { filterable: true });
+        let filtering = sampler(SamplerBindingType::Filtering);
+        let normals = TextureSampleType::Float { filterable: false };
+        let single = render_device.create_bind_group_layout(
+            "outline_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    screen,
+                    filtering,
+                    texture_depth_2d(),
+                    texture_2d(normals),
+                    uniform_buffer_sized(false, NonZeroU64::new(OUTLINE_UNIFORM_SIZE)),
+                ),
+            ),
+        );
+        let multisampled = render_device.create_bind_group_layout(
+            "outline_multisampled_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    screen,
+                    filtering,
+                    texture_depth_2d_multisampled(),
+                    texture_2d_multisampled(normals),
+                    uniform_buffer_sized(false, NonZeroU64::new(OUTLINE_UNIFORM_SIZE)),
+                ),
+            ),
+        );
+        Self {
+            layouts: [single, multisampled],
+            sampler: render_device.create_sampler(&SamplerDescriptor::default()),
+        }
+    }
+}
+
+/// The view's main texture format, and whether its prepass textures are multisampled.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct OutlinePipelineKey {
+    format: TextureFormat,
+    multisampled: bool,
+}
+
+impl SpecializedRenderPipeline for OutlinePipeline {
+    type Key = OutlinePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("outline_pipeline".into()),
+            layout: vec![self.layouts[usize::from(key.multisampled)].clone()],
+            push_constant_ranges: Vec::new(),
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: OUTLINE_SHADER,
+                shader_defs: if key.multisampled {
+                    vec!["MULTISAMPLED".into()]
+                } else {
+                    Vec::new()
+                },
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: key.format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct OutlinePipelineId(CachedRenderPipelineId);
+
+fn prepare_outline_pipelines_system(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<OutlinePipeline>>,
+    pipeline: Res<OutlinePipeline>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ViewTarget), With<EdgeOutline>>,
+) {
+    for (entity, target) in &views {
+        let key = OutlinePipelineKey {
+            format: target.main_texture_format(),
+            multisampled: msaa.samples() > 1,
+        };
+        let id = pipelines.specialize(&pipeline_cache, &pipeline, key);
+        commands.entity(entity).insert(OutlinePipelineId(id));
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, Hash, PartialEq, Eq)]
+struct OutlineLabel;
+
+#[derive(Default)]
+struct OutlineNode;
+
+impl ViewNode for OutlineNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewPrepassTextures,
+        &'static OutlinePipelineId,
+        &'static EdgeOutline,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, prepass, pipeline_id, outline): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let outline_pipeline = world.resource::<OutlinePipeline>();
+        let (Some(pipeline), Some(depth), Some(normal)) = (
+            world
+                .resource::<PipelineCache>()
+                .get_render_pipeline(pipeline_id.0),
+            prepass.depth_view(),
+            prepass.normal_view(),
+        ) else {
+            return Ok(());
+        };
+        let multisampled = world.resource::<Msaa>().samples() > 1;
+
+        let uniform =
+            render_context
+                .render_device()
+                .create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("outline_uniform"),
+                    contents: &outline.uniform(),
+                    usage: BufferUsages::UNIFORM,
+                });
+        let post_process = target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "outline_bind_group",
+            &outline_pipeline.layouts[usize::from(multisampled)],
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &outline_pipeline.sampler,
+                depth,
+                normal,
+                uniform.as_entire_binding(),
+            )),
+        );
+        let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("outline_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_render_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowLevel},
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    background::ViewportBackground,
+    icons::{Icon, IconButtonsExt},
+    panels::PanelRegistry,
+    settings::Settings,
+    sprites::SandboxMode,
+    transform_gizmo::{GizmoMode, GizmoSpace},
+    ViewportCamera,
+};
+
+/// Overlay mode, toggled from the Graphics settings: the window loses its frame and floats above
+/// other applications, and everything but the viewport and a small toolbar is hidden. When the
+/// window was created transparent the background shows through wherever the scene is empty,
+/// so the sandbox can float over other work as a 3D or annotation overlay.
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OverlayState>().add_systems(
+            Update,
+            (
+                apply_overlay_system,
+                overlay_toolbar_system
+                    .run_if(overlay_on)
+                    .in_set(crate::UiSet::Panels),
+            ),
+        );
+    }
+}
+
+/// The primary window, created transparent when overlay mode was saved on. Transparency can
+/// only be chosen when the OS window is created, so turning overlay mode on later keeps an
+/// opaque background until the next launch.
+pub fn primary_window() -> Window {
+    let overlay = Settings::load().is_ok_and(|settings| settings.graphics.overlay_mode);
+    Window {
+        prevent_default_event_handling: false,
+        transparent: overlay,
+        decorations: !overlay,
+        window_level: if overlay {
+            WindowLevel::AlwaysOnTop
+        } else {
+            WindowLevel::Normal
+        },
+        ..default()
+    }
+}
+
+/// Run condition for the regular chrome, which overlay mode hides.
+pub fn overlay_off(settings: Res<Settings>) -> bool {
+    !settings.graphics.overlay_mode
+}
+
+fn overlay_on(settings: Res<Settings>) -> bool {
+    settings.graphics.overlay_mode
+}
+
+#[derive(Default, Resource)]
+struct OverlayState {
+    /// Whether overlay mode has been applied to the window.
+    active: bool,
+    /// Panels that were open when the overlay started, reopened when it ends.
+    hidden_panels: Vec<String>,
+}
+
+fn window_clear_color(settings: &Settings) -> Color {
+    let [r, g, b, a] = settings.graphics.clear_color;
+    Color::srgba(r, g, b, a)
+}
+
+fn apply_overlay_system(
+    settings: Res<Settings>,
+    mut state: ResMut<OverlayState>,
+    mut panels: ResMut<PanelRegistry>,
+    mut clear_color: ResMut<ClearColor>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&mut Camera, &mut ViewportBackground), With<ViewportCamera>>,
+) {
+    let overlay = settings.graphics.overlay_mode;
+    if state.active == overlay {
+        if !overlay && settings.is_changed() {
+            clear_color.0 = window_clear_color(&settings);
+        }
+        return;
+    }
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    state.active = overlay;
+
+    window.decorations = !overlay;
+    window.window_level = if overlay {
+        WindowLevel::AlwaysOnTop
+    } else {
+        WindowLevel::Normal
+    };
+    clear_color.0 = if overlay {
+        Color::NONE
+    } else {
+        window_clear_color(&settings)
+    };
+
+    if overlay {
+        state.hidden_panels = panels.open_titles();
+        for title in &state.hidden_panels {
+            panels.set_open(title, false);
+        }
+    } else {
+        for title in std::mem::take(&mut state.hidden_panels) {
+            panels.set_open(&title, true);
+        }
+    }
+
+    for (mut camera, mut background) in &mut cameras {
+        if overlay {
+            camera.clear_color = ClearColorConfig::Custom(Color::NONE);
+        } else {
+            // Let the Background window's settings take over again.
+            background.set_changed();
+        }
+    }
+}
+
+fn overlay_toolbar_system(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<Settings>,
+    mut gizmo_mode: ResMut<GizmoMode>,
+    mut gizmo_space: ResMut<GizmoSpace>,
+    mut sandbox_mode: ResMut<SandboxMode>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let see_through = windows.get_single().is_ok_and(|window| window.transparent);
+    egui::TopBottomPanel::top("overlay_toolbar")
+        .frame(egui::Frame::none().inner_margin(4.0))
+        .show_separator_line(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .icon_button(Icon::Close, "Exit overlay")
+                        .on_hover_text("Restore the window frame and the full interface")
+                        .clicked()
+                    {
+                        settings.graphics.overlay_mode = false;
+                    }
+                    ui.separator();
+                    for (mode, label, tooltip) in SandboxMode::ALL {
+                        ui.selectable_value(&mut *sandbox_mode, mode, label)
+                            .on_hover_text(tooltip);
+                    }
+                    ui.separator();
+                    for (mode, icon, tooltip) in GizmoMode::ALL {
+                        ui.selectable_value(&mut *gizmo_mode, mode, icon)
+                            .on_hover_text(tooltip);
+                    }
+                    for (space, label, tooltip) in GizmoSpace::ALL {
+                        ui.selectable_value(&mut *gizmo_space, space, label)
+                            .on_hover_text(tooltip);
+                    }
+                    if !see_through {
+                        ui.separator();
+                        ui.weak("Restart to make the background see-through");
+                    }
+                });
+            });
+        });
+}
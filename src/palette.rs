@@ -0,0 +1,296 @@
+use bevy::{prelude::*, render::render_resource::TextureFormat};
+use bevy_egui::egui;
+use rand::{seq::SliceRandom, SeedableRng};
+
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+use crate::RenderCube;
+
+/// Palette generation for spawned entities.
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColorPalette>()
+            .register_panel::<PaletteWindow>()
+            .add_event::<RecolorAll>()
+            .add_systems(
+                Update,
+                (
+                    palette_window_system.in_set(PaletteWindow::ui_set()),
+                    recolor_all_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PaletteMode {
+    Complementary,
+    Analogous,
+    SampledFromImage,
+}
+
+impl PaletteMode {
+    const ALL: [PaletteMode; 3] = [
+        PaletteMode::Complementary,
+        PaletteMode::Analogous,
+        PaletteMode::SampledFromImage,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PaletteMode::Complementary => "Complementary",
+            PaletteMode::Analogous => "Analogous",
+            PaletteMode::SampledFromImage => "Sampled from image",
+        }
+    }
+}
+
+/// The active palette and whether new cubes should cycle through it.
+#[derive(Resource)]
+pub struct ColorPalette {
+    pub colors: Vec<Color>,
+    pub cycle_spawns: bool,
+    next: usize,
+}
+
+impl Default for ColorPalette {
+    fn default() -> Self {
+        Self {
+            colors: generate(Color::srgb(0.8, 0.7, 0.6), PaletteMode::Analogous, 5),
+            cycle_spawns: false,
+            next: 0,
+        }
+    }
+}
+
+impl ColorPalette {
+    /// Color for the next spawned cube, or `None` when cycling is disabled.
+    pub fn next_spawn_color(&mut self) -> Option<Color> {
+        if !self.cycle_spawns || self.colors.is_empty() {
+            return None;
+        }
+        let color = self.colors[self.next % self.colors.len()];
+        self.next = (self.next + 1) % self.colors.len();
+        Some(color)
+    }
+}
+
+/// Applies the active palette to every cube in the scene.
+#[derive(Event)]
+pub struct RecolorAll;
+
+fn generate(base: Color, mode: PaletteMode, count: usize) -> Vec<Color> {
+    let count = count.max(1);
+    match mode {
+        PaletteMode::Complementary => (0..count)
+            .map(|i| {
+                // Alternate between the base and its complement, stepping lightness for variety.
+                let hue_offset = if i % 2 == 0 { 0.0 } else { 180.0 };
+                let step = (i / 2) as f32 / count as f32;
+                let hsla = Hsla::from(base).rotate_hue(hue_offset);
+                let lightness = (hsla.lightness + step * 0.4).fract().clamp(0.15, 0.85);
+                Color::from(hsla.with_lightness(lightness))
+            })
+            .collect(),
+        PaletteMode::Analogous => {
+            let spread = 60.0;
+            (0..count)
+                .map(|i| {
+                    let t = if count == 1 {
+                        0.0
+                    } else {
+                        i as f32 / (count - 1) as f32 - 0.5
+                    };
+                    Color::from(Hsla::from(base).rotate_hue(t * spread))
+                })
+                .collect()
+        }
+        // Sampling needs image data; see `sample_image`.
+        PaletteMode::SampledFromImage => vec![base],
+    }
+}
+
+/// Picks `count` representative colors from an RGBA8 image with a few rounds of k-means.
+fn sample_image(image: &Image, count: usize) -> Option<Vec<Color>> {
+    if !matches!(
+        image.texture_descriptor.format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return None;
+    }
+
+    let pixels: Vec<Vec3> = image
+        .data
+        .chunks_exact(4)
+        .step_by(7)
+        .filter(|rgba| rgba[3] > 128)
+        .map(|rgba| Vec3::new(rgba[0] as f32, rgba[1] as f32, rgba[2] as f32) / 255.0)
+        .collect();
+    if pixels.is_empty() {
+        return None;
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut centroids: Vec<Vec3> = pixels
+        .choose_multiple(&mut rng, count.max(1))
+        .copied()
+        .collect();
+    for _ in 0..8 {
+        let mut sums = vec![(Vec3::ZERO, 0u32); centroids.len()];
+        for pixel in &pixels {
+            let nearest = centroids
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.distance_squared(*pixel)
+                        .total_cmp(&b.distance_squared(*pixel))
+                })
+                .map(|(i, _)| i)
+                .unwrap();
+            sums[nearest].0 += *pixel;
+            sums[nearest].1 += 1;
+        }
+        for (centroid, (sum, n)) in centroids.iter_mut().zip(sums) {
+            if n > 0 {
+                *centroid = sum / n as f32;
+            }
+        }
+    }
+    Some(
+        centroids
+            .into_iter()
+            .map(|c| Color::srgb(c.x, c.y, c.z))
+            .collect(),
+    )
+}
+
+#[derive(Resource)]
+pub struct PaletteWindow {
+    pub is_open: bool,
+    mode: PaletteMode,
+    base: [f32; 3],
+    count: usize,
+    source_image: Handle<Image>,
+}
+
+impl Panel for PaletteWindow {
+    const TITLE: &'static str = "Palette";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl FromWorld for PaletteWindow {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            is_open: false,
+            mode: PaletteMode::Analogous,
+            base: [0.8, 0.7, 0.6],
+            count: 5,
+            source_image: world.resource::<AssetServer>().load("icon.png"),
+        }
+    }
+}
+
+fn palette_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<PaletteWindow>,
+    mut palette: ResMut<ColorPalette>,
+    mut recolor: EventWriter<RecolorAll>,
+    images: Res<Assets<Image>>,
+) {
+    let PaletteWindow {
+        is_open,
+        mode,
+        base,
+        count,
+        source_image,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Palette")
+        .open(is_open)
+        .show(contexts.ctx::<PaletteWindow>(), |ui| {
+            egui::Grid::new("palette_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Scheme");
+                    egui::ComboBox::from_id_source("palette_mode")
+                        .selected_text(mode.label())
+                        .show_ui(ui, |ui| {
+                            for m in PaletteMode::ALL {
+                                ui.selectable_value(mode, m, m.label());
+                            }
+                        });
+                    ui.end_row();
+                    if *mode == PaletteMode::SampledFromImage {
+                        ui.label("Source");
+                        ui.label("icon.png");
+                    } else {
+                        ui.label("Base color");
+                        ui.color_edit_button_rgb(base);
+                    }
+                    ui.end_row();
+                    ui.label("Colors");
+                    ui.add(egui::Slider::new(count, 1..=12));
+                    ui.end_row();
+                });
+
+            if ui.button("Generate").clicked() {
+                let [r, g, b] = *base;
+                palette.colors = if *mode == PaletteMode::SampledFromImage {
+                    match images
+                        .get(&*source_image)
+                        .and_then(|image| sample_image(image, *count))
+                    {
+                        Some(colors) => colors,
+                        None => {
+                            warn!("Palette source image is not loaded or not RGBA8");
+                            palette.colors.clone()
+                        }
+                    }
+                } else {
+                    generate(Color::srgb(r, g, b), *mode, *count)
+                };
+                palette.next = 0;
+            }
+
+            ui.horizontal_wrapped(|ui| {
+                for color in &palette.colors {
+                    let [r, g, b, _] = color.to_srgba().to_u8_array();
+                    let (rect, response) =
+                        ui.allocate_exact_size(egui::vec2(24.0, 24.0), egui::Sense::hover());
+                    ui.painter()
+                        .rect_filled(rect, 2.0, egui::Color32::from_rgb(r, g, b));
+                    response.on_hover_text(format!("#{r:02x}{g:02x}{b:02x}"));
+                }
+            });
+
+            ui.separator();
+            ui.checkbox(&mut palette.cycle_spawns, "New cubes cycle through palette");
+            if ui.button("Recolor all").clicked() {
+                recolor.send(RecolorAll);
+            }
+        });
+}
+
+fn recolor_all_system(
+    mut events: EventReader<RecolorAll>,
+    palette: Res<ColorPalette>,
+    cubes: Query<&Handle<StandardMaterial>, With<RenderCube>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if events.read().count() == 0 || palette.colors.is_empty() {
+        return;
+    }
+    for (material, color) in cubes.iter().zip(palette.colors.iter().cycle()) {
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = *color;
+        }
+    }
+}
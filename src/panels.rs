@@ -0,0 +1,502 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{system::SystemParam, world::Command},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    icons::{self, Icon, IconButtonsExt},
+    keybindings::{Action, Keybindings, Shortcuts},
+    settings::Workspace,
+};
+
+/// Runs shortcuts of contributed menu items. The registry itself is created on first use by
+/// [`RegisterPanelExt`], so plugins may register before or after this one.
+pub struct PanelsPlugin;
+
+impl Plugin for PanelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.world_mut()
+            .get_resource_or_insert_with(PanelRegistry::default);
+        app.init_resource::<UiStateRegistry>()
+            .add_event::<MenuItemRun>()
+            .add_systems(Update, (menu_shortcuts_system, closed_windows_system));
+    }
+}
+
+/// A toggleable window. Registering it with [`RegisterPanelExt::register_panel`] lists it in
+/// the View menu, so adding a window does not mean touching the menu bar.
+pub trait Panel: Resource + FromWorld {
+    const TITLE: &'static str;
+
+    fn is_open_mut(&mut self) -> &mut bool;
+
+    /// The set the panel's window system goes in, so it does not run at all while the panel is
+    /// closed. Systems that also react while it is closed, say to end a session it started,
+    /// stay out of it and return early themselves.
+    fn ui_set() -> PanelUi<Self>
+    where
+        Self: Sized,
+    {
+        PanelUi(PhantomData)
+    }
+}
+
+/// The systems building panel `T`'s UI, configured by [`RegisterPanelExt::register_panel`] to
+/// run only while it is open.
+#[derive(SystemSet)]
+pub struct PanelUi<T>(PhantomData<fn() -> T>);
+
+impl<T> Clone for PanelUi<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for PanelUi<T> {}
+
+impl<T> PartialEq for PanelUi<T> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> Eq for PanelUi<T> {}
+
+impl<T> std::hash::Hash for PanelUi<T> {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+
+impl<T> std::fmt::Debug for PanelUi<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "PanelUi<{}>", std::any::type_name::<T>())
+    }
+}
+
+pub struct PanelEntry {
+    pub title: &'static str,
+    pub open: bool,
+    /// Set by the menu when `open` was flipped; consumed by the panel's sync system.
+    toggled: bool,
+}
+
+impl PanelEntry {
+    pub fn toggle_ui(&mut self, ui: &mut egui::Ui) {
+        if ui.checkbox(&mut self.open, self.title).changed() {
+            self.toggled = true;
+        }
+    }
+
+    /// Lists `windows` to move this panel into, `None` standing for the primary window.
+    pub fn move_ui(
+        &self,
+        ui: &mut egui::Ui,
+        ui_states: &mut UiStateRegistry,
+        windows: &[(Option<Entity>, String)],
+    ) {
+        let current = ui_states.window_of(self.title);
+        ui.menu_button(self.title, |ui| {
+            for (window, title) in windows {
+                if ui.radio(current == *window, title.as_str()).clicked() {
+                    ui_states.move_panel(self.title, *window);
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Menu {
+    File,
+    Edit,
+    View,
+    Help,
+}
+
+impl Menu {
+    pub const ALL: [Menu; 4] = [Menu::File, Menu::Edit, Menu::View, Menu::Help];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Menu::File => "File",
+            Menu::Edit => "Edit",
+            Menu::View => "View",
+            Menu::Help => "Help",
+        }
+    }
+}
+
+/// A menu entry, optionally mirrored as a toolbar button, contributed by a plugin. Its
+/// shortcut, if any, is dispatched by [`PanelsPlugin`].
+pub struct MenuItem {
+    pub menu: Menu,
+    pub label: &'static str,
+    pub icon: Option<Icon>,
+    pub shortcut: Option<Action>,
+    pub in_toolbar: bool,
+    pub separator_before: bool,
+    pub run: fn(&mut World),
+}
+
+impl MenuItem {
+    pub fn new(menu: Menu, label: &'static str, run: fn(&mut World)) -> Self {
+        Self {
+            menu,
+            label,
+            icon: None,
+            shortcut: None,
+            in_toolbar: false,
+            separator_before: false,
+            run,
+        }
+    }
+
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn shortcut(mut self, action: Action) -> Self {
+        self.shortcut = Some(action);
+        self
+    }
+
+    /// Also show the item as an icon button in the toolbar.
+    pub fn in_toolbar(mut self) -> Self {
+        self.in_toolbar = true;
+        self
+    }
+
+    pub fn separator_before(mut self) -> Self {
+        self.separator_before = true;
+        self
+    }
+
+    fn shortcut_text(&self, keybindings: &Keybindings) -> String {
+        self.shortcut
+            .map(|action| keybindings.label(action))
+            .unwrap_or_default()
+    }
+
+    pub fn menu_button(&self, ui: &mut egui::Ui, keybindings: &Keybindings) -> egui::Response {
+        if self.separator_before {
+            ui.separator();
+        }
+        let button = match self.icon {
+            Some(icon) => icons::button(ui, icon, self.label),
+            None => egui::Button::new(self.label),
+        };
+        ui.add(button.shortcut_text(self.shortcut_text(keybindings)))
+    }
+
+    pub fn toolbar_button(&self, ui: &mut egui::Ui, keybindings: &Keybindings) -> egui::Response {
+        let shortcut = self.shortcut_text(keybindings);
+        let tooltip = if shortcut.is_empty() {
+            self.label.to_owned()
+        } else {
+            format!("{} ({shortcut})", self.label)
+        };
+        let button = match self.icon {
+            Some(icon) => icons::button(ui, icon, ""),
+            None => egui::Button::new(self.label),
+        };
+        ui.add(button).on_hover_text(tooltip)
+    }
+}
+
+/// Every registered panel and menu item in registration order. Panel open states are mirrored
+/// each frame.
+#[derive(Default, Resource)]
+pub struct PanelRegistry {
+    panels: Vec<PanelEntry>,
+    items: Vec<MenuItem>,
+}
+
+impl PanelRegistry {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut PanelEntry> {
+        self.panels.iter_mut()
+    }
+
+    /// Opens or closes the panel titled `title`, ignoring case. Returns false if there is none.
+    pub fn set_open(&mut self, title: &str, open: bool) -> bool {
+        let Some(entry) = self
+            .panels
+            .iter_mut()
+            .find(|entry| entry.title.eq_ignore_ascii_case(title))
+        else {
+            return false;
+        };
+        entry.open = open;
+        entry.toggled = true;
+        true
+    }
+
+    /// Opens exactly the panels of `workspace`, closing the rest.
+    pub fn apply_workspace(&mut self, workspace: &Workspace) {
+        for entry in &mut self.panels {
+            let open = workspace.panels.iter().any(|title| title == entry.title);
+            if entry.open != open {
+                entry.open = open;
+                entry.toggled = true;
+            }
+        }
+    }
+
+    pub fn open_titles(&self) -> Vec<String> {
+        self.panels
+            .iter()
+            .filter(|entry| entry.open)
+            .map(|entry| entry.title.to_owned())
+            .collect()
+    }
+
+    pub fn items(&self, menu: Menu) -> impl Iterator<Item = &MenuItem> {
+        self.items.iter().filter(move |item| item.menu == menu)
+    }
+
+    pub fn toolbar_items(&self) -> impl Iterator<Item = &MenuItem> {
+        self.items.iter().filter(|item| item.in_toolbar)
+    }
+
+    pub fn find_item(&self, label: &str) -> Option<&MenuItem> {
+        self.items.iter().find(|item| item.label == label)
+    }
+}
+
+/// The View › Workspaces submenu: switch to, overwrite, delete or add a workspace. Returns
+/// whether `workspaces` changed.
+pub fn workspaces_menu(
+    ui: &mut egui::Ui,
+    registry: &mut PanelRegistry,
+    workspaces: &mut Vec<Workspace>,
+) -> bool {
+    let mut changed = false;
+    ui.menu_button("Workspaces", |ui| {
+        let mut delete = None;
+        for (index, workspace) in workspaces.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button(&workspace.name).clicked() {
+                    registry.apply_workspace(workspace);
+                    ui.close_menu();
+                }
+                if ui
+                    .small_icon_button(Icon::Save, "")
+                    .on_hover_text("Store the open panels in this workspace")
+                    .clicked()
+                {
+                    workspace.panels = registry.open_titles();
+                    changed = true;
+                }
+                if ui
+                    .small_icon_button(Icon::Delete, "")
+                    .on_hover_text("Delete")
+                    .clicked()
+                {
+                    delete = Some(index);
+                }
+            });
+        }
+        if let Some(index) = delete {
+            workspaces.remove(index);
+            changed = true;
+        }
+
+        ui.separator();
+        let id = egui::Id::new("new_workspace_name");
+        let mut name = ui.data_mut(|data| data.get_temp::<String>(id).unwrap_or_default());
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut name)
+                    .hint_text("New workspace")
+                    .desired_width(120.0),
+            );
+            if ui
+                .add_enabled(!name.trim().is_empty(), egui::Button::new("Save current"))
+                .clicked()
+            {
+                workspaces.push(Workspace {
+                    name: std::mem::take(&mut name).trim().to_owned(),
+                    panels: registry.open_titles(),
+                });
+                changed = true;
+            }
+        });
+        ui.data_mut(|data| data.insert_temp(id, name));
+    });
+    changed
+}
+
+/// Runs the menu item labelled `label` as if the user had picked it, then announces it with
+/// [`MenuItemRun`]. The menu bar, toolbar and shortcuts all go through this, as can buttons
+/// that duplicate a menu entry.
+pub struct RunMenuItem(pub &'static str);
+
+impl Command for RunMenuItem {
+    fn apply(self, world: &mut World) {
+        let Some(run) = world
+            .resource::<PanelRegistry>()
+            .find_item(self.0)
+            .map(|item| item.run)
+        else {
+            warn!("There is no menu item labelled \"{}\"", self.0);
+            return;
+        };
+        run(world);
+        world.send_event(MenuItemRun { label: self.0 });
+    }
+}
+
+/// A menu item the user ran, for recording what they did.
+#[derive(Event)]
+pub struct MenuItemRun {
+    pub label: &'static str,
+}
+
+pub trait RegisterPanelExt {
+    fn register_panel<T: Panel>(&mut self) -> &mut Self;
+
+    fn add_menu_item(&mut self, item: MenuItem) -> &mut Self;
+}
+
+impl RegisterPanelExt for App {
+    fn register_panel<T: Panel>(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(PanelRegistry::default)
+            .panels
+            .push(PanelEntry {
+                title: T::TITLE,
+                open: false,
+                toggled: false,
+            });
+        self.init_resource::<T>()
+            .configure_sets(Update, T::ui_set().run_if(panel_open::<T>))
+            .add_systems(Update, sync_panel_system::<T>.after(crate::UiSet::Panels))
+    }
+
+    fn add_menu_item(&mut self, item: MenuItem) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(PanelRegistry::default)
+            .items
+            .push(item);
+        self
+    }
+}
+
+fn menu_shortcuts_system(
+    shortcuts: Shortcuts,
+    registry: Res<PanelRegistry>,
+    mut commands: Commands,
+) {
+    for item in &registry.items {
+        if item
+            .shortcut
+            .is_some_and(|action| shortcuts.just_pressed(action))
+        {
+            commands.add(RunMenuItem(item.label));
+        }
+    }
+}
+
+/// Whether panel `T` is open, going by the registry's mirror of it. Hidden panels, such as
+/// those the overlay mode puts away, are closed through the registry too.
+fn panel_open<T: Panel>(registry: Res<PanelRegistry>) -> bool {
+    registry
+        .panels
+        .iter()
+        .any(|entry| entry.title == T::TITLE && entry.open)
+}
+
+/// Applies menu toggles to the panel's resource, otherwise mirrors the resource into the menu.
+fn sync_panel_system<T: Panel>(mut registry: ResMut<PanelRegistry>, mut panel: ResMut<T>) {
+    let Some(entry) = registry
+        .bypass_change_detection()
+        .panels
+        .iter_mut()
+        .find(|entry| entry.title == T::TITLE)
+    else {
+        return;
+    };
+    if std::mem::take(&mut entry.toggled) {
+        *panel.is_open_mut() = entry.open;
+    } else {
+        entry.open = *panel.bypass_change_detection().is_open_mut();
+    }
+}
+
+/// Per-window UI state, keyed by window entity. Panel resources are shared by every window;
+/// what differs is which window hosts each panel, and with it the egui context whose memory
+/// holds the panel's position and size. Panels not listed here live in the primary window.
+#[derive(Default, Resource)]
+pub struct UiStateRegistry {
+    windows: HashMap<Entity, WindowUiState>,
+}
+
+#[derive(Default)]
+pub struct WindowUiState {
+    /// Titles of the panels hosted by this window.
+    pub panels: Vec<&'static str>,
+}
+
+impl UiStateRegistry {
+    /// The window hosting the panel titled `title`, or `None` for the primary window.
+    pub fn window_of(&self, title: &str) -> Option<Entity> {
+        self.windows
+            .iter()
+            .find(|(_, state)| state.panels.contains(&title))
+            .map(|(window, _)| *window)
+    }
+
+    /// Moves the panel titled `title` to `window`; `None` returns it to the primary window.
+    pub fn move_panel(&mut self, title: &'static str, window: Option<Entity>) {
+        for state in self.windows.values_mut() {
+            state.panels.retain(|panel| *panel != title);
+        }
+        if let Some(window) = window {
+            self.windows.entry(window).or_default().panels.push(title);
+        }
+        self.windows.retain(|_, state| !state.panels.is_empty());
+    }
+
+    /// Forgets `window`, sending its panels back to the primary window.
+    pub fn close_window(&mut self, window: Entity) -> Option<WindowUiState> {
+        self.windows.remove(&window)
+    }
+}
+
+/// Egui contexts resolved per panel, for panel systems that may be hosted by any window.
+#[derive(SystemParam)]
+pub struct PanelContexts<'w, 's> {
+    contexts: EguiContexts<'w, 's>,
+    registry: Res<'w, UiStateRegistry>,
+}
+
+impl PanelContexts<'_, '_> {
+    /// The context of the window hosting `T`, falling back to the primary window if that
+    /// window has no egui context (yet).
+    pub fn ctx<T: Panel>(&mut self) -> &mut egui::Context {
+        if let Some(window) = self.registry.window_of(T::TITLE) {
+            if self.contexts.try_ctx_for_window_mut(window).is_some() {
+                return self.contexts.ctx_for_window_mut(window);
+            }
+        }
+        self.contexts.ctx_mut()
+    }
+}
+
+fn closed_windows_system(
+    mut closed: RemovedComponents<Window>,
+    mut registry: ResMut<UiStateRegistry>,
+) {
+    for window in closed.read() {
+        if let Some(state) = registry.close_window(window) {
+            info!(
+                "Window closed; moved {} back to the main window",
+                state.panels.join(", ")
+            );
+        }
+    }
+}
@@ -0,0 +1,940 @@
+use std::{num::NonZeroU64, sync::Arc, time::Instant};
+
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    ecs::system::{lifetimeless::SRes, SystemParamItem},
+    pbr::{
+        MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshBindGroup, SetMeshViewBindGroup,
+    },
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        graph::CameraDriverLabel,
+        mesh::{GpuBufferInfo, GpuMesh, MeshVertexBufferLayoutRef},
+        render_asset::RenderAssets,
+        render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel},
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+            RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+        },
+        render_resource::{
+            binding_types::{storage_buffer_sized, uniform_buffer_sized},
+            BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, Buffer,
+            BufferDescriptor, BufferInitDescriptor, BufferUsages, CachedComputePipelineId,
+            ComputePassDescriptor, ComputePipelineDescriptor, PipelineCache,
+            RenderPipelineDescriptor, ShaderStages, SpecializedMeshPipeline,
+            SpecializedMeshPipelineError, SpecializedMeshPipelines, VertexAttribute,
+            VertexBufferLayout, VertexFormat, VertexStepMode,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        view::{ExtractedView, NoFrustumCulling},
+        Render, RenderApp, RenderSet,
+    },
+};
+use bevy_egui::egui;
+use rand::Rng;
+use xihydra_bevy::widgets::{ColorStop, Gradient, GradientEditor};
+
+use crate::icons::{Icon, IconButtonsExt};
+use crate::numeric::drag_value;
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+
+const UPDATE_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x5f1c_2a8e_93d4_4b07_a6e1_0c7d_42b9_e861);
+const DRAW_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x8b3e_61f0_27ac_4d95_b1c8_f45a_0e6d_937c);
+
+/// Bytes per particle on the GPU: position and size, velocity and age, colour.
+const PARTICLE_SIZE: u64 = 48;
+/// Colours the GPU interpolates the colour-over-life gradient from.
+const COLOR_SAMPLES: usize = 16;
+/// Bytes of the `Emitter` uniform, padded to the WGSL layout.
+const EMITTER_SIZE: u64 = 336;
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_CPU_PARTICLES: u32 = 200_000;
+const MAX_GPU_PARTICLES: u32 = 2_000_000;
+
+const UPDATE_WGSL: &str = r#"struct Particle {
+    position_size: vec4<f32>,
+    velocity_age: vec4<f32>,
+    color: vec4<f32>,
+}
+
+struct Emitter {
+    origin: vec3<f32>,
+    spread: f32,
+    direction: vec3<f32>,
+    speed: f32,
+    gravity: vec3<f32>,
+    lifetime: f32,
+    colors: array<vec4<f32>, 16>,
+    size: f32,
+    dt: f32,
+    seed: u32,
+    count: u32,
+    reset: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(1) var<uniform> emitter: Emitter;
+
+fn hash(value: u32) -> u32 {
+    var x = value;
+    x = (x ^ 61u) ^ (x >> 16u);
+    x = x * 9u;
+    x = x ^ (x >> 4u);
+    x = x * 0x27d4eb2du;
+    return x ^ (x >> 15u);
+}
+
+fn random(seed: u32) -> f32 {
+    return f32(hash(seed)) / 4294967295.0;
+}
+
+fn random_direction(seed: u32) -> vec3<f32> {
+    let z = random(seed) * 2.0 - 1.0;
+    let angle = random(seed + 1u) * 6.2831855;
+    let r = sqrt(max(1.0 - z * z, 0.0));
+    return vec3<f32>(r * cos(angle), r * sin(angle), z);
+}
+
+@compute @workgroup_size(64, 1, 1)
+fn update(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= emitter.count {
+        return;
+    }
+    var particle = particles[index];
+    var age = particle.velocity_age.w;
+    if emitter.reset != 0u {
+        // Births are staggered over one lifetime so emission is steady from the start.
+        age = -random(hash(index)) * emitter.lifetime;
+    }
+    let previous = age;
+    age += emitter.dt;
+    if age < 0.0 {
+        // Not born yet: zero size keeps it invisible.
+        particles[index] = Particle(
+            vec4<f32>(emitter.origin, 0.0),
+            vec4<f32>(0.0, 0.0, 0.0, age),
+            vec4<f32>(0.0),
+        );
+        return;
+    }
+
+    var position = particle.position_size.xyz;
+    var velocity = particle.velocity_age.xyz;
+    if previous < 0.0 || age >= emitter.lifetime {
+        age = age % emitter.lifetime;
+        let seed = hash(index ^ emitter.seed);
+        position = emitter.origin;
+        let direction = normalize(emitter.direction + random_direction(seed) * emitter.spread);
+        velocity = direction * emitter.speed * (0.75 + 0.5 * random(seed + 7u));
+    }
+    velocity += emitter.gravity * emitter.dt;
+    position += velocity * emitter.dt;
+    let t = clamp(age / emitter.lifetime, 0.0, 1.0) * 15.0;
+    let sample = min(u32(t), 14u);
+    particles[index] = Particle(
+        vec4<f32>(position, emitter.size),
+        vec4<f32>(velocity, age),
+        mix(emitter.colors[sample], emitter.colors[sample + 1u], t - f32(sample)),
+    );
+}
+"#;
+
+const DRAW_WGSL: &str = r#"#import bevy_pbr::mesh_view_bindings::view
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(3) position_size: vec4<f32>,
+    @location(4) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let world = vertex.position * vertex.position_size.w + vertex.position_size.xyz;
+    let light = 0.6 + 0.4 * max(dot(vertex.normal, normalize(vec3<f32>(0.4, 1.0, 0.3))), 0.0);
+    var out: VertexOutput;
+    out.clip_position = view.clip_from_world * vec4<f32>(world, 1.0);
+    out.color = vec4<f32>(vertex.color.rgb * light, 1.0);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+"#;
+
+/// The Particles window: one emitter of instanced cubes, simulated either on the CPU or by a
+/// compute shader, drawn the same way in both modes so they can be compared directly.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        let mut shaders = app.world_mut().resource_mut::<Assets<Shader>>();
+        shaders.insert(
+            UPDATE_SHADER.id(),
+            Shader::from_wgsl(UPDATE_WGSL, "particles_update.wgsl"),
+        );
+        shaders.insert(
+            DRAW_SHADER.id(),
+            Shader::from_wgsl(DRAW_WGSL, "particles_draw.wgsl"),
+        );
+
+        app.init_resource::<CpuParticles>()
+            .init_resource::<ParticleTicks>()
+            .insert_resource(ParticleFrame::default())
+            .register_panel::<ParticlesWindow>()
+            .add_plugins((
+                ExtractComponentPlugin::<ParticleInstances>::default(),
+                ExtractResourcePlugin::<ParticleFrame>::default(),
+            ))
+            .add_systems(Startup, spawn_particles_system)
+            .add_systems(FixedUpdate, simulate_particles_system)
+            .add_systems(
+                Update,
+                (
+                    particles_window_system.in_set(ParticlesWindow::ui_set()),
+                    publish_particles_system,
+                )
+                    .chain(),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_command::<Transparent3d, DrawParticlesCommands>()
+            .init_resource::<SpecializedMeshPipelines<ParticlePipeline>>()
+            .add_systems(
+                Render,
+                (
+                    prepare_particles_system.in_set(RenderSet::PrepareResources),
+                    queue_particles_system.in_set(RenderSet::QueueMeshes),
+                ),
+            );
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(ParticlesLabel, ParticlesNode);
+        graph.add_node_edge(ParticlesLabel, CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<ParticlePipeline>()
+                .init_resource::<ParticleBuffers>();
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationMode {
+    Cpu,
+    #[default]
+    Gpu,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Emitter {
+    pub count: u32,
+    pub origin: Vec3,
+    pub direction: Vec3,
+    /// How far velocities scatter around `direction`; 1 is a full sphere of jitter.
+    pub spread: f32,
+    pub speed: f32,
+    pub gravity: f32,
+    pub lifetime: f32,
+    pub size: f32,
+    /// Colour over each particle's life, from birth at 0 to death at 1.
+    pub colors: Gradient,
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self {
+            count: 100_000,
+            origin: Vec3::new(0.0, 0.5, 0.0),
+            direction: Vec3::Y,
+            spread: 0.35,
+            speed: 6.0,
+            gravity: -9.81,
+            lifetime: 2.5,
+            size: 0.03,
+            colors: Gradient::new([
+                ColorStop::new(0.0, [1.0, 0.8, 0.3]),
+                ColorStop::new(0.6, [0.9, 0.35, 0.1]),
+                ColorStop::new(1.0, [0.3, 0.05, 0.02]),
+            ]),
+        }
+    }
+}
+
+impl Emitter {
+    fn max_count(mode: SimulationMode) -> u32 {
+        match mode {
+            SimulationMode::Cpu => MAX_CPU_PARTICLES,
+            SimulationMode::Gpu => MAX_GPU_PARTICLES,
+        }
+    }
+
+    fn direction(&self) -> Vec3 {
+        self.direction.try_normalize().unwrap_or(Vec3::Y)
+    }
+
+    fn color_at(&self, t: f32) -> [f32; 4] {
+        let [r, g, b] = self.colors.sample(t);
+        [r, g, b, 1.0]
+    }
+}
+
+#[derive(Resource)]
+pub struct ParticlesWindow {
+    pub is_open: bool,
+    emitting: bool,
+    mode: SimulationMode,
+    emitter: Emitter,
+    /// Set by edits that need every particle respawned.
+    reset: bool,
+    /// Smoothed CPU simulation cost in milliseconds.
+    cpu_ms: f32,
+}
+
+impl Default for ParticlesWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            emitting: false,
+            mode: SimulationMode::default(),
+            emitter: Emitter::default(),
+            reset: true,
+            cpu_ms: 0.0,
+        }
+    }
+}
+
+impl Panel for ParticlesWindow {
+    const TITLE: &'static str = "Particles";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// Marks the entity whose mesh is instanced once per particle.
+#[derive(Component, Clone, ExtractComponent)]
+struct ParticleInstances;
+
+/// What the render world draws this frame.
+#[derive(Resource, Clone, Default, ExtractResource)]
+struct ParticleFrame {
+    mode: SimulationMode,
+    /// Particles to draw; zero while not emitting.
+    count: u32,
+    /// The emitter uniform for the compute pass, in GPU mode.
+    uniform: Vec<u8>,
+    /// Particle data simulated on the CPU, in CPU mode.
+    cpu_data: Arc<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct CpuParticle {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+}
+
+#[derive(Default, Resource)]
+struct CpuParticles(Vec<CpuParticle>);
+
+/// What the fixed ticks since the last frame left for [`ParticleFrame`].
+#[derive(Default, Resource)]
+struct ParticleTicks {
+    /// The latest CPU step, in the GPU particle layout.
+    cpu_data: Arc<Vec<u8>>,
+    /// Simulated time the compute pass still has to catch up on.
+    gpu_seconds: f32,
+}
+
+fn spawn_particles_system(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.spawn((
+        meshes.add(Cuboid::from_length(1.0)),
+        SpatialBundle {
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        // Also keeps bounds off the entity, so picking never hits the unit cube.
+        NoFrustumCulling,
+        ParticleInstances,
+        Name::new("Particles"),
+    ));
+}
+
+fn particles_window_system(mut contexts: PanelContexts, mut window: ResMut<ParticlesWindow>) {
+    let ParticlesWindow {
+        is_open,
+        emitting,
+        mode,
+        emitter,
+        reset,
+        cpu_ms,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(ParticlesWindow::TITLE)
+        .open(is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<ParticlesWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.toggle_value(emitting, "▶ Emit");
+                if ui.icon_button(Icon::Restore, "Reset").clicked() {
+                    *reset = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Simulate on");
+                let before = *mode;
+                ui.selectable_value(mode, SimulationMode::Cpu, "CPU");
+                ui.selectable_value(mode, SimulationMode::Gpu, "GPU (compute)");
+                if *mode != before {
+                    emitter.count = emitter.count.min(Emitter::max_count(*mode));
+                    *reset = true;
+                }
+            });
+            match mode {
+                SimulationMode::Cpu => ui.weak(format!("Simulation: {cpu_ms:.2} ms per tick")),
+                SimulationMode::Gpu => ui.weak("Simulation runs in a compute pass"),
+            };
+            ui.separator();
+
+            let before = emitter.clone();
+            egui::Grid::new("particle_emitter")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Count");
+                    ui.add(
+                        egui::Slider::new(&mut emitter.count, 1_000..=Emitter::max_count(*mode))
+                            .logarithmic(true),
+                    );
+                    ui.end_row();
+                    ui.label("Origin");
+                    vec3_edit(ui, &mut emitter.origin);
+                    ui.end_row();
+                    ui.label("Direction");
+                    vec3_edit(ui, &mut emitter.direction);
+                    ui.end_row();
+                    ui.label("Spread");
+                    ui.add(egui::Slider::new(&mut emitter.spread, 0.0..=1.0));
+                    ui.end_row();
+                    ui.label("Speed");
+                    ui.add(egui::Slider::new(&mut emitter.speed, 0.0..=30.0).suffix(" m/s"));
+                    ui.end_row();
+                    ui.label("Gravity");
+                    ui.add(egui::Slider::new(&mut emitter.gravity, -20.0..=20.0).suffix(" m/s²"));
+                    ui.end_row();
+                    ui.label("Lifetime");
+                    ui.add(egui::Slider::new(&mut emitter.lifetime, 0.1..=10.0).suffix(" s"));
+                    ui.end_row();
+                    ui.label("Size");
+                    ui.add(egui::Slider::new(&mut emitter.size, 0.005..=0.5).logarithmic(true));
+                    ui.end_row();
+                    ui.label("Colour over life");
+                    GradientEditor::new("particle_colors", &mut emitter.colors)
+                        .width(180.0)
+                        .show(ui);
+                    ui.end_row();
+                });
+            // Staggered births depend on the count and lifetime, so those restart the emitter.
+            if emitter.count != before.count || emitter.lifetime != before.lifetime {
+                *reset = true;
+            }
+        });
+}
+
+fn vec3_edit(ui: &mut egui::Ui, value: &mut Vec3) {
+    ui.horizontal(|ui| {
+        for axis in [&mut value.x, &mut value.y, &mut value.z] {
+            ui.add(drag_value(axis).speed(0.05).fixed_decimals(2));
+        }
+    });
+}
+
+/// Steps the particles once per fixed tick, so they move at the same rate at any frame rate.
+///
+/// The compute pass dispatches once per rendered frame, so GPU mode only banks the tick for
+/// [`publish_particles_system`] to hand over.
+fn simulate_particles_system(
+    mut window: ResMut<ParticlesWindow>,
+    mut cpu: ResMut<CpuParticles>,
+    mut ticks: ResMut<ParticleTicks>,
+    time: Res<Time<Fixed>>,
+) {
+    let ParticlesWindow {
+        emitting,
+        mode,
+        emitter,
+        reset,
+        cpu_ms,
+        ..
+    } = &mut *window;
+    if !*emitting {
+        return;
+    }
+
+    let dt = time.delta_seconds();
+    match mode {
+        SimulationMode::Cpu => {
+            let started = Instant::now();
+            let data = simulate_cpu(&mut cpu.0, emitter, dt, *reset, &mut rand::thread_rng());
+            ticks.cpu_data = Arc::new(data);
+            let ms = started.elapsed().as_secs_f32() * 1000.0;
+            *cpu_ms += (ms - *cpu_ms) * 0.1;
+            *reset = false;
+        }
+        SimulationMode::Gpu => {
+            cpu.0.clear();
+            ticks.gpu_seconds += dt;
+        }
+    }
+}
+
+fn publish_particles_system(
+    mut window: ResMut<ParticlesWindow>,
+    mut ticks: ResMut<ParticleTicks>,
+    mut frame: ResMut<ParticleFrame>,
+    mut instances: Query<&mut Visibility, With<ParticleInstances>>,
+) {
+    let ParticlesWindow {
+        emitting,
+        mode,
+        emitter,
+        reset,
+        ..
+    } = &mut *window;
+    for mut visibility in &mut instances {
+        let wanted = if *emitting {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    if !*emitting {
+        ticks.gpu_seconds = 0.0;
+        if frame.count != 0 {
+            *frame = ParticleFrame::default();
+        }
+        return;
+    }
+
+    let mut next = ParticleFrame {
+        mode: *mode,
+        count: emitter.count,
+        ..default()
+    };
+    match mode {
+        SimulationMode::Cpu => next.cpu_data = ticks.cpu_data.clone(),
+        SimulationMode::Gpu => {
+            // A frame without a tick still dispatches, with nothing to step.
+            let dt = std::mem::take(&mut ticks.gpu_seconds).min(0.1);
+            next.uniform = emitter_uniform(emitter, dt, rand::thread_rng().gen(), *reset);
+            *reset = false;
+        }
+    }
+    *frame = next;
+}
+
+/// The same update as the compute shader, writing the GPU particle layout.
+fn simulate_cpu(
+    particles: &mut Vec<CpuParticle>,
+    emitter: &Emitter,
+    dt: f32,
+    reset: bool,
+    rng: &mut impl Rng,
+) -> Vec<u8> {
+    let count = emitter.count as usize;
+    if reset || particles.len() != count {
+        particles.clear();
+        particles.extend((0..count).map(|_| CpuParticle {
+            age: -rng.gen::<f32>() * emitter.lifetime,
+            ..default()
+        }));
+    }
+
+    let direction = emitter.direction();
+    let gravity = Vec3::Y * emitter.gravity;
+    let mut data = Vec::with_capacity(count * PARTICLE_SIZE as usize);
+    for particle in particles.iter_mut() {
+        let previous = particle.age;
+        particle.age += dt;
+        let mut row = [0.0; 12];
+        if particle.age >= 0.0 {
+            if previous < 0.0 || particle.age >= emitter.lifetime {
+                particle.age %= emitter.lifetime;
+                let jitter = random_direction(rng) * emitter.spread;
+                particle.position = emitter.origin;
+                particle.velocity = (direction + jitter).normalize_or(Vec3::Y)
+                    * emitter.speed
+                    * rng.gen_range(0.75..1.25);
+            }
+            particle.velocity += gravity * dt;
+            particle.position += particle.velocity * dt;
+            let t = (particle.age / emitter.lifetime).clamp(0.0, 1.0);
+            row[..3].copy_from_slice(&particle.position.to_array());
+            row[3] = emitter.size;
+            row[8..].copy_from_slice(&emitter.color_at(t));
+        }
+        data.extend(row.iter().flat_map(|value| value.to_le_bytes()));
+    }
+    data
+}
+
+fn random_direction(rng: &mut impl Rng) -> Vec3 {
+    let z: f32 = rng.gen_range(-1.0..=1.0);
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    Vec3::new(r * angle.cos(), r * angle.sin(), z)
+}
+
+/// Packs the `Emitter` uniform: each vec3 shares its 16 bytes with the scalar after it.
+fn emitter_uniform(emitter: &Emitter, dt: f32, seed: u32, reset: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(EMITTER_SIZE as usize);
+    let mut floats = |values: &[f32]| bytes.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+    floats(&emitter.origin.to_array());
+    floats(&[emitter.spread]);
+    floats(&emitter.direction().to_array());
+    floats(&[emitter.speed]);
+    floats(&[0.0, emitter.gravity, 0.0, emitter.lifetime]);
+    for [r, g, b] in emitter.colors.samples(COLOR_SAMPLES) {
+        floats(&[r, g, b, 1.0]);
+    }
+    floats(&[emitter.size, dt]);
+    for value in [seed, emitter.count, u32::from(reset)] {
+        bytes.extend(value.to_le_bytes());
+    }
+    bytes.resize(EMITTER_SIZE as usize, 0);
+    bytes
+}
+
+#[derive(Resource)]
+struct ParticlePipeline {
+    mesh_pipeline: MeshPipeline,
+    update_layout: BindGroupLayout,
+    update_pipeline: CachedComputePipelineId,
+}
+
+impl FromWorld for ParticlePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let update_layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "particles_update_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    storage_buffer_sized(false, NonZeroU64::new(PARTICLE_SIZE)),
+                    uniform_buffer_sized(false, NonZeroU64::new(EMITTER_SIZE)),
+                ),
+            ),
+        );
+        let update_pipeline =
+            world
+                .resource::<PipelineCache>()
+                .queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some("particles_update_pipeline".into()),
+                    layout: vec![update_layout.clone()],
+                    push_constant_ranges: Vec::new(),
+                    shader: UPDATE_SHADER,
+                    shader_defs: Vec::new(),
+                    entry_point: "update".into(),
+                });
+        Self {
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+            update_layout,
+            update_pipeline,
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for ParticlePipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.label = Some("particles_draw_pipeline".into());
+        descriptor.vertex.shader = DRAW_SHADER;
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: PARTICLE_SIZE,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 3,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 32,
+                    shader_location: 4,
+                },
+            ],
+        });
+        descriptor.fragment.as_mut().unwrap().shader = DRAW_SHADER;
+        Ok(descriptor)
+    }
+}
+
+/// The particle storage (also the instance vertex buffer) and this frame's compute work.
+#[derive(Resource)]
+struct ParticleBuffers {
+    particles: Option<Buffer>,
+    capacity: u32,
+    emitter: Buffer,
+    bind_group: Option<BindGroup>,
+    /// Particles to draw this frame.
+    instances: u32,
+    /// Workgroups for the update pass, in GPU mode.
+    dispatch: Option<u32>,
+}
+
+impl FromWorld for ParticleBuffers {
+    fn from_world(world: &mut World) -> Self {
+        let emitter =
+            world
+                .resource::<RenderDevice>()
+                .create_buffer_with_data(&BufferInitDescriptor {
+                    label: Some("particles_emitter"),
+                    contents: &[0; EMITTER_SIZE as usize],
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                });
+        Self {
+            particles: None,
+            capacity: 0,
+            emitter,
+            bind_group: None,
+            instances: 0,
+            dispatch: None,
+        }
+    }
+}
+
+fn prepare_particles_system(
+    frame: Option<Res<ParticleFrame>>,
+    mut buffers: ResMut<ParticleBuffers>,
+    pipeline: Res<ParticlePipeline>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let buffers = &mut *buffers;
+    buffers.dispatch = None;
+    buffers.instances = 0;
+    let Some(frame) = frame else {
+        return;
+    };
+    let limit = render_device.limits().max_storage_buffer_binding_size as u64 / PARTICLE_SIZE;
+    let count = frame.count.min(limit as u32);
+    buffers.instances = count;
+    if count == 0 {
+        return;
+    }
+
+    if buffers.particles.is_none() || buffers.capacity != count {
+        let particles = render_device.create_buffer(&BufferDescriptor {
+            label: Some("particles"),
+            size: count as u64 * PARTICLE_SIZE,
+            usage: BufferUsages::STORAGE | BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        buffers.bind_group = Some(render_device.create_bind_group(
+            "particles_update_bind_group",
+            &pipeline.update_layout,
+            &BindGroupEntries::sequential((
+                particles.as_entire_binding(),
+                buffers.emitter.as_entire_binding(),
+            )),
+        ));
+        buffers.particles = Some(particles);
+        buffers.capacity = count;
+    }
+    let particles = buffers.particles.as_ref().unwrap();
+
+    match frame.mode {
+        SimulationMode::Cpu => {
+            let len = (count as u64 * PARTICLE_SIZE) as usize;
+            if frame.cpu_data.len() >= len {
+                render_queue.write_buffer(particles, 0, &frame.cpu_data[..len]);
+            }
+        }
+        SimulationMode::Gpu => {
+            if frame.uniform.len() == EMITTER_SIZE as usize {
+                render_queue.write_buffer(&buffers.emitter, 0, &frame.uniform);
+                buffers.dispatch = Some(count.div_ceil(WORKGROUP_SIZE));
+            }
+        }
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, Hash, PartialEq, Eq)]
+struct ParticlesLabel;
+
+/// Steps the GPU particles before any camera draws them.
+struct ParticlesNode;
+
+impl render_graph::Node for ParticlesNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let (Some(buffers), Some(pipeline)) = (
+            world.get_resource::<ParticleBuffers>(),
+            world.get_resource::<ParticlePipeline>(),
+        ) else {
+            return Ok(());
+        };
+        let (Some(workgroups), Some(bind_group)) = (buffers.dispatch, &buffers.bind_group) else {
+            return Ok(());
+        };
+        let Some(update) = world
+            .resource::<PipelineCache>()
+            .get_compute_pipeline(pipeline.update_pipeline)
+        else {
+            return Ok(());
+        };
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("particles_update"),
+                    timestamp_writes: None,
+                });
+        pass.set_pipeline(update);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.dispatch_workgroups(workgroups, 1, 1);
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_particles_system(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    pipeline: Res<ParticlePipeline>,
+    msaa: Res<Msaa>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<ParticlePipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<GpuMesh>>,
+    mesh_instances: Res<RenderMeshInstances>,
+    frame: Option<Res<ParticleFrame>>,
+    emitters: Query<Entity, With<ParticleInstances>>,
+    mut phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(Entity, &ExtractedView)>,
+) {
+    // Queueing runs before prepare, so go by this frame's extracted count.
+    if frame.is_none_or(|frame| frame.count == 0) {
+        return;
+    }
+    let draw_particles = draw_functions.read().id::<DrawParticlesCommands>();
+    let msaa_key = MeshPipelineKey::from_msaa_samples(msaa.samples());
+    for (view_entity, view) in &views {
+        let Some(phase) = phases.get_mut(&view_entity) else {
+            continue;
+        };
+        let view_key = msaa_key | MeshPipelineKey::from_hdr(view.hdr);
+        let rangefinder = view.rangefinder3d();
+        for entity in &emitters {
+            let Some(mesh_instance) = mesh_instances.render_mesh_queue_data(entity) else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+            let key =
+                view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let id = match pipelines.specialize(&pipeline_cache, &pipeline, key, &mesh.layout) {
+                Ok(id) => id,
+                Err(err) => {
+                    error_once!("Particle pipeline failed to specialize: {err}");
+                    continue;
+                }
+            };
+            phase.add(Transparent3d {
+                entity,
+                pipeline: id,
+                draw_function: draw_particles,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::NONE,
+            });
+        }
+    }
+}
+
+type DrawParticlesCommands = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    DrawParticles,
+);
+
+/// Draws the entity's mesh once per particle, with the particle buffer as instance data.
+struct DrawParticles;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawParticles {
+    type Param = (
+        SRes<RenderAssets<GpuMesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<ParticleBuffers>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        _entity: Option<()>,
+        (meshes, mesh_instances, buffers): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(mesh_instance) = mesh_instances.render_mesh_queue_data(item.entity()) else {
+            return RenderCommandResult::Failure;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Failure;
+        };
+        let buffers = buffers.into_inner();
+        let Some(particles) = buffers.particles.as_ref() else {
+            return RenderCommandResult::Failure;
+        };
+
+        let instances = 0..buffers.instances;
+        pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+        pass.set_vertex_buffer(1, particles.slice(..));
+        match &gpu_mesh.buffer_info {
+            GpuBufferInfo::Indexed {
+                buffer,
+                index_format,
+                count,
+            } => {
+                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(0..*count, 0, instances);
+            }
+            GpuBufferInfo::NonIndexed => pass.draw(0..gpu_mesh.vertex_count, instances),
+        }
+        RenderCommandResult::Success
+    }
+}
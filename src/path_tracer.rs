@@ -0,0 +1,343 @@
+//! A small CPU path tracer over a snapshot of the scene's meshes and point lights, for work
+//! that cannot wait on the GPU or needs more than it renders: baking lightmaps and
+//! progressive stills. Surfaces are diffuse, with the albedo of their material.
+
+use std::f32::consts::PI;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{PrimitiveTopology, VertexAttributeValues},
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Rays start this far off surfaces, so they do not hit the one they leave.
+pub const RAY_OFFSET: f32 = 1e-3;
+const LEAF_SIZE: usize = 4;
+
+/// A world-space triangle the tracer can hit, with the colour it reflects.
+struct Triangle {
+    vertices: [Vec3; 3],
+    albedo: Vec3,
+}
+
+struct TraceLight {
+    position: Vec3,
+    /// Linear colour times luminous intensity, in candela.
+    intensity: Vec3,
+    range: f32,
+}
+
+/// A bounding volume hierarchy over the scene's triangles, flattened depth first: an inner
+/// node's left child follows it and `right` indexes the other.
+struct TraceNode {
+    min: Vec3,
+    max: Vec3,
+    /// For leaves, the range of [`Tracer::order`] they hold; `right` is unused.
+    start: u32,
+    count: u32,
+    right: u32,
+}
+
+pub struct Tracer {
+    triangles: Vec<Triangle>,
+    /// Triangle indices, grouped by leaf.
+    order: Vec<u32>,
+    nodes: Vec<TraceNode>,
+    lights: Vec<TraceLight>,
+}
+
+impl Tracer {
+    fn new(triangles: Vec<Triangle>, lights: Vec<TraceLight>) -> Self {
+        let mut tracer = Self {
+            order: (0..triangles.len() as u32).collect(),
+            triangles,
+            nodes: Vec::new(),
+            lights,
+        };
+        if !tracer.triangles.is_empty() {
+            let count = tracer.order.len();
+            tracer.build(0, count);
+        }
+        tracer
+    }
+
+    /// Adds the node over `order[start..end]` and its children, splitting at the median
+    /// along the widest axis of the triangles' centres.
+    fn build(&mut self, start: usize, end: usize) -> u32 {
+        let (mut min, mut max) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        let (mut low, mut high) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for &index in &self.order[start..end] {
+            let vertices = self.triangles[index as usize].vertices;
+            for vertex in vertices {
+                min = min.min(vertex);
+                max = max.max(vertex);
+            }
+            let center = (vertices[0] + vertices[1] + vertices[2]) / 3.0;
+            low = low.min(center);
+            high = high.max(center);
+        }
+        let node = self.nodes.len() as u32;
+        self.nodes.push(TraceNode {
+            min,
+            max,
+            start: start as u32,
+            count: (end - start) as u32,
+            right: 0,
+        });
+        if end - start <= LEAF_SIZE {
+            return node;
+        }
+
+        let extent = high - low;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let triangles = &self.triangles;
+        let middle = (start + end) / 2;
+        self.order[start..end].select_nth_unstable_by(middle - start, |a, b| {
+            let center = |index: u32| {
+                let vertices = triangles[index as usize].vertices;
+                vertices[0][axis] + vertices[1][axis] + vertices[2][axis]
+            };
+            center(*a).total_cmp(&center(*b))
+        });
+        self.nodes[node as usize].count = 0;
+        self.build(start, middle);
+        let right = self.build(middle, end);
+        self.nodes[node as usize].right = right;
+        node
+    }
+
+    /// The nearest triangle along the ray within `max_distance`, and how far it is.
+    fn trace(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<(f32, usize)> {
+        let inverse = direction.recip();
+        let mut nearest: Option<(f32, usize)> = None;
+        let mut stack = vec![0u32];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index as usize) else {
+                continue;
+            };
+            let limit = nearest.map_or(max_distance, |(distance, _)| distance);
+            if !box_hit(node.min, node.max, origin, inverse, limit) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.right);
+                stack.push(index + 1);
+                continue;
+            }
+            for &triangle in &self.order[node.start as usize..(node.start + node.count) as usize] {
+                let limit = nearest.map_or(max_distance, |(distance, _)| distance);
+                if let Some(distance) = triangle_hit(
+                    &self.triangles[triangle as usize].vertices,
+                    origin,
+                    direction,
+                )
+                .filter(|distance| *distance < limit)
+                {
+                    nearest = Some((distance, triangle as usize));
+                }
+            }
+        }
+        nearest
+    }
+
+    /// Light arriving straight from the point lights at `point`, facing `normal`, in lux.
+    fn direct(&self, point: Vec3, normal: Vec3) -> Vec3 {
+        let mut irradiance = Vec3::ZERO;
+        for light in &self.lights {
+            let to_light = light.position - point;
+            let distance_squared = to_light.length_squared();
+            let distance = distance_squared.sqrt();
+            let direction = to_light / distance;
+            let cosine = normal.dot(direction);
+            if cosine <= 0.0 || distance_squared >= light.range * light.range {
+                continue;
+            }
+            let origin = point + normal * RAY_OFFSET;
+            if self
+                .trace(origin, direction, distance - RAY_OFFSET * 2.0)
+                .is_some()
+            {
+                continue;
+            }
+            // Bevy's falloff: inverse square, smoothly reaching zero at the light's range.
+            let window = (1.0 - (distance_squared / (light.range * light.range)).powi(2))
+                .clamp(0.0, 1.0)
+                .powi(2);
+            irradiance += light.intensity * cosine * window / distance_squared.max(1e-4);
+        }
+        irradiance
+    }
+
+    /// Light reaching `origin` along the reverse of `direction` after bouncing off the scene
+    /// up to `bounces` times, per unit of the receiving surface's albedo. Rays leaving the
+    /// scene see `sky` all around. `None` when the first ray leaves it straight away.
+    pub fn radiance(
+        &self,
+        mut origin: Vec3,
+        mut direction: Vec3,
+        bounces: u32,
+        sky: Vec3,
+        rng: &mut StdRng,
+    ) -> Option<Vec3> {
+        let mut radiance = Vec3::ZERO;
+        let mut throughput = Vec3::ONE;
+        for bounce in 0..bounces {
+            let Some((distance, index)) = self.trace(origin, direction, f32::INFINITY) else {
+                if bounce == 0 {
+                    return None;
+                }
+                radiance += throughput * sky;
+                break;
+            };
+            let triangle = &self.triangles[index];
+            let [a, b, c] = triangle.vertices;
+            let mut normal = (b - a).cross(c - a).normalize_or_zero();
+            if normal.dot(direction) > 0.0 {
+                normal = -normal;
+            }
+            let point = origin + direction * distance;
+            throughput *= triangle.albedo;
+            radiance += throughput * self.direct(point, normal) / PI;
+            origin = point + normal * RAY_OFFSET;
+            direction = cosine_sample(normal, rng);
+        }
+        Some(radiance)
+    }
+}
+
+fn box_hit(min: Vec3, max: Vec3, origin: Vec3, inverse: Vec3, limit: f32) -> bool {
+    let t1 = (min - origin) * inverse;
+    let t2 = (max - origin) * inverse;
+    let near = t1.min(t2).max_element().max(0.0);
+    let far = t1.max(t2).min_element().min(limit);
+    near <= far
+}
+
+/// Möller–Trumbore, from either side.
+fn triangle_hit([a, b, c]: &[Vec3; 3], origin: Vec3, direction: Vec3) -> Option<f32> {
+    let edge1 = *b - *a;
+    let edge2 = *c - *a;
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < 1e-9 {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let offset = origin - *a;
+    let u = offset.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(edge1);
+    let v = direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse;
+    (distance > 0.0).then_some(distance)
+}
+
+/// A direction about `normal`, more likely the closer to it, as light falls on a diffuse
+/// surface; averaging what arrives along them needs no cosine weighting.
+pub fn cosine_sample(normal: Vec3, rng: &mut StdRng) -> Vec3 {
+    let angle = rng.gen::<f32>() * 2.0 * PI;
+    let radius_squared: f32 = rng.gen();
+    let radius = radius_squared.sqrt();
+    let (tangent, bitangent) = normal.any_orthonormal_pair();
+    (tangent * radius * angle.cos()
+        + bitangent * radius * angle.sin()
+        + normal * (1.0 - radius_squared).sqrt())
+    .normalize()
+}
+
+/// World-space corners of each triangle of `mesh` placed by `transform`, in index order.
+pub fn world_triangles(mesh: &Mesh, transform: &GlobalTransform) -> Option<Vec<[(Vec3, Vec3); 3]>> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+        _ => None,
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    let affine = transform.affine();
+    let normal_matrix = Mat3::from(affine.matrix3).inverse().transpose();
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|corners| {
+            let world = |index: usize| affine.transform_point3(Vec3::from(positions[index]));
+            let face = (world(corners[1]) - world(corners[0]))
+                .cross(world(corners[2]) - world(corners[0]))
+                .normalize_or_zero();
+            std::array::from_fn(|corner| {
+                let index = corners[corner];
+                let normal = normals.map_or(face, |normals| {
+                    (normal_matrix * Vec3::from(normals[index])).normalize_or_zero()
+                });
+                (world(index), normal)
+            })
+        })
+        .collect();
+    Some(triangles)
+}
+
+pub type SceneMesh<'a> = (
+    Entity,
+    &'a Handle<Mesh>,
+    &'a Handle<StandardMaterial>,
+    &'a GlobalTransform,
+);
+
+/// Copies the scene for a tracer that runs while the app goes on. `visit` sees the
+/// world-space corners of each mesh it takes, so callers can keep those of some entities.
+pub fn snapshot<'a>(
+    scene: impl IntoIterator<Item = SceneMesh<'a>>,
+    lights: impl IntoIterator<Item = (&'a PointLight, &'a GlobalTransform)>,
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+    mut visit: impl FnMut(Entity, Vec<[(Vec3, Vec3); 3]>),
+) -> Tracer {
+    let mut triangles = Vec::new();
+    for (entity, mesh, material, transform) in scene {
+        let Some(corners) = meshes
+            .get(mesh)
+            .and_then(|mesh| world_triangles(mesh, transform))
+        else {
+            continue;
+        };
+        let albedo = materials
+            .get(material)
+            .map_or(Vec3::splat(0.8), |material| {
+                let color = material.base_color.to_linear();
+                Vec3::new(color.red, color.green, color.blue)
+            });
+        triangles.extend(corners.iter().map(|corners| Triangle {
+            vertices: corners.map(|(position, _)| position),
+            albedo,
+        }));
+        visit(entity, corners);
+    }
+    let lights = lights
+        .into_iter()
+        .map(|(light, transform)| {
+            let color = light.color.to_linear();
+            TraceLight {
+                position: transform.translation(),
+                intensity: Vec3::new(color.red, color.green, color.blue) * light.intensity
+                    / (4.0 * PI),
+                range: light.range,
+            }
+        })
+        .collect();
+    Tracer::new(triangles, lights)
+}
@@ -0,0 +1,194 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{mesh::VertexAttributeValues, primitives::Aabb, view::VisibilitySystems},
+    transform::TransformSystem,
+};
+
+use crate::{
+    bvh::{Bounds, Bvh},
+    viewport::Viewport,
+    ViewportCamera,
+};
+
+/// Keeps the [`PickingBvh`] in step with the meshes in the world.
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickingBvh>().add_systems(
+            PostUpdate,
+            update_picking_bvh_system
+                .after(TransformSystem::TransformPropagate)
+                .after(VisibilitySystems::CalculateBounds),
+        );
+    }
+}
+
+/// Boxes of every mesh, so a ray only tests the meshes along it.
+#[derive(Default, Resource)]
+pub struct PickingBvh(Bvh);
+
+#[allow(clippy::type_complexity)]
+fn update_picking_bvh_system(
+    mut bvh: ResMut<PickingBvh>,
+    meshes: Query<(Entity, &GlobalTransform, &Aabb), With<Handle<Mesh>>>,
+    moved: Query<
+        (Entity, &GlobalTransform, &Aabb),
+        (
+            With<Handle<Mesh>>,
+            Or<(Changed<GlobalTransform>, Changed<Aabb>)>,
+        ),
+    >,
+    mut removed_aabbs: RemovedComponents<Aabb>,
+    mut removed_meshes: RemovedComponents<Handle<Mesh>>,
+) {
+    let removed = removed_aabbs.read().chain(removed_meshes.read()).count() > 0;
+    let added = moved.iter().any(|(entity, ..)| !bvh.0.contains(entity));
+    if removed || added {
+        bvh.0 = Bvh::build(
+            meshes
+                .iter()
+                .map(|(entity, transform, aabb)| (entity, Bounds::from_aabb(aabb, transform)))
+                .collect(),
+        );
+    } else if !moved.is_empty() {
+        for (entity, transform, aabb) in &moved {
+            bvh.0.set_bounds(entity, Bounds::from_aabb(aabb, transform));
+        }
+        bvh.0.refit();
+    }
+}
+
+/// The closest mesh under a ray.
+#[derive(Clone, Copy, Debug)]
+pub struct PickHit {
+    pub entity: Entity,
+    pub distance: f32,
+    /// World-space hit point and surface normal.
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+/// Intersects `ray` with an oriented box given by a mesh's local `aabb` and its transform.
+/// Returns the distance along the ray and the world-space surface normal.
+pub fn ray_obb(ray: Ray3d, aabb: &Aabb, transform: &GlobalTransform) -> Option<(f32, Vec3)> {
+    let to_local = transform.affine().inverse();
+    let origin = to_local.transform_point3(ray.origin) - Vec3::from(aabb.center);
+    // Not normalized, so the ray parameter stays the world-space distance.
+    let direction = to_local.transform_vector3(*ray.direction);
+    let half = Vec3::from(aabb.half_extents);
+
+    let inv = direction.recip();
+    let t1 = (-half - origin) * inv;
+    let t2 = (half - origin) * inv;
+    let t_near = t1.min(t2).max_element();
+    let t_far = t1.max(t2).min_element();
+    if t_near > t_far || t_far < 0.0 {
+        return None;
+    }
+    let t = if t_near >= 0.0 { t_near } else { t_far };
+
+    // The face hit is the axis where the local hit point touches the box boundary.
+    let local = (origin + direction * t) / half.max(Vec3::splat(f32::EPSILON));
+    let abs = local.abs();
+    let local_normal = if abs.x >= abs.y && abs.x >= abs.z {
+        Vec3::X * local.x.signum()
+    } else if abs.y >= abs.z {
+        Vec3::Y * local.y.signum()
+    } else {
+        Vec3::Z * local.z.signum()
+    };
+    let normal = (to_local.matrix3.transpose() * local_normal).normalize_or_zero();
+    Some((t, normal))
+}
+
+/// Intersects `ray` with the triangles of `mesh` placed by `transform`, returning the distance
+/// to the nearest hit. Exact, unlike the box test picking uses, and so much slower.
+pub fn ray_mesh(ray: Ray3d, mesh: &Mesh, transform: &GlobalTransform) -> Option<f32> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let to_local = transform.affine().inverse();
+    let origin = to_local.transform_point3(ray.origin);
+    let direction = to_local.transform_vector3(*ray.direction);
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    // Möller–Trumbore; `t` stays in world units because `direction` is not renormalized.
+    indices
+        .chunks_exact(3)
+        .filter_map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i]]));
+            let (edge1, edge2) = (b - a, c - a);
+            let p = direction.cross(edge2);
+            let det = edge1.dot(p);
+            if det.abs() < f32::EPSILON {
+                return None;
+            }
+            let offset = origin - a;
+            let u = offset.dot(p) / det;
+            let q = offset.cross(edge1);
+            let v = direction.dot(q) / det;
+            let t = edge2.dot(q) / det;
+            (u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t >= 0.0).then_some(t)
+        })
+        .min_by(f32::total_cmp)
+}
+
+/// Ray casting against every visible mesh, driven from the egui viewport.
+#[derive(SystemParam)]
+pub struct Picking<'w, 's> {
+    viewport: Res<'w, Viewport>,
+    bvh: Res<'w, PickingBvh>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<ViewportCamera>>,
+    meshes: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static GlobalTransform,
+            &'static Aabb,
+            &'static ViewVisibility,
+        ),
+        With<Handle<Mesh>>,
+    >,
+}
+
+impl Picking<'_, '_> {
+    /// World ray through the viewport pixel under the pointer.
+    pub fn pointer_ray(&self) -> Option<Ray3d> {
+        self.ray_through(self.viewport.hovered_pixel?.as_vec2() + 0.5)
+    }
+
+    /// World ray through a position in viewport image pixels.
+    pub fn ray_through(&self, pixel: Vec2) -> Option<Ray3d> {
+        let (camera, camera_transform) = self.cameras.get_single().ok()?;
+        camera.viewport_to_world(camera_transform, pixel)
+    }
+
+    pub fn cast(&self, ray: Ray3d, ignore: &[Entity]) -> Option<PickHit> {
+        self.bvh.0.cast(ray, |entity| {
+            let (_, transform, aabb, visibility) = self.meshes.get(entity).ok()?;
+            if !visibility.get() || ignore.contains(&entity) {
+                return None;
+            }
+            let (distance, normal) = ray_obb(ray, aabb, transform)?;
+            let hit = PickHit {
+                entity,
+                distance,
+                point: ray.get_point(distance),
+                normal,
+            };
+            Some((hit.distance, hit))
+        })
+    }
+
+    pub fn pick_pointer(&self, ignore: &[Entity]) -> Option<PickHit> {
+        self.cast(self.pointer_ray()?, ignore)
+    }
+}
@@ -0,0 +1,219 @@
+use std::f32::consts::TAU;
+
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::{frame_bounds, world_bounds},
+    input::InputRouting,
+    keybindings::{Action, Shortcuts},
+    picking::Picking,
+    scene::SpawnQueue,
+    selection::Selection,
+    settings::Settings,
+    tour::{RegisterTourExt, TourStep},
+    transform_gizmo::GizmoMode,
+    viewport::{Viewport, ViewportTool},
+    ViewportCamera,
+};
+
+const RADIUS: f32 = 90.0;
+/// Pointer distance from the centre below which nothing is highlighted.
+const DEAD_ZONE: f32 = 20.0;
+
+/// A radial menu of common viewport actions, opened at the cursor with Q. Point towards an
+/// item and release Q, or click it.
+pub struct PieMenuPlugin;
+
+impl Plugin for PieMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PieMenu>()
+            .add_systems(Update, pie_menu_system.after(crate::UiSet::Central))
+            .add_tour_step(TourStep::new(
+                "viewport",
+                "Quick menu",
+                "Hold Q over the viewport for a radial menu of tools; point at one and release.",
+            ));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PieAction {
+    Select,
+    Place,
+    Move,
+    Rotate,
+    Scale,
+    FrameSelection,
+    AddCube,
+}
+
+impl PieAction {
+    /// Clockwise from the top.
+    const ALL: [PieAction; 7] = [
+        PieAction::FrameSelection,
+        PieAction::Move,
+        PieAction::Rotate,
+        PieAction::Scale,
+        PieAction::AddCube,
+        PieAction::Place,
+        PieAction::Select,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PieAction::Select => "Select",
+            PieAction::Place => "Place",
+            PieAction::Move => "Move",
+            PieAction::Rotate => "Rotate",
+            PieAction::Scale => "Scale",
+            PieAction::FrameSelection => "Frame selection",
+            PieAction::AddCube => "Add cube",
+        }
+    }
+
+    /// Unit direction of the item from the centre, in egui's y-down space.
+    fn direction(index: usize) -> egui::Vec2 {
+        let angle = index as f32 / Self::ALL.len() as f32 * TAU;
+        egui::vec2(angle.sin(), -angle.cos())
+    }
+}
+
+#[derive(Default, Resource)]
+struct PieMenu {
+    /// Centre of the open menu, in egui points.
+    center: Option<egui::Pos2>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pie_menu_system(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<PieMenu>,
+    shortcuts: Shortcuts,
+    keys: Res<ButtonInput<KeyCode>>,
+    routing: Res<InputRouting>,
+    viewport: Res<Viewport>,
+    mut tool: ResMut<ViewportTool>,
+    mut gizmo_mode: ResMut<GizmoMode>,
+    mut spawns: ResMut<SpawnQueue>,
+    settings: Res<Settings>,
+    selection: Res<Selection>,
+    picking: Picking,
+    bounds: Query<(&GlobalTransform, &Aabb)>,
+    mut cameras: Query<(&mut Transform, &Projection), With<ViewportCamera>>,
+) {
+    if menu.center.is_none() {
+        if routing.keyboard_is_free() && shortcuts.just_pressed(Action::PieMenu) {
+            menu.center = viewport.pointer;
+        }
+        return;
+    }
+    let Some(center) = menu.center else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.center = None;
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    let pointer = ctx.input(|input| input.pointer.hover_pos());
+    let hovered = pointer
+        .map(|pointer| pointer - center)
+        .filter(|offset| offset.length() > DEAD_ZONE)
+        .map(|offset| {
+            let angle = offset.x.atan2(-offset.y).rem_euclid(TAU);
+            let count = PieAction::ALL.len();
+            (angle / TAU * count as f32).round() as usize % count
+        });
+
+    let mut chosen = None;
+    let mut dismissed = false;
+    egui::Area::new(egui::Id::new("pie_menu"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(center - egui::Vec2::splat(RADIUS + 60.0))
+        .show(ctx, |ui| {
+            let (rect, response) = ui.allocate_exact_size(
+                egui::Vec2::splat((RADIUS + 60.0) * 2.0),
+                egui::Sense::click(),
+            );
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals();
+            painter.circle_stroke(center, DEAD_ZONE, visuals.widgets.noninteractive.fg_stroke);
+            for (index, action) in PieAction::ALL.into_iter().enumerate() {
+                let position = center + PieAction::direction(index) * RADIUS;
+                let widget = if hovered == Some(index) {
+                    &visuals.widgets.hovered
+                } else {
+                    &visuals.widgets.inactive
+                };
+                let galley = painter.layout_no_wrap(
+                    action.label().to_owned(),
+                    egui::FontId::proportional(14.0),
+                    widget.fg_stroke.color,
+                );
+                let label = egui::Rect::from_center_size(position, galley.size())
+                    .expand2(egui::vec2(8.0, 4.0));
+                painter.rect(label, 4.0, widget.bg_fill, widget.bg_stroke);
+                painter.galley(
+                    label.center() - galley.size() / 2.0,
+                    galley,
+                    widget.fg_stroke.color,
+                );
+            }
+            if response.clicked() {
+                match hovered {
+                    Some(index) => chosen = Some(PieAction::ALL[index]),
+                    None => dismissed = true,
+                }
+            }
+        });
+    // Releasing the key picks the item pointed at; a quick tap leaves the menu open for a click.
+    if chosen.is_none() && keys.just_released(KeyCode::KeyQ) {
+        chosen = hovered.map(|index| PieAction::ALL[index]);
+    }
+    if dismissed || chosen.is_some() {
+        menu.center = None;
+    }
+
+    match chosen {
+        None => {}
+        Some(PieAction::Select) => *tool = ViewportTool::Select,
+        Some(PieAction::Place) => *tool = ViewportTool::PlaceOnSurface,
+        Some(PieAction::Move) => *gizmo_mode = GizmoMode::Translate,
+        Some(PieAction::Rotate) => *gizmo_mode = GizmoMode::Rotate,
+        Some(PieAction::Scale) => *gizmo_mode = GizmoMode::Scale,
+        Some(PieAction::FrameSelection) => {
+            let boxes: Vec<(Vec3, Vec3)> = selection
+                .entities
+                .iter()
+                .filter_map(|entity| bounds.get(*entity).ok())
+                .map(|(transform, aabb)| world_bounds(transform, aabb))
+                .collect();
+            let Some((min, max)) = boxes
+                .into_iter()
+                .reduce(|(a0, a1), (b0, b1)| (a0.min(b0), a1.max(b1)))
+            else {
+                return;
+            };
+            if let Ok((mut transform, projection)) = cameras.get_single_mut() {
+                frame_bounds(&mut transform, projection, min, max);
+            }
+        }
+        Some(PieAction::AddCube) => {
+            // On the surface under the menu, or a little in front of the camera.
+            let ray = picking.ray_through(viewport.pixel_at(center).as_vec2() + 0.5);
+            let position = ray.map(|ray| {
+                picking.cast(ray, &[]).map_or(ray.get_point(10.0), |hit| {
+                    hit.point + hit.normal * settings.spawn.cube_size * 0.5
+                })
+            });
+            let [r, g, b] = settings.spawn.color;
+            spawns.push(
+                Transform::from_translation(position.unwrap_or(Vec3::ZERO))
+                    .with_scale(Vec3::splat(settings.spawn.cube_size)),
+                Color::srgb(r, g, b),
+            );
+        }
+    }
+}
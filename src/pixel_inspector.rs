@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    cursor::{CursorKind, ViewportCursor},
+    keybindings::{Action, Keybindings, Shortcuts},
+    readback::{ReadbackComplete, ReadbackRequests, ReadbackSource},
+    viewport::Viewport,
+    ViewImage,
+};
+
+/// Pixels on each side of the inspected one shown in the loupe.
+const RADIUS: u32 = 5;
+const CELL: f32 = 10.0;
+
+/// Shows a magnified loupe with exact pixel values while [`Action::InspectPixels`] (Alt) is
+/// held over the viewport. [`Action::ToggleInspectorSource`] (Alt+L) switches between the
+/// displayed output and the linear pre-tonemap values.
+pub struct PixelInspectorPlugin;
+
+impl Plugin for PixelInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PixelInspector>().add_systems(
+            Update,
+            (
+                request_inspector_readback_system,
+                receive_inspector_readback_system,
+                pixel_inspector_system,
+            )
+                .chain()
+                .after(crate::UiSet::Central),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+struct PixelInspector {
+    active: bool,
+    source: ReadbackSource,
+    /// The most recent readback around the cursor from `source`.
+    region: Option<ReadbackComplete>,
+}
+
+fn request_inspector_readback_system(
+    shortcuts: Shortcuts,
+    viewport: Res<Viewport>,
+    view_image: Res<ViewImage>,
+    mut inspector: ResMut<PixelInspector>,
+    mut requests: ResMut<ReadbackRequests>,
+    mut cursor: ResMut<ViewportCursor>,
+) {
+    inspector.active = shortcuts.pressed(Action::InspectPixels) && viewport.hovered_pixel.is_some();
+    if inspector.active && shortcuts.just_pressed(Action::ToggleInspectorSource) {
+        inspector.source = match inspector.source {
+            ReadbackSource::Output => ReadbackSource::Linear,
+            ReadbackSource::Linear => ReadbackSource::Output,
+        };
+        inspector.region = None;
+    }
+    if let (true, Some(pixel)) = (inspector.active, viewport.hovered_pixel) {
+        cursor.request(CursorKind::Eyedropper);
+        let min = pixel.saturating_sub(UVec2::splat(RADIUS));
+        let max = pixel + UVec2::splat(RADIUS + 1);
+        requests.request_from(
+            &view_image,
+            Some(URect::from_corners(min, max)),
+            inspector.source,
+        );
+    }
+}
+
+fn receive_inspector_readback_system(
+    mut events: EventReader<ReadbackComplete>,
+    view_image: Res<ViewImage>,
+    mut inspector: ResMut<PixelInspector>,
+) {
+    let source = inspector.source;
+    if let Some(readback) = events
+        .read()
+        .filter(|readback| {
+            readback.image == view_image.id()
+                && readback.region.is_some()
+                && readback.source == source
+        })
+        .last()
+    {
+        inspector.region = Some(readback.clone());
+    }
+}
+
+fn pixel_inspector_system(
+    mut contexts: EguiContexts,
+    viewport: Res<Viewport>,
+    inspector: Res<PixelInspector>,
+    keybindings: Res<Keybindings>,
+) {
+    let (true, Some(pixel)) = (inspector.active, viewport.hovered_pixel) else {
+        return;
+    };
+    let region = inspector.region.as_ref();
+
+    let index = |p: IVec2| -> Option<usize> {
+        let region = region?;
+        let (origin, size) = (region.origin.as_ivec2(), region.size.as_ivec2());
+        let local = p - origin;
+        if local.x < 0 || local.y < 0 || local.x >= size.x || local.y >= size.y {
+            return None;
+        }
+        Some((local.y * size.x + local.x) as usize)
+    };
+    let sample = |p: IVec2| -> Option<[u8; 4]> {
+        let i = index(p)? * 4;
+        region?
+            .data
+            .get(i..i + 4)
+            .map(|rgba| [rgba[0], rgba[1], rgba[2], rgba[3]])
+    };
+    let hdr = index(pixel.as_ivec2()).and_then(|i| region?.linear.as_ref()?.get(i).copied());
+
+    egui::show_tooltip_at_pointer(
+        contexts.ctx_mut(),
+        egui::LayerId::background(),
+        egui::Id::new("pixel_inspector"),
+        |ui| {
+            let side = (RADIUS * 2 + 1) as f32 * CELL;
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            let radius = RADIUS as i32;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let cell = egui::Rect::from_min_size(
+                        rect.min + egui::vec2((dx + radius) as f32, (dy + radius) as f32) * CELL,
+                        egui::vec2(CELL, CELL),
+                    );
+                    let color = match sample(pixel.as_ivec2() + IVec2::new(dx, dy)) {
+                        Some([r, g, b, _]) => egui::Color32::from_rgb(r, g, b),
+                        None => egui::Color32::from_gray(40),
+                    };
+                    painter.rect_filled(cell, 0.0, color);
+                }
+            }
+            let center = egui::Rect::from_min_size(
+                rect.min + egui::vec2(RADIUS as f32, RADIUS as f32) * CELL,
+                egui::vec2(CELL, CELL),
+            );
+            painter.rect_stroke(center, 0.0, egui::Stroke::new(1.5, egui::Color32::WHITE));
+
+            ui.monospace(format!("pixel {}, {}", pixel.x, pixel.y));
+            ui.weak(format!(
+                "{} · {} to switch",
+                inspector.source.label(),
+                keybindings.label(Action::ToggleInspectorSource)
+            ));
+            if let Some([r, g, b, a]) = hdr {
+                ui.monospace(format!("hdr    {r:.3} {g:.3} {b:.3} {a:.3}"));
+            } else if inspector.source == ReadbackSource::Linear && region.is_some() {
+                ui.weak("HDR is off: values are tonemapped in the shaders");
+            }
+            match sample(pixel.as_ivec2()) {
+                Some([r, g, b, a]) => {
+                    let linear = Color::srgba_u8(r, g, b, a).to_linear();
+                    ui.monospace(format!("rgba8  {r:>3} {g:>3} {b:>3} {a:>3}"));
+                    ui.monospace(format!(
+                        "srgb   {:.3} {:.3} {:.3} {:.3}",
+                        r as f32 / 255.0,
+                        g as f32 / 255.0,
+                        b as f32 / 255.0,
+                        a as f32 / 255.0
+                    ));
+                    ui.monospace(format!(
+                        "linear {:.3} {:.3} {:.3} {:.3}",
+                        linear.red, linear.green, linear.blue, linear.alpha
+                    ));
+                }
+                None => {
+                    ui.weak("Reading back…");
+                }
+            }
+        },
+    );
+}
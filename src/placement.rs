@@ -0,0 +1,170 @@
+use bevy::{pbr::NotShadowCaster, prelude::*};
+
+use crate::{
+    input::{InputOwner, InputRouting},
+    palette::ColorPalette,
+    picking::Picking,
+    scene,
+    selection::Selection,
+    settings::Settings,
+    viewport::{Viewport, ViewportTool},
+    Helper, Static,
+};
+
+/// The "place on surface" tool: a ghost cube follows the surface under the pointer and a click
+/// drops a real cube there. Placed cubes are static, so they keep resting against the surface
+/// instead of spinning through it.
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Placement>().add_systems(
+            Update,
+            (placement_ghost_system, place_on_click_system)
+                .chain()
+                .after(crate::UiSet::Central),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct Placement {
+    /// Rotate placed cubes so their local +Z points along the surface normal.
+    pub align_to_normal: bool,
+    ghost: Option<(Entity, Handle<StandardMaterial>)>,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self {
+            align_to_normal: true,
+            ghost: None,
+        }
+    }
+}
+
+/// Where a cube of `size` would rest against the surface at `point` with `normal`.
+fn resting_transform(point: Vec3, normal: Vec3, size: f32, align_to_normal: bool) -> Transform {
+    let rotation = if align_to_normal {
+        Quat::from_rotation_arc(Vec3::Z, normal)
+    } else {
+        Quat::IDENTITY
+    };
+    Transform::from_translation(point + normal * size * 0.5)
+        .with_rotation(rotation)
+        .with_scale(Vec3::splat(size))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn placement_ghost_system(
+    mut commands: Commands,
+    mut placement: ResMut<Placement>,
+    tool: Res<ViewportTool>,
+    settings: Res<Settings>,
+    picking: Picking,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility)>,
+) {
+    if *tool != ViewportTool::PlaceOnSurface {
+        if let Some((ghost, _)) = placement.ghost.take() {
+            commands.entity(ghost).despawn_recursive();
+        }
+        return;
+    }
+
+    let [r, g, b] = settings.spawn.color;
+    let (ghost, material) = placement.ghost.get_or_insert_with(|| {
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgba(r, g, b, 0.4),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+        let ghost = commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+                material: material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .insert((NotShadowCaster, Helper))
+            .id();
+        (ghost, material)
+    });
+    let tint = Color::srgba(r, g, b, 0.4);
+    if materials
+        .get(&*material)
+        .is_some_and(|m| m.base_color != tint)
+    {
+        if let Some(material) = materials.get_mut(&*material) {
+            material.base_color = tint;
+        }
+    }
+
+    // Freshly spawned ghosts have no components until the commands are applied.
+    let Ok((mut transform, mut visibility)) = ghosts.get_mut(*ghost) else {
+        return;
+    };
+    // With nothing under the pointer, rest on the z = 0 plane facing the camera.
+    let surface = picking
+        .pick_pointer(&[*ghost])
+        .map(|hit| (hit.point, hit.normal))
+        .or_else(|| {
+            let ray = picking.pointer_ray()?;
+            let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z))?;
+            Some((ray.get_point(distance), Vec3::Z))
+        });
+    match surface {
+        Some((point, normal)) => {
+            *transform = resting_transform(
+                point,
+                normal,
+                settings.spawn.cube_size,
+                placement.align_to_normal,
+            );
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn place_on_click_system(
+    mut commands: Commands,
+    viewport: Res<Viewport>,
+    tool: Res<ViewportTool>,
+    routing: Res<InputRouting>,
+    placement: Res<Placement>,
+    settings: Res<Settings>,
+    mut palette: ResMut<ColorPalette>,
+    mut selection: ResMut<Selection>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ghosts: Query<(&Transform, &Visibility)>,
+) {
+    if !viewport.clicked
+        || *tool != ViewportTool::PlaceOnSurface
+        || routing.pointer != InputOwner::Tool
+    {
+        return;
+    }
+    let Some(Ok((transform, visibility))) = placement.ghost.as_ref().map(|(g, _)| ghosts.get(*g))
+    else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let [r, g, b] = settings.spawn.color;
+    let color = palette.next_spawn_color().unwrap_or(Color::srgb(r, g, b));
+    let entity = scene::spawn_cube(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        *transform,
+        color,
+    );
+    commands.entity(entity).insert(Static);
+    selection.select(entity);
+}
@@ -0,0 +1,109 @@
+use bevy::{ecs::world::Command, prelude::*};
+
+use crate::RenderCube;
+
+/// Free cubes kept around at most; releases beyond this despawn as before.
+const POOL_CAPACITY: usize = 20_000;
+
+/// Recycles despawned cubes so spawn/despawn-heavy workflows (stress tests, particle-like
+/// scripts) reuse entities and materials instead of allocating new ones every cycle.
+pub struct PoolPlugin;
+
+impl Plugin for PoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CubePool>();
+    }
+}
+
+/// Marks a hidden cube waiting in the [`CubePool`] to be reused.
+#[derive(Component)]
+pub struct Pooled;
+
+/// What a pooled cube keeps; everything else is stripped when it is released.
+type PooledComponents = (
+    Handle<Mesh>,
+    Handle<StandardMaterial>,
+    Transform,
+    GlobalTransform,
+    Visibility,
+    InheritedVisibility,
+    ViewVisibility,
+);
+
+#[derive(Default, Resource)]
+pub struct CubePool {
+    free: Vec<Entity>,
+    /// Cubes spawned as new entities because the pool was empty.
+    pub allocated: usize,
+    /// Cubes spawned by reusing a pooled entity.
+    pub reused: usize,
+    /// Cubes handed back to the pool.
+    pub released: usize,
+}
+
+impl CubePool {
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Takes a pooled cube and its material, skipping entries despawned since their release.
+    pub fn acquire(
+        &mut self,
+        pooled: &Query<&Handle<StandardMaterial>, With<Pooled>>,
+    ) -> Option<(Entity, Handle<StandardMaterial>)> {
+        while let Some(entity) = self.free.pop() {
+            if let Ok(material) = pooled.get(entity) {
+                self.reused += 1;
+                return Some((entity, material.clone()));
+            }
+        }
+        None
+    }
+
+    /// Despawns every pooled cube, returning their entities and materials to Bevy.
+    pub fn drain(&mut self, commands: &mut Commands) {
+        for entity in self.free.drain(..) {
+            if let Some(entity) = commands.get_entity(entity) {
+                entity.despawn_recursive();
+            }
+        }
+    }
+}
+
+pub trait ReleaseCubeExt {
+    /// Hands a cube back to the [`CubePool`] in place of `despawn_recursive`.
+    fn release_cube(&mut self, entity: Entity);
+}
+
+impl ReleaseCubeExt for Commands<'_, '_> {
+    fn release_cube(&mut self, entity: Entity) {
+        self.add(ReleaseCube(entity));
+    }
+}
+
+struct ReleaseCube(Entity);
+
+impl Command for ReleaseCube {
+    fn apply(self, world: &mut World) {
+        let Some(entity) = world.get_entity(self.0) else {
+            return;
+        };
+        if entity.contains::<Pooled>() {
+            return;
+        }
+        let poolable = entity.contains::<RenderCube>()
+            && world.resource::<CubePool>().free.len() < POOL_CAPACITY;
+        let mut entity = world.entity_mut(self.0);
+        if !poolable {
+            entity.despawn_recursive();
+            return;
+        }
+        entity.despawn_descendants();
+        entity.remove_parent();
+        entity.retain::<PooledComponents>();
+        entity.insert((Visibility::Hidden, Pooled));
+        let mut pool = world.resource_mut::<CubePool>();
+        pool.free.push(self.0);
+        pool.released += 1;
+    }
+}
@@ -0,0 +1,246 @@
+use bevy::{
+    pbr::{VolumetricFogSettings, VolumetricLight},
+    prelude::*,
+};
+use bevy_egui::egui;
+
+use crate::{
+    day_night::DayNightWindow,
+    numeric::slider,
+    outline::EdgeOutline,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    ViewportCamera,
+};
+
+/// The Post FX window: HDR rendering, edge outlines and volumetric fog with light shafts.
+pub struct PostFxPlugin;
+
+impl Plugin for PostFxPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<PostFxWindow>().add_systems(
+            Update,
+            (
+                post_fx_window_system.in_set(PostFxWindow::ui_set()),
+                volumetric_lights_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct PostFxWindow {
+    pub is_open: bool,
+}
+
+impl Panel for PostFxWindow {
+    const TITLE: &'static str = "Post FX";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn post_fx_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<PostFxWindow>,
+    mut day_night: ResMut<DayNightWindow>,
+    mut commands: Commands,
+    mut cameras: Query<
+        (
+            Entity,
+            &mut Camera,
+            Option<&EdgeOutline>,
+            Option<&VolumetricFogSettings>,
+        ),
+        With<ViewportCamera>,
+    >,
+    lights: Query<&DirectionalLight>,
+) {
+    if !window.is_open {
+        return;
+    }
+
+    egui::Window::new("Post FX")
+        .open(&mut window.is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<PostFxWindow>(), |ui| {
+            let Ok((entity, mut camera, outline, fog)) = cameras.get_single_mut() else {
+                return;
+            };
+            let mut hdr = camera.hdr;
+            if ui
+                .checkbox(&mut hdr, "HDR")
+                .on_hover_text("Render in floating point and tonemap into the viewport image")
+                .changed()
+            {
+                camera.hdr = hdr;
+            }
+
+            ui.separator();
+            let mut outlined = outline.is_some();
+            if ui
+                .checkbox(&mut outlined, "Edge outlines")
+                .on_hover_text("Lines where depth or surface direction jumps, for illustrations")
+                .changed()
+            {
+                if outlined {
+                    commands.entity(entity).insert(EdgeOutline::default());
+                } else {
+                    commands.entity(entity).remove::<EdgeOutline>();
+                }
+            }
+            if let Some(outline) = outline {
+                let mut edited = *outline;
+                egui::Grid::new("edge_outline_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Thickness");
+                        ui.add(
+                            slider(&mut edited.thickness, 1.0..=6.0)
+                                .step_by(1.0)
+                                .suffix(" px"),
+                        );
+                        ui.end_row();
+                        ui.label("Depth threshold");
+                        ui.add(slider(&mut edited.depth_threshold, 0.005..=1.0).logarithmic(true))
+                            .on_hover_text(
+                                "Relative change in distance between neighbouring pixels",
+                            );
+                        ui.end_row();
+                        ui.label("Crease threshold");
+                        ui.add(slider(&mut edited.normal_threshold, 0.02..=2.0).logarithmic(true))
+                            .on_hover_text(
+                                "How sharply surfaces must fold; lower finds softer creases",
+                            );
+                        ui.end_row();
+                        ui.label("Line colour");
+                        let linear = edited.color;
+                        let mut rgba = Color::linear_rgba(linear.x, linear.y, linear.z, linear.w)
+                            .to_srgba()
+                            .to_f32_array();
+                        if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                            let [r, g, b, a] = rgba;
+                            edited.color = Color::srgba(r, g, b, a).to_linear().to_vec4();
+                        }
+                        ui.end_row();
+                    });
+                if edited != *outline {
+                    commands.entity(entity).insert(edited);
+                }
+            }
+
+            ui.separator();
+            let mut enabled = fog.is_some();
+            if ui
+                .checkbox(&mut enabled, "Volumetric fog and light shafts")
+                .on_hover_text("Ray-marched through the directional lights' shadow maps; slow")
+                .changed()
+            {
+                if enabled {
+                    commands.entity(entity).insert(VolumetricFogSettings {
+                        ambient_intensity: 0.0,
+                        ..default()
+                    });
+                } else {
+                    commands.entity(entity).remove::<VolumetricFogSettings>();
+                }
+            }
+            let shadowed = lights.iter().filter(|light| light.shadows_enabled).count();
+            if enabled && shadowed == 0 {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Shafts need a directional light with shadows.",
+                    );
+                    if ui.button("Turn on the sun").clicked() {
+                        day_night.enabled = true;
+                    }
+                });
+            }
+            let Some(fog) = fog else {
+                return;
+            };
+
+            let mut edited = *fog;
+            let mut changed = false;
+            egui::Grid::new("volumetric_fog_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Density");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut edited.density, 0.0..=1.0).logarithmic(true))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Scattering");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut edited.scattering, 0.0..=1.0))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Absorption");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut edited.absorption, 0.0..=1.0))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Asymmetry");
+                    changed |= ui
+                        .add(egui::Slider::new(
+                            &mut edited.scattering_asymmetry,
+                            -0.95..=0.95,
+                        ))
+                        .on_hover_text("Positive values scatter light forwards, towards the camera")
+                        .changed();
+                    ui.end_row();
+                    ui.label("Light intensity");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut edited.light_intensity, 0.0..=10.0))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Fog colour");
+                    let mut rgb = edited.fog_color.to_srgba().to_f32_array_no_alpha();
+                    if ui.color_edit_button_rgb(&mut rgb).changed() {
+                        edited.fog_color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+                        changed = true;
+                    }
+                    ui.end_row();
+                    ui.label("Steps");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut edited.step_count, 8..=256))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Max depth");
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut edited.max_depth, 1.0..=500.0)
+                                .logarithmic(true)
+                                .suffix(" m"),
+                        )
+                        .changed();
+                    ui.end_row();
+                });
+            if changed {
+                commands.entity(entity).insert(edited);
+            }
+            if !camera.hdr {
+                ui.weak("Turn on HDR to keep bright shafts from clipping.");
+            }
+        });
+}
+
+/// Makes every shadowed directional light volumetric while the fog is on.
+fn volumetric_lights_system(
+    mut commands: Commands,
+    cameras: Query<Has<VolumetricFogSettings>, With<ViewportCamera>>,
+    lights: Query<(Entity, &DirectionalLight, Has<VolumetricLight>)>,
+) {
+    let fog = cameras.get_single().unwrap_or(false);
+    for (entity, light, volumetric) in &lights {
+        let wanted = fog && light.shadows_enabled;
+        if wanted && !volumetric {
+            commands.entity(entity).insert(VolumetricLight);
+        } else if !wanted && volumetric {
+            commands.entity(entity).remove::<VolumetricLight>();
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    keybindings::KeyChord,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+};
+
+/// Seconds a point of the mouse trail lasts.
+const TRAIL_SECONDS: f64 = 0.5;
+/// Seconds a click's ripple takes to spread and fade.
+const RIPPLE_SECONDS: f64 = 0.45;
+const RIPPLE_RADIUS: f32 = 28.0;
+/// Seconds a pressed key stays on screen after its last press.
+const KEY_SECONDS: f64 = 2.0;
+const MAX_KEYS: usize = 5;
+
+/// Presenter mode, toggled from the View menu: shows the mouse, its clicks and the keys
+/// pressed on top of everything, so recordings and tutorials can be followed. A fading trail
+/// follows the pointer, each click leaves a ripple coloured by its button, and key chords are
+/// listed along the bottom of the window as they are typed.
+pub struct PresenterPlugin;
+
+impl Plugin for PresenterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Presenter>()
+            .add_systems(Update, presenter_system.after(crate::UiSet::Central))
+            .add_menu_item(
+                MenuItem::new(Menu::View, "Presenter Mode", |world| {
+                    let mut presenter = world.resource_mut::<Presenter>();
+                    presenter.enabled ^= true;
+                    if !presenter.enabled {
+                        *presenter = Presenter::default();
+                    }
+                })
+                .separator_before(),
+            );
+    }
+}
+
+struct PressedKey {
+    label: String,
+    /// Presses in a row, shown as "×3".
+    count: u32,
+    time: f64,
+}
+
+#[derive(Default, Resource)]
+struct Presenter {
+    enabled: bool,
+    trail: VecDeque<(egui::Pos2, f64)>,
+    ripples: Vec<(egui::Pos2, egui::PointerButton, f64)>,
+    keys: VecDeque<PressedKey>,
+}
+
+impl Presenter {
+    /// Adds a chord, or counts a repeat of the latest one while it is still shown.
+    fn press(&mut self, label: String, time: f64) {
+        match self.keys.back_mut() {
+            Some(last) if last.label == label => {
+                last.count += 1;
+                last.time = time;
+            }
+            _ => {
+                self.keys.push_back(PressedKey {
+                    label,
+                    count: 1,
+                    time,
+                });
+                if self.keys.len() > MAX_KEYS {
+                    self.keys.pop_front();
+                }
+            }
+        }
+    }
+}
+
+fn button_color(button: egui::PointerButton) -> egui::Color32 {
+    match button {
+        egui::PointerButton::Primary => egui::Color32::from_rgb(255, 210, 60),
+        egui::PointerButton::Secondary => egui::Color32::from_rgb(90, 170, 255),
+        egui::PointerButton::Middle => egui::Color32::from_rgb(120, 220, 120),
+        egui::PointerButton::Extra1 | egui::PointerButton::Extra2 => {
+            egui::Color32::from_rgb(220, 130, 240)
+        }
+    }
+}
+
+fn presenter_system(
+    mut contexts: EguiContexts,
+    mut presenter: ResMut<Presenter>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if !presenter.enabled {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+    let (time, pointer, held, pressed) = ctx.input(|input| {
+        let pressed: Vec<(egui::Pos2, egui::PointerButton)> = input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                egui::Event::PointerButton {
+                    pos,
+                    button,
+                    pressed: true,
+                    ..
+                } => Some((*pos, *button)),
+                _ => None,
+            })
+            .collect();
+        let held = [
+            egui::PointerButton::Primary,
+            egui::PointerButton::Secondary,
+            egui::PointerButton::Middle,
+        ]
+        .into_iter()
+        .find(|button| input.pointer.button_down(*button));
+        (input.time, input.pointer.latest_pos(), held, pressed)
+    });
+
+    // Modifiers only show as part of a chord, as the shortcut list writes them.
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    for key in keys.get_just_pressed() {
+        if matches!(
+            key,
+            KeyCode::ControlLeft
+                | KeyCode::ControlRight
+                | KeyCode::ShiftLeft
+                | KeyCode::ShiftRight
+                | KeyCode::AltLeft
+                | KeyCode::AltRight
+        ) {
+            continue;
+        }
+        let chord = KeyChord {
+            key: *key,
+            ctrl,
+            shift,
+            alt,
+        };
+        presenter.press(chord.to_string(), time);
+    }
+
+    let Presenter {
+        trail,
+        ripples,
+        keys: pressed_keys,
+        ..
+    } = &mut *presenter;
+    if let Some(pos) = pointer {
+        if trail.back().is_none_or(|(last, _)| *last != pos) {
+            trail.push_back((pos, time));
+        }
+    }
+    while trail
+        .front()
+        .is_some_and(|(_, at)| time - at > TRAIL_SECONDS)
+    {
+        trail.pop_front();
+    }
+    ripples.extend(pressed.into_iter().map(|(pos, button)| (pos, button, time)));
+    ripples.retain(|(_, _, at)| time - at < RIPPLE_SECONDS);
+    pressed_keys.retain(|key| time - key.time < KEY_SECONDS);
+
+    // Above every window, popup and tooltip.
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Debug,
+        egui::Id::new("presenter_overlay"),
+    ));
+    let trail_color = egui::Color32::from_rgb(255, 210, 60);
+    for pair in trail.make_contiguous().windows(2) {
+        let fade = 1.0 - ((time - pair[1].1) / TRAIL_SECONDS) as f32;
+        painter.line_segment(
+            [pair[0].0, pair[1].0],
+            egui::Stroke::new(1.0 + 3.0 * fade, trail_color.gamma_multiply(0.7 * fade)),
+        );
+    }
+    if let Some(pos) = pointer {
+        let (fill, stroke) = match held {
+            Some(button) => (
+                button_color(button).gamma_multiply(0.45),
+                button_color(button),
+            ),
+            None => (
+                trail_color.gamma_multiply(0.2),
+                trail_color.gamma_multiply(0.6),
+            ),
+        };
+        painter.circle(pos, 14.0, fill, egui::Stroke::new(2.0, stroke));
+    }
+    for (pos, button, at) in ripples.iter() {
+        let progress = ((time - at) / RIPPLE_SECONDS) as f32;
+        painter.circle_stroke(
+            *pos,
+            8.0 + RIPPLE_RADIUS * progress,
+            egui::Stroke::new(3.0, button_color(*button).gamma_multiply(1.0 - progress)),
+        );
+    }
+
+    // The latest chord on the right, older ones fading to its left.
+    let font = egui::FontId::proportional(22.0);
+    let galleys: Vec<_> = pressed_keys
+        .iter()
+        .map(|key| {
+            let text = match key.count {
+                1 => key.label.clone(),
+                count => format!("{} ×{count}", key.label),
+            };
+            // Fades out over the last half of its time on screen.
+            let fade = (1.0 - (time - key.time) / KEY_SECONDS).clamp(0.0, 1.0) as f32;
+            let alpha = (fade * 2.0).min(1.0);
+            let color = egui::Color32::WHITE.gamma_multiply(alpha);
+            (painter.layout_no_wrap(text, font.clone(), color), alpha)
+        })
+        .collect();
+    let spacing = 10.0;
+    let total: f32 = galleys
+        .iter()
+        .map(|(galley, _)| galley.size().x + 24.0 + spacing)
+        .sum();
+    let screen = ctx.screen_rect();
+    let mut right = screen.center().x + total / 2.0;
+    for (galley, alpha) in galleys.into_iter().rev() {
+        let size = galley.size() + egui::vec2(24.0, 12.0);
+        let rect = egui::Rect::from_min_size(
+            egui::pos2(right - size.x, screen.bottom() - 60.0 - size.y),
+            size,
+        );
+        right -= size.x + spacing;
+        painter.rect(
+            rect,
+            6.0,
+            egui::Color32::from_black_alpha((200.0 * alpha) as u8),
+            egui::Stroke::new(1.0, egui::Color32::from_white_alpha((90.0 * alpha) as u8)),
+        );
+        painter.galley(
+            rect.min + egui::vec2(12.0, 6.0),
+            galley,
+            egui::Color32::WHITE,
+        );
+    }
+
+    if !trail.is_empty() || !ripples.is_empty() || !pressed_keys.is_empty() {
+        ctx.request_repaint();
+    }
+}
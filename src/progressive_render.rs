@@ -0,0 +1,512 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    mpsc, Arc, Mutex,
+};
+
+use bevy::{
+    prelude::*,
+    render::camera::{CameraProjection, Exposure},
+};
+use bevy_egui::{egui, EguiContexts};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use xihydra_bevy::widgets::StreamedTexture;
+
+use crate::{
+    errors::AppError,
+    numeric::slider,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    path_tracer::{self, SceneMesh, Tracer},
+    report::encode_png,
+    status_bar::StatusBar,
+    viewport::Viewport,
+    window_title::TaskProgress,
+    RenderCube, ViewportCamera,
+};
+
+const TASK_NAME: &str = "Progressive render";
+/// Rows a worker traces at a time, one sample per pixel.
+const ROWS_PER_JOB: u32 = 8;
+/// Seconds between refreshes of the shown image, which is rebuilt from every pixel.
+const REFRESH_SECONDS: f32 = 0.25;
+
+/// A progressive beauty render of the viewport: the CPU path tracer the lightmap baker uses
+/// accumulates samples of the current view, one pass over every pixel at a time, and the
+/// average so far is drawn over the viewport until the render is stopped. Rays that leave the
+/// scene stay transparent, so the viewport's background shows through and saved stills can be
+/// composited.
+pub struct ProgressiveRenderPlugin;
+
+impl Plugin for ProgressiveRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ProgressiveRenderWindow>()
+            .add_systems(
+                Update,
+                (
+                    progressive_render_window_system,
+                    accumulate_system,
+                    paint_render_system.after(crate::UiSet::Central),
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::View, "Progressive Render…", |world| {
+                world.resource_mut::<ProgressiveRenderWindow>().is_open = true;
+            }));
+    }
+}
+
+#[derive(Resource)]
+pub struct ProgressiveRenderWindow {
+    pub is_open: bool,
+    /// Samples per pixel the render stops at.
+    samples: u32,
+    bounces: u32,
+    /// Traced pixels per side of a viewport pixel, as a fraction: 1 is full resolution.
+    scale: f32,
+    save_when_done: bool,
+    render: Option<Render>,
+}
+
+impl Default for ProgressiveRenderWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            samples: 256,
+            bounces: 3,
+            scale: 0.5,
+            save_when_done: false,
+            render: None,
+        }
+    }
+}
+
+impl Panel for ProgressiveRenderWindow {
+    const TITLE: &'static str = "Progressive Render";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// How to turn an image pixel into a ray, copied from the camera when the render starts.
+#[derive(Clone, Copy)]
+struct View {
+    size: UVec2,
+    world_from_clip: Mat4,
+    /// `world_from_clip` with fixed clipping planes. Automatic clipping moves the planes as
+    /// the scene changes, which barely changes the image, so views compare by this instead.
+    framing: Mat4,
+    exposure: f32,
+}
+
+impl PartialEq for View {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.framing == other.framing && self.exposure == other.exposure
+    }
+}
+
+impl View {
+    /// The ray through `pixel`, offset within it by `jitter` in `0..1`.
+    fn ray(&self, pixel: UVec2, jitter: Vec2) -> (Vec3, Vec3) {
+        let uv = (pixel.as_vec2() + jitter) / self.size.as_vec2();
+        let ndc = Vec2::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+        // Bevy's depth is reversed: 1 is the near plane and 0 infinitely far.
+        let near = self.world_from_clip.project_point3(ndc.extend(1.0));
+        let far = self
+            .world_from_clip
+            .project_point3(ndc.extend(f32::EPSILON));
+        (near, (far - near).normalize())
+    }
+}
+
+/// Rows `first..` of one pass, as a worker traced them: the summed radiance of the samples
+/// that hit the scene in each pixel, and whether each did.
+struct TracedRows {
+    first: u32,
+    pixels: Vec<(Vec3, bool)>,
+}
+
+/// A render in progress. Worker threads take chunks of rows in turn, pass after pass, and
+/// send back what they traced. Dropping it stops them after their current chunk.
+struct Render {
+    view: View,
+    target: u32,
+    bounces: u32,
+    traced: Mutex<mpsc::Receiver<TracedRows>>,
+    stop: Arc<AtomicBool>,
+    /// Per pixel, the radiance summed over samples that hit and how many did.
+    sums: Vec<(Vec3, u32)>,
+    /// Samples taken so far in each chunk of rows.
+    chunk_samples: Vec<u32>,
+    seconds: f32,
+    since_refresh: f32,
+    texture: StreamedTexture,
+    saved: bool,
+}
+
+impl Render {
+    fn start(tracer: Tracer, view: View, target: u32, bounces: u32, sky: Vec3) -> Self {
+        let chunks = view.size.y.div_ceil(ROWS_PER_JOB) as usize;
+        let tracer = Arc::new(tracer);
+        let next = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let workers = std::thread::available_parallelism().map_or(2, |count| count.get());
+        for _ in 0..workers {
+            let (tracer, next, stop, sender) =
+                (tracer.clone(), next.clone(), stop.clone(), sender.clone());
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let job = next.fetch_add(1, Ordering::Relaxed);
+                    let pass = (job / chunks) as u32;
+                    if pass >= target {
+                        break;
+                    }
+                    let first = (job % chunks) as u32 * ROWS_PER_JOB;
+                    let mut rng = StdRng::seed_from_u64(job as u64);
+                    let mut pixels = Vec::new();
+                    for y in first..(first + ROWS_PER_JOB).min(view.size.y) {
+                        for x in 0..view.size.x {
+                            let jitter = Vec2::new(rng.gen(), rng.gen());
+                            let (origin, direction) = view.ray(UVec2::new(x, y), jitter);
+                            let radiance =
+                                tracer.radiance(origin, direction, bounces + 1, sky, &mut rng);
+                            pixels.push((radiance.unwrap_or(Vec3::ZERO), radiance.is_some()));
+                        }
+                    }
+                    if sender.send(TracedRows { first, pixels }).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Self {
+            view,
+            target,
+            bounces,
+            traced: Mutex::new(receiver),
+            stop,
+            sums: vec![(Vec3::ZERO, 0); (view.size.x * view.size.y) as usize],
+            chunk_samples: vec![0; chunks],
+            seconds: 0.0,
+            since_refresh: REFRESH_SECONDS,
+            texture: StreamedTexture::new("progressive_render", egui::TextureOptions::LINEAR),
+            saved: false,
+        }
+    }
+
+    /// Adds in the rows traced since the last call; whether there were any.
+    fn receive(&mut self) -> bool {
+        let Ok(traced) = self.traced.get_mut() else {
+            return false;
+        };
+        let mut received = false;
+        for rows in traced.try_iter() {
+            let start = (rows.first * self.view.size.x) as usize;
+            for (sum, (radiance, hit)) in self.sums[start..].iter_mut().zip(rows.pixels) {
+                if hit {
+                    sum.0 += radiance;
+                    sum.1 += 1;
+                }
+            }
+            self.chunk_samples[(rows.first / ROWS_PER_JOB) as usize] += 1;
+            received = true;
+        }
+        received
+    }
+
+    /// Samples every pixel has had.
+    fn samples(&self) -> u32 {
+        self.chunk_samples.iter().copied().min().unwrap_or(0)
+    }
+
+    fn is_done(&self) -> bool {
+        self.samples() >= self.target
+    }
+
+    /// The average so far, exposed like the camera's view and tonemapped, in sRGB.
+    fn rgba(&self) -> Vec<u8> {
+        let width = self.view.size.x as usize;
+        self.sums
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (sum, hits))| {
+                let samples = self.chunk_samples[index / width / ROWS_PER_JOB as usize];
+                if *hits == 0 {
+                    return [0; 4];
+                }
+                let color = *sum / *hits as f32 * self.view.exposure;
+                // Reinhard on luminance, which keeps hues as they brighten.
+                let color = color / (1.0 + color.dot(Vec3::new(0.2126, 0.7152, 0.0722)));
+                let alpha = *hits as f32 / samples.max(1) as f32;
+                Color::linear_rgba(color.x, color.y, color.z, alpha)
+                    .to_srgba()
+                    .to_u8_array()
+            })
+            .collect()
+    }
+
+    fn save(&self) -> Result<String, String> {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let path = format!("render-{seconds}.png");
+        let png = encode_png(self.view.size, &self.rgba()).map_err(|err| err.to_string())?;
+        std::fs::write(&path, png).map_err(|err| err.to_string())?;
+        Ok(path)
+    }
+}
+
+impl Drop for Render {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The camera's view, scaled to the traced resolution.
+fn current_view(
+    camera: &Camera,
+    projection: &Projection,
+    transform: &GlobalTransform,
+    exposure: Option<&Exposure>,
+    viewport: &Viewport,
+    scale: f32,
+) -> Option<View> {
+    let size = (viewport.image_size.as_vec2() * scale).round().as_uvec2();
+    let mut lens = projection.clone();
+    match &mut lens {
+        Projection::Perspective(perspective) => (perspective.near, perspective.far) = (1.0, 2.0),
+        Projection::Orthographic(orthographic) => {
+            (orthographic.near, orthographic.far) = (0.0, 1.0)
+        }
+    }
+    let world_from_view = transform.compute_matrix();
+    (size.min_element() > 0).then(|| View {
+        size,
+        world_from_clip: world_from_view * camera.clip_from_view().inverse(),
+        framing: world_from_view * lens.get_clip_from_view().inverse(),
+        exposure: exposure.copied().unwrap_or_default().exposure(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn progressive_render_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ProgressiveRenderWindow>,
+    cameras: Query<
+        (&Camera, &Projection, &GlobalTransform, Option<&Exposure>),
+        With<ViewportCamera>,
+    >,
+    viewport: Res<Viewport>,
+    scene: Query<SceneMesh, With<RenderCube>>,
+    lights: Query<(&PointLight, &GlobalTransform)>,
+    ambient: Res<AmbientLight>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    time: Res<Time<Real>>,
+) {
+    let ProgressiveRenderWindow {
+        is_open,
+        samples,
+        bounces,
+        scale,
+        save_when_done,
+        render,
+    } = &mut *window;
+    let view = cameras
+        .get_single()
+        .ok()
+        .and_then(|(camera, projection, transform, exposure)| {
+            current_view(camera, projection, transform, exposure, &viewport, *scale)
+        });
+
+    let mut start = false;
+    let mut save = false;
+    if *is_open {
+        egui::Window::new(ProgressiveRenderWindow::TITLE)
+            .open(is_open)
+            .default_width(280.0)
+            .show(contexts.ctx::<ProgressiveRenderWindow>(), |ui| {
+                egui::Grid::new("progressive_render_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Samples");
+                        ui.add(slider(samples, 1..=4096).logarithmic(true))
+                            .on_hover_text("Per pixel; the render stops once every pixel has them");
+                        ui.end_row();
+                        ui.label("Bounces");
+                        ui.add(slider(bounces, 0..=8));
+                        ui.end_row();
+                        ui.label("Resolution");
+                        egui::ComboBox::from_id_source("progressive_render_scale")
+                            .selected_text(format!("{}%", (*scale * 100.0).round()))
+                            .show_ui(ui, |ui| {
+                                for option in [0.25, 0.5, 1.0] {
+                                    ui.selectable_value(
+                                        scale,
+                                        option,
+                                        format!("{}%", option * 100.0),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text("Of the viewport image");
+                        ui.end_row();
+                    });
+                ui.checkbox(save_when_done, "Save when done");
+                ui.separator();
+                match render {
+                    Some(running) => {
+                        let done = running.samples();
+                        ui.add(
+                            egui::ProgressBar::new(done as f32 / running.target as f32).text(
+                                format!(
+                                    "{done} / {} samples, {:.0} s",
+                                    running.target, running.seconds
+                                ),
+                            ),
+                        );
+                        ui.horizontal(|ui| {
+                            if ui.button("Restart").clicked() {
+                                start = true;
+                            }
+                            if ui.button("Stop").clicked() {
+                                *render = None;
+                            }
+                            save = ui
+                                .add_enabled(done > 0, egui::Button::new("Save PNG"))
+                                .clicked();
+                        });
+                    }
+                    None => {
+                        start = ui
+                            .add_enabled(view.is_some(), egui::Button::new("Start"))
+                            .clicked();
+                    }
+                }
+                ui.weak(
+                    "Renders the scene as it was when started, lit by its point lights and the \
+                     ambient light; restart after editing. Moving the camera restarts it.",
+                );
+            });
+    }
+
+    // Settings only take effect on a restart, the view as soon as it changes.
+    if let (Some(running), Some(view)) = (&*render, view) {
+        start |= running.view != view;
+    }
+    if save {
+        if let Some(running) = render {
+            match running.save() {
+                Ok(path) => status.flash(format!("Saved {path}"), time.elapsed_seconds()),
+                Err(err) => {
+                    errors.send(AppError::new(
+                        "Progressive Render",
+                        format!("Failed to save the render: {err}"),
+                    ));
+                }
+            }
+        }
+    }
+    let Some(view) = view.filter(|_| start) else {
+        return;
+    };
+    let tracer = path_tracer::snapshot(&scene, &lights, &meshes, &materials, |_, _| {});
+    let color = ambient.color.to_linear();
+    let sky = Vec3::new(color.red, color.green, color.blue) * ambient.brightness;
+    *render = Some(Render::start(tracer, view, *samples, *bounces, sky));
+}
+
+fn accumulate_system(
+    mut window: ResMut<ProgressiveRenderWindow>,
+    mut progress: ResMut<TaskProgress>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    time: Res<Time<Real>>,
+) {
+    let ProgressiveRenderWindow {
+        save_when_done,
+        render,
+        ..
+    } = &mut *window;
+    let Some(running) = render else {
+        return;
+    };
+    if progress.take_cancel(TASK_NAME) {
+        *render = None;
+        return;
+    }
+    let received = running.receive();
+    running.since_refresh += time.delta_seconds();
+    let done = running.is_done();
+    if !done {
+        running.seconds += time.delta_seconds();
+        progress.report_cancellable(TASK_NAME, running.samples() as f32 / running.target as f32);
+    }
+    if received && (done || running.since_refresh >= REFRESH_SECONDS) {
+        running.since_refresh = 0.0;
+        let size = [running.view.size.x as usize, running.view.size.y as usize];
+        let image = egui::ColorImage::from_rgba_unmultiplied(size, &running.rgba());
+        running.texture.update(image);
+    }
+    if done && *save_when_done && !running.saved {
+        running.saved = true;
+        match running.save() {
+            Ok(path) => status.flash(format!("Saved {path}"), time.elapsed_seconds()),
+            Err(err) => {
+                errors.send(AppError::new(
+                    "Progressive Render",
+                    format!("Failed to save the render: {err}"),
+                ));
+            }
+        }
+    }
+}
+
+/// Draws the render over the viewport image, under every window.
+fn paint_render_system(
+    mut contexts: EguiContexts,
+    mut window: ResMut<ProgressiveRenderWindow>,
+    viewport: Res<Viewport>,
+) {
+    let Some(running) = &mut window.render else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    let Some(texture) = running.texture.upload(ctx).map(egui::TextureHandle::id) else {
+        return;
+    };
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let rect = viewport.rect;
+    painter.image(
+        texture,
+        rect,
+        egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+        egui::Color32::WHITE,
+    );
+    let samples = running.samples();
+    let text = if running.is_done() {
+        format!("{samples} samples, {:.0} s", running.seconds)
+    } else {
+        format!(
+            "{samples} / {} samples, {} bounces",
+            running.target, running.bounces
+        )
+    };
+    let galley =
+        painter.layout_no_wrap(text, egui::FontId::proportional(13.0), egui::Color32::WHITE);
+    let label = egui::Rect::from_min_size(
+        rect.left_bottom() + egui::vec2(6.0, -6.0 - galley.size().y - 4.0),
+        galley.size() + egui::vec2(8.0, 4.0),
+    );
+    painter.rect_filled(label, 3.0, egui::Color32::from_black_alpha(160));
+    painter.galley(
+        label.min + egui::vec2(4.0, 2.0),
+        galley,
+        egui::Color32::WHITE,
+    );
+    if !running.is_done() {
+        ctx.request_repaint();
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::icons::{Icon, IconButtonsExt};
+use crate::numeric::drag_value;
+
+/// A typed custom property value.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyValue {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Color([f32; 4]),
+}
+
+impl PropertyValue {
+    #[allow(clippy::type_complexity)]
+    const KINDS: [(&'static str, fn() -> PropertyValue); 4] = [
+        ("Text", || PropertyValue::Text(String::new())),
+        ("Number", || PropertyValue::Number(0.0)),
+        ("Bool", || PropertyValue::Bool(false)),
+        ("Color", || PropertyValue::Color([1.0; 4])),
+    ];
+
+    fn kind(&self) -> &'static str {
+        match self {
+            PropertyValue::Text(_) => "Text",
+            PropertyValue::Number(_) => "Number",
+            PropertyValue::Bool(_) => "Bool",
+            PropertyValue::Color(_) => "Color",
+        }
+    }
+
+    fn edit(&mut self, ui: &mut egui::Ui) -> bool {
+        match self {
+            PropertyValue::Text(text) => ui.text_edit_singleline(text).changed(),
+            PropertyValue::Number(number) => ui.add(drag_value(number).speed(0.1)).changed(),
+            PropertyValue::Bool(value) => ui.checkbox(value, "").changed(),
+            PropertyValue::Color(color) => ui.color_edit_button_rgba_unmultiplied(color).changed(),
+        }
+    }
+}
+
+impl std::fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyValue::Text(text) => write!(f, "{text:?}"),
+            PropertyValue::Number(number) => write!(f, "{number}"),
+            PropertyValue::Bool(value) => write!(f, "{value}"),
+            PropertyValue::Color([r, g, b, a]) => write!(f, "rgba({r:.2}, {g:.2}, {b:.2}, {a:.2})"),
+        }
+    }
+}
+
+/// Arbitrary key/value metadata on a scene entity, edited in the Inspector and saved with the
+/// scene. Keys are unique and kept in the order they were added.
+#[derive(Component, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Properties(pub Vec<(String, PropertyValue)>);
+
+impl Properties {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `key = value` pairs on one line, for diffs.
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "-".to_owned();
+        }
+        self.0
+            .iter()
+            .map(|(key, value)| format!("{key} = {value}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The Inspector's property list, with a row for adding new keys. Returns whether anything
+/// changed.
+pub fn properties_edit(ui: &mut egui::Ui, properties: &mut Properties) -> bool {
+    let mut changed = false;
+    let mut removed = None;
+    egui::Grid::new("inspector_properties")
+        .num_columns(3)
+        .show(ui, |ui| {
+            for (index, (key, value)) in properties.0.iter_mut().enumerate() {
+                ui.label(key.as_str()).on_hover_text(value.kind());
+                changed |= value.edit(ui);
+                if ui
+                    .small_icon_button(Icon::Delete, "")
+                    .on_hover_text("Remove property")
+                    .clicked()
+                {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = removed {
+        properties.0.remove(index);
+        changed = true;
+    }
+
+    // The key being typed lives in egui's memory until it is added.
+    let id = ui.id().with("new_property");
+    let mut key: String = ui.data_mut(|data| data.get_temp(id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut key)
+                .hint_text("New property")
+                .desired_width(110.0),
+        );
+        let key_taken = properties
+            .0
+            .iter()
+            .any(|(existing, _)| *existing == key.trim());
+        for (label, value) in PropertyValue::KINDS {
+            if ui
+                .add_enabled(
+                    !key.trim().is_empty() && !key_taken,
+                    egui::Button::new(label).small(),
+                )
+                .clicked()
+            {
+                properties.0.push((key.trim().to_owned(), value()));
+                key.clear();
+                changed = true;
+            }
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(id, key));
+    changed
+}
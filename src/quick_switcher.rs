@@ -0,0 +1,236 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::{frame_bounds, world_bounds},
+    groups::Group,
+    hierarchy::entity_label,
+    keybindings::Action,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    selection::Selection,
+    thumbnails::EntityTooltips,
+    RenderCube, ViewportCamera,
+};
+
+/// Matches listed under the query box.
+const MAX_RESULTS: usize = 12;
+
+/// A popup for jumping to an entity by name, opened with Ctrl+P. Typing some letters of a name
+/// in order finds it ("rcu" finds "Red cube"); picking a match with the arrow keys and Enter,
+/// or a click, selects the entity and frames it in the viewport.
+pub struct QuickSwitcherPlugin;
+
+impl Plugin for QuickSwitcherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuickSwitcher>()
+            .add_systems(Update, quick_switcher_system.after(crate::UiSet::Central))
+            .add_menu_item(
+                MenuItem::new(Menu::View, "Go to Entity…", |world| {
+                    world.resource_mut::<QuickSwitcher>().open();
+                })
+                .shortcut(Action::QuickSwitcher),
+            );
+    }
+}
+
+#[derive(Default, Resource)]
+struct QuickSwitcher {
+    is_open: bool,
+    query: String,
+    /// Index into the current matches of the one Enter picks.
+    highlighted: usize,
+    /// Set on opening, to give the query box keyboard focus.
+    focus: bool,
+}
+
+impl QuickSwitcher {
+    fn open(&mut self) {
+        self.is_open = true;
+        self.query.clear();
+        self.highlighted = 0;
+        self.focus = true;
+    }
+}
+
+/// Scores `text` against `query` as a case-insensitive subsequence, returning the score and
+/// the char indices matched, or `None` if some query char is missing. Runs of consecutive
+/// matches and matches at word starts score higher, and shorter texts break ties.
+fn fuzzy_match(query: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let mut matched = Vec::new();
+    let mut score = 0;
+    let mut chars = text.chars().enumerate();
+    let mut previous: Option<(usize, char)> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next().unwrap_or(wanted);
+        loop {
+            let (index, c) = chars.next()?;
+            let is_match = c.to_lowercase().next() == Some(wanted);
+            if is_match {
+                let word_start = previous.is_none_or(|(_, before)| {
+                    !before.is_alphanumeric() || (before.is_lowercase() && c.is_uppercase())
+                });
+                let consecutive = matched.last().is_some_and(|last| last + 1 == index);
+                score += 1 + 4 * i32::from(word_start) + 3 * i32::from(consecutive);
+                matched.push(index);
+                previous = Some((index, c));
+                break;
+            }
+            previous = Some((index, c));
+        }
+    }
+    if matched.is_empty() {
+        return None;
+    }
+    Some((score * 100 - text.chars().count() as i32, matched))
+}
+
+/// `text` with the chars at `matched` emphasised.
+fn highlighted_text(ui: &egui::Ui, text: &str, matched: &[usize]) -> egui::text::LayoutJob {
+    let font = egui::TextStyle::Button.resolve(ui.style());
+    let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
+    let strong = egui::TextFormat {
+        underline: egui::Stroke::new(1.0, ui.visuals().strong_text_color()),
+        ..egui::TextFormat::simple(font, ui.visuals().strong_text_color())
+    };
+    let mut job = egui::text::LayoutJob::default();
+    for (index, c) in text.chars().enumerate() {
+        let format = if matched.contains(&index) {
+            strong.clone()
+        } else {
+            normal.clone()
+        };
+        job.append(c.encode_utf8(&mut [0; 4]), 0.0, format);
+    }
+    job
+}
+
+#[allow(clippy::type_complexity)]
+fn quick_switcher_system(
+    mut contexts: EguiContexts,
+    mut switcher: ResMut<QuickSwitcher>,
+    mut selection: ResMut<Selection>,
+    entities: Query<
+        (
+            Entity,
+            Option<&Name>,
+            Has<Group>,
+            &GlobalTransform,
+            Option<&Aabb>,
+        ),
+        Or<(With<RenderCube>, With<Group>, With<Name>)>,
+    >,
+    mut cameras: Query<(&mut Transform, &Projection), With<ViewportCamera>>,
+    mut tooltips: EntityTooltips,
+) {
+    if !switcher.is_open {
+        return;
+    }
+    let QuickSwitcher {
+        is_open,
+        query,
+        highlighted,
+        focus,
+    } = &mut *switcher;
+
+    let mut matches: Vec<(i32, Entity, String, Vec<usize>)> = entities
+        .iter()
+        .filter_map(|(entity, name, is_group, ..)| {
+            let label = entity_label(entity, name, is_group);
+            if query.trim().is_empty() {
+                return Some((0, entity, label, Vec::new()));
+            }
+            let (score, matched) = fuzzy_match(query, &label)?;
+            Some((score, entity, label, matched))
+        })
+        .collect();
+    let total = matches.len();
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.2.cmp(&b.2)));
+    matches.truncate(MAX_RESULTS);
+    *highlighted = (*highlighted).min(matches.len().saturating_sub(1));
+
+    let ctx = contexts.ctx_mut();
+    let mut chosen = None;
+    let (up, down, enter, escape) = ctx.input(|input| {
+        (
+            input.key_pressed(egui::Key::ArrowUp),
+            input.key_pressed(egui::Key::ArrowDown),
+            input.key_pressed(egui::Key::Enter),
+            input.key_pressed(egui::Key::Escape),
+        )
+    });
+    if up {
+        *highlighted = highlighted.saturating_sub(1);
+    }
+    if down && *highlighted + 1 < matches.len() {
+        *highlighted += 1;
+    }
+    if enter {
+        chosen = matches.get(*highlighted).map(|(_, entity, ..)| *entity);
+    }
+
+    let area = egui::Area::new(egui::Id::new("quick_switcher"))
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(360.0);
+                let edit = ui.add(
+                    egui::TextEdit::singleline(query)
+                        .hint_text("Go to entity…")
+                        .desired_width(f32::INFINITY),
+                );
+                if std::mem::take(focus) {
+                    edit.request_focus();
+                }
+                if edit.changed() {
+                    *highlighted = 0;
+                }
+                ui.separator();
+                if matches.is_empty() {
+                    ui.weak("No entity matches.");
+                }
+                for (index, (_, entity, label, matched)) in matches.iter().enumerate() {
+                    let row = ui.selectable_label(
+                        index == *highlighted,
+                        highlighted_text(ui, label, matched),
+                    );
+                    let row = tooltips.on_hover(row, *entity);
+                    if row.clicked() {
+                        chosen = Some(*entity);
+                    }
+                    if row.hovered() && ui.input(|input| input.pointer.delta() != egui::Vec2::ZERO)
+                    {
+                        *highlighted = index;
+                    }
+                }
+                if total > matches.len() {
+                    ui.weak(format!(
+                        "{} more; keep typing to narrow down",
+                        total - matches.len()
+                    ));
+                }
+            });
+        });
+
+    if escape || area.response.clicked_elsewhere() {
+        *is_open = false;
+    }
+    let Some(entity) = chosen else {
+        return;
+    };
+    *is_open = false;
+    selection.select(entity);
+    let Ok((_, _, _, transform, aabb)) = entities.get(entity) else {
+        return;
+    };
+    let (min, max) = match aabb {
+        Some(aabb) => world_bounds(transform, aabb),
+        None => {
+            let center = transform.translation();
+            (center - Vec3::ONE, center + Vec3::ONE)
+        }
+    };
+    if let Ok((mut camera, projection)) = cameras.get_single_mut() {
+        frame_bounds(&mut camera, projection, min, max);
+    }
+}
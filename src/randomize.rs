@@ -0,0 +1,245 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    icons::{Icon, IconButtonsExt},
+    numeric::{drag_value, Unit},
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    selection::{vec3_edit, Selection},
+    settings::Settings,
+};
+
+/// Jitters the position, rotation and scale of every selected entity within per-axis ranges.
+/// The same seed and selection always give the same result; the outcome is previewed as
+/// outlines until applied, and applying moves everything in one frame for a single undo.
+pub struct RandomizePlugin;
+
+impl Plugin for RandomizePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<RandomizeWindow>()
+            .add_systems(
+                Update,
+                (
+                    randomize_window_system.in_set(RandomizeWindow::ui_set()),
+                    draw_preview_system,
+                    apply_randomize_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(
+                Menu::Edit,
+                "Randomize Transforms…",
+                |world| {
+                    world.resource_mut::<RandomizeWindow>().is_open = true;
+                },
+            ));
+    }
+}
+
+#[derive(Resource)]
+pub struct RandomizeWindow {
+    pub is_open: bool,
+    /// Largest offset either way along each axis.
+    translation: Vec3,
+    /// Largest turn either way about each axis, in degrees.
+    rotation: Vec3,
+    /// Largest change either way as a fraction of the current scale, per axis.
+    scale: Vec3,
+    /// Scale all axes by the same factor, taken from the x range.
+    uniform_scale: bool,
+    seed: u64,
+    preview: bool,
+    apply: bool,
+}
+
+impl Default for RandomizeWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            translation: Vec3::new(0.5, 0.0, 0.5),
+            rotation: Vec3::new(0.0, 180.0, 0.0),
+            scale: Vec3::splat(0.2),
+            uniform_scale: true,
+            seed: 1,
+            preview: true,
+            apply: false,
+        }
+    }
+}
+
+impl Panel for RandomizeWindow {
+    const TITLE: &'static str = "Randomize";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl RandomizeWindow {
+    /// `transforms` jittered, drawing from one generator seeded afresh each call, in order.
+    fn jitter(&self, transforms: impl Iterator<Item = Transform>) -> Vec<Transform> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut spread = |range: f32| rng.gen_range(-range.abs()..=range.abs());
+        transforms
+            .map(|mut transform| {
+                let offset = Vec3::new(
+                    spread(self.translation.x),
+                    spread(self.translation.y),
+                    spread(self.translation.z),
+                );
+                let turn = Vec3::new(
+                    spread(self.rotation.x),
+                    spread(self.rotation.y),
+                    spread(self.rotation.z),
+                );
+                let factor = if self.uniform_scale {
+                    Vec3::splat(1.0 + spread(self.scale.x))
+                } else {
+                    Vec3::new(
+                        1.0 + spread(self.scale.x),
+                        1.0 + spread(self.scale.y),
+                        1.0 + spread(self.scale.z),
+                    )
+                };
+                transform.translation += offset;
+                transform.rotation = Quat::from_euler(
+                    EulerRot::XYZ,
+                    turn.x.to_radians(),
+                    turn.y.to_radians(),
+                    turn.z.to_radians(),
+                ) * transform.rotation;
+                transform.scale *= factor.max(Vec3::splat(0.01));
+                transform
+            })
+            .collect()
+    }
+}
+
+fn randomize_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<RandomizeWindow>,
+    selection: Res<Selection>,
+) {
+    let RandomizeWindow {
+        is_open,
+        translation,
+        rotation,
+        scale,
+        uniform_scale,
+        seed,
+        preview,
+        apply,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let count = selection.entities.len();
+    egui::Window::new(RandomizeWindow::TITLE)
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<RandomizeWindow>(), |ui| {
+            egui::Grid::new("randomize_ranges")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Position ±");
+                    vec3_edit(ui, translation, 0.05, Some(Unit::Meters));
+                    ui.end_row();
+                    ui.label("Rotation ±");
+                    vec3_edit(ui, rotation, 1.0, Some(Unit::Degrees));
+                    ui.end_row();
+                    ui.label("Scale ±");
+                    if *uniform_scale {
+                        ui.add(drag_value(&mut scale.x).speed(0.01).range(0.0..=0.99));
+                    } else {
+                        vec3_edit(ui, scale, 0.01, None);
+                    }
+                    ui.end_row();
+                    ui.label("");
+                    ui.checkbox(uniform_scale, "Uniform scale");
+                    ui.end_row();
+                    ui.label("Seed");
+                    ui.horizontal(|ui| {
+                        ui.add(drag_value(seed));
+                        if ui
+                            .icon_button(Icon::Random, "")
+                            .on_hover_text("New seed")
+                            .clicked()
+                        {
+                            *seed = rand::random::<u32>().into();
+                        }
+                    });
+                    ui.end_row();
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.checkbox(preview, "Preview");
+                if ui
+                    .add_enabled(count > 0, egui::Button::new(format!("Apply to {count}")))
+                    .on_disabled_hover_text("Select the entities to randomize")
+                    .clicked()
+                {
+                    *apply = true;
+                }
+            });
+        });
+}
+
+fn draw_preview_system(
+    window: Res<RandomizeWindow>,
+    selection: Res<Selection>,
+    entities: Query<(&Transform, Option<&Aabb>, Option<&Parent>)>,
+    globals: Query<&GlobalTransform>,
+    settings: Res<Settings>,
+    mut gizmos: Gizmos,
+) {
+    if !window.is_open || !window.preview {
+        return;
+    }
+    let selected: Vec<_> = selection
+        .entities
+        .iter()
+        .filter_map(|entity| entities.get(*entity).ok())
+        .collect();
+    let jittered = window.jitter(selected.iter().map(|(transform, ..)| **transform));
+    let color = settings.highlights().positive;
+    for ((_, aabb, parent), transform) in selected.iter().zip(jittered) {
+        let parent = parent
+            .and_then(|parent| globals.get(parent.get()).ok())
+            .copied()
+            .unwrap_or_default();
+        let aabb = aabb
+            .copied()
+            .unwrap_or_else(|| Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5)));
+        let bounds = Transform::from_translation(aabb.center.into())
+            .with_scale(Vec3::from(aabb.half_extents) * 2.0);
+        gizmos.cuboid(parent.mul_transform(transform).mul_transform(bounds), color);
+    }
+}
+
+fn apply_randomize_system(
+    mut window: ResMut<RandomizeWindow>,
+    selection: Res<Selection>,
+    mut transforms: Query<&mut Transform>,
+) {
+    if !std::mem::take(&mut window.apply) {
+        return;
+    }
+    let selected: Vec<Entity> = selection
+        .entities
+        .iter()
+        .copied()
+        .filter(|entity| transforms.contains(*entity))
+        .collect();
+    let jittered = window.jitter(
+        selected
+            .iter()
+            .filter_map(|entity| transforms.get(*entity).ok().copied()),
+    );
+    for (entity, jittered) in selected.into_iter().zip(jittered) {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            *transform = jittered;
+        }
+    }
+}
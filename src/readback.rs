@@ -0,0 +1,555 @@
+use std::sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex, OnceLock,
+};
+
+use bevy::{
+    core_pipeline::core_3d::graph::{Core3d, Node3d},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        camera::{ExtractedCamera, NormalizedRenderTarget},
+        graph::CameraDriverLabel,
+        render_asset::RenderAssets,
+        render_graph::{
+            self, NodeRunError, RenderGraph, RenderGraphApp, RenderGraphContext, RenderLabel,
+            ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageCopyTexture,
+            ImageDataLayout, Maintain, MapMode, Origin3d, Texture, TextureAspect, TextureFormat,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::GpuImage,
+        view::ViewTarget,
+        Extract, Render, RenderApp, RenderSet,
+    },
+};
+
+/// Copies render-target images back to the CPU on request.
+///
+/// Request an image with [`ReadbackRequests::request`] during `Update`; the pixels arrive as a
+/// [`ReadbackComplete`] event a frame or more later. Copies go to double-buffered staging
+/// buffers that are mapped asynchronously, so a readback never stalls rendering; a request
+/// made while both buffers of its kind are still in flight is skipped.
+///
+/// Each request picks a [`ReadbackSource`]. Both are single-sample: the output is the
+/// camera's final target and the linear stage is the main texture MSAA resolves into, so
+/// either is safe to sample pixel by pixel.
+pub struct ReadbackPlugin;
+
+impl Plugin for ReadbackPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        app.init_resource::<ReadbackRequests>()
+            .insert_resource(ReadbackReceiver(Mutex::new(receiver)))
+            .add_event::<ReadbackComplete>()
+            .add_systems(First, clear_readback_requests_system)
+            .add_systems(PreUpdate, receive_readbacks_system);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(ReadbackSender(sender))
+            .init_resource::<PendingReadbacks>()
+            .init_resource::<StagingBuffers>()
+            .add_systems(ExtractSchedule, extract_readback_requests_system)
+            .add_systems(
+                Render,
+                (
+                    prepare_readbacks_system.in_set(RenderSet::PrepareResources),
+                    map_readbacks_system.in_set(RenderSet::Cleanup),
+                ),
+            );
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<LinearReadbackNode>>(
+                Core3d,
+                LinearReadbackLabel,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPass,
+                    LinearReadbackLabel,
+                    Node3d::Tonemapping,
+                ),
+            );
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(ReadbackLabel, ReadbackNode);
+        graph.add_node_edge(CameraDriverLabel, ReadbackLabel);
+    }
+}
+
+/// Which stage of a camera's rendering a readback copies.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ReadbackSource {
+    /// The image as displayed: resolved, tonemapped and post-processed.
+    #[default]
+    Output,
+    /// The resolved main texture of the camera rendering into the image, after the main
+    /// passes and before tonemapping. Holds scene-linear HDR values while the camera has HDR
+    /// on; without HDR, tonemapping happens in the material shaders and this matches `Output`.
+    Linear,
+}
+
+impl ReadbackSource {
+    pub const ALL: [ReadbackSource; 2] = [ReadbackSource::Output, ReadbackSource::Linear];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReadbackSource::Output => "Output (tonemapped)",
+            ReadbackSource::Linear => "Linear (pre-tonemap)",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct ReadbackRequest {
+    image: Handle<Image>,
+    region: Option<URect>,
+    source: ReadbackSource,
+}
+
+/// Images to read back at the end of this frame. Cleared every frame.
+#[derive(Default, Resource)]
+pub struct ReadbackRequests(Vec<ReadbackRequest>);
+
+impl ReadbackRequests {
+    /// The image's texture must have been created with `TextureUsages::COPY_SRC`.
+    pub fn request(&mut self, image: &Handle<Image>) {
+        self.request_from(image, None, ReadbackSource::Output);
+    }
+
+    /// Reads back `region` (clamped to the image bounds), or the whole image, from the given
+    /// stage.
+    pub fn request_from(
+        &mut self,
+        image: &Handle<Image>,
+        region: Option<URect>,
+        source: ReadbackSource,
+    ) {
+        let request = ReadbackRequest {
+            image: image.clone_weak(),
+            region,
+            source,
+        };
+        if !self.0.contains(&request) {
+            self.0.push(request);
+        }
+    }
+}
+
+/// Pixels of a read-back image as tightly packed sRGB RGBA8 rows.
+#[derive(Event, Clone)]
+pub struct ReadbackComplete {
+    pub image: AssetId<Image>,
+    /// The requested region, or `None` for a full-image readback.
+    pub region: Option<URect>,
+    pub source: ReadbackSource,
+    /// Top-left pixel of `data` within the image.
+    pub origin: UVec2,
+    pub size: UVec2,
+    pub data: Vec<u8>,
+    /// Unclamped linear RGBA, one entry per pixel, when the source held floating-point HDR
+    /// values; `data` then holds them clamped and sRGB-encoded.
+    pub linear: Option<Vec<[f32; 4]>>,
+}
+
+#[derive(Resource)]
+struct ReadbackReceiver(Mutex<Receiver<ReadbackComplete>>);
+
+#[derive(Resource)]
+struct ReadbackSender(Sender<ReadbackComplete>);
+
+#[derive(Default, Resource)]
+struct PendingReadbacks(Vec<ReadbackRequest>);
+
+/// Readbacks of one kind (an image, full or region) allowed in flight at once. While both
+/// staging buffers are busy further requests of that kind are dropped rather than waited on.
+const MAX_IN_FLIGHT: usize = 2;
+/// Free staging buffers unused for this many frames are released.
+const IDLE_FRAMES: u64 = 120;
+
+struct ReadbackJob {
+    image: AssetId<Image>,
+    region: Option<URect>,
+    source: ReadbackSource,
+    origin: UVec2,
+    size: UVec2,
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+}
+
+enum StagingState {
+    Free,
+    /// Filled by this frame's copy; mapping starts once the frame has been submitted.
+    Copying(ReadbackJob),
+    /// Waiting for the GPU to finish the copy and the map to complete; holds its success.
+    Mapping(ReadbackJob, Arc<OnceLock<bool>>),
+}
+
+struct StagingBuffer {
+    buffer: Buffer,
+    capacity: u64,
+    last_used: u64,
+    state: StagingState,
+}
+
+impl StagingBuffer {
+    fn job(&self) -> Option<&ReadbackJob> {
+        match &self.state {
+            StagingState::Free => None,
+            StagingState::Copying(job) | StagingState::Mapping(job, _) => Some(job),
+        }
+    }
+}
+
+/// Reusable `MAP_READ` buffers, so readbacks are pipelined across frames instead of
+/// waiting on the GPU.
+#[derive(Default, Resource)]
+struct StagingBuffers {
+    buffers: Vec<StagingBuffer>,
+    frame: u64,
+}
+
+fn clear_readback_requests_system(mut requests: ResMut<ReadbackRequests>) {
+    if !requests.0.is_empty() {
+        requests.0.clear();
+    }
+}
+
+fn receive_readbacks_system(
+    receiver: Res<ReadbackReceiver>,
+    mut events: EventWriter<ReadbackComplete>,
+) {
+    let receiver = receiver.0.lock().unwrap();
+    events.send_batch(receiver.try_iter());
+}
+
+fn extract_readback_requests_system(
+    requests: Extract<Res<ReadbackRequests>>,
+    mut pending: ResMut<PendingReadbacks>,
+) {
+    pending.0.clone_from(&requests.0);
+}
+
+/// The camera rendering into `image`, for reading its main texture.
+fn view_rendering_to<'a>(
+    views: &'a Query<(&ViewTarget, &ExtractedCamera)>,
+    image: AssetId<Image>,
+) -> Option<&'a ViewTarget> {
+    views
+        .iter()
+        .find_map(|(target, camera)| match &camera.target {
+            Some(NormalizedRenderTarget::Image(handle)) if handle.id() == image => Some(target),
+            _ => None,
+        })
+}
+
+fn bytes_per_pixel(format: TextureFormat) -> Option<u32> {
+    match format {
+        TextureFormat::Bgra8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Rgba8Unorm => Some(4),
+        TextureFormat::Rgba16Float => Some(8),
+        _ => None,
+    }
+}
+
+fn prepare_readbacks_system(
+    pending: Res<PendingReadbacks>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    views: Query<(&ViewTarget, &ExtractedCamera)>,
+    render_device: Res<RenderDevice>,
+    mut staging: ResMut<StagingBuffers>,
+) {
+    let frame = staging.frame;
+    for request in &pending.0 {
+        let (image_size, format) = match request.source {
+            ReadbackSource::Output => {
+                let Some(gpu_image) = gpu_images.get(&request.image) else {
+                    continue;
+                };
+                (gpu_image.size, gpu_image.texture_format)
+            }
+            ReadbackSource::Linear => {
+                let Some(target) = view_rendering_to(&views, request.image.id()) else {
+                    continue;
+                };
+                let size = target.main_texture().size();
+                (
+                    UVec2::new(size.width, size.height),
+                    target.main_texture_format(),
+                )
+            }
+        };
+        let in_flight = staging
+            .buffers
+            .iter()
+            .filter_map(StagingBuffer::job)
+            .filter(|job| {
+                job.image == request.image.id()
+                    && job.source == request.source
+                    && job.region.is_some() == request.region.is_some()
+            })
+            .count();
+        if in_flight >= MAX_IN_FLIGHT {
+            continue;
+        }
+        let bounds = URect::from_corners(UVec2::ZERO, image_size);
+        let rect = request
+            .region
+            .map_or(bounds, |region| region.intersect(bounds));
+        if rect.is_empty() {
+            continue;
+        }
+        let Some(pixel_bytes) = bytes_per_pixel(format) else {
+            warn_once!("Readback of {format:?} images is not supported");
+            continue;
+        };
+        let size = rect.size();
+        let padded_bytes_per_row =
+            RenderDevice::align_copy_bytes_per_row((size.x * pixel_bytes) as usize) as u32;
+        let len = padded_bytes_per_row as u64 * size.y as u64;
+        let job = ReadbackJob {
+            image: request.image.id(),
+            region: request.region,
+            source: request.source,
+            origin: rect.min,
+            size,
+            padded_bytes_per_row,
+            format,
+        };
+
+        // Reuse a free buffer that fits without wasting too much memory.
+        let free = staging.buffers.iter_mut().find(|staging| {
+            matches!(staging.state, StagingState::Free)
+                && (len..=len * 4).contains(&staging.capacity)
+        });
+        match free {
+            Some(free) => {
+                free.last_used = frame;
+                free.state = StagingState::Copying(job);
+            }
+            None => {
+                let buffer = render_device.create_buffer(&BufferDescriptor {
+                    label: Some("readback_buffer"),
+                    size: len,
+                    usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                staging.buffers.push(StagingBuffer {
+                    buffer,
+                    capacity: len,
+                    last_used: frame,
+                    state: StagingState::Copying(job),
+                });
+            }
+        }
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, Hash, PartialEq, Eq)]
+struct ReadbackLabel;
+
+/// Runs after all cameras so the copy sees this frame's render-target contents.
+struct ReadbackNode;
+
+impl render_graph::Node for ReadbackNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+        for staging in &world.resource::<StagingBuffers>().buffers {
+            let StagingState::Copying(readback) = &staging.state else {
+                continue;
+            };
+            if readback.source != ReadbackSource::Output {
+                continue;
+            }
+            let Some(gpu_image) = gpu_images.get(readback.image) else {
+                continue;
+            };
+            copy_to_staging(render_context, &gpu_image.texture, staging, readback);
+        }
+        Ok(())
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, Hash, PartialEq, Eq)]
+struct LinearReadbackLabel;
+
+/// Runs between the main passes and tonemapping, copying linear readbacks of this view's
+/// target image out of its resolved main texture.
+#[derive(Default)]
+struct LinearReadbackNode;
+
+impl ViewNode for LinearReadbackNode {
+    type ViewQuery = (&'static ViewTarget, &'static ExtractedCamera);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, camera): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(NormalizedRenderTarget::Image(image)) = &camera.target else {
+            return Ok(());
+        };
+        for staging in &world.resource::<StagingBuffers>().buffers {
+            let StagingState::Copying(readback) = &staging.state else {
+                continue;
+            };
+            if readback.source == ReadbackSource::Linear && readback.image == image.id() {
+                copy_to_staging(render_context, target.main_texture(), staging, readback);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn copy_to_staging(
+    render_context: &mut RenderContext,
+    texture: &Texture,
+    staging: &StagingBuffer,
+    readback: &ReadbackJob,
+) {
+    render_context.command_encoder().copy_texture_to_buffer(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d {
+                x: readback.origin.x,
+                y: readback.origin.y,
+                z: 0,
+            },
+            aspect: TextureAspect::All,
+        },
+        ImageCopyBuffer {
+            buffer: &staging.buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(readback.padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width: readback.size.x,
+            height: readback.size.y,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Starts mapping the buffers copied this frame and collects those whose map has completed,
+/// without blocking on the GPU.
+fn map_readbacks_system(
+    mut staging: ResMut<StagingBuffers>,
+    render_device: Res<RenderDevice>,
+    sender: Res<ReadbackSender>,
+) {
+    for staging in &mut staging.buffers {
+        if let StagingState::Copying(job) =
+            std::mem::replace(&mut staging.state, StagingState::Free)
+        {
+            let mapped = Arc::new(OnceLock::new());
+            let done = mapped.clone();
+            staging
+                .buffer
+                .slice(..)
+                .map_async(MapMode::Read, move |result| {
+                    let _ = done.set(result.is_ok());
+                });
+            staging.state = StagingState::Mapping(job, mapped);
+        }
+    }
+    render_device.poll(Maintain::Poll);
+
+    for staging in &mut staging.buffers {
+        let StagingState::Mapping(_, mapped) = &staging.state else {
+            continue;
+        };
+        let Some(&ok) = mapped.get() else {
+            continue;
+        };
+        let StagingState::Mapping(readback, _) =
+            std::mem::replace(&mut staging.state, StagingState::Free)
+        else {
+            unreachable!();
+        };
+        if !ok {
+            warn!("Failed to map readback buffer");
+            continue;
+        }
+
+        let pixel_bytes = bytes_per_pixel(readback.format).unwrap_or(4);
+        let row_bytes = (readback.size.x * pixel_bytes) as usize;
+        let rows_len = readback.padded_bytes_per_row as u64 * readback.size.y as u64;
+        let slice = staging.buffer.slice(..);
+        let mut data = Vec::with_capacity(row_bytes * readback.size.y as usize);
+        for row in slice.get_mapped_range()[..rows_len as usize]
+            .chunks_exact(readback.padded_bytes_per_row as usize)
+        {
+            data.extend_from_slice(&row[..row_bytes]);
+        }
+        staging.buffer.unmap();
+
+        if matches!(
+            readback.format,
+            TextureFormat::Bgra8UnormSrgb | TextureFormat::Bgra8Unorm
+        ) {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        let mut linear = None;
+        if readback.format == TextureFormat::Rgba16Float {
+            let pixels: Vec<[f32; 4]> = data
+                .chunks_exact(8)
+                .map(|pixel| {
+                    let channel =
+                        |i: usize| f16_to_f32(u16::from_le_bytes([pixel[i], pixel[i + 1]]));
+                    [channel(0), channel(2), channel(4), channel(6)]
+                })
+                .collect();
+            data = pixels
+                .iter()
+                .flat_map(|&[r, g, b, a]| Srgba::from(LinearRgba::new(r, g, b, a)).to_u8_array())
+                .collect();
+            linear = Some(pixels);
+        }
+        let _ = sender.0.send(ReadbackComplete {
+            image: readback.image,
+            region: readback.region,
+            source: readback.source,
+            origin: readback.origin,
+            size: readback.size,
+            data,
+            linear,
+        });
+    }
+
+    staging.frame += 1;
+    let frame = staging.frame;
+    staging.buffers.retain(|staging| {
+        !matches!(staging.state, StagingState::Free) || frame - staging.last_used < IDLE_FRAMES
+    });
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f32::from(bits & 0x3ff);
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        31 if mantissa == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
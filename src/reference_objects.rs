@@ -0,0 +1,263 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        primitives::Aabb,
+        render_asset::RenderAssetUsages,
+        view::RenderLayers,
+    },
+};
+
+use crate::{
+    camera::world_bounds,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    RenderCube, ViewportCamera,
+};
+
+/// Layer the reference objects render on, so scene lights, thumbnails and sprite sheets
+/// leave them out.
+const REFERENCE_LAYER: usize = 28;
+/// Room left between the scene and the first object, and between objects.
+const SPACING: f32 = 1.0;
+
+/// Props of known size to judge the scale of the scene by: a 1 m cube, a 1.75 m figure and a
+/// banana. Each is toggled from the View menu and stands on the scene's floor beside it. They
+/// are drawn unlit on a render layer of their own and are not part of the saved scene, but can
+/// be picked and moved like anything else.
+pub struct ReferenceObjectsPlugin;
+
+impl Plugin for ReferenceObjectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToggleReference>()
+            .add_systems(
+                Update,
+                (
+                    toggle_reference_system,
+                    reference_layer_system,
+                    draw_reference_cube_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::View, "Reference Cube (1 m)", |world| {
+                    world.send_event(ToggleReference(ReferenceObject::Cube));
+                })
+                .separator_before(),
+            )
+            .add_menu_item(MenuItem::new(
+                Menu::View,
+                "Reference Figure (1.75 m)",
+                |world| {
+                    world.send_event(ToggleReference(ReferenceObject::Figure));
+                },
+            ))
+            .add_menu_item(MenuItem::new(Menu::View, "Reference Banana", |world| {
+                world.send_event(ToggleReference(ReferenceObject::Banana));
+            }));
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum ReferenceObject {
+    Cube,
+    Figure,
+    Banana,
+}
+
+impl ReferenceObject {
+    /// Its place in the row beside the scene.
+    fn slot(self) -> f32 {
+        match self {
+            ReferenceObject::Cube => 0.0,
+            ReferenceObject::Figure => 1.0,
+            ReferenceObject::Banana => 2.0,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ReferenceObject::Cube => "1 m cube",
+            ReferenceObject::Figure => "1.75 m figure",
+            ReferenceObject::Banana => "Banana",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            ReferenceObject::Cube => Color::srgb(0.55, 0.6, 0.7),
+            ReferenceObject::Figure => Color::srgb(0.3, 0.34, 0.42),
+            ReferenceObject::Banana => Color::srgb(0.95, 0.8, 0.2),
+        }
+    }
+
+    /// The object's mesh, standing on the origin.
+    fn mesh(self) -> Mesh {
+        match self {
+            ReferenceObject::Cube => {
+                Mesh::from(Cuboid::new(1.0, 1.0, 1.0)).translated_by(Vec3::Y * 0.5)
+            }
+            ReferenceObject::Figure => figure_mesh(),
+            ReferenceObject::Banana => banana_mesh(),
+        }
+    }
+}
+
+/// Spawns the object if it is not in the scene, and removes it if it is.
+#[derive(Event)]
+struct ToggleReference(ReferenceObject);
+
+fn toggle_reference_system(
+    mut events: EventReader<ToggleReference>,
+    mut commands: Commands,
+    existing: Query<(Entity, &ReferenceObject)>,
+    scene: Query<(&GlobalTransform, &Aabb), With<RenderCube>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for ToggleReference(object) in events.read() {
+        if let Some((entity, _)) = existing.iter().find(|(_, kind)| *kind == object) {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // A row along the scene's left edge, on the floor it stands on.
+        let (min, max) = scene
+            .iter()
+            .map(|(transform, aabb)| world_bounds(transform, aabb))
+            .reduce(|(min, max), (low, high)| (min.min(low), max.max(high)))
+            .unwrap_or((Vec3::ZERO, Vec3::ZERO));
+        let translation = Vec3::new(
+            min.x - SPACING * (1.0 + object.slot()) - 0.5,
+            min.y,
+            (min.z + max.z) * 0.5,
+        );
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(object.mesh()),
+                material: materials.add(StandardMaterial {
+                    base_color: object.color(),
+                    unlit: true,
+                    ..default()
+                }),
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            RenderLayers::layer(REFERENCE_LAYER),
+            Name::new(object.name()),
+            *object,
+        ));
+    }
+}
+
+/// Shows the reference layer in the viewport while any object is out.
+fn reference_layer_system(
+    objects: Query<(), With<ReferenceObject>>,
+    mut cameras: Query<&mut RenderLayers, With<ViewportCamera>>,
+) {
+    let shown = !objects.is_empty();
+    for mut layers in &mut cameras {
+        if layers.intersects(&RenderLayers::layer(REFERENCE_LAYER)) != shown {
+            *layers = if shown {
+                layers.clone().with(REFERENCE_LAYER)
+            } else {
+                layers.clone().without(REFERENCE_LAYER)
+            };
+        }
+    }
+}
+
+/// Unlit, the cube's faces are all one colour, so its edges are outlined.
+fn draw_reference_cube_system(
+    objects: Query<(&ReferenceObject, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    for (object, transform) in &objects {
+        if *object == ReferenceObject::Cube {
+            let local = Transform::from_translation(Vec3::Y * 0.5);
+            gizmos.cuboid(transform.mul_transform(local), Color::srgb(0.2, 0.22, 0.28));
+        }
+    }
+}
+
+/// A figure 1.75 m tall, built from capsules, facing +Z.
+fn figure_mesh() -> Mesh {
+    let limb = |radius: f32, from: Vec3, to: Vec3| {
+        let length = from.distance(to);
+        Mesh::from(Capsule3d::new(radius, length)).transformed_by(
+            Transform::from_translation((from + to) * 0.5)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Y, (to - from) / length)),
+        )
+    };
+    let mut mesh = Sphere::new(0.11)
+        .mesh()
+        .uv(24, 12)
+        .translated_by(Vec3::Y * 1.64);
+    for part in [
+        // Torso and neck.
+        limb(0.16, Vec3::Y * 1.0, Vec3::Y * 1.32),
+        limb(0.05, Vec3::Y * 1.4, Vec3::Y * 1.52),
+        // Legs.
+        limb(0.07, Vec3::new(-0.1, 0.9, 0.0), Vec3::new(-0.11, 0.07, 0.0)),
+        limb(0.07, Vec3::new(0.1, 0.9, 0.0), Vec3::new(0.11, 0.07, 0.0)),
+        // Arms, hanging a little away from the body.
+        limb(
+            0.05,
+            Vec3::new(-0.21, 1.42, 0.0),
+            Vec3::new(-0.3, 0.82, 0.0),
+        ),
+        limb(0.05, Vec3::new(0.21, 1.42, 0.0), Vec3::new(0.3, 0.82, 0.0)),
+    ] {
+        mesh.merge(&part);
+    }
+    mesh
+}
+
+/// A banana about 20 cm long, lying on its side and tapered at both ends.
+fn banana_mesh() -> Mesh {
+    const RINGS: u32 = 24;
+    const SIDES: u32 = 12;
+    const BEND_RADIUS: f32 = 0.14;
+    const BEND: f32 = 1.5;
+    const THICKNESS: f32 = 0.018;
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for ring in 0..=RINGS {
+        let t = ring as f32 / RINGS as f32;
+        // Along an arc in the XY plane, bowing down toward the floor in the middle.
+        let angle = -FRAC_PI_2 + (t - 0.5) * BEND;
+        let center = Vec3::new(angle.cos(), angle.sin() + 1.0, 0.0) * BEND_RADIUS;
+        let outward = Vec3::new(angle.cos(), angle.sin(), 0.0);
+        let radius = THICKNESS * (PI * t).sin().powf(0.6);
+        for side in 0..=SIDES {
+            let s = side as f32 / SIDES as f32;
+            let around = s * 2.0 * PI;
+            let normal = outward * around.cos() + Vec3::Z * around.sin();
+            positions.push((center + normal * radius).to_array());
+            normals.push(normal.to_array());
+            uvs.push([s, t]);
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..RINGS {
+        for side in 0..SIDES {
+            let a = ring * (SIDES + 1) + side;
+            let b = a + 1;
+            let c = a + SIDES + 2;
+            let d = a + SIDES + 1;
+            indices.extend([a, d, c, c, b, a]);
+        }
+    }
+    // The middle of the arc touches y = 0; lifted by its thickness, it rests on the floor.
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+    .translated_by(Vec3::Y * THICKNESS)
+}
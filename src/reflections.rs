@@ -0,0 +1,208 @@
+use bevy::{
+    core_pipeline::prepass::{DeferredPrepass, DepthPrepass},
+    pbr::{
+        DefaultOpaqueRendererMethod, ScreenSpaceAmbientOcclusionSettings,
+        ScreenSpaceReflectionsBundle, ScreenSpaceReflectionsSettings,
+    },
+    prelude::*,
+};
+use bevy_egui::egui;
+
+use crate::{
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    selection::material_edit,
+    settings::Settings,
+    ViewportCamera,
+};
+
+/// The Reflections window: a reflective ground plane and screen-space reflection settings.
+pub struct ReflectionsPlugin;
+
+impl Plugin for ReflectionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ReflectionsWindow>().add_systems(
+            Update,
+            reflections_window_system.in_set(ReflectionsWindow::ui_set()),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct ReflectionsWindow {
+    pub is_open: bool,
+    ground: Option<Entity>,
+    ground_height: f32,
+}
+
+impl Default for ReflectionsWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            ground: None,
+            ground_height: -4.0,
+        }
+    }
+}
+
+impl Panel for ReflectionsWindow {
+    const TITLE: &'static str = "Reflections";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn reflections_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ReflectionsWindow>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut grounds: Query<(&mut Transform, &Handle<StandardMaterial>), Without<ViewportCamera>>,
+    cameras: Query<
+        (
+            Entity,
+            Option<&ScreenSpaceReflectionsSettings>,
+            Has<ScreenSpaceAmbientOcclusionSettings>,
+        ),
+        With<ViewportCamera>,
+    >,
+    mut opaque_method: ResMut<DefaultOpaqueRendererMethod>,
+    mut settings: ResMut<Settings>,
+) {
+    let ReflectionsWindow {
+        is_open,
+        ground,
+        ground_height,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Reflections")
+        .open(is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<ReflectionsWindow>(), |ui| {
+            let mut show_ground = ground.is_some();
+            if ui
+                .checkbox(&mut show_ground, "Reflective ground plane")
+                .changed()
+            {
+                match ground.take() {
+                    Some(entity) => commands.entity(entity).despawn_recursive(),
+                    None => {
+                        let material = StandardMaterial {
+                            base_color: Color::srgb(0.2, 0.2, 0.22),
+                            perceptual_roughness: 0.1,
+                            reflectance: 0.5,
+                            ..default()
+                        };
+                        let entity = commands
+                            .spawn(PbrBundle {
+                                mesh: meshes.add(Plane3d::default().mesh().size(60.0, 60.0)),
+                                material: materials.add(material),
+                                transform: Transform::from_xyz(0.0, *ground_height, 0.0),
+                                ..default()
+                            })
+                            .insert(Name::new("Ground"))
+                            .id();
+                        *ground = Some(entity);
+                    }
+                }
+            }
+            if let Some(Ok((mut transform, material))) = ground.map(|e| grounds.get_mut(e)) {
+                ui.horizontal(|ui| {
+                    ui.label("Height");
+                    if ui.add(drag_value(ground_height).speed(0.05)).changed() {
+                        transform.translation.y = *ground_height;
+                    }
+                });
+                if let Some(mut edited) = materials.get(material).cloned() {
+                    if material_edit(ui, &mut edited) {
+                        materials.insert(material, edited);
+                    }
+                }
+            }
+
+            ui.separator();
+            let Ok((camera, ssr, has_ssao)) = cameras.get_single() else {
+                return;
+            };
+            let mut enabled = ssr.is_some();
+            if ui
+                .checkbox(&mut enabled, "Screen-space reflections")
+                .on_hover_text("Not supported on WebGL2")
+                .changed()
+            {
+                if enabled {
+                    commands
+                        .entity(camera)
+                        .insert(ScreenSpaceReflectionsBundle::default());
+                    *opaque_method = DefaultOpaqueRendererMethod::deferred();
+                } else {
+                    commands
+                        .entity(camera)
+                        .remove::<(ScreenSpaceReflectionsSettings, DeferredPrepass)>();
+                    // SSAO needs the depth prepass too.
+                    if !has_ssao {
+                        commands.entity(camera).remove::<DepthPrepass>();
+                    }
+                    *opaque_method = DefaultOpaqueRendererMethod::forward();
+                }
+                // SSR only sees deferred-shaded surfaces, and materials pick their renderer
+                // method when prepared, so have every material prepared again.
+                materials.iter_mut().for_each(drop);
+            }
+            let Some(ssr) = ssr else {
+                return;
+            };
+
+            let mut edited = *ssr;
+            let mut changed = false;
+            egui::Grid::new("ssr_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Roughness threshold");
+                changed |= ui
+                    .add(egui::Slider::new(
+                        &mut edited.perceptual_roughness_threshold,
+                        0.0..=1.0,
+                    ))
+                    .changed();
+                ui.end_row();
+                ui.label("Thickness");
+                changed |= ui
+                    .add(egui::Slider::new(&mut edited.thickness, 0.01..=2.0).logarithmic(true))
+                    .changed();
+                ui.end_row();
+                ui.label("Linear steps");
+                changed |= ui
+                    .add(egui::Slider::new(&mut edited.linear_steps, 4..=64))
+                    .changed();
+                ui.end_row();
+                ui.label("Bisection steps");
+                changed |= ui
+                    .add(egui::Slider::new(&mut edited.bisection_steps, 0..=16))
+                    .changed();
+                ui.end_row();
+                ui.label("Secant refinement");
+                changed |= ui.checkbox(&mut edited.use_secant, "").changed();
+                ui.end_row();
+            });
+            if changed {
+                commands.entity(camera).insert(edited);
+            }
+            if settings.graphics.msaa_samples != 1 {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Deferred rendering needs MSAA off.",
+                    );
+                    if ui.button("Turn MSAA off").clicked() {
+                        settings.graphics.msaa_samples = 1;
+                    }
+                });
+            }
+        });
+}
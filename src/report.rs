@@ -0,0 +1,240 @@
+use std::fmt::Write as _;
+
+use base64::Engine as _;
+use bevy::prelude::*;
+
+use crate::{
+    errors::AppError,
+    groups::Group,
+    icons::Icon,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests, ReadbackSource},
+    scene::{Project, SceneId},
+    RenderCube, RestRotation, ViewImage,
+};
+
+pub const REPORT_PATH: &str = "report.html";
+
+/// Exports a self-contained HTML scene review: a viewport screenshot, the entity table,
+/// material summaries and the project notes.
+pub struct ReportPlugin;
+
+impl Plugin for ReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportReport>()
+            .add_event::<ReportScreenshot>()
+            .add_systems(Update, (request_report_system, write_report_system).chain())
+            .add_menu_item(
+                MenuItem::new(Menu::File, "Export Report", |world| {
+                    world.send_event(ExportReport);
+                })
+                .icon(Icon::Report)
+                .in_toolbar(),
+            );
+    }
+}
+
+#[derive(Event)]
+pub struct ExportReport;
+
+fn request_report_system(
+    mut events: EventReader<ExportReport>,
+    mut requests: ResMut<ReadbackRequests>,
+    view_image: Res<ViewImage>,
+    // Set while waiting for the screenshot readback.
+    mut pending: Local<bool>,
+    mut readbacks: EventReader<ReadbackComplete>,
+    mut screenshots: EventWriter<ReportScreenshot>,
+) {
+    if events.read().count() > 0 {
+        requests.request(&view_image);
+        *pending = true;
+    }
+    if !*pending {
+        readbacks.clear();
+        return;
+    }
+    let id = view_image.id();
+    if let Some(readback) = readbacks
+        .read()
+        .filter(|readback| {
+            readback.image == id
+                && readback.region.is_none()
+                && readback.source == ReadbackSource::Output
+        })
+        .last()
+    {
+        *pending = false;
+        screenshots.send(ReportScreenshot {
+            size: readback.size,
+            data: readback.data.clone(),
+        });
+    }
+}
+
+#[derive(Event)]
+struct ReportScreenshot {
+    size: UVec2,
+    data: Vec<u8>,
+}
+
+pub fn encode_png(size: UVec2, rgba: &[u8]) -> Result<Vec<u8>, png::EncodingError> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, size.x, size.y);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(bytes)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn hex(color: Color) -> String {
+    let [r, g, b, _] = color.to_srgba().to_u8_array();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn vec3(value: Vec3) -> String {
+    format!("{:.2}, {:.2}, {:.2}", value.x, value.y, value.z)
+}
+
+#[allow(clippy::type_complexity)]
+fn write_report_system(
+    mut screenshots: EventReader<ReportScreenshot>,
+    project: Res<Project>,
+    cubes: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&RestRotation>,
+            Option<&SceneId>,
+            Option<&Parent>,
+            &Handle<StandardMaterial>,
+        ),
+        With<RenderCube>,
+    >,
+    groups: Query<Option<&SceneId>, With<Group>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut errors: EventWriter<AppError>,
+) {
+    let Some(screenshot) = screenshots.read().last() else {
+        return;
+    };
+
+    let image = match encode_png(screenshot.size, &screenshot.data) {
+        Ok(png) => base64::engine::general_purpose::STANDARD.encode(png),
+        Err(err) => {
+            errors.send(AppError::new(
+                "Report",
+                format!("Failed to encode the report screenshot: {err}"),
+            ));
+            return;
+        }
+    };
+
+    let mut entities: Vec<_> = cubes.iter().collect();
+    entities.sort_by_key(|(entity, ..)| *entity);
+    let mut material_order: Vec<AssetId<StandardMaterial>> = Vec::new();
+    let mut table = String::new();
+    for (entity, transform, rest, id, parent, material) in &entities {
+        let index = match material_order.iter().position(|m| *m == material.id()) {
+            Some(index) => index,
+            None => {
+                material_order.push(material.id());
+                material_order.len() - 1
+            }
+        };
+        let rotation = rest.map_or(transform.rotation, |rest| **rest);
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        let group = parent
+            .and_then(|parent| groups.get(parent.get()).ok())
+            .map_or(String::new(), |id| {
+                id.map_or("yes".to_owned(), |id| format!("{:08x}", **id >> 32))
+            });
+        let _ = writeln!(
+            table,
+            "<tr><td>{entity}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{group}</td><td>M{}</td></tr>",
+            id.map_or(String::new(), |id| format!("{:08x}", **id >> 32)),
+            vec3(transform.translation),
+            vec3(Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees())),
+            vec3(transform.scale),
+            index + 1,
+        );
+    }
+
+    let mut material_rows = String::new();
+    for (index, id) in material_order.iter().enumerate() {
+        let Some(material) = materials.get(*id) else {
+            continue;
+        };
+        let users = entities
+            .iter()
+            .filter(|(.., handle)| handle.id() == *id)
+            .count();
+        let color = hex(material.base_color);
+        let _ = writeln!(
+            material_rows,
+            "<tr><td>M{}</td><td><span class=\"swatch\" style=\"background:{color}\"></span>{color}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{}</td><td>{users}</td></tr>",
+            index + 1,
+            material.metallic,
+            material.perceptual_roughness,
+            material.reflectance,
+            if material.unlit { "yes" } else { "no" },
+        );
+    }
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Scene report</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; margin-bottom: 2em; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; font-size: 13px; }}
+th {{ background: #f0f0f0; }}
+.swatch {{ display: inline-block; width: 12px; height: 12px; margin-right: 6px; border: 1px solid #888; vertical-align: middle; }}
+pre {{ background: #f7f7f7; padding: 1em; white-space: pre-wrap; }}
+</style>
+</head>
+<body>
+<h1>Scene report</h1>
+<h2>Viewport</h2>
+<img src="data:image/png;base64,{image}" width="{width}" height="{height}">
+<h2>Entities ({entity_count})</h2>
+<table>
+<tr><th>Entity</th><th>Scene id</th><th>Translation</th><th>Rotation (°)</th><th>Scale</th><th>Group</th><th>Material</th></tr>
+{table}</table>
+<h2>Materials ({material_count})</h2>
+<table>
+<tr><th>Material</th><th>Base color</th><th>Metallic</th><th>Roughness</th><th>Reflectance</th><th>Unlit</th><th>Used by</th></tr>
+{material_rows}</table>
+<h2>Notes</h2>
+<pre>{notes}</pre>
+</body>
+</html>
+"#,
+        width = screenshot.size.x,
+        height = screenshot.size.y,
+        entity_count = entities.len(),
+        material_count = material_order.len(),
+        notes = escape(&project.notes),
+    );
+
+    match std::fs::write(REPORT_PATH, html) {
+        Ok(()) => info!("Exported report to {REPORT_PATH}"),
+        Err(err) => {
+            errors.send(
+                AppError::new("Report", format!("Failed to write {REPORT_PATH}: {err}")).suggest(
+                    "Close report.html if another program holds it open, then export again.",
+                ),
+            );
+        }
+    }
+}
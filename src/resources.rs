@@ -0,0 +1,105 @@
+use bevy::{ecs::entity::Entities, prelude::*};
+use bevy_egui::egui;
+
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+use crate::pool::CubePool;
+use crate::RenderCube;
+
+/// Shows what the sandbox is holding on to: live entities, loaded assets and the cube pool.
+pub struct ResourcesPlugin;
+
+impl Plugin for ResourcesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ResourcesWindow>().add_systems(
+            Update,
+            resources_window_system.in_set(ResourcesWindow::ui_set()),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct ResourcesWindow {
+    pub is_open: bool,
+}
+
+impl Panel for ResourcesWindow {
+    const TITLE: &'static str = "Resources";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn resources_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ResourcesWindow>,
+    mut commands: Commands,
+    mut pool: ResMut<CubePool>,
+    entities: &Entities,
+    cubes: Query<(), With<RenderCube>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    images: Res<Assets<Image>>,
+) {
+    let ResourcesWindow { is_open } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(ResourcesWindow::TITLE)
+        .open(is_open)
+        .default_width(260.0)
+        .show(contexts.ctx::<ResourcesWindow>(), |ui| {
+            egui::Grid::new("resource_counts")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    let rows = [
+                        ("Entities", entities.len() as usize),
+                        ("Cubes", cubes.iter().len()),
+                        ("Meshes", meshes.len()),
+                        ("Materials", materials.len()),
+                        ("Images", images.len()),
+                    ];
+                    for (label, count) in rows {
+                        ui.label(label);
+                        ui.monospace(count.to_string());
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+            ui.strong("Cube pool");
+            egui::Grid::new("cube_pool")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    let spawned = pool.allocated + pool.reused;
+                    let hit_rate = if spawned > 0 {
+                        pool.reused as f32 / spawned as f32
+                    } else {
+                        0.0
+                    };
+                    let rows = [
+                        ("Pooled", pool.free_count().to_string()),
+                        ("Allocated", pool.allocated.to_string()),
+                        ("Reused", pool.reused.to_string()),
+                        ("Released", pool.released.to_string()),
+                        ("Reuse rate", format!("{:.0}%", hit_rate * 100.0)),
+                    ];
+                    for (label, value) in rows {
+                        ui.label(label);
+                        ui.monospace(value);
+                        ui.end_row();
+                    }
+                });
+            if ui
+                .add_enabled(pool.free_count() > 0, egui::Button::new("Drain pool"))
+                .on_hover_text("Despawn the pooled cubes and free their materials")
+                .clicked()
+            {
+                pool.drain(&mut commands);
+            }
+        });
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    errors::AppError,
+    settings::{Settings, SETTINGS_PATH},
+    status_bar::StatusBar,
+};
+
+/// Shown when the persisted settings cannot be read or no longer deserialize, e.g. after an
+/// update changed their shape. The sandbox starts on defaults and leaves the old file alone until the user chooses to
+/// migrate it, delete it or keep running on defaults.
+pub struct SafeModePlugin;
+
+impl Plugin for SafeModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            safe_mode_banner_system
+                .in_set(crate::UiSet::Panels)
+                .after(crate::menu_bar_system),
+        );
+    }
+}
+
+/// Present only while running on defaults because the settings file was rejected.
+#[derive(Resource)]
+pub struct SafeMode {
+    reason: String,
+    collapsed: bool,
+}
+
+impl SafeMode {
+    pub fn new(reason: String) -> Self {
+        Self {
+            reason,
+            collapsed: false,
+        }
+    }
+}
+
+fn safe_mode_banner_system(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    safe_mode: Option<ResMut<SafeMode>>,
+    mut settings: ResMut<Settings>,
+    time: Res<Time>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+) {
+    let Some(mut safe_mode) = safe_mode else {
+        return;
+    };
+
+    let mut resolved = false;
+    egui::TopBottomPanel::top("safe_mode_banner").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("⚠ Safe mode: {SETTINGS_PATH} could not be read, so defaults are in use."),
+            );
+            let details = if safe_mode.collapsed {
+                "Details"
+            } else {
+                "Hide details"
+            };
+            if ui.link(details).clicked() {
+                safe_mode.collapsed = !safe_mode.collapsed;
+            }
+            if ui
+                .button("Migrate")
+                .on_hover_text("Keep every option that still parses and drop the rest")
+                .clicked()
+            {
+                match Settings::migrate() {
+                    Ok((migrated, dropped)) => {
+                        *settings = migrated;
+                        match settings.save() {
+                            Ok(()) => {
+                                let message = if dropped.is_empty() {
+                                    "Migrated settings; nothing was dropped".to_owned()
+                                } else {
+                                    format!("Migrated settings; reset {}", dropped.join(", "))
+                                };
+                                info!("{message}");
+                                status.flash(message, time.elapsed_seconds());
+                                resolved = true;
+                            }
+                            Err(err) => {
+                                errors.send(err);
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        errors.send(
+                            AppError::new("Settings", format!("Could not migrate: {err}"))
+                                .suggest("Delete the old settings file instead."),
+                        );
+                    }
+                }
+            }
+            if ui
+                .button("Delete old settings")
+                .on_hover_text(format!(
+                    "Remove {SETTINGS_PATH}; autosave will write the defaults"
+                ))
+                .clicked()
+            {
+                match Settings::delete_file() {
+                    Ok(()) => resolved = true,
+                    Err(err) => {
+                        errors.send(AppError::new(
+                            "Settings",
+                            format!("Failed to delete {SETTINGS_PATH}: {err}"),
+                        ));
+                    }
+                }
+            }
+        });
+        if !safe_mode.collapsed {
+            ui.weak(format!(
+                "Ignored: {}. Autosave is paused so the old file is kept until you choose.",
+                safe_mode.reason
+            ));
+        }
+    });
+
+    if resolved {
+        commands.remove_resource::<SafeMode>();
+    }
+}
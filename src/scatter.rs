@@ -0,0 +1,405 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::egui;
+use rand::Rng;
+
+use crate::{
+    boids::closest_on_box,
+    history::{DriveExt, Driver},
+    numeric::drag_value,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{SceneId, SceneReader, SceneWriter},
+    selection::Selection,
+    RenderCube, RestRotation,
+};
+
+const GRAVITY: Vec3 = Vec3::new(0.0, -9.81, 0.0);
+const SUBSTEPS: usize = 4;
+/// Overlap relaxation passes per substep.
+const ITERATIONS: usize = 4;
+/// Fraction of the speed into a surface that bounces back.
+const RESTITUTION: f32 = 0.2;
+/// Fraction of the sliding speed lost per substep while touching something.
+const FRICTION: f32 = 0.15;
+/// Fraction of the spin kept per substep.
+const ANGULAR_DAMPING: f32 = 0.98;
+/// Speed below which a body counts as still.
+const REST_SPEED: f32 = 0.05;
+/// Seconds a body must stay still to be settled.
+const REST_TIME: f32 = 0.5;
+/// Seconds after which the pile is baked however much it still moves.
+const MAX_SETTLE_TIME: f32 = 15.0;
+/// Longest tick simulated, so a very low tick rate does not explode the pile.
+const MAX_DELTA: f32 = 1.0 / 30.0;
+
+/// Drops copies of the primary selection from above the ground and lets them tumble into a
+/// pile on the ground and the cubes already there. Once everything has come to rest the
+/// transforms are kept and the simulation state removed. There is no physics engine in the
+/// sandbox, so each copy collides as a sphere that fits its box; piles come out loose but
+/// natural looking.
+pub struct ScatterPlugin;
+
+impl Plugin for ScatterPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ScatterWindow>()
+            .add_systems(
+                Update,
+                (
+                    scatter_window_system.in_set(ScatterWindow::ui_set()),
+                    drop_copies_system,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (simulate_scatter_system, bake_settled_system).chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Scatter…", |world| {
+                world.resource_mut::<ScatterWindow>().is_open = true;
+            }));
+    }
+}
+
+/// A copy still falling or settling.
+#[derive(Component)]
+struct ScatterBody {
+    velocity: Vec3,
+    spin: Vec3,
+    radius: f32,
+    /// Seconds spent below [`REST_SPEED`].
+    still_for: f32,
+}
+
+#[derive(Resource)]
+pub struct ScatterWindow {
+    pub is_open: bool,
+    copies: usize,
+    /// Radius of the disc the copies are dropped over, around the original.
+    spread: f32,
+    /// Height above the ground of the lowest copy.
+    height: f32,
+    ground: f32,
+    drop: bool,
+    /// Bake the pile as it is, or throw it away.
+    finish: Option<Finish>,
+    /// Seconds since the copies were dropped.
+    elapsed: f32,
+}
+
+#[derive(Clone, Copy)]
+enum Finish {
+    Bake,
+    Cancel,
+}
+
+impl Default for ScatterWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            copies: 30,
+            spread: 2.0,
+            height: 4.0,
+            ground: 0.0,
+            drop: false,
+            finish: None,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl Panel for ScatterWindow {
+    const TITLE: &'static str = "Scatter";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn scatter_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ScatterWindow>,
+    selection: Res<Selection>,
+    cubes: Query<(), With<RenderCube>>,
+    bodies: Query<&ScatterBody>,
+) {
+    let ScatterWindow {
+        is_open,
+        copies,
+        spread,
+        height,
+        ground,
+        drop,
+        finish,
+        elapsed,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let has_source = selection
+        .primary()
+        .is_some_and(|primary| cubes.contains(primary));
+    let falling = bodies.iter().len();
+    egui::Window::new(ScatterWindow::TITLE)
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<ScatterWindow>(), |ui| {
+            egui::Grid::new("scatter_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Copies");
+                    ui.add(drag_value(copies).range(1..=500));
+                    ui.end_row();
+                    ui.label("Spread");
+                    ui.add(drag_value(spread).speed(0.05).range(0.0..=100.0))
+                        .on_hover_text("Radius of the area the copies fall over");
+                    ui.end_row();
+                    ui.label("Drop height");
+                    ui.add(drag_value(height).speed(0.05).range(0.0..=100.0));
+                    ui.end_row();
+                    ui.label("Ground height");
+                    ui.add(drag_value(ground).speed(0.05));
+                    ui.end_row();
+                });
+            ui.separator();
+            if falling == 0 {
+                if ui
+                    .add_enabled(has_source, egui::Button::new("Drop copies"))
+                    .on_disabled_hover_text("Select a cube to scatter")
+                    .clicked()
+                {
+                    *drop = true;
+                }
+                return;
+            }
+            let settled = bodies
+                .iter()
+                .filter(|body| body.still_for >= REST_TIME)
+                .count();
+            ui.label(format!(
+                "Settling: {settled} of {falling} at rest after {elapsed:.1} s"
+            ));
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Bake now")
+                    .on_hover_text("Keep the copies where they are")
+                    .clicked()
+                {
+                    *finish = Some(Finish::Bake);
+                }
+                if ui
+                    .button("Cancel")
+                    .on_hover_text("Remove the copies")
+                    .clicked()
+                {
+                    *finish = Some(Finish::Cancel);
+                }
+            });
+        });
+}
+
+/// Spawns the copies spread over a disc above the original, stacked so none start inside
+/// another, with a random orientation each. The copies are static, so the animation does not
+/// turn them away from how they land.
+fn drop_copies_system(
+    mut commands: Commands,
+    mut window: ResMut<ScatterWindow>,
+    mut selection: ResMut<Selection>,
+    mut scene: ParamSet<(SceneReader, SceneWriter)>,
+    sources: Query<(&SceneId, &GlobalTransform, Option<&Aabb>)>,
+) {
+    if !std::mem::take(&mut window.drop) {
+        return;
+    }
+    let Some((id, transform, aabb)) = selection
+        .primary()
+        .and_then(|primary| sources.get(primary).ok())
+    else {
+        return;
+    };
+    let Some(template) = scene
+        .p0()
+        .capture()
+        .entities
+        .into_iter()
+        .find(|entity| entity.id == **id)
+    else {
+        return;
+    };
+    let (scale, _, origin) = transform.to_scale_rotation_translation();
+    let half_extents = aabb.map_or(Vec3::splat(0.5), |aabb| aabb.half_extents.into()) * scale;
+    // The mean of the half extents, so boxes rest a little into each other instead of
+    // floating apart on their corners.
+    let radius = ((half_extents.x + half_extents.y + half_extents.z) / 3.0).max(0.01);
+
+    let mut rng = rand::thread_rng();
+    let mut writer = scene.p1();
+    let mut dropped = Vec::new();
+    for index in 0..window.copies {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = window.spread * rng.gen::<f32>().sqrt();
+        let translation = Vec3::new(
+            origin.x + angle.cos() * distance,
+            window.ground + window.height + radius + index as f32 * radius * 2.0,
+            origin.z + angle.sin() * distance,
+        );
+        let rotation = Quat::from_euler(
+            EulerRot::XYZ,
+            rng.gen_range(0.0..std::f32::consts::TAU),
+            rng.gen_range(0.0..std::f32::consts::TAU),
+            rng.gen_range(0.0..std::f32::consts::TAU),
+        );
+        let mut entity = template.clone();
+        entity.id = 0;
+        entity.group = None;
+        entity.translation = translation.to_array();
+        entity.rotation = rotation.to_array();
+        entity.scale = scale.to_array();
+        entity.is_static = true;
+        let copy = writer.spawn(&entity, None);
+        commands.entity(copy).insert((
+            RestRotation(rotation),
+            ScatterBody {
+                velocity: Vec3::ZERO,
+                spin: Vec3::new(
+                    rng.gen_range(-2.0..2.0),
+                    rng.gen_range(-2.0..2.0),
+                    rng.gen_range(-2.0..2.0),
+                ),
+                radius,
+                still_for: 0.0,
+            },
+        ));
+        commands.drive(copy, Driver::Scatter, true);
+        dropped.push(copy);
+    }
+    selection.entities = dropped;
+    window.elapsed = 0.0;
+}
+
+#[allow(clippy::type_complexity)]
+fn simulate_scatter_system(
+    time: Res<Time>,
+    mut window: ResMut<ScatterWindow>,
+    mut bodies: Query<(Entity, &mut Transform, &mut RestRotation, &mut ScatterBody)>,
+    obstacles: Query<(&GlobalTransform, &Aabb), (With<RenderCube>, Without<ScatterBody>)>,
+) {
+    if bodies.is_empty() {
+        return;
+    }
+    let delta = time.delta_seconds().min(MAX_DELTA);
+    window.elapsed += delta;
+    let step = delta / SUBSTEPS as f32;
+    let ground = window.ground;
+    // Bounding spheres first, so distant cubes cost one distance check.
+    let obstacles: Vec<_> = obstacles
+        .iter()
+        .map(|(transform, aabb)| {
+            let (scale, _, _) = transform.to_scale_rotation_translation();
+            let center = transform.transform_point(aabb.center.into());
+            let reach = (Vec3::from(aabb.half_extents) * scale.abs()).length();
+            (transform, aabb, center, reach)
+        })
+        .collect();
+
+    for _ in 0..SUBSTEPS {
+        let mut state: Vec<(Entity, Vec3, f32)> = bodies
+            .iter_mut()
+            .map(|(entity, mut transform, _, mut body)| {
+                body.velocity += GRAVITY * step;
+                transform.translation += body.velocity * step;
+                (entity, transform.translation, body.radius)
+            })
+            .collect();
+
+        // Push overlapping bodies apart, and out of the ground and the cubes already placed.
+        let mut touching = vec![false; state.len()];
+        for _ in 0..ITERATIONS {
+            for a in 0..state.len() {
+                for b in a + 1..state.len() {
+                    let offset = state[b].1 - state[a].1;
+                    let overlap = state[a].2 + state[b].2 - offset.length();
+                    if overlap > 0.0 {
+                        let push = offset.try_normalize().unwrap_or(Vec3::Y) * overlap / 2.0;
+                        state[a].1 -= push;
+                        state[b].1 += push;
+                        touching[a] = true;
+                        touching[b] = true;
+                    }
+                }
+            }
+            for (index, (_, position, radius)) in state.iter_mut().enumerate() {
+                if position.y < ground + *radius {
+                    position.y = ground + *radius;
+                    touching[index] = true;
+                }
+                for (transform, aabb, center, reach) in &obstacles {
+                    if position.distance_squared(*center) > (reach + *radius).powi(2) {
+                        continue;
+                    }
+                    let closest = closest_on_box(*position, aabb, transform);
+                    let offset = *position - closest;
+                    let distance = offset.length();
+                    if distance < *radius {
+                        *position = closest + offset.try_normalize().unwrap_or(Vec3::Y) * *radius;
+                        touching[index] = true;
+                    }
+                }
+            }
+        }
+
+        for ((entity, position, _), touching) in state.into_iter().zip(touching) {
+            let Ok((_, mut transform, mut rest, mut body)) = bodies.get_mut(entity) else {
+                continue;
+            };
+            // The velocity the corrections imply, so resting contacts stop the fall.
+            let corrected = (position - transform.translation) / step;
+            let mut velocity = body.velocity + corrected;
+            if touching {
+                let bounce = velocity.y.min(0.0);
+                velocity.y -= bounce * (1.0 + RESTITUTION);
+                velocity.x *= 1.0 - FRICTION;
+                velocity.z *= 1.0 - FRICTION;
+                // Roll in the direction of travel.
+                let rolling = Vec3::Y.cross(velocity) / body.radius;
+                body.spin = body.spin.lerp(rolling, FRICTION);
+            }
+            body.velocity = velocity;
+            body.spin *= ANGULAR_DAMPING;
+            transform.translation = position;
+            // Turned through the rest orientation, which the displayed rotation follows.
+            if let Some(axis) = body.spin.try_normalize() {
+                let turn = Quat::from_axis_angle(axis, body.spin.length() * step);
+                rest.0 = (turn * rest.0).normalize();
+            }
+            let still = touching && body.velocity.length() < REST_SPEED;
+            body.still_for = if still { body.still_for + step } else { 0.0 };
+        }
+    }
+}
+
+/// Removes the simulation state once the pile has come to rest, or when asked to.
+fn bake_settled_system(
+    mut commands: Commands,
+    mut window: ResMut<ScatterWindow>,
+    bodies: Query<(Entity, &ScatterBody)>,
+) {
+    if bodies.is_empty() {
+        window.finish = None;
+        return;
+    }
+    let settled = bodies.iter().all(|(_, body)| body.still_for >= REST_TIME);
+    let finish = match window.finish.take() {
+        Some(finish) => finish,
+        None if settled || window.elapsed >= MAX_SETTLE_TIME => Finish::Bake,
+        None => return,
+    };
+    for (entity, _) in &bodies {
+        match finish {
+            Finish::Bake => {
+                commands.entity(entity).remove::<ScatterBody>();
+                commands.drive(entity, Driver::Scatter, false);
+            }
+            Finish::Cancel => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
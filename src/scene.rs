@@ -0,0 +1,900 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::{query::ROQueryItem, system::SystemParam},
+    prelude::*,
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use xihydra_bevy::widgets::{CodeEditor, Language};
+
+use crate::{
+    batching::BakedBatch,
+    bookmarks::CameraBookmark,
+    csg::CsgMesh,
+    errors::AppError,
+    exploded_view::ExplodedPart,
+    fade::OpacityTrack,
+    groups::Group,
+    icons::Icon,
+    keybindings::Action,
+    lsystem::Plant,
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    pool::{CubePool, Pooled},
+    properties::Properties,
+    scripts::EntityScript,
+    selection_sets::SelectionSet,
+    session_stats::SessionEvent,
+    sprites::Sprite2d,
+    text3d::Text3d,
+    tilemap::TileLayer,
+    versioning::{unversioned, Migration, Versioned},
+    watch_folder::ImportedMesh,
+    RenderCube, RestRotation, Static,
+};
+
+pub const SCENE_PATH: &str = "scene.ron";
+
+/// Owns the project file: the spawned cubes plus everything that travels with them (notes,
+/// camera bookmarks, the tile layer, ...).
+pub struct ScenePlugin;
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Project>()
+            .init_resource::<SpawnQueue>()
+            .register_panel::<SceneSourceWindow>()
+            .add_event::<SaveScene>()
+            .add_event::<LoadScene>()
+            .add_systems(
+                Update,
+                (
+                    scene_source_window_system,
+                    save_scene_system,
+                    load_scene_system,
+                    spawn_queued_cubes_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::File, "Open Scene", |world| {
+                    world.send_event(LoadScene);
+                })
+                .icon(Icon::Open)
+                .shortcut(Action::OpenScene)
+                .in_toolbar(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::File, "Save Scene", |world| {
+                    world.send_event(SaveScene);
+                })
+                .icon(Icon::Save)
+                .shortcut(Action::SaveScene)
+                .in_toolbar(),
+            );
+    }
+}
+
+/// Scene-level data that is not stored on entities.
+#[derive(Default, Resource)]
+pub struct Project {
+    pub notes: String,
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    pub tile_layer: TileLayer,
+    pub selection_sets: Vec<SelectionSet>,
+}
+
+#[derive(Event)]
+pub struct SaveScene;
+
+#[derive(Event)]
+pub struct LoadScene;
+
+/// Identifies a cube or group across saves, so two versions of a scene file can be compared.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct SceneId(pub u64);
+
+impl SceneId {
+    pub fn random() -> Self {
+        Self(rand::random::<u64>().max(1))
+    }
+}
+
+/// On-disk representation of a project.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneFile {
+    /// Zero in files written before the format was versioned, see [`Versioned`].
+    pub version: u32,
+    pub notes: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    #[serde(skip_serializing_if = "TileLayer::is_empty")]
+    pub tile_layer: TileLayer,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub selection_sets: Vec<SelectionSet>,
+    pub groups: Vec<SceneGroup>,
+    pub entities: Vec<SceneEntity>,
+}
+
+impl Versioned for SceneFile {
+    const VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration<Self>] = &[unversioned()];
+    const FORMAT: &'static str = "scene";
+
+    fn version_mut(&mut self) -> &mut u32 {
+        &mut self.version
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    /// Zero in files written before ids existed.
+    #[serde(default)]
+    pub id: u64,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub color: [f32; 4],
+    /// Index into `SceneFile::groups`; the transform is then relative to that group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>,
+    /// Excluded from the spin animation, see [`Static`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_static: bool,
+    /// Set for 3D text, whose mesh is rebuilt from it on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<Text3d>,
+    /// Set for the result of a boolean operation, whose triangles are stored inline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csg: Option<CsgMesh>,
+    /// Set for a generated plant, whose mesh is regrown from it on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plant: Option<Plant>,
+    /// Custom key/value metadata.
+    #[serde(default, skip_serializing_if = "Properties::is_empty")]
+    pub properties: Properties,
+    /// Opacity keys played back from the timeline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fade: Option<OpacityTrack>,
+    /// A script run on the entity every frame.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<EntityScript>,
+    /// Set for a sprite, whose quad and texture are rebuilt from it on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprite: Option<Sprite2d>,
+    /// Set for an imported model, whose mesh is reloaded from the file on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh_file: Option<ImportedMesh>,
+    #[serde(default, skip_serializing_if = "Shape::is_cube")]
+    pub shape: Shape,
+    /// Metallic and perceptual roughness, when either differs from the material defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<[f32; 2]>,
+    /// The rest of the material, when any of it differs from a new cube's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish: Option<Finish>,
+    /// The base colour texture and the mesh its UVs were made for, such as a projected decal's.
+    /// Images are not written to scene files, so only in-memory copies like snapshots keep it.
+    #[serde(skip)]
+    pub texture: Option<(Handle<Image>, Handle<Mesh>)>,
+}
+
+/// Material settings besides colour and surface that the inspector, scripts and fades change.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Finish {
+    pub reflectance: f32,
+    pub unlit: bool,
+    /// Linear RGB, premultiplied by intensity, and alpha.
+    pub emissive: [f32; 4],
+    pub alpha_mode: SceneAlphaMode,
+}
+
+impl Default for Finish {
+    /// A new cube's, see [`spawn_cube`].
+    fn default() -> Self {
+        Self {
+            reflectance: 1.0,
+            unlit: false,
+            emissive: LinearRgba::BLACK.to_f32_array(),
+            alpha_mode: SceneAlphaMode::Opaque,
+        }
+    }
+}
+
+impl Finish {
+    /// `material`'s finish, unless it is a new cube's.
+    fn of(material: &StandardMaterial, alpha_mode: AlphaMode) -> Option<Self> {
+        Some(Self {
+            reflectance: material.reflectance,
+            unlit: material.unlit,
+            emissive: material.emissive.to_f32_array(),
+            alpha_mode: alpha_mode.into(),
+        })
+        .filter(|finish| *finish != Self::default())
+    }
+}
+
+/// [`AlphaMode`], which has no serde support.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SceneAlphaMode {
+    Opaque,
+    Mask(f32),
+    Blend,
+    Premultiplied,
+    AlphaToCoverage,
+    Add,
+    Multiply,
+}
+
+impl From<AlphaMode> for SceneAlphaMode {
+    fn from(alpha_mode: AlphaMode) -> Self {
+        match alpha_mode {
+            AlphaMode::Opaque => Self::Opaque,
+            AlphaMode::Mask(cutoff) => Self::Mask(cutoff),
+            AlphaMode::Blend => Self::Blend,
+            AlphaMode::Premultiplied => Self::Premultiplied,
+            AlphaMode::AlphaToCoverage => Self::AlphaToCoverage,
+            AlphaMode::Add => Self::Add,
+            AlphaMode::Multiply => Self::Multiply,
+        }
+    }
+}
+
+impl From<SceneAlphaMode> for AlphaMode {
+    fn from(alpha_mode: SceneAlphaMode) -> Self {
+        match alpha_mode {
+            SceneAlphaMode::Opaque => Self::Opaque,
+            SceneAlphaMode::Mask(cutoff) => Self::Mask(cutoff),
+            SceneAlphaMode::Blend => Self::Blend,
+            SceneAlphaMode::Premultiplied => Self::Premultiplied,
+            SceneAlphaMode::AlphaToCoverage => Self::AlphaToCoverage,
+            SceneAlphaMode::Add => Self::Add,
+            SceneAlphaMode::Multiply => Self::Multiply,
+        }
+    }
+}
+
+/// The primitive a scene entity is drawn with. Only non-cube shapes are stored as a
+/// component, alongside [`CustomMesh`].
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Shape {
+    #[default]
+    Cube,
+    Sphere,
+    /// The material preview object: a banded sphere on a stepped pedestal.
+    ShaderBall,
+}
+
+impl Shape {
+    fn is_cube(&self) -> bool {
+        *self == Shape::Cube
+    }
+
+    pub fn mesh(self) -> Mesh {
+        match self {
+            Shape::Cube => Cuboid::new(1.0, 1.0, 1.0).into(),
+            Shape::Sphere => Sphere::new(0.5).mesh().uv(48, 24),
+            Shape::ShaderBall => shader_ball_mesh(),
+        }
+    }
+}
+
+/// A sphere with a tilted band around it, on a neck and a round base, fitting the unit cube
+/// like the other shapes. Curves show highlights and Fresnel falloff, the band's inner
+/// edge shows contact shading, and the base's flat top and sharp rim show reflections.
+fn shader_ball_mesh() -> Mesh {
+    let center = Vec3::new(0.0, 0.08, 0.0);
+    let mut mesh = Sphere::new(0.36).mesh().uv(64, 32).translated_by(center);
+    let band = Mesh::from(Torus {
+        minor_radius: 0.035,
+        major_radius: 0.365,
+    })
+    .rotated_by(Quat::from_rotation_x(-0.45))
+    .translated_by(center);
+    let neck = Mesh::from(Cylinder::new(0.12, 0.12)).translated_by(Vec3::new(0.0, -0.3, 0.0));
+    let base = Mesh::from(Cylinder::new(0.4, 0.14)).translated_by(Vec3::new(0.0, -0.43, 0.0));
+    for part in [band, neck, base] {
+        mesh.merge(&part);
+    }
+    mesh
+}
+
+/// A group pivot. Groups may nest, in which case `parent` precedes it in the list.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneGroup {
+    #[serde(default)]
+    pub id: u64,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+}
+
+impl SceneGroup {
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: Vec3::from_array(self.translation),
+            rotation: Quat::from_array(self.rotation),
+            scale: Vec3::from_array(self.scale),
+        }
+    }
+}
+
+/// Spawns an empty group pivot at `transform`.
+pub fn spawn_group(commands: &mut Commands, transform: Transform) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle {
+                transform,
+                global_transform: transform.into(),
+                ..default()
+            },
+            Group,
+            Name::new("Group"),
+            SceneId::random(),
+        ))
+        .id()
+}
+
+impl SceneEntity {
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: Vec3::from_array(self.translation),
+            rotation: Quat::from_array(self.rotation),
+            scale: Vec3::from_array(self.scale),
+        }
+    }
+
+    /// Sets the parts of `material` a scene file stores.
+    pub fn apply_material(&self, material: &mut StandardMaterial) {
+        let defaults = StandardMaterial::default();
+        let [metallic, perceptual_roughness] = self
+            .surface
+            .unwrap_or([defaults.metallic, defaults.perceptual_roughness]);
+        let finish = self.finish.unwrap_or_default();
+        let [r, g, b, a] = self.color;
+        material.base_color = Color::srgba(r, g, b, a);
+        material.base_color_texture = self.texture.as_ref().map(|(image, _)| image.clone());
+        material.emissive = LinearRgba::from_f32_array(finish.emissive);
+        material.metallic = metallic;
+        material.perceptual_roughness = perceptual_roughness;
+        material.reflectance = finish.reflectance;
+        material.unlit = finish.unlit;
+        material.alpha_mode = finish.alpha_mode.into();
+    }
+}
+
+/// Marks a scene entity whose mesh is no longer the unit cube (text, CSG results), so
+/// operations that rebuild cubes from their transforms, like baking and decals, skip it.
+#[derive(Component)]
+pub struct CustomMesh;
+
+/// Spawns a unit cube scaled by `transform`, with its own material so it can be edited alone.
+pub fn spawn_cube(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    transform: Transform,
+    color: Color,
+) -> Entity {
+    let material = StandardMaterial {
+        base_color: color,
+        reflectance: 1.0,
+        unlit: false,
+        ..default()
+    };
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+            material: materials.add(material),
+            transform,
+            ..default()
+        })
+        .insert((RenderCube, SceneId::random()))
+        .id()
+}
+
+/// Cubes spawned in bulk per frame at most.
+const SPAWNS_PER_FRAME: usize = 1000;
+
+/// Plain cubes waiting to be spawned. Requests from the UI and scripts go through here, so a
+/// script asking for thousands of cubes spreads them over several frames instead of hitching.
+/// Use [`spawn_cube`] instead when the entity is needed right away.
+#[derive(Default, Resource)]
+pub struct SpawnQueue {
+    pending: VecDeque<(Transform, Color)>,
+    /// The unit cube shared by every queued cube.
+    mesh: Option<Handle<Mesh>>,
+}
+
+impl SpawnQueue {
+    pub fn push(&mut self, transform: Transform, color: Color) {
+        self.pending.push_back((transform, color));
+    }
+}
+
+fn spawn_queued_cubes_system(
+    mut queue: ResMut<SpawnQueue>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pool: ResMut<CubePool>,
+    pooled: Query<&Handle<StandardMaterial>, With<Pooled>>,
+    mut session: EventWriter<SessionEvent>,
+) {
+    if queue.pending.is_empty() {
+        return;
+    }
+    let mesh = queue
+        .mesh
+        .get_or_insert_with(|| meshes.add(Cuboid::new(1.0, 1.0, 1.0)))
+        .clone();
+    let count = queue.pending.len().min(SPAWNS_PER_FRAME);
+    let mut batch = Vec::new();
+    for (transform, color) in queue.pending.drain(..count) {
+        let material = StandardMaterial {
+            base_color: color,
+            reflectance: 1.0,
+            unlit: false,
+            ..default()
+        };
+        if let Some((entity, handle)) = pool.acquire(&pooled) {
+            // Reset the recycled cube instead of allocating a new entity and material.
+            if let Some(reused) = materials.get_mut(&handle) {
+                *reused = material;
+            }
+            commands.entity(entity).remove::<Pooled>().insert((
+                mesh.clone(),
+                transform,
+                Visibility::Inherited,
+                RenderCube,
+                SceneId::random(),
+            ));
+            continue;
+        }
+        pool.allocated += 1;
+        batch.push((
+            PbrBundle {
+                mesh: mesh.clone(),
+                // Materials stay per cube so each can still be edited alone.
+                material: materials.add(material),
+                transform,
+                ..default()
+            },
+            RenderCube,
+            SceneId::random(),
+        ));
+    }
+    commands.spawn_batch(batch);
+    session.send(SessionEvent::Spawned(count));
+}
+
+/// Raw RON view of the scene file, for hand edits without leaving the sandbox.
+#[derive(Default, Resource)]
+pub struct SceneSourceWindow {
+    pub is_open: bool,
+    source: Option<String>,
+}
+
+impl Panel for SceneSourceWindow {
+    const TITLE: &'static str = "Scene Source";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn scene_source_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SceneSourceWindow>,
+    mut load: EventWriter<LoadScene>,
+    mut errors: EventWriter<AppError>,
+) {
+    let SceneSourceWindow { is_open, source } = &mut *window;
+    if !*is_open {
+        *source = None;
+        return;
+    }
+    let source =
+        source.get_or_insert_with(|| std::fs::read_to_string(SCENE_PATH).unwrap_or_default());
+
+    egui::Window::new("Scene Source")
+        .open(is_open)
+        .default_size([560.0, 420.0])
+        .show(contexts.ctx::<SceneSourceWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Reload from disk").clicked() {
+                    *source = std::fs::read_to_string(SCENE_PATH).unwrap_or_default();
+                }
+                if ui.button("Write and load").clicked() {
+                    match std::fs::write(SCENE_PATH, &*source) {
+                        Ok(()) => {
+                            load.send(LoadScene);
+                        }
+                        Err(err) => {
+                            errors.send(
+                                AppError::new(
+                                    "Scene",
+                                    format!("Failed to write {SCENE_PATH}: {err}"),
+                                )
+                                .suggest("Check that the working directory is writable."),
+                            );
+                        }
+                    }
+                }
+            });
+            ui.separator();
+            CodeEditor::new("scene_source", source, Language::Ron).show(ui);
+        });
+}
+
+/// What [`SceneReader`] reads of each cube.
+type CubeData = (
+    &'static Transform,
+    Option<&'static RestRotation>,
+    &'static Handle<StandardMaterial>,
+    Option<&'static Parent>,
+    Option<&'static SceneId>,
+    Has<Static>,
+    Option<&'static Text3d>,
+    Option<&'static CsgMesh>,
+    Option<&'static Plant>,
+    Option<&'static Properties>,
+    Option<&'static OpacityTrack>,
+    Option<&'static EntityScript>,
+    Option<&'static Sprite2d>,
+    Option<&'static ImportedMesh>,
+    (
+        Option<&'static Shape>,
+        &'static Handle<Mesh>,
+        Option<&'static ExplodedPart>,
+    ),
+);
+
+/// Read access to everything a [`SceneFile`] is built from.
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct SceneReader<'w, 's> {
+    project: Res<'w, Project>,
+    cubes: Query<'w, 's, CubeData, With<RenderCube>>,
+    groups: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Transform,
+            Option<&'static Parent>,
+            Option<&'static SceneId>,
+            Option<&'static ExplodedPart>,
+        ),
+        With<Group>,
+    >,
+    batches: Query<'w, 's, (&'static BakedBatch, &'static Transform)>,
+    materials: Res<'w, Assets<StandardMaterial>>,
+}
+
+impl SceneReader<'_, '_> {
+    /// The current scene as it would be saved.
+    pub fn capture(&self) -> SceneFile {
+        let groups = &self.groups;
+        // Order groups so every parent comes before its children.
+        let mut order: Vec<Entity> = Vec::new();
+        while order.len() < groups.iter().len() {
+            let before = order.len();
+            for (entity, _, parent, _, _) in groups {
+                let parent_placed = parent.is_none_or(|parent| {
+                    !groups.contains(parent.get()) || order.contains(&parent.get())
+                });
+                if parent_placed && !order.contains(&entity) {
+                    order.push(entity);
+                }
+            }
+            if order.len() == before {
+                break;
+            }
+        }
+        let index_of = |parent: Option<&Parent>| {
+            parent.and_then(|parent| order.iter().position(|e| *e == parent.get()))
+        };
+        let scene_groups = order
+            .iter()
+            .map(|entity| {
+                let (_, transform, parent, id, exploded) = groups.get(*entity).unwrap();
+                SceneGroup {
+                    id: id.map_or(0, |id| **id),
+                    translation: exploded
+                        .map_or(transform.translation, |part| part.rest)
+                        .to_array(),
+                    rotation: transform.rotation.to_array(),
+                    scale: transform.scale.to_array(),
+                    parent: index_of(parent),
+                }
+            })
+            .collect();
+
+        let mut entities: Vec<SceneEntity> = self
+            .cubes
+            .iter()
+            .map(|cube| {
+                let group = index_of(cube.3);
+                self.scene_entity(cube, group)
+            })
+            .collect();
+        // Baked cubes are written out individually; loading restores them as plain cubes.
+        entities.extend(
+            self.batches
+                .iter()
+                .flat_map(|(batch, transform)| batch.placed_cubes(transform)),
+        );
+        SceneFile {
+            version: SceneFile::VERSION,
+            notes: self.project.notes.clone(),
+            camera_bookmarks: self.project.camera_bookmarks.clone(),
+            tile_layer: self.project.tile_layer.clone(),
+            selection_sets: self.project.selection_sets.clone(),
+            groups: scene_groups,
+            entities,
+        }
+    }
+
+    /// One cube as [`Self::capture`] would save it, outside any group.
+    pub fn entity(&self, entity: Entity) -> Option<SceneEntity> {
+        self.cubes
+            .get(entity)
+            .ok()
+            .map(|cube| self.scene_entity(cube, None))
+    }
+
+    fn scene_entity(&self, cube: ROQueryItem<'_, CubeData>, group: Option<usize>) -> SceneEntity {
+        let (
+            transform,
+            rest_rotation,
+            material,
+            _,
+            id,
+            is_static,
+            text,
+            csg,
+            plant,
+            properties,
+            fade,
+            script,
+            sprite,
+            mesh_file,
+            (shape, mesh, exploded),
+        ) = cube;
+        let material = self.materials.get(material);
+        let mut color = material.map_or(Color::WHITE, |material| material.base_color);
+        let defaults = StandardMaterial::default();
+        let surface = material
+            .map(|material| [material.metallic, material.perceptual_roughness])
+            .filter(|surface| *surface != [defaults.metallic, defaults.perceptual_roughness]);
+        // Save the authored alpha and blend mode, not the faded ones.
+        if let Some(alpha) = fade.and_then(OpacityTrack::rest_alpha) {
+            color.set_alpha(alpha);
+        }
+        let finish = material.and_then(|material| {
+            let alpha_mode = fade
+                .and_then(OpacityTrack::rest_alpha_mode)
+                .unwrap_or(material.alpha_mode);
+            Finish::of(material, alpha_mode)
+        });
+        let texture = material
+            .and_then(|material| material.base_color_texture.clone())
+            .map(|texture| (texture, mesh.clone()));
+        SceneEntity {
+            id: id.map_or(0, |id| **id),
+            // Save where parts sit assembled, not where the exploded view moved them.
+            translation: exploded
+                .map_or(transform.translation, |part| part.rest)
+                .to_array(),
+            // Save the authored orientation, not the animated one.
+            rotation: rest_rotation
+                .map_or(transform.rotation, |rest| **rest)
+                .to_array(),
+            scale: transform.scale.to_array(),
+            color: color.to_srgba().to_f32_array(),
+            group,
+            is_static,
+            text: text.cloned(),
+            csg: csg.cloned(),
+            plant: plant.cloned(),
+            properties: properties.cloned().unwrap_or_default(),
+            fade: fade.cloned(),
+            script: script.cloned(),
+            sprite: sprite.cloned(),
+            mesh_file: mesh_file.cloned(),
+            shape: shape.copied().unwrap_or_default(),
+            surface,
+            finish,
+            texture,
+        }
+    }
+}
+
+fn save_scene_system(
+    mut events: EventReader<SaveScene>,
+    scene: SceneReader,
+    mut errors: EventWriter<AppError>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let file = scene.capture();
+    match write_scene_file(SCENE_PATH, &file) {
+        Ok(()) => info!("Saved scene to {SCENE_PATH}"),
+        Err(err) => {
+            errors.send(
+                AppError::new("Scene", format!("Failed to save {SCENE_PATH}: {err}")).suggest(
+                    "Check that the working directory is writable and the file is not locked.",
+                ),
+            );
+        }
+    }
+}
+
+pub fn read_scene_file(path: &str) -> Result<SceneFile, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let mut file: SceneFile = ron::from_str(&contents).map_err(|err| err.to_string())?;
+    file.upgrade();
+    Ok(file)
+}
+
+pub fn write_scene_file(path: &str, file: &SceneFile) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(file, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())?;
+    std::fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+/// Write access for replacing the scene with the contents of a [`SceneFile`].
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct SceneWriter<'w, 's> {
+    commands: Commands<'w, 's>,
+    project: ResMut<'w, Project>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    existing: Query<'w, 's, Entity, Or<(With<RenderCube>, With<Group>, With<BakedBatch>)>>,
+    cube_materials: Query<'w, 's, &'static Handle<StandardMaterial>, With<RenderCube>>,
+}
+
+impl SceneWriter<'_, '_> {
+    /// Despawns the current cubes and groups and spawns those of `file` in their place.
+    pub fn replace(&mut self, file: &SceneFile) {
+        let commands = &mut self.commands;
+        for entity in &self.existing {
+            commands.entity(entity).despawn_recursive();
+        }
+        let groups: Vec<Entity> = file
+            .groups
+            .iter()
+            .map(|group| {
+                let entity = spawn_group(commands, group.transform());
+                if group.id != 0 {
+                    commands.entity(entity).insert(SceneId(group.id));
+                }
+                entity
+            })
+            .collect();
+        for (group, entity) in file.groups.iter().zip(&groups) {
+            if let Some(parent) = group.parent.and_then(|index| groups.get(index)) {
+                commands.entity(*entity).set_parent(*parent);
+            }
+        }
+        for entity in &file.entities {
+            let parent = entity.group.and_then(|index| groups.get(index)).copied();
+            self.spawn(entity, parent);
+        }
+        self.project.notes.clone_from(&file.notes);
+        self.project
+            .camera_bookmarks
+            .clone_from(&file.camera_bookmarks);
+        self.project.tile_layer.clone_from(&file.tile_layer);
+        self.project.selection_sets.clone_from(&file.selection_sets);
+    }
+
+    /// Moves and restyles the existing cube `cube` to match `entity`, without respawning it.
+    pub fn update(&mut self, cube: Entity, entity: &SceneEntity) {
+        let transform = entity.transform();
+        let mut commands = self.commands.entity(cube);
+        commands.insert((transform, RestRotation(transform.rotation)));
+        if let Some((_, mesh)) = &entity.texture {
+            commands.insert(mesh.clone());
+        }
+        let material = self.cube_materials.get(cube).ok();
+        if let Some(material) = material.and_then(|handle| self.materials.get_mut(handle)) {
+            entity.apply_material(material);
+        }
+    }
+
+    /// Spawns one cube of a scene file under `parent`. A zero id gets a new random one.
+    pub fn spawn(&mut self, entity: &SceneEntity, parent: Option<Entity>) -> Entity {
+        let [r, g, b, a] = entity.color;
+        let id = spawn_cube(
+            &mut self.commands,
+            &mut self.meshes,
+            &mut self.materials,
+            entity.transform(),
+            Color::srgba(r, g, b, a),
+        );
+        let mut cube = self.commands.entity(id);
+        if entity.id != 0 {
+            cube.insert(SceneId(entity.id));
+        }
+        if entity.is_static {
+            cube.insert(Static);
+        }
+        if let Some(text) = &entity.text {
+            cube.insert(text.clone());
+        }
+        if let Some(csg) = &entity.csg {
+            cube.insert(csg.clone());
+        }
+        if let Some(plant) = &entity.plant {
+            cube.insert(plant.clone());
+        }
+        if !entity.properties.is_empty() {
+            cube.insert(entity.properties.clone());
+        }
+        if let Some(fade) = &entity.fade {
+            cube.insert(fade.clone());
+        }
+        if let Some(script) = &entity.script {
+            cube.insert(script.clone());
+        }
+        if let Some(sprite) = &entity.sprite {
+            cube.insert(sprite.clone());
+        }
+        if let Some(mesh_file) = &entity.mesh_file {
+            cube.insert(mesh_file.clone());
+        }
+        if !entity.shape.is_cube() {
+            cube.insert((
+                self.meshes.add(entity.shape.mesh()),
+                entity.shape,
+                CustomMesh,
+            ));
+        }
+        if entity.surface.is_some() || entity.finish.is_some() || entity.texture.is_some() {
+            let mut material = StandardMaterial::default();
+            entity.apply_material(&mut material);
+            cube.insert(self.materials.add(material));
+        }
+        if let Some((_, mesh)) = &entity.texture {
+            cube.insert(mesh.clone());
+        }
+        if let Some(parent) = parent {
+            cube.set_parent(parent);
+        }
+        id
+    }
+}
+
+fn load_scene_system(
+    mut events: EventReader<LoadScene>,
+    mut scene: SceneWriter,
+    mut errors: EventWriter<AppError>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let file = match read_scene_file(SCENE_PATH) {
+        Ok(file) => file,
+        Err(err) => {
+            errors.send(
+                AppError::new("Scene", format!("Failed to load {SCENE_PATH}: {err}")).suggest(
+                    "Save a scene first, or fix the file in File › Scene Source if it is malformed.",
+                ),
+            );
+            return;
+        }
+    };
+
+    scene.replace(&file);
+    info!("Loaded {} entities from {SCENE_PATH}", file.entities.len());
+}
@@ -0,0 +1,574 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    errors::AppError,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    scene::{
+        read_scene_file, write_scene_file, Finish, LoadScene, SceneEntity, SceneFile, SCENE_PATH,
+    },
+    settings::{egui_color, Settings},
+};
+
+/// Compares two scene files entity by entity and merges selected changes from one into the other.
+pub struct SceneDiffPlugin;
+
+impl Plugin for SceneDiffPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<SceneDiffWindow>().add_systems(
+            Update,
+            scene_diff_window_system.in_set(SceneDiffWindow::ui_set()),
+        );
+    }
+}
+
+struct FieldChange {
+    name: &'static str,
+    from: String,
+    to: String,
+}
+
+enum ChangeKind {
+    Notes,
+    Added {
+        b: usize,
+    },
+    Removed {
+        a: usize,
+    },
+    Modified {
+        a: usize,
+        b: usize,
+        fields: Vec<FieldChange>,
+    },
+}
+
+struct Change {
+    kind: ChangeKind,
+    apply: bool,
+}
+
+struct SceneDiff {
+    a: SceneFile,
+    b: SceneFile,
+    changes: Vec<Change>,
+}
+
+#[derive(Resource)]
+pub struct SceneDiffWindow {
+    pub is_open: bool,
+    /// The file merges are written into.
+    path_a: String,
+    path_b: String,
+    diff: Option<SceneDiff>,
+    error: Option<String>,
+}
+
+impl Default for SceneDiffWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            path_a: SCENE_PATH.to_owned(),
+            path_b: String::new(),
+            diff: None,
+            error: None,
+        }
+    }
+}
+
+impl Panel for SceneDiffWindow {
+    const TITLE: &'static str = "Scene Diff";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl SceneDiffWindow {
+    fn compare(&mut self) {
+        let files = read_scene_file(&self.path_a)
+            .map_err(|err| format!("{}: {err}", self.path_a))
+            .and_then(|a| {
+                read_scene_file(&self.path_b)
+                    .map(|b| (a, b))
+                    .map_err(|err| format!("{}: {err}", self.path_b))
+            });
+        match files {
+            Ok((a, b)) => {
+                let changes = diff(&a, &b)
+                    .into_iter()
+                    .map(|kind| Change { kind, apply: true })
+                    .collect();
+                self.diff = Some(SceneDiff { a, b, changes });
+                self.error = None;
+            }
+            Err(err) => {
+                self.diff = None;
+                self.error = Some(err);
+            }
+        }
+    }
+}
+
+fn floats(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|v| format!("{v:.3}")).collect();
+    format!("({})", values.join(", "))
+}
+
+fn floats_differ(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).any(|(a, b)| (a - b).abs() > 1e-4)
+}
+
+fn short_id(id: u64) -> String {
+    format!("{:08x}", id >> 32)
+}
+
+fn entity_label(entity: &SceneEntity, index: usize) -> String {
+    if entity.id == 0 {
+        format!("Cube #{index}")
+    } else {
+        format!("Cube {}", short_id(entity.id))
+    }
+}
+
+fn group_label(file: &SceneFile, entity: &SceneEntity) -> String {
+    match entity.group {
+        None => "none".to_owned(),
+        Some(index) => match file.groups.get(index) {
+            Some(group) if group.id != 0 => short_id(group.id),
+            _ => format!("#{index}"),
+        },
+    }
+}
+
+fn field_changes(
+    a: &SceneFile,
+    ea: &SceneEntity,
+    b: &SceneFile,
+    eb: &SceneEntity,
+) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    let mut compare = |name, from: &[f32], to: &[f32]| {
+        if floats_differ(from, to) {
+            fields.push(FieldChange {
+                name,
+                from: floats(from),
+                to: floats(to),
+            });
+        }
+    };
+    compare("translation", &ea.translation, &eb.translation);
+    compare("rotation", &ea.rotation, &eb.rotation);
+    compare("scale", &ea.scale, &eb.scale);
+    compare("color", &ea.color, &eb.color);
+    // Metallic and roughness; none is the material defaults.
+    let defaults = StandardMaterial::default();
+    let surface = |entity: &SceneEntity| {
+        entity
+            .surface
+            .unwrap_or([defaults.metallic, defaults.perceptual_roughness])
+    };
+    compare("surface", &surface(ea), &surface(eb));
+    if ea.shape != eb.shape {
+        fields.push(FieldChange {
+            name: "shape",
+            from: format!("{:?}", ea.shape),
+            to: format!("{:?}", eb.shape),
+        });
+    }
+    // No finish is a new cube's.
+    let (from, to) = (ea.finish.unwrap_or_default(), eb.finish.unwrap_or_default());
+    if from != to {
+        let finish = |finish: Finish| {
+            let unlit = if finish.unlit { ", unlit" } else { "" };
+            format!(
+                "{:?}, reflectance {:.3}, emissive {}{unlit}",
+                finish.alpha_mode,
+                finish.reflectance,
+                floats(&finish.emissive)
+            )
+        };
+        fields.push(FieldChange {
+            name: "finish",
+            from: finish(from),
+            to: finish(to),
+        });
+    }
+    if ea.is_static != eb.is_static {
+        fields.push(FieldChange {
+            name: "static",
+            from: ea.is_static.to_string(),
+            to: eb.is_static.to_string(),
+        });
+    }
+    let text = |entity: &SceneEntity| {
+        entity
+            .text
+            .as_ref()
+            .map_or_else(|| "-".to_owned(), |text| format!("{:?}", text.text))
+    };
+    if ea.text != eb.text {
+        fields.push(FieldChange {
+            name: "text",
+            from: text(ea),
+            to: text(eb),
+        });
+    }
+    if ea.plant != eb.plant {
+        let plant = |entity: &SceneEntity| {
+            entity.plant.as_ref().map_or_else(
+                || "-".to_owned(),
+                |plant| format!("{} × {}", plant.axiom, plant.iterations),
+            )
+        };
+        fields.push(FieldChange {
+            name: "plant",
+            from: plant(ea),
+            to: plant(eb),
+        });
+    }
+    if ea.properties != eb.properties {
+        fields.push(FieldChange {
+            name: "properties",
+            from: ea.properties.summary(),
+            to: eb.properties.summary(),
+        });
+    }
+    if ea.csg != eb.csg {
+        let csg = |entity: &SceneEntity| {
+            entity.csg.as_ref().map_or_else(
+                || "-".to_owned(),
+                |csg| format!("{} triangles", csg.positions.len() / 3),
+            )
+        };
+        fields.push(FieldChange {
+            name: "csg",
+            from: csg(ea),
+            to: csg(eb),
+        });
+    }
+    // Only the keys are saved; the captured rest alpha is not part of the scene.
+    let keys = |entity: &SceneEntity| entity.fade.as_ref().map(|fade| fade.keys.clone());
+    if keys(ea) != keys(eb) {
+        let fade = |entity: &SceneEntity| {
+            keys(entity).map_or_else(|| "-".to_owned(), |keys| format!("{} keys", keys.len()))
+        };
+        fields.push(FieldChange {
+            name: "fade",
+            from: fade(ea),
+            to: fade(eb),
+        });
+    }
+    let script = |entity: &SceneEntity| {
+        entity
+            .script
+            .as_ref()
+            .map(|script| (script.source.clone(), script.enabled))
+    };
+    if script(ea) != script(eb) {
+        let label = |entity: &SceneEntity| {
+            script(entity).map_or_else(
+                || "-".to_owned(),
+                |(source, enabled)| {
+                    let off = if enabled { "" } else { " (off)" };
+                    format!("{source:?}{off}")
+                },
+            )
+        };
+        fields.push(FieldChange {
+            name: "script",
+            from: label(ea),
+            to: label(eb),
+        });
+    }
+    if ea.sprite != eb.sprite {
+        let sprite = |entity: &SceneEntity| {
+            entity.sprite.as_ref().map_or_else(
+                || "-".to_owned(),
+                |sprite| format!("{} at {} px/unit", sprite.image, sprite.pixels_per_unit),
+            )
+        };
+        fields.push(FieldChange {
+            name: "sprite",
+            from: sprite(ea),
+            to: sprite(eb),
+        });
+    }
+    if ea.mesh_file != eb.mesh_file {
+        let mesh_file = |entity: &SceneEntity| {
+            entity
+                .mesh_file
+                .as_ref()
+                .map_or_else(|| "-".to_owned(), |mesh| mesh.path.clone())
+        };
+        fields.push(FieldChange {
+            name: "mesh file",
+            from: mesh_file(ea),
+            to: mesh_file(eb),
+        });
+    }
+    let (from, to) = (group_label(a, ea), group_label(b, eb));
+    if from != to {
+        fields.push(FieldChange {
+            name: "group",
+            from,
+            to,
+        });
+    }
+    fields
+}
+
+/// Entities are matched by id; files without ids fall back to matching by position.
+fn diff(a: &SceneFile, b: &SceneFile) -> Vec<ChangeKind> {
+    let mut changes = Vec::new();
+    if a.notes != b.notes {
+        changes.push(ChangeKind::Notes);
+    }
+    let mut matched = vec![false; a.entities.len()];
+    for (bi, eb) in b.entities.iter().enumerate() {
+        let ai = if eb.id != 0 {
+            a.entities.iter().position(|ea| ea.id == eb.id)
+        } else {
+            a.entities.get(bi).filter(|ea| ea.id == 0).map(|_| bi)
+        };
+        match ai {
+            Some(ai) => {
+                matched[ai] = true;
+                let fields = field_changes(a, &a.entities[ai], b, eb);
+                if !fields.is_empty() {
+                    changes.push(ChangeKind::Modified {
+                        a: ai,
+                        b: bi,
+                        fields,
+                    });
+                }
+            }
+            None => changes.push(ChangeKind::Added { b: bi }),
+        }
+    }
+    changes.extend(
+        matched
+            .iter()
+            .enumerate()
+            .filter(|(_, matched)| !**matched)
+            .map(|(ai, _)| ChangeKind::Removed { a: ai }),
+    );
+    changes
+}
+
+/// How an entity differs from its match in the other scene, for showing the difference.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntityChange {
+    Added,
+    Removed,
+    /// Its transform or group changed.
+    Moved,
+    /// Anything else about it changed.
+    Changed,
+}
+
+/// The change of each entity of `a` and of `b`, by index, or `None` where it is the same in
+/// both.
+#[allow(clippy::type_complexity)]
+pub fn entity_changes(
+    a: &SceneFile,
+    b: &SceneFile,
+) -> (Vec<Option<EntityChange>>, Vec<Option<EntityChange>>) {
+    let mut changes_a = vec![None; a.entities.len()];
+    let mut changes_b = vec![None; b.entities.len()];
+    for change in diff(a, b) {
+        match change {
+            ChangeKind::Notes => {}
+            ChangeKind::Added { b } => changes_b[b] = Some(EntityChange::Added),
+            ChangeKind::Removed { a } => changes_a[a] = Some(EntityChange::Removed),
+            ChangeKind::Modified { a, b, fields } => {
+                let moved = fields.iter().any(|field| {
+                    matches!(field.name, "translation" | "rotation" | "scale" | "group")
+                });
+                let change = if moved {
+                    EntityChange::Moved
+                } else {
+                    EntityChange::Changed
+                };
+                changes_a[a] = Some(change);
+                changes_b[b] = Some(change);
+            }
+        }
+    }
+    (changes_a, changes_b)
+}
+
+/// Finds or copies `b`'s group `index` (and its parents) into `merged`.
+fn import_group(merged: &mut SceneFile, b: &SceneFile, index: usize) -> Option<usize> {
+    let group = b.groups.get(index)?;
+    if group.id != 0 {
+        if let Some(existing) = merged.groups.iter().position(|g| g.id == group.id) {
+            return Some(existing);
+        }
+    } else if index < merged.groups.len() {
+        return Some(index);
+    }
+    let mut group = group.clone();
+    group.parent = group
+        .parent
+        .and_then(|parent| import_group(merged, b, parent));
+    merged.groups.push(group);
+    Some(merged.groups.len() - 1)
+}
+
+fn merge(diff: &SceneDiff) -> SceneFile {
+    let SceneDiff { a, b, changes } = diff;
+    let mut merged = a.clone();
+    let mut removed = Vec::new();
+    let import = |merged: &mut SceneFile, bi: usize| {
+        let mut entity = b.entities[bi].clone();
+        entity.group = entity
+            .group
+            .and_then(|group| import_group(merged, b, group));
+        entity
+    };
+    for change in changes.iter().filter(|change| change.apply) {
+        match change.kind {
+            ChangeKind::Notes => merged.notes = b.notes.clone(),
+            ChangeKind::Added { b } => {
+                let entity = import(&mut merged, b);
+                merged.entities.push(entity);
+            }
+            ChangeKind::Modified { a, b, .. } => {
+                merged.entities[a] = import(&mut merged, b);
+            }
+            ChangeKind::Removed { a } => removed.push(a),
+        }
+    }
+    // Added entities were appended, so removing original indices back to front is safe.
+    removed.sort_unstable();
+    for index in removed.into_iter().rev() {
+        merged.entities.remove(index);
+    }
+    merged
+}
+
+fn scene_diff_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SceneDiffWindow>,
+    mut load: EventWriter<LoadScene>,
+    mut errors: EventWriter<AppError>,
+    settings: Res<Settings>,
+) {
+    let highlights = settings.highlights();
+    if !window.is_open {
+        return;
+    }
+
+    let mut is_open = true;
+    let mut compare = false;
+    egui::Window::new("Scene Diff")
+        .open(&mut is_open)
+        .default_size([480.0, 420.0])
+        .show(contexts.ctx::<SceneDiffWindow>(), |ui| {
+            egui::Grid::new("scene_diff_paths")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Base (A)");
+                    ui.text_edit_singleline(&mut window.path_a);
+                    ui.end_row();
+                    ui.label("Other (B)");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut window.path_b)
+                            .hint_text("e.g. a teammate's scene.ron"),
+                    );
+                    ui.end_row();
+                });
+            compare = ui.button("Compare").clicked();
+            if let Some(error) = &window.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            let SceneDiffWindow { path_a, diff, .. } = &mut *window;
+            let Some(diff) = diff else {
+                return;
+            };
+            ui.separator();
+            if diff.changes.is_empty() {
+                ui.weak("The scenes are identical.");
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Select all").clicked() {
+                    diff.changes
+                        .iter_mut()
+                        .for_each(|change| change.apply = true);
+                }
+                if ui.button("Select none").clicked() {
+                    diff.changes
+                        .iter_mut()
+                        .for_each(|change| change.apply = false);
+                }
+                if ui.button("Merge selected into A").clicked() {
+                    match write_scene_file(path_a, &merge(diff)) {
+                        Ok(()) => {
+                            let applied = diff.changes.iter().filter(|c| c.apply).count();
+                            info!("Merged {applied} changes into {path_a}");
+                            if path_a.as_str() == SCENE_PATH {
+                                load.send(LoadScene);
+                            }
+                            compare = true;
+                        }
+                        Err(err) => {
+                            errors.send(
+                                AppError::new(
+                                    "Scene Diff",
+                                    format!("Failed to write {path_a}: {err}"),
+                                )
+                                .suggest("Check the path of A and that it is writable."),
+                            );
+                        }
+                    }
+                }
+            });
+
+            let SceneDiff { a, b, changes } = diff;
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for change in changes {
+                    let (symbol, color, label) = match &change.kind {
+                        ChangeKind::Notes => {
+                            ("~", egui_color(highlights.warning), "Notes".to_owned())
+                        }
+                        ChangeKind::Added { b: bi } => (
+                            "+",
+                            egui_color(highlights.positive),
+                            entity_label(&b.entities[*bi], *bi),
+                        ),
+                        ChangeKind::Removed { a: ai } => (
+                            "−",
+                            egui_color(highlights.negative),
+                            entity_label(&a.entities[*ai], *ai),
+                        ),
+                        ChangeKind::Modified { a: ai, .. } => (
+                            "~",
+                            egui_color(highlights.warning),
+                            entity_label(&a.entities[*ai], *ai),
+                        ),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut change.apply, "");
+                        ui.colored_label(color, egui::RichText::new(symbol).monospace());
+                        ui.label(label);
+                    });
+                    if let ChangeKind::Modified { a: ai, fields, .. } = &change.kind {
+                        ui.indent(("fields", *ai), |ui| {
+                            for field in fields {
+                                ui.weak(format!("{}: {} → {}", field.name, field.from, field.to));
+                            }
+                        });
+                    }
+                }
+            });
+        });
+    window.is_open = is_open;
+
+    if compare {
+        window.compare();
+    }
+}
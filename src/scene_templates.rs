@@ -0,0 +1,314 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{SceneEntity, SceneFile, SceneWriter, Shape},
+    settings::Settings,
+    SceneLight, ViewportCamera,
+};
+
+/// Starting points for a new scene, generated rather than loaded from files: File › New
+/// Scene picks one, and the one chosen in the Spawn settings is built at startup.
+pub struct SceneTemplatesPlugin;
+
+impl Plugin for SceneTemplatesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<NewSceneWindow>()
+            .add_event::<NewScene>()
+            .add_systems(PostStartup, startup_template_system)
+            .add_systems(
+                Update,
+                (
+                    new_scene_window_system.in_set(NewSceneWindow::ui_set()),
+                    new_scene_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::File, "New Scene…", |world| {
+                    world.resource_mut::<NewSceneWindow>().is_open = true;
+                })
+                .separator_before(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SceneTemplate {
+    Empty,
+    #[default]
+    SingleCube,
+    CubeGrid,
+    LightingTest,
+    MaterialLineup,
+    ShaderBall,
+}
+
+impl SceneTemplate {
+    pub const ALL: [SceneTemplate; 6] = [
+        SceneTemplate::Empty,
+        SceneTemplate::SingleCube,
+        SceneTemplate::CubeGrid,
+        SceneTemplate::LightingTest,
+        SceneTemplate::MaterialLineup,
+        SceneTemplate::ShaderBall,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SceneTemplate::Empty => "Empty",
+            SceneTemplate::SingleCube => "Single cube",
+            SceneTemplate::CubeGrid => "Cube grid",
+            SceneTemplate::LightingTest => "Lighting test spheres",
+            SceneTemplate::MaterialLineup => "Material ball lineup",
+            SceneTemplate::ShaderBall => "Shader ball",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            SceneTemplate::Empty => "Just the light and camera.",
+            SceneTemplate::SingleCube => "One cube in front of the camera.",
+            SceneTemplate::CubeGrid => "Ten by ten cubes shading from blue to orange.",
+            SceneTemplate::LightingTest => {
+                "White, grey and black spheres with a chrome and a matte one, on a floor."
+            }
+            SceneTemplate::MaterialLineup => {
+                "Five by five spheres, metallic rising upwards and roughness to the right."
+            }
+            SceneTemplate::ShaderBall => {
+                "The material preview object on a floor; turn the environment to evaluate it."
+            }
+        }
+    }
+
+    /// The template's entities, as if loaded from a scene file.
+    fn scene(self) -> SceneFile {
+        let mut entities = Vec::new();
+        match self {
+            SceneTemplate::Empty => {}
+            SceneTemplate::SingleCube => {
+                let mut cube = entity(
+                    Shape::Cube,
+                    Transform::from_xyz(0.0, 0.0, 1.0),
+                    [0.8, 0.7, 0.6],
+                );
+                cube.is_static = false;
+                entities.push(cube);
+            }
+            SceneTemplate::CubeGrid => {
+                for row in 0..10 {
+                    for column in 0..10 {
+                        let t = (row + column) as f32 / 18.0;
+                        let color = Vec3::new(0.2, 0.4, 0.8)
+                            .lerp(Vec3::new(0.9, 0.5, 0.15), t)
+                            .to_array();
+                        let position = Vec3::new(column as f32 - 4.5, row as f32 - 4.5, 0.0) * 1.5;
+                        entities.push(entity(
+                            Shape::Cube,
+                            Transform::from_translation(position),
+                            color,
+                        ));
+                    }
+                }
+            }
+            SceneTemplate::LightingTest => {
+                entities.push(entity(
+                    Shape::Cube,
+                    Transform::from_xyz(0.0, -1.1, 0.0).with_scale(Vec3::new(13.0, 0.2, 5.0)),
+                    [0.5, 0.5, 0.5],
+                ));
+                let spheres = [
+                    ([0.95, 0.95, 0.95], None),
+                    // Middle grey, 18% reflectance in linear terms.
+                    ([0.46, 0.46, 0.46], None),
+                    ([0.2, 0.2, 0.2], None),
+                    ([0.95, 0.95, 0.95], Some([1.0, 0.05])),
+                    ([0.6, 0.6, 0.6], Some([0.0, 1.0])),
+                ];
+                for (index, (color, surface)) in spheres.into_iter().enumerate() {
+                    let mut sphere = entity(
+                        Shape::Sphere,
+                        Transform::from_xyz(index as f32 * 2.5 - 5.0, 0.0, 0.0)
+                            .with_scale(Vec3::splat(2.0)),
+                        color,
+                    );
+                    sphere.surface = surface;
+                    entities.push(sphere);
+                }
+            }
+            SceneTemplate::MaterialLineup => {
+                for metallic in 0..5 {
+                    for roughness in 0..5 {
+                        let mut sphere = entity(
+                            Shape::Sphere,
+                            Transform::from_xyz(
+                                roughness as f32 * 2.2 - 4.4,
+                                metallic as f32 * 2.2 - 4.4,
+                                0.0,
+                            )
+                            .with_scale(Vec3::splat(1.8)),
+                            [0.9, 0.45, 0.2],
+                        );
+                        // Roughness near zero makes point-light highlights vanish to a dot.
+                        sphere.surface =
+                            Some([metallic as f32 / 4.0, 0.05 + roughness as f32 * 0.2375]);
+                        entities.push(sphere);
+                    }
+                }
+            }
+            SceneTemplate::ShaderBall => {
+                entities.push(entity(
+                    Shape::Cube,
+                    Transform::from_xyz(0.0, -1.1, 0.0).with_scale(Vec3::new(8.0, 0.2, 8.0)),
+                    [0.35, 0.35, 0.35],
+                ));
+                entities.push(entity(
+                    Shape::ShaderBall,
+                    Transform::from_scale(Vec3::splat(2.0)),
+                    [0.8, 0.8, 0.8],
+                ));
+            }
+        }
+        SceneFile {
+            entities,
+            ..default()
+        }
+    }
+
+    /// Where the scene light goes.
+    fn light(self) -> Vec3 {
+        match self {
+            SceneTemplate::LightingTest => Vec3::new(-4.0, 6.0, 8.0),
+            SceneTemplate::ShaderBall => Vec3::new(3.0, 5.0, 6.0),
+            SceneTemplate::CubeGrid | SceneTemplate::MaterialLineup => Vec3::new(4.0, 6.0, 14.0),
+            SceneTemplate::Empty | SceneTemplate::SingleCube => Vec3::new(0.0, 0.0, 10.0),
+        }
+    }
+
+    fn camera(self) -> Transform {
+        let eye = match self {
+            SceneTemplate::LightingTest => Vec3::new(0.0, 3.0, 16.0),
+            SceneTemplate::CubeGrid => Vec3::new(0.0, 0.0, 26.0),
+            SceneTemplate::MaterialLineup => Vec3::new(0.0, 0.0, 20.0),
+            SceneTemplate::ShaderBall => Vec3::new(0.0, 1.5, 5.5),
+            SceneTemplate::Empty | SceneTemplate::SingleCube => Vec3::new(0.0, 0.0, 30.0),
+        };
+        Transform::from_translation(eye).looking_at(Vec3::ZERO, Vec3::Y)
+    }
+}
+
+/// A static entity: the lineups are for looking at, not for the spin animation.
+pub fn entity(shape: Shape, transform: Transform, [r, g, b]: [f32; 3]) -> SceneEntity {
+    SceneEntity {
+        id: 0,
+        translation: transform.translation.to_array(),
+        rotation: transform.rotation.to_array(),
+        scale: transform.scale.to_array(),
+        color: [r, g, b, 1.0],
+        group: None,
+        is_static: true,
+        text: None,
+        csg: None,
+        plant: None,
+        properties: default(),
+        fade: None,
+        script: None,
+        sprite: None,
+        mesh_file: None,
+        shape,
+        surface: None,
+        finish: None,
+        texture: None,
+    }
+}
+
+/// Replaces the scene with a template's, moving the light and camera to suit it.
+#[derive(Event)]
+pub struct NewScene(pub SceneTemplate);
+
+#[derive(Default, Resource)]
+pub struct NewSceneWindow {
+    pub is_open: bool,
+    template: SceneTemplate,
+}
+
+impl Panel for NewSceneWindow {
+    const TITLE: &'static str = "New Scene";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn startup_template_system(settings: Res<Settings>, mut events: EventWriter<NewScene>) {
+    events.send(NewScene(settings.spawn.startup_template));
+}
+
+fn new_scene_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<NewSceneWindow>,
+    mut settings: ResMut<Settings>,
+    mut events: EventWriter<NewScene>,
+) {
+    let NewSceneWindow { is_open, template } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut create = false;
+    egui::Window::new(NewSceneWindow::TITLE)
+        .open(is_open)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx::<NewSceneWindow>(), |ui| {
+            for option in SceneTemplate::ALL {
+                let response = ui.selectable_value(template, option, option.label());
+                if response.double_clicked() {
+                    create = true;
+                }
+                ui.weak(option.describe());
+                ui.add_space(4.0);
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                create |= ui.button("Create").clicked();
+                if settings.spawn.startup_template == *template {
+                    ui.weak("Built at startup");
+                } else if ui
+                    .button("Use at Startup")
+                    .on_hover_text("Also set in Settings › Spawn")
+                    .clicked()
+                {
+                    settings.spawn.startup_template = *template;
+                }
+            });
+            ui.weak("Creating replaces the current scene; save it first to keep it.");
+        });
+
+    if create {
+        events.send(NewScene(*template));
+        window.is_open = false;
+    }
+}
+
+fn new_scene_system(
+    mut events: EventReader<NewScene>,
+    mut scene: SceneWriter,
+    mut lights: Query<&mut Transform, (With<SceneLight>, Without<ViewportCamera>)>,
+    mut cameras: Query<&mut Transform, With<ViewportCamera>>,
+) {
+    let Some(NewScene(template)) = events.read().last() else {
+        return;
+    };
+    scene.replace(&template.scene());
+    for mut light in &mut lights {
+        light.translation = template.light();
+    }
+    if let Ok(mut camera) = cameras.get_single_mut() {
+        *camera = template.camera();
+    }
+    info!("Created a new scene from the {} template", template.label());
+}
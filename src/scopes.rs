@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use xihydra_bevy::widgets::StreamedTexture;
+
+use crate::{
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests, ReadbackSource},
+    ViewImage,
+};
+
+const WAVEFORM_SIZE: [usize; 2] = [256, 128];
+
+/// The Scopes window: histograms and a luminance waveform of the viewport image.
+pub struct ScopesPlugin;
+
+impl Plugin for ScopesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ScopesWindow>().add_systems(
+            Update,
+            (
+                request_scopes_readback_system,
+                update_scopes_system,
+                scopes_window_system.in_set(ScopesWindow::ui_set()),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct ScopesWindow {
+    pub is_open: bool,
+    refresh_hz: f32,
+    /// Only every `stride`-th pixel in each direction is analysed.
+    stride: usize,
+    show: [bool; 4],
+    source: ReadbackSource,
+    /// The last linear readback had no HDR values to show.
+    linear_unavailable: bool,
+    since_request: f32,
+    /// Red, green, blue and luminance bins.
+    histograms: [[u32; 256]; 4],
+    waveform: StreamedTexture,
+}
+
+impl Panel for ScopesWindow {
+    const TITLE: &'static str = "Scopes";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl Default for ScopesWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            refresh_hz: 4.0,
+            stride: 2,
+            show: [true; 4],
+            source: ReadbackSource::Output,
+            linear_unavailable: false,
+            since_request: f32::INFINITY,
+            histograms: [[0; 256]; 4],
+            waveform: StreamedTexture::new("scopes_waveform", egui::TextureOptions::NEAREST),
+        }
+    }
+}
+
+fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32).round() as u8
+}
+
+fn request_scopes_readback_system(
+    time: Res<Time>,
+    mut scopes: ResMut<ScopesWindow>,
+    mut requests: ResMut<ReadbackRequests>,
+    view_image: Res<ViewImage>,
+) {
+    if !scopes.is_open {
+        return;
+    }
+    scopes.since_request += time.delta_seconds();
+    if scopes.since_request >= 1.0 / scopes.refresh_hz {
+        scopes.since_request = 0.0;
+        let source = scopes.source;
+        requests.request_from(&view_image, None, source);
+    }
+}
+
+fn update_scopes_system(
+    mut events: EventReader<ReadbackComplete>,
+    mut scopes: ResMut<ScopesWindow>,
+    view_image: Res<ViewImage>,
+) {
+    let source = scopes.source;
+    let Some(readback) = events
+        .read()
+        .filter(|readback| {
+            readback.image == view_image.id()
+                && readback.region.is_none()
+                && readback.source == source
+        })
+        .last()
+    else {
+        return;
+    };
+    if !scopes.is_open {
+        return;
+    }
+    scopes.linear_unavailable = source == ReadbackSource::Linear && readback.linear.is_none();
+    // Linear readbacks are binned by their linear values, clipped to 0..=1, not their sRGB
+    // encoding.
+    let channel = |i: usize| match &readback.linear {
+        Some(linear) => (linear[i / 4][i % 4].clamp(0.0, 1.0) * 255.0).round() as u8,
+        None => readback.data[i],
+    };
+
+    let (width, height) = (readback.size.x as usize, readback.size.y as usize);
+    let stride = scopes.stride;
+    let mut histograms = [[0u32; 256]; 4];
+    let [waveform_width, waveform_height] = WAVEFORM_SIZE;
+    let mut waveform = vec![0u32; waveform_width * waveform_height];
+    for y in (0..height).step_by(stride) {
+        for x in (0..width).step_by(stride) {
+            let i = (y * width + x) * 4;
+            let [r, g, b] = [channel(i), channel(i + 1), channel(i + 2)];
+            let l = luminance(r, g, b);
+            for (histogram, value) in histograms.iter_mut().zip([r, g, b, l]) {
+                histogram[value as usize] += 1;
+            }
+            let column = x * waveform_width / width;
+            let row = waveform_height - 1 - l as usize * (waveform_height - 1) / 255;
+            waveform[row * waveform_width + column] += 1;
+        }
+    }
+    scopes.histograms = histograms;
+
+    // Log scaling keeps sparse traces visible next to dense ones.
+    let max = waveform.iter().copied().max().unwrap_or(1).max(1) as f32;
+    let pixels = waveform
+        .iter()
+        .map(|&count| {
+            let v = ((1.0 + count as f32).ln() / (1.0 + max).ln() * 255.0) as u8;
+            egui::Color32::from_rgb(v / 3, v, v / 3)
+        })
+        .collect();
+    let image = egui::ColorImage {
+        size: WAVEFORM_SIZE,
+        pixels,
+    };
+    scopes.waveform.update(image);
+}
+
+fn scopes_window_system(mut contexts: PanelContexts, mut scopes: ResMut<ScopesWindow>) {
+    let ScopesWindow {
+        is_open,
+        refresh_hz,
+        stride,
+        show,
+        source,
+        linear_unavailable,
+        histograms,
+        waveform,
+        ..
+    } = &mut *scopes;
+    if !*is_open {
+        return;
+    }
+
+    let ctx = contexts.ctx::<ScopesWindow>();
+    let waveform = waveform.upload(ctx).map(egui::TextureHandle::id);
+    egui::Window::new("Scopes").open(is_open).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.add(
+                drag_value(refresh_hz)
+                    .range(0.5..=30.0)
+                    .speed(0.1)
+                    .suffix(" Hz"),
+            );
+            egui::ComboBox::from_id_source("scopes_stride")
+                .selected_text(format!("1/{stride}"))
+                .show_ui(ui, |ui| {
+                    for s in [1, 2, 4, 8] {
+                        ui.selectable_value(stride, s, format!("1/{s}"));
+                    }
+                });
+            egui::ComboBox::from_id_source("scopes_source")
+                .selected_text(source.label())
+                .show_ui(ui, |ui| {
+                    for option in ReadbackSource::ALL {
+                        ui.selectable_value(source, option, option.label());
+                    }
+                });
+        });
+        if *source == ReadbackSource::Linear && *linear_unavailable {
+            ui.weak("HDR is off, so the camera tonemaps in its shaders and this is the output.");
+        }
+        ui.horizontal(|ui| {
+            for (visible, label) in show.iter_mut().zip(["R", "G", "B", "Luma"]) {
+                ui.checkbox(visible, label);
+            }
+        });
+
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(256.0, 100.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, egui::Color32::from_gray(16));
+        let colors = [
+            egui::Color32::from_rgb(255, 80, 80),
+            egui::Color32::from_rgb(80, 255, 80),
+            egui::Color32::from_rgb(80, 140, 255),
+            egui::Color32::from_gray(230),
+        ];
+        // Ignore the clipped extremes when normalizing, they would flatten everything else.
+        let max = histograms
+            .iter()
+            .zip(show.iter())
+            .filter(|(_, visible)| **visible)
+            .flat_map(|(histogram, _)| histogram[1..255].iter().copied())
+            .max()
+            .unwrap_or(1)
+            .max(1) as f32;
+        for ((histogram, visible), color) in histograms.iter().zip(show.iter()).zip(colors) {
+            if !*visible {
+                continue;
+            }
+            let points = histogram
+                .iter()
+                .enumerate()
+                .map(|(i, &count)| {
+                    let x = rect.left() + i as f32 / 255.0 * rect.width();
+                    let y = rect.bottom() - (count as f32 / max).min(1.0) * rect.height();
+                    egui::pos2(x, y)
+                })
+                .collect();
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+        }
+
+        ui.label("Waveform (luminance)");
+        match waveform {
+            Some(texture) => {
+                ui.image(egui::load::SizedTexture::new(
+                    texture,
+                    egui::vec2(256.0, 128.0),
+                ));
+            }
+            None => {
+                ui.weak("Waiting for the first readback…");
+            }
+        }
+    });
+}
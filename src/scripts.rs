@@ -0,0 +1,384 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AppError,
+    expr::{Expr, ExprError},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    selection::Selection,
+    timeline::AnimationTime,
+    RestRotation,
+};
+
+/// Written into new scripts so the syntax is discoverable.
+const EXAMPLE_SCRIPT: &str = "# spin and bob\nry = ry + dt*90\ny = sin(t*2)*0.5";
+
+/// Variables scripts may read but not assign.
+const READ_ONLY: [&str; 3] = ["t", "time", "dt"];
+
+/// Small per-entity scripts run every frame, attached from the Inspector. A script is a list of
+/// `name = expression` lines in the [`Expr`] language; names other than the entity's own
+/// values are locals for the rest of that frame:
+///
+/// ```text
+/// # comments start with a hash
+/// x, y, z        translation
+/// rx, ry, rz     rotation in degrees
+/// sx, sy, sz     scale
+/// r, g, b, a     material colour, 0 to 1
+/// t, time, dt    animation seconds, real seconds, frame seconds (read only)
+/// ```
+///
+/// Failures are shown next to the script and reported to the Errors window once each.
+pub struct ScriptsPlugin;
+
+impl Plugin for ScriptsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<ScriptsWindow>().add_systems(
+            Update,
+            (
+                scripts_window_system.in_set(ScriptsWindow::ui_set()),
+                run_entity_scripts_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Statement {
+    /// 1-based, for error messages.
+    line: usize,
+    target: String,
+    expr: Expr,
+}
+
+fn compile(source: &str) -> Result<Vec<Statement>, ExprError> {
+    let mut statements = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line = line.split_once('#').map_or(line, |(code, _)| code).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| ExprError(format!("line {}: {message}", index + 1));
+        let Some((target, expr)) = line.split_once('=') else {
+            return Err(error("expected `name = expression`".to_owned()));
+        };
+        let target = target.trim();
+        let valid_name = target
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && target.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(error(format!("`{target}` is not a variable name")));
+        }
+        if READ_ONLY.contains(&target) {
+            return Err(error(format!("`{target}` is read only")));
+        }
+        statements.push(Statement {
+            line: index + 1,
+            target: target.to_owned(),
+            expr: Expr::parse(expr).map_err(|err| error(err.0))?,
+        });
+    }
+    Ok(statements)
+}
+
+/// A script attached to one entity. Only the source and toggle are saved with the scene.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct EntityScript {
+    pub source: String,
+    pub enabled: bool,
+    /// Compiled on first run and whenever the source is edited.
+    #[serde(skip)]
+    compiled: Option<Result<Vec<Statement>, ExprError>>,
+    /// The last runtime failure, cleared by a successful run.
+    #[serde(skip)]
+    failure: Option<ExprError>,
+}
+
+impl Default for EntityScript {
+    fn default() -> Self {
+        Self {
+            source: EXAMPLE_SCRIPT.to_owned(),
+            enabled: true,
+            compiled: None,
+            failure: None,
+        }
+    }
+}
+
+impl PartialEq for EntityScript {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.enabled == other.enabled
+    }
+}
+
+impl EntityScript {
+    /// Why the script does not compile or last failed to run.
+    pub fn error(&self) -> Option<&ExprError> {
+        match &self.compiled {
+            Some(Err(err)) => Some(err),
+            _ => self.failure.as_ref(),
+        }
+    }
+
+    fn statements(&mut self) -> &Result<Vec<Statement>, ExprError> {
+        self.compiled.get_or_insert_with(|| compile(&self.source))
+    }
+}
+
+/// The Inspector's script editor. Returns whether anything changed.
+pub fn script_edit(ui: &mut egui::Ui, script: &mut EntityScript) -> bool {
+    let mut changed = ui
+        .checkbox(&mut script.enabled, "Run every frame")
+        .changed();
+    if ui
+        .add(
+            egui::TextEdit::multiline(&mut script.source)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY),
+        )
+        .changed()
+    {
+        script.compiled = None;
+        script.failure = None;
+        changed = true;
+    }
+    match script.error() {
+        Some(err) => {
+            ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+        }
+        None => {
+            ui.weak("x y z · rx ry rz · sx sy sz · r g b a · t time dt");
+        }
+    }
+    changed
+}
+
+#[derive(Default, Resource)]
+pub struct ScriptsWindow {
+    pub is_open: bool,
+}
+
+impl Panel for ScriptsWindow {
+    const TITLE: &'static str = "Scripts";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn scripts_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<ScriptsWindow>,
+    mut scripts: Query<(Entity, &mut EntityScript, Option<&Name>)>,
+    mut selection: ResMut<Selection>,
+) {
+    let ScriptsWindow { is_open } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(ScriptsWindow::TITLE)
+        .open(is_open)
+        .default_width(320.0)
+        .show(contexts.ctx::<ScriptsWindow>(), |ui| {
+            if scripts.is_empty() {
+                ui.weak("No scripts. Add one to the selected entity from the Inspector.");
+                return;
+            }
+            ui.horizontal(|ui| {
+                for (label, enabled) in [("Enable all", true), ("Disable all", false)] {
+                    if ui.button(label).clicked() {
+                        for (_, mut script, _) in &mut scripts {
+                            script.enabled = enabled;
+                        }
+                    }
+                }
+            });
+            ui.separator();
+            let mut scripts: Vec<_> = scripts.iter_mut().collect();
+            scripts.sort_by_key(|(entity, ..)| *entity);
+            egui::Grid::new("scripts_grid")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (entity, script, name) in &mut scripts {
+                        // Only touch the component when toggled, so change detection stays quiet.
+                        let mut enabled = script.enabled;
+                        if ui.checkbox(&mut enabled, "").changed() {
+                            script.enabled = enabled;
+                        }
+                        let label =
+                            name.map_or_else(|| format!("{entity}"), |name| name.to_string());
+                        let selected = selection.entities.contains(entity);
+                        if ui.selectable_label(selected, label).clicked() {
+                            selection.select(*entity);
+                        }
+                        match script.error() {
+                            Some(err) => {
+                                ui.colored_label(ui.visuals().error_fg_color, err.to_string())
+                            }
+                            None => ui.weak(format!("{} lines", script.source.lines().count())),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn run_entity_scripts_system(
+    mut scripts: Query<(
+        Entity,
+        &mut EntityScript,
+        &mut Transform,
+        Option<&mut RestRotation>,
+        Option<&Handle<StandardMaterial>>,
+        Option<&Name>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    animation_time: Res<AnimationTime>,
+    mut errors: EventWriter<AppError>,
+) {
+    for (entity, mut script, mut transform, rest_rotation, material, name) in &mut scripts {
+        if !script.enabled {
+            continue;
+        }
+        let statements = match script.bypass_change_detection().statements() {
+            Ok(statements) => statements.clone(),
+            Err(_) => continue,
+        };
+
+        // Animated cubes are scripted through their rest orientation, as in the Inspector.
+        let rotation = rest_rotation
+            .as_deref()
+            .map_or(transform.rotation, |rest| **rest);
+        let (rx, ry, rz) = rotation.to_euler(EulerRot::XYZ);
+        let color = material
+            .and_then(|handle| materials.get(handle))
+            .map(|material| material.base_color.to_srgba());
+        let mut values: Vec<(&str, f64)> = vec![
+            ("t", animation_time.seconds as f64),
+            ("time", time.elapsed_seconds_f64()),
+            ("dt", time.delta_seconds_f64()),
+            ("x", transform.translation.x as f64),
+            ("y", transform.translation.y as f64),
+            ("z", transform.translation.z as f64),
+            ("rx", rx.to_degrees() as f64),
+            ("ry", ry.to_degrees() as f64),
+            ("rz", rz.to_degrees() as f64),
+            ("sx", transform.scale.x as f64),
+            ("sy", transform.scale.y as f64),
+            ("sz", transform.scale.z as f64),
+        ];
+        if let Some(color) = color {
+            values.extend([
+                ("r", color.red as f64),
+                ("g", color.green as f64),
+                ("b", color.blue as f64),
+                ("a", color.alpha as f64),
+            ]);
+        }
+        let initial = values.clone();
+
+        let mut failure = None;
+        for statement in &statements {
+            let variable = |name: &str| {
+                values
+                    .iter()
+                    .find(|(existing, _)| *existing == name)
+                    .map(|(_, value)| *value)
+            };
+            let result = statement.expr.eval(&variable).and_then(|value| {
+                if value.is_finite() {
+                    Ok(value)
+                } else {
+                    Err(ExprError("the result is not a finite number".to_owned()))
+                }
+            });
+            match result {
+                Ok(value) => match values
+                    .iter_mut()
+                    .find(|(existing, _)| *existing == statement.target)
+                {
+                    Some((_, existing)) => *existing = value,
+                    None => values.push((statement.target.as_str(), value)),
+                },
+                Err(err) => {
+                    failure = Some(ExprError(format!("line {}: {}", statement.line, err.0)));
+                    break;
+                }
+            }
+        }
+
+        if let Some(err) = failure {
+            if script.failure.as_ref() != Some(&err) {
+                let owner = name.map_or_else(|| format!("{entity}"), |name| name.to_string());
+                errors.send(
+                    AppError::new("Scripts", format!("Script on {owner} failed: {err}"))
+                        .suggest("Edit the script in the Inspector, or disable it."),
+                );
+                script.failure = Some(err);
+            }
+            continue;
+        }
+        if script.failure.is_some() {
+            script.failure = None;
+        }
+
+        let value = |name: &str| {
+            values
+                .iter()
+                .find(|(existing, _)| *existing == name)
+                .map_or(0.0, |(_, value)| *value as f32)
+        };
+        let changed = |names: &[&str]| {
+            names.iter().any(|name| {
+                initial
+                    .iter()
+                    .zip(&values)
+                    .any(|((existing, before), (_, after))| existing == name && before != after)
+            })
+        };
+        // Only write what the script changed, so untouched components keep quiet.
+        if changed(&["x", "y", "z"]) {
+            transform.translation = Vec3::new(value("x"), value("y"), value("z"));
+        }
+        if changed(&["sx", "sy", "sz"]) {
+            transform.scale = Vec3::new(value("sx"), value("sy"), value("sz"));
+        }
+        if changed(&["rx", "ry", "rz"]) {
+            let rotation = Quat::from_euler(
+                EulerRot::XYZ,
+                value("rx").to_radians(),
+                value("ry").to_radians(),
+                value("rz").to_radians(),
+            );
+            match rest_rotation {
+                Some(mut rest) => rest.0 = rotation,
+                None => transform.rotation = rotation,
+            }
+        }
+        if color.is_some() && changed(&["r", "g", "b", "a"]) {
+            if let Some(material) = material.and_then(|handle| materials.get_mut(handle)) {
+                let alpha = value("a").clamp(0.0, 1.0);
+                material.base_color = Srgba::new(
+                    value("r").clamp(0.0, 1.0),
+                    value("g").clamp(0.0, 1.0),
+                    value("b").clamp(0.0, 1.0),
+                    alpha,
+                )
+                .into();
+                if alpha < 1.0 && material.alpha_mode == AlphaMode::Opaque {
+                    material.alpha_mode = AlphaMode::Blend;
+                }
+            }
+        }
+    }
+}
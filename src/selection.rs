@@ -0,0 +1,480 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{primitives::Aabb, view::RenderLayers},
+};
+use bevy_egui::egui;
+use xihydra_bevy::widgets::Trackball;
+
+use crate::{
+    batching::{BakeCommand, BakedBatch},
+    groups::{outermost_group, Group, GroupCommand},
+    input::{InputOwner, InputRouting},
+    numeric::{drag_value, unit_drag_value, unit_slider, Unit},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    picking::Picking,
+    properties::{properties_edit, Properties},
+    scripts::{script_edit, EntityScript},
+    settings::Settings,
+    viewport::{Viewport, ViewportTool},
+    RestRotation, Static, ViewportCamera,
+};
+
+/// Entity selection by clicking in the viewport, its outline and the Inspector window.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .register_panel::<InspectorWindow>()
+            .add_systems(
+                Update,
+                (
+                    click_select_system.after(crate::UiSet::Central),
+                    prune_selection_system,
+                    draw_selection_system,
+                    inspector_window_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Selected entities in selection order; the last one is the primary selection.
+#[derive(Default, Resource)]
+pub struct Selection {
+    pub entities: Vec<Entity>,
+}
+
+impl Selection {
+    pub fn primary(&self) -> Option<Entity> {
+        self.entities.last().copied()
+    }
+
+    pub fn select(&mut self, entity: Entity) {
+        self.entities.clear();
+        self.entities.push(entity);
+    }
+
+    pub fn toggle(&mut self, entity: Entity) {
+        match self.entities.iter().position(|e| *e == entity) {
+            Some(index) => {
+                self.entities.remove(index);
+            }
+            None => self.entities.push(entity),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+}
+
+fn click_select_system(
+    viewport: Res<Viewport>,
+    routing: Res<InputRouting>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    picking: Picking,
+    parents: Query<&Parent>,
+    groups: Query<(), With<Group>>,
+    mut selection: ResMut<Selection>,
+) {
+    if !viewport.clicked || routing.pointer != InputOwner::Viewport {
+        return;
+    }
+    let additive = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    // Clicks pick whole groups; Ctrl reaches into them for a single member.
+    let into_groups = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let hit = picking.pick_pointer(&[]).map(|hit| {
+        if into_groups {
+            hit.entity
+        } else {
+            outermost_group(hit.entity, &parents, &groups)
+        }
+    });
+    match (hit, additive) {
+        (Some(entity), true) => selection.toggle(entity),
+        (Some(entity), false) => selection.select(entity),
+        (None, false) => selection.clear(),
+        (None, true) => {}
+    }
+}
+
+fn prune_selection_system(mut selection: ResMut<Selection>, entities: Query<Entity>) {
+    if selection.entities.iter().any(|e| !entities.contains(*e)) {
+        selection.entities.retain(|e| entities.contains(*e));
+    }
+}
+
+fn draw_selection_system(
+    selection: Res<Selection>,
+    settings: Res<Settings>,
+    query: Query<(&GlobalTransform, &Aabb)>,
+    mut gizmos: Gizmos,
+) {
+    let highlights = settings.highlights();
+    for &entity in &selection.entities {
+        let Ok((transform, aabb)) = query.get(entity) else {
+            continue;
+        };
+        let color = if Some(entity) == selection.primary() {
+            highlights.primary
+        } else {
+            highlights.secondary
+        };
+        let local = Transform::from_translation(aabb.center.into())
+            .with_scale(Vec3::from(aabb.half_extents) * 2.02);
+        gizmos.cuboid(transform.mul_transform(local), color);
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct InspectorWindow {
+    pub is_open: bool,
+}
+
+impl Panel for InspectorWindow {
+    const TITLE: &'static str = "Inspector";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn inspector_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<InspectorWindow>,
+    selection: Res<Selection>,
+    mut query: Query<(
+        &mut Transform,
+        Option<&mut RestRotation>,
+        Option<&Handle<StandardMaterial>>,
+        Has<Static>,
+        Has<BakedBatch>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    groups: Query<(), With<Group>>,
+    mut group_commands: EventWriter<GroupCommand>,
+    mut tool: ResMut<ViewportTool>,
+    mut bake_commands: EventWriter<BakeCommand>,
+    mut commands: Commands,
+    properties: Query<&Properties>,
+    scripts: Query<&EntityScript>,
+    meshes: Query<
+        (
+            Option<&RenderLayers>,
+            Has<NotShadowCaster>,
+            Has<NotShadowReceiver>,
+        ),
+        With<Handle<Mesh>>,
+    >,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    // Selecting something is the natural moment to show its properties.
+    if selection.is_changed() && selection.primary().is_some() {
+        window.is_open = true;
+    }
+    if !window.is_open {
+        return;
+    }
+
+    egui::Window::new("Inspector")
+        .open(&mut window.is_open)
+        .default_width(260.0)
+        .show(contexts.ctx::<InspectorWindow>(), |ui| {
+            let Some(entity) = selection.primary() else {
+                ui.weak("Click an entity in the viewport to select it.");
+                return;
+            };
+            if selection.entities.len() > 1 {
+                ui.horizontal(|ui| {
+                    ui.weak(format!("{} entities selected", selection.entities.len()));
+                    if ui.button("Group").clicked() {
+                        group_commands.send(GroupCommand::Group);
+                    }
+                    if ui
+                        .button("Bake static")
+                        .on_hover_text("Merge selected static cubes sharing a material")
+                        .clicked()
+                    {
+                        bake_commands.send(BakeCommand::Bake);
+                    }
+                });
+            }
+            if groups.contains(entity) {
+                ui.horizontal(|ui| {
+                    if ui.button("Ungroup").clicked() {
+                        group_commands.send(GroupCommand::Ungroup);
+                    }
+                    if ui.button("Center pivot").clicked() {
+                        group_commands.send(GroupCommand::CenterPivot);
+                    }
+                    let picking_pivot = *tool == ViewportTool::PickPivot;
+                    if ui.selectable_label(picking_pivot, "Pick pivot").clicked() {
+                        *tool = if picking_pivot {
+                            ViewportTool::Select
+                        } else {
+                            ViewportTool::PickPivot
+                        };
+                    }
+                });
+            }
+            let Ok((mut transform, rest_rotation, material, is_static, is_batch)) =
+                query.get_mut(entity)
+            else {
+                return;
+            };
+            if groups.contains(entity) {
+                ui.label(format!("Group {entity}"));
+            } else if is_batch {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Baked batch {entity}"));
+                    if ui.button("Unbake").clicked() {
+                        bake_commands.send(BakeCommand::Unbake);
+                    }
+                });
+            } else {
+                ui.label(format!("Entity {entity}"));
+            }
+            if rest_rotation.is_some() {
+                let mut animated = !is_static;
+                if ui.checkbox(&mut animated, "Animated").changed() {
+                    if animated {
+                        commands.entity(entity).remove::<Static>();
+                    } else {
+                        commands.entity(entity).insert(Static);
+                    }
+                }
+            }
+
+            // Edit copies so change detection only fires on actual edits.
+            let mut translation = transform.translation;
+            let mut scale = transform.scale;
+            // Animated cubes are edited through their rest orientation.
+            let rotation = rest_rotation
+                .as_deref()
+                .map_or(transform.rotation, |rest| **rest);
+            let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+            let mut degrees = Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees());
+
+            egui::Grid::new("inspector_transform")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Translation");
+                    if vec3_edit(ui, &mut translation, 0.05, Some(Unit::Meters)) {
+                        transform.translation = translation;
+                    }
+                    ui.end_row();
+
+                    ui.label("Rotation");
+                    let mut edited = None;
+                    if vec3_edit(ui, &mut degrees, 1.0, Some(Unit::Degrees)) {
+                        edited = Some(Quat::from_euler(
+                            EulerRot::XYZ,
+                            degrees.x.to_radians(),
+                            degrees.y.to_radians(),
+                            degrees.z.to_radians(),
+                        ));
+                    }
+                    ui.end_row();
+
+                    ui.label("");
+                    let mut turned = rotation;
+                    // Seen through the viewport camera, so the ball turns the way drags look.
+                    let view = cameras.get_single().map_or(Quat::IDENTITY, |camera| {
+                        camera.to_scale_rotation_translation().1
+                    });
+                    if ui
+                        .add(Trackball::new(&mut turned).diameter(84.0).view(view))
+                        .changed()
+                    {
+                        edited = Some(turned);
+                    }
+                    ui.end_row();
+                    if let Some(rotation) = edited {
+                        match rest_rotation {
+                            Some(mut rest) => rest.0 = rotation,
+                            None => transform.rotation = rotation,
+                        }
+                    }
+
+                    ui.label("Scale");
+                    if vec3_edit(ui, &mut scale, 0.01, None) {
+                        transform.scale = scale;
+                    }
+                    ui.end_row();
+                });
+
+            if let Some(handle) = material {
+                if let Some(mut edited) = materials.get(handle).cloned() {
+                    ui.separator();
+                    if material_edit(ui, &mut edited) {
+                        materials.insert(handle, edited);
+                    }
+                }
+            }
+
+            if let Ok((layers, not_caster, not_receiver)) = meshes.get(entity) {
+                egui::CollapsingHeader::new("Rendering").show(ui, |ui| {
+                    egui::Grid::new("inspector_rendering")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Layers");
+                            let mut edited = layers.cloned().unwrap_or_default();
+                            if render_layers_edit(ui, &mut edited) {
+                                commands.entity(entity).insert(edited);
+                            }
+                            ui.end_row();
+
+                            ui.label("Shadows");
+                            ui.horizontal(|ui| {
+                                let mut casts = !not_caster;
+                                if ui.checkbox(&mut casts, "Cast").changed() {
+                                    if casts {
+                                        commands.entity(entity).remove::<NotShadowCaster>();
+                                    } else {
+                                        commands.entity(entity).insert(NotShadowCaster);
+                                    }
+                                }
+                                let mut receives = !not_receiver;
+                                if ui.checkbox(&mut receives, "Receive").changed() {
+                                    if receives {
+                                        commands.entity(entity).remove::<NotShadowReceiver>();
+                                    } else {
+                                        commands.entity(entity).insert(NotShadowReceiver);
+                                    }
+                                }
+                            });
+                            ui.end_row();
+                        });
+                });
+            }
+
+            ui.separator();
+            egui::CollapsingHeader::new("Properties")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let mut edited = properties.get(entity).cloned().unwrap_or_default();
+                    if properties_edit(ui, &mut edited) {
+                        commands.entity(entity).insert(edited);
+                    }
+                });
+            egui::CollapsingHeader::new("Script").show(ui, |ui| match scripts.get(entity) {
+                Ok(script) => {
+                    let mut edited = script.clone();
+                    if script_edit(ui, &mut edited) {
+                        commands.entity(entity).insert(edited);
+                    }
+                    if ui.button("Remove script").clicked() {
+                        commands.entity(entity).remove::<EntityScript>();
+                    }
+                }
+                Err(_) => {
+                    if ui
+                        .button("Add script")
+                        .on_hover_text("Run a small script on this entity every frame")
+                        .clicked()
+                    {
+                        commands.entity(entity).insert(EntityScript::default());
+                    }
+                }
+            });
+        });
+}
+
+/// Edits the three components of `value`, each shown in `unit` when it has one.
+pub fn vec3_edit(ui: &mut egui::Ui, value: &mut Vec3, speed: f64, unit: Option<Unit>) -> bool {
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        for (component, label) in [&mut value.x, &mut value.y, &mut value.z]
+            .into_iter()
+            .zip(["x ", "y ", "z "])
+        {
+            let field = match unit {
+                Some(unit) => unit_drag_value(component, unit),
+                None => drag_value(component),
+            };
+            changed |= ui.add(field.speed(speed).prefix(label)).changed();
+        }
+        changed
+    })
+    .inner
+}
+
+/// Render layers offered for editing; the higher ones are used internally by the stereo and
+/// sprite sheet cameras.
+const EDITABLE_LAYERS: usize = 8;
+
+/// Membership toggles for the first render layers. Entities and lights only interact with
+/// cameras and lights sharing a layer with them.
+pub fn render_layers_edit(ui: &mut egui::Ui, layers: &mut RenderLayers) -> bool {
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        for layer in 0..EDITABLE_LAYERS {
+            let mut member = layers.intersects(&RenderLayers::layer(layer));
+            if ui
+                .toggle_value(&mut member, layer.to_string())
+                .on_hover_text(format!("Render layer {layer}"))
+                .changed()
+            {
+                *layers = if member {
+                    layers.clone().with(layer)
+                } else {
+                    layers.clone().without(layer)
+                };
+                changed = true;
+            }
+        }
+        changed
+    })
+    .inner
+}
+
+/// The `StandardMaterial` fields worth tweaking interactively.
+pub fn material_edit(ui: &mut egui::Ui, material: &mut StandardMaterial) -> bool {
+    let mut changed = false;
+    egui::Grid::new(ui.next_auto_id())
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Base color");
+            let mut rgba = material.base_color.to_srgba().to_f32_array();
+            if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                material.base_color = Color::srgba(rgba[0], rgba[1], rgba[2], rgba[3]);
+                changed = true;
+            }
+            ui.end_row();
+            ui.label("Metallic");
+            changed |= ui
+                .add(unit_slider(
+                    &mut material.metallic,
+                    0.0..=1.0,
+                    Unit::Percent,
+                ))
+                .changed();
+            ui.end_row();
+            ui.label("Roughness");
+            changed |= ui
+                .add(unit_slider(
+                    &mut material.perceptual_roughness,
+                    0.089..=1.0,
+                    Unit::Percent,
+                ))
+                .changed();
+            ui.end_row();
+            ui.label("Reflectance");
+            changed |= ui
+                .add(unit_slider(
+                    &mut material.reflectance,
+                    0.0..=1.0,
+                    Unit::Percent,
+                ))
+                .changed();
+            ui.end_row();
+            ui.label("Unlit");
+            changed |= ui.checkbox(&mut material.unlit, "").changed();
+            ui.end_row();
+        });
+    changed
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    icons::{Icon, IconButtonsExt},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    scene::{Project, SceneId},
+    selection::Selection,
+};
+
+/// Named multi-selections saved with the scene. Recalling a set either replaces the current
+/// selection or adds to it, so the same subset of a large scene can be picked up again after
+/// working elsewhere.
+pub struct SelectionSetsPlugin;
+
+impl Plugin for SelectionSetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<SelectionSetsWindow>().add_systems(
+            Update,
+            selection_sets_window_system.in_set(SelectionSetsWindow::ui_set()),
+        );
+    }
+}
+
+/// A saved selection, by the scene ids of its cubes and groups.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionSet {
+    pub name: String,
+    pub ids: Vec<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum RecallMode {
+    #[default]
+    Replace,
+    Add,
+}
+
+#[derive(Default, Resource)]
+pub struct SelectionSetsWindow {
+    pub is_open: bool,
+    new_name: String,
+    mode: RecallMode,
+}
+
+impl Panel for SelectionSetsWindow {
+    const TITLE: &'static str = "Selection Sets";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+enum SetAction {
+    Recall(usize),
+    Update(usize),
+    Remove(usize),
+}
+
+fn selection_sets_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SelectionSetsWindow>,
+    mut project: ResMut<Project>,
+    mut selection: ResMut<Selection>,
+    ids: Query<(Entity, &SceneId)>,
+) {
+    let SelectionSetsWindow {
+        is_open,
+        new_name,
+        mode,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let selected_ids: Vec<u64> = selection
+        .entities
+        .iter()
+        .filter_map(|entity| ids.get(*entity).ok())
+        .map(|(_, id)| **id)
+        .collect();
+    let by_id: HashMap<u64, Entity> = ids.iter().map(|(entity, id)| (**id, entity)).collect();
+    let mut action = None;
+    egui::Window::new(SelectionSetsWindow::TITLE)
+        .open(is_open)
+        .default_width(320.0)
+        .show(contexts.ctx::<SelectionSetsWindow>(), |ui| {
+            let sets = &mut project.selection_sets;
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(new_name)
+                        .hint_text(format!("Set {}", sets.len() + 1))
+                        .desired_width(150.0),
+                );
+                if ui
+                    .add_enabled(
+                        !selected_ids.is_empty(),
+                        egui::Button::new("Save selection"),
+                    )
+                    .on_disabled_hover_text("Select some entities first")
+                    .clicked()
+                {
+                    let name = match new_name.trim() {
+                        "" => format!("Set {}", sets.len() + 1),
+                        name => name.to_owned(),
+                    };
+                    sets.push(SelectionSet {
+                        name,
+                        ids: selected_ids.clone(),
+                    });
+                    new_name.clear();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Recall");
+                ui.selectable_value(mode, RecallMode::Replace, "Replace")
+                    .on_hover_text("Select only the set's entities");
+                ui.selectable_value(mode, RecallMode::Add, "Add")
+                    .on_hover_text("Add the set's entities to the selection");
+            });
+
+            ui.separator();
+            if sets.is_empty() {
+                ui.weak("No selection sets. Select some entities and save them.");
+                return;
+            }
+            egui::Grid::new("selection_sets")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, set) in sets.iter_mut().enumerate() {
+                        if ui
+                            .icon_button(Icon::Play, "")
+                            .on_hover_text("Recall this set")
+                            .clicked()
+                        {
+                            action = Some(SetAction::Recall(index));
+                        }
+                        ui.add(egui::TextEdit::singleline(&mut set.name).desired_width(140.0));
+                        let found = set.ids.iter().filter(|id| by_id.contains_key(id)).count();
+                        let count = ui.label(format!("{found}"));
+                        if found < set.ids.len() {
+                            count.on_hover_text(format!(
+                                "{} of the saved entities no longer exist",
+                                set.ids.len() - found
+                            ));
+                        }
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    !selected_ids.is_empty(),
+                                    egui::Button::new("⟳").small(),
+                                )
+                                .on_hover_text("Replace with the current selection")
+                                .clicked()
+                            {
+                                action = Some(SetAction::Update(index));
+                            }
+                            if ui
+                                .small_icon_button(Icon::Delete, "")
+                                .on_hover_text("Remove set")
+                                .clicked()
+                            {
+                                action = Some(SetAction::Remove(index));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+
+    match action {
+        Some(SetAction::Recall(index)) => {
+            let set = &project.selection_sets[index];
+            if *mode == RecallMode::Replace {
+                selection.clear();
+            }
+            // Kept in the order saved, so the primary selection is the same as before.
+            for id in &set.ids {
+                let Some(&entity) = by_id.get(id) else {
+                    continue;
+                };
+                if let Some(position) = selection.entities.iter().position(|e| *e == entity) {
+                    selection.entities.remove(position);
+                }
+                selection.entities.push(entity);
+            }
+        }
+        Some(SetAction::Update(index)) => project.selection_sets[index].ids = selected_ids,
+        Some(SetAction::Remove(index)) => {
+            project.selection_sets.remove(index);
+        }
+        None => {}
+    }
+}
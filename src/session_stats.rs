@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::Serialize;
+
+use crate::errors::AppError;
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+use crate::viewport::ViewportTool;
+
+pub const SESSION_STATS_PATH: &str = "session_stats.ron";
+
+/// Counts what happens during a session (spawns, strokes, undos, time per viewport tool) so
+/// users and maintainers can see which parts of the sandbox actually get used.
+pub struct SessionStatsPlugin;
+
+impl Plugin for SessionStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionStats>()
+            .add_event::<SessionEvent>()
+            .register_panel::<SessionStatsWindow>()
+            .add_systems(
+                Update,
+                (
+                    count_session_events_system,
+                    session_stats_window_system.in_set(SessionStatsWindow::ui_set()),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Sent by the features being counted; the stats never reach into them.
+#[derive(Event, Clone, Copy)]
+pub enum SessionEvent {
+    Spawned(usize),
+    StrokeDrawn,
+    Undo,
+}
+
+#[derive(Default, Resource, Serialize)]
+pub struct SessionStats {
+    pub entities_spawned: usize,
+    pub strokes_drawn: usize,
+    pub undo_count: usize,
+    /// Seconds spent in each viewport tool, in first-use order.
+    pub tool_seconds: Vec<(String, f32)>,
+}
+
+impl SessionStats {
+    fn add_tool_time(&mut self, tool: ViewportTool, seconds: f32) {
+        let name = format!("{tool:?}");
+        match self.tool_seconds.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, total)) => *total += seconds,
+            None => self.tool_seconds.push((name, seconds)),
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct SessionStatsWindow {
+    pub is_open: bool,
+}
+
+impl Panel for SessionStatsWindow {
+    const TITLE: &'static str = "Session Stats";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn count_session_events_system(
+    mut events: EventReader<SessionEvent>,
+    mut stats: ResMut<SessionStats>,
+    tool: Res<ViewportTool>,
+    time: Res<Time<Real>>,
+) {
+    for event in events.read() {
+        match *event {
+            SessionEvent::Spawned(count) => stats.entities_spawned += count,
+            SessionEvent::StrokeDrawn => stats.strokes_drawn += 1,
+            SessionEvent::Undo => stats.undo_count += 1,
+        }
+    }
+    stats.add_tool_time(*tool, time.delta_seconds());
+}
+
+fn session_stats_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SessionStatsWindow>,
+    stats: Res<SessionStats>,
+    time: Res<Time<Real>>,
+    mut errors: EventWriter<AppError>,
+) {
+    let SessionStatsWindow { is_open } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut export = false;
+    egui::Window::new(SessionStatsWindow::TITLE)
+        .open(is_open)
+        .default_width(280.0)
+        .show(contexts.ctx::<SessionStatsWindow>(), |ui| {
+            let elapsed = time.elapsed_seconds();
+            egui::Grid::new("session_counters")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Session length");
+                    ui.monospace(duration(elapsed));
+                    ui.end_row();
+                    ui.label("Entities spawned");
+                    ui.monospace(stats.entities_spawned.to_string());
+                    ui.end_row();
+                    ui.label("Strokes drawn");
+                    ui.monospace(stats.strokes_drawn.to_string());
+                    ui.end_row();
+                    ui.label("Undos");
+                    ui.monospace(stats.undo_count.to_string());
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.strong("Time per tool");
+            for (tool, seconds) in &stats.tool_seconds {
+                let share = if elapsed > 0.0 {
+                    seconds / elapsed
+                } else {
+                    0.0
+                };
+                ui.horizontal(|ui| {
+                    ui.add_sized([110.0, 16.0], egui::Label::new(tool.as_str()));
+                    ui.add(
+                        egui::ProgressBar::new(share.clamp(0.0, 1.0))
+                            .desired_width(90.0)
+                            .text(duration(*seconds)),
+                    );
+                });
+            }
+
+            ui.separator();
+            export = ui
+                .button("Export")
+                .on_hover_text(format!("Write these counters to {SESSION_STATS_PATH}"))
+                .clicked();
+        });
+
+    if export {
+        let written = ron::ser::to_string_pretty(&*stats, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                std::fs::write(SESSION_STATS_PATH, contents).map_err(|err| err.to_string())
+            });
+        match written {
+            Ok(()) => info!("Exported session stats to {SESSION_STATS_PATH}"),
+            Err(err) => {
+                errors.send(
+                    AppError::new(
+                        "Session Stats",
+                        format!("Failed to write {SESSION_STATS_PATH}: {err}"),
+                    )
+                    .suggest("Check that the working directory is writable, then export again."),
+                );
+            }
+        }
+    }
+}
+
+fn duration(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
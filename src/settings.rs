@@ -0,0 +1,1253 @@
+use std::ops::RangeInclusive;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AppError,
+    icons::{Icon, IconButtonsExt},
+    keybindings::MACRO_SLOT_KEYS,
+    numeric::{self, drag_value, NumberFormat},
+    safe_mode::SafeMode,
+    scene_templates::SceneTemplate,
+    versioning::{unversioned, Migration, Versioned},
+};
+
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+// What the Settings window allows; loaded values are brought into the same bounds.
+const MSAA_SAMPLES: [u32; 4] = [1, 2, 4, 8];
+const WINDOW_ROUNDING: RangeInclusive<f32> = 0.0..=12.0;
+const AUTOSAVE_INTERVAL: RangeInclusive<f32> = 1.0..=3600.0;
+const GALLERY_INTERVAL: RangeInclusive<f32> = 10.0..=3600.0;
+const GALLERY_LIMIT: RangeInclusive<usize> = 1..=500;
+const SPAWN_RANGE: RangeInclusive<f32> = 1.0..=50.0;
+const CUBE_SIZE: RangeInclusive<f32> = 0.1..=5.0;
+const ENTITY_BUDGET: RangeInclusive<u32> = 1..=100_000;
+const TICK_HZ: RangeInclusive<f64> = 5.0..=240.0;
+
+/// Owns the `Settings` resource, its persistence and the Settings window.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load().unwrap_or_else(|err| {
+            warn!("Ignoring {SETTINGS_PATH}, starting in safe mode: {err}");
+            app.insert_resource(SafeMode::new(err));
+            Settings::default()
+        });
+        app.insert_resource(settings)
+            .init_resource::<SettingsWindow>()
+            .add_systems(
+                Update,
+                (
+                    settings_window_system,
+                    apply_settings_system,
+                    autosave_settings_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Every user-facing option of the sandbox, persisted as a single RON file.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Zero in files written before the format was versioned, see [`Versioned`].
+    #[serde(default)]
+    pub version: u32,
+    pub graphics: GraphicsSettings,
+    pub input: InputSettings,
+    pub theme: ThemeSettings,
+    pub fonts: FontSettings,
+    pub autosave: AutosaveSettings,
+    pub spawn: SpawnSettings,
+    pub accessibility: AccessibilitySettings,
+    pub simulation: SimulationSettings,
+    pub import: ImportSettings,
+    pub units: UnitSettings,
+    /// Named sets of open panels, switched from View › Workspaces.
+    pub workspaces: Vec<Workspace>,
+    /// Recorded sequences of menu commands, replayed from the Macros window.
+    pub macros: Vec<UiMacro>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    pub msaa_samples: u32,
+    pub hidpi_scaling: bool,
+    /// Borderless, always-on-top window showing only the viewport; see `overlay`.
+    pub overlay_mode: bool,
+    /// The window's `ClearColor`, straight RGBA; it shows wherever egui draws nothing.
+    pub clear_color: [f32; 4],
+    /// How the viewport camera clears its image in the Solid background mode.
+    pub viewport_clear: ViewportClear,
+    pub viewport_clear_color: [f32; 4],
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            msaa_samples: 4,
+            hidpi_scaling: true,
+            overlay_mode: false,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            viewport_clear: ViewportClear::Custom,
+            viewport_clear_color: [0.07, 0.07, 0.07, 1.0],
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// The viewport clear mode and colour, shared by the Settings and Background windows.
+    pub fn viewport_clear_ui(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let mut response = ui
+            .horizontal(|ui| {
+                let mut response = egui::ComboBox::from_id_source("viewport_clear")
+                    .selected_text(self.viewport_clear.label())
+                    .show_ui(ui, |ui| {
+                        ViewportClear::ALL
+                            .into_iter()
+                            .map(|mode| {
+                                ui.selectable_value(&mut self.viewport_clear, mode, mode.label())
+                            })
+                            .reduce(|a, b| a | b)
+                            .unwrap()
+                    });
+                if response.inner.as_ref().is_some_and(|inner| inner.changed()) {
+                    response.response.mark_changed();
+                }
+                if self.viewport_clear == ViewportClear::Custom {
+                    response.response |= ui
+                        .color_edit_button_rgba_unmultiplied(&mut self.viewport_clear_color)
+                        .on_hover_text(
+                            "Alpha below 1 lets the panel behind the viewport image show through",
+                        );
+                }
+                response.response
+            })
+            .inner;
+        if self.viewport_clear == ViewportClear::None {
+            response = response.on_hover_text("The image keeps whatever the last frame left in it");
+        }
+        response
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewportClear {
+    /// The window clear colour.
+    Window,
+    Custom,
+    /// Not cleared at all.
+    None,
+}
+
+impl ViewportClear {
+    const ALL: [ViewportClear; 3] = [
+        ViewportClear::Window,
+        ViewportClear::Custom,
+        ViewportClear::None,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ViewportClear::Window => "Window clear color",
+            ViewportClear::Custom => "Custom",
+            ViewportClear::None => "Don't clear",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputSettings {
+    pub shortcuts_enabled: bool,
+    /// Show an on-screen keyboard while a text field has focus.
+    pub virtual_keyboard: bool,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            shortcuts_enabled: true,
+            virtual_keyboard: false,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub dark_mode: bool,
+    pub window_rounding: f32,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            dark_mode: true,
+            window_rounding: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSettings {
+    /// Font files tried in order after egui's built-in fonts, for the scripts and symbols those
+    /// lack. Missing files are skipped, so the defaults can name fonts from every platform.
+    pub fallbacks: Vec<String>,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        let fallbacks = [
+            "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+            "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+            "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+            "/usr/share/fonts/truetype/noto/NotoSansSymbols2-Regular.ttf",
+            "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+            "C:\\Windows\\Fonts\\msyh.ttc",
+            "C:\\Windows\\Fonts\\malgun.ttf",
+            "C:\\Windows\\Fonts\\seguisym.ttf",
+        ];
+        Self {
+            fallbacks: fallbacks.map(str::to_owned).to_vec(),
+        }
+    }
+}
+
+impl FontSettings {
+    /// Text in several scripts plus emoji, to check the fallback chain covers them.
+    pub const SAMPLE: &'static str = "Aa Ωψ Жж أب हि 中文 日本語 한국어 😀🎉✔";
+
+    /// The fallback chain as a reorderable list, with a field for adding fonts.
+    pub fn fallbacks_ui(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let mut response = ui.vertical(|ui| {
+            let mut changed = false;
+            let mut moved = None;
+            let mut removed = None;
+            let count = self.fallbacks.len();
+            for (index, path) in self.fallbacks.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("⏶").small())
+                        .on_hover_text("Try this font earlier")
+                        .clicked()
+                    {
+                        moved = Some((index, index - 1));
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("⏷").small())
+                        .on_hover_text("Try this font later")
+                        .clicked()
+                    {
+                        moved = Some((index, index + 1));
+                    }
+                    if ui
+                        .small_icon_button(Icon::Delete, "")
+                        .on_hover_text("Remove")
+                        .clicked()
+                    {
+                        removed = Some(index);
+                    }
+                    if std::path::Path::new(path).exists() {
+                        ui.monospace(path);
+                    } else {
+                        ui.add_enabled(
+                            false,
+                            egui::Label::new(egui::RichText::new(path).monospace()),
+                        )
+                        .on_disabled_hover_text("Not found; skipped");
+                    }
+                });
+            }
+            if let Some((from, to)) = moved {
+                self.fallbacks.swap(from, to);
+                changed = true;
+            }
+            if let Some(index) = removed {
+                self.fallbacks.remove(index);
+                changed = true;
+            }
+
+            let id = ui.id().with("new_fallback_font");
+            let mut new_path: String = ui.data_mut(|data| data.get_temp(id).unwrap_or_default());
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut new_path)
+                        .hint_text("path to a .ttf, .otf or .ttc file")
+                        .desired_width(220.0),
+                );
+                if ui
+                    .add_enabled(!new_path.trim().is_empty(), egui::Button::new("Add"))
+                    .clicked()
+                {
+                    self.fallbacks.push(new_path.trim().to_owned());
+                    new_path.clear();
+                    changed = true;
+                }
+            });
+            ui.data_mut(|data| data.insert_temp(id, new_path));
+            changed
+        });
+        if response.inner {
+            response.response.mark_changed();
+        }
+        response.response
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutosaveSettings {
+    pub enabled: bool,
+    pub interval_secs: f32,
+    /// Take a snapshot with a viewport thumbnail whenever the scene changed in this long.
+    pub gallery_enabled: bool,
+    pub gallery_interval_secs: f32,
+    /// Automatic snapshots kept; the oldest are dropped past this.
+    pub gallery_limit: usize,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 30.0,
+            gallery_enabled: true,
+            gallery_interval_secs: 180.0,
+            gallery_limit: 40,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnSettings {
+    pub range: f32,
+    pub cube_size: f32,
+    pub color: [f32; 3],
+    /// Cube count above which the sandbox warns and offers cleanup.
+    pub entity_budget: u32,
+    /// The scene built when the sandbox starts.
+    pub startup_template: SceneTemplate,
+}
+
+impl Default for SpawnSettings {
+    fn default() -> Self {
+        Self {
+            range: 10.0,
+            cube_size: 1.0,
+            color: [0.8, 0.7, 0.6],
+            entity_budget: 1000,
+            startup_template: SceneTemplate::default(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    /// Titles of the panels open in this workspace; every other panel is closed.
+    pub panels: Vec<String>,
+}
+
+impl Workspace {
+    fn new(name: &str, panels: &[&str]) -> Self {
+        Self {
+            name: name.to_owned(),
+            panels: panels.iter().map(|title| (*title).to_owned()).collect(),
+        }
+    }
+
+    fn presets() -> Vec<Self> {
+        vec![
+            Self::new("Modeling", &["Hierarchy", "Inspector", "Palette"]),
+            Self::new(
+                "Animation",
+                &["Hierarchy", "Inspector", "Bindings", "Day/Night"],
+            ),
+            Self::new("Debug", &["Errors", "Culling", "Telemetry", "Scopes"]),
+        ]
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiMacro {
+    pub name: String,
+    /// Labels of the menu items run, in order.
+    pub steps: Vec<String>,
+    /// Run with Ctrl and this digit, 1 to 9.
+    #[serde(default)]
+    pub slot: Option<u8>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationSettings {
+    /// Fixed ticks per second for boids and cloth, whatever the display refresh rate.
+    pub tick_hz: f64,
+    /// Blend simulated transforms between the last two ticks when drawing.
+    pub interpolate: bool,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            tick_hz: 60.0,
+            interpolate: true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportSettings {
+    pub watch_enabled: bool,
+    /// Folder under `assets/` whose new models and images are imported automatically.
+    pub watch_folder: String,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            watch_enabled: false,
+            watch_folder: "incoming".to_owned(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnitSettings {
+    pub decimal_separator: DecimalSeparator,
+    /// Suffix measured fields with their unit: m, °, %.
+    pub show_units: bool,
+}
+
+impl Default for UnitSettings {
+    fn default() -> Self {
+        Self {
+            decimal_separator: DecimalSeparator::System,
+            show_units: true,
+        }
+    }
+}
+
+impl UnitSettings {
+    pub fn format(&self) -> NumberFormat {
+        NumberFormat {
+            decimal_comma: match self.decimal_separator {
+                DecimalSeparator::System => numeric::system_decimal_comma(),
+                DecimalSeparator::Dot => false,
+                DecimalSeparator::Comma => true,
+            },
+            show_units: self.show_units,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecimalSeparator {
+    /// Whichever the system locale uses.
+    #[default]
+    System,
+    Dot,
+    Comma,
+}
+
+impl DecimalSeparator {
+    const ALL: [DecimalSeparator; 3] = [
+        DecimalSeparator::System,
+        DecimalSeparator::Dot,
+        DecimalSeparator::Comma,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            DecimalSeparator::System => "System locale",
+            DecimalSeparator::Dot => "Dot (1.5)",
+            DecimalSeparator::Comma => "Comma (1,5)",
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Stops the cubes spinning and makes camera changes instant instead of eased.
+    pub reduced_motion: bool,
+    pub high_contrast: bool,
+    pub highlight_palette: HighlightPalette,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HighlightPalette {
+    #[default]
+    Standard,
+    /// Okabe–Ito colours, distinguishable with the common forms of colour blindness.
+    ColorblindSafe,
+}
+
+/// Colours with a meaning, shared by gizmos, outlines and charts so a palette applies everywhere.
+pub struct Highlights {
+    /// The primary selection and other "this one" markers.
+    pub primary: Color,
+    pub secondary: Color,
+    /// Structure rather than state: groups, frusta.
+    pub info: Color,
+    /// Visible, added.
+    pub positive: Color,
+    /// Culled, removed.
+    pub negative: Color,
+    /// Modified.
+    pub warning: Color,
+    pub muted: Color,
+}
+
+impl HighlightPalette {
+    pub fn highlights(self) -> Highlights {
+        match self {
+            HighlightPalette::Standard => Highlights {
+                primary: Color::srgb(1.0, 0.6, 0.1),
+                secondary: Color::srgb(1.0, 0.9, 0.4),
+                info: Color::srgb(0.4, 0.8, 1.0),
+                positive: Color::srgb(0.3, 0.9, 0.4),
+                negative: Color::srgb(0.95, 0.3, 0.3),
+                warning: Color::srgb(1.0, 0.84, 0.0),
+                muted: Color::srgb(0.5, 0.5, 0.5),
+            },
+            HighlightPalette::ColorblindSafe => Highlights {
+                primary: Color::srgb_u8(230, 159, 0),
+                secondary: Color::srgb_u8(240, 228, 66),
+                info: Color::srgb_u8(204, 121, 167),
+                positive: Color::srgb_u8(86, 180, 233),
+                negative: Color::srgb_u8(213, 94, 0),
+                warning: Color::srgb_u8(240, 228, 66),
+                muted: Color::srgb(0.5, 0.5, 0.5),
+            },
+        }
+    }
+}
+
+impl Settings {
+    pub fn highlights(&self) -> Highlights {
+        self.accessibility.highlight_palette.highlights()
+    }
+}
+
+pub fn egui_color(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.to_srgba().to_u8_array();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            graphics: default(),
+            input: default(),
+            theme: default(),
+            fonts: default(),
+            autosave: default(),
+            spawn: default(),
+            accessibility: default(),
+            simulation: default(),
+            import: default(),
+            units: default(),
+            workspaces: Workspace::presets(),
+            macros: Vec::new(),
+        }
+    }
+}
+
+impl Versioned for Settings {
+    const VERSION: u32 = 1;
+    const MIGRATIONS: &'static [Migration<Self>] = &[unversioned()];
+    const FORMAT: &'static str = "settings";
+
+    fn version_mut(&mut self) -> &mut u32 {
+        &mut self.version
+    }
+}
+
+impl Settings {
+    /// A missing file yields the defaults; a file that cannot be read or no longer parses is an
+    /// error, so the caller can start in safe mode instead of overwriting it. Values outside what
+    /// the Settings window allows are brought back into range.
+    pub fn load() -> Result<Self, String> {
+        match std::fs::read_to_string(SETTINGS_PATH) {
+            Ok(contents) => {
+                let mut settings: Self = ron::from_str(&contents).map_err(|err| err.to_string())?;
+                settings.upgrade();
+                settings.clamp();
+                Ok(settings)
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(format!("could not read {SETTINGS_PATH}: {err}")),
+        }
+    }
+
+    /// Salvages what still parses from a malformed settings file, one section and then one
+    /// option at a time. Returns the settings plus the `section.option` paths that were dropped.
+    pub fn migrate() -> Result<(Self, Vec<String>), String> {
+        let contents = std::fs::read_to_string(SETTINGS_PATH).map_err(|err| err.to_string())?;
+        let sections =
+            ron_fields(&contents).ok_or_else(|| format!("{SETTINGS_PATH} is not a RON struct"))?;
+        let parses = |document: &str| ron::from_str::<Self>(document).is_ok();
+
+        let mut kept = Vec::new();
+        let mut dropped = Vec::new();
+        for (section, body) in sections {
+            if parses(&format!("({section}: {body})")) {
+                kept.push(format!("{section}: {body}"));
+                continue;
+            }
+            let Some(options) = ron_fields(body) else {
+                dropped.push(section.to_owned());
+                continue;
+            };
+            let mut kept_options = Vec::new();
+            for (option, value) in options {
+                if parses(&format!("({section}: ({option}: {value}))")) {
+                    kept_options.push(format!("{option}: {value}"));
+                } else {
+                    dropped.push(format!("{section}.{option}"));
+                }
+            }
+            kept.push(format!("{section}: ({})", kept_options.join(", ")));
+        }
+        let mut settings: Self =
+            ron::from_str(&format!("({})", kept.join(", "))).map_err(|err| err.to_string())?;
+        settings.upgrade();
+        settings.clamp();
+        Ok((settings, dropped))
+    }
+
+    /// Brings every option the Settings window limits back into its range, as a hand-edited
+    /// file may hold anything; some, like the spawn range, panic the systems using them.
+    fn clamp(&mut self) {
+        fn clamp<T: PartialOrd + Copy>(value: &mut T, range: RangeInclusive<T>) {
+            // NaN compares as nothing, and ends up at the start too.
+            if (*value)
+                .partial_cmp(range.start())
+                .is_none_or(std::cmp::Ordering::is_lt)
+            {
+                *value = *range.start();
+            } else if *value > *range.end() {
+                *value = *range.end();
+            }
+        }
+        if !MSAA_SAMPLES.contains(&self.graphics.msaa_samples) {
+            self.graphics.msaa_samples = GraphicsSettings::default().msaa_samples;
+        }
+        clamp(&mut self.theme.window_rounding, WINDOW_ROUNDING);
+        clamp(&mut self.autosave.interval_secs, AUTOSAVE_INTERVAL);
+        clamp(&mut self.autosave.gallery_interval_secs, GALLERY_INTERVAL);
+        clamp(&mut self.autosave.gallery_limit, GALLERY_LIMIT);
+        clamp(&mut self.spawn.range, SPAWN_RANGE);
+        clamp(&mut self.spawn.cube_size, CUBE_SIZE);
+        clamp(&mut self.spawn.entity_budget, ENTITY_BUDGET);
+        clamp(&mut self.simulation.tick_hz, TICK_HZ);
+        for recorded in &mut self.macros {
+            if recorded
+                .slot
+                .is_some_and(|slot| !(1..=MACRO_SLOT_KEYS.len() as u8).contains(&slot))
+            {
+                recorded.slot = None;
+            }
+        }
+    }
+
+    pub fn delete_file() -> std::io::Result<()> {
+        std::fs::remove_file(SETTINGS_PATH)
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| {
+                AppError::new("Settings", format!("Failed to serialize settings: {err}"))
+            })?;
+        std::fs::write(SETTINGS_PATH, contents).map_err(|err| {
+            AppError::new(
+                "Settings",
+                format!("Failed to write {SETTINGS_PATH}: {err}"),
+            )
+            .suggest("Check that the working directory is writable.")
+        })
+    }
+}
+
+/// Splits the text of a RON struct, `(key: value, ...)`, into its top-level fields. Nesting and
+/// string literals are respected; anything that is not a parenthesised struct yields `None`.
+fn ron_fields(source: &str) -> Option<Vec<(&str, &str)>> {
+    let body = source.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let mut fields = Vec::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut colon = None;
+    for (index, c) in body.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ':' if depth == 0 && colon.is_none() => colon = Some(index),
+            ',' if depth == 0 => {
+                fields.push((start, colon.take(), index));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push((start, colon, body.len()));
+
+    fields
+        .into_iter()
+        .filter(|(start, _, end)| !body[*start..*end].trim().is_empty())
+        .map(|(start, colon, end)| {
+            let colon = colon?;
+            Some((body[start..colon].trim(), body[colon + 1..end].trim()))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Graphics,
+    Input,
+    Theme,
+    Autosave,
+    Spawn,
+    Simulation,
+    Import,
+    Units,
+    Accessibility,
+}
+
+impl Category {
+    const ALL: [Category; 9] = [
+        Category::Graphics,
+        Category::Input,
+        Category::Theme,
+        Category::Autosave,
+        Category::Spawn,
+        Category::Simulation,
+        Category::Import,
+        Category::Units,
+        Category::Accessibility,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Category::Graphics => "Graphics",
+            Category::Input => "Input",
+            Category::Theme => "Theme",
+            Category::Autosave => "Autosave",
+            Category::Spawn => "Spawn",
+            Category::Simulation => "Simulation",
+            Category::Import => "Import",
+            Category::Units => "Units",
+            Category::Accessibility => "Accessibility",
+        }
+    }
+}
+
+/// A single row of the Settings window. The name is what the search box matches against.
+struct SettingEntry {
+    category: Category,
+    name: &'static str,
+    ui: fn(&mut Settings, &mut egui::Ui) -> egui::Response,
+}
+
+const ENTRIES: &[SettingEntry] = &[
+    SettingEntry {
+        category: Category::Graphics,
+        name: "MSAA samples",
+        ui: |settings, ui| {
+            let samples = &mut settings.graphics.msaa_samples;
+            let mut response = egui::ComboBox::from_id_source("msaa_samples")
+                .selected_text(format!("{samples}x"))
+                .show_ui(ui, |ui| {
+                    MSAA_SAMPLES
+                        .into_iter()
+                        .map(|n| ui.selectable_value(samples, n, format!("{n}x")))
+                        .reduce(|a, b| a | b)
+                        .unwrap()
+                });
+            if response.inner.as_ref().is_some_and(|inner| inner.changed()) {
+                response.response.mark_changed();
+            }
+            response.response
+        },
+    },
+    SettingEntry {
+        category: Category::Graphics,
+        name: "HiDPI scaling",
+        ui: |settings, ui| ui.checkbox(&mut settings.graphics.hidpi_scaling, ""),
+    },
+    SettingEntry {
+        category: Category::Graphics,
+        name: "Overlay mode",
+        ui: |settings, ui| {
+            ui.checkbox(&mut settings.graphics.overlay_mode, "")
+                .on_hover_text(
+                    "Borderless, always-on-top window with only the viewport and a small \
+                     toolbar. The background becomes see-through after a restart.",
+                )
+        },
+    },
+    SettingEntry {
+        category: Category::Graphics,
+        name: "Window clear color",
+        ui: |settings, ui| {
+            ui.color_edit_button_rgba_unmultiplied(&mut settings.graphics.clear_color)
+                .on_hover_text(
+                    "Behind everything egui draws; replaced by transparency in overlay mode",
+                )
+        },
+    },
+    SettingEntry {
+        category: Category::Graphics,
+        name: "Viewport clear",
+        ui: |settings, ui| settings.graphics.viewport_clear_ui(ui),
+    },
+    SettingEntry {
+        category: Category::Input,
+        name: "Enable keyboard shortcuts",
+        ui: |settings, ui| ui.checkbox(&mut settings.input.shortcuts_enabled, ""),
+    },
+    SettingEntry {
+        category: Category::Input,
+        name: "On-screen keyboard",
+        ui: |settings, ui| ui.checkbox(&mut settings.input.virtual_keyboard, ""),
+    },
+    SettingEntry {
+        category: Category::Theme,
+        name: "Dark mode",
+        ui: |settings, ui| ui.checkbox(&mut settings.theme.dark_mode, ""),
+    },
+    SettingEntry {
+        category: Category::Theme,
+        name: "Fallback fonts",
+        ui: |settings, ui| {
+            settings.fonts.fallbacks_ui(ui).on_hover_text(
+                "Used, in order, for characters egui's own fonts lack. Colour emoji fonts are \
+                 not supported.",
+            )
+        },
+    },
+    SettingEntry {
+        category: Category::Theme,
+        name: "Font preview",
+        ui: |_, ui| ui.label(FontSettings::SAMPLE),
+    },
+    SettingEntry {
+        category: Category::Theme,
+        name: "Window rounding",
+        ui: |settings, ui| {
+            ui.add(egui::Slider::new(
+                &mut settings.theme.window_rounding,
+                WINDOW_ROUNDING,
+            ))
+        },
+    },
+    SettingEntry {
+        category: Category::Autosave,
+        name: "Autosave enabled",
+        ui: |settings, ui| ui.checkbox(&mut settings.autosave.enabled, ""),
+    },
+    SettingEntry {
+        category: Category::Autosave,
+        name: "Autosave interval",
+        ui: |settings, ui| {
+            ui.add(
+                drag_value(&mut settings.autosave.interval_secs)
+                    .range(AUTOSAVE_INTERVAL)
+                    .suffix(" s"),
+            )
+        },
+    },
+    SettingEntry {
+        category: Category::Autosave,
+        name: "Snapshot gallery",
+        ui: |settings, ui| ui.checkbox(&mut settings.autosave.gallery_enabled, ""),
+    },
+    SettingEntry {
+        category: Category::Autosave,
+        name: "Gallery interval",
+        ui: |settings, ui| {
+            ui.add(
+                drag_value(&mut settings.autosave.gallery_interval_secs)
+                    .range(GALLERY_INTERVAL)
+                    .suffix(" s"),
+            )
+        },
+    },
+    SettingEntry {
+        category: Category::Autosave,
+        name: "Gallery size",
+        ui: |settings, ui| {
+            ui.add(
+                drag_value(&mut settings.autosave.gallery_limit)
+                    .range(GALLERY_LIMIT)
+                    .suffix(" snapshots"),
+            )
+        },
+    },
+    SettingEntry {
+        category: Category::Spawn,
+        name: "Spawn range",
+        ui: |settings, ui| ui.add(egui::Slider::new(&mut settings.spawn.range, SPAWN_RANGE)),
+    },
+    SettingEntry {
+        category: Category::Spawn,
+        name: "Cube size",
+        ui: |settings, ui| ui.add(egui::Slider::new(&mut settings.spawn.cube_size, CUBE_SIZE)),
+    },
+    SettingEntry {
+        category: Category::Spawn,
+        name: "Cube color",
+        ui: |settings, ui| ui.color_edit_button_rgb(&mut settings.spawn.color),
+    },
+    SettingEntry {
+        category: Category::Spawn,
+        name: "Entity budget",
+        ui: |settings, ui| {
+            ui.add(drag_value(&mut settings.spawn.entity_budget).range(ENTITY_BUDGET))
+        },
+    },
+    SettingEntry {
+        category: Category::Spawn,
+        name: "Startup scene",
+        ui: |settings, ui| {
+            let template = &mut settings.spawn.startup_template;
+            let mut response = egui::ComboBox::from_id_source("startup_template")
+                .selected_text(template.label())
+                .show_ui(ui, |ui| {
+                    SceneTemplate::ALL
+                        .into_iter()
+                        .map(|option| ui.selectable_value(template, option, option.label()))
+                        .reduce(|a, b| a | b)
+                        .unwrap()
+                });
+            if response.inner.as_ref().is_some_and(|inner| inner.changed()) {
+                response.response.mark_changed();
+            }
+            response.response
+        },
+    },
+    SettingEntry {
+        category: Category::Simulation,
+        name: "Tick rate",
+        ui: |settings, ui| {
+            ui.add(
+                drag_value(&mut settings.simulation.tick_hz)
+                    .range(TICK_HZ)
+                    .suffix(" Hz"),
+            )
+        },
+    },
+    SettingEntry {
+        category: Category::Simulation,
+        name: "Interpolate between ticks",
+        ui: |settings, ui| ui.checkbox(&mut settings.simulation.interpolate, ""),
+    },
+    SettingEntry {
+        category: Category::Import,
+        name: "Watch folder for new files",
+        ui: |settings, ui| ui.checkbox(&mut settings.import.watch_enabled, ""),
+    },
+    SettingEntry {
+        category: Category::Import,
+        name: "Watched folder",
+        ui: |settings, ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut settings.import.watch_folder)
+                    .hint_text("folder under assets/")
+                    .desired_width(160.0),
+            )
+            .on_hover_text(
+                "glTF, OBJ and PNG files appearing here are imported; edited ones are reloaded",
+            )
+        },
+    },
+    SettingEntry {
+        category: Category::Units,
+        name: "Decimal separator",
+        ui: |settings, ui| {
+            let separator = &mut settings.units.decimal_separator;
+            let mut response = egui::ComboBox::from_id_source("decimal_separator")
+                .selected_text(separator.label())
+                .show_ui(ui, |ui| {
+                    DecimalSeparator::ALL
+                        .into_iter()
+                        .map(|option| ui.selectable_value(separator, option, option.label()))
+                        .reduce(|a, b| a | b)
+                        .unwrap()
+                });
+            if response.inner.as_ref().is_some_and(|inner| inner.changed()) {
+                response.response.mark_changed();
+            }
+            response
+                .response
+                .on_hover_text("Typing accepts either separator whichever is shown")
+        },
+    },
+    SettingEntry {
+        category: Category::Units,
+        name: "Show units",
+        ui: |settings, ui| {
+            ui.checkbox(&mut settings.units.show_units, "")
+                .on_hover_text(
+                    "Suffix lengths, angles and percentages in fields and the status bar",
+                )
+        },
+    },
+    SettingEntry {
+        category: Category::Accessibility,
+        name: "Reduced motion",
+        ui: |settings, ui| ui.checkbox(&mut settings.accessibility.reduced_motion, ""),
+    },
+    SettingEntry {
+        category: Category::Accessibility,
+        name: "High contrast",
+        ui: |settings, ui| ui.checkbox(&mut settings.accessibility.high_contrast, ""),
+    },
+    SettingEntry {
+        category: Category::Accessibility,
+        name: "Highlight palette",
+        ui: |settings, ui| {
+            let palette = &mut settings.accessibility.highlight_palette;
+            ui.horizontal(|ui| {
+                ui.selectable_value(palette, HighlightPalette::Standard, "Standard")
+                    | ui.selectable_value(
+                        palette,
+                        HighlightPalette::ColorblindSafe,
+                        "Colorblind-safe",
+                    )
+            })
+            .inner
+        },
+    },
+];
+
+#[derive(Resource)]
+pub struct SettingsWindow {
+    pub is_open: bool,
+    search: String,
+    category: Category,
+}
+
+impl Default for SettingsWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            search: String::new(),
+            category: Category::Graphics,
+        }
+    }
+}
+
+fn settings_window_system(
+    mut contexts: EguiContexts,
+    mut window: ResMut<SettingsWindow>,
+    mut settings: ResMut<Settings>,
+    mut errors: EventWriter<AppError>,
+) {
+    let SettingsWindow {
+        is_open,
+        search,
+        category,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    // Widgets take `&mut Settings`, so only flag the resource as changed when a value did change.
+    let mut edited = settings.bypass_change_detection().clone();
+    egui::Window::new("Settings")
+        .open(is_open)
+        .default_width(420.0)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Search:");
+                ui.add(egui::TextEdit::singleline(search).hint_text("Filter settings by name"));
+            });
+            ui.separator();
+
+            let query = search.trim().to_lowercase();
+            ui.horizontal_top(|ui| {
+                if query.is_empty() {
+                    ui.vertical(|ui| {
+                        for c in Category::ALL {
+                            ui.selectable_value(category, c, c.label());
+                        }
+                    });
+                    ui.separator();
+                }
+                ui.vertical(|ui| {
+                    let mut shown = 0;
+                    egui::Grid::new("settings_grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            let entries = ENTRIES.iter().filter(|entry| {
+                                if query.is_empty() {
+                                    entry.category == *category
+                                } else {
+                                    entry.name.to_lowercase().contains(&query)
+                                }
+                            });
+                            for entry in entries {
+                                if query.is_empty() {
+                                    ui.label(entry.name);
+                                } else {
+                                    ui.label(format!(
+                                        "{} › {}",
+                                        entry.category.label(),
+                                        entry.name
+                                    ));
+                                }
+                                (entry.ui)(&mut edited, ui);
+                                ui.end_row();
+                                shown += 1;
+                            }
+                        });
+                    if shown == 0 {
+                        ui.weak("No settings match the search.");
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    if let Err(err) = edited.save() {
+                        errors.send(err);
+                    }
+                }
+                if ui.button("Reset to defaults").clicked() {
+                    edited = Settings::default();
+                }
+            });
+        });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+}
+
+fn apply_settings_system(
+    settings: Res<Settings>,
+    mut msaa: ResMut<Msaa>,
+    mut contexts: EguiContexts,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let samples = match settings.graphics.msaa_samples {
+        1 => Msaa::Off,
+        2 => Msaa::Sample2,
+        8 => Msaa::Sample8,
+        _ => Msaa::Sample4,
+    };
+    if *msaa != samples {
+        *msaa = samples;
+    }
+    numeric::set_format(settings.units.format());
+
+    let visuals = theme_visuals(&settings.theme, settings.accessibility.high_contrast);
+    let ctx = contexts.ctx_mut();
+    ctx.set_visuals(visuals);
+    let reduced_motion = settings.accessibility.reduced_motion;
+    ctx.style_mut(|style| {
+        style.animation_time = if reduced_motion { 0.0 } else { 1.0 / 12.0 };
+    });
+}
+
+/// The egui visuals a theme produces. The settings apply them globally; the style comparison
+/// panel previews candidates with them.
+pub fn theme_visuals(theme: &ThemeSettings, contrast: bool) -> egui::Visuals {
+    let mut visuals = if theme.dark_mode {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    };
+    visuals.window_rounding = theme.window_rounding.into();
+    if contrast {
+        high_contrast(&mut visuals);
+    }
+    visuals
+}
+
+/// Pure foreground on pure background, with outlined widgets.
+fn high_contrast(visuals: &mut egui::Visuals) {
+    let (fg, bg) = if visuals.dark_mode {
+        (egui::Color32::WHITE, egui::Color32::BLACK)
+    } else {
+        (egui::Color32::BLACK, egui::Color32::WHITE)
+    };
+    visuals.override_text_color = Some(fg);
+    visuals.panel_fill = bg;
+    visuals.window_fill = bg;
+    visuals.extreme_bg_color = bg;
+    visuals.faint_bg_color = bg;
+    visuals.window_stroke = egui::Stroke::new(1.5, fg);
+    visuals.selection.stroke = egui::Stroke::new(2.0, fg);
+    let widgets = &mut visuals.widgets;
+    for widget in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        widget.bg_stroke = egui::Stroke::new(1.0, fg);
+        widget.fg_stroke.color = fg;
+    }
+}
+
+fn autosave_settings_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut dirty: Local<bool>,
+    mut since_save: Local<f32>,
+    mut errors: EventWriter<AppError>,
+    safe_mode: Option<Res<SafeMode>>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *dirty = true;
+    }
+    // Never overwrite the file safe mode is protecting until the user decides what to do with it.
+    if !*dirty || !settings.autosave.enabled || safe_mode.is_some() {
+        return;
+    }
+
+    *since_save += time.delta_seconds();
+    if *since_save >= settings.autosave.interval_secs {
+        if let Err(err) = settings.save() {
+            errors.send(err);
+        }
+        *dirty = false;
+        *since_save = 0.0;
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::settings::Settings;
+
+/// Runs simulations (boids, cloth) in `FixedUpdate` at the tick rate from the settings, so they
+/// behave the same at any refresh rate, and smooths [`Interpolated`] transforms between ticks.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_tick_rate_system).add_systems(
+            PostUpdate,
+            interpolate_transforms_system.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// The simulated transform at the last two ticks. Simulations write `current`; the rendered
+/// `Transform` is blended between them by how far the frame is into the next tick.
+#[derive(Component, Clone, Copy)]
+pub struct Interpolated {
+    pub previous: Transform,
+    pub current: Transform,
+}
+
+impl Interpolated {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+        }
+    }
+
+    /// Starts a tick: the current state becomes the one to blend from.
+    pub fn advance(&mut self) {
+        self.previous = self.current;
+    }
+}
+
+fn apply_tick_rate_system(settings: Res<Settings>, mut time: ResMut<Time<Fixed>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let timestep = std::time::Duration::from_secs_f64(1.0 / settings.simulation.tick_hz.max(1.0));
+    if time.timestep() != timestep {
+        time.set_timestep(timestep);
+    }
+}
+
+fn interpolate_transforms_system(
+    time: Res<Time<Fixed>>,
+    settings: Res<Settings>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    let alpha = if settings.simulation.interpolate {
+        time.overstep_fraction()
+    } else {
+        1.0
+    };
+    for (mut transform, interpolated) in &mut query {
+        let (previous, current) = (interpolated.previous, interpolated.current);
+        *transform = Transform {
+            translation: previous.translation.lerp(current.translation, alpha),
+            rotation: previous.rotation.slerp(current.rotation, alpha),
+            scale: previous.scale.lerp(current.scale, alpha),
+        };
+    }
+}
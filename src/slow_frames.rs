@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use rand::Rng;
+
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+
+/// A developer window that slows frames down on purpose, to check that the UI, input routing
+/// and animations hold up at low frame rates. Nothing here is saved; every run starts at full
+/// speed.
+pub struct SlowFramesPlugin;
+
+impl Plugin for SlowFramesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<SlowFramesWindow>()
+            .add_systems(
+                Update,
+                slow_frames_window_system.in_set(SlowFramesWindow::ui_set()),
+            )
+            .add_systems(Last, stall_frame_system);
+    }
+}
+
+#[derive(Resource)]
+pub struct SlowFramesWindow {
+    pub is_open: bool,
+    enabled: bool,
+    /// Added to every frame.
+    delay_ms: u32,
+    /// Chance per frame of an additional stall of `stall_ms`.
+    stall_chance: f32,
+    stall_ms: u32,
+    /// Smoothed frame time, for showing the resulting rate.
+    frame_secs: f32,
+}
+
+impl Default for SlowFramesWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            enabled: false,
+            delay_ms: 50,
+            stall_chance: 0.0,
+            stall_ms: 500,
+            frame_secs: 0.0,
+        }
+    }
+}
+
+impl Panel for SlowFramesWindow {
+    const TITLE: &'static str = "Slow Frames";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn slow_frames_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SlowFramesWindow>,
+    time: Res<Time>,
+) {
+    let SlowFramesWindow {
+        is_open,
+        enabled,
+        delay_ms,
+        stall_chance,
+        stall_ms,
+        frame_secs,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+    *frame_secs += (time.delta_seconds() - *frame_secs) * 0.1;
+
+    egui::Window::new("Slow Frames")
+        .open(is_open)
+        .show(contexts.ctx::<SlowFramesWindow>(), |ui| {
+            ui.checkbox(enabled, "Inject frame delays");
+            ui.add_enabled_ui(*enabled, |ui| {
+                egui::Grid::new("slow_frames_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Delay every frame");
+                        ui.add(egui::Slider::new(delay_ms, 0..=500).suffix(" ms"));
+                        ui.end_row();
+                        ui.label("Random stall chance");
+                        ui.add(
+                            egui::Slider::new(stall_chance, 0.0..=0.5)
+                                .custom_formatter(|value, _| format!("{:.0}%", value * 100.0)),
+                        );
+                        ui.end_row();
+                        ui.label("Stall length");
+                        ui.add(egui::Slider::new(stall_ms, 10..=3000).suffix(" ms"));
+                        ui.end_row();
+                    });
+            });
+            ui.separator();
+            if *frame_secs > 0.0 {
+                ui.label(format!(
+                    "{:.1} ms/frame ({:.0} fps)",
+                    *frame_secs * 1000.0,
+                    frame_secs.recip()
+                ));
+            }
+        });
+}
+
+fn stall_frame_system(window: Res<SlowFramesWindow>) {
+    if !window.enabled {
+        return;
+    }
+    let mut delay = window.delay_ms;
+    if window.stall_chance > 0.0 && rand::thread_rng().gen_bool(window.stall_chance as f64) {
+        delay += window.stall_ms;
+    }
+    if delay > 0 {
+        std::thread::sleep(Duration::from_millis(delay as u64));
+    }
+}
@@ -0,0 +1,525 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        primitives::Aabb,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+    utils::HashMap,
+};
+use bevy_egui::{egui, EguiUserTextures};
+
+use crate::{
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{SceneEntity, SceneFile, SceneId, Shape},
+    scene_diff::{entity_changes, EntityChange},
+    settings::{Highlights, Settings},
+    snapshots::SnapshotsWindow,
+    ViewportCamera,
+};
+
+/// Layers the two snapshots' stand-ins render on, apart from the scene and each other.
+const COMPARE_LAYERS: [usize; 2] = [26, 27];
+const VIEW_SIZE: UVec2 = UVec2::new(480, 360);
+
+/// Shows two snapshots side by side, each rendered by its own camera that follows the
+/// viewport camera, with the entities that differ between them outlined: removed ones in the
+/// first, added ones in the second and moved or otherwise changed ones in both. Snapshots are
+/// drawn with stand-ins of their cubes and shapes; generated meshes such as text and imported
+/// models are borrowed from the live entity with the same id, or shown as a cube without one.
+pub struct SnapshotComparePlugin;
+
+impl Plugin for SnapshotComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<SnapshotCompareWindow>()
+            .insert_gizmo_config(
+                BeforeGizmos,
+                GizmoConfig {
+                    render_layers: RenderLayers::layer(COMPARE_LAYERS[0]),
+                    ..default()
+                },
+            )
+            .insert_gizmo_config(
+                AfterGizmos,
+                GizmoConfig {
+                    render_layers: RenderLayers::layer(COMPARE_LAYERS[1]),
+                    ..default()
+                },
+            )
+            .add_systems(Startup, setup_snapshot_compare_system)
+            .add_systems(
+                Update,
+                (
+                    snapshot_compare_window_system.in_set(SnapshotCompareWindow::ui_set()),
+                    compare_stand_ins_system,
+                    sync_compare_cameras_system,
+                    draw_compare_changes_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(MenuItem::new(Menu::View, "Compare Snapshots…", |world| {
+                world.resource_mut::<SnapshotCompareWindow>().is_open = true;
+            }));
+    }
+}
+
+/// Outlines drawn in the first snapshot's view only.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct BeforeGizmos;
+
+/// Outlines drawn in the second snapshot's view only.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct AfterGizmos;
+
+impl EntityChange {
+    fn label(self) -> &'static str {
+        match self {
+            EntityChange::Added => "added",
+            EntityChange::Removed => "removed",
+            EntityChange::Moved => "moved",
+            EntityChange::Changed => "changed",
+        }
+    }
+
+    fn color(self, highlights: &Highlights) -> Color {
+        match self {
+            EntityChange::Added => highlights.positive,
+            EntityChange::Removed => highlights.negative,
+            EntityChange::Moved => highlights.warning,
+            EntityChange::Changed => highlights.info,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct SnapshotCompareWindow {
+    pub is_open: bool,
+    /// Ids of the snapshots shown on the left and right, see [`SnapshotsWindow::list`].
+    picked: [Option<usize>; 2],
+    highlight: bool,
+    /// The snapshots the stand-ins were spawned for.
+    shown: Option<[usize; 2]>,
+    /// How many entities differ, in the order of [`CHANGES`].
+    counts: [usize; 4],
+}
+
+impl Default for SnapshotCompareWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            picked: [None; 2],
+            highlight: true,
+            shown: None,
+            counts: [0; 4],
+        }
+    }
+}
+
+impl Panel for SnapshotCompareWindow {
+    const TITLE: &'static str = "Snapshot Compare";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+const CHANGES: [EntityChange; 4] = [
+    EntityChange::Added,
+    EntityChange::Removed,
+    EntityChange::Moved,
+    EntityChange::Changed,
+];
+
+/// The cameras rendering each side, and their images as egui textures.
+#[derive(Resource)]
+struct CompareViews {
+    cameras: [Entity; 2],
+    light: Entity,
+    textures: [egui::TextureId; 2],
+}
+
+/// An entity of one of the compared snapshots, drawn on that side's layer.
+#[derive(Component)]
+struct CompareStandIn {
+    side: usize,
+    change: Option<EntityChange>,
+}
+
+fn setup_snapshot_compare_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+) {
+    let size = Extent3d {
+        width: VIEW_SIZE.x,
+        height: VIEW_SIZE.y,
+        depth_or_array_layers: 1,
+    };
+    let mut view = |layer: usize| {
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: Some("snapshot_compare"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+            ..default()
+        };
+        image.resize(size);
+        let image = images.add(image);
+        let texture = user_textures.add_image(image.clone());
+        let camera = commands
+            .spawn((
+                Camera3dBundle {
+                    camera: Camera {
+                        target: RenderTarget::Image(image),
+                        clear_color: ClearColorConfig::Custom(Color::srgb(0.12, 0.12, 0.13)),
+                        is_active: false,
+                        ..default()
+                    },
+                    ..default()
+                },
+                RenderLayers::layer(layer),
+            ))
+            .id();
+        (camera, texture)
+    };
+    let (before, before_texture) = view(COMPARE_LAYERS[0]);
+    let (after, after_texture) = view(COMPARE_LAYERS[1]);
+    // A light over the camera's shoulder lights both sides alike, whatever the scene's lights.
+    let light = commands
+        .spawn((
+            DirectionalLightBundle {
+                directional_light: DirectionalLight {
+                    illuminance: 6_000.0,
+                    ..default()
+                },
+                ..default()
+            },
+            RenderLayers::from_layers(&COMPARE_LAYERS),
+        ))
+        .id();
+    commands.insert_resource(CompareViews {
+        cameras: [before, after],
+        light,
+        textures: [before_texture, after_texture],
+    });
+}
+
+fn snapshot_compare_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SnapshotCompareWindow>,
+    views: Res<CompareViews>,
+    snapshots: Res<SnapshotsWindow>,
+    settings: Res<Settings>,
+) {
+    let SnapshotCompareWindow {
+        is_open,
+        picked,
+        highlight,
+        counts,
+        ..
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+    for id in picked.iter_mut() {
+        if id.is_some_and(|id| snapshots.scene(id).is_none()) {
+            *id = None;
+        }
+    }
+    // Start from the two latest snapshots.
+    let list: Vec<(usize, &str)> = snapshots.list().collect();
+    if *picked == [None; 2] {
+        if let [.., (before, _), (after, _)] = list[..] {
+            *picked = [Some(before), Some(after)];
+        }
+    }
+
+    let highlights = settings.highlights();
+    let color = |color: Color| {
+        let [r, g, b, _] = color.to_srgba().to_u8_array();
+        egui::Color32::from_rgb(r, g, b)
+    };
+    egui::Window::new(SnapshotCompareWindow::TITLE)
+        .open(is_open)
+        .default_width(760.0)
+        .show(contexts.ctx::<SnapshotCompareWindow>(), |ui| {
+            if list.len() < 2 {
+                ui.weak("Take two snapshots to compare them.");
+                return;
+            }
+            ui.columns(2, |columns| {
+                for (side, ui) in columns.iter_mut().enumerate() {
+                    let name = |id: Option<usize>| {
+                        list.iter()
+                            .find(|(snapshot, _)| Some(*snapshot) == id)
+                            .map_or("None", |(_, name)| name)
+                    };
+                    egui::ComboBox::from_id_source(("snapshot_compare", side))
+                        .selected_text(name(picked[side]))
+                        .width(ui.available_width())
+                        .show_ui(ui, |ui| {
+                            for (id, name) in &list {
+                                ui.selectable_value(&mut picked[side], Some(*id), *name);
+                            }
+                        });
+                    let width = ui.available_width();
+                    let size = egui::vec2(width, width * VIEW_SIZE.y as f32 / VIEW_SIZE.x as f32);
+                    if picked.iter().all(Option::is_some) {
+                        ui.image(egui::load::SizedTexture::new(views.textures[side], size));
+                    } else {
+                        ui.allocate_space(size);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(highlight, "Highlight differences");
+                ui.separator();
+                for (change, count) in CHANGES.into_iter().zip(*counts) {
+                    ui.colored_label(
+                        color(change.color(&highlights)),
+                        format!("{count} {}", change.label()),
+                    );
+                }
+            });
+            ui.weak("Both views follow the viewport camera.");
+        });
+}
+
+/// World transforms of `file`'s entities, through their groups.
+fn world_transforms(file: &SceneFile) -> Vec<Transform> {
+    // Parents come before their children in the list.
+    let mut groups: Vec<Transform> = Vec::with_capacity(file.groups.len());
+    for group in &file.groups {
+        let local = group.transform();
+        let world = group
+            .parent
+            .and_then(|parent| groups.get(parent))
+            .map_or(local, |parent| parent.mul_transform(local));
+        groups.push(world);
+    }
+    file.entities
+        .iter()
+        .map(|entity| {
+            let local = entity.transform();
+            entity
+                .group
+                .and_then(|group| groups.get(group))
+                .map_or(local, |group| group.mul_transform(local))
+        })
+        .collect()
+}
+
+/// The mesh an entity is drawn with: its shape's, or for generated meshes the live entity's.
+fn stand_in_mesh(
+    entity: &SceneEntity,
+    live: &HashMap<u64, Handle<Mesh>>,
+    shapes: &mut Vec<(Shape, Handle<Mesh>)>,
+    meshes: &mut Assets<Mesh>,
+) -> Handle<Mesh> {
+    let generated = entity.text.is_some()
+        || entity.csg.is_some()
+        || entity.plant.is_some()
+        || entity.sprite.is_some()
+        || entity.mesh_file.is_some();
+    if let Some(mesh) = live.get(&entity.id).filter(|_| generated && entity.id != 0) {
+        return mesh.clone();
+    }
+    let shape = if generated { Shape::Cube } else { entity.shape };
+    if let Some((_, mesh)) = shapes.iter().find(|(cached, _)| *cached == shape) {
+        return mesh.clone();
+    }
+    let mesh = meshes.add(shape.mesh());
+    shapes.push((shape, mesh.clone()));
+    mesh
+}
+
+/// Respawns the stand-ins whenever other snapshots are picked, and removes them on closing.
+#[allow(clippy::too_many_arguments)]
+fn compare_stand_ins_system(
+    mut commands: Commands,
+    mut window: ResMut<SnapshotCompareWindow>,
+    snapshots: Res<SnapshotsWindow>,
+    stand_ins: Query<Entity, With<CompareStandIn>>,
+    live: Query<(&SceneId, &Handle<Mesh>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let wanted = match window.picked {
+        [Some(before), Some(after)] if window.is_open => Some([before, after]),
+        _ => None,
+    };
+    if window.shown == wanted {
+        return;
+    }
+    for entity in &stand_ins {
+        commands.entity(entity).despawn();
+    }
+    window.shown = wanted;
+    window.counts = [0; 4];
+    let Some(scenes) = wanted
+        .and_then(|[before, after]| Some([snapshots.scene(before)?, snapshots.scene(after)?]))
+    else {
+        return;
+    };
+
+    let [before, after] = scenes;
+    let (mut changes_before, mut changes_after) = entity_changes(before, after);
+    let transforms = scenes.map(world_transforms);
+    // Entities whose groups moved keep their own fields, but still stand elsewhere.
+    let before_index: HashMap<u64, usize> = before
+        .entities
+        .iter()
+        .enumerate()
+        .filter(|(_, entity)| entity.id != 0)
+        .map(|(index, entity)| (entity.id, index))
+        .collect();
+    for (index, entity) in after.entities.iter().enumerate() {
+        let Some(&matched) = before_index.get(&entity.id) else {
+            continue;
+        };
+        let (a, b) = (transforms[0][matched], transforms[1][index]);
+        let moved = a.translation.distance(b.translation) > 1e-4
+            || a.rotation.angle_between(b.rotation) > 1e-4
+            || a.scale.distance(b.scale) > 1e-4;
+        if moved && changes_after[index].is_none() {
+            changes_before[matched] = Some(EntityChange::Moved);
+            changes_after[index] = Some(EntityChange::Moved);
+        }
+    }
+    // Matched entities are marked on both sides, so only removals are counted from the first.
+    let removed = changes_before
+        .iter()
+        .filter(|change| **change == Some(EntityChange::Removed));
+    for change in changes_after.iter().chain(removed).flatten() {
+        if let Some(slot) = CHANGES.iter().position(|c| c == change) {
+            window.counts[slot] += 1;
+        }
+    }
+
+    let live: HashMap<u64, Handle<Mesh>> =
+        live.iter().map(|(id, mesh)| (id.0, mesh.clone())).collect();
+    let mut shapes = Vec::new();
+    for (side, (scene, changes)) in scenes
+        .into_iter()
+        .zip([changes_before, changes_after])
+        .enumerate()
+    {
+        for ((entity, transform), change) in
+            scene.entities.iter().zip(&transforms[side]).zip(changes)
+        {
+            let [r, g, b, a] = entity.color;
+            let [metallic, perceptual_roughness] = entity.surface.unwrap_or([0.0, 0.5]);
+            let finish = entity.finish.unwrap_or_default();
+            commands.spawn((
+                PbrBundle {
+                    mesh: stand_in_mesh(entity, &live, &mut shapes, &mut meshes),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::srgba(r, g, b, a),
+                        base_color_texture: entity.texture.as_ref().map(|(image, _)| image.clone()),
+                        emissive: LinearRgba::from_f32_array(finish.emissive),
+                        reflectance: finish.reflectance,
+                        metallic,
+                        perceptual_roughness,
+                        unlit: finish.unlit,
+                        alpha_mode: finish.alpha_mode.into(),
+                        ..default()
+                    }),
+                    transform: *transform,
+                    ..default()
+                },
+                RenderLayers::layer(COMPARE_LAYERS[side]),
+                CompareStandIn { side, change },
+            ));
+        }
+    }
+}
+
+/// Renders while both snapshots are shown, from wherever the viewport camera is.
+#[allow(clippy::type_complexity)]
+fn sync_compare_cameras_system(
+    window: Res<SnapshotCompareWindow>,
+    views: Res<CompareViews>,
+    viewport_cameras: Query<(&Transform, &Projection), With<ViewportCamera>>,
+    mut cameras: Query<(&mut Camera, &mut Transform, &mut Projection), Without<ViewportCamera>>,
+    mut lights: Query<
+        &mut Transform,
+        (
+            With<DirectionalLight>,
+            Without<Camera>,
+            Without<ViewportCamera>,
+        ),
+    >,
+) {
+    let active = window.shown.is_some();
+    let source = viewport_cameras.get_single().ok();
+    for entity in views.cameras {
+        let Ok((mut camera, mut transform, mut projection)) = cameras.get_mut(entity) else {
+            continue;
+        };
+        if camera.is_active != active {
+            camera.is_active = active;
+        }
+        let Some((source_transform, source_projection)) = source.filter(|_| active) else {
+            continue;
+        };
+        if *transform != *source_transform {
+            *transform = *source_transform;
+        }
+        // The aspect ratio is each camera's own, so only the rest is compared.
+        let differs = match (&*projection, source_projection) {
+            (Projection::Perspective(own), Projection::Perspective(source)) => {
+                own.fov != source.fov || own.near != source.near || own.far != source.far
+            }
+            (Projection::Orthographic(own), Projection::Orthographic(source)) => {
+                own.scale != source.scale || own.near != source.near || own.far != source.far
+            }
+            _ => true,
+        };
+        if differs {
+            *projection = source_projection.clone();
+        }
+    }
+    if let (Some((source_transform, _)), Ok(mut light)) = (source, lights.get_mut(views.light)) {
+        let rotation = source_transform.rotation * Quat::from_euler(EulerRot::YXZ, 0.4, -0.5, 0.0);
+        if light.rotation != rotation {
+            light.rotation = rotation;
+        }
+    }
+}
+
+fn draw_compare_changes_system(
+    window: Res<SnapshotCompareWindow>,
+    settings: Res<Settings>,
+    stand_ins: Query<(&CompareStandIn, &GlobalTransform, &Aabb)>,
+    mut before: Gizmos<BeforeGizmos>,
+    mut after: Gizmos<AfterGizmos>,
+) {
+    if !window.highlight || window.shown.is_none() {
+        return;
+    }
+    let highlights = settings.highlights();
+    for (stand_in, transform, aabb) in &stand_ins {
+        let Some(change) = stand_in.change else {
+            continue;
+        };
+        let local = Transform::from_translation(aabb.center.into())
+            .with_scale(Vec3::from(aabb.half_extents) * 2.04);
+        let cuboid = transform.mul_transform(local);
+        let color = change.color(&highlights);
+        match stand_in.side {
+            0 => before.cuboid(cuboid, color),
+            _ => after.cuboid(cuboid, color),
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    icons::{Icon, IconButtonsExt},
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests, ReadbackSource},
+    scene::{SceneFile, SceneReader, SceneWriter},
+    settings::Settings,
+    timeline::AnimationTime,
+    ViewImage, ViewportCamera,
+};
+
+/// Width of snapshot thumbnails, in pixels.
+const THUMBNAIL_WIDTH: u32 = 160;
+
+/// In-memory snapshots of the whole scene, for experimenting without saving to disk. With the
+/// gallery enabled, one is also taken automatically whenever the scene changed over the
+/// configured interval, each with a viewport thumbnail to scroll back through.
+pub struct SnapshotsPlugin;
+
+impl Plugin for SnapshotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<SnapshotsWindow>()
+            .register_panel::<GalleryWindow>()
+            .add_event::<TakeSnapshot>()
+            .add_event::<RestoreSnapshot>()
+            .add_event::<TakeAutoSnapshot>()
+            .add_systems(
+                Update,
+                (
+                    snapshots_window_system.in_set(SnapshotsWindow::ui_set()),
+                    gallery_window_system.in_set(GalleryWindow::ui_set()),
+                    auto_snapshot_system,
+                    take_snapshot_system,
+                    receive_thumbnails_system,
+                    restore_snapshot_system,
+                )
+                    .chain(),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Take Snapshot", |world| {
+                    world.send_event(TakeSnapshot(None));
+                })
+                .icon(Icon::Snapshot),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::Edit, "Restore Last Snapshot", |world| {
+                    let last = world.resource::<SnapshotsWindow>().snapshots.len();
+                    if let Some(index) = last.checked_sub(1) {
+                        world.send_event(RestoreSnapshot(index));
+                    }
+                })
+                .icon(Icon::Restore),
+            );
+    }
+}
+
+/// Captures the scene under the given name, or a numbered one.
+#[derive(Event)]
+pub struct TakeSnapshot(pub Option<String>);
+
+/// Taken by the gallery timer rather than asked for.
+#[derive(Event)]
+struct TakeAutoSnapshot;
+
+/// Replaces the scene with the snapshot at this index.
+#[derive(Event)]
+pub struct RestoreSnapshot(pub usize);
+
+struct Snapshot {
+    /// Unique for the session, unlike the index, which shifts as snapshots are deleted.
+    id: usize,
+    name: String,
+    scene: SceneFile,
+    seconds: f32,
+    /// The projection is left to the Camera window, which eases towards its own settings.
+    camera: Option<Transform>,
+    /// Taken by the gallery timer; only these count towards the gallery limit.
+    auto: bool,
+    /// Filled in a few frames later, once the viewport readback arrives.
+    thumbnail: Option<egui::TextureHandle>,
+}
+
+#[derive(Default, Resource)]
+pub struct SnapshotsWindow {
+    pub is_open: bool,
+    name: String,
+    snapshots: Vec<Snapshot>,
+    /// Used to name snapshots taken without a name.
+    taken: usize,
+    /// Snapshot ids waiting for a viewport thumbnail.
+    pending_thumbnails: Vec<usize>,
+    /// Real seconds since the gallery last checked for changes.
+    since_auto: f32,
+}
+
+#[derive(Default, Resource)]
+pub struct GalleryWindow {
+    pub is_open: bool,
+    auto_only: bool,
+}
+
+impl Panel for GalleryWindow {
+    const TITLE: &'static str = "Snapshot Gallery";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl SnapshotsWindow {
+    /// Each snapshot's session-unique id and name, oldest first.
+    pub fn list(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.snapshots
+            .iter()
+            .map(|snapshot| (snapshot.id, snapshot.name.as_str()))
+    }
+
+    /// The scene captured in the snapshot with `id`, unless it was deleted.
+    pub fn scene(&self, id: usize) -> Option<&SceneFile> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.id == id)
+            .map(|snapshot| &snapshot.scene)
+    }
+}
+
+impl Panel for SnapshotsWindow {
+    const TITLE: &'static str = "Snapshots";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn snapshots_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SnapshotsWindow>,
+    mut take: EventWriter<TakeSnapshot>,
+    mut restore: EventWriter<RestoreSnapshot>,
+) {
+    let SnapshotsWindow {
+        is_open,
+        name,
+        snapshots,
+        ..
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let mut delete = None;
+    egui::Window::new("Snapshots")
+        .open(is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<SnapshotsWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(name)
+                        .hint_text("Snapshot name")
+                        .desired_width(160.0),
+                );
+                if ui.icon_button(Icon::Snapshot, "Take snapshot").clicked() {
+                    let name = std::mem::take(name);
+                    take.send(TakeSnapshot((!name.trim().is_empty()).then_some(name)));
+                }
+            });
+            ui.separator();
+            if snapshots.is_empty() {
+                ui.weak("No snapshots yet. They are kept in memory until the app closes.");
+            }
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (index, snapshot) in snapshots.iter().enumerate().rev() {
+                    ui.horizontal(|ui| {
+                        ui.strong(&snapshot.name);
+                        ui.weak(format!(
+                            "{} entities, t = {:.2}s",
+                            snapshot.scene.entities.len(),
+                            snapshot.seconds
+                        ));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui
+                                .small_icon_button(Icon::Delete, "")
+                                .on_hover_text("Delete")
+                                .clicked()
+                            {
+                                delete = Some(index);
+                            }
+                            if ui.small_button("Restore").clicked() {
+                                restore.send(RestoreSnapshot(index));
+                            }
+                        });
+                    });
+                }
+            });
+        });
+    if let Some(index) = delete {
+        snapshots.remove(index);
+    }
+}
+
+fn gallery_window_system(
+    mut contexts: PanelContexts,
+    mut gallery: ResMut<GalleryWindow>,
+    window: Res<SnapshotsWindow>,
+    settings: Res<Settings>,
+    mut restore: EventWriter<RestoreSnapshot>,
+) {
+    let GalleryWindow { is_open, auto_only } = &mut *gallery;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(GalleryWindow::TITLE)
+        .open(is_open)
+        .default_width(540.0)
+        .default_height(420.0)
+        .show(contexts.ctx::<GalleryWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(auto_only, "Automatic snapshots only");
+                let autosave = &settings.autosave;
+                if autosave.gallery_enabled {
+                    ui.weak(format!(
+                        "Taken every {:.0} s while the scene changes, the last {} kept",
+                        autosave.gallery_interval_secs, autosave.gallery_limit
+                    ));
+                } else {
+                    ui.weak("Automatic snapshots are off in Settings › Autosave");
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let shown = window
+                        .snapshots
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .filter(|(_, snapshot)| snapshot.auto || !*auto_only);
+                    for (index, snapshot) in shown {
+                        let hover = format!(
+                            "{}\n{} entities, t = {:.2}s\nClick to restore",
+                            snapshot.name,
+                            snapshot.scene.entities.len(),
+                            snapshot.seconds
+                        );
+                        ui.vertical(|ui| {
+                            let clicked = match &snapshot.thumbnail {
+                                Some(texture) => ui
+                                    .add(egui::ImageButton::new(egui::load::SizedTexture::new(
+                                        texture.id(),
+                                        texture.size_vec2(),
+                                    )))
+                                    .on_hover_text(hover)
+                                    .clicked(),
+                                None => ui
+                                    .add_sized(
+                                        [THUMBNAIL_WIDTH as f32, 90.0],
+                                        egui::Button::new("No preview"),
+                                    )
+                                    .on_hover_text(hover)
+                                    .clicked(),
+                            };
+                            if clicked {
+                                restore.send(RestoreSnapshot(index));
+                            }
+                            ui.small(&snapshot.name);
+                        });
+                    }
+                });
+                if window.snapshots.is_empty() {
+                    ui.weak("No snapshots yet.");
+                }
+            });
+        });
+}
+
+/// Asks for a snapshot whenever the scene differs from the newest one after an interval.
+fn auto_snapshot_system(
+    mut window: ResMut<SnapshotsWindow>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+    scene: SceneReader,
+    mut take: EventWriter<TakeAutoSnapshot>,
+) {
+    let autosave = &settings.autosave;
+    if !autosave.gallery_enabled {
+        return;
+    }
+    window.since_auto += time.delta_seconds();
+    if window.since_auto < autosave.gallery_interval_secs {
+        return;
+    }
+    window.since_auto = 0.0;
+    let current = scene.capture();
+    let unchanged = window
+        .snapshots
+        .last()
+        .is_some_and(|snapshot| snapshot.scene == current);
+    if !unchanged && !current.entities.is_empty() {
+        take.send(TakeAutoSnapshot);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn take_snapshot_system(
+    mut events: EventReader<TakeSnapshot>,
+    mut auto_events: EventReader<TakeAutoSnapshot>,
+    mut window: ResMut<SnapshotsWindow>,
+    scene: SceneReader,
+    time: Res<AnimationTime>,
+    clock: Res<Time<Real>>,
+    settings: Res<Settings>,
+    cameras: Query<&Transform, With<ViewportCamera>>,
+    mut requests: ResMut<ReadbackRequests>,
+    view_image: Res<ViewImage>,
+) {
+    let auto = auto_events.read().count() > 0;
+    let requested = events
+        .read()
+        .map(|TakeSnapshot(name)| (name.clone(), false))
+        .collect::<Vec<_>>();
+    let autosnapshot = auto.then(|| {
+        let minutes = clock.elapsed_seconds() as u32 / 60;
+        (
+            Some(format!("Auto {}:{:02}", minutes / 60, minutes % 60)),
+            true,
+        )
+    });
+    for (name, auto) in requested.into_iter().chain(autosnapshot) {
+        window.taken += 1;
+        let id = window.taken;
+        let name = name.unwrap_or_else(|| format!("Snapshot {id}"));
+        let snapshot = Snapshot {
+            id,
+            name,
+            scene: scene.capture(),
+            seconds: time.seconds,
+            camera: cameras.get_single().ok().copied(),
+            auto,
+            thumbnail: None,
+        };
+        info!("Took snapshot \"{}\"", snapshot.name);
+        window.snapshots.push(snapshot);
+        window.pending_thumbnails.push(id);
+        requests.request(&view_image);
+    }
+
+    let limit = settings.autosave.gallery_limit.max(1);
+    let autos = window.snapshots.iter().filter(|s| s.auto).count();
+    if autos > limit {
+        let mut excess = autos - limit;
+        window.snapshots.retain(|snapshot| {
+            let drop = snapshot.auto && excess > 0;
+            excess -= drop as usize;
+            !drop
+        });
+    }
+}
+
+/// Gives snapshots waiting for a thumbnail a downscaled copy of the next viewport readback.
+fn receive_thumbnails_system(
+    mut window: ResMut<SnapshotsWindow>,
+    mut readbacks: EventReader<ReadbackComplete>,
+    view_image: Res<ViewImage>,
+    mut contexts: EguiContexts,
+) {
+    if window.pending_thumbnails.is_empty() {
+        readbacks.clear();
+        return;
+    }
+    let id = view_image.id();
+    let Some(readback) = readbacks
+        .read()
+        .filter(|readback| {
+            readback.image == id
+                && readback.region.is_none()
+                && readback.source == ReadbackSource::Output
+        })
+        .last()
+    else {
+        return;
+    };
+
+    let thumbnail = thumbnail(readback.size, &readback.data);
+    let texture = contexts.ctx_mut().load_texture(
+        "snapshot_thumbnail",
+        thumbnail,
+        egui::TextureOptions::LINEAR,
+    );
+    let SnapshotsWindow {
+        snapshots,
+        pending_thumbnails,
+        ..
+    } = &mut *window;
+    for snapshot in snapshots
+        .iter_mut()
+        .filter(|snapshot| pending_thumbnails.contains(&snapshot.id))
+    {
+        snapshot.thumbnail = Some(texture.clone());
+    }
+    pending_thumbnails.clear();
+}
+
+/// Nearest-neighbour downscale of tightly packed RGBA8 rows to [`THUMBNAIL_WIDTH`].
+fn thumbnail(size: UVec2, rgba: &[u8]) -> egui::ColorImage {
+    let width = THUMBNAIL_WIDTH.min(size.x.max(1));
+    let height = (size.y * width / size.x.max(1)).max(1);
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        let source_y = y * size.y / height;
+        for x in 0..width {
+            let source = ((source_y * size.x + x * size.x / width) * 4) as usize;
+            pixels.extend_from_slice(rgba.get(source..source + 4).unwrap_or(&[0, 0, 0, 255]));
+        }
+    }
+    egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &pixels)
+}
+
+fn restore_snapshot_system(
+    mut events: EventReader<RestoreSnapshot>,
+    window: Res<SnapshotsWindow>,
+    mut scene: SceneWriter,
+    mut time: ResMut<AnimationTime>,
+    mut cameras: Query<&mut Transform, With<ViewportCamera>>,
+) {
+    // Restoring twice in one frame would only spawn the scene twice.
+    let Some(snapshot) = events
+        .read()
+        .last()
+        .and_then(|RestoreSnapshot(index)| window.snapshots.get(*index))
+    else {
+        return;
+    };
+
+    scene.replace(&snapshot.scene);
+    time.seconds = snapshot.seconds;
+    if let (Some(transform), Ok(mut camera)) = (snapshot.camera, cameras.get_single_mut()) {
+        *camera = transform;
+    }
+    info!("Restored snapshot \"{}\"", snapshot.name);
+}
@@ -0,0 +1,362 @@
+use std::{f32::consts::TAU, fmt::Write as _};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        primitives::Aabb,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    errors::AppError,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    readback::{ReadbackComplete, ReadbackRequests},
+    report::encode_png,
+    selection::Selection,
+    status_bar::StatusBar,
+    window_title::TaskProgress,
+    SceneLight,
+};
+
+pub const SHEET_PATH: &str = "sprites.png";
+pub const ATLAS_PATH: &str = "sprites.json";
+
+/// Layer the selection is added to while it is captured, so the sprite cameras see nothing else.
+const SPRITE_LAYER: usize = 30;
+
+/// Renders the selection from evenly spaced angles around the Y axis into a packed sprite sheet
+/// with a JSON atlas describing each frame.
+pub struct SpriteSheetPlugin;
+
+impl Plugin for SpriteSheetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<SpriteSheetWindow>().add_systems(
+            Update,
+            (
+                sprite_sheet_window_system.in_set(SpriteSheetWindow::ui_set()),
+                capture_sprites_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A capture in progress. `layers` holds what each isolated entity had before, to restore it.
+struct SpriteCapture {
+    cameras: Vec<Entity>,
+    images: Vec<Handle<Image>>,
+    frames: Vec<Option<Vec<u8>>>,
+    layers: Vec<(Entity, Option<RenderLayers>)>,
+}
+
+#[derive(Resource)]
+pub struct SpriteSheetWindow {
+    pub is_open: bool,
+    angles: u32,
+    resolution: u32,
+    /// Camera pitch above the horizon, in degrees.
+    elevation: f32,
+    capture: Option<SpriteCapture>,
+}
+
+impl Default for SpriteSheetWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            angles: 8,
+            resolution: 128,
+            elevation: 30.0,
+            capture: None,
+        }
+    }
+}
+
+impl Panel for SpriteSheetWindow {
+    const TITLE: &'static str = "Sprite Sheet";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn frame_target(size: u32) -> Image {
+    let size = Extent3d {
+        width: size,
+        height: size,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+/// Columns and rows of the sheet: as square as possible.
+fn grid(frames: u32) -> UVec2 {
+    let columns = (frames as f32).sqrt().ceil() as u32;
+    UVec2::new(columns, frames.div_ceil(columns))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sprite_sheet_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SpriteSheetWindow>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    selection: Res<Selection>,
+    meshes: Query<(&Aabb, &GlobalTransform, Option<&RenderLayers>)>,
+    lights: Query<(Entity, Option<&RenderLayers>), With<SceneLight>>,
+) {
+    let SpriteSheetWindow {
+        is_open,
+        angles,
+        resolution,
+        elevation,
+        capture,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Sprite Sheet")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<SpriteSheetWindow>(), |ui| {
+            ui.add(egui::Slider::new(angles, 1..=64).text("Angles"));
+            egui::ComboBox::from_label("Frame size")
+                .selected_text(format!("{resolution}²"))
+                .show_ui(ui, |ui| {
+                    for size in [32, 64, 128, 256, 512] {
+                        ui.selectable_value(resolution, size, format!("{size}²"));
+                    }
+                });
+            ui.add(egui::Slider::new(elevation, -89.0..=89.0).text("Elevation °"));
+            let cells = grid(*angles);
+            ui.weak(format!(
+                "Sheet {}×{} px, {}×{} frames, written to {SHEET_PATH} and {ATLAS_PATH}",
+                cells.x * *resolution,
+                cells.y * *resolution,
+                cells.x,
+                cells.y
+            ));
+
+            ui.separator();
+            if let Some(capture) = capture {
+                let done = capture.frames.iter().flatten().count();
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / capture.frames.len() as f32)
+                        .text("Rendering…"),
+                );
+                return;
+            }
+
+            let targets: Vec<_> = selection
+                .entities
+                .iter()
+                .filter_map(|entity| meshes.get(*entity).ok().map(|mesh| (*entity, mesh)))
+                .collect();
+            let clicked = ui
+                .add_enabled(
+                    !targets.is_empty(),
+                    egui::Button::new("Render sprite sheet"),
+                )
+                .on_disabled_hover_text("Select one or more meshes first")
+                .clicked();
+            if !clicked {
+                return;
+            }
+
+            // World bounds of the selection, framed by an orthographic camera circling it.
+            let (mut min, mut max) = (Vec3::MAX, Vec3::MIN);
+            for (_, (aabb, transform, _)) in &targets {
+                let (center, half) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+                for corner in 0..8 {
+                    let sign = Vec3::new(
+                        if corner & 1 == 0 { -1.0 } else { 1.0 },
+                        if corner & 2 == 0 { -1.0 } else { 1.0 },
+                        if corner & 4 == 0 { -1.0 } else { 1.0 },
+                    );
+                    let point = transform.transform_point(center + half * sign);
+                    min = min.min(point);
+                    max = max.max(point);
+                }
+            }
+            let center = (min + max) * 0.5;
+            let radius = ((max - min).length() * 0.5).max(0.01);
+
+            let mut layers = Vec::new();
+            let isolated = lights.iter().chain(
+                targets
+                    .iter()
+                    .map(|(entity, (_, _, layers))| (*entity, *layers)),
+            );
+            for (entity, previous) in isolated {
+                let with_sprite = previous.cloned().unwrap_or_default().with(SPRITE_LAYER);
+                commands.entity(entity).insert(with_sprite);
+                layers.push((entity, previous.cloned()));
+            }
+
+            let mut cameras = Vec::new();
+            let mut frames_images = Vec::new();
+            for index in 0..*angles {
+                let yaw = index as f32 / *angles as f32 * TAU;
+                let direction =
+                    Quat::from_euler(EulerRot::YXZ, yaw, -elevation.to_radians(), 0.0) * Vec3::Z;
+                let image = images.add(frame_target(*resolution));
+                cameras.push(
+                    commands
+                        .spawn((
+                            Camera3dBundle {
+                                camera: Camera {
+                                    target: RenderTarget::Image(image.clone()),
+                                    clear_color: ClearColorConfig::Custom(Color::NONE),
+                                    ..default()
+                                },
+                                projection: OrthographicProjection {
+                                    scaling_mode: ScalingMode::Fixed {
+                                        width: radius * 2.0,
+                                        height: radius * 2.0,
+                                    },
+                                    far: radius * 8.0,
+                                    ..default()
+                                }
+                                .into(),
+                                transform: Transform::from_translation(
+                                    center + direction * radius * 4.0,
+                                )
+                                .looking_at(center, Vec3::Y),
+                                ..default()
+                            },
+                            RenderLayers::layer(SPRITE_LAYER),
+                        ))
+                        .id(),
+                );
+                frames_images.push(image);
+            }
+            *capture = Some(SpriteCapture {
+                cameras,
+                frames: vec![None; frames_images.len()],
+                images: frames_images,
+                layers,
+            });
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn capture_sprites_system(
+    mut commands: Commands,
+    mut window: ResMut<SpriteSheetWindow>,
+    mut requests: ResMut<ReadbackRequests>,
+    mut readbacks: EventReader<ReadbackComplete>,
+    mut errors: EventWriter<AppError>,
+    mut status: ResMut<StatusBar>,
+    mut progress: ResMut<TaskProgress>,
+    time: Res<Time>,
+) {
+    let Some(capture) = &mut window.capture else {
+        readbacks.clear();
+        return;
+    };
+    for readback in readbacks
+        .read()
+        .filter(|readback| readback.region.is_none())
+    {
+        if let Some(index) = capture
+            .images
+            .iter()
+            .position(|image| image.id() == readback.image)
+        {
+            capture.frames[index] = Some(readback.data.clone());
+        }
+    }
+    for (image, frame) in capture.images.iter().zip(&capture.frames) {
+        if frame.is_none() {
+            requests.request(image);
+        }
+    }
+    if capture.frames.iter().any(Option::is_none) {
+        let done = capture.frames.iter().flatten().count();
+        progress.report("Sprite sheet", done as f32 / capture.frames.len() as f32);
+        return;
+    }
+
+    let Some(capture) = window.capture.take() else {
+        return;
+    };
+    for camera in capture.cameras {
+        commands.entity(camera).despawn();
+    }
+    for (entity, layers) in capture.layers {
+        let Some(mut entity) = commands.get_entity(entity) else {
+            continue;
+        };
+        match layers {
+            Some(layers) => entity.insert(layers),
+            None => entity.remove::<RenderLayers>(),
+        };
+    }
+
+    let size = window.resolution;
+    let count = capture.frames.len() as u32;
+    let cells = grid(count);
+    let sheet_size = cells * size;
+    let mut sheet = vec![0; (sheet_size.x * sheet_size.y * 4) as usize];
+    let mut atlas = String::new();
+    for (index, frame) in capture.frames.into_iter().flatten().enumerate() {
+        let index = index as u32;
+        let origin = UVec2::new(index % cells.x, index / cells.x) * size;
+        let row_bytes = (size * 4) as usize;
+        for (y, row) in frame.chunks_exact(row_bytes).enumerate() {
+            let start = (((origin.y + y as u32) * sheet_size.x + origin.x) * 4) as usize;
+            sheet[start..start + row_bytes].copy_from_slice(row);
+        }
+        let separator = if index + 1 < count { "," } else { "" };
+        let _ = writeln!(
+            atlas,
+            r#"    {{ "index": {index}, "angle": {:.3}, "x": {}, "y": {}, "w": {size}, "h": {size} }}{separator}"#,
+            index as f32 / count as f32 * 360.0,
+            origin.x,
+            origin.y,
+        );
+    }
+    let atlas = format!(
+        "{{\n  \"image\": \"{SHEET_PATH}\",\n  \"size\": [{}, {}],\n  \"frame_size\": [{size}, {size}],\n  \"elevation\": {},\n  \"frames\": [\n{atlas}  ]\n}}\n",
+        sheet_size.x, sheet_size.y, window.elevation,
+    );
+
+    let result = encode_png(sheet_size, &sheet)
+        .map_err(|err| err.to_string())
+        .and_then(|png| std::fs::write(SHEET_PATH, png).map_err(|err| err.to_string()))
+        .and_then(|()| std::fs::write(ATLAS_PATH, atlas).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => status.flash(
+            format!("Saved {count} sprites to {SHEET_PATH}"),
+            time.elapsed_seconds(),
+        ),
+        Err(err) => {
+            errors.send(AppError::new(
+                "Sprite Sheet",
+                format!("Failed to save the sprite sheet: {err}"),
+            ));
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::CameraWindow,
+    input::{InputOwner, InputRouting},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    scene::{self, CustomMesh},
+    selection::Selection,
+    viewport::{Viewport, ViewportTool},
+    Static, ViewportCamera,
+};
+
+/// Where the camera stands in 2D mode, looking down -Z at the sprite plane.
+const CAMERA_DEPTH: f32 = 100.0;
+/// Zoom per wheel notch in 2D mode.
+const ZOOM_STEP: f32 = 1.1;
+
+/// The 2D sandbox mode and its sprites. Switching to 2D turns the viewport camera into an
+/// orthographic view of the XY plane with pan (middle drag, or right drag while selecting) and
+/// zoom (wheel), and the
+/// transform gizmo to planar handles. Sprites are textured quads spawned from loaded images;
+/// they are ordinary scene entities, so selection, the Inspector and the Hierarchy apply.
+pub struct SpritesPlugin;
+
+impl Plugin for SpritesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SandboxMode>()
+            .init_resource::<SavedView>()
+            .register_panel::<SpritesWindow>()
+            .add_systems(
+                Update,
+                (
+                    switch_mode_system,
+                    navigate_2d_system.after(crate::UiSet::Central),
+                    sprites_window_system.in_set(SpritesWindow::ui_set()),
+                    build_sprite_meshes_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Which kind of scene the viewport edits, switched from the toolbar.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SandboxMode {
+    #[default]
+    Scene3d,
+    Sprites2d,
+}
+
+impl SandboxMode {
+    pub const ALL: [(SandboxMode, &'static str, &'static str); 2] = [
+        (
+            SandboxMode::Scene3d,
+            "🧊 3D",
+            "Perspective view of the 3D scene",
+        ),
+        (
+            SandboxMode::Sprites2d,
+            "🖼 2D",
+            "Orthographic view of the XY plane for sprites; drag with the middle or right \
+             button to pan and scroll to zoom",
+        ),
+    ];
+}
+
+/// The 3D camera to return to when leaving 2D mode.
+#[derive(Default, Resource)]
+struct SavedView {
+    applied: SandboxMode,
+    camera: Option<(Transform, Projection)>,
+}
+
+/// An image shown as a quad; its mesh and texture are rebuilt from this on load.
+#[derive(Component, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sprite2d {
+    /// Asset path of the image.
+    pub image: String,
+    /// Image pixels per world unit.
+    pub pixels_per_unit: f32,
+}
+
+/// A sprite whose image has not loaded yet, so its size is unknown.
+#[derive(Component)]
+struct PendingSprite(Handle<Image>);
+
+#[derive(Resource)]
+pub struct SpritesWindow {
+    pub is_open: bool,
+    path: String,
+    chosen: Option<String>,
+    pixels_per_unit: f32,
+    /// Images loaded from this window, kept alive so they stay in the list.
+    loaded: Vec<Handle<Image>>,
+}
+
+impl Default for SpritesWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            path: "icon.png".to_owned(),
+            chosen: None,
+            pixels_per_unit: 100.0,
+            loaded: Vec::new(),
+        }
+    }
+}
+
+impl Panel for SpritesWindow {
+    const TITLE: &'static str = "Sprites";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn switch_mode_system(
+    mode: Res<SandboxMode>,
+    mut saved: ResMut<SavedView>,
+    mut camera_window: ResMut<CameraWindow>,
+    mut cameras: Query<(&mut Transform, &Projection), With<ViewportCamera>>,
+) {
+    if saved.applied == *mode {
+        return;
+    }
+    let Ok((mut transform, projection)) = cameras.get_single_mut() else {
+        return;
+    };
+    saved.applied = *mode;
+    match *mode {
+        SandboxMode::Sprites2d => {
+            saved.camera = Some((*transform, projection.clone()));
+            // Keep looking at roughly the same spot, now straight down -Z.
+            let target = transform.translation + *transform.forward() * 10.0;
+            *transform = Transform::from_xyz(target.x, target.y, CAMERA_DEPTH);
+            let height = camera_window.ortho_height();
+            camera_window.set_orthographic(height);
+        }
+        SandboxMode::Scene3d => {
+            let Some((restored, projection)) = saved.camera.take() else {
+                return;
+            };
+            *transform = restored;
+            match projection {
+                Projection::Perspective(perspective) => camera_window.set_fov(perspective.fov),
+                Projection::Orthographic(orthographic) => {
+                    camera_window.set_orthographic(orthographic.scale);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate_2d_system(
+    mut contexts: EguiContexts,
+    mode: Res<SandboxMode>,
+    viewport: Res<Viewport>,
+    routing: Res<InputRouting>,
+    tool: Res<ViewportTool>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut camera_window: ResMut<CameraWindow>,
+    mut cameras: Query<&mut Transform, With<ViewportCamera>>,
+) {
+    if *mode != SandboxMode::Sprites2d
+        || routing.pointer == InputOwner::Egui
+        || viewport.rect.height() <= 0.0
+    {
+        return;
+    }
+    let Ok(mut transform) = cameras.get_single_mut() else {
+        return;
+    };
+    let (delta, scroll) = contexts
+        .ctx_mut()
+        .input(|input| (input.pointer.delta(), input.smooth_scroll_delta.y));
+    let height = camera_window.ortho_height();
+    let units_per_point = height / viewport.rect.height();
+
+    // Other tools may use the right button themselves, e.g. to erase tiles.
+    let panning = mouse.pressed(MouseButton::Middle)
+        || (*tool == ViewportTool::Select && mouse.pressed(MouseButton::Right));
+    if panning && delta != egui::Vec2::ZERO {
+        transform.translation.x -= delta.x * units_per_point;
+        transform.translation.y += delta.y * units_per_point;
+    }
+    if viewport.pointer.is_some() && scroll != 0.0 {
+        camera_window.set_orthographic(height * ZOOM_STEP.powf(-scroll / 50.0));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sprites_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<SpritesWindow>,
+    mut mode: ResMut<SandboxMode>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut selection: ResMut<Selection>,
+    mut sprites: Query<&mut Sprite2d>,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    let SpritesWindow {
+        is_open,
+        path,
+        chosen,
+        pixels_per_unit,
+        loaded,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    // Only images with an asset path can be saved with the scene and found again on load.
+    let mut candidates: Vec<String> = images
+        .ids()
+        .filter_map(|id| asset_server.get_path(id))
+        .map(|path| path.to_string())
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+
+    egui::Window::new(SpritesWindow::TITLE)
+        .open(is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<SpritesWindow>(), |ui| {
+            if *mode != SandboxMode::Sprites2d && ui.button("Switch to 2D mode").clicked() {
+                *mode = SandboxMode::Sprites2d;
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(path)
+                        .hint_text("path under assets/")
+                        .desired_width(180.0),
+                );
+                if ui
+                    .add_enabled(!path.trim().is_empty(), egui::Button::new("Load"))
+                    .clicked()
+                {
+                    let handle: Handle<Image> = asset_server.load(path.trim().to_owned());
+                    *chosen = Some(path.trim().to_owned());
+                    loaded.push(handle);
+                }
+            });
+            egui::ComboBox::from_label("Image")
+                .selected_text(chosen.as_deref().unwrap_or("Choose an image…"))
+                .width(200.0)
+                .show_ui(ui, |ui| {
+                    for candidate in &candidates {
+                        ui.selectable_value(chosen, Some(candidate.clone()), candidate);
+                    }
+                });
+            ui.add(
+                egui::Slider::new(pixels_per_unit, 1.0..=1000.0)
+                    .logarithmic(true)
+                    .text("Pixels per unit"),
+            );
+
+            let ready = chosen.as_ref().filter(|chosen| candidates.contains(chosen));
+            let spawn = ui
+                .add_enabled(ready.is_some(), egui::Button::new("Spawn sprite"))
+                .on_disabled_hover_text("Load an image first")
+                .clicked();
+            if let (true, Some(image)) = (spawn, ready) {
+                let translation = cameras.get_single().map_or(Vec3::ZERO, |camera| {
+                    match *mode {
+                        // On the sprite plane under the view centre.
+                        SandboxMode::Sprites2d => camera.translation().truncate().extend(0.0),
+                        SandboxMode::Scene3d => camera.translation() + camera.forward() * 10.0,
+                    }
+                });
+                let entity = scene::spawn_cube(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    Transform::from_translation(translation),
+                    Color::WHITE,
+                );
+                let name = image.rsplit('/').next().unwrap_or(image).to_owned();
+                commands.entity(entity).insert((
+                    Sprite2d {
+                        image: image.clone(),
+                        pixels_per_unit: *pixels_per_unit,
+                    },
+                    Static,
+                    Name::new(name),
+                ));
+                selection.select(entity);
+            }
+
+            let Some(mut selected) = selection.primary().and_then(|e| sprites.get_mut(e).ok())
+            else {
+                return;
+            };
+            ui.separator();
+            ui.strong(format!("Selected sprite: {}", selected.image));
+            let mut ppu = selected.pixels_per_unit;
+            if ui
+                .add(
+                    egui::Slider::new(&mut ppu, 1.0..=1000.0)
+                        .logarithmic(true)
+                        .text("Pixels per unit"),
+                )
+                .changed()
+            {
+                selected.pixels_per_unit = ppu;
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn build_sprite_meshes_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut sprites: Query<
+        (
+            Entity,
+            &Sprite2d,
+            &mut Handle<Mesh>,
+            &Handle<StandardMaterial>,
+            Option<&PendingSprite>,
+        ),
+        Or<(Changed<Sprite2d>, With<PendingSprite>)>,
+    >,
+) {
+    for (entity, sprite, mut mesh, material, pending) in &mut sprites {
+        let handle = match pending {
+            Some(PendingSprite(handle)) => handle.clone(),
+            None => asset_server.load(sprite.image.clone()),
+        };
+        let Some(image) = images.get(&handle) else {
+            if pending.is_none() {
+                commands.entity(entity).insert(PendingSprite(handle));
+            }
+            continue;
+        };
+        let size = image.size().as_vec2() / sprite.pixels_per_unit.max(f32::EPSILON);
+        *mesh = meshes.add(Rectangle::new(size.x, size.y));
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color_texture = Some(handle);
+            material.alpha_mode = AlphaMode::Blend;
+            material.unlit = true;
+            material.double_sided = true;
+            material.cull_mode = None;
+        }
+        commands
+            .entity(entity)
+            .insert(CustomMesh)
+            .remove::<PendingSprite>();
+    }
+}
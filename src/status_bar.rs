@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::errors::ErrorsWindow;
+use crate::input::InputRouting;
+use crate::keybindings::Shortcuts;
+use crate::numeric::{format_quantity, parse_quantity, Unit};
+use crate::picking::Picking;
+use crate::selection::Selection;
+use crate::viewport::Viewport;
+use crate::ViewportCamera;
+
+/// How long a transient status message stays visible.
+const MESSAGE_SECS: f32 = 3.0;
+/// How far from a coordinate the camera stands when nothing is in front of it to keep the
+/// distance to.
+const GO_TO_DISTANCE: f32 = 10.0;
+
+/// A one-line bar along the bottom of the window for transient messages and input state. It
+/// reads out the world position under the pointer and of the primary selection, and has a box
+/// for typing a position to go to.
+pub struct StatusBarPlugin;
+
+impl Plugin for StatusBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatusBar>()
+            .add_event::<GoTo>()
+            .add_systems(
+                Update,
+                (
+                    report_suppressed_shortcuts_system,
+                    status_bar_system.run_if(crate::overlay::overlay_off),
+                    go_to_system,
+                )
+                    .chain()
+                    // Bottom panels stack inwards, so the status bar claims the edge first.
+                    .before(crate::UiSet::Panels),
+            );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct StatusBar {
+    message: Option<(String, f32)>,
+    /// The go-to box's text.
+    go_to: String,
+}
+
+/// Centres the view on a world position, or moves the primary selection there.
+#[derive(Event)]
+struct GoTo {
+    position: Vec3,
+    move_selection: bool,
+}
+
+impl StatusBar {
+    /// Shows `text` for a few seconds, replacing any current message.
+    pub fn flash(&mut self, text: impl Into<String>, now: f32) {
+        self.message = Some((text.into(), now + MESSAGE_SECS));
+    }
+}
+
+fn report_suppressed_shortcuts_system(
+    shortcuts: Shortcuts,
+    time: Res<Time>,
+    mut status: ResMut<StatusBar>,
+) {
+    if let Some(binding) = shortcuts.suppressed() {
+        status.flash(
+            format!(
+                "{} ({}) suppressed while editing text",
+                binding.chord, binding.description
+            ),
+            time.elapsed_seconds(),
+        );
+    }
+}
+
+/// `position` as the status bar writes it, for the shown separator and units.
+fn format_position(position: Vec3) -> String {
+    let axes = [("x", position.x), ("y", position.y), ("z", position.z)];
+    axes.map(|(axis, value)| {
+        format!(
+            "{axis} {}",
+            format_quantity(value as f64, 2, Some(Unit::Meters))
+        )
+    })
+    .join("  ")
+}
+
+/// Reads "x, y, z" in meters, each value a number or expression that may carry a unit.
+/// Semicolons or spaces also separate the values, where commas are decimal separators.
+fn parse_position(text: &str) -> Option<Vec3> {
+    let [x, y, z] = [';', ',', ' ']
+        .into_iter()
+        .map(|separator| {
+            text.split(separator)
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .find(|parts| parts.len() == 3)?[..]
+    else {
+        return None;
+    };
+    let axis = |part: &str| parse_quantity(part, Unit::Meters).map(|value| value as f32);
+    Some(Vec3::new(axis(x)?, axis(y)?, axis(z)?))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn status_bar_system(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    routing: Res<InputRouting>,
+    mut status: ResMut<StatusBar>,
+    mut errors: ResMut<ErrorsWindow>,
+    selection: Res<Selection>,
+    transforms: Query<&GlobalTransform>,
+    picking: Picking,
+    mut go_to: EventWriter<GoTo>,
+) {
+    let now = time.elapsed_seconds();
+    if status
+        .message
+        .as_ref()
+        .is_some_and(|(_, expires)| *expires <= now)
+    {
+        status.message = None;
+    }
+
+    egui::TopBottomPanel::bottom("status_bar").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            match &status.message {
+                Some((text, _)) => {
+                    ui.label(egui::RichText::new(text).color(ui.visuals().warn_fg_color))
+                }
+                None => ui.weak("Ready"),
+            };
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if errors.len() > 0
+                    && ui
+                        .link(
+                            egui::RichText::new(format!("⚠ {}", errors.len()))
+                                .color(ui.visuals().error_fg_color),
+                        )
+                        .on_hover_text("Open the Errors window")
+                        .clicked()
+                {
+                    errors.is_open = true;
+                }
+                if !routing.keyboard_is_free() {
+                    ui.weak("⌨ Typing: shortcuts paused");
+                }
+                if let Some(transform) = selection
+                    .primary()
+                    .and_then(|entity| transforms.get(entity).ok())
+                {
+                    ui.weak(format_position(transform.translation()))
+                        .on_hover_text("Position of the primary selection");
+                }
+                if let Some(hit) = picking.pick_pointer(&[]) {
+                    ui.weak(format!("pointer {}", format_position(hit.point)))
+                        .on_hover_text("Surface position under the pointer");
+                    ui.separator();
+                }
+
+                let response = ui
+                    .add(
+                        egui::TextEdit::singleline(&mut status.go_to)
+                            .hint_text("Go to x, y, z")
+                            .desired_width(120.0),
+                    )
+                    .on_hover_text(
+                        "Enter centres the view on the position; Shift+Enter moves the \
+                         selection there",
+                    );
+                if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                    match parse_position(&status.go_to) {
+                        Some(position) => {
+                            go_to.send(GoTo {
+                                position,
+                                move_selection: ui.input(|input| input.modifiers.shift),
+                            });
+                        }
+                        None => {
+                            let text = format!("Could not read \"{}\" as x, y, z", status.go_to);
+                            status.flash(text, now);
+                        }
+                    }
+                }
+            });
+        });
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn go_to_system(
+    mut events: EventReader<GoTo>,
+    time: Res<Time>,
+    mut status: ResMut<StatusBar>,
+    selection: Res<Selection>,
+    viewport: Res<Viewport>,
+    picking: Picking,
+    cameras: Query<Entity, With<ViewportCamera>>,
+    parents: Query<&Parent>,
+    globals: Query<&GlobalTransform>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for event in events.read() {
+        let now = time.elapsed_seconds();
+        if event.move_selection {
+            let Some(entity) = selection.primary() else {
+                status.flash("Nothing selected to move", now);
+                continue;
+            };
+            // Translations are relative to the parent.
+            let local = parents
+                .get(entity)
+                .ok()
+                .and_then(|parent| globals.get(parent.get()).ok())
+                .map_or(event.position, |parent| {
+                    parent.affine().inverse().transform_point3(event.position)
+                });
+            if let Ok(mut transform) = transforms.get_mut(entity) {
+                transform.translation = local;
+            }
+            status.flash(format!("Moved to {}", format_position(event.position)), now);
+        } else {
+            let Ok(camera) = cameras.get_single() else {
+                continue;
+            };
+            // Keep the distance to whatever is in the middle of the view, so the camera moves
+            // as if its look-at point slid over to the position.
+            let distance = picking
+                .ray_through(viewport.image_size.as_vec2() * 0.5)
+                .and_then(|ray| picking.cast(ray, &[]))
+                .map_or(GO_TO_DISTANCE, |hit| hit.distance);
+            if let Ok(mut transform) = transforms.get_mut(camera) {
+                transform.translation = event.position - *transform.forward() * distance;
+            }
+            status.flash(
+                format!("Centred on {}", format_position(event.position)),
+                now,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_take_any_separator() {
+        let expected = Some(Vec3::new(1.0, -2.0, 3.5));
+        assert_eq!(parse_position("1, -2, 3.5"), expected);
+        assert_eq!(parse_position("1 -2 3.5"), expected);
+        assert_eq!(parse_position("  1   -2   3.5 "), expected);
+        // With semicolons, commas are decimal separators.
+        assert_eq!(parse_position("1; -2; 3,5"), expected);
+    }
+
+    #[test]
+    fn positions_take_units_and_expressions() {
+        assert_eq!(
+            parse_position("50 cm, 2 m, 1+1"),
+            Some(Vec3::new(0.5, 2.0, 2.0))
+        );
+        assert_eq!(
+            parse_position("1km; 10*2 cm; -3"),
+            Some(Vec3::new(1000.0, 0.2, -3.0))
+        );
+    }
+
+    #[test]
+    fn positions_need_three_numbers() {
+        for text in ["", "1, 2", "1, 2, 3, 4", "1, two, 3", "1; 2; 3 kg"] {
+            assert_eq!(parse_position(text), None, "{text:?}");
+        }
+    }
+}
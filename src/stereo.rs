@@ -0,0 +1,254 @@
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    ViewImage, ViewportCamera,
+};
+
+/// Layer the anaglyph composite lives on, away from the scene.
+const COMPOSITE_LAYER: usize = 31;
+
+/// Experimental red/cyan anaglyph preview. Two eye cameras parented to the viewport camera
+/// render into their own images; an orthographic camera then adds them, tinted red and cyan,
+/// into the viewport image using only unlit additive materials.
+pub struct StereoPlugin;
+
+impl Plugin for StereoPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<StereoWindow>().add_systems(
+            Update,
+            (
+                stereo_window_system.in_set(StereoWindow::ui_set()),
+                stereo_rig_system,
+                sync_eyes_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The entities spawned while stereo is on.
+struct StereoRig {
+    eyes: [Entity; 2],
+    /// The composite camera and its two quads.
+    composite: [Entity; 3],
+}
+
+#[derive(Resource)]
+pub struct StereoWindow {
+    pub is_open: bool,
+    enabled: bool,
+    /// Distance between the eye cameras, in world units.
+    eye_separation: f32,
+    /// Distance in front of the camera where both eyes converge; objects there have no parallax.
+    convergence: f32,
+    rig: Option<StereoRig>,
+}
+
+impl Default for StereoWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            enabled: false,
+            eye_separation: 0.5,
+            convergence: 30.0,
+            rig: None,
+        }
+    }
+}
+
+impl Panel for StereoWindow {
+    const TITLE: &'static str = "Stereo";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn stereo_window_system(mut contexts: PanelContexts, mut window: ResMut<StereoWindow>) {
+    let StereoWindow {
+        is_open,
+        enabled,
+        eye_separation,
+        convergence,
+        ..
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Stereo")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<StereoWindow>(), |ui| {
+            ui.checkbox(enabled, "Red/cyan anaglyph (experimental)");
+            ui.add(
+                egui::Slider::new(eye_separation, 0.0..=3.0)
+                    .text("Eye separation")
+                    .fixed_decimals(2),
+            );
+            ui.add(
+                egui::Slider::new(convergence, 1.0..=100.0)
+                    .text("Convergence")
+                    .logarithmic(true),
+            );
+            ui.weak("Left eye is red, right eye cyan.");
+        });
+}
+
+fn render_target(size: Extent3d) -> Image {
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+fn stereo_rig_system(
+    mut commands: Commands,
+    mut window: ResMut<StereoWindow>,
+    mut cameras: Query<(Entity, &mut Camera), With<ViewportCamera>>,
+    view_image: Res<ViewImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Ok((viewport_camera, mut camera)) = cameras.get_single_mut() else {
+        return;
+    };
+    match (window.enabled, window.rig.is_some()) {
+        (true, false) => {
+            let Some(size) = images
+                .get(&**view_image)
+                .map(|image| image.texture_descriptor.size)
+            else {
+                return;
+            };
+            camera.is_active = false;
+
+            let mut eyes = [Entity::PLACEHOLDER; 2];
+            let mut quads = [Entity::PLACEHOLDER; 2];
+            let quad = meshes.add(Rectangle::new(1.0, 1.0));
+            for (index, tint) in [Color::srgb(1.0, 0.0, 0.0), Color::srgb(0.0, 1.0, 1.0)]
+                .into_iter()
+                .enumerate()
+            {
+                let image = images.add(render_target(size));
+                eyes[index] = commands
+                    .spawn(Camera3dBundle {
+                        camera: Camera {
+                            target: RenderTarget::Image(image.clone()),
+                            clear_color: camera.clear_color,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .set_parent(viewport_camera)
+                    .id();
+                quads[index] = commands
+                    .spawn((
+                        PbrBundle {
+                            mesh: quad.clone(),
+                            material: materials.add(StandardMaterial {
+                                base_color: tint,
+                                base_color_texture: Some(image),
+                                unlit: true,
+                                alpha_mode: AlphaMode::Add,
+                                ..default()
+                            }),
+                            transform: Transform::from_xyz(0.0, 0.0, -1.0),
+                            ..default()
+                        },
+                        RenderLayers::layer(COMPOSITE_LAYER),
+                    ))
+                    .id();
+            }
+            let composite = commands
+                .spawn((
+                    Camera3dBundle {
+                        camera: Camera {
+                            target: RenderTarget::Image(view_image.clone()),
+                            clear_color: ClearColorConfig::Custom(Color::BLACK),
+                            ..default()
+                        },
+                        projection: OrthographicProjection {
+                            scaling_mode: ScalingMode::Fixed {
+                                width: 1.0,
+                                height: 1.0,
+                            },
+                            ..default()
+                        }
+                        .into(),
+                        // The eye images are already tonemapped.
+                        tonemapping: Tonemapping::None,
+                        ..default()
+                    },
+                    RenderLayers::layer(COMPOSITE_LAYER),
+                ))
+                .id();
+            window.rig = Some(StereoRig {
+                eyes,
+                composite: [composite, quads[0], quads[1]],
+            });
+        }
+        (false, true) => {
+            let Some(rig) = window.rig.take() else {
+                return;
+            };
+            for entity in rig.eyes.into_iter().chain(rig.composite) {
+                commands.entity(entity).despawn_recursive();
+            }
+            camera.is_active = true;
+        }
+        _ => {}
+    }
+}
+
+/// Keeps the eye cameras toed in on the convergence point and matching the viewport camera.
+fn sync_eyes_system(
+    window: Res<StereoWindow>,
+    viewport_cameras: Query<(&Camera, &Projection), With<ViewportCamera>>,
+    mut eyes: Query<(&mut Transform, &mut Camera, &mut Projection), Without<ViewportCamera>>,
+) {
+    let (Some(rig), Ok((viewport_camera, viewport_projection))) =
+        (&window.rig, viewport_cameras.get_single())
+    else {
+        return;
+    };
+    let target = Vec3::new(0.0, 0.0, -window.convergence);
+    for (eye, side) in rig.eyes.into_iter().zip([-0.5, 0.5]) {
+        let Ok((mut transform, mut camera, mut projection)) = eyes.get_mut(eye) else {
+            continue;
+        };
+        let eye_transform =
+            Transform::from_xyz(side * window.eye_separation, 0.0, 0.0).looking_at(target, Vec3::Y);
+        if *transform != eye_transform {
+            *transform = eye_transform;
+        }
+        camera.clear_color = viewport_camera.clear_color;
+        *projection = viewport_projection.clone();
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+use crate::settings::{theme_visuals, Settings, ThemeSettings};
+
+/// Renders the same widgets under the current theme and a candidate one, side by side, so
+/// theme edits can be judged before they are applied to the whole UI.
+pub struct StyleComparePlugin;
+
+impl Plugin for StyleComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<StyleCompareWindow>().add_systems(
+            Update,
+            style_compare_window_system.in_set(StyleCompareWindow::ui_set()),
+        );
+    }
+}
+
+/// A theme under evaluation: what the settings would hold after applying it.
+#[derive(Clone, PartialEq)]
+struct Candidate {
+    theme: ThemeSettings,
+    high_contrast: bool,
+}
+
+impl Candidate {
+    fn current(settings: &Settings) -> Self {
+        Self {
+            theme: settings.theme.clone(),
+            high_contrast: settings.accessibility.high_contrast,
+        }
+    }
+}
+
+/// Values the sample widgets edit; shared by both columns so they stay in step.
+struct Samples {
+    checked: bool,
+    choice: usize,
+    value: f32,
+    text: String,
+}
+
+impl Default for Samples {
+    fn default() -> Self {
+        Self {
+            checked: true,
+            choice: 0,
+            value: 0.4,
+            text: "Editable text".to_owned(),
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct StyleCompareWindow {
+    pub is_open: bool,
+    /// Seeded from the settings the first time the window opens.
+    candidate: Option<Candidate>,
+    samples: Samples,
+}
+
+impl Panel for StyleCompareWindow {
+    const TITLE: &'static str = "Style Compare";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn style_compare_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<StyleCompareWindow>,
+    mut settings: ResMut<Settings>,
+) {
+    let StyleCompareWindow {
+        is_open,
+        candidate,
+        samples,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    let current = Candidate::current(&settings);
+    let candidate = candidate.get_or_insert_with(|| current.clone());
+    let mut apply = false;
+
+    egui::Window::new(StyleCompareWindow::TITLE)
+        .open(is_open)
+        .default_width(560.0)
+        .show(contexts.ctx::<StyleCompareWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Candidate:");
+                ui.checkbox(&mut candidate.theme.dark_mode, "Dark mode");
+                ui.checkbox(&mut candidate.high_contrast, "High contrast");
+                ui.label("Rounding");
+                ui.add(egui::Slider::new(
+                    &mut candidate.theme.window_rounding,
+                    0.0..=12.0,
+                ));
+            });
+            ui.horizontal(|ui| {
+                let differs = *candidate != current;
+                apply = ui
+                    .add_enabled(differs, egui::Button::new("Apply candidate"))
+                    .on_hover_text("Make the candidate the theme of the whole UI")
+                    .clicked();
+                if ui
+                    .add_enabled(differs, egui::Button::new("Reset to current"))
+                    .clicked()
+                {
+                    *candidate = current.clone();
+                }
+            });
+            ui.separator();
+
+            ui.columns(2, |columns| {
+                let sides = [("Current", &current), ("Candidate", &*candidate)];
+                for (column, (label, theme)) in columns.iter_mut().zip(sides) {
+                    column.push_id(label, |ui| {
+                        let visuals = theme_visuals(&theme.theme, theme.high_contrast);
+                        sample_widgets(ui, label, visuals, samples);
+                    });
+                }
+            });
+        });
+
+    if apply {
+        settings.theme = candidate.theme.clone();
+        settings.accessibility.high_contrast = candidate.high_contrast;
+    }
+}
+
+/// A representative widget set drawn on a panel-coloured frame under `visuals`.
+fn sample_widgets(ui: &mut egui::Ui, label: &str, visuals: egui::Visuals, samples: &mut Samples) {
+    ui.scope(|ui| {
+        let frame = egui::Frame::none()
+            .fill(visuals.panel_fill)
+            .stroke(visuals.window_stroke)
+            .rounding(visuals.window_rounding)
+            .inner_margin(8.0);
+        ui.style_mut().visuals = visuals;
+        frame.show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            ui.heading(label);
+            ui.label("Regular label text");
+            ui.weak("Weak secondary text");
+            ui.hyperlink_to("Hyperlink", "https://github.com/emilk/egui");
+            ui.separator();
+            ui.horizontal(|ui| {
+                let _ = ui.button("Button");
+                ui.add_enabled(false, egui::Button::new("Disabled"));
+                ui.toggle_value(&mut samples.checked, "Toggle");
+            });
+            ui.checkbox(&mut samples.checked, "Checkbox");
+            ui.horizontal(|ui| {
+                for (index, name) in ["One", "Two", "Three"].into_iter().enumerate() {
+                    ui.radio_value(&mut samples.choice, index, name);
+                }
+            });
+            ui.add(egui::Slider::new(&mut samples.value, 0.0..=1.0).text("Slider"));
+            ui.add(egui::DragValue::new(&mut samples.value).speed(0.01));
+            ui.text_edit_singleline(&mut samples.text);
+            ui.add(egui::ProgressBar::new(samples.value).show_percentage());
+            ui.collapsing("Collapsing header", |ui| {
+                ui.label("Nested content");
+            });
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut samples.checked, true, "Selectable on");
+                ui.selectable_value(&mut samples.checked, false, "Off");
+            });
+        });
+    });
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    window_title::TaskProgress,
+};
+
+/// The Tasks window: a progress bar for each long-running task, such as cubemap captures and
+/// lightmap bakes, with a button to stop those that can be stopped.
+pub struct TasksPlugin;
+
+impl Plugin for TasksPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<TasksWindow>()
+            .add_systems(Update, tasks_window_system.in_set(TasksWindow::ui_set()));
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct TasksWindow {
+    pub is_open: bool,
+}
+
+impl Panel for TasksWindow {
+    const TITLE: &'static str = "Tasks";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn tasks_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<TasksWindow>,
+    mut progress: ResMut<TaskProgress>,
+) {
+    let TasksWindow { is_open } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new(TasksWindow::TITLE)
+        .open(is_open)
+        .default_width(280.0)
+        .show(contexts.ctx::<TasksWindow>(), |ui| {
+            let running = progress.running().to_vec();
+            if running.is_empty() {
+                ui.weak("No tasks running.");
+            } else {
+                // Progress is reported every frame, so keep the bars moving.
+                ui.ctx().request_repaint();
+            }
+            for task in running {
+                ui.horizontal(|ui| {
+                    ui.label(task.name);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if task.cancellable && ui.button("Cancel").clicked() {
+                            progress.cancel(task.name);
+                        }
+                        ui.add(egui::ProgressBar::new(task.fraction).show_percentage());
+                    });
+                });
+            }
+        });
+}
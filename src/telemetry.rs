@@ -0,0 +1,177 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::frame_capture::FrameCapture;
+use crate::icons::{Icon, IconButtonsExt};
+use crate::panels::{Panel, PanelContexts, RegisterPanelExt};
+
+pub const TELEMETRY_PATH: &str = "telemetry.csv";
+const HEADER: &str = "elapsed_secs,fps,entities,meshes,visible_meshes,texture_bytes";
+
+/// Appends one row of scene statistics per second to a CSV file while recording, for soak
+/// tests that are analysed afterwards.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<TelemetryWindow>().add_systems(
+            Update,
+            (
+                telemetry_window_system.in_set(TelemetryWindow::ui_set()),
+                record_telemetry_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct TelemetryWindow {
+    pub is_open: bool,
+    file: Option<File>,
+    rows: usize,
+    /// Frames and time accumulated since the last row.
+    frames: u32,
+    since_row: f32,
+    last_row: String,
+    error: Option<String>,
+}
+
+impl Panel for TelemetryWindow {
+    const TITLE: &'static str = "Telemetry";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl TelemetryWindow {
+    fn start(&mut self) {
+        let exists = std::fs::metadata(TELEMETRY_PATH).is_ok_and(|meta| meta.len() > 0);
+        let opened = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(TELEMETRY_PATH)
+            .and_then(|mut file| {
+                if !exists {
+                    writeln!(file, "{HEADER}")?;
+                }
+                Ok(file)
+            });
+        match opened {
+            Ok(file) => {
+                self.file = Some(file);
+                self.rows = 0;
+                self.frames = 0;
+                self.since_row = 0.0;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(format!("Failed to open {TELEMETRY_PATH}: {err}")),
+        }
+    }
+}
+
+fn telemetry_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<TelemetryWindow>,
+    mut capture: ResMut<FrameCapture>,
+) {
+    if !window.is_open {
+        return;
+    }
+
+    let mut is_open = true;
+    egui::Window::new("Telemetry").open(&mut is_open).show(
+        contexts.ctx::<TelemetryWindow>(),
+        |ui| {
+            ui.horizontal(|ui| {
+                if window.file.is_some() {
+                    if ui.icon_button(Icon::Stop, "Stop").clicked() {
+                        window.file = None;
+                    }
+                    ui.label(format!(
+                        "Recording to {TELEMETRY_PATH}: {} rows",
+                        window.rows
+                    ));
+                } else {
+                    if ui.icon_button(Icon::Record, "Start").clicked() {
+                        window.start();
+                    }
+                    ui.weak(format!("Appends to {TELEMETRY_PATH} every second"));
+                }
+            });
+            if let Some(error) = &window.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            if !window.last_row.is_empty() {
+                ui.separator();
+                ui.monospace(HEADER);
+                ui.monospace(&window.last_row);
+            }
+            ui.weak(
+                "Visible meshes stand in for draw calls, before batching. Texture bytes count \
+                 CPU-side images only; render-only targets are not included.",
+            );
+            ui.separator();
+            let renderdoc = capture.renderdoc_loaded();
+            if ui
+                .add_enabled(
+                    renderdoc != Some(false) && !capture.is_pending(),
+                    egui::Button::new("📸 Capture frame"),
+                )
+                .on_hover_text("Record the next frame, viewport and egui passes included")
+                .on_disabled_hover_text("Launch the sandbox from RenderDoc to capture frames")
+                .clicked()
+            {
+                capture.request();
+            }
+            if renderdoc.is_none() {
+                ui.weak("Captures need the sandbox to be launched from RenderDoc.");
+            }
+        },
+    );
+    window.is_open = is_open;
+}
+
+fn record_telemetry_system(
+    time: Res<Time>,
+    mut window: ResMut<TelemetryWindow>,
+    entities: Query<()>,
+    meshes: Query<&ViewVisibility, With<Handle<Mesh>>>,
+    images: Res<Assets<Image>>,
+) {
+    if window.file.is_none() {
+        return;
+    }
+    window.frames += 1;
+    window.since_row += time.delta_seconds();
+    if window.since_row < 1.0 {
+        return;
+    }
+
+    let fps = window.frames as f32 / window.since_row;
+    let visible = meshes.iter().filter(|visibility| visibility.get()).count();
+    let texture_bytes: usize = images.iter().map(|(_, image)| image.data.len()).sum();
+    let row = format!(
+        "{:.1},{fps:.1},{},{},{visible},{texture_bytes}",
+        time.elapsed_seconds(),
+        entities.iter().len(),
+        meshes.iter().len(),
+    );
+    let window = &mut *window;
+    if let Some(file) = &mut window.file {
+        if let Err(err) = writeln!(file, "{row}") {
+            window.error = Some(format!("Failed to write {TELEMETRY_PATH}: {err}"));
+            window.file = None;
+        }
+    }
+    window.rows += 1;
+    window.frames = 0;
+    window.since_row = 0.0;
+    window.last_row = row;
+}
@@ -0,0 +1,393 @@
+use std::fs::File;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        primitives::Aabb,
+        render_asset::RenderAssetUsages,
+    },
+};
+use bevy_egui::egui;
+
+use crate::{
+    errors::AppError,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    RenderCube,
+};
+
+/// Gap left between the highest peak and the lowest cube.
+const CLEARANCE: f32 = 0.05;
+
+/// The Terrain window: turns a grayscale PNG into a height-field mesh under the scene.
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<TerrainWindow>().add_systems(
+            Update,
+            (
+                terrain_window_system.in_set(TerrainWindow::ui_set()),
+                build_terrain_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct Terrain;
+
+/// Row-major heights from 0 to 1.
+struct HeightField {
+    size: UVec2,
+    heights: Vec<f32>,
+}
+
+impl HeightField {
+    fn read_png(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        let channels = info.color_type.samples();
+        let heights = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let luminance = match pixel {
+                    [r, g, b, ..] => 0.2126 * *r as f32 + 0.7152 * *g as f32 + 0.0722 * *b as f32,
+                    [gray, ..] => *gray as f32,
+                    [] => 0.0,
+                };
+                luminance / 255.0
+            })
+            .collect();
+        Ok(Self {
+            size: UVec2::new(info.width, info.height),
+            heights,
+        })
+    }
+
+    /// Rolling hills from a few octaves of sines, for trying the tool without an image.
+    fn sample() -> Self {
+        let size = UVec2::splat(128);
+        let heights = (0..size.x * size.y)
+            .map(|i| {
+                let (x, y) = ((i % size.x) as f32 / 16.0, (i / size.x) as f32 / 16.0);
+                let mut height = 0.0;
+                for octave in 0..4 {
+                    let scale = 2.0f32.powi(octave);
+                    height += ((x * scale + octave as f32).sin() * (y * scale * 0.8).cos()) / scale;
+                }
+                (height / 1.9 + 0.5).clamp(0.0, 1.0)
+            })
+            .collect();
+        Self { size, heights }
+    }
+
+    /// Bilinear sample at `uv` in 0..=1.
+    fn sample_at(&self, uv: Vec2) -> f32 {
+        let max = (self.size - 1).as_vec2();
+        let pixel = (uv * max).clamp(Vec2::ZERO, max);
+        let (x0, y0) = (pixel.x.floor() as u32, pixel.y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(self.size.x - 1), (y0 + 1).min(self.size.y - 1));
+        let at = |x: u32, y: u32| self.heights[(y * self.size.x + x) as usize];
+        let fraction = pixel.fract();
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * fraction.x;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * fraction.x;
+        top + (bottom - top) * fraction.y
+    }
+}
+
+#[derive(Resource)]
+pub struct TerrainWindow {
+    pub is_open: bool,
+    path: String,
+    source: Option<HeightField>,
+    /// World size of one side.
+    size: f32,
+    height_scale: f32,
+    /// Vertices along each side.
+    resolution: u32,
+    /// Colour vertices by slope and height instead of one flat colour.
+    splat: bool,
+    /// Normalised height where grass gives way to snow.
+    snow_line: f32,
+    /// Slope, from 0 (flat) to 1 (vertical), past which grass gives way to rock.
+    rock_slope: f32,
+    dirty: bool,
+}
+
+impl Default for TerrainWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            path: "heightmap.png".to_owned(),
+            source: None,
+            size: 40.0,
+            height_scale: 4.0,
+            resolution: 128,
+            splat: true,
+            snow_line: 0.75,
+            rock_slope: 0.35,
+            dirty: false,
+        }
+    }
+}
+
+impl Panel for TerrainWindow {
+    const TITLE: &'static str = "Terrain";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl TerrainWindow {
+    fn splat_color(&self, height: f32, slope: f32) -> [f32; 4] {
+        let sand = Vec3::new(0.76, 0.7, 0.5);
+        let grass = Vec3::new(0.25, 0.45, 0.18);
+        let rock = Vec3::new(0.42, 0.4, 0.38);
+        let snow = Vec3::new(0.95, 0.95, 0.97);
+        let blend = |edge: f32, value: f32| ((value - edge) / 0.06 + 0.5).clamp(0.0, 1.0);
+        let mut color = sand.lerp(grass, blend(0.12, height));
+        color = color.lerp(snow, blend(self.snow_line, height));
+        color = color.lerp(rock, blend(self.rock_slope, slope));
+        Color::srgb(color.x, color.y, color.z)
+            .to_linear()
+            .to_f32_array()
+    }
+
+    fn mesh(&self, field: &HeightField) -> Mesh {
+        let n = self.resolution.max(2);
+        let step = self.size / (n - 1) as f32;
+        let heights: Vec<f32> = (0..n * n)
+            .map(|i| {
+                let uv = Vec2::new((i % n) as f32, (i / n) as f32) / (n - 1) as f32;
+                field.sample_at(uv)
+            })
+            .collect();
+        let at = |x: u32, z: u32| heights[(z.min(n - 1) * n + x.min(n - 1)) as usize];
+
+        let mut positions = Vec::with_capacity((n * n) as usize);
+        let mut normals = Vec::with_capacity((n * n) as usize);
+        let mut uvs = Vec::with_capacity((n * n) as usize);
+        let mut colors = Vec::with_capacity((n * n) as usize);
+        for z in 0..n {
+            for x in 0..n {
+                let height = at(x, z);
+                positions.push([
+                    x as f32 * step - self.size * 0.5,
+                    height * self.height_scale,
+                    z as f32 * step - self.size * 0.5,
+                ]);
+                // Central differences, one-sided at the edges.
+                let dx = (at(x + 1, z) - at(x.saturating_sub(1), z)) * self.height_scale;
+                let dz = (at(x, z + 1) - at(x, z.saturating_sub(1))) * self.height_scale;
+                let normal = Vec3::new(-dx, 2.0 * step, -dz).normalize();
+                normals.push(normal.to_array());
+                uvs.push([x as f32 / (n - 1) as f32, z as f32 / (n - 1) as f32]);
+                colors.push(if self.splat {
+                    self.splat_color(height, 1.0 - normal.y)
+                } else {
+                    self.splat_color(0.5, 0.0)
+                });
+            }
+        }
+        let mut indices = Vec::with_capacity(((n - 1) * (n - 1) * 6) as usize);
+        for z in 0..n - 1 {
+            for x in 0..n - 1 {
+                let here = z * n + x;
+                indices.extend([here, here + n, here + 1, here + 1, here + n, here + n + 1]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+fn terrain_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<TerrainWindow>,
+    mut commands: Commands,
+    terrains: Query<Entity, With<Terrain>>,
+    mut errors: EventWriter<AppError>,
+) {
+    if !window.is_open {
+        return;
+    }
+    let TerrainWindow {
+        is_open,
+        path,
+        source,
+        size,
+        height_scale,
+        resolution,
+        splat,
+        snow_line,
+        rock_slope,
+        dirty,
+    } = &mut *window;
+
+    egui::Window::new("Terrain")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<TerrainWindow>(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Heightmap");
+                ui.text_edit_singleline(path)
+                    .on_hover_text("A grayscale PNG; brighter is higher");
+                if ui.button("Import").clicked() {
+                    match HeightField::read_png(path) {
+                        Ok(field) => {
+                            *source = Some(field);
+                            *dirty = true;
+                        }
+                        Err(err) => {
+                            errors.send(
+                                AppError::new("Terrain", format!("Failed to read {path}: {err}"))
+                                    .suggest("The path is relative to the working directory."),
+                            );
+                        }
+                    }
+                }
+                if ui
+                    .button("Sample")
+                    .on_hover_text("Generate rolling hills instead")
+                    .clicked()
+                {
+                    *source = Some(HeightField::sample());
+                    *dirty = true;
+                }
+            });
+            if let Some(field) = source {
+                ui.weak(format!("{} × {} samples", field.size.x, field.size.y));
+            }
+
+            ui.separator();
+            let changed = egui::Grid::new("terrain_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Size");
+                    let mut changed = ui
+                        .add(
+                            egui::Slider::new(size, 2.0..=400.0)
+                                .logarithmic(true)
+                                .suffix(" m"),
+                        )
+                        .changed();
+                    ui.end_row();
+                    ui.label("Height scale");
+                    changed |= ui
+                        .add(egui::Slider::new(height_scale, 0.0..=50.0).suffix(" m"))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Resolution");
+                    changed |= ui.add(egui::Slider::new(resolution, 8..=512)).changed();
+                    ui.end_row();
+                    ui.label("Splatting");
+                    changed |= ui.checkbox(splat, "Colour by slope and height").changed();
+                    ui.end_row();
+                    ui.label("Snow line");
+                    changed |= ui
+                        .add_enabled(*splat, egui::Slider::new(snow_line, 0.0..=1.0))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Rock slope");
+                    changed |= ui
+                        .add_enabled(*splat, egui::Slider::new(rock_slope, 0.0..=1.0))
+                        .changed();
+                    ui.end_row();
+                    changed
+                })
+                .inner;
+            *dirty |= changed && source.is_some();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!terrains.is_empty(), egui::Button::new("Remove"))
+                    .clicked()
+                {
+                    for entity in &terrains {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                    *source = None;
+                }
+                ui.weak("Placed just below the lowest cube.");
+            });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn build_terrain_system(
+    mut commands: Commands,
+    mut window: ResMut<TerrainWindow>,
+    mut terrains: Query<
+        (Entity, &mut Handle<Mesh>, &mut Transform),
+        (With<Terrain>, Without<RenderCube>),
+    >,
+    cubes: Query<(&GlobalTransform, &Aabb), With<RenderCube>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !std::mem::take(&mut window.dirty) {
+        return;
+    }
+    let Some(field) = &window.source else {
+        return;
+    };
+    let mesh = meshes.add(window.mesh(field));
+
+    // Centre under the cubes, with the highest peak just below the lowest of them.
+    let mut bottom = f32::INFINITY;
+    let mut center = Vec3::ZERO;
+    for (transform, aabb) in &cubes {
+        let affine = transform.affine();
+        let extent = affine.matrix3.abs() * Vec3::from(aabb.half_extents);
+        let world_center = affine.transform_point3(aabb.center.into());
+        bottom = bottom.min(world_center.y - extent.y);
+        center += world_center;
+    }
+    let count = cubes.iter().len();
+    if count > 0 {
+        center /= count as f32;
+    } else {
+        bottom = 0.0;
+    }
+    let translation = Vec3::new(center.x, bottom - window.height_scale - CLEARANCE, center.z);
+
+    if let Ok((entity, mut handle, mut transform)) = terrains.get_single_mut() {
+        *handle = mesh;
+        transform.translation = translation;
+        // Recomputed from the new mesh next frame.
+        commands.entity(entity).remove::<Aabb>();
+        return;
+    }
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            // The colours come from the vertices, which multiply this.
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                perceptual_roughness: 0.95,
+                ..default()
+            }),
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        Terrain,
+        Name::new("Terrain"),
+    ));
+}
@@ -0,0 +1,411 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    panels::{Menu, MenuItem, Panel, PanelContexts, RegisterPanelExt},
+    scene::{self, CustomMesh},
+    selection::Selection,
+    RenderCube, Static, ViewportCamera,
+};
+
+/// Size glyphs are rasterised at. Extruded text is built from the coverage pixels, so this
+/// sets how fine the outlines are.
+const FONT_POINTS: f32 = 32.0;
+
+/// The 3D Text window: spawns text as an extruded mesh or a camera-facing billboard, built
+/// from egui's own font atlas. Text entities are ordinary scene entities otherwise.
+pub struct Text3dPlugin;
+
+impl Plugin for Text3dPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<Text3dWindow>()
+            .add_systems(
+                Update,
+                (
+                    text3d_window_system.in_set(Text3dWindow::ui_set()),
+                    build_text_meshes_system,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                face_camera_system.before(TransformSystem::TransformPropagate),
+            )
+            .add_menu_item(MenuItem::new(Menu::Edit, "Add 3D Text…", |world| {
+                world.resource_mut::<Text3dWindow>().is_open = true;
+            }));
+    }
+}
+
+/// The source of a text entity's mesh; it is rebuilt whenever this changes.
+#[derive(Component, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Text3d {
+    pub text: String,
+    pub monospace: bool,
+    /// World height of one line.
+    pub height: f32,
+    /// Extrusion depth; ignored by billboards.
+    pub depth: f32,
+    pub billboard: bool,
+}
+
+impl Default for Text3d {
+    fn default() -> Self {
+        Self {
+            text: "Hello".to_owned(),
+            monospace: false,
+            height: 1.0,
+            depth: 0.2,
+            billboard: false,
+        }
+    }
+}
+
+/// Turned to face the viewport camera every frame.
+#[derive(Component)]
+pub struct Billboard;
+
+#[derive(Default, Resource)]
+pub struct Text3dWindow {
+    pub is_open: bool,
+    template: Text3d,
+}
+
+impl Panel for Text3dWindow {
+    const TITLE: &'static str = "3D Text";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+fn text3d_ui(ui: &mut egui::Ui, text: &mut Text3d) {
+    ui.text_edit_singleline(&mut text.text);
+    ui.horizontal(|ui| {
+        ui.label("Font");
+        ui.selectable_value(&mut text.monospace, false, "Proportional");
+        ui.selectable_value(&mut text.monospace, true, "Monospace");
+    });
+    ui.add(
+        egui::Slider::new(&mut text.height, 0.1..=10.0)
+            .text("Height")
+            .logarithmic(true),
+    );
+    ui.add_enabled(
+        !text.billboard,
+        egui::Slider::new(&mut text.depth, 0.0..=2.0).text("Depth"),
+    );
+    ui.checkbox(&mut text.billboard, "Billboard (always faces the camera)");
+}
+
+#[allow(clippy::too_many_arguments)]
+fn text3d_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<Text3dWindow>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut selection: ResMut<Selection>,
+    mut texts: Query<&mut Text3d>,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    let Text3dWindow { is_open, template } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("3D Text")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<Text3dWindow>(), |ui| {
+            text3d_ui(ui, template);
+            if ui.button("Spawn").clicked() && !template.text.trim().is_empty() {
+                // In front of the camera, upright.
+                let translation = cameras.get_single().map_or(Vec3::ZERO, |camera| {
+                    camera.translation() + camera.forward() * 10.0
+                });
+                let entity = scene::spawn_cube(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    Transform::from_translation(translation),
+                    Color::WHITE,
+                );
+                commands.entity(entity).insert((
+                    template.clone(),
+                    Static,
+                    Name::new(template.text.clone()),
+                ));
+                selection.select(entity);
+            }
+
+            let Some(mut selected) = selection.primary().and_then(|e| texts.get_mut(e).ok()) else {
+                return;
+            };
+            ui.separator();
+            ui.strong("Selected text");
+            let mut edited = selected.clone();
+            text3d_ui(ui, &mut edited);
+            if edited != *selected {
+                *selected = edited;
+            }
+        });
+}
+
+/// Lays `text` out with egui and copies each glyph's coverage out of the font atlas into one
+/// bitmap. Returns its size and row-major coverage, or `None` when nothing is visible.
+fn rasterize(ctx: &egui::Context, text: &Text3d) -> Option<(UVec2, Vec<f32>, f32)> {
+    let family = if text.monospace {
+        egui::FontFamily::Monospace
+    } else {
+        egui::FontFamily::Proportional
+    };
+    ctx.fonts(|fonts| {
+        let galley = fonts.layout_no_wrap(
+            text.text.clone(),
+            egui::FontId::new(FONT_POINTS, family),
+            egui::Color32::WHITE,
+        );
+        let atlas = fonts.image();
+        let ppp = fonts.pixels_per_point();
+
+        let glyphs: Vec<(IVec2, [u16; 2], [u16; 2])> = galley
+            .rows
+            .iter()
+            .flat_map(|row| &row.glyphs)
+            .filter(|glyph| !glyph.uv_rect.is_nothing())
+            .map(|glyph| {
+                let left_top = (glyph.pos + glyph.uv_rect.offset) * ppp;
+                let left_top = IVec2::new(left_top.x.round() as i32, left_top.y.round() as i32);
+                (left_top, glyph.uv_rect.min, glyph.uv_rect.max)
+            })
+            .collect();
+        let (mut min, mut max) = (IVec2::MAX, IVec2::MIN);
+        for (left_top, uv_min, uv_max) in &glyphs {
+            let size = IVec2::new(
+                (uv_max[0] - uv_min[0]) as i32,
+                (uv_max[1] - uv_min[1]) as i32,
+            );
+            min = min.min(*left_top);
+            max = max.max(*left_top + size);
+        }
+        if glyphs.is_empty() || max.cmple(min).any() {
+            return None;
+        }
+
+        let size = (max - min).as_uvec2();
+        let mut coverage = vec![0.0f32; (size.x * size.y) as usize];
+        for (left_top, uv_min, uv_max) in glyphs {
+            let origin = (left_top - min).as_uvec2();
+            for v in uv_min[1]..uv_max[1] {
+                for u in uv_min[0]..uv_max[0] {
+                    let source = atlas.pixels[v as usize * atlas.size[0] + u as usize];
+                    let x = origin.x + (u - uv_min[0]) as u32;
+                    let y = origin.y + (v - uv_min[1]) as u32;
+                    let target = &mut coverage[(y * size.x + x) as usize];
+                    *target = target.max(source);
+                }
+            }
+        }
+        // World units per bitmap pixel, so a line is `height` tall.
+        let pixel = text.height / (galley.rect.height() * ppp).max(1.0);
+        Some((size, coverage, pixel))
+    })
+}
+
+/// Extrudes the covered pixels into a closed mesh: merged runs for the front and back, and a
+/// wall wherever a covered pixel borders an empty one. Centred on the origin.
+fn extruded_mesh(size: UVec2, coverage: &[f32], pixel: f32, depth: f32) -> Mesh {
+    let filled = |x: i32, y: i32| {
+        x >= 0
+            && y >= 0
+            && x < size.x as i32
+            && y < size.y as i32
+            && coverage[(y as u32 * size.x + x as u32) as usize] >= 0.5
+    };
+    let half = size.as_vec2() * 0.5;
+    let point = |x: i32, y: i32, z: f32| {
+        Vec3::new(
+            (x as f32 - half.x) * pixel,
+            (half.y - y as f32) * pixel,
+            z * depth * 0.5,
+        )
+    };
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    // Adds a quad, flipping its winding if needed so it faces along `normal`.
+    let mut quad = |corners: [Vec3; 4], normal: Vec3| {
+        let start = positions.len() as u32;
+        positions.extend(corners.map(|corner| corner.to_array()));
+        normals.extend([normal.to_array(); 4]);
+        let facing = (corners[1] - corners[0])
+            .cross(corners[2] - corners[0])
+            .dot(normal);
+        let order = if facing >= 0.0 {
+            [0, 1, 2, 0, 2, 3]
+        } else {
+            [0, 2, 1, 0, 3, 2]
+        };
+        indices.extend(order.map(|i| start + i));
+    };
+
+    for y in 0..size.y as i32 {
+        let mut x = 0;
+        while x < size.x as i32 {
+            if !filled(x, y) {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while filled(x, y) {
+                x += 1;
+            }
+            for z in [1.0, -1.0] {
+                quad(
+                    [
+                        point(start, y + 1, z),
+                        point(x, y + 1, z),
+                        point(x, y, z),
+                        point(start, y, z),
+                    ],
+                    Vec3::Z * z,
+                );
+            }
+        }
+        for x in 0..size.x as i32 {
+            if !filled(x, y) {
+                continue;
+            }
+            let walls = [
+                (x - 1, y, Vec3::NEG_X, [(x, y), (x, y + 1)]),
+                (x + 1, y, Vec3::X, [(x + 1, y), (x + 1, y + 1)]),
+                (x, y - 1, Vec3::Y, [(x, y), (x + 1, y)]),
+                (x, y + 1, Vec3::NEG_Y, [(x, y + 1), (x + 1, y + 1)]),
+            ];
+            for (nx, ny, normal, [(ax, ay), (bx, by)]) in walls {
+                if !filled(nx, ny) {
+                    quad(
+                        [
+                            point(ax, ay, -1.0),
+                            point(bx, by, -1.0),
+                            point(bx, by, 1.0),
+                            point(ax, ay, 1.0),
+                        ],
+                        normal,
+                    );
+                }
+            }
+        }
+    }
+
+    let uvs = vec![[0.0, 0.0]; positions.len()];
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// White text whose alpha is the glyph coverage, for billboards.
+fn coverage_image(size: UVec2, coverage: &[f32]) -> Image {
+    let data = coverage
+        .iter()
+        .flat_map(|alpha| [255, 255, 255, (alpha * 255.0) as u8])
+        .collect();
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn build_text_meshes_system(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    mut texts: Query<
+        (
+            Entity,
+            &Text3d,
+            &mut Handle<Mesh>,
+            &Handle<StandardMaterial>,
+        ),
+        Changed<Text3d>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if texts.is_empty() {
+        return;
+    }
+    let ctx = contexts.ctx_mut().clone();
+    for (entity, text, mut mesh, material) in &mut texts {
+        let Some((size, coverage, pixel)) = rasterize(&ctx, text) else {
+            continue;
+        };
+        let material = materials.get_mut(material);
+        if text.billboard {
+            *mesh = meshes.add(Rectangle::new(size.x as f32 * pixel, size.y as f32 * pixel));
+            if let Some(material) = material {
+                material.base_color_texture = Some(images.add(coverage_image(size, &coverage)));
+                material.alpha_mode = AlphaMode::Blend;
+                material.double_sided = true;
+                material.cull_mode = None;
+            }
+            commands.entity(entity).insert((Billboard, CustomMesh));
+        } else {
+            *mesh = meshes.add(extruded_mesh(size, &coverage, pixel, text.depth));
+            if let Some(material) = material {
+                material.base_color_texture = None;
+                material.alpha_mode = AlphaMode::Opaque;
+            }
+            commands
+                .entity(entity)
+                .insert(CustomMesh)
+                .remove::<Billboard>();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn face_camera_system(
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+    parents: Query<&GlobalTransform>,
+    mut billboards: Query<(&mut Transform, Option<&Parent>), (With<Billboard>, With<RenderCube>)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let (_, camera_rotation, _) = camera.to_scale_rotation_translation();
+    for (mut transform, parent) in &mut billboards {
+        let parent_rotation = parent
+            .and_then(|parent| parents.get(parent.get()).ok())
+            .map_or(Quat::IDENTITY, |parent| {
+                parent.to_scale_rotation_translation().1
+            });
+        let rotation = parent_rotation.inverse() * camera_rotation;
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
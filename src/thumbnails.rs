@@ -0,0 +1,306 @@
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        primitives::Aabb,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+    transform::TransformSystem,
+};
+use bevy_egui::{egui, EguiUserTextures};
+
+use crate::{
+    camera::{frame_bounds, world_bounds},
+    groups::Group,
+    hierarchy::entity_label,
+    ViewportCamera,
+};
+
+/// Layer the thumbnail camera, light and stand-ins render on, apart from the scene.
+const THUMBNAIL_LAYER: usize = 29;
+const THUMBNAIL_SIZE: u32 = 128;
+/// Frames a new subject renders before its thumbnail is shown, so the first is not blank.
+const WARM_UP_FRAMES: u8 = 2;
+
+/// Renders a thumbnail of one entity on request, for tooltips that identify objects without
+/// selecting them. Stand-ins sharing the entity's meshes and materials are drawn on a layer of
+/// their own by a camera that is only active while a thumbnail is wanted.
+pub struct ThumbnailsPlugin;
+
+impl Plugin for ThumbnailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_thumbnails_system)
+            .add_systems(
+                PostUpdate,
+                render_thumbnail_system.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct Thumbnails {
+    camera: Entity,
+    texture: egui::TextureId,
+    /// Asked for since the last render; cleared every frame.
+    wanted: Option<Entity>,
+    /// The entity the stand-ins currently show.
+    subject: Option<Entity>,
+    /// `(source, stand-in)` for each mesh of the subject and its descendants.
+    stand_ins: Vec<(Entity, Entity)>,
+    frames: u8,
+}
+
+impl Thumbnails {
+    /// The thumbnail of `entity`, once rendered. Call it every frame the thumbnail is shown;
+    /// the camera stops when nothing asks.
+    pub fn request(&mut self, entity: Entity) -> Option<egui::TextureId> {
+        self.wanted = Some(entity);
+        (self.subject == Some(entity)
+            && self.frames >= WARM_UP_FRAMES
+            && !self.stand_ins.is_empty())
+        .then_some(self.texture)
+    }
+}
+
+fn setup_thumbnails_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+) {
+    let size = Extent3d {
+        width: THUMBNAIL_SIZE,
+        height: THUMBNAIL_SIZE,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("thumbnail"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+    let texture = user_textures.add_image(image.clone());
+
+    let camera = commands
+        .spawn((
+            Camera3dBundle {
+                camera: Camera {
+                    target: RenderTarget::Image(image),
+                    clear_color: ClearColorConfig::Custom(Color::srgb(0.12, 0.12, 0.13)),
+                    is_active: false,
+                    ..default()
+                },
+                ..default()
+            },
+            RenderLayers::layer(THUMBNAIL_LAYER),
+        ))
+        .id();
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: 6_000.0,
+                ..default()
+            },
+            transform: Transform::from_xyz(1.0, 2.0, 1.5).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+        RenderLayers::layer(THUMBNAIL_LAYER),
+    ));
+    commands.insert_resource(Thumbnails {
+        camera,
+        texture,
+        wanted: None,
+        subject: None,
+        stand_ins: Vec::new(),
+        frames: 0,
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn render_thumbnail_system(
+    mut commands: Commands,
+    mut thumbnails: ResMut<Thumbnails>,
+    sources: Query<(
+        &GlobalTransform,
+        Option<&Handle<Mesh>>,
+        Option<&Handle<StandardMaterial>>,
+        Option<&Aabb>,
+        Option<&Children>,
+    )>,
+    mut cameras: Query<(&mut Camera, &mut Transform, &Projection)>,
+    mut stand_ins: Query<&mut Transform, Without<Camera>>,
+) {
+    let wanted = thumbnails.wanted.take();
+    if wanted != thumbnails.subject {
+        for (_, stand_in) in thumbnails.stand_ins.drain(..) {
+            commands.entity(stand_in).despawn();
+        }
+        thumbnails.subject = wanted;
+        thumbnails.frames = 0;
+        let mut pending: Vec<Entity> = wanted.into_iter().collect();
+        while let Some(entity) = pending.pop() {
+            let Ok((transform, mesh, material, _, children)) = sources.get(entity) else {
+                continue;
+            };
+            pending.extend(children.into_iter().flatten());
+            let (Some(mesh), Some(material)) = (mesh, material) else {
+                continue;
+            };
+            let stand_in = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material: material.clone(),
+                        transform: transform.compute_transform(),
+                        ..default()
+                    },
+                    RenderLayers::layer(THUMBNAIL_LAYER),
+                ))
+                .id();
+            thumbnails.stand_ins.push((entity, stand_in));
+        }
+    }
+    let Ok((mut camera, mut camera_transform, projection)) = cameras.get_mut(thumbnails.camera)
+    else {
+        return;
+    };
+    let active = !thumbnails.stand_ins.is_empty();
+    if camera.is_active != active {
+        camera.is_active = active;
+    }
+    if !active {
+        return;
+    }
+
+    // Follow the sources, which may be animated, and frame all of them.
+    let mut bounds: Option<(Vec3, Vec3)> = None;
+    for (source, stand_in) in &thumbnails.stand_ins {
+        let Ok((transform, _, _, aabb, _)) = sources.get(*source) else {
+            continue;
+        };
+        if let Ok(mut stand_in) = stand_ins.get_mut(*stand_in) {
+            *stand_in = transform.compute_transform();
+        }
+        let (min, max) = match aabb {
+            Some(aabb) => world_bounds(transform, aabb),
+            None => (transform.translation(), transform.translation()),
+        };
+        bounds = Some(match bounds {
+            Some((low, high)) => (low.min(min), high.max(max)),
+            None => (min, max),
+        });
+    }
+    if let Some((min, max)) = bounds {
+        let center = (min + max) * 0.5;
+        *camera_transform = Transform::from_translation(center + Vec3::new(1.0, 0.7, 1.2))
+            .looking_at(center, Vec3::Y);
+        frame_bounds(&mut camera_transform, projection, min, max);
+    }
+    thumbnails.frames = thumbnails.frames.saturating_add(1);
+}
+
+/// What a tooltip shows about an entity: a thumbnail, its transform and its material.
+#[derive(SystemParam)]
+pub struct EntityTooltips<'w, 's> {
+    thumbnails: ResMut<'w, Thumbnails>,
+    #[allow(clippy::type_complexity)]
+    entities: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            Option<&'static Name>,
+            Has<Group>,
+            Option<&'static Handle<StandardMaterial>>,
+            Option<&'static Children>,
+        ),
+        Without<ViewportCamera>,
+    >,
+    materials: Res<'w, Assets<StandardMaterial>>,
+}
+
+impl EntityTooltips<'_, '_> {
+    /// Shows `entity`'s tooltip while `response` is hovered.
+    pub fn on_hover(&mut self, response: egui::Response, entity: Entity) -> egui::Response {
+        response.on_hover_ui(|ui| self.ui(ui, entity))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, entity: Entity) {
+        let Ok((transform, name, is_group, material, children)) = self.entities.get(entity) else {
+            ui.weak("This entity no longer exists.");
+            return;
+        };
+        ui.strong(entity_label(entity, name, is_group));
+        let size = egui::Vec2::splat(THUMBNAIL_SIZE as f32);
+        match self.thumbnails.request(entity) {
+            Some(texture) => {
+                ui.image(egui::load::SizedTexture::new(texture, size));
+            }
+            None => {
+                ui.allocate_ui(size, |ui| {
+                    ui.centered_and_justified(|ui| ui.spinner());
+                });
+                ui.ctx().request_repaint();
+            }
+        }
+
+        let (x, y, z) = transform.rotation.to_euler(EulerRot::XYZ);
+        egui::Grid::new("entity_tooltip")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let vec3 = |value: Vec3| format!("{:.2}, {:.2}, {:.2}", value.x, value.y, value.z);
+                ui.weak("Position");
+                ui.label(vec3(transform.translation));
+                ui.end_row();
+                ui.weak("Rotation");
+                ui.label(format!(
+                    "{:.0}°, {:.0}°, {:.0}°",
+                    x.to_degrees(),
+                    y.to_degrees(),
+                    z.to_degrees()
+                ));
+                ui.end_row();
+                ui.weak("Scale");
+                ui.label(vec3(transform.scale));
+                ui.end_row();
+                if let Some(material) = material.and_then(|handle| self.materials.get(handle)) {
+                    ui.weak("Material");
+                    ui.horizontal(|ui| {
+                        let [r, g, b, a] = material.base_color.to_srgba().to_u8_array();
+                        let (swatch, _) =
+                            ui.allocate_exact_size(egui::vec2(24.0, 14.0), egui::Sense::hover());
+                        ui.painter().rect(
+                            swatch,
+                            2.0,
+                            egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+                            ui.visuals().widgets.noninteractive.bg_stroke,
+                        );
+                        ui.label(format!(
+                            "metallic {:.2}, roughness {:.2}",
+                            material.metallic, material.perceptual_roughness
+                        ));
+                    });
+                    ui.end_row();
+                }
+                if is_group {
+                    ui.weak("Members");
+                    ui.label(children.map_or(0, |children| children.len()).to_string());
+                    ui.end_row();
+                }
+            });
+    }
+}
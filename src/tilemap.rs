@@ -0,0 +1,448 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        texture::ImageSampler,
+        view::NoFrustumCulling,
+    },
+};
+use bevy_egui::{egui, EguiUserTextures};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{InputOwner, InputRouting},
+    numeric::drag_value,
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    picking::Picking,
+    scene::Project,
+    sprites::SandboxMode,
+    viewport::{Viewport, ViewportTool},
+};
+
+/// Just behind sprites on the XY plane, so they draw over the tiles.
+const LAYER_DEPTH: f32 = -0.01;
+/// Grid lines are only drawn while fewer cells than this are in view along each axis.
+const MAX_GRID_LINES: i32 = 200;
+/// Palette buttons shown at most; larger tilesets are cut off.
+const MAX_PALETTE_TILES: u32 = 1024;
+
+/// Tile painting for the 2D mode: pick a tileset image and tile size, then paint or erase
+/// tiles on a grid over the XY plane. The layer is saved in the scene file and drawn as one
+/// mesh.
+pub struct TilemapPlugin;
+
+impl Plugin for TilemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<TilemapWindow>().add_systems(
+            Update,
+            (
+                tilemap_window_system,
+                paint_tiles_system.after(crate::UiSet::Central),
+                build_tile_layer_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The painted tiles of the scene.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TileLayer {
+    /// Asset path of the tileset image, empty until one is chosen.
+    pub tileset: String,
+    /// Size of one tile in the tileset, in pixels.
+    pub tile_size: [u32; 2],
+    /// World size of one grid cell.
+    pub cell_size: f32,
+    /// Painted cells as `(column, row, tile)`; tiles count row by row from the tileset's top
+    /// left.
+    pub tiles: Vec<(i32, i32, u32)>,
+}
+
+impl Default for TileLayer {
+    fn default() -> Self {
+        Self {
+            tileset: String::new(),
+            tile_size: [16, 16],
+            cell_size: 1.0,
+            tiles: Vec::new(),
+        }
+    }
+}
+
+impl TileLayer {
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    fn tile_at(&self, cell: IVec2) -> Option<u32> {
+        self.tiles
+            .iter()
+            .find(|(x, y, _)| IVec2::new(*x, *y) == cell)
+            .map(|(.., tile)| *tile)
+    }
+
+    /// Sets or, with `None`, erases one cell.
+    fn set(&mut self, cell: IVec2, tile: Option<u32>) {
+        let index = self
+            .tiles
+            .iter()
+            .position(|(x, y, _)| IVec2::new(*x, *y) == cell);
+        match (index, tile) {
+            (Some(index), Some(tile)) => self.tiles[index].2 = tile,
+            (Some(index), None) => {
+                self.tiles.swap_remove(index);
+            }
+            (None, Some(tile)) => self.tiles.push((cell.x, cell.y, tile)),
+            (None, None) => {}
+        }
+    }
+
+    fn cell_at(&self, point: Vec3) -> IVec2 {
+        (point.truncate() / self.cell_size.max(f32::EPSILON))
+            .floor()
+            .as_ivec2()
+    }
+
+    /// Tileset columns and rows for an image of `size` pixels.
+    fn grid(&self, size: UVec2) -> UVec2 {
+        size / UVec2::from(self.tile_size).max(UVec2::ONE)
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct TilemapWindow {
+    pub is_open: bool,
+    /// The tile painted next.
+    brush: u32,
+    erasing: bool,
+    /// The entity drawing the layer, once tiles exist.
+    layer: Option<Entity>,
+    /// What `layer` was last built from, to rebuild only after edits.
+    built: Option<TileLayer>,
+}
+
+impl Panel for TilemapWindow {
+    const TITLE: &'static str = "Tilemap";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tilemap_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<TilemapWindow>,
+    mut tool: ResMut<ViewportTool>,
+    mut mode: ResMut<SandboxMode>,
+    mut project: ResMut<Project>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+) {
+    let TilemapWindow {
+        is_open,
+        brush,
+        erasing,
+        ..
+    } = &mut *window;
+    if *tool == ViewportTool::PaintTiles && (!*is_open || *mode != SandboxMode::Sprites2d) {
+        *tool = ViewportTool::Select;
+    }
+    if !*is_open {
+        return;
+    }
+
+    // Edit a copy so drawing the window does not count as a change to the project.
+    let mut layer = project.tile_layer.clone();
+    let tileset =
+        (!layer.tileset.is_empty()).then(|| asset_server.load::<Image>(layer.tileset.clone()));
+    let tileset_size = tileset
+        .as_ref()
+        .and_then(|handle| images.get(handle))
+        .map(Image::size);
+
+    egui::Window::new(TilemapWindow::TITLE)
+        .open(is_open)
+        .default_width(300.0)
+        .show(contexts.ctx::<TilemapWindow>(), |ui| {
+            let mut painting = *tool == ViewportTool::PaintTiles;
+            if ui
+                .toggle_value(&mut painting, "🖌 Paint tiles")
+                .on_hover_text(
+                    "Drag in the viewport to paint; the right button or erase mode removes",
+                )
+                .changed()
+            {
+                *tool = if painting {
+                    *mode = SandboxMode::Sprites2d;
+                    ViewportTool::PaintTiles
+                } else {
+                    ViewportTool::Select
+                };
+            }
+            egui::Grid::new("tilemap_settings")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Tileset");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut layer.tileset)
+                            .hint_text("path under assets/")
+                            .desired_width(180.0),
+                    );
+                    ui.end_row();
+                    ui.label("Tile size");
+                    ui.horizontal(|ui| {
+                        let [width, height] = &mut layer.tile_size;
+                        ui.add(drag_value(width).range(1..=1024).suffix(" px"));
+                        ui.label("×");
+                        ui.add(drag_value(height).range(1..=1024).suffix(" px"));
+                    });
+                    ui.end_row();
+                    ui.label("Cell size");
+                    ui.add(
+                        drag_value(&mut layer.cell_size)
+                            .speed(0.01)
+                            .range(0.01..=100.0),
+                    );
+                    ui.end_row();
+                });
+
+            ui.separator();
+            match (&tileset, tileset_size) {
+                (Some(handle), Some(size)) => {
+                    let grid = layer.grid(size);
+                    let count = (grid.x * grid.y).min(MAX_PALETTE_TILES);
+                    let texture = user_textures.add_image(handle.clone_weak());
+                    ui.horizontal(|ui| {
+                        ui.toggle_value(erasing, "⌫ Erase");
+                        ui.weak(format!("{count} tiles, {} painted", layer.tiles.len()));
+                    });
+                    egui::ScrollArea::vertical()
+                        .max_height(220.0)
+                        .show(ui, |ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+                                for tile in 0..count {
+                                    let cell =
+                                        UVec2::new(tile % grid.x.max(1), tile / grid.x.max(1));
+                                    let tile_size = UVec2::from(layer.tile_size).as_vec2();
+                                    let min = cell.as_vec2() * tile_size / size.as_vec2();
+                                    let max = (cell.as_vec2() + 1.0) * tile_size / size.as_vec2();
+                                    let image = egui::Image::new(egui::load::SizedTexture::new(
+                                        texture,
+                                        [28.0, 28.0],
+                                    ))
+                                    .uv(
+                                        egui::Rect::from_min_max(
+                                            egui::pos2(min.x, min.y),
+                                            egui::pos2(max.x, max.y),
+                                        ),
+                                    );
+                                    let selected = *brush == tile && !*erasing;
+                                    if ui
+                                        .add(egui::ImageButton::new(image).selected(selected))
+                                        .on_hover_text(format!("Tile {tile}"))
+                                        .clicked()
+                                    {
+                                        *brush = tile;
+                                        *erasing = false;
+                                    }
+                                }
+                            });
+                        });
+                }
+                (Some(_), None) => {
+                    ui.weak("Loading the tileset…");
+                }
+                (None, _) => {
+                    ui.weak("Enter the path of a tileset image, e.g. icon.png.");
+                }
+            }
+
+            ui.separator();
+            if ui
+                .add_enabled(!layer.is_empty(), egui::Button::new("Clear layer"))
+                .clicked()
+            {
+                layer.tiles.clear();
+            }
+        });
+
+    if layer != project.tile_layer {
+        project.tile_layer = layer;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn paint_tiles_system(
+    tool: Res<ViewportTool>,
+    routing: Res<InputRouting>,
+    viewport: Res<Viewport>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window: Res<TilemapWindow>,
+    mut project: ResMut<Project>,
+    picking: Picking,
+    mut gizmos: Gizmos,
+) {
+    if *tool != ViewportTool::PaintTiles {
+        return;
+    }
+    let layer = &project.tile_layer;
+    let cell_size = layer.cell_size.max(f32::EPSILON);
+    let on_plane = |ray: Ray3d| {
+        ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z))
+            .map(|distance| ray.get_point(distance))
+    };
+
+    // The grid over the visible part of the plane.
+    let corners = [Vec2::ZERO, viewport.image_size.as_vec2()]
+        .map(|pixel| picking.ray_through(pixel).and_then(on_plane));
+    if let [Some(a), Some(b)] = corners {
+        let (min, max) = (layer.cell_at(a.min(b)), layer.cell_at(a.max(b)) + 1);
+        let span = max - min;
+        if span.x < MAX_GRID_LINES && span.y < MAX_GRID_LINES {
+            let color = Color::srgba(1.0, 1.0, 1.0, 0.12);
+            let (low, high) = (min.as_vec2() * cell_size, max.as_vec2() * cell_size);
+            for x in min.x..=max.x {
+                let x = x as f32 * cell_size;
+                gizmos.line(Vec3::new(x, low.y, 0.0), Vec3::new(x, high.y, 0.0), color);
+            }
+            for y in min.y..=max.y {
+                let y = y as f32 * cell_size;
+                gizmos.line(Vec3::new(low.x, y, 0.0), Vec3::new(high.x, y, 0.0), color);
+            }
+        }
+    }
+
+    if routing.pointer != InputOwner::Tool {
+        return;
+    }
+    let Some(point) = picking.pointer_ray().and_then(on_plane) else {
+        return;
+    };
+    let cell = layer.cell_at(point);
+    let erase = window.erasing || mouse.pressed(MouseButton::Right);
+    let center = (cell.as_vec2() + 0.5) * cell_size;
+    gizmos.rect(
+        center.extend(0.0),
+        Quat::IDENTITY,
+        Vec2::splat(cell_size),
+        if erase {
+            Color::srgb(1.0, 0.3, 0.3)
+        } else {
+            Color::srgb(1.0, 0.9, 0.2)
+        },
+    );
+
+    if layer.tileset.is_empty() || !mouse.any_pressed([MouseButton::Left, MouseButton::Right]) {
+        return;
+    }
+    let tile = (!erase).then_some(window.brush);
+    if layer.tile_at(cell) != tile {
+        project.tile_layer.set(cell, tile);
+    }
+}
+
+fn build_tile_layer_system(
+    mut window: ResMut<TilemapWindow>,
+    mut commands: Commands,
+    project: Res<Project>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let layer = &project.tile_layer;
+    if window.built.as_ref() == Some(layer) {
+        return;
+    }
+    if layer.is_empty() || layer.tileset.is_empty() {
+        if let Some(entity) = window.layer.take() {
+            commands.entity(entity).despawn();
+        }
+        window.built = Some(layer.clone());
+        return;
+    }
+    let tileset: Handle<Image> = asset_server.load(layer.tileset.clone());
+    // Wait for the tileset; its size decides the UVs.
+    let Some(image) = images.get_mut(&tileset) else {
+        return;
+    };
+    if matches!(image.sampler, ImageSampler::Default) {
+        // Tiles are usually pixel art, and linear filtering bleeds across their edges.
+        image.sampler = ImageSampler::nearest();
+    }
+    let size = image.size().as_vec2();
+    let grid = layer.grid(image.size()).max(UVec2::ONE);
+    let tile_uv = UVec2::from(layer.tile_size).as_vec2() / size;
+
+    let mut positions = Vec::with_capacity(layer.tiles.len() * 4);
+    let mut uvs = Vec::with_capacity(layer.tiles.len() * 4);
+    let mut indices = Vec::with_capacity(layer.tiles.len() * 6);
+    for &(x, y, tile) in &layer.tiles {
+        let min = Vec2::new(x as f32, y as f32) * layer.cell_size;
+        let max = min + layer.cell_size;
+        let uv_min = UVec2::new(tile % grid.x, tile / grid.x).as_vec2() * tile_uv;
+        let uv_max = uv_min + tile_uv;
+        let base = positions.len() as u32;
+        positions.extend([
+            [min.x, min.y, LAYER_DEPTH],
+            [max.x, min.y, LAYER_DEPTH],
+            [max.x, max.y, LAYER_DEPTH],
+            [min.x, max.y, LAYER_DEPTH],
+        ]);
+        // Image rows run downwards, world rows upwards.
+        uvs.extend([
+            [uv_min.x, uv_max.y],
+            [uv_max.x, uv_max.y],
+            [uv_max.x, uv_min.y],
+            [uv_min.x, uv_min.y],
+        ]);
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices));
+
+    let mesh = meshes.add(mesh);
+    let material = materials.add(StandardMaterial {
+        base_color_texture: Some(tileset),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    match window.layer {
+        Some(entity) => {
+            commands.entity(entity).insert((mesh, material));
+        }
+        None => {
+            // Without frustum culling the layer gets no `Aabb`, which also keeps it out of
+            // picking, so clicks fall through to the sprites and cubes.
+            let entity = commands
+                .spawn((
+                    PbrBundle {
+                        mesh,
+                        material,
+                        ..default()
+                    },
+                    NoFrustumCulling,
+                    Name::new("Tile layer"),
+                ))
+                .id();
+            window.layer = Some(entity);
+        }
+    }
+    window.built = Some(layer.clone());
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    icons::{Icon, IconButtonsExt},
+    keybindings::Action,
+    numeric::drag_value,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    UiSet,
+};
+
+/// Owns the animation clock and the timeline panel used to scrub it.
+pub struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnimationTime>()
+            .init_resource::<TimelineMarkers>()
+            .add_systems(Update, advance_animation_time_system)
+            .add_systems(
+                Update,
+                timeline_panel_system
+                    .run_if(crate::overlay::overlay_off)
+                    .in_set(UiSet::Panels),
+            )
+            .add_menu_item(
+                MenuItem::new(Menu::View, "Play/Pause Animation", |world| {
+                    let mut animation_time = world.resource_mut::<AnimationTime>();
+                    animation_time.playing = !animation_time.playing;
+                })
+                .icon(Icon::PlayPause)
+                .shortcut(Action::TogglePlayback)
+                .in_toolbar(),
+            );
+    }
+}
+
+/// The time every animated system samples from. Unlike `Time`, it can be paused, scaled,
+/// rewound and scrubbed, so animation state must be a pure function of `seconds`.
+#[derive(Resource)]
+pub struct AnimationTime {
+    pub seconds: f32,
+    pub playing: bool,
+    pub speed: f32,
+    pub duration: f32,
+    pub looping: bool,
+}
+
+impl Default for AnimationTime {
+    fn default() -> Self {
+        Self {
+            seconds: 0.0,
+            playing: true,
+            speed: 1.0,
+            duration: 60.0,
+            looping: true,
+        }
+    }
+}
+
+impl AnimationTime {
+    fn set(&mut self, seconds: f32) {
+        self.seconds = if self.looping {
+            seconds.rem_euclid(self.duration)
+        } else {
+            seconds.clamp(0.0, self.duration)
+        };
+    }
+}
+
+/// Keyframe times other systems show on the scrubber, one list per track.
+#[derive(Default, Resource)]
+pub struct TimelineMarkers {
+    tracks: Vec<(&'static str, Vec<f32>)>,
+}
+
+impl TimelineMarkers {
+    /// Replaces the markers shown for `track`.
+    pub fn set(&mut self, track: &'static str, seconds: Vec<f32>) {
+        match self.tracks.iter_mut().find(|(name, _)| *name == track) {
+            Some((_, markers)) => *markers = seconds,
+            None => self.tracks.push((track, seconds)),
+        }
+    }
+}
+
+fn advance_animation_time_system(time: Res<Time>, mut animation_time: ResMut<AnimationTime>) {
+    if !animation_time.playing {
+        return;
+    }
+    let seconds = animation_time.seconds + time.delta_seconds() * animation_time.speed;
+    animation_time.set(seconds);
+    if !animation_time.looping && animation_time.seconds >= animation_time.duration {
+        animation_time.playing = false;
+    }
+}
+
+fn timeline_panel_system(
+    mut contexts: EguiContexts,
+    mut animation_time: ResMut<AnimationTime>,
+    markers: Res<TimelineMarkers>,
+) {
+    const STEP: f32 = 1.0 / 30.0;
+
+    egui::TopBottomPanel::bottom("timeline_panel").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            let time = &mut *animation_time;
+            if ui
+                .icon_button(Icon::Rewind, "")
+                .on_hover_text("Rewind")
+                .clicked()
+            {
+                time.set(0.0);
+            }
+            if ui
+                .icon_button(Icon::StepBack, "")
+                .on_hover_text("Step back")
+                .clicked()
+            {
+                time.set(time.seconds - STEP);
+            }
+            let icon = if time.playing {
+                Icon::Pause
+            } else {
+                Icon::Play
+            };
+            if ui
+                .icon_button(icon, "")
+                .on_hover_text("Play/pause")
+                .clicked()
+            {
+                time.playing = !time.playing;
+            }
+            if ui
+                .icon_button(Icon::StepForward, "")
+                .on_hover_text("Step forward")
+                .clicked()
+            {
+                time.set(time.seconds + STEP);
+            }
+            ui.checkbox(&mut time.looping, "Loop");
+            ui.add(
+                drag_value(&mut time.speed)
+                    .speed(0.05)
+                    .range(-4.0..=4.0)
+                    .prefix("speed ")
+                    .suffix("x"),
+            );
+            ui.add(
+                drag_value(&mut time.duration)
+                    .speed(1.0)
+                    .range(1.0..=3600.0)
+                    .prefix("length ")
+                    .suffix(" s"),
+            );
+
+            let slider_width = (ui.available_width() - 80.0).max(100.0);
+            ui.spacing_mut().slider_width = slider_width;
+            let mut seconds = time.seconds;
+            let scrubber = ui.add(
+                egui::Slider::new(&mut seconds, 0.0..=time.duration)
+                    .suffix(" s")
+                    .fixed_decimals(2),
+            );
+            if scrubber.changed() {
+                time.set(seconds);
+            }
+
+            // Ticks under the rail, inset by the handle radius like the slider's own range.
+            let rail = egui::Rect::from_min_size(
+                scrubber.rect.min,
+                egui::vec2(slider_width, scrubber.rect.height()),
+            );
+            let inset = rail.height() / 2.5;
+            let painter = ui.painter();
+            let color = ui.visuals().warn_fg_color;
+            for (_, track) in &markers.tracks {
+                for marker in track {
+                    let t = (marker / time.duration).clamp(0.0, 1.0);
+                    let x = egui::lerp(rail.left() + inset..=rail.right() - inset, t);
+                    painter.line_segment(
+                        [
+                            egui::pos2(x, rail.bottom() - 4.0),
+                            egui::pos2(x, rail.bottom()),
+                        ],
+                        egui::Stroke::new(2.0, color),
+                    );
+                }
+            }
+        });
+    });
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::icons::Icon;
+use crate::panels::{Menu, MenuItem, RegisterPanelExt};
+
+/// The guided tour: walks through registered UI regions one at a time over a dimmed backdrop.
+pub struct TourPlugin;
+
+impl Plugin for TourPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tour>()
+            .init_resource::<TourAnchors>()
+            .add_systems(Update, tour_system.after(crate::UiSet::Central))
+            .add_menu_item(
+                MenuItem::new(Menu::Help, "Guided Tour", |world| {
+                    world.resource_mut::<Tour>().start();
+                })
+                .icon(Icon::Tour),
+            );
+    }
+}
+
+/// One stop of the tour, pointing at a region published through [`TourAnchors`].
+#[derive(Clone, Copy)]
+pub struct TourStep {
+    pub anchor: &'static str,
+    pub title: &'static str,
+    pub text: &'static str,
+}
+
+impl TourStep {
+    pub const fn new(anchor: &'static str, title: &'static str, text: &'static str) -> Self {
+        Self {
+            anchor,
+            title,
+            text,
+        }
+    }
+}
+
+/// The registered steps in tour order, plus the one currently shown.
+#[derive(Default, Resource)]
+pub struct Tour {
+    steps: Vec<TourStep>,
+    current: Option<usize>,
+}
+
+impl Tour {
+    pub fn start(&mut self) {
+        self.current = (!self.steps.is_empty()).then_some(0);
+    }
+}
+
+/// Screen rects of the regions the tour can point at. UI systems publish them every frame;
+/// a step whose anchor was not drawn this frame is shown without a highlight.
+#[derive(Default, Resource)]
+pub struct TourAnchors(HashMap<&'static str, egui::Rect>);
+
+impl TourAnchors {
+    pub fn set(&mut self, anchor: &'static str, rect: egui::Rect) {
+        self.0.insert(anchor, rect);
+    }
+}
+
+pub trait RegisterTourExt {
+    /// Appends a step; steps are shown in registration order.
+    fn add_tour_step(&mut self, step: TourStep) -> &mut Self;
+}
+
+impl RegisterTourExt for App {
+    fn add_tour_step(&mut self, step: TourStep) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(Tour::default)
+            .steps
+            .push(step);
+        self
+    }
+}
+
+const CALLOUT_WIDTH: f32 = 260.0;
+const CALLOUT_GAP: f32 = 12.0;
+const BACKDROP: egui::Color32 = egui::Color32::from_black_alpha(170);
+
+fn tour_system(
+    mut contexts: EguiContexts,
+    mut tour: ResMut<Tour>,
+    mut anchors: ResMut<TourAnchors>,
+) {
+    let anchors = std::mem::take(&mut anchors.0);
+    let Tour { steps, current } = &mut *tour;
+    let Some(index) = *current else {
+        return;
+    };
+    let Some(step) = steps.get(index) else {
+        *current = None;
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+    let screen = ctx.screen_rect();
+    let highlight = anchors
+        .get(step.anchor)
+        .map(|rect| rect.expand(4.0).intersect(screen));
+
+    // The backdrop swallows clicks so the tour can't be lost behind a stray interaction.
+    egui::Area::new(egui::Id::new("tour_backdrop"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(screen.min)
+        .show(ctx, |ui| {
+            ui.allocate_rect(screen, egui::Sense::click());
+            let painter = ui.painter();
+            match highlight {
+                Some(rect) => {
+                    for band in bands_around(screen, rect) {
+                        painter.rect_filled(band, 0.0, BACKDROP);
+                    }
+                    let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+                    painter.rect_stroke(rect, 4.0, stroke);
+                }
+                None => {
+                    painter.rect_filled(screen, 0.0, BACKDROP);
+                }
+            }
+        });
+
+    let area = egui::Area::new(egui::Id::new("tour_callout"))
+        .order(egui::Order::Tooltip)
+        .constrain(true);
+    let area = match highlight {
+        Some(rect) if rect.right() + CALLOUT_GAP + CALLOUT_WIDTH < screen.right() => {
+            area.fixed_pos(rect.right_top() + egui::vec2(CALLOUT_GAP, 0.0))
+        }
+        Some(rect) if rect.left() - CALLOUT_GAP - CALLOUT_WIDTH > screen.left() => area
+            .pivot(egui::Align2::RIGHT_TOP)
+            .fixed_pos(rect.left_top() - egui::vec2(CALLOUT_GAP, 0.0)),
+        Some(rect) => area.fixed_pos(rect.left_bottom() + egui::vec2(0.0, CALLOUT_GAP)),
+        None => area.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO),
+    };
+
+    let last = index + 1 == steps.len();
+    let mut next = None;
+    area.show(ctx, |ui| {
+        egui::Frame::popup(ui.style()).show(ui, |ui| {
+            ui.set_width(CALLOUT_WIDTH);
+            ui.strong(step.title);
+            ui.label(step.text);
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.weak(format!("Step {} of {}", index + 1, steps.len()));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(if last { "Finish" } else { "Next" }).clicked() {
+                        next = Some((!last).then_some(index + 1));
+                    }
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("Back"))
+                        .clicked()
+                    {
+                        next = Some(Some(index - 1));
+                    }
+                    if !last && ui.button("Skip").clicked() {
+                        next = Some(None);
+                    }
+                });
+            });
+        });
+    });
+
+    if ctx.input(|input| input.key_pressed(egui::Key::Escape)) {
+        next = Some(None);
+    }
+    if let Some(next) = next {
+        *current = next;
+    }
+}
+
+/// The four screen bands around `hole`, so the highlighted region stays undimmed.
+fn bands_around(screen: egui::Rect, hole: egui::Rect) -> [egui::Rect; 4] {
+    [
+        egui::Rect::from_min_max(screen.min, egui::pos2(screen.max.x, hole.min.y)),
+        egui::Rect::from_min_max(egui::pos2(screen.min.x, hole.max.y), screen.max),
+        egui::Rect::from_min_max(
+            egui::pos2(screen.min.x, hole.min.y),
+            egui::pos2(hole.min.x, hole.max.y),
+        ),
+        egui::Rect::from_min_max(
+            egui::pos2(hole.max.x, hole.min.y),
+            egui::pos2(screen.max.x, hole.max.y),
+        ),
+    ]
+}
@@ -0,0 +1,635 @@
+use std::ops::Range;
+
+use bevy::{math::Affine3A, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    constraints::snap_angle,
+    input::{InputOwner, InputRouting},
+    picking::Picking,
+    selection::Selection,
+    sprites::SandboxMode,
+    viewport::{Viewport, ViewportTool},
+    RestRotation, ViewportCamera,
+};
+
+/// Arrow length as a fraction of the distance to the camera, so the gizmo keeps its size.
+const SCREEN_SIZE: f32 = 0.15;
+/// How close the pointer must be to a handle to grab it, in image pixels.
+const GRAB_DISTANCE: f32 = 8.0;
+/// Segments used to hit-test rotation rings.
+const RING_SEGMENTS: usize = 32;
+const AXIS_NAMES: [&str; 3] = ["X", "Y", "Z"];
+
+/// A transform gizmo on the primary selection. Drag an arrow to move or scale along it, a ring
+/// to rotate about its axis, or the centre to move or scale freely; the axes follow
+/// [`GizmoSpace`]. In 2D mode only the in-plane arrows and the Z ring are offered. While dragging, typing a number applies an exact amount, confirmed with
+/// Enter and cancelled with Escape.
+pub struct TransformGizmoPlugin;
+
+impl Plugin for TransformGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GizmoSpace>()
+            .init_resource::<GizmoMode>()
+            .init_resource::<GizmoDrag>()
+            .add_systems(
+                Update,
+                (drag_gizmo_system, draw_gizmo_system, gizmo_readout_system)
+                    .chain()
+                    .after(crate::UiSet::Central),
+            );
+    }
+}
+
+/// What the transform gizmo's axes align to.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GizmoSpace {
+    #[default]
+    Global,
+    Local,
+    /// The camera's right and up axes, for moving in the view plane.
+    Screen,
+}
+
+impl GizmoSpace {
+    pub const ALL: [(GizmoSpace, &'static str, &'static str); 3] = [
+        (
+            GizmoSpace::Global,
+            "🌐 Global",
+            "Gizmo axes follow the world",
+        ),
+        (
+            GizmoSpace::Local,
+            "📦 Local",
+            "Gizmo axes follow the selected entity's rotation",
+        ),
+        (
+            GizmoSpace::Screen,
+            "🖵 Screen",
+            "Gizmo axes follow the camera",
+        ),
+    ];
+}
+
+/// Which transform the gizmo edits.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    /// Always along the entity's own axes, whatever the gizmo space.
+    Scale,
+}
+
+impl GizmoMode {
+    pub const ALL: [(GizmoMode, &'static str, &'static str); 3] = [
+        (GizmoMode::Translate, "✥", "Move"),
+        (GizmoMode::Rotate, "⟲", "Rotate"),
+        (GizmoMode::Scale, "⤢", "Scale"),
+    ];
+
+    fn verb(self) -> &'static str {
+        match self {
+            GizmoMode::Translate => "Move",
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Scale => "Scale",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum GizmoHandle {
+    Axis(usize),
+    /// The centre: moves or scales in the plane facing the camera.
+    Center,
+}
+
+struct Drag {
+    entity: Entity,
+    mode: GizmoMode,
+    handle: GizmoHandle,
+    /// Axis direction, or the view plane's normal for the centre handle.
+    direction: Vec3,
+    origin: Vec3,
+    /// Where the pointer first touched the axis or plane.
+    start_point: Vec3,
+    start: Transform,
+    start_rest: Option<Quat>,
+    parent_inverse: Affine3A,
+    /// The number being typed, if any; it overrides the pointer.
+    typed: String,
+    /// The button was let go with a number typed, so the drag waits for Enter or Escape.
+    released: bool,
+    readout: String,
+}
+
+impl Drag {
+    fn axis_name(&self, space: GizmoSpace) -> &'static str {
+        match (self.handle, space) {
+            (GizmoHandle::Center, _) => "view plane",
+            (GizmoHandle::Axis(axis), GizmoSpace::Screen) => ["right", "up", "view"][axis],
+            (GizmoHandle::Axis(axis), _) => AXIS_NAMES[axis],
+        }
+    }
+
+    /// The transform for the pointer over `point` on the handle's axis or plane.
+    /// `constrain` (Shift held) snaps rotations to 15° steps.
+    fn at_pointer(&self, point: Vec3, constrain: bool) -> (Transform, Option<Quat>, String) {
+        match self.mode {
+            GizmoMode::Translate => {
+                let delta = match self.handle {
+                    GizmoHandle::Axis(_) => {
+                        self.direction * (point - self.start_point).dot(self.direction)
+                    }
+                    GizmoHandle::Center => point - self.start_point,
+                };
+                let readout = match self.handle {
+                    GizmoHandle::Axis(_) => format!("{:.3}", delta.dot(self.direction)),
+                    GizmoHandle::Center => {
+                        format!("{:.2}, {:.2}, {:.2}", delta.x, delta.y, delta.z)
+                    }
+                };
+                let (transform, rest) = self.translated(delta);
+                (transform, rest, readout)
+            }
+            GizmoMode::Rotate => {
+                let (from, to) = (self.start_point - self.origin, point - self.origin);
+                let mut angle = self.direction.dot(from.cross(to)).atan2(from.dot(to));
+                if constrain {
+                    angle = snap_angle(angle);
+                }
+                let (transform, rest) = self.rotated(angle);
+                (transform, rest, format!("{:.1}°", angle.to_degrees()))
+            }
+            GizmoMode::Scale => {
+                let along = |p: Vec3| match self.handle {
+                    GizmoHandle::Axis(_) => (p - self.origin).dot(self.direction),
+                    GizmoHandle::Center => (p - self.origin).length(),
+                };
+                let start = along(self.start_point);
+                let factor = if start.abs() > f32::EPSILON {
+                    along(point) / start
+                } else {
+                    1.0
+                };
+                let (transform, rest) = self.scaled(factor);
+                (transform, rest, format!("×{factor:.3}"))
+            }
+        }
+    }
+
+    /// The transform for a typed amount: a distance, an angle in degrees or a factor.
+    fn with_amount(&self, amount: f32) -> Option<(Transform, Option<Quat>)> {
+        match self.mode {
+            GizmoMode::Translate => match self.handle {
+                GizmoHandle::Axis(_) => Some(self.translated(self.direction * amount)),
+                // A single number has no direction in a plane.
+                GizmoHandle::Center => None,
+            },
+            GizmoMode::Rotate => Some(self.rotated(amount.to_radians())),
+            GizmoMode::Scale => Some(self.scaled(amount)),
+        }
+    }
+
+    fn translated(&self, delta: Vec3) -> (Transform, Option<Quat>) {
+        let mut transform = self.start;
+        transform.translation = self.parent_inverse.transform_point3(self.origin + delta);
+        (transform, self.start_rest)
+    }
+
+    fn rotated(&self, angle: f32) -> (Transform, Option<Quat>) {
+        // The axis is in world space; bring it into the parent's space.
+        let axis = self
+            .parent_inverse
+            .transform_vector3(self.direction)
+            .normalize_or(self.direction);
+        let delta = Quat::from_axis_angle(axis, angle);
+        let mut transform = self.start;
+        match self.start_rest {
+            // Animated cubes are edited through their rest orientation, like in the Inspector.
+            Some(rest) => (transform, Some((delta * rest).normalize())),
+            None => {
+                transform.rotation = (delta * self.start.rotation).normalize();
+                (transform, None)
+            }
+        }
+    }
+
+    fn scaled(&self, factor: f32) -> (Transform, Option<Quat>) {
+        let mut transform = self.start;
+        match self.handle {
+            GizmoHandle::Axis(axis) => transform.scale[axis] *= factor,
+            GizmoHandle::Center => transform.scale *= factor,
+        }
+        (transform, self.start_rest)
+    }
+}
+
+#[derive(Resource, Default)]
+struct GizmoDrag {
+    active: Option<Drag>,
+    hovered: Option<GizmoHandle>,
+}
+
+/// The gizmo's origin, axis directions and size for `transform` seen from `camera`.
+fn gizmo_frame(
+    space: GizmoSpace,
+    mode: GizmoMode,
+    transform: &GlobalTransform,
+    camera: &GlobalTransform,
+    projection: &Projection,
+) -> (Vec3, [Vec3; 3], f32) {
+    let origin = transform.translation();
+    let local = || {
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        [rotation * Vec3::X, rotation * Vec3::Y, rotation * Vec3::Z]
+    };
+    let axes = match (space, mode) {
+        (_, GizmoMode::Scale) | (GizmoSpace::Local, _) => local(),
+        (GizmoSpace::Global, _) => [Vec3::X, Vec3::Y, Vec3::Z],
+        (GizmoSpace::Screen, _) => [*camera.right(), *camera.up(), *camera.back()],
+    };
+    let length = match projection {
+        // The view height, not the distance, decides the apparent size.
+        Projection::Orthographic(orthographic) => orthographic.scale * SCREEN_SIZE,
+        Projection::Perspective(_) => origin.distance(camera.translation()) * SCREEN_SIZE,
+    };
+    (origin, axes, length)
+}
+
+/// Axes the pointer can grab: the third screen axis points at the camera, and 2D mode keeps
+/// to the XY plane.
+fn grabbable_axes(space: GizmoSpace, mode: GizmoMode, sandbox: SandboxMode) -> Range<usize> {
+    match (sandbox, mode) {
+        (SandboxMode::Sprites2d, GizmoMode::Rotate) => 2..3,
+        (SandboxMode::Sprites2d, _) => 0..2,
+        (_, GizmoMode::Rotate | GizmoMode::Scale) => 0..3,
+        _ if space == GizmoSpace::Screen => 0..2,
+        _ => 0..3,
+    }
+}
+
+fn ring_point(origin: Vec3, axis: Vec3, radius: f32, t: f32) -> Vec3 {
+    let (u, v) = axis.any_orthonormal_pair();
+    let angle = t * std::f32::consts::TAU;
+    origin + (u * angle.cos() + v * angle.sin()) * radius
+}
+
+fn distance_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = ((point - a).dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}
+
+/// Parameter along the line `origin + s * direction` of its closest point to `ray`.
+fn closest_on_line(origin: Vec3, direction: Vec3, ray: Ray3d) -> Option<f32> {
+    let w0 = origin - ray.origin;
+    let rd = *ray.direction;
+    let (a, b, c) = (direction.dot(direction), direction.dot(rd), rd.dot(rd));
+    let (d, e) = (direction.dot(w0), rd.dot(w0));
+    let denominator = a * c - b * b;
+    // Nearly parallel to the view ray: the axis can't be dragged meaningfully.
+    (denominator.abs() > 1e-5).then(|| (b * e - c * d) / denominator)
+}
+
+/// Where `ray` meets the handle: along the axis for arrows, in the plane otherwise.
+fn handle_point(
+    mode: GizmoMode,
+    handle: GizmoHandle,
+    origin: Vec3,
+    direction: Vec3,
+    ray: Ray3d,
+) -> Option<Vec3> {
+    match (mode, handle) {
+        (GizmoMode::Translate | GizmoMode::Scale, GizmoHandle::Axis(_)) => {
+            closest_on_line(origin, direction, ray).map(|s| origin + direction * s)
+        }
+        _ => ray
+            .intersect_plane(origin, InfinitePlane3d::new(direction))
+            .map(|distance| ray.get_point(distance)),
+    }
+}
+
+fn typed_key(key: KeyCode) -> Option<char> {
+    Some(match key {
+        KeyCode::Digit0 | KeyCode::Numpad0 => '0',
+        KeyCode::Digit1 | KeyCode::Numpad1 => '1',
+        KeyCode::Digit2 | KeyCode::Numpad2 => '2',
+        KeyCode::Digit3 | KeyCode::Numpad3 => '3',
+        KeyCode::Digit4 | KeyCode::Numpad4 => '4',
+        KeyCode::Digit5 | KeyCode::Numpad5 => '5',
+        KeyCode::Digit6 | KeyCode::Numpad6 => '6',
+        KeyCode::Digit7 | KeyCode::Numpad7 => '7',
+        KeyCode::Digit8 | KeyCode::Numpad8 => '8',
+        KeyCode::Digit9 | KeyCode::Numpad9 => '9',
+        KeyCode::Period | KeyCode::NumpadDecimal => '.',
+        KeyCode::Minus | KeyCode::NumpadSubtract => '-',
+        _ => return None,
+    })
+}
+
+fn write_transform(
+    entity: Entity,
+    (transform, rest): (Transform, Option<Quat>),
+    transforms: &mut Query<(&mut Transform, Option<&mut RestRotation>)>,
+) {
+    let Ok((mut current, current_rest)) = transforms.get_mut(entity) else {
+        return;
+    };
+    if *current != transform {
+        *current = transform;
+    }
+    if let (Some(mut current_rest), Some(rest)) = (current_rest, rest) {
+        if current_rest.0 != rest {
+            current_rest.0 = rest;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drag_gizmo_system(
+    mut drag: ResMut<GizmoDrag>,
+    space: Res<GizmoSpace>,
+    mode: Res<GizmoMode>,
+    tool: Res<ViewportTool>,
+    routing: Res<InputRouting>,
+    viewport: Res<Viewport>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    selection: Res<Selection>,
+    sandbox: Res<SandboxMode>,
+    picking: Picking,
+    cameras: Query<(&Camera, &GlobalTransform, &Projection), With<ViewportCamera>>,
+    globals: Query<&GlobalTransform>,
+    parents: Query<&Parent>,
+    mut transforms: Query<(&mut Transform, Option<&mut RestRotation>)>,
+) {
+    let ray = picking.pointer_ray();
+
+    if let Some(active) = &mut drag.active {
+        if routing.keyboard_is_free() {
+            for key in keys.get_just_pressed() {
+                match (key, typed_key(*key)) {
+                    (_, Some(c)) => active.typed.push(c),
+                    (KeyCode::Backspace, _) => {
+                        active.typed.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let released = !mouse.pressed(MouseButton::Left);
+        active.released |= released && !active.typed.is_empty();
+        let cancel = keys.just_pressed(KeyCode::Escape);
+        let confirm = keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter])
+            || (active.released && mouse.just_pressed(MouseButton::Left));
+
+        let entity = active.entity;
+        if cancel {
+            write_transform(entity, (active.start, active.start_rest), &mut transforms);
+            drag.active = None;
+            return;
+        }
+        let typed = active.typed.parse::<f32>().ok();
+        let target = match typed {
+            Some(amount) => {
+                active.readout = active.typed.clone();
+                active.with_amount(amount)
+            }
+            None if !active.typed.is_empty() && active.typed != "-" => None,
+            None if active.released => None,
+            None => ray
+                .and_then(|ray| {
+                    handle_point(
+                        active.mode,
+                        active.handle,
+                        active.origin,
+                        active.direction,
+                        ray,
+                    )
+                })
+                .map(|point| {
+                    let constrain = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+                    let (transform, rest, readout) = active.at_pointer(point, constrain);
+                    active.readout = readout;
+                    (transform, rest)
+                }),
+        };
+        if let Some(target) = target {
+            write_transform(entity, target, &mut transforms);
+        }
+        if confirm || (released && !active.released) {
+            drag.active = None;
+        }
+        return;
+    }
+
+    drag.hovered = None;
+    if *tool != ViewportTool::Select || routing.pointer != InputOwner::Viewport {
+        return;
+    }
+    let (Some(entity), Ok((camera, camera_transform, projection))) =
+        (selection.primary(), cameras.get_single())
+    else {
+        return;
+    };
+    let (Ok(global), Some(pointer)) = (
+        globals.get(entity),
+        viewport.hovered_pixel.map(|pixel| pixel.as_vec2() + 0.5),
+    ) else {
+        return;
+    };
+    let (origin, axes, length) = gizmo_frame(*space, *mode, global, camera_transform, projection);
+    let project = |world: Vec3| camera.world_to_viewport(camera_transform, world);
+    let Some(center) = project(origin) else {
+        return;
+    };
+
+    let hovered = if *mode != GizmoMode::Rotate && pointer.distance(center) < GRAB_DISTANCE * 1.5 {
+        Some(GizmoHandle::Center)
+    } else {
+        let distance_to = |axis: usize| -> Option<f32> {
+            if *mode == GizmoMode::Rotate {
+                let points: Vec<Vec2> = (0..=RING_SEGMENTS)
+                    .filter_map(|i| {
+                        project(ring_point(
+                            origin,
+                            axes[axis],
+                            length,
+                            i as f32 / RING_SEGMENTS as f32,
+                        ))
+                    })
+                    .collect();
+                points
+                    .windows(2)
+                    .map(|segment| distance_to_segment(pointer, segment[0], segment[1]))
+                    .min_by(f32::total_cmp)
+            } else {
+                let tip = project(origin + axes[axis] * length)?;
+                Some(distance_to_segment(pointer, center, tip))
+            }
+        };
+        grabbable_axes(*space, *mode, *sandbox)
+            .filter_map(|axis| distance_to(axis).map(|distance| (axis, distance)))
+            .filter(|(_, distance)| *distance < GRAB_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(axis, _)| GizmoHandle::Axis(axis))
+    };
+    drag.hovered = hovered;
+
+    let (Some(handle), Some(ray)) = (hovered, ray) else {
+        return;
+    };
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let direction = match handle {
+        GizmoHandle::Axis(axis) => axes[axis],
+        GizmoHandle::Center => *camera_transform.back(),
+    };
+    let Some(start_point) = handle_point(*mode, handle, origin, direction, ray) else {
+        return;
+    };
+    let Ok((transform, rest)) = transforms.get(entity) else {
+        return;
+    };
+    let parent_inverse = parents
+        .get(entity)
+        .ok()
+        .and_then(|parent| globals.get(parent.get()).ok())
+        .map_or(Affine3A::IDENTITY, |parent| parent.affine().inverse());
+    drag.active = Some(Drag {
+        entity,
+        mode: *mode,
+        handle,
+        direction,
+        origin,
+        start_point,
+        start: *transform,
+        start_rest: rest.map(|rest| rest.0),
+        parent_inverse,
+        typed: String::new(),
+        released: false,
+        readout: String::new(),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_gizmo_system(
+    drag: Res<GizmoDrag>,
+    space: Res<GizmoSpace>,
+    mode: Res<GizmoMode>,
+    tool: Res<ViewportTool>,
+    selection: Res<Selection>,
+    sandbox: Res<SandboxMode>,
+    cameras: Query<(&GlobalTransform, &Projection), With<ViewportCamera>>,
+    globals: Query<&GlobalTransform>,
+    mut gizmos: Gizmos,
+) {
+    if *tool != ViewportTool::Select {
+        return;
+    }
+    let (Some(entity), Ok((camera, projection))) = (selection.primary(), cameras.get_single())
+    else {
+        return;
+    };
+    let Ok(global) = globals.get(entity) else {
+        return;
+    };
+    let mode = drag.active.as_ref().map_or(*mode, |active| active.mode);
+    let (origin, axes, length) = gizmo_frame(*space, mode, global, camera, projection);
+    let axis_range = grabbable_axes(*space, mode, *sandbox);
+    let active = drag
+        .active
+        .as_ref()
+        .map(|active| active.handle)
+        .or(drag.hovered);
+    let highlight = |handle: GizmoHandle, color: Color| {
+        if active == Some(handle) {
+            Color::srgb(1.0, 0.9, 0.2)
+        } else {
+            color
+        }
+    };
+    let colors = [
+        Color::srgb(0.9, 0.25, 0.25),
+        Color::srgb(0.3, 0.85, 0.3),
+        Color::srgb(0.3, 0.45, 0.95),
+    ];
+    match mode {
+        GizmoMode::Translate => {
+            for axis in axis_range {
+                let color = highlight(GizmoHandle::Axis(axis), colors[axis]);
+                gizmos.arrow(origin, origin + axes[axis] * length, color);
+            }
+        }
+        GizmoMode::Rotate => {
+            for axis in axis_range {
+                let color = highlight(GizmoHandle::Axis(axis), colors[axis]);
+                let rotation = Quat::from_rotation_arc(Vec3::Z, axes[axis]);
+                gizmos.circle(
+                    origin,
+                    Dir3::new_unchecked(rotation * Vec3::Z),
+                    length,
+                    color,
+                );
+            }
+        }
+        GizmoMode::Scale => {
+            for axis in axis_range {
+                let color = highlight(GizmoHandle::Axis(axis), colors[axis]);
+                let tip = origin + axes[axis] * length;
+                gizmos.line(origin, tip, color);
+                gizmos.cuboid(
+                    Transform::from_translation(tip).with_scale(Vec3::splat(length * 0.08)),
+                    color,
+                );
+            }
+        }
+    }
+    if mode != GizmoMode::Rotate {
+        gizmos.circle(
+            origin,
+            camera.back(),
+            length * 0.08,
+            highlight(GizmoHandle::Center, Color::WHITE),
+        );
+    }
+}
+
+/// Shows the amount being applied, and the number being typed, at the bottom of the viewport.
+fn gizmo_readout_system(
+    mut contexts: EguiContexts,
+    drag: Res<GizmoDrag>,
+    space: Res<GizmoSpace>,
+    viewport: Res<Viewport>,
+) {
+    let Some(active) = &drag.active else {
+        return;
+    };
+    let mut text = format!(
+        "{} {}: {}",
+        active.mode.verb(),
+        active.axis_name(*space),
+        active.readout
+    );
+    if active.typed.is_empty() {
+        text.push_str("   Type a value for an exact amount");
+    } else {
+        text.push_str("▏   Enter to confirm, Esc to cancel");
+    }
+    if active.mode == GizmoMode::Translate
+        && active.handle == GizmoHandle::Center
+        && !active.typed.is_empty()
+    {
+        text = "Drag an axis arrow to type a distance".to_owned();
+    }
+    egui::Area::new(egui::Id::new("gizmo_readout"))
+        .fixed_pos(viewport.rect.left_bottom() + egui::vec2(8.0, -32.0))
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.monospace(text);
+            });
+        });
+}
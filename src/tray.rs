@@ -0,0 +1,150 @@
+use std::sync::{mpsc, Mutex};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use tray_icon::{
+    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+    Icon, TrayIcon, TrayIconBuilder,
+};
+
+use crate::{errors::AppError, status_bar::StatusBar};
+
+const ICON_PATH: &str = "assets/icon.png";
+const SHOW_HIDE: &str = "show_hide";
+const SCREENSHOT: &str = "screenshot";
+const QUIT: &str = "quit";
+
+/// A system tray icon, built with the `tray` feature, whose menu shows or hides the window,
+/// saves a screenshot of it and quits. Handy when the sandbox runs as a long-lived overlay
+/// with its window tucked away.
+pub struct TrayPlugin;
+
+impl Plugin for TrayPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        app.insert_resource(TrayFailures(Mutex::new(receiver)))
+            .add_systems(Startup, move |world: &mut World| {
+                spawn_tray(world, sender.clone())
+            })
+            .add_systems(Update, tray_actions_system);
+    }
+}
+
+/// Why the tray icon could not be created, sent from wherever it is built.
+#[derive(Resource)]
+struct TrayFailures(Mutex<mpsc::Receiver<String>>);
+
+/// Keeps the icon alive on the main thread; it disappears when dropped.
+#[cfg(not(target_os = "linux"))]
+struct TrayHandle(#[allow(dead_code)] TrayIcon);
+
+fn build_tray() -> Result<TrayIcon, String> {
+    let menu = Menu::new();
+    menu.append_items(&[
+        &MenuItem::with_id(SHOW_HIDE, "Show/Hide Window", true, None),
+        &MenuItem::with_id(SCREENSHOT, "Save Screenshot", true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(QUIT, "Quit", true, None),
+    ])
+    .map_err(|err| err.to_string())?;
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip("bevy_egui sandbox")
+        .with_icon(load_icon()?)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+fn load_icon() -> Result<Icon, String> {
+    let file = std::fs::File::open(ICON_PATH).map_err(|err| format!("{ICON_PATH}: {err}"))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| err.to_string())?;
+    let pixels = &buffer[..info.buffer_size()];
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|la| [la[0], la[0], la[0], la[1]])
+            .collect(),
+        _ => pixels.iter().flat_map(|l| [*l, *l, *l, 255]).collect(),
+    };
+    Icon::from_rgba(rgba, info.width, info.height).map_err(|err| err.to_string())
+}
+
+/// On Linux the icon lives on its own thread with a GTK main loop; elsewhere it must be made
+/// on the main thread, where winit's event loop is already running by the first update.
+fn spawn_tray(world: &mut World, failures: mpsc::Sender<String>) {
+    #[cfg(target_os = "linux")]
+    {
+        let _ = world;
+        std::thread::spawn(move || {
+            if let Err(err) = gtk::init() {
+                let _ = failures.send(err.to_string());
+                return;
+            }
+            match build_tray() {
+                Ok(_tray) => gtk::main(),
+                Err(err) => {
+                    let _ = failures.send(err);
+                }
+            }
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    match build_tray() {
+        Ok(tray) => world.insert_non_send_resource(TrayHandle(tray)),
+        Err(err) => {
+            let _ = failures.send(err);
+        }
+    }
+}
+
+fn tray_actions_system(
+    failures: Res<TrayFailures>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    mut exit: EventWriter<AppExit>,
+    mut errors: EventWriter<AppError>,
+    mut status: ResMut<StatusBar>,
+    time: Res<Time>,
+) {
+    if let Ok(failures) = failures.0.lock() {
+        for err in failures.try_iter() {
+            errors.send(
+                AppError::new("Tray", format!("Could not create the tray icon: {err}")).suggest(
+                    "On Linux, install GTK 3 and libappindicator (or libayatana-appindicator).",
+                ),
+            );
+        }
+    }
+
+    let Ok((entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+    for event in MenuEvent::receiver().try_iter() {
+        match event.id.0.as_str() {
+            SHOW_HIDE => window.visible = !window.visible,
+            SCREENSHOT => {
+                let seconds = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs());
+                let path = format!("screenshot-{seconds}.png");
+                if screenshots.save_screenshot_to_disk(entity, &path).is_ok() {
+                    status.flash(format!("Saved {path}"), time.elapsed_seconds());
+                }
+            }
+            QUIT => {
+                exit.send(AppExit::Success);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::time::SystemTime;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppError;
+use crate::icons::Icon;
+use crate::panels::{Menu, MenuItem, RegisterPanelExt};
+
+pub const UI_LAYOUT_PATH: &str = "ui_layout.ron";
+
+/// Seconds between checks of the layout file for edits.
+const POLL_INTERVAL: f32 = 0.5;
+
+/// Lets the side panel's contents be described in `ui_layout.ron`, which is picked up again
+/// whenever it changes, so the panel can be rearranged without recompiling.
+pub struct UiLayoutPlugin;
+
+impl Plugin for UiLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SidePanelLayout>()
+            .init_resource::<LayoutWatcher>()
+            .add_systems(Update, reload_ui_layout_system)
+            .add_menu_item(
+                MenuItem::new(Menu::File, "Export UI Layout", |world| {
+                    let layout = world.resource::<SidePanelLayout>();
+                    if let Err(err) = layout.save() {
+                        world.send_event(err);
+                    }
+                })
+                .icon(Icon::Layout),
+            );
+    }
+}
+
+/// One entry of the side panel, drawn in file order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SideWidget {
+    Heading(String),
+    Label(String),
+    Separator,
+    Space(f32),
+    /// Queues a cube at a random spot using the spawn settings.
+    AddEntity(String),
+    /// Select / place-on-surface picker for viewport clicks.
+    ClickTool(String),
+    TextInput(String),
+    ExampleImage,
+    ValueSlider(String),
+    IncrementButton(String),
+    /// The Load / Invert / Remove buttons for the Bevy icon.
+    IconButtons,
+    IconImage,
+    WindowToggle(String),
+    /// The "powered by egui" link, pinned to the bottom of the panel.
+    Footer,
+}
+
+#[derive(Clone, Debug, Resource, Serialize, Deserialize)]
+pub struct SidePanelLayout {
+    pub widgets: Vec<SideWidget>,
+}
+
+impl Default for SidePanelLayout {
+    fn default() -> Self {
+        use SideWidget::*;
+        Self {
+            widgets: vec![
+                Heading("Side Panel".to_owned()),
+                AddEntity("Add Entity".to_owned()),
+                ClickTool("Click to:".to_owned()),
+                TextInput("Write something: ".to_owned()),
+                ExampleImage,
+                ValueSlider("value".to_owned()),
+                IncrementButton("Increment".to_owned()),
+                Space(100.0),
+                IconButtons,
+                IconImage,
+                Space(10.0),
+                WindowToggle("Window Is Open".to_owned()),
+                Footer,
+            ],
+        }
+    }
+}
+
+impl SidePanelLayout {
+    fn load() -> Result<Self, String> {
+        let contents = std::fs::read_to_string(UI_LAYOUT_PATH).map_err(|err| err.to_string())?;
+        ron::from_str(&contents).map_err(|err| err.to_string())
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| {
+                AppError::new(
+                    "UI Layout",
+                    format!("Failed to serialize the layout: {err}"),
+                )
+            })?;
+        std::fs::write(UI_LAYOUT_PATH, contents).map_err(|err| {
+            AppError::new(
+                "UI Layout",
+                format!("Failed to write {UI_LAYOUT_PATH}: {err}"),
+            )
+            .suggest("Check that the working directory is writable.")
+        })?;
+        info!("Exported the side panel layout to {UI_LAYOUT_PATH}");
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct LayoutWatcher {
+    poll: Timer,
+    /// Set after the first check, which runs right away so the file applies at startup.
+    polled: bool,
+    /// Modification time of the file last loaded; `None` while it does not exist.
+    modified: Option<SystemTime>,
+}
+
+impl Default for LayoutWatcher {
+    fn default() -> Self {
+        Self {
+            poll: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+            polled: false,
+            modified: None,
+        }
+    }
+}
+
+fn reload_ui_layout_system(
+    mut watcher: ResMut<LayoutWatcher>,
+    mut layout: ResMut<SidePanelLayout>,
+    time: Res<Time<Real>>,
+    mut errors: EventWriter<AppError>,
+) {
+    let due = watcher.poll.tick(time.delta()).just_finished();
+    if !due && watcher.polled {
+        return;
+    }
+    watcher.polled = true;
+    let modified = std::fs::metadata(UI_LAYOUT_PATH)
+        .and_then(|meta| meta.modified())
+        .ok();
+    if modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+    if modified.is_none() {
+        // The file was removed: fall back to the built-in panel.
+        *layout = SidePanelLayout::default();
+        return;
+    }
+    match SidePanelLayout::load() {
+        Ok(loaded) => {
+            info!("Loaded the side panel layout from {UI_LAYOUT_PATH}");
+            *layout = loaded;
+        }
+        Err(err) => {
+            errors.send(
+                AppError::new(
+                    "UI Layout",
+                    format!("Failed to read {UI_LAYOUT_PATH}: {err}"),
+                )
+                .suggest("Fix the file and save it again; the previous layout stays in use."),
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+/// A persisted format with an explicit version and the steps that upgrade older files.
+///
+/// Renamed or added fields are handled by serde (`alias`, `default`) so old files still parse;
+/// a migration is for changes of meaning, run on the parsed value before it is used. Append a
+/// migration and bump `VERSION` together: `MIGRATIONS[n]` upgrades version `n` to `n + 1`.
+pub trait Versioned: Sized + 'static {
+    /// The version written by this build.
+    const VERSION: u32;
+    const MIGRATIONS: &'static [Migration<Self>];
+    /// Used in log messages, e.g. "scene".
+    const FORMAT: &'static str;
+
+    fn version_mut(&mut self) -> &mut u32;
+
+    /// Brings a freshly parsed file up to `VERSION`. Files from a newer build are left as they
+    /// are and loaded on a best-effort basis.
+    fn upgrade(&mut self) {
+        debug_assert_eq!(Self::MIGRATIONS.len() as u32, Self::VERSION);
+        let from = *self.version_mut();
+        if from > Self::VERSION {
+            warn!(
+                "{} file is version {from}, newer than the supported {}; some data may be ignored",
+                Self::FORMAT,
+                Self::VERSION
+            );
+            return;
+        }
+        for migration in &Self::MIGRATIONS[from as usize..] {
+            (migration.upgrade)(self);
+            info!("Upgraded {} file: {}", Self::FORMAT, migration.description);
+        }
+        *self.version_mut() = Self::VERSION;
+    }
+}
+
+pub struct Migration<T> {
+    pub description: &'static str,
+    pub upgrade: fn(&mut T),
+}
+
+/// Version 0 files predate the version field and are otherwise identical to version 1.
+pub const fn unversioned<T>() -> Migration<T> {
+    fn unchanged<T>(_: &mut T) {}
+    Migration {
+        description: "added the format version",
+        upgrade: unchanged::<T>,
+    }
+}
@@ -0,0 +1,406 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+};
+use bevy_egui::egui;
+
+use crate::{
+    history::History,
+    input::{InputOwner, InputRouting},
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    picking::{ray_mesh, Picking},
+    scene::CustomMesh,
+    selection::Selection,
+    session_stats::SessionEvent,
+    settings::{egui_color, Settings},
+    viewport::ViewportTool,
+    RenderCube,
+};
+
+/// The Vertex Paint window and tool: drag over the selected mesh to paint its vertex colours.
+pub struct VertexPaintPlugin;
+
+impl Plugin for VertexPaintPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_panel::<VertexPaintWindow>().add_systems(
+            Update,
+            (vertex_paint_window_system, paint_system)
+                .chain()
+                .after(crate::UiSet::Central),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Falloff {
+    Constant,
+    Linear,
+    Smooth,
+}
+
+impl Falloff {
+    /// Brush weight at `t`, the distance from the centre over the radius.
+    fn weight(self, t: f32) -> f32 {
+        match self {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => 1.0 - t,
+            Falloff::Smooth => {
+                let s = 1.0 - t;
+                s * s * (3.0 - 2.0 * s)
+            }
+        }
+    }
+}
+
+/// A mesh's vertex colours before a stroke.
+struct Stroke {
+    mesh: AssetId<Mesh>,
+    colors: Vec<[f32; 4]>,
+}
+
+#[derive(Resource)]
+pub struct VertexPaintWindow {
+    pub is_open: bool,
+    color: [f32; 4],
+    /// In world units.
+    radius: f32,
+    strength: f32,
+    falloff: Falloff,
+    /// The stroke being painted, recorded in the history when the button is released.
+    stroke: Option<Stroke>,
+}
+
+impl Default for VertexPaintWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            color: [0.9, 0.2, 0.2, 1.0],
+            radius: 0.5,
+            strength: 0.5,
+            falloff: Falloff::Smooth,
+            stroke: None,
+        }
+    }
+}
+
+impl Panel for VertexPaintWindow {
+    const TITLE: &'static str = "Vertex Paint";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+/// Splits every triangle into four, without sharing vertices, so there is more to paint.
+fn subdivide(mesh: &Mesh) -> Option<Mesh> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return None;
+    };
+    let attribute = |id| match mesh.attribute(id) {
+        Some(VertexAttributeValues::Float32x3(values)) => Some(values.clone()),
+        _ => None,
+    };
+    let normals = attribute(Mesh::ATTRIBUTE_NORMAL);
+    let colors = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(values)) => Some(values.clone()),
+        _ => None,
+    };
+    let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(values)) => Some(values.clone()),
+        _ => None,
+    };
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    fn split<const N: usize>(values: &[[f32; N]], triangle: &[usize], out: &mut Vec<[f32; N]>) {
+        let [a, b, c] = [0, 1, 2].map(|i| values[triangle[i]]);
+        let mid = |p: [f32; N], q: [f32; N]| std::array::from_fn(|i| (p[i] + q[i]) * 0.5);
+        let (ab, bc, ca) = (mid(a, b), mid(b, c), mid(c, a));
+        out.extend([a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+    }
+    let mut out_positions = Vec::new();
+    let mut out_normals = Vec::new();
+    let mut out_colors = Vec::new();
+    let mut out_uvs = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        split(positions, triangle, &mut out_positions);
+        if let Some(normals) = &normals {
+            split(normals, triangle, &mut out_normals);
+        }
+        if let Some(colors) = &colors {
+            split(colors, triangle, &mut out_colors);
+        }
+        if let Some(uvs) = &uvs {
+            split(uvs, triangle, &mut out_uvs);
+        }
+    }
+
+    let count = out_positions.len() as u32;
+    let mut subdivided = Mesh::new(mesh.primitive_topology(), mesh.asset_usage)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, out_positions)
+        .with_inserted_indices(Indices::U32((0..count).collect()));
+    if normals.is_some() {
+        subdivided.insert_attribute(Mesh::ATTRIBUTE_NORMAL, out_normals);
+    }
+    if colors.is_some() {
+        subdivided.insert_attribute(Mesh::ATTRIBUTE_COLOR, out_colors);
+    }
+    if uvs.is_some() {
+        subdivided.insert_attribute(Mesh::ATTRIBUTE_UV_0, out_uvs);
+    }
+    Some(subdivided)
+}
+
+/// Gives `entity` a mesh of its own with a colour attribute, and moves the material's colour
+/// into it, so painting multiplies against white.
+fn prepare_paintable(
+    commands: &mut Commands,
+    entity: Entity,
+    mesh_handle: &mut Handle<Mesh>,
+    material: &Handle<StandardMaterial>,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) -> Option<()> {
+    let mesh = meshes.get(&*mesh_handle)?;
+    if mesh.attribute(Mesh::ATTRIBUTE_COLOR).is_some() {
+        return Some(());
+    }
+    let material = materials.get_mut(material)?;
+    let base = material.base_color.to_linear().to_f32_array();
+    material.base_color = Color::WHITE;
+    let mut mesh = mesh.clone();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![base; mesh.count_vertices()]);
+    *mesh_handle = meshes.add(mesh);
+    // Baking and decals rebuild unit cubes, which would drop the paint.
+    commands.entity(entity).insert(CustomMesh);
+    Some(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn vertex_paint_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<VertexPaintWindow>,
+    mut tool: ResMut<ViewportTool>,
+    mut commands: Commands,
+    selection: Res<Selection>,
+    mut targets: Query<(&mut Handle<Mesh>, &Handle<StandardMaterial>), With<RenderCube>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut history: ResMut<History>,
+) {
+    let VertexPaintWindow {
+        is_open,
+        color,
+        radius,
+        strength,
+        falloff,
+        ..
+    } = &mut *window;
+    if !*is_open {
+        if *tool == ViewportTool::VertexPaint {
+            *tool = ViewportTool::Select;
+        }
+        return;
+    }
+
+    egui::Window::new("Vertex Paint")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<VertexPaintWindow>(), |ui| {
+            let mut painting = *tool == ViewportTool::VertexPaint;
+            if ui
+                .toggle_value(&mut painting, "🖌 Paint mode")
+                .on_hover_text("Drag over the selected mesh to paint")
+                .changed()
+            {
+                *tool = if painting {
+                    ViewportTool::VertexPaint
+                } else {
+                    ViewportTool::Select
+                };
+            }
+            egui::Grid::new("vertex_paint_brush")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Colour");
+                    ui.color_edit_button_rgba_unmultiplied(color);
+                    ui.end_row();
+                    ui.label("Radius");
+                    ui.add(egui::Slider::new(radius, 0.02..=5.0).logarithmic(true));
+                    ui.end_row();
+                    ui.label("Strength");
+                    ui.add(egui::Slider::new(strength, 0.0..=1.0));
+                    ui.end_row();
+                    ui.label("Falloff");
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(falloff, Falloff::Constant, "Constant");
+                        ui.selectable_value(falloff, Falloff::Linear, "Linear");
+                        ui.selectable_value(falloff, Falloff::Smooth, "Smooth");
+                    });
+                    ui.end_row();
+                });
+
+            ui.separator();
+            let target = selection
+                .primary()
+                .filter(|entity| targets.contains(*entity));
+            ui.horizontal(|ui| {
+                let vertices = target
+                    .and_then(|entity| targets.get(entity).ok())
+                    .and_then(|(mesh, _)| meshes.get(mesh))
+                    .map_or(0, Mesh::count_vertices);
+                if ui
+                    .add_enabled(target.is_some(), egui::Button::new("Subdivide"))
+                    .on_hover_text("Split each triangle in four for finer painting")
+                    .clicked()
+                {
+                    if let Some((mut handle, material)) =
+                        target.and_then(|entity| targets.get_mut(entity).ok())
+                    {
+                        let entity = target.unwrap_or(Entity::PLACEHOLDER);
+                        prepare_paintable(
+                            &mut commands,
+                            entity,
+                            &mut handle,
+                            material,
+                            &mut meshes,
+                            &mut materials,
+                        );
+                        if let Some(mesh) = meshes.get(&*handle).and_then(subdivide) {
+                            *handle = meshes.add(mesh);
+                        }
+                    }
+                }
+                ui.weak(format!("{vertices} vertices"));
+            });
+            if target.is_none() {
+                ui.weak("Select a mesh to paint.");
+            }
+            if ui
+                .add_enabled(history.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text("Strokes share the undo history with scene edits")
+                .clicked()
+            {
+                history.undo();
+            }
+            ui.weak("Vertex colours are not saved with the scene.");
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn paint_system(
+    mut window: ResMut<VertexPaintWindow>,
+    tool: Res<ViewportTool>,
+    routing: Res<InputRouting>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    picking: Picking,
+    selection: Res<Selection>,
+    mut commands: Commands,
+    mut targets: Query<
+        (
+            &GlobalTransform,
+            &mut Handle<Mesh>,
+            &Handle<StandardMaterial>,
+        ),
+        With<RenderCube>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut gizmos: Gizmos,
+    settings: Res<Settings>,
+    mut history: ResMut<History>,
+    mut session: EventWriter<SessionEvent>,
+) {
+    // Finish the stroke wherever the button is released, even off the mesh.
+    if !mouse.pressed(MouseButton::Left) {
+        if let Some(stroke) = window.stroke.take() {
+            if let Some(VertexAttributeValues::Float32x4(after)) = meshes
+                .get(stroke.mesh)
+                .and_then(|mesh| mesh.attribute(Mesh::ATTRIBUTE_COLOR))
+            {
+                history.push_stroke(stroke.mesh, stroke.colors, after.clone());
+            }
+        }
+    }
+    if *tool != ViewportTool::VertexPaint || routing.pointer != InputOwner::Tool {
+        return;
+    }
+    let (Some(entity), Some(ray)) = (selection.primary(), picking.pointer_ray()) else {
+        return;
+    };
+    let Ok((transform, mut handle, material)) = targets.get_mut(entity) else {
+        return;
+    };
+    let Some(distance) = meshes
+        .get(&*handle)
+        .and_then(|mesh| ray_mesh(ray, mesh, transform))
+    else {
+        return;
+    };
+    let hit = ray.get_point(distance);
+    let brush = egui_color(settings.highlights().primary);
+    gizmos.sphere(
+        hit,
+        Quat::IDENTITY,
+        window.radius,
+        Color::srgba_u8(brush.r(), brush.g(), brush.b(), 160),
+    );
+    if !mouse.pressed(MouseButton::Left) {
+        return;
+    }
+
+    prepare_paintable(
+        &mut commands,
+        entity,
+        &mut handle,
+        material,
+        &mut meshes,
+        &mut materials,
+    );
+    let Some(mesh) = meshes.get_mut(&*handle) else {
+        return;
+    };
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION).cloned()
+    else {
+        return;
+    };
+    let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+    else {
+        return;
+    };
+    if window.stroke.is_none() {
+        window.stroke = Some(Stroke {
+            mesh: handle.id(),
+            colors: colors.clone(),
+        });
+        session.send(SessionEvent::StrokeDrawn);
+    }
+
+    let brush = Color::srgba(
+        window.color[0],
+        window.color[1],
+        window.color[2],
+        window.color[3],
+    )
+    .to_linear()
+    .to_f32_array();
+    let affine = transform.affine();
+    for (position, color) in positions.iter().zip(colors.iter_mut()) {
+        let world = affine.transform_point3(Vec3::from(*position));
+        let t = world.distance(hit) / window.radius;
+        if t > 1.0 {
+            continue;
+        }
+        // Brush strength is per frame, so holding still keeps building up colour.
+        let amount = (window.strength * window.falloff.weight(t)).clamp(0.0, 1.0);
+        for (channel, target) in color.iter_mut().zip(brush) {
+            *channel += (target - *channel) * amount;
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// What a click in the viewport does.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Resource)]
+pub enum ViewportTool {
+    #[default]
+    Select,
+    PlaceOnSurface,
+    /// Set the selected group's pivot to the clicked surface point.
+    PickPivot,
+    /// Set the camera's focal distance to the clicked surface.
+    Focus,
+    /// Drag to paint vertex colours on the selected mesh.
+    VertexPaint,
+    /// Click a cloth to pin or release the point nearest the click.
+    PinCloth,
+    /// Drag on the XY plane to paint tiles, see [`crate::tilemap`].
+    PaintTiles,
+}
+
+/// Where the viewport image was drawn this frame and what the pointer is doing over it,
+/// for tools that need to map between screen and image-pixel space.
+#[derive(Resource)]
+pub struct Viewport {
+    pub rect: egui::Rect,
+    pub image_size: UVec2,
+    /// Pointer position in egui points while it hovers the viewport.
+    pub pointer: Option<egui::Pos2>,
+    /// Image pixel under the pointer while it hovers the viewport.
+    pub hovered_pixel: Option<UVec2>,
+    /// The viewport was clicked (pressed and released without dragging) this frame.
+    pub clicked: bool,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            rect: egui::Rect::NOTHING,
+            image_size: UVec2::ZERO,
+            pointer: None,
+            hovered_pixel: None,
+            clicked: false,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn update(&mut self, response: &egui::Response, image_size: UVec2) {
+        self.rect = response.rect;
+        self.image_size = image_size;
+        self.pointer = response.hover_pos();
+        self.hovered_pixel = self.pointer.map(|pos| self.pixel_at(pos));
+        self.clicked = response.clicked();
+    }
+
+    /// Image pixel drawn at screen position `pos`, clamped to the image.
+    pub fn pixel_at(&self, pos: egui::Pos2) -> UVec2 {
+        let uv = (pos - self.rect.min) / self.rect.size();
+        let pixel = Vec2::new(uv.x, uv.y) * self.image_size.as_vec2();
+        pixel
+            .floor()
+            .clamp(Vec2::ZERO, (self.image_size.max(UVec2::ONE) - 1).as_vec2())
+            .as_uvec2()
+    }
+
+    /// Screen position at which image pixel coordinates `pixel` are drawn.
+    pub fn screen_pos(&self, pixel: Vec2) -> egui::Pos2 {
+        let uv = pixel / self.image_size.max(UVec2::ONE).as_vec2();
+        self.rect.min + egui::vec2(uv.x, uv.y) * self.rect.size()
+    }
+}
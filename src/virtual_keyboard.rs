@@ -0,0 +1,168 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiInput, EguiSet};
+
+use crate::settings::Settings;
+
+const KEY_SIZE: egui::Vec2 = egui::vec2(36.0, 36.0);
+const LETTER_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+const NUMBER_ROWS: [&str; 4] = ["789", "456", "123", "-0."];
+
+/// An on-screen keyboard for touch and kiosk setups. It pops up while a text field has focus,
+/// as a numeric pad when that field is a `DragValue` being typed into, and feeds its key
+/// presses to egui as ordinary input events.
+pub struct VirtualKeyboardPlugin;
+
+impl Plugin for VirtualKeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VirtualKeyboard>()
+            .add_systems(
+                PreUpdate,
+                inject_virtual_keys_system
+                    .after(EguiSet::ProcessInput)
+                    .before(EguiSet::BeginFrame),
+            )
+            .add_systems(Update, virtual_keyboard_system.after(crate::UiSet::Central));
+    }
+}
+
+#[derive(Default, Resource)]
+struct VirtualKeyboard {
+    /// The field being typed into. Pressing a key takes egui focus away from it, so it is
+    /// remembered here and focused again.
+    target: Option<egui::Id>,
+    numeric: bool,
+    shift: bool,
+    /// Events for the next egui frame.
+    pending: Vec<egui::Event>,
+}
+
+impl VirtualKeyboard {
+    fn text(&mut self, text: impl Into<String>) {
+        self.pending.push(egui::Event::Text(text.into()));
+    }
+
+    fn key(&mut self, key: egui::Key) {
+        for pressed in [true, false] {
+            self.pending.push(egui::Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers: egui::Modifiers::NONE,
+            });
+        }
+    }
+}
+
+fn inject_virtual_keys_system(
+    mut keyboard: ResMut<VirtualKeyboard>,
+    mut inputs: Query<&mut EguiInput, With<PrimaryWindow>>,
+) {
+    if keyboard.pending.is_empty() {
+        return;
+    }
+    if let Ok(mut input) = inputs.get_single_mut() {
+        input.events.append(&mut keyboard.pending);
+    }
+}
+
+fn virtual_keyboard_system(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    mut keyboard: ResMut<VirtualKeyboard>,
+) {
+    if !settings.input.virtual_keyboard {
+        if keyboard.target.is_some() {
+            keyboard.target = None;
+        }
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+    let focused = ctx.memory(|memory| memory.focused());
+    let over_keyboard = ctx
+        .layer_id_at(ctx.pointer_latest_pos().unwrap_or(egui::Pos2::ZERO))
+        .is_some_and(|layer| layer.id == egui::Id::new("virtual_keyboard"));
+    match focused {
+        Some(id) if keyboard.target != Some(id) => {
+            keyboard.target = Some(id);
+            // A DragValue being typed into keeps its edit text as temp data under its id.
+            keyboard.numeric = ctx.data(|data| data.get_temp::<String>(id)).is_some();
+        }
+        Some(_) => {}
+        None if over_keyboard => {}
+        None => keyboard.target = None,
+    }
+    let Some(target) = keyboard.target else {
+        return;
+    };
+    if focused != Some(target) {
+        ctx.memory_mut(|memory| memory.request_focus(target));
+    }
+
+    egui::Area::new(egui::Id::new("virtual_keyboard"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -8.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style()).show(ui, |ui| {
+                ui.spacing_mut().item_spacing = egui::vec2(4.0, 4.0);
+                let rows = if keyboard.numeric {
+                    NUMBER_ROWS
+                } else {
+                    LETTER_ROWS
+                };
+                for row in rows {
+                    ui.horizontal(|ui| {
+                        for c in row.chars() {
+                            let c = if keyboard.shift {
+                                c.to_ascii_uppercase()
+                            } else {
+                                c
+                            };
+                            if key_button(ui, &c.to_string()).clicked() {
+                                keyboard.text(c);
+                                keyboard.shift = false;
+                            }
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    let layout = if keyboard.numeric { "ABC" } else { "123" };
+                    if key_button(ui, layout).clicked() {
+                        keyboard.numeric = !keyboard.numeric;
+                    }
+                    if !keyboard.numeric {
+                        if ui
+                            .add(
+                                egui::Button::new("⇧")
+                                    .min_size(KEY_SIZE)
+                                    .selected(keyboard.shift),
+                            )
+                            .clicked()
+                        {
+                            keyboard.shift = !keyboard.shift;
+                        }
+                        if ui
+                            .add(egui::Button::new("space").min_size(egui::vec2(120.0, KEY_SIZE.y)))
+                            .clicked()
+                        {
+                            keyboard.text(" ");
+                        }
+                    }
+                    for (label, key) in [
+                        ("⏴", egui::Key::ArrowLeft),
+                        ("⏵", egui::Key::ArrowRight),
+                        ("⌫", egui::Key::Backspace),
+                        ("⏎", egui::Key::Enter),
+                    ] {
+                        if key_button(ui, label).clicked() {
+                            keyboard.key(key);
+                        }
+                    }
+                });
+            });
+        });
+}
+
+fn key_button(ui: &mut egui::Ui, label: &str) -> egui::Response {
+    ui.add(egui::Button::new(label).min_size(KEY_SIZE))
+}
@@ -0,0 +1,354 @@
+use std::{collections::HashMap, path::Path, time::SystemTime};
+
+use bevy::{
+    gltf::GltfAssetLabel,
+    prelude::*,
+    render::{mesh::PrimitiveTopology, primitives::Aabb, render_asset::RenderAssetUsages},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::AppError,
+    scene::{self, CustomMesh},
+    selection::Selection,
+    settings::Settings,
+    status_bar::StatusBar,
+    ViewportCamera,
+};
+
+const ASSETS_DIR: &str = "assets";
+/// Seconds between scans of the watched folder.
+const SCAN_INTERVAL: f32 = 1.0;
+
+/// Imports files that appear in the folder chosen in Settings › Import, for round trips with
+/// external tools: glTF and OBJ models are added to the scene in front of the camera, PNG
+/// images are loaded so the Sprites and Tilemap windows list them. Files that change
+/// afterwards are reloaded in place. The folder is polled rather than watched, so no platform
+/// file notification is needed.
+pub struct WatchFolderPlugin;
+
+impl Plugin for WatchFolderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WatchedFolder>().add_systems(
+            Update,
+            (scan_watch_folder_system, build_imported_meshes_system).chain(),
+        );
+    }
+}
+
+/// A model loaded from a file under `assets/`; its mesh is rebuilt from the file on load.
+#[derive(Component, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedMesh {
+    /// Asset path of the glTF or OBJ file.
+    pub path: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    Gltf,
+    Obj,
+    Png,
+}
+
+impl FileKind {
+    fn of(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gltf" | "glb" => Some(FileKind::Gltf),
+            "obj" => Some(FileKind::Obj),
+            "png" => Some(FileKind::Png),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+struct WatchedFolder {
+    /// The folder the `seen` times belong to; files already there when watching started are
+    /// not imported.
+    folder: Option<String>,
+    /// Modification times of the files last scanned, by asset path.
+    seen: HashMap<String, SystemTime>,
+    since_scan: f32,
+    /// Imported images, kept alive so they stay listed.
+    images: Vec<Handle<Image>>,
+}
+
+/// The supported files directly inside `folder`, by asset path, with their modification times.
+fn scan(folder: &str) -> std::io::Result<HashMap<String, SystemTime>> {
+    let mut files = HashMap::new();
+    for entry in std::fs::read_dir(Path::new(ASSETS_DIR).join(folder))? {
+        let entry = entry?;
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let path = format!("{folder}/{name}");
+        if FileKind::of(&path).is_none() {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            files.insert(path, metadata.modified()?);
+        }
+    }
+    Ok(files)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn scan_watch_folder_system(
+    mut commands: Commands,
+    mut watched: ResMut<WatchedFolder>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut imported: Query<&mut ImportedMesh>,
+    mut selection: ResMut<Selection>,
+    mut status: ResMut<StatusBar>,
+    mut errors: EventWriter<AppError>,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+) {
+    let import = &settings.import;
+    let folder = import.watch_folder.trim().trim_matches('/');
+    if !import.watch_enabled || folder.is_empty() {
+        watched.folder = None;
+        return;
+    }
+    watched.since_scan += time.delta_seconds();
+    if watched.folder.as_deref() == Some(folder) && watched.since_scan < SCAN_INTERVAL {
+        return;
+    }
+    watched.since_scan = 0.0;
+    let files = match scan(folder) {
+        Ok(files) => files,
+        Err(err) => {
+            // Report once per folder rather than on every scan.
+            if watched.folder.as_deref() != Some(folder) {
+                errors.send(
+                    AppError::new(
+                        "Watch folder",
+                        format!("Cannot read {ASSETS_DIR}/{folder}: {err}"),
+                    )
+                    .suggest("Create the folder, or choose another in Settings › Import."),
+                );
+            }
+            watched.folder = Some(folder.to_owned());
+            watched.seen.clear();
+            return;
+        }
+    };
+    let first_scan = watched.folder.as_deref() != Some(folder);
+    watched.folder = Some(folder.to_owned());
+    let previous = std::mem::replace(&mut watched.seen, files);
+    if first_scan {
+        return;
+    }
+
+    let mut changed: Vec<(&String, bool)> = watched
+        .seen
+        .iter()
+        .filter_map(|(path, modified)| match previous.get(path) {
+            None => Some((path, true)),
+            Some(before) if before != modified => Some((path, false)),
+            Some(_) => None,
+        })
+        .collect();
+    changed.sort();
+    let mut new_images = Vec::new();
+    let now = time.elapsed_seconds();
+    for (path, is_new) in changed {
+        let Some(kind) = FileKind::of(path) else {
+            continue;
+        };
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if !is_new {
+            match kind {
+                FileKind::Obj => {
+                    for mut mesh in &mut imported {
+                        if mesh.path == *path {
+                            mesh.set_changed();
+                        }
+                    }
+                }
+                FileKind::Gltf | FileKind::Png => asset_server.reload(path.clone()),
+            }
+            status.flash(format!("Reloaded {name}"), now);
+            continue;
+        }
+        match kind {
+            FileKind::Gltf | FileKind::Obj => {
+                let translation = cameras.get_single().map_or(Vec3::ZERO, |camera| {
+                    camera.translation() + camera.forward() * 10.0
+                });
+                let entity = scene::spawn_cube(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    Transform::from_translation(translation),
+                    Color::WHITE,
+                );
+                commands.entity(entity).insert((
+                    ImportedMesh { path: path.clone() },
+                    Name::new(name.to_owned()),
+                ));
+                selection.select(entity);
+                status.flash(format!("Imported {name} into the scene"), now);
+            }
+            FileKind::Png => {
+                new_images.push(asset_server.load::<Image>(path.clone()));
+                status.flash(format!("Imported {name} into the image list"), now);
+            }
+        }
+    }
+    watched.images.extend(new_images);
+}
+
+fn build_imported_meshes_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut models: Query<(Entity, &ImportedMesh, &mut Handle<Mesh>), Changed<ImportedMesh>>,
+    mut errors: EventWriter<AppError>,
+) {
+    for (entity, imported, mut mesh) in &mut models {
+        match FileKind::of(&imported.path) {
+            Some(FileKind::Gltf) => {
+                // The first primitive of the first mesh; the file's scene graph is not kept.
+                *mesh = asset_server.load(
+                    GltfAssetLabel::Primitive {
+                        mesh: 0,
+                        primitive: 0,
+                    }
+                    .from_asset(imported.path.clone()),
+                );
+            }
+            Some(FileKind::Obj) => {
+                let path = Path::new(ASSETS_DIR).join(&imported.path);
+                let parsed = std::fs::read_to_string(&path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|source| parse_obj(&source));
+                match parsed {
+                    Ok(parsed) => *mesh = meshes.add(parsed),
+                    Err(err) => {
+                        errors.send(
+                            AppError::new(
+                                "Watch folder",
+                                format!("Cannot import {}: {err}", path.display()),
+                            )
+                            .suggest("Export the model again as a triangulated OBJ."),
+                        );
+                        continue;
+                    }
+                }
+            }
+            Some(FileKind::Png) | None => continue,
+        }
+        // Picking bounds are recomputed from the new mesh once it has loaded.
+        commands.entity(entity).insert(CustomMesh).remove::<Aabb>();
+    }
+}
+
+/// Builds a mesh from Wavefront OBJ text: positions, texture coordinates and normals, with
+/// polygons fanned into triangles. Flat normals are computed when the file has none.
+fn parse_obj(source: &str) -> Result<Mesh, String> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    // One entry per triangle corner: indices into the lists above.
+    let mut corners: Vec<(usize, Option<usize>, Option<usize>)> = Vec::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {message}", number + 1);
+        let mut words = line.split_whitespace();
+        let floats = |words: std::str::SplitWhitespace, count: usize| {
+            let values: Vec<f32> = words.take(count).filter_map(|w| w.parse().ok()).collect();
+            (values.len() == count).then_some(values)
+        };
+        match words.next() {
+            Some("v") => {
+                let v = floats(words, 3).ok_or_else(|| error("expected `v x y z`"))?;
+                positions.push([v[0], v[1], v[2]]);
+            }
+            Some("vt") => {
+                let v = floats(words, 2).ok_or_else(|| error("expected `vt u v`"))?;
+                uvs.push([v[0], 1.0 - v[1]]);
+            }
+            Some("vn") => {
+                let v = floats(words, 3).ok_or_else(|| error("expected `vn x y z`"))?;
+                normals.push([v[0], v[1], v[2]]);
+            }
+            Some("f") => {
+                // Indices are 1-based, or negative to count back from the latest entry.
+                let resolve = |word: &str, len: usize| -> Result<Option<usize>, String> {
+                    if word.is_empty() {
+                        return Ok(None);
+                    }
+                    let index: i64 = word.parse().map_err(|_| error("bad face index"))?;
+                    let resolved = if index < 0 {
+                        len as i64 + index
+                    } else {
+                        index - 1
+                    };
+                    usize::try_from(resolved)
+                        .ok()
+                        .filter(|resolved| *resolved < len)
+                        .map(Some)
+                        .ok_or_else(|| error("face index out of range"))
+                };
+                let mut face = Vec::new();
+                for word in words {
+                    let mut parts = word.split('/');
+                    let position = resolve(parts.next().unwrap_or(""), positions.len())?
+                        .ok_or_else(|| error("face corner without a position"))?;
+                    let uv = resolve(parts.next().unwrap_or(""), uvs.len())?;
+                    let normal = resolve(parts.next().unwrap_or(""), normals.len())?;
+                    face.push((position, uv, normal));
+                }
+                if face.len() < 3 {
+                    return Err(error("a face needs at least three corners"));
+                }
+                for i in 1..face.len() - 1 {
+                    corners.extend([face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    if corners.is_empty() {
+        return Err("the file has no faces".to_owned());
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        corners
+            .iter()
+            .map(|(position, ..)| positions[*position])
+            .collect::<Vec<_>>(),
+    );
+    if corners.iter().all(|(_, uv, _)| uv.is_some()) {
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_UV_0,
+            corners
+                .iter()
+                .map(|(_, uv, _)| uv.map_or([0.0; 2], |uv| uvs[uv]))
+                .collect::<Vec<_>>(),
+        );
+    }
+    if corners.iter().all(|(.., normal)| normal.is_some()) {
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            corners
+                .iter()
+                .map(|(.., normal)| normal.map_or([0.0; 3], |normal| normals[normal]))
+                .collect::<Vec<_>>(),
+        );
+    } else {
+        mesh.compute_flat_normals();
+    }
+    Ok(mesh)
+}
@@ -0,0 +1,328 @@
+use bevy::{
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        view::NoFrustumCulling,
+    },
+};
+use bevy_egui::egui;
+use rand::Rng;
+
+use crate::{
+    panels::{Panel, PanelContexts, RegisterPanelExt},
+    ViewportCamera,
+};
+
+/// Side of the box of particles kept centred on the camera.
+const VOLUME: f32 = 30.0;
+const RAIN_SPEED: f32 = 14.0;
+const SNOW_SPEED: f32 = 1.2;
+
+/// The Environment window: rain and snow around the camera, and the wind shared with the cloth
+/// and boids simulations.
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>()
+            .register_panel::<EnvironmentWindow>()
+            .add_systems(
+                Update,
+                (
+                    environment_window_system.in_set(EnvironmentWindow::ui_set()),
+                    precipitation_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The global wind. Cloth is pushed by it, boids drift with it and rain and snow are blown
+/// along it.
+#[derive(Resource)]
+pub struct Wind {
+    /// Compass heading in degrees, about +Y.
+    pub heading: f32,
+    pub strength: f32,
+    /// How much the strength wavers over time, from 0 to 1.
+    pub gustiness: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            heading: 90.0,
+            strength: 6.0,
+            gustiness: 0.5,
+        }
+    }
+}
+
+impl Wind {
+    /// The wind velocity at `seconds`, gusts included.
+    pub fn at(&self, seconds: f32) -> Vec3 {
+        let heading = self.heading.to_radians();
+        let gust = 1.0 + self.gustiness * (seconds * 1.7).sin() * (seconds * 0.6 + 1.0).cos();
+        Vec3::new(heading.sin(), 0.0, heading.cos()) * self.strength * gust
+    }
+}
+
+pub fn wind_ui(ui: &mut egui::Ui, wind: &mut Wind) {
+    egui::Grid::new("wind").num_columns(2).show(ui, |ui| {
+        ui.label("Wind heading");
+        ui.add(egui::Slider::new(&mut wind.heading, 0.0..=360.0).suffix("°"));
+        ui.end_row();
+        ui.label("Wind strength");
+        ui.add(egui::Slider::new(&mut wind.strength, 0.0..=30.0).suffix(" m/s"));
+        ui.end_row();
+        ui.label("Gustiness");
+        ui.add(egui::Slider::new(&mut wind.gustiness, 0.0..=1.0));
+        ui.end_row();
+    });
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Precipitation {
+    Rain,
+    Snow,
+}
+
+/// One layer of falling particles, drawn as a single mesh rebuilt every frame.
+#[derive(Component)]
+struct PrecipitationLayer {
+    kind: Precipitation,
+    /// Positions relative to the camera, wrapped into the volume.
+    particles: Vec<Vec3>,
+}
+
+#[derive(Resource)]
+pub struct EnvironmentWindow {
+    pub is_open: bool,
+    rain: bool,
+    snow: bool,
+    /// Particles per layer.
+    rain_density: usize,
+    snow_density: usize,
+}
+
+impl Default for EnvironmentWindow {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            rain: false,
+            snow: false,
+            rain_density: 3000,
+            snow_density: 2000,
+        }
+    }
+}
+
+impl Panel for EnvironmentWindow {
+    const TITLE: &'static str = "Environment";
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.is_open
+    }
+}
+
+impl EnvironmentWindow {
+    fn wanted(&self, kind: Precipitation) -> usize {
+        match kind {
+            Precipitation::Rain if self.rain => self.rain_density,
+            Precipitation::Snow if self.snow => self.snow_density,
+            _ => 0,
+        }
+    }
+}
+
+fn environment_window_system(
+    mut contexts: PanelContexts,
+    mut window: ResMut<EnvironmentWindow>,
+    mut wind: ResMut<Wind>,
+) {
+    let EnvironmentWindow {
+        is_open,
+        rain,
+        snow,
+        rain_density,
+        snow_density,
+    } = &mut *window;
+    if !*is_open {
+        return;
+    }
+
+    egui::Window::new("Environment")
+        .open(is_open)
+        .resizable(false)
+        .show(contexts.ctx::<EnvironmentWindow>(), |ui| {
+            egui::Grid::new("precipitation")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.checkbox(rain, "Rain");
+                    ui.add_enabled(
+                        *rain,
+                        egui::Slider::new(rain_density, 100..=20_000)
+                            .logarithmic(true)
+                            .text("drops"),
+                    );
+                    ui.end_row();
+                    ui.checkbox(snow, "Snow");
+                    ui.add_enabled(
+                        *snow,
+                        egui::Slider::new(snow_density, 100..=20_000)
+                            .logarithmic(true)
+                            .text("flakes"),
+                    );
+                    ui.end_row();
+                });
+            ui.separator();
+            wind_ui(ui, &mut wind);
+            ui.weak("The wind also pushes cloth and drifts boids.");
+        });
+}
+
+fn random_particle(rng: &mut impl Rng) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(-0.5..0.5),
+        rng.gen_range(-0.5..0.5),
+        rng.gen_range(-0.5..0.5),
+    ) * VOLUME
+}
+
+/// Camera-facing quads: streaks along the fall direction for rain, small squares for snow.
+fn write_layer_mesh(mesh: &mut Mesh, layer: &PrecipitationLayer, fall: Vec3, camera: &Transform) {
+    let (right, up) = (*camera.right(), *camera.up());
+    let mut positions = Vec::with_capacity(layer.particles.len() * 4);
+    for particle in &layer.particles {
+        let center = camera.translation + *particle;
+        let [a, b, c, d] = match layer.kind {
+            Precipitation::Rain => {
+                let streak = fall.normalize_or(Vec3::NEG_Y) * 0.35;
+                let to_camera = (camera.translation - center).normalize_or(Vec3::Z);
+                let side = streak.cross(to_camera).normalize_or(right) * 0.008;
+                [
+                    center - side,
+                    center + side,
+                    center + side + streak,
+                    center - side + streak,
+                ]
+            }
+            Precipitation::Snow => {
+                let (r, u) = (right * 0.03, up * 0.03);
+                [
+                    center - r - u,
+                    center + r - u,
+                    center + r + u,
+                    center - r + u,
+                ]
+            }
+        };
+        positions.extend([a, b, c, d].map(|p| p.to_array()));
+    }
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        VertexAttributeValues::Float32x3(positions),
+    );
+    let quads = layer.particles.len() as u32;
+    let indices = (0..quads)
+        .flat_map(|quad| {
+            let base = quad * 4;
+            [base, base + 1, base + 2, base, base + 2, base + 3]
+        })
+        .collect();
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn precipitation_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    window: Res<EnvironmentWindow>,
+    wind: Res<Wind>,
+    cameras: Query<&GlobalTransform, With<ViewportCamera>>,
+    mut layers: Query<(Entity, &mut PrecipitationLayer, &Handle<Mesh>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for kind in [Precipitation::Rain, Precipitation::Snow] {
+        let exists = layers.iter().any(|(_, layer, _)| layer.kind == kind);
+        if window.wanted(kind) > 0 && !exists {
+            let color = match kind {
+                Precipitation::Rain => Color::srgba(0.7, 0.75, 0.85, 0.5),
+                Precipitation::Snow => Color::srgba(1.0, 1.0, 1.0, 0.9),
+            };
+            commands.spawn((
+                PbrBundle {
+                    mesh: meshes.add(Mesh::new(
+                        PrimitiveTopology::TriangleList,
+                        RenderAssetUsages::default(),
+                    )),
+                    material: materials.add(StandardMaterial {
+                        base_color: color,
+                        unlit: true,
+                        alpha_mode: AlphaMode::Blend,
+                        double_sided: true,
+                        cull_mode: None,
+                        ..default()
+                    }),
+                    ..default()
+                },
+                PrecipitationLayer {
+                    kind,
+                    particles: Vec::new(),
+                },
+                NotShadowCaster,
+                NoFrustumCulling,
+                Name::new(match kind {
+                    Precipitation::Rain => "Rain",
+                    Precipitation::Snow => "Snow",
+                }),
+            ));
+        }
+    }
+
+    let Ok(camera) = cameras.get_single().map(GlobalTransform::compute_transform) else {
+        return;
+    };
+    let dt = time.delta_seconds();
+    let seconds = time.elapsed_seconds();
+    let wind = wind.at(seconds);
+    let mut rng = rand::thread_rng();
+    for (entity, mut layer, mesh) in &mut layers {
+        let wanted = window.wanted(layer.kind);
+        if wanted == 0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let count = layer.particles.len();
+        if count > wanted {
+            layer.particles.truncate(wanted);
+        } else {
+            layer
+                .particles
+                .extend((count..wanted).map(|_| random_particle(&mut rng)));
+        }
+
+        let kind = layer.kind;
+        let fall = match kind {
+            Precipitation::Rain => Vec3::NEG_Y * RAIN_SPEED + wind * 0.5,
+            Precipitation::Snow => Vec3::NEG_Y * SNOW_SPEED + wind * 0.3,
+        };
+        let half = Vec3::splat(VOLUME * 0.5);
+        for (index, particle) in layer.particles.iter_mut().enumerate() {
+            let mut velocity = fall;
+            if kind == Precipitation::Snow {
+                // Flakes flutter, each on its own phase.
+                let phase = index as f32 * 0.37 + seconds;
+                velocity += Vec3::new(phase.sin(), 0.0, (phase * 1.3).cos()) * 0.4;
+            }
+            // Wrap around the camera, so the volume follows it without respawning.
+            *particle = (*particle + velocity * dt + half).rem_euclid(Vec3::splat(VOLUME)) - half;
+        }
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            write_layer_mesh(mesh, &layer, fall, &camera);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::{
+    batching::BakedBatch,
+    errors::AppError,
+    icons::Icon,
+    panels::{Menu, MenuItem, RegisterPanelExt},
+    scene::{write_scene_file, CustomMesh, SceneReader},
+    RenderCube, ViewportCamera,
+};
+
+pub const WEB_EXPORT_DIR: &str = "web_export";
+
+/// Writes the scene and a small standalone viewer into [`WEB_EXPORT_DIR`], a folder any
+/// static file server can host, for sharing an orbitable copy of what was built.
+pub struct WebExportPlugin;
+
+impl Plugin for WebExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportWeb>()
+            .add_systems(Update, export_web_system)
+            .add_menu_item(
+                MenuItem::new(Menu::File, "Export Web Viewer", |world| {
+                    world.send_event(ExportWeb);
+                })
+                .icon(Icon::Web),
+            );
+    }
+}
+
+#[derive(Event)]
+pub struct ExportWeb;
+
+#[allow(clippy::type_complexity)]
+fn export_web_system(
+    mut events: EventReader<ExportWeb>,
+    scene: SceneReader,
+    cubes: Query<
+        (
+            &GlobalTransform,
+            &Handle<StandardMaterial>,
+            &InheritedVisibility,
+            Has<CustomMesh>,
+        ),
+        With<RenderCube>,
+    >,
+    batches: Query<(&BakedBatch, &Transform, &InheritedVisibility)>,
+    camera: Query<&GlobalTransform, With<ViewportCamera>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut errors: EventWriter<AppError>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    // The viewer only draws boxes, so other meshes are left out rather than shown as boxes.
+    let mut skipped = 0;
+    let mut boxes: Vec<(Mat4, [f32; 4])> = Vec::new();
+    for (transform, material, visibility, custom) in &cubes {
+        if !visibility.get() {
+            continue;
+        }
+        if custom {
+            skipped += 1;
+            continue;
+        }
+        let color = materials
+            .get(material)
+            .map_or(Color::WHITE, |material| material.base_color);
+        boxes.push((transform.compute_matrix(), color.to_srgba().to_f32_array()));
+    }
+    for (batch, transform, visibility) in &batches {
+        if visibility.get() {
+            let placed = batch.placed_cubes(transform);
+            boxes.extend(placed.map(|cube| (cube.transform().compute_matrix(), cube.color)));
+        }
+    }
+
+    let mut center = Vec3::ZERO;
+    let mut json = String::from("{\"cubes\":[");
+    for (index, (matrix, color)) in boxes.iter().enumerate() {
+        if index > 0 {
+            json.push(',');
+        }
+        let _ = write!(
+            json,
+            "{{\"m\":{},\"c\":{}}}",
+            numbers(&matrix.to_cols_array()),
+            numbers(color),
+        );
+        center += matrix.w_axis.truncate();
+    }
+    let count = boxes.len();
+    if count > 0 {
+        center /= count as f32;
+    }
+    // The viewer orbits the cubes' centre, starting from where the viewport camera stands.
+    let eye = camera
+        .get_single()
+        .map_or(Vec3::new(0.0, 4.0, 12.0), GlobalTransform::translation);
+    let _ = write!(
+        json,
+        "],\"skipped\":{skipped},\"eye\":{},\"target\":{}}}",
+        numbers(&eye.to_array()),
+        numbers(&center.to_array()),
+    );
+
+    let dir = Path::new(WEB_EXPORT_DIR);
+    let written = std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(dir.join("index.html"), VIEWER_HTML))
+        .and_then(|()| std::fs::write(dir.join("scene.json"), json))
+        .map_err(|err| err.to_string())
+        .and_then(|()| {
+            // Travels with the export so the scene can be opened in the sandbox again.
+            let path = dir.join("scene.ron");
+            write_scene_file(&path.to_string_lossy(), &scene.capture())
+        });
+    match written {
+        Ok(()) => {
+            info!(
+                "Exported {count} cubes to {WEB_EXPORT_DIR}/, leaving out {skipped} custom \
+                 meshes; serve the folder to view them"
+            )
+        }
+        Err(err) => {
+            errors.send(
+                AppError::new(
+                    "Web Export",
+                    format!("Failed to write {WEB_EXPORT_DIR}/: {err}"),
+                )
+                .suggest("Check that the working directory is writable, then export again."),
+            );
+        }
+    }
+}
+
+fn numbers(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|value| format!("{value:.4}")).collect();
+    format!("[{}]", values.join(","))
+}
+
+/// A dependency-free WebGL2 page drawing the cubes of `scene.json`, instanced, with a drag to
+/// orbit and wheel to zoom camera.
+const VIEWER_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Sandbox scene</title>
+<style>
+  html, body { margin: 0; height: 100%; background: #1b1b1b; overflow: hidden; }
+  canvas { width: 100%; height: 100%; display: block; cursor: grab; }
+  #info { position: absolute; left: 12px; bottom: 10px; color: #aaa; font: 13px sans-serif; }
+</style>
+</head>
+<body>
+<canvas id="view"></canvas>
+<div id="info">Drag to orbit, scroll to zoom</div>
+<script>
+const canvas = document.getElementById("view");
+const gl = canvas.getContext("webgl2");
+const info = document.getElementById("info");
+
+const VERTEX = `#version 300 es
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in mat4 model;
+layout(location = 6) in vec4 color;
+uniform mat4 view_proj;
+out vec3 v_normal;
+out vec4 v_color;
+void main() {
+  v_normal = mat3(model) * normal;
+  v_color = color;
+  gl_Position = view_proj * model * vec4(position, 1.0);
+}`;
+
+const FRAGMENT = `#version 300 es
+precision highp float;
+in vec3 v_normal;
+in vec4 v_color;
+out vec4 out_color;
+void main() {
+  float light = max(dot(normalize(v_normal), normalize(vec3(0.4, 1.0, 0.6))), 0.0);
+  out_color = vec4(v_color.rgb * (0.25 + 0.75 * light), v_color.a);
+}`;
+
+function compile(type, source) {
+  const shader = gl.createShader(type);
+  gl.shaderSource(shader, source);
+  gl.compileShader(shader);
+  if (!gl.getShaderParameter(shader, gl.COMPILE_STATUS)) throw gl.getShaderInfoLog(shader);
+  return shader;
+}
+
+function cubeVertices() {
+  const data = [];
+  const faces = [[0, 1], [0, -1], [1, 1], [1, -1], [2, 1], [2, -1]];
+  for (const [axis, sign] of faces) {
+    const u = (axis + 1) % 3, v = (axis + 2) % 3;
+    const corners = [[-1, -1], [1, -1], [1, 1], [-1, -1], [1, 1], [-1, 1]];
+    for (const [a, b] of corners) {
+      const p = [0, 0, 0], n = [0, 0, 0];
+      p[axis] = 0.5 * sign; p[u] = 0.5 * a * sign; p[v] = 0.5 * b;
+      n[axis] = sign;
+      data.push(...p, ...n);
+    }
+  }
+  return new Float32Array(data);
+}
+
+function perspective(fovy, aspect, near, far) {
+  const f = 1 / Math.tan(fovy / 2), r = 1 / (near - far);
+  return [f / aspect, 0, 0, 0, 0, f, 0, 0, 0, 0, (far + near) * r, -1, 0, 0, 2 * far * near * r, 0];
+}
+
+function lookAt(eye, target) {
+  const sub = (a, b) => a.map((x, i) => x - b[i]);
+  const norm = (a) => { const l = Math.hypot(...a) || 1; return a.map((x) => x / l); };
+  const cross = (a, b) => [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]];
+  const dot = (a, b) => a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+  const f = norm(sub(target, eye)), s = norm(cross(f, [0, 1, 0])), u = cross(s, f);
+  return [s[0], u[0], -f[0], 0, s[1], u[1], -f[1], 0, s[2], u[2], -f[2], 0,
+    -dot(s, eye), -dot(u, eye), dot(f, eye), 1];
+}
+
+function multiply(a, b) {
+  const out = new Array(16).fill(0);
+  for (let c = 0; c < 4; c++)
+    for (let r = 0; r < 4; r++)
+      for (let k = 0; k < 4; k++) out[c * 4 + r] += a[k * 4 + r] * b[c * 4 + k];
+  return out;
+}
+
+fetch("scene.json").then((response) => response.json()).then((scene) => {
+  const program = gl.createProgram();
+  gl.attachShader(program, compile(gl.VERTEX_SHADER, VERTEX));
+  gl.attachShader(program, compile(gl.FRAGMENT_SHADER, FRAGMENT));
+  gl.linkProgram(program);
+  const viewProj = gl.getUniformLocation(program, "view_proj");
+
+  const vao = gl.createVertexArray();
+  gl.bindVertexArray(vao);
+  gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+  gl.bufferData(gl.ARRAY_BUFFER, cubeVertices(), gl.STATIC_DRAW);
+  gl.enableVertexAttribArray(0);
+  gl.vertexAttribPointer(0, 3, gl.FLOAT, false, 24, 0);
+  gl.enableVertexAttribArray(1);
+  gl.vertexAttribPointer(1, 3, gl.FLOAT, false, 24, 12);
+
+  const instances = new Float32Array(scene.cubes.length * 20);
+  scene.cubes.forEach((cube, i) => instances.set([...cube.m, ...cube.c], i * 20));
+  gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+  gl.bufferData(gl.ARRAY_BUFFER, instances, gl.STATIC_DRAW);
+  for (let i = 0; i < 5; i++) {
+    gl.enableVertexAttribArray(2 + i);
+    gl.vertexAttribPointer(2 + i, 4, gl.FLOAT, false, 80, i * 16);
+    gl.vertexAttribDivisor(2 + i, 1);
+  }
+
+  const offset = scene.eye.map((x, i) => x - scene.target[i]);
+  let distance = Math.hypot(...offset) || 10;
+  let yaw = Math.atan2(offset[0], offset[2]);
+  let pitch = Math.asin(offset[1] / distance);
+  const skipped = scene.skipped ? `, ${scene.skipped} custom meshes left out` : "";
+  info.textContent = `${scene.cubes.length} cubes${skipped}. Drag to orbit, scroll to zoom`;
+
+  let drag = null;
+  canvas.addEventListener("pointerdown", (e) => { drag = [e.clientX, e.clientY]; canvas.setPointerCapture(e.pointerId); });
+  canvas.addEventListener("pointerup", () => { drag = null; });
+  canvas.addEventListener("pointermove", (e) => {
+    if (!drag) return;
+    yaw -= (e.clientX - drag[0]) * 0.005;
+    pitch = Math.max(-1.5, Math.min(1.5, pitch + (e.clientY - drag[1]) * 0.005));
+    drag = [e.clientX, e.clientY];
+  });
+  canvas.addEventListener("wheel", (e) => { e.preventDefault(); distance *= Math.exp(e.deltaY * 0.001); }, { passive: false });
+
+  function frame() {
+    canvas.width = canvas.clientWidth * devicePixelRatio;
+    canvas.height = canvas.clientHeight * devicePixelRatio;
+    gl.viewport(0, 0, canvas.width, canvas.height);
+    gl.clearColor(0.1, 0.1, 0.1, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT);
+    gl.enable(gl.DEPTH_TEST);
+    gl.enable(gl.CULL_FACE);
+    const t = scene.target;
+    const eye = [t[0] + distance * Math.cos(pitch) * Math.sin(yaw), t[1] + distance * Math.sin(pitch),
+      t[2] + distance * Math.cos(pitch) * Math.cos(yaw)];
+    const proj = perspective(Math.PI / 4, canvas.width / canvas.height, 0.1, 1000);
+    gl.useProgram(program);
+    gl.uniformMatrix4fv(viewProj, false, multiply(proj, lookAt(eye, t)));
+    gl.bindVertexArray(vao);
+    gl.drawArraysInstanced(gl.TRIANGLES, 0, 36, scene.cubes.length);
+    requestAnimationFrame(frame);
+  }
+  frame();
+}).catch((err) => { info.textContent = `Failed to load scene.json: ${err}`; });
+</script>
+</body>
+</html>
+"#;
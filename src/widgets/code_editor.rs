@@ -0,0 +1,401 @@
+use bevy_egui::egui::{self, text::LayoutJob, Color32, FontId, TextFormat};
+
+/// Syntaxes understood by the highlighter.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Language {
+    Wgsl,
+    Ron,
+}
+
+impl Language {
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::Wgsl => &[
+                "alias",
+                "break",
+                "case",
+                "const",
+                "continue",
+                "default",
+                "diagnostic",
+                "discard",
+                "else",
+                "enable",
+                "false",
+                "fn",
+                "for",
+                "if",
+                "let",
+                "loop",
+                "override",
+                "return",
+                "struct",
+                "switch",
+                "true",
+                "var",
+                "while",
+            ],
+            Language::Ron => &["true", "false", "Some", "None"],
+        }
+    }
+
+    fn types(self) -> &'static [&'static str] {
+        match self {
+            Language::Wgsl => &[
+                "bool",
+                "f16",
+                "f32",
+                "i32",
+                "u32",
+                "vec2",
+                "vec3",
+                "vec4",
+                "mat2x2",
+                "mat3x3",
+                "mat4x4",
+                "array",
+                "atomic",
+                "ptr",
+                "sampler",
+                "texture_2d",
+                "texture_storage_2d",
+                "uniform",
+                "storage",
+                "read",
+                "write",
+                "read_write",
+                "function",
+                "private",
+                "workgroup",
+            ],
+            Language::Ron => &[],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Comment,
+    String,
+    Number,
+    Keyword,
+    Type,
+    Attribute,
+    Identifier,
+    Punctuation,
+}
+
+struct Palette {
+    comment: Color32,
+    string: Color32,
+    number: Color32,
+    keyword: Color32,
+    ty: Color32,
+    attribute: Color32,
+    text: Color32,
+    find_match: Color32,
+}
+
+impl Palette {
+    fn new(dark_mode: bool) -> Self {
+        if dark_mode {
+            Self {
+                comment: Color32::from_gray(120),
+                string: Color32::from_rgb(206, 145, 120),
+                number: Color32::from_rgb(181, 206, 168),
+                keyword: Color32::from_rgb(86, 156, 214),
+                ty: Color32::from_rgb(78, 201, 176),
+                attribute: Color32::from_rgb(220, 220, 170),
+                text: Color32::from_gray(212),
+                find_match: Color32::from_rgba_unmultiplied(255, 200, 0, 60),
+            }
+        } else {
+            Self {
+                comment: Color32::from_gray(128),
+                string: Color32::from_rgb(163, 21, 21),
+                number: Color32::from_rgb(9, 134, 88),
+                keyword: Color32::from_rgb(0, 0, 255),
+                ty: Color32::from_rgb(38, 127, 153),
+                attribute: Color32::from_rgb(121, 94, 38),
+                text: Color32::from_gray(30),
+                find_match: Color32::from_rgba_unmultiplied(255, 200, 0, 110),
+            }
+        }
+    }
+
+    fn color(&self, token: Token) -> Color32 {
+        match token {
+            Token::Comment => self.comment,
+            Token::String => self.string,
+            Token::Number => self.number,
+            Token::Keyword => self.keyword,
+            Token::Type => self.ty,
+            Token::Attribute => self.attribute,
+            Token::Identifier | Token::Punctuation => self.text,
+        }
+    }
+}
+
+/// Splits `source` into `(byte range, token)` spans covering the whole string.
+fn tokenize(source: &str, language: Language) -> Vec<(std::ops::Range<usize>, Token)> {
+    let bytes = source.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let token = if source[i..].starts_with("//") {
+            i = source[i..].find('\n').map_or(bytes.len(), |end| i + end);
+            Token::Comment
+        } else if source[i..].starts_with("/*") {
+            i = source[i + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |end| i + 2 + end + 2);
+            Token::Comment
+        } else if bytes[i] == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            Token::String
+        } else if bytes[i].is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                i += 1;
+            }
+            Token::Number
+        } else if bytes[i] == b'@' || bytes[i].is_ascii_alphabetic() || bytes[i] == b'_' {
+            i += 1;
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            let word = &source[start..i];
+            if word.starts_with('@') {
+                Token::Attribute
+            } else if language.keywords().contains(&word) {
+                Token::Keyword
+            } else if language.types().contains(&word) {
+                Token::Type
+            } else if language == Language::Ron && word.starts_with(char::is_uppercase) {
+                // RON struct and enum variant names.
+                Token::Type
+            } else {
+                Token::Identifier
+            }
+        } else {
+            i += source[i..].chars().next().map_or(1, char::len_utf8);
+            Token::Punctuation
+        };
+        spans.push((start..i.min(bytes.len()), token));
+    }
+    spans
+}
+
+fn find_matches(haystack: &str, needle: &str, match_case: bool) -> Vec<std::ops::Range<usize>> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    if match_case {
+        haystack
+            .match_indices(needle)
+            .map(|(start, m)| start..start + m.len())
+            .collect()
+    } else {
+        // Lowercasing ASCII keeps byte offsets identical, which is all the highlighter needs.
+        let haystack = haystack.to_ascii_lowercase();
+        let needle = needle.to_ascii_lowercase();
+        haystack
+            .match_indices(&needle)
+            .map(|(start, m)| start..start + m.len())
+            .collect()
+    }
+}
+
+fn highlight(
+    source: &str,
+    language: Language,
+    matches: &[std::ops::Range<usize>],
+    palette: &Palette,
+    font_id: &FontId,
+) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    for (range, token) in tokenize(source, language) {
+        // Split spans at find-match boundaries so matches get their own background.
+        let mut cuts = vec![range.start, range.end];
+        for m in matches {
+            for cut in [m.start, m.end] {
+                if range.start < cut && cut < range.end {
+                    cuts.push(cut);
+                }
+            }
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for pair in cuts.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            if !source.is_char_boundary(start) || !source.is_char_boundary(end) {
+                continue;
+            }
+            let matched = matches.iter().any(|m| m.start <= start && end <= m.end);
+            job.append(
+                &source[start..end],
+                0.0,
+                TextFormat {
+                    font_id: font_id.clone(),
+                    color: palette.color(token),
+                    background: if matched {
+                        palette.find_match
+                    } else {
+                        Color32::TRANSPARENT
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    job
+}
+
+#[derive(Clone, Default)]
+struct FindReplace {
+    open: bool,
+    find: String,
+    replace: String,
+    match_case: bool,
+}
+
+/// A multiline code editor with line numbers, syntax highlighting and find/replace.
+pub struct CodeEditor<'a> {
+    id_source: &'a str,
+    text: &'a mut String,
+    language: Language,
+    desired_rows: usize,
+}
+
+impl<'a> CodeEditor<'a> {
+    pub fn new(id_source: &'a str, text: &'a mut String, language: Language) -> Self {
+        Self {
+            id_source,
+            text,
+            language,
+            desired_rows: 20,
+        }
+    }
+
+    pub fn desired_rows(mut self, desired_rows: usize) -> Self {
+        self.desired_rows = desired_rows;
+        self
+    }
+
+    pub fn show(self, ui: &mut egui::Ui) -> egui::Response {
+        let id = ui.make_persistent_id(self.id_source);
+        let mut state: FindReplace = ui.data_mut(|data| data.get_temp(id).unwrap_or_default());
+
+        let toggle_find =
+            ui.input_mut(|input| input.consume_key(egui::Modifiers::COMMAND, egui::Key::F));
+        if toggle_find {
+            state.open = !state.open;
+        }
+
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut state.open, "Find/Replace");
+            ui.weak(match self.language {
+                Language::Wgsl => "WGSL",
+                Language::Ron => "RON",
+            });
+        });
+        let matches = if state.open {
+            find_replace_bar(ui, &mut state, self.text)
+        } else {
+            Vec::new()
+        };
+
+        let language = self.language;
+        let palette = Palette::new(ui.visuals().dark_mode);
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let mut layouter = |ui: &egui::Ui, source: &str, wrap_width: f32| {
+            let mut job = highlight(source, language, &matches, &palette, &font_id);
+            job.wrap.max_width = wrap_width;
+            ui.fonts(|fonts| fonts.layout_job(job))
+        };
+
+        let line_count = self.text.lines().count().max(1) + usize::from(self.text.ends_with('\n'));
+        let mut line_numbers = (1..=line_count.max(self.desired_rows))
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let gutter_width = ui.fonts(|fonts| {
+            fonts.glyph_width(&font_id, '0') * (line_count.to_string().len() as f32 + 1.0)
+        });
+
+        let response = egui::ScrollArea::vertical()
+            .id_source(id.with("scroll"))
+            .show(ui, |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut line_numbers)
+                            .font(egui::TextStyle::Monospace)
+                            .interactive(false)
+                            .frame(false)
+                            .desired_width(gutter_width)
+                            .text_color(ui.visuals().weak_text_color()),
+                    );
+                    ui.add(
+                        egui::TextEdit::multiline(self.text)
+                            .id(id.with("text"))
+                            .code_editor()
+                            .lock_focus(true)
+                            .desired_rows(self.desired_rows)
+                            .desired_width(f32::INFINITY)
+                            .layouter(&mut layouter),
+                    )
+                })
+                .inner
+            })
+            .inner;
+
+        ui.data_mut(|data| data.insert_temp(id, state));
+        response
+    }
+}
+
+/// Draws the find/replace row, applies replacements and returns the current match ranges.
+fn find_replace_bar(
+    ui: &mut egui::Ui,
+    state: &mut FindReplace,
+    text: &mut String,
+) -> Vec<std::ops::Range<usize>> {
+    let mut replace_one = false;
+    let mut replace_all = false;
+    let count = find_matches(text, &state.find, state.match_case).len();
+    egui::Grid::new(ui.next_auto_id())
+        .num_columns(3)
+        .show(ui, |ui| {
+            ui.label("Find");
+            ui.text_edit_singleline(&mut state.find);
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut state.match_case, "Aa")
+                    .on_hover_text("Match case");
+                ui.weak(format!("{count} matches"));
+            });
+            ui.end_row();
+            ui.label("Replace");
+            ui.text_edit_singleline(&mut state.replace);
+            ui.horizontal(|ui| {
+                replace_one = ui.button("Replace").clicked();
+                replace_all = ui.button("Replace all").clicked();
+            });
+            ui.end_row();
+        });
+
+    let matches = find_matches(text, &state.find, state.match_case);
+    if replace_all {
+        for m in matches.iter().rev() {
+            text.replace_range(m.clone(), &state.replace);
+        }
+    } else if let (true, Some(m)) = (replace_one, matches.first()) {
+        text.replace_range(m.clone(), &state.replace);
+    } else {
+        return matches;
+    }
+    find_matches(text, &state.find, state.match_case)
+}
@@ -0,0 +1,322 @@
+use bevy_egui::egui::{self, Color32, Stroke};
+
+/// Columns the bar is painted with; enough that constant steps look sharp.
+const PAINT_COLUMNS: usize = 128;
+const BAR_HEIGHT: f32 = 18.0;
+const HANDLE_SIZE: f32 = 8.0;
+
+/// How colours blend between neighbouring stops.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Eases in and out of each stop, so stops read as bands rather than corners.
+    Smooth,
+    /// Each stop's colour holds until the next stop.
+    Constant,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 3] = [
+        Interpolation::Linear,
+        Interpolation::Smooth,
+        Interpolation::Constant,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Interpolation::Linear => "Linear",
+            Interpolation::Smooth => "Smooth",
+            Interpolation::Constant => "Constant",
+        }
+    }
+}
+
+/// A colour at a position along a gradient, 0 to 1. Colours are linear RGB.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorStop {
+    pub t: f32,
+    pub color: [f32; 3],
+}
+
+impl ColorStop {
+    pub fn new(t: f32, color: [f32; 3]) -> Self {
+        Self { t, color }
+    }
+}
+
+/// Colour stops kept sorted by position, and how to blend between them.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Gradient {
+    stops: Vec<ColorStop>,
+    pub interpolation: Interpolation,
+}
+
+impl Gradient {
+    /// A linear gradient through `stops`, which need not be sorted. An empty list gives black.
+    pub fn new(stops: impl IntoIterator<Item = ColorStop>) -> Self {
+        let mut stops: Vec<ColorStop> = stops
+            .into_iter()
+            .map(|stop| ColorStop::new(stop.t.clamp(0.0, 1.0), stop.color))
+            .collect();
+        stops.sort_by(|a, b| a.t.total_cmp(&b.t));
+        if stops.is_empty() {
+            stops.push(ColorStop::new(0.0, [0.0; 3]));
+        }
+        Self {
+            stops,
+            interpolation: Interpolation::Linear,
+        }
+    }
+
+    pub fn two(start: [f32; 3], end: [f32; 3]) -> Self {
+        Self::new([ColorStop::new(0.0, start), ColorStop::new(1.0, end)])
+    }
+
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    /// The colour at `t`, clamped to 0 to 1; before the first stop and after the last their
+    /// colours hold.
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        let t = t.clamp(0.0, 1.0);
+        let next = self.stops.partition_point(|stop| stop.t <= t);
+        let (Some(before), Some(after)) = (
+            next.checked_sub(1).map(|index| self.stops[index]),
+            self.stops.get(next),
+        ) else {
+            return self.stops[next.min(self.stops.len() - 1)].color;
+        };
+        let span = after.t - before.t;
+        let mut blend = if span > f32::EPSILON {
+            (t - before.t) / span
+        } else {
+            0.0
+        };
+        blend = match self.interpolation {
+            Interpolation::Linear => blend,
+            Interpolation::Smooth => blend * blend * (3.0 - 2.0 * blend),
+            Interpolation::Constant => 0.0,
+        };
+        std::array::from_fn(|channel| {
+            before.color[channel] + (after.color[channel] - before.color[channel]) * blend
+        })
+    }
+
+    /// `count` colours evenly spaced from 0 to 1, for handing the gradient to a shader.
+    pub fn samples(&self, count: usize) -> Vec<[f32; 3]> {
+        let last = count.saturating_sub(1).max(1) as f32;
+        (0..count)
+            .map(|index| self.sample(index as f32 / last))
+            .collect()
+    }
+
+    pub fn sample_color32(&self, t: f32) -> Color32 {
+        let [r, g, b] = self.sample(t);
+        egui::Rgba::from_rgb(r, g, b).into()
+    }
+
+    /// Paints the gradient over `rect`, running from 0 to 1 in `direction`.
+    pub fn paint(&self, painter: &egui::Painter, rect: egui::Rect, direction: egui::Direction) {
+        let mut mesh = egui::Mesh::default();
+        for column in 0..=PAINT_COLUMNS {
+            let t = column as f32 / PAINT_COLUMNS as f32;
+            let color = self.sample_color32(t);
+            let (a, b) = match direction {
+                egui::Direction::LeftToRight => {
+                    let x = egui::lerp(rect.x_range(), t);
+                    (egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom()))
+                }
+                egui::Direction::RightToLeft => {
+                    let x = egui::lerp(rect.x_range(), 1.0 - t);
+                    (egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom()))
+                }
+                egui::Direction::TopDown => {
+                    let y = egui::lerp(rect.y_range(), t);
+                    (egui::pos2(rect.left(), y), egui::pos2(rect.right(), y))
+                }
+                egui::Direction::BottomUp => {
+                    let y = egui::lerp(rect.y_range(), 1.0 - t);
+                    (egui::pos2(rect.left(), y), egui::pos2(rect.right(), y))
+                }
+            };
+            mesh.colored_vertex(a, color);
+            mesh.colored_vertex(b, color);
+            if column > 0 {
+                let first = (column as u32 - 1) * 2;
+                mesh.add_triangle(first, first + 1, first + 2);
+                mesh.add_triangle(first + 1, first + 3, first + 2);
+            }
+        }
+        painter.add(mesh);
+    }
+
+    /// Moves stop `index` to `t`, keeping the stops sorted; returns its new index.
+    fn move_stop(&mut self, index: usize, t: f32) -> usize {
+        let mut stop = self.stops.remove(index);
+        stop.t = t.clamp(0.0, 1.0);
+        self.insert(stop)
+    }
+
+    fn insert(&mut self, stop: ColorStop) -> usize {
+        let index = self.stops.partition_point(|other| other.t <= stop.t);
+        self.stops.insert(index, stop);
+        index
+    }
+}
+
+/// The selected stop, and whether its handle is being dragged.
+#[derive(Clone, Copy, Default)]
+struct EditorState {
+    selected: usize,
+    dragging: bool,
+}
+
+/// Edits a [`Gradient`]: a bar with a handle under each stop. Drag a handle to move its stop,
+/// click one to select it for the colour and position fields below, click the bar to add a
+/// stop there, and right-click a handle to remove it. At least one stop always remains.
+pub struct GradientEditor<'a> {
+    id_source: &'a str,
+    gradient: &'a mut Gradient,
+    width: f32,
+}
+
+impl<'a> GradientEditor<'a> {
+    pub fn new(id_source: &'a str, gradient: &'a mut Gradient) -> Self {
+        Self {
+            id_source,
+            gradient,
+            width: 200.0,
+        }
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn show(self, ui: &mut egui::Ui) -> egui::Response {
+        let id = ui.make_persistent_id(self.id_source);
+        let gradient = self.gradient;
+        let before = gradient.clone();
+        let EditorState {
+            mut selected,
+            mut dragging,
+        } = ui.data_mut(|data| data.get_temp(id).unwrap_or_default());
+        selected = selected.min(gradient.stops.len() - 1);
+
+        let mut inner = ui.vertical(|ui| {
+            let size = egui::vec2(self.width, BAR_HEIGHT + HANDLE_SIZE + 2.0);
+            let (rect, bar) = ui.allocate_exact_size(size, egui::Sense::click());
+            let bar_rect =
+                egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), BAR_HEIGHT));
+            let t_at = |x: f32| ((x - bar_rect.left()) / bar_rect.width()).clamp(0.0, 1.0);
+
+            if bar.clicked() {
+                if let Some(pos) = bar.interact_pointer_pos() {
+                    let t = t_at(pos.x);
+                    selected = gradient.insert(ColorStop::new(t, gradient.sample(t)));
+                }
+            }
+
+            let handle_rect = |t: f32| {
+                let x = egui::lerp(bar_rect.x_range(), t);
+                egui::Rect::from_center_size(
+                    egui::pos2(x, bar_rect.bottom() + HANDLE_SIZE / 2.0 + 1.0),
+                    egui::vec2(HANDLE_SIZE + 4.0, HANDLE_SIZE + 2.0),
+                )
+            };
+            let mut removed = None;
+            for (index, stop) in gradient.stops.iter().enumerate() {
+                let handle = ui
+                    .interact(
+                        handle_rect(stop.t),
+                        id.with(("stop", index)),
+                        egui::Sense::click_and_drag(),
+                    )
+                    .on_hover_text(format!("{:.2}; right-click to remove", stop.t));
+                if handle.clicked() || handle.drag_started() {
+                    selected = index;
+                }
+                dragging |= handle.drag_started();
+                if handle.secondary_clicked() {
+                    removed = Some(index);
+                }
+            }
+            // Followed here rather than through the handle, whose id changes when its stop
+            // passes another.
+            if dragging {
+                let (down, pos) =
+                    ui.input(|input| (input.pointer.primary_down(), input.pointer.latest_pos()));
+                match pos.filter(|_| down) {
+                    Some(pos) => selected = gradient.move_stop(selected, t_at(pos.x)),
+                    None => dragging = false,
+                }
+            }
+            if let Some(index) = removed.filter(|_| gradient.stops.len() > 1) {
+                gradient.stops.remove(index);
+                selected = selected.min(gradient.stops.len() - 1);
+            }
+
+            if ui.is_rect_visible(rect) {
+                let painter = ui.painter();
+                gradient.paint(painter, bar_rect, egui::Direction::LeftToRight);
+                painter.rect_stroke(bar_rect, 0.0, ui.visuals().widgets.inactive.bg_stroke);
+                for (index, stop) in gradient.stops.iter().enumerate() {
+                    let handle = handle_rect(stop.t);
+                    let tip = egui::pos2(handle.center().x, bar_rect.bottom());
+                    let points = vec![
+                        tip,
+                        egui::pos2(handle.center().x + HANDLE_SIZE / 2.0, handle.bottom()),
+                        egui::pos2(handle.center().x - HANDLE_SIZE / 2.0, handle.bottom()),
+                    ];
+                    let stroke = if index == selected {
+                        Stroke::new(2.0, ui.visuals().selection.stroke.color)
+                    } else {
+                        ui.visuals().widgets.inactive.fg_stroke
+                    };
+                    painter.add(egui::Shape::convex_polygon(
+                        points,
+                        gradient.sample_color32(stop.t),
+                        stroke,
+                    ));
+                }
+            }
+
+            ui.horizontal(|ui| {
+                let stop = &mut gradient.stops[selected];
+                ui.color_edit_button_rgb(&mut stop.color);
+                let mut t = stop.t;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut t)
+                            .range(0.0..=1.0)
+                            .speed(0.005)
+                            .fixed_decimals(2),
+                    )
+                    .changed()
+                {
+                    selected = gradient.move_stop(selected, t);
+                }
+                egui::ComboBox::from_id_source(id.with("interpolation"))
+                    .selected_text(gradient.interpolation.label())
+                    .show_ui(ui, |ui| {
+                        for interpolation in Interpolation::ALL {
+                            ui.selectable_value(
+                                &mut gradient.interpolation,
+                                interpolation,
+                                interpolation.label(),
+                            );
+                        }
+                    });
+            });
+        });
+
+        ui.data_mut(|data| data.insert_temp(id, EditorState { selected, dragging }));
+        if *gradient != before {
+            inner.response.mark_changed();
+        }
+        inner.response
+    }
+}
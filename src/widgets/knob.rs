@@ -0,0 +1,146 @@
+use std::{f32::consts::PI, ops::RangeInclusive};
+
+use bevy_egui::egui::{self, Response, Sense, Stroke, Ui, Widget};
+
+/// Fraction of the range covered by one keyboard step or scroll notch.
+const STEP: f32 = 0.01;
+/// Sweep of the knob, measured from straight down.
+const SWEEP: f32 = 1.5 * PI;
+
+/// A rotary knob. Drag vertically, scroll, or use the arrow keys (Home/End for the limits)
+/// while focused to change the value.
+pub struct Knob<'a> {
+    value: &'a mut f32,
+    range: RangeInclusive<f32>,
+    diameter: f32,
+    logarithmic: bool,
+    suffix: String,
+}
+
+impl<'a> Knob<'a> {
+    pub fn new(value: &'a mut f32, range: RangeInclusive<f32>) -> Self {
+        Self {
+            value,
+            range,
+            diameter: 36.0,
+            logarithmic: false,
+            suffix: String::new(),
+        }
+    }
+
+    pub fn diameter(mut self, diameter: f32) -> Self {
+        self.diameter = diameter;
+        self
+    }
+
+    /// Maps the knob angle logarithmically, for ranges spanning several orders of magnitude.
+    pub fn logarithmic(mut self, logarithmic: bool) -> Self {
+        self.logarithmic = logarithmic;
+        self
+    }
+
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    fn normalized(&self, value: f32) -> f32 {
+        let (min, max) = (*self.range.start(), *self.range.end());
+        let t = if self.logarithmic {
+            (value - min + 1.0).ln() / (max - min + 1.0).ln()
+        } else {
+            (value - min) / (max - min)
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+    fn denormalized(&self, t: f32) -> f32 {
+        let (min, max) = (*self.range.start(), *self.range.end());
+        let t = t.clamp(0.0, 1.0);
+        if self.logarithmic {
+            min + (max - min + 1.0).powf(t) - 1.0
+        } else {
+            min + t * (max - min)
+        }
+    }
+}
+
+impl Widget for Knob<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = egui::vec2(self.diameter, self.diameter);
+        let (rect, mut response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+
+        let mut t = self.normalized(*self.value);
+        let before = t;
+        if response.dragged() {
+            t -= response.drag_delta().y / 200.0;
+        }
+        if response.hovered() {
+            let scroll = ui.input(|input| input.raw_scroll_delta.y);
+            t += scroll.signum() * STEP * f32::from(scroll != 0.0);
+        }
+        if response.has_focus() {
+            ui.input(|input| {
+                for (key, delta) in [
+                    (egui::Key::ArrowUp, STEP),
+                    (egui::Key::ArrowRight, STEP),
+                    (egui::Key::ArrowDown, -STEP),
+                    (egui::Key::ArrowLeft, -STEP),
+                ] {
+                    if input.key_pressed(key) {
+                        t += delta;
+                    }
+                }
+                if input.key_pressed(egui::Key::Home) {
+                    t = 0.0;
+                }
+                if input.key_pressed(egui::Key::End) {
+                    t = 1.0;
+                }
+            });
+        }
+        if t != before {
+            *self.value = self.denormalized(t);
+            response.mark_changed();
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter();
+            let center = rect.center();
+            let radius = rect.width() / 2.0 - 2.0;
+            painter.circle(center, radius, visuals.bg_fill, visuals.bg_stroke);
+
+            let angle_at = |t: f32| PI / 2.0 + (PI - SWEEP) / 2.0 + t * SWEEP;
+            let point_at = |angle: f32, r: f32| center + egui::vec2(angle.cos(), angle.sin()) * r;
+            let t = self.normalized(*self.value);
+            let arc: Vec<_> = (0..=32)
+                .map(|i| point_at(angle_at(i as f32 / 32.0 * t), radius - 2.0))
+                .collect();
+            painter.add(egui::Shape::line(
+                arc,
+                Stroke::new(3.0, ui.visuals().selection.bg_fill),
+            ));
+            painter.line_segment(
+                [center, point_at(angle_at(t), radius - 4.0)],
+                Stroke::new(2.0, visuals.fg_stroke.color),
+            );
+            if response.has_focus() {
+                painter.circle_stroke(center, radius + 1.5, ui.visuals().selection.stroke);
+            }
+        }
+
+        let text = format!("{:.2}{}", *self.value, self.suffix);
+        if response.dragged() || response.has_focus() && response.changed() {
+            egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), response.id, |ui| {
+                ui.label(&text);
+            });
+            response
+        } else {
+            response.on_hover_text(text)
+        }
+    }
+}
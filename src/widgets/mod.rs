@@ -0,0 +1,15 @@
+//! Reusable egui widgets shared by several panels.
+
+pub mod code_editor;
+pub mod gradient;
+pub mod knob;
+pub mod streamed_texture;
+pub mod trackball;
+pub mod xy_pad;
+
+pub use code_editor::{CodeEditor, Language};
+pub use gradient::{ColorStop, Gradient, GradientEditor, Interpolation};
+pub use knob::Knob;
+pub use streamed_texture::StreamedTexture;
+pub use trackball::Trackball;
+pub use xy_pad::XyPad;
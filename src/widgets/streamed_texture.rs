@@ -0,0 +1,72 @@
+use bevy_egui::egui::{ColorImage, Context, TextureHandle, TextureOptions};
+
+/// An egui texture for images that change often (previews, scopes, flipbooks). Several
+/// updates in one frame are coalesced into the last one, and [`StreamedTexture::upload`] only
+/// sends the rectangle of pixels that changed since the previous upload.
+pub struct StreamedTexture {
+    name: String,
+    options: TextureOptions,
+    handle: Option<TextureHandle>,
+    /// What the GPU copy currently holds, to diff the next image against.
+    uploaded: Option<ColorImage>,
+    pending: Option<ColorImage>,
+}
+
+impl StreamedTexture {
+    pub fn new(name: impl Into<String>, options: TextureOptions) -> Self {
+        Self {
+            name: name.into(),
+            options,
+            handle: None,
+            uploaded: None,
+            pending: None,
+        }
+    }
+
+    /// Queues `image` for the next upload, replacing any image queued before it.
+    pub fn update(&mut self, image: ColorImage) {
+        self.pending = Some(image);
+    }
+
+    /// Uploads the queued image, if any, and returns the texture once there is one.
+    pub fn upload(&mut self, ctx: &Context) -> Option<&TextureHandle> {
+        if let Some(image) = self.pending.take() {
+            match (&mut self.handle, &self.uploaded) {
+                (Some(handle), Some(uploaded)) if uploaded.size == image.size => {
+                    if let Some([x, y, width, height]) = changed_region(uploaded, &image) {
+                        let region = ColorImage {
+                            size: [width, height],
+                            pixels: (y..y + height)
+                                .flat_map(|row| {
+                                    let start = row * image.size[0] + x;
+                                    image.pixels[start..start + width].iter().copied()
+                                })
+                                .collect(),
+                        };
+                        handle.set_partial([x, y], region, self.options);
+                    }
+                }
+                (Some(handle), _) => handle.set(image.clone(), self.options),
+                (None, _) => {
+                    self.handle = Some(ctx.load_texture(&self.name, image.clone(), self.options))
+                }
+            }
+            self.uploaded = Some(image);
+        }
+        self.handle.as_ref()
+    }
+}
+
+/// `[x, y, width, height]` of the smallest rectangle containing every differing pixel.
+fn changed_region(before: &ColorImage, after: &ColorImage) -> Option<[usize; 4]> {
+    let width = after.size[0];
+    let (mut min, mut max) = ([usize::MAX; 2], [0; 2]);
+    for (index, (a, b)) in before.pixels.iter().zip(&after.pixels).enumerate() {
+        if a != b {
+            let (x, y) = (index % width, index / width);
+            min = [min[0].min(x), min[1].min(y)];
+            max = [max[0].max(x), max[1].max(y)];
+        }
+    }
+    (min[0] != usize::MAX).then(|| [min[0], min[1], max[0] - min[0] + 1, max[1] - min[1] + 1])
+}
@@ -0,0 +1,197 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::math::{Mat3, Quat, Vec3};
+use bevy_egui::egui::{self, Color32, Response, Sense, Stroke, Ui, Widget};
+
+/// Rotation of one keyboard step.
+const STEP: f32 = FRAC_PI_2 / 6.0;
+/// Segments drawing each ring.
+const RING_SEGMENTS: usize = 48;
+
+/// A trackball for editing a rotation: drag to turn it as if rolling a ball under the pointer,
+/// with drags starting outside the ball spinning it about the view axis. The object's axes and
+/// the rings around them show its orientation. Shift-click snaps to the nearest orientation
+/// whose axes line up with the world's, double-click resets it, and the arrow keys turn it in
+/// 15° steps while focused.
+pub struct Trackball<'a> {
+    rotation: &'a mut Quat,
+    diameter: f32,
+    view: Quat,
+}
+
+impl<'a> Trackball<'a> {
+    pub fn new(rotation: &'a mut Quat) -> Self {
+        Self {
+            rotation,
+            diameter: 96.0,
+            view: Quat::IDENTITY,
+        }
+    }
+
+    pub fn diameter(mut self, diameter: f32) -> Self {
+        self.diameter = diameter;
+        self
+    }
+
+    /// The orientation of the camera the ball is seen through, so dragging right turns the
+    /// object rightwards as it appears in that camera's view.
+    pub fn view(mut self, view: Quat) -> Self {
+        self.view = view;
+        self
+    }
+}
+
+/// `pos` on the unit ball of `rect`, in view space: x right, y up, z towards the viewer.
+fn ball_point(rect: egui::Rect, pos: egui::Pos2) -> Vec3 {
+    let local = (pos - rect.center()) / (rect.width() / 2.0);
+    let point = Vec3::new(local.x, -local.y, 0.0);
+    let length_squared = point.length_squared();
+    if length_squared <= 1.0 {
+        Vec3::new(point.x, point.y, (1.0 - length_squared).sqrt())
+    } else {
+        point / length_squared.sqrt()
+    }
+}
+
+/// The orientation nearest `rotation` whose axes each lie along a world axis, one of 24.
+fn snap_to_axes(rotation: Quat) -> Quat {
+    let nearest_axis = |direction: Vec3, taken: Option<usize>| {
+        let (index, component) = direction
+            .to_array()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| Some(*index) != taken)
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .unwrap_or((0, 1.0));
+        let mut axis = Vec3::ZERO;
+        axis[index] = component.signum();
+        (index, axis)
+    };
+    let (taken, x) = nearest_axis(rotation * Vec3::X, None);
+    let (_, y) = nearest_axis(rotation * Vec3::Y, Some(taken));
+    Quat::from_mat3(&Mat3::from_cols(x, y, x.cross(y)))
+}
+
+impl Widget for Trackball<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = egui::vec2(self.diameter, self.diameter);
+        let (rect, mut response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+
+        // Turns are found in view space and applied in world space.
+        let before = *self.rotation;
+        let mut turn = Quat::IDENTITY;
+        if let (true, Some(pos)) = (response.dragged(), response.interact_pointer_pos()) {
+            let delta = response.drag_delta();
+            if delta != egui::Vec2::ZERO {
+                turn =
+                    Quat::from_rotation_arc(ball_point(rect, pos - delta), ball_point(rect, pos));
+            }
+        }
+        if response.has_focus() {
+            ui.input(|input| {
+                for (key, axis, angle) in [
+                    (egui::Key::ArrowRight, Vec3::Y, STEP),
+                    (egui::Key::ArrowLeft, Vec3::Y, -STEP),
+                    (egui::Key::ArrowUp, Vec3::X, -STEP),
+                    (egui::Key::ArrowDown, Vec3::X, STEP),
+                ] {
+                    if input.key_pressed(key) {
+                        turn = Quat::from_axis_angle(axis, angle) * turn;
+                    }
+                }
+            });
+        }
+        let mut rotation = (self.view * turn * self.view.inverse() * before).normalize();
+        let shift = ui.input(|input| input.modifiers.shift);
+        if response.double_clicked() {
+            rotation = Quat::IDENTITY;
+        } else if shift && (response.clicked() || response.drag_stopped()) {
+            rotation = snap_to_axes(rotation);
+        }
+        if !rotation.abs_diff_eq(before, 1e-6) {
+            *self.rotation = rotation;
+            response.mark_changed();
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter();
+            let center = rect.center();
+            let radius = rect.width() / 2.0 - 2.0;
+            painter.circle(
+                center,
+                radius,
+                ui.visuals().extreme_bg_color,
+                visuals.bg_stroke,
+            );
+
+            // The object's axes in view space, drawn back to front.
+            let in_view = self.view.inverse() * *self.rotation;
+            let to_screen = |point: Vec3| center + egui::vec2(point.x, -point.y) * radius;
+            let axes = [
+                (Vec3::X, Color32::from_rgb(230, 80, 80)),
+                (Vec3::Y, Color32::from_rgb(100, 200, 90)),
+                (Vec3::Z, Color32::from_rgb(80, 140, 240)),
+            ];
+            let shade = |color: Color32, depth: f32| color.gamma_multiply(0.55 + 0.45 * depth);
+            for (axis, color) in axes {
+                let normal = in_view * axis;
+                let (u, v) = normal.any_orthonormal_pair();
+                let ring: Vec<Vec3> = (0..=RING_SEGMENTS)
+                    .map(|i| {
+                        let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                        u * angle.cos() + v * angle.sin()
+                    })
+                    .collect();
+                for pair in ring.windows(2) {
+                    let depth = (pair[0].z + pair[1].z) / 2.0;
+                    let width = if depth < 0.0 { 1.0 } else { 1.5 };
+                    painter.line_segment(
+                        [to_screen(pair[0]), to_screen(pair[1])],
+                        Stroke::new(width, shade(color, depth).gamma_multiply(0.6)),
+                    );
+                }
+            }
+            // Positive ends get the larger dots.
+            let mut ends: Vec<(Vec3, Color32, f32)> = axes
+                .iter()
+                .flat_map(|&(axis, color)| {
+                    [(in_view * axis, color, 3.5), (in_view * -axis, color, 2.0)]
+                })
+                .collect();
+            ends.sort_by(|a, b| a.0.z.total_cmp(&b.0.z));
+            for (end, color, dot) in ends {
+                let color = shade(color, end.z);
+                painter.line_segment(
+                    [center, to_screen(end * 0.8)],
+                    Stroke::new(2.0, color.gamma_multiply(0.8)),
+                );
+                painter.circle_filled(to_screen(end * 0.8), dot, color);
+            }
+            if response.has_focus() {
+                painter.circle_stroke(center, radius + 1.5, ui.visuals().selection.stroke);
+            }
+        }
+
+        let (x, y, z) = self.rotation.to_euler(bevy::math::EulerRot::XYZ);
+        let text = format!(
+            "{:.0}°, {:.0}°, {:.0}°",
+            x.to_degrees(),
+            y.to_degrees(),
+            z.to_degrees()
+        );
+        if response.dragged() {
+            egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), response.id, |ui| {
+                ui.label(&text);
+            });
+            response
+        } else {
+            response.on_hover_text(format!(
+                "{text}\nDrag to rotate, Shift-click to snap to 90°, double-click to reset"
+            ))
+        }
+    }
+}
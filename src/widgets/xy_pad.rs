@@ -0,0 +1,125 @@
+use std::ops::RangeInclusive;
+
+use bevy_egui::egui::{self, Response, Sense, Stroke, Ui, Widget};
+
+/// Fraction of the range covered by one keyboard step or scroll notch.
+const STEP: f32 = 0.01;
+
+/// A 2D pad editing two values at once. Click or drag to place the point, scroll to nudge it,
+/// or use the arrow keys while focused. Y grows upwards.
+pub struct XyPad<'a> {
+    value: &'a mut egui::Vec2,
+    x_range: RangeInclusive<f32>,
+    y_range: RangeInclusive<f32>,
+    size: egui::Vec2,
+}
+
+impl<'a> XyPad<'a> {
+    pub fn new(
+        value: &'a mut egui::Vec2,
+        x_range: RangeInclusive<f32>,
+        y_range: RangeInclusive<f32>,
+    ) -> Self {
+        Self {
+            value,
+            x_range,
+            y_range,
+            size: egui::vec2(120.0, 120.0),
+        }
+    }
+
+    pub fn size(mut self, size: egui::Vec2) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+fn normalize(value: f32, range: &RangeInclusive<f32>) -> f32 {
+    ((value - range.start()) / (range.end() - range.start())).clamp(0.0, 1.0)
+}
+
+fn denormalize(t: f32, range: &RangeInclusive<f32>) -> f32 {
+    range.start() + t.clamp(0.0, 1.0) * (range.end() - range.start())
+}
+
+impl Widget for XyPad<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, mut response) = ui.allocate_exact_size(self.size, Sense::click_and_drag());
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+
+        let mut t = egui::vec2(
+            normalize(self.value.x, &self.x_range),
+            normalize(self.value.y, &self.y_range),
+        );
+        let before = t;
+        if let Some(pos) = response.interact_pointer_pos() {
+            if response.is_pointer_button_down_on() {
+                let local = (pos - rect.min) / rect.size();
+                t = egui::vec2(local.x, 1.0 - local.y);
+            }
+        }
+        if response.hovered() {
+            let scroll = ui.input(|input| input.raw_scroll_delta);
+            t.x += scroll.x.signum() * STEP * f32::from(scroll.x != 0.0);
+            t.y += scroll.y.signum() * STEP * f32::from(scroll.y != 0.0);
+        }
+        if response.has_focus() {
+            ui.input(|input| {
+                for (key, delta) in [
+                    (egui::Key::ArrowRight, egui::vec2(STEP, 0.0)),
+                    (egui::Key::ArrowLeft, egui::vec2(-STEP, 0.0)),
+                    (egui::Key::ArrowUp, egui::vec2(0.0, STEP)),
+                    (egui::Key::ArrowDown, egui::vec2(0.0, -STEP)),
+                ] {
+                    if input.key_pressed(key) {
+                        t += delta;
+                    }
+                }
+            });
+        }
+        if t != before {
+            *self.value = egui::vec2(
+                denormalize(t.x, &self.x_range),
+                denormalize(t.y, &self.y_range),
+            );
+            response.mark_changed();
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact(&response);
+            let painter = ui.painter_at(rect);
+            painter.rect(rect, 2.0, ui.visuals().extreme_bg_color, visuals.bg_stroke);
+            let grid = Stroke::new(1.0, ui.visuals().faint_bg_color);
+            painter.hline(rect.x_range(), rect.center().y, grid);
+            painter.vline(rect.center().x, rect.y_range(), grid);
+
+            let t = egui::vec2(
+                normalize(self.value.x, &self.x_range),
+                normalize(self.value.y, &self.y_range),
+            );
+            let point = egui::pos2(
+                rect.left() + t.x * rect.width(),
+                rect.bottom() - t.y * rect.height(),
+            );
+            let crosshair = Stroke::new(1.0, visuals.fg_stroke.color.gamma_multiply(0.4));
+            painter.hline(rect.x_range(), point.y, crosshair);
+            painter.vline(point.x, rect.y_range(), crosshair);
+            painter.circle_filled(point, 4.0, ui.visuals().selection.bg_fill);
+            if response.has_focus() {
+                painter.rect_stroke(rect.shrink(1.0), 2.0, ui.visuals().selection.stroke);
+            }
+        }
+
+        let text = format!("{:.2}, {:.2}", self.value.x, self.value.y);
+        if response.dragged() {
+            egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), response.id, |ui| {
+                ui.label(&text);
+            });
+            response
+        } else {
+            response.on_hover_text(text)
+        }
+    }
+}
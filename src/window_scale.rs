@@ -0,0 +1,55 @@
+use bevy::{prelude::*, window::WindowBackendScaleFactorChanged};
+use bevy_egui::EguiContexts;
+
+use crate::settings::Settings;
+
+/// Scales each window's UI for the monitor it is on. bevy_egui multiplies every window's own
+/// scale factor with the single `EguiSettings::scale_factor`, so that is left at 1 and the
+/// HiDPI setting is applied per window instead: following the monitor when on, and as a scale
+/// factor override of 1 when off. A window dragged to a monitor with another scale factor, or
+/// opened on one, then gets UI that is neither blurry nor mis-sized.
+pub struct WindowScalePlugin;
+
+impl Plugin for WindowScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (apply_window_scale_system, rescaled_windows_system).chain(),
+        );
+    }
+}
+
+fn apply_window_scale_system(settings: Res<Settings>, mut windows: Query<&mut Window>) {
+    let scale_override = (!settings.graphics.hidpi_scaling).then_some(1.0);
+    for mut window in &mut windows {
+        if !settings.is_changed() && !window.is_added() {
+            continue;
+        }
+        if window.resolution.scale_factor_override() != scale_override {
+            window.resolution.set_scale_factor_override(scale_override);
+        }
+    }
+}
+
+/// The OS reports a new scale factor when a window moves to another monitor. bevy_egui picks
+/// it up on its next frame; the window is repainted at once rather than on the next input.
+fn rescaled_windows_system(
+    mut events: EventReader<WindowBackendScaleFactorChanged>,
+    windows: Query<&Window>,
+    mut contexts: EguiContexts,
+) {
+    for event in events.read() {
+        let Ok(window) = windows.get(event.window) else {
+            continue;
+        };
+        info!(
+            "Window \"{}\" moved to a monitor with scale factor {}; its UI is drawn at {}",
+            window.title,
+            event.scale_factor,
+            window.scale_factor()
+        );
+        if let Some(ctx) = contexts.try_ctx_for_window_mut(event.window) {
+            ctx.request_repaint();
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::scene::{LoadScene, SaveScene, SceneFile, SceneReader, SCENE_PATH};
+
+const APP_NAME: &str = "bevy_egui sandbox";
+/// Seconds between comparisons of the scene with the one last saved or loaded.
+const CHECK_INTERVAL: f32 = 1.0;
+
+/// Keeps the primary window title in step with the open scene: its name, a `*` while it has
+/// unsaved changes, and the progress of long-running tasks. winit has no taskbar progress API,
+/// so the percentage leads the title, which is what the taskbar shows.
+pub struct WindowTitlePlugin;
+
+impl Plugin for WindowTitlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneStatus>()
+            .init_resource::<TaskProgress>()
+            .add_systems(
+                PostUpdate,
+                (scene_status_system, window_title_system).chain(),
+            );
+    }
+}
+
+/// Progress of long-running tasks, reported each frame while they run.
+#[derive(Default, Resource)]
+pub struct TaskProgress {
+    tasks: Vec<RunningTask>,
+    /// Those reported last frame, for the Tasks window.
+    shown: Vec<RunningTask>,
+    /// Tasks the Tasks window asked to stop.
+    cancelled: Vec<&'static str>,
+}
+
+#[derive(Clone, Copy)]
+pub struct RunningTask {
+    pub name: &'static str,
+    pub fraction: f32,
+    /// Whether the task checks [`TaskProgress::take_cancel`], so offering to stop it works.
+    pub cancellable: bool,
+}
+
+impl TaskProgress {
+    /// Reports `fraction` (0 to 1) of `task` as done this frame.
+    pub fn report(&mut self, task: &'static str, fraction: f32) {
+        self.push(task, fraction, false);
+    }
+
+    /// Like [`report`](Self::report), for a task that stops when asked to.
+    pub fn report_cancellable(&mut self, task: &'static str, fraction: f32) {
+        self.push(task, fraction, true);
+    }
+
+    fn push(&mut self, name: &'static str, fraction: f32, cancellable: bool) {
+        self.tasks.push(RunningTask {
+            name,
+            fraction: fraction.clamp(0.0, 1.0),
+            cancellable,
+        });
+    }
+
+    /// The tasks running as of last frame.
+    pub fn running(&self) -> &[RunningTask] {
+        &self.shown
+    }
+
+    pub fn cancel(&mut self, task: &'static str) {
+        self.cancelled.push(task);
+    }
+
+    /// Whether `task` was asked to stop since the last call.
+    pub fn take_cancel(&mut self, task: &'static str) -> bool {
+        let before = self.cancelled.len();
+        self.cancelled.retain(|name| *name != task);
+        self.cancelled.len() != before
+    }
+}
+
+/// The scene as last saved or loaded, to tell whether the current one has unsaved changes.
+#[derive(Default, Resource)]
+struct SceneStatus {
+    /// `None` until the scene is first saved or loaded.
+    baseline: Option<SceneFile>,
+    dirty: bool,
+    since_check: f32,
+}
+
+fn scene_status_system(
+    mut saves: EventReader<SaveScene>,
+    mut loads: EventReader<LoadScene>,
+    mut status: ResMut<SceneStatus>,
+    scene: SceneReader,
+    time: Res<Time<Real>>,
+) {
+    // Both have been applied by now: the save wrote this capture, and the loaded entities exist.
+    if saves.read().count() + loads.read().count() > 0 {
+        status.baseline = Some(scene.capture());
+        status.dirty = false;
+        status.since_check = 0.0;
+        return;
+    }
+    status.since_check += time.delta_seconds();
+    if status.since_check < CHECK_INTERVAL {
+        return;
+    }
+    status.since_check = 0.0;
+    let current = scene.capture();
+    let dirty = match &status.baseline {
+        Some(baseline) => *baseline != current,
+        None => !current.entities.is_empty(),
+    };
+    if status.dirty != dirty {
+        status.dirty = dirty;
+    }
+}
+
+fn window_title_system(
+    status: Res<SceneStatus>,
+    mut progress: ResMut<TaskProgress>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let progress = progress.bypass_change_detection();
+    progress.shown = std::mem::take(&mut progress.tasks);
+    let tasks = &progress.shown;
+    progress
+        .cancelled
+        .retain(|name| tasks.iter().any(|task| task.name == *name));
+    let name = match status.baseline {
+        Some(_) => SCENE_PATH,
+        None => "Untitled",
+    };
+    let marker = if status.dirty { "*" } else { "" };
+    let mut title = format!("{marker}{name} — {APP_NAME}");
+    if let Some(RunningTask { name, fraction, .. }) = tasks.first() {
+        let more = match tasks.len() {
+            1 => String::new(),
+            count => format!(" +{}", count - 1),
+        };
+        title = format!("[{:.0}% {name}{more}] {title}", fraction * 100.0);
+    }
+    if window.title != title {
+        window.title = title;
+    }
+}